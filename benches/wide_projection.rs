@@ -0,0 +1,39 @@
+//! Wide projections: `RETURN n.p0, ..., n.p29` evaluates `n` once per row.
+//!
+//! Compares it with the same projection wrapped in `coalesce()`, which hides
+//! the property reads and so evaluates `n` once per item. Run with
+//! `cargo bench --bench wide_projection`; prints the best of several runs.
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use neo4j_rs::{Graph, PropertyMap};
+
+const WIDTH: usize = 30;
+const NODES: usize = 500;
+const RUNS: usize = 10;
+
+#[tokio::main]
+async fn main() {
+    let graph = Graph::open_memory().await.unwrap();
+    let props: Vec<String> = (0..WIDTH).map(|i| format!("p{i}: {i}")).collect();
+    for _ in 0..NODES {
+        graph.mutate(&format!("CREATE (:Wide {{{}}})", props.join(", ")), PropertyMap::new()).await.unwrap();
+    }
+    let items = |wrap: fn(String) -> String| -> String {
+        (0..WIDTH).map(|i| format!("{} AS p{i}", wrap(format!("n.p{i}")))).collect::<Vec<_>>().join(", ")
+    };
+
+    for (name, query) in [
+        ("shared base", format!("MATCH (n:Wide) RETURN {}", items(|e| e))),
+        ("per item", format!("MATCH (n:Wide) RETURN {}", items(|e| format!("coalesce({e})")))),
+    ] {
+        let mut best = Duration::MAX;
+        for _ in 0..RUNS {
+            let started = Instant::now();
+            graph.execute(&query, PropertyMap::new()).await.unwrap();
+            best = best.min(started.elapsed());
+        }
+        writeln!(std::io::stdout(), "{name:>12}: {best:?}").unwrap();
+    }
+}
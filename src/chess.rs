@@ -119,6 +119,7 @@ fn proc_evaluate(args: Vec<Value>) -> Result<ProcedureResult> {
             return Err(Error::TypeError {
                 expected: "STRING".into(),
                 got: other.type_name().into(),
+                span: None,
             });
         }
     };
@@ -168,6 +169,7 @@ fn proc_similar(args: Vec<Value>) -> Result<ProcedureResult> {
             return Err(Error::TypeError {
                 expected: "STRING".into(),
                 got: other.type_name().into(),
+                span: None,
             });
         }
     };
@@ -185,6 +187,7 @@ fn proc_similar(args: Vec<Value>) -> Result<ProcedureResult> {
             return Err(Error::TypeError {
                 expected: "INTEGER".into(),
                 got: other.type_name().into(),
+                span: None,
             });
         }
     };
@@ -277,6 +280,7 @@ fn proc_opening_lookup(args: Vec<Value>) -> Result<ProcedureResult> {
             return Err(Error::TypeError {
                 expected: "STRING".into(),
                 got: other.type_name().into(),
+                span: None,
             });
         }
     };
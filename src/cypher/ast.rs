@@ -5,6 +5,8 @@
 
 use std::collections::HashMap;
 
+pub use super::lexer::Span;
+
 /// A complete Cypher statement.
 #[derive(Debug, Clone)]
 pub enum Statement {
@@ -22,6 +24,10 @@ pub enum Statement {
     Remove(RemoveClause),
     /// Schema commands
     Schema(SchemaCommand),
+    /// Procedure call: CALL name(args) YIELD ...
+    Call(CallClause),
+    /// LOAD CSV ... AS row, followed by the statement consuming each row
+    LoadCsv { load: LoadCsvClause, body: Box<Statement> },
 }
 
 /// A read query (MATCH + RETURN).
@@ -35,6 +41,8 @@ pub struct Query {
     pub order_by: Option<Vec<OrderExpr>>,
     pub skip: Option<Expr>,
     pub limit: Option<Expr>,
+    /// `LIMIT n PERCENT`: `limit` is a percentage of the rows, not a count.
+    pub limit_percent: bool,
 }
 
 /// MATCH clause with pattern and optional WHERE.
@@ -42,12 +50,29 @@ pub struct Query {
 pub struct MatchClause {
     pub optional: bool,
     pub patterns: Vec<Pattern>,
+    /// `USING INDEX` hints following the patterns.
+    pub hints: Vec<IndexHint>,
+}
+
+/// `USING INDEX variable:Label(property)`: look `variable` up through the
+/// index on `Label(property)` instead of scanning its label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexHint {
+    pub variable: String,
+    pub label: String,
+    pub property: String,
+    /// The hint's text, from `USING`.
+    pub span: Option<Span>,
 }
 
 /// A pattern: (a:Person)-[:KNOWS]->(b:Person)
 #[derive(Debug, Clone)]
 pub struct Pattern {
     pub elements: Vec<PatternElement>,
+    /// `p` in `p = (a)-[:KNOWS]->(b)`, bound to the matched path.
+    pub path: Option<String>,
+    /// The pattern's text; `None` for patterns built in code.
+    pub span: Option<Span>,
 }
 
 /// Element of a pattern — either a node or a relationship.
@@ -57,15 +82,17 @@ pub enum PatternElement {
     Relationship(RelPattern),
 }
 
-/// Node pattern: (alias:Label1:Label2 {prop: value})
+/// Node pattern: (alias:Label1:Label2 {prop: value} WHERE predicate)
 #[derive(Debug, Clone)]
 pub struct NodePattern {
     pub alias: Option<String>,
     pub labels: Vec<String>,
     pub properties: HashMap<String, Expr>,
+    /// Inline `WHERE`, only allowed when matching.
+    pub predicate: Option<Expr>,
 }
 
-/// Relationship pattern: -[alias:TYPE *min..max {props}]->
+/// Relationship pattern: -[alias:TYPE *min..max {props} WHERE predicate]->
 #[derive(Debug, Clone)]
 pub struct RelPattern {
     pub alias: Option<String>,
@@ -73,10 +100,12 @@ pub struct RelPattern {
     pub direction: PatternDirection,
     pub properties: HashMap<String, Expr>,
     pub var_length: Option<VarLength>,
+    /// Inline `WHERE`, only allowed when matching a single hop.
+    pub predicate: Option<Expr>,
 }
 
 /// Pattern direction.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PatternDirection {
     /// ->
     Right,
@@ -93,6 +122,13 @@ pub struct VarLength {
     pub max: Option<usize>,
 }
 
+impl VarLength {
+    /// The lower bound, which defaults to one hop when omitted (`*..3`).
+    pub fn min_hops(&self) -> usize {
+        self.min.unwrap_or(1)
+    }
+}
+
 /// RETURN clause.
 #[derive(Debug, Clone)]
 pub struct ReturnClause {
@@ -112,6 +148,10 @@ pub struct ReturnItem {
 pub struct WithClause {
     pub items: Vec<ReturnItem>,
     pub where_clause: Option<Expr>,
+    /// How many of the query's `matches` and `unwinds` come before this
+    /// WITH, which tells the variables it passes on from those bound after.
+    pub preceding_matches: usize,
+    pub preceding_unwinds: usize,
 }
 
 /// ORDER BY expression.
@@ -130,14 +170,14 @@ pub struct OrderExpr {
 pub enum Expr {
     /// Literal value
     Literal(Literal),
-    /// Variable reference: `n`, `r`, `p`
-    Variable(String),
+    /// Variable reference: `n`, `r`, `p`. `span` is `None` for planner-made references.
+    Variable { name: String, span: Option<Span> },
     /// Property access: `n.name`
     Property { expr: Box<Expr>, key: String },
     /// Parameter: `$name`
-    Parameter(String),
+    Parameter { name: String, span: Option<Span> },
     /// Function call: `count(n)`, `id(n)`, `labels(n)`
-    FunctionCall { name: String, args: Vec<Expr>, distinct: bool },
+    FunctionCall { name: String, args: Vec<Expr>, distinct: bool, span: Option<Span> },
     /// Binary operation: `a + b`, `a = b`, `a AND b`
     BinaryOp { left: Box<Expr>, op: BinaryOp, right: Box<Expr> },
     /// Unary operation: `NOT a`, `-a`
@@ -148,8 +188,13 @@ pub enum Expr {
     MapLiteral(HashMap<String, Expr>),
     /// CASE expression
     Case { operand: Option<Box<Expr>>, whens: Vec<(Expr, Expr)>, else_expr: Option<Box<Expr>> },
-    /// EXISTS subquery
-    Exists(Box<MatchClause>),
+    /// Existence subquery: `EXISTS((n)-->(m))` or
+    /// `EXISTS { MATCH (n)-->(m) WHERE m.active }`. True when the pattern,
+    /// with the outer row's variables bound, has at least one match.
+    Exists { pattern: Box<MatchClause>, where_clause: Option<Box<Expr>> },
+    /// Counting subquery: `COUNT { (n)-->(m) WHERE m.active }`. The number of
+    /// matches of the pattern with the outer row's variables bound.
+    CountSubquery { pattern: Box<MatchClause>, where_clause: Option<Box<Expr>> },
     /// IN predicate: `x IN [1, 2, 3]`
     In { expr: Box<Expr>, list: Box<Expr> },
     /// IS NULL / IS NOT NULL
@@ -162,6 +207,19 @@ pub enum Expr {
     Star,
 }
 
+impl Expr {
+    /// `operands`, left to right, joined by the associative `op` into a tree
+    /// of logarithmic depth, so walking a long chain can't overflow the
+    /// stack. Up to three operands nest to the left. `None` without operands.
+    pub fn balanced(mut operands: Vec<Expr>, op: BinaryOp) -> Option<Expr> {
+        if operands.len() <= 1 {
+            return operands.pop();
+        }
+        let right = operands.split_off(operands.len().div_ceil(2));
+        Some(Expr::BinaryOp { left: Box::new(Self::balanced(operands, op)?), op, right: Box::new(Self::balanced(right, op)?) })
+    }
+}
+
 /// Literal values.
 #[derive(Debug, Clone)]
 pub enum Literal {
@@ -173,7 +231,7 @@ pub enum Literal {
 }
 
 /// Binary operators.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BinaryOp {
     // Arithmetic
     Add, Sub, Mul, Div, Mod, Pow,
@@ -186,14 +244,14 @@ pub enum BinaryOp {
 }
 
 /// Unary operators.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum UnaryOp {
     Not,
     Negate,
 }
 
 /// String-specific operators.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StringOp {
     StartsWith,
     EndsWith,
@@ -260,6 +318,23 @@ pub enum SetItem {
     Label { variable: String, label: String },
 }
 
+/// CALL clause (standalone procedure call).
+#[derive(Debug, Clone)]
+pub struct CallClause {
+    pub name: String,
+    pub args: Vec<Expr>,
+    pub yields: Vec<String>,
+}
+
+/// LOAD CSV [WITH HEADERS] FROM url AS alias [FIELDTERMINATOR c]
+#[derive(Debug, Clone)]
+pub struct LoadCsvClause {
+    pub url: Expr,
+    pub with_headers: bool,
+    pub alias: String,
+    pub field_terminator: Option<char>,
+}
+
 /// REMOVE clause.
 #[derive(Debug, Clone)]
 pub struct RemoveClause {
@@ -30,7 +30,8 @@ pub enum TokenKind {
     StartsWith, EndsWith, Contains,
     OnCreate, OnMatch,
     Index, Constraint, Drop, On, For,
-    Call, Yield,
+    Call, Yield, Using,
+    Load, Csv,
 
     // Literals
     Integer, Float, StringLiteral,
@@ -142,7 +143,8 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>> {
                     if c.is_ascii_digit() {
                         num.push(c);
                         chars.next();
-                    } else if c == '.' && !is_float {
+                    } else if c == '.' && !is_float && !matches!(chars.clone().nth(1), Some((_, '.'))) {
+                        // `1..3` is a range, not the float `1.`
                         is_float = true;
                         num.push(c);
                         chars.next();
@@ -382,6 +384,9 @@ fn keyword_or_ident(s: &str) -> TokenKind {
         "CONTAINS" => TokenKind::Contains,
         "CALL" => TokenKind::Call,
         "YIELD" => TokenKind::Yield,
+        "USING" => TokenKind::Using,
+        "LOAD" => TokenKind::Load,
+        "CSV" => TokenKind::Csv,
         _ => TokenKind::Identifier,
     }
 }
@@ -442,6 +447,14 @@ mod tests {
         assert_eq!(tokens[0].text, "name");
     }
 
+    #[test]
+    fn test_integer_before_range() {
+        let tokens = tokenize("*1..3").unwrap();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(kinds[..4], [TokenKind::Star, TokenKind::Integer, TokenKind::DotDot, TokenKind::Integer]);
+        assert_eq!(tokenize("1.5").unwrap()[0].kind, TokenKind::Float);
+    }
+
     #[test]
     fn test_block_comment() {
         let tokens = tokenize("MATCH /* this is a comment */ (n) RETURN n").unwrap();
@@ -520,4 +533,13 @@ mod tests {
         let tokens = tokenize("MATCH (n) WITH n RETURN n").unwrap();
         assert!(tokens.iter().any(|t| t.kind == TokenKind::With));
     }
+
+    #[test]
+    fn test_empty_input_yields_eof() {
+        for input in ["", "   ", "// just a comment"] {
+            let tokens = tokenize(input).unwrap();
+            assert_eq!(tokens.len(), 1, "{input:?}");
+            assert_eq!(tokens[0].kind, TokenKind::Eof);
+        }
+    }
 }
@@ -5,6 +5,7 @@
 //! - WHERE, RETURN, ORDER BY, SKIP, LIMIT
 //! - CREATE, DELETE / DETACH DELETE, SET
 //! - CALL ... YIELD
+//! - LOAD CSV [WITH HEADERS] FROM ... AS row
 //! - Full expression parsing with precedence
 
 use crate::{Error, Result};
@@ -12,27 +13,50 @@ use super::ast::*;
 use super::lexer::{Token, TokenKind};
 use std::collections::HashMap;
 
+/// How deeply expressions and patterns may nest (parentheses, lists, maps,
+/// function arguments, `CASE`, `EXISTS`, `NOT`, `^`) in `parse_statement`.
+/// Deeper input is a syntax error rather than a stack overflow. Each level
+/// costs a dozen or so stack frames, some 32 KiB in a debug build, so 48
+/// leaves headroom in a 2 MiB thread stack.
+pub const DEFAULT_MAX_DEPTH: usize = 48;
+
+/// How many arithmetic and property links (`a + b - c`, `n.a.b`) may sit
+/// on one path through an expression. They nest to the left, and the
+/// planner walks the tree recursively; `OR`, `XOR` and `AND` chains are
+/// built balanced instead, so their length is not limited.
+const MAX_CHAIN_LINKS: usize = 1024;
+
+/// What `peek` sees when there are no tokens at all.
+static EOF: Token = Token { kind: TokenKind::Eof, span: Span { start: 0, end: 0 }, text: String::new() };
+
 /// Parser state — wraps a token slice with cursor.
 struct Parser<'t> {
     tokens: &'t [Token],
     pos: usize,
+    /// Current expression/pattern nesting, bounded by `max_depth`.
+    depth: usize,
+    max_depth: usize,
+    /// Arithmetic and property links on the current path, bounded by
+    /// `MAX_CHAIN_LINKS`.
+    links: usize,
 }
 
 impl<'t> Parser<'t> {
-    fn new(tokens: &'t [Token]) -> Self {
-        Self { tokens, pos: 0 }
+    fn new(tokens: &'t [Token], max_depth: usize) -> Self {
+        Self { tokens, pos: 0, depth: 0, max_depth, links: 0 }
     }
 
+    /// The current token; past the end, the last one (normally `Eof`).
     fn peek(&self) -> &Token {
-        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+        self.tokens.get(self.pos.min(self.tokens.len().saturating_sub(1))).unwrap_or(&EOF)
     }
 
     fn peek_kind(&self) -> TokenKind {
         self.peek().kind
     }
 
-    fn advance(&mut self) -> &Token {
-        let tok = &self.tokens[self.pos.min(self.tokens.len() - 1)];
+    fn advance(&mut self) -> &'t Token {
+        let tok = self.tokens.get(self.pos.min(self.tokens.len().saturating_sub(1))).unwrap_or(&EOF);
         if self.pos < self.tokens.len() {
             self.pos += 1;
         }
@@ -65,6 +89,35 @@ impl<'t> Parser<'t> {
         }
     }
 
+    /// From the token at `start` to the last one consumed.
+    fn span_from(&self, start: usize) -> Option<Span> {
+        let first = self.tokens.get(start)?;
+        let last = self.tokens.get(self.pos.checked_sub(1)?)?;
+        Some(Span { start: first.span.start, end: last.span.end.max(first.span.start) })
+    }
+
+    /// Run `f` one nesting level deeper, failing past `max_depth`.
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= self.max_depth {
+            return Err(self.error("expression too deeply nested".into()));
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// One more operator in a left-nested chain (`a + b + c`, `n.a.b`),
+    /// failing past `MAX_CHAIN_LINKS`. The caller restores `links` once the
+    /// chain ends; an error aborts the whole parse, so it needn't on that path.
+    fn link(&mut self) -> Result<()> {
+        if self.links >= MAX_CHAIN_LINKS {
+            return Err(self.error("expression chain too long".into()));
+        }
+        self.links += 1;
+        Ok(())
+    }
+
     fn error(&self, msg: String) -> Error {
         Error::SyntaxError {
             position: self.peek().span.start,
@@ -87,42 +140,57 @@ impl<'t> Parser<'t> {
 
 /// Parse a complete Cypher statement from tokens.
 pub fn parse_statement(tokens: &[Token]) -> Result<Statement> {
-    let mut p = Parser::new(tokens);
+    parse_statement_with_max_depth(tokens, DEFAULT_MAX_DEPTH)
+}
+
+/// Like `parse_statement`, with a nesting limit other than `DEFAULT_MAX_DEPTH`.
+pub fn parse_statement_with_max_depth(tokens: &[Token], max_depth: usize) -> Result<Statement> {
+    let mut p = Parser::new(tokens, max_depth);
+    if p.at(TokenKind::Eof) {
+        return Err(p.error("empty query".into()));
+    }
+
+    let stmt = parse_clauses(&mut p)?;
 
+    // Allow optional semicolon + EOF
+    p.eat(TokenKind::Semicolon);
+    if !p.at(TokenKind::Eof) {
+        return Err(p.error(format!("Unexpected token after statement: {:?}", p.peek_kind())));
+    }
+
+    Ok(stmt)
+}
+
+/// Dispatch on the leading clause keyword.
+fn parse_clauses(p: &mut Parser) -> Result<Statement> {
     let stmt = match p.peek_kind() {
-        TokenKind::Match | TokenKind::OptionalMatch => parse_query_stmt(&mut p)?,
+        TokenKind::Match | TokenKind::OptionalMatch => parse_query_stmt(p)?,
         TokenKind::Create => {
             // Peek ahead: CREATE INDEX / CREATE CONSTRAINT → schema
             let saved = p.pos;
             p.advance(); // eat CREATE
             if p.at(TokenKind::Index) || p.at(TokenKind::Constraint) {
                 p.pos = saved;
-                parse_schema_stmt(&mut p)?
+                parse_schema_stmt(p)?
             } else {
                 p.pos = saved;
-                parse_create_stmt(&mut p)?
+                parse_create_stmt(p)?
             }
         }
-        TokenKind::Merge => parse_merge_stmt(&mut p)?,
-        TokenKind::Delete | TokenKind::DetachDelete => parse_delete_stmt(&mut p)?,
-        TokenKind::Call => parse_call_stmt(&mut p)?,
-        TokenKind::Drop => parse_schema_stmt(&mut p)?,
+        TokenKind::Merge => parse_merge_stmt(p)?,
+        TokenKind::Delete | TokenKind::DetachDelete => parse_delete_stmt(p)?,
+        TokenKind::Call => parse_call_stmt(p)?,
+        TokenKind::Load => parse_load_csv_stmt(p)?,
+        TokenKind::Drop => parse_schema_stmt(p)?,
         kind => {
             // Try to parse as a query with UNWIND or WITH as starting clause
             if kind == TokenKind::Unwind || kind == TokenKind::With {
-                parse_query_stmt(&mut p)?
+                parse_query_stmt(p)?
             } else {
                 return Err(p.error(format!("Unexpected token {:?} at start of statement", kind)));
             }
         }
     };
-
-    // Allow optional semicolon + EOF
-    p.eat(TokenKind::Semicolon);
-    if !p.at(TokenKind::Eof) {
-        return Err(p.error(format!("Unexpected token after statement: {:?}", p.peek_kind())));
-    }
-
     Ok(stmt)
 }
 
@@ -162,7 +230,8 @@ fn parse_query_stmt(p: &mut Parser) -> Result<Statement> {
             };
 
             let patterns = parse_pattern_list(p)?;
-            matches.push(MatchClause { optional, patterns });
+            let hints = parse_index_hints(p)?;
+            matches.push(MatchClause { optional, patterns, hints });
 
             // WHERE after MATCH
             if p.at(TokenKind::Where) {
@@ -183,7 +252,9 @@ fn parse_query_stmt(p: &mut Parser) -> Result<Statement> {
         // Check for WITH clause
         if p.at(TokenKind::With) {
             p.advance();
-            let with = parse_with_clause(p)?;
+            let mut with = parse_with_clause(p)?;
+            with.preceding_matches = matches.len();
+            with.preceding_unwinds = unwinds.len();
             with_clauses.push(with);
             // After WITH, continue to parse more MATCH/WITH/UNWIND/RETURN clauses
             continue;
@@ -282,6 +353,11 @@ fn parse_query_stmt(p: &mut Parser) -> Result<Statement> {
     } else {
         None
     };
+    // PERCENT stays an identifier, so it remains usable as a name elsewhere
+    let limit_percent = limit.is_some() && p.at(TokenKind::Identifier) && p.peek().text.eq_ignore_ascii_case("PERCENT");
+    if limit_percent {
+        p.advance();
+    }
 
     Ok(Statement::Query(Query {
         matches,
@@ -292,6 +368,7 @@ fn parse_query_stmt(p: &mut Parser) -> Result<Statement> {
         order_by,
         skip,
         limit,
+        limit_percent,
     }))
 }
 
@@ -637,6 +714,23 @@ fn parse_drop_constraint(p: &mut Parser) -> Result<Statement> {
     }
 }
 
+/// One bound of `*min..max`, if present. Bounds are non-negative integers.
+fn parse_hop_bound(p: &mut Parser) -> Result<Option<usize>> {
+    match p.peek_kind() {
+        TokenKind::Integer => {
+            let tok = p.advance();
+            tok.text.parse::<usize>().map(Some).map_err(|_| Error::SyntaxError {
+                position: tok.span.start,
+                message: format!("Variable-length bound {} is out of range", tok.text),
+            })
+        }
+        TokenKind::Float | TokenKind::Dash => {
+            Err(p.error("Variable-length bound must be a non-negative integer".into()))
+        }
+        _ => Ok(None),
+    }
+}
+
 /// Skip a brace-delimited block `{ ... }`.
 fn skip_braced(p: &mut Parser) -> Result<()> {
     p.expect(TokenKind::LBrace)?;
@@ -672,7 +766,12 @@ fn parse_call_stmt(p: &mut Parser) -> Result<Statement> {
     // Parse procedure name: name or name.name.name
     let mut name = p.expect(TokenKind::Identifier)?.text.clone();
     while p.eat(TokenKind::Dot) {
-        let part = p.expect(TokenKind::Identifier)?.text.clone();
+        // Namespace parts may collide with keywords (db.index.vector...)
+        let word = p.peek().text.starts_with(|c: char| c.is_alphabetic() || c == '_');
+        if !word {
+            return Err(p.error(format!("Expected procedure name part, got '{}'", p.peek().text)));
+        }
+        let part = p.advance().text.clone();
         name = format!("{name}.{part}");
     }
 
@@ -696,29 +795,76 @@ fn parse_call_stmt(p: &mut Parser) -> Result<Statement> {
         }
     }
 
-    // Build a Query wrapping the CALL
-    let return_items: Vec<ReturnItem> = yields.iter().map(|y| ReturnItem {
-        expr: Expr::Variable(y.clone()),
-        alias: None,
-    }).collect();
+    Ok(Statement::Call(CallClause { name, args, yields }))
+}
 
-    let return_clause = if return_items.is_empty() {
-        ReturnClause { distinct: false, items: vec![ReturnItem { expr: Expr::Star, alias: None }] }
+/// Parse LOAD CSV [WITH HEADERS] FROM url AS alias [FIELDTERMINATOR c] <statement>
+fn parse_load_csv_stmt(p: &mut Parser) -> Result<Statement> {
+    p.expect(TokenKind::Load)?;
+    p.expect(TokenKind::Csv)?;
+
+    // HEADERS, FROM and FIELDTERMINATOR are contextual, not reserved words
+    let with_headers = p.at(TokenKind::With);
+    if with_headers {
+        p.advance();
+        expect_word(p, "HEADERS")?;
+    }
+    expect_word(p, "FROM")?;
+    let url = parse_expr(p)?;
+    p.expect(TokenKind::As)?;
+    let alias = p.expect(TokenKind::Identifier)?.text.clone();
+
+    let field_terminator = if p.peek().text.eq_ignore_ascii_case("FIELDTERMINATOR") {
+        p.advance();
+        let text = p.expect(TokenKind::StringLiteral)?.text.clone();
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => return Err(p.error(format!("FIELDTERMINATOR must be a single character, got '{text}'"))),
+        }
     } else {
-        ReturnClause { distinct: false, items: return_items }
+        None
     };
 
-    // For now, wrap as Query with no MATCH
-    Ok(Statement::Query(Query {
-        matches: Vec::new(),
-        where_clause: None,
-        unwinds: Vec::new(),
-        with_clauses: Vec::new(),
-        return_clause,
-        order_by: None,
-        skip: None,
-        limit: None,
-    }))
+    // The consuming statement may also start with a bare RETURN
+    let body = if p.at(TokenKind::Return) {
+        parse_query_stmt(p)?
+    } else {
+        parse_clauses(p)?
+    };
+
+    Ok(Statement::LoadCsv {
+        load: LoadCsvClause { url, with_headers, alias, field_terminator },
+        body: Box::new(body),
+    })
+}
+
+/// Zero or more `USING INDEX var:Label(prop)` hints after a MATCH pattern.
+fn parse_index_hints(p: &mut Parser) -> Result<Vec<IndexHint>> {
+    let mut hints = Vec::new();
+    while p.at(TokenKind::Using) {
+        let start = p.pos;
+        p.advance();
+        p.expect(TokenKind::Index)?;
+        let variable = p.expect(TokenKind::Identifier)?.text.clone();
+        p.expect(TokenKind::Colon)?;
+        let label = p.expect(TokenKind::Identifier)?.text.clone();
+        p.expect(TokenKind::LParen)?;
+        let property = p.expect(TokenKind::Identifier)?.text.clone();
+        p.expect(TokenKind::RParen)?;
+        hints.push(IndexHint { variable, label, property, span: p.span_from(start) });
+    }
+    Ok(hints)
+}
+
+/// Consume an identifier spelled `word` (case-insensitive).
+fn expect_word(p: &mut Parser, word: &str) -> Result<()> {
+    if p.at(TokenKind::Identifier) && p.peek().text.eq_ignore_ascii_case(word) {
+        p.advance();
+        Ok(())
+    } else {
+        Err(p.error(format!("Expected {word}, got '{}'", p.peek().text)))
+    }
 }
 
 // ============================================================================
@@ -727,15 +873,16 @@ fn parse_call_stmt(p: &mut Parser) -> Result<Statement> {
 
 fn parse_with_clause(p: &mut Parser) -> Result<WithClause> {
     // Parse return items (same syntax as RETURN items)
+    // `WITH *` may be followed by more items: `WITH *, n.age AS age`
     let mut items = Vec::new();
     if p.at(TokenKind::Star) {
         p.advance();
         items.push(ReturnItem { expr: Expr::Star, alias: None });
     } else {
         items.push(parse_return_item(p)?);
-        while p.eat(TokenKind::Comma) {
-            items.push(parse_return_item(p)?);
-        }
+    }
+    while p.eat(TokenKind::Comma) {
+        items.push(parse_return_item(p)?);
     }
 
     // Optional WHERE after WITH items
@@ -746,7 +893,7 @@ fn parse_with_clause(p: &mut Parser) -> Result<WithClause> {
         None
     };
 
-    Ok(WithClause { items, where_clause })
+    Ok(WithClause { items, where_clause, preceding_matches: 0, preceding_unwinds: 0 })
 }
 
 // ============================================================================
@@ -771,7 +918,7 @@ fn _parse_remove_stmt(p: &mut Parser) -> Result<Statement> {
         };
 
         let patterns = parse_pattern_list(p)?;
-        matches.push(MatchClause { optional, patterns });
+        matches.push(MatchClause { optional, patterns, hints: Vec::new() });
 
         if p.at(TokenKind::Where) {
             p.advance();
@@ -856,6 +1003,20 @@ fn parse_pattern_list(p: &mut Parser) -> Result<Vec<Pattern>> {
 }
 
 fn parse_pattern(p: &mut Parser) -> Result<Pattern> {
+    let start = p.pos;
+    // `p = (a)-->(b)` names the path
+    let path = if p.at(TokenKind::Identifier) && p.tokens.get(p.pos + 1).is_some_and(|t| t.kind == TokenKind::Eq) {
+        let name = p.advance().text.clone();
+        p.advance();
+        Some(name)
+    } else {
+        None
+    };
+    let elements = p.nested(parse_pattern_elements)?;
+    Ok(Pattern { elements, path, span: p.span_from(start) })
+}
+
+fn parse_pattern_elements(p: &mut Parser) -> Result<Vec<PatternElement>> {
     let mut elements = Vec::new();
 
     // A pattern starts with a node
@@ -868,7 +1029,7 @@ fn parse_pattern(p: &mut Parser) -> Result<Pattern> {
         elements.push(PatternElement::Node(parse_node_pattern(p)?));
     }
 
-    Ok(Pattern { elements })
+    Ok(elements)
 }
 
 fn parse_node_pattern(p: &mut Parser) -> Result<NodePattern> {
@@ -895,9 +1056,12 @@ fn parse_node_pattern(p: &mut Parser) -> Result<NodePattern> {
         properties = parse_map_literal_inner(p)?;
     }
 
+    // Inline predicate: WHERE expr
+    let predicate = if p.eat(TokenKind::Where) { Some(parse_expr(p)?) } else { None };
+
     p.expect(TokenKind::RParen)?;
 
-    Ok(NodePattern { alias, labels, properties })
+    Ok(NodePattern { alias, labels, properties, predicate })
 }
 
 fn parse_rel_pattern(p: &mut Parser) -> Result<(RelPattern, PatternDirection)> {
@@ -913,6 +1077,7 @@ fn parse_rel_pattern(p: &mut Parser) -> Result<(RelPattern, PatternDirection)> {
     let mut rel_types = Vec::new();
     let mut properties = HashMap::new();
     let mut var_length = None;
+    let mut predicate = None;
 
     // Optional [details]
     if p.at(TokenKind::LBracket) {
@@ -934,17 +1099,18 @@ fn parse_rel_pattern(p: &mut Parser) -> Result<(RelPattern, PatternDirection)> {
 
         // Variable length: *min..max
         if p.eat(TokenKind::Star) {
-            let min = if p.at(TokenKind::Integer) {
-                Some(p.advance().text.parse::<usize>().unwrap_or(1))
-            } else {
-                None
-            };
+            let start = p.peek().span.start;
+            let min = parse_hop_bound(p)?;
             if p.eat(TokenKind::DotDot) {
-                let max = if p.at(TokenKind::Integer) {
-                    Some(p.advance().text.parse::<usize>().unwrap_or(100))
-                } else {
-                    None
-                };
+                let max = parse_hop_bound(p)?;
+                if let (Some(lo), Some(hi)) = (min, max)
+                    && lo > hi
+                {
+                    return Err(Error::SyntaxError {
+                        position: start,
+                        message: format!("Variable-length lower bound {lo} exceeds upper bound {hi}"),
+                    });
+                }
                 var_length = Some(VarLength { min, max });
             } else if let Some(n) = min {
                 var_length = Some(VarLength { min: Some(n), max: Some(n) });
@@ -958,6 +1124,18 @@ fn parse_rel_pattern(p: &mut Parser) -> Result<(RelPattern, PatternDirection)> {
             properties = parse_map_literal_inner(p)?;
         }
 
+        // Inline predicate: WHERE expr, over a single relationship
+        if p.at(TokenKind::Where) {
+            let start = p.advance().span.start;
+            if var_length.is_some() {
+                return Err(Error::SyntaxError {
+                    position: start,
+                    message: "WHERE is not allowed inside a variable-length relationship pattern".into(),
+                });
+            }
+            predicate = Some(parse_expr(p)?);
+        }
+
         p.expect(TokenKind::RBracket)?;
     }
 
@@ -982,6 +1160,7 @@ fn parse_rel_pattern(p: &mut Parser) -> Result<(RelPattern, PatternDirection)> {
         direction,
         properties,
         var_length,
+        predicate,
     }, direction))
 }
 
@@ -1086,42 +1265,39 @@ fn parse_variable_list(p: &mut Parser) -> Result<Vec<String>> {
 // ============================================================================
 
 fn parse_expr(p: &mut Parser) -> Result<Expr> {
-    parse_or_expr(p)
+    p.nested(parse_or_expr)
 }
 
 fn parse_or_expr(p: &mut Parser) -> Result<Expr> {
-    let mut left = parse_xor_expr(p)?;
-    while p.at(TokenKind::Or) {
-        p.advance();
-        let right = parse_xor_expr(p)?;
-        left = Expr::BinaryOp { left: Box::new(left), op: BinaryOp::Or, right: Box::new(right) };
-    }
-    Ok(left)
+    parse_logical_chain(p, TokenKind::Or, BinaryOp::Or, parse_xor_expr)
 }
 
 fn parse_xor_expr(p: &mut Parser) -> Result<Expr> {
-    let mut left = parse_and_expr(p)?;
-    while p.at(TokenKind::Xor) {
-        p.advance();
-        let right = parse_and_expr(p)?;
-        left = Expr::BinaryOp { left: Box::new(left), op: BinaryOp::Xor, right: Box::new(right) };
-    }
-    Ok(left)
+    parse_logical_chain(p, TokenKind::Xor, BinaryOp::Xor, parse_and_expr)
 }
 
 fn parse_and_expr(p: &mut Parser) -> Result<Expr> {
-    let mut left = parse_not_expr(p)?;
-    while p.at(TokenKind::And) {
-        p.advance();
-        let right = parse_not_expr(p)?;
-        left = Expr::BinaryOp { left: Box::new(left), op: BinaryOp::And, right: Box::new(right) };
-    }
-    Ok(left)
+    parse_logical_chain(p, TokenKind::And, BinaryOp::And, parse_not_expr)
+}
+
+/// Operands separated by `token`, joined by `op` into a balanced tree
+/// rather than one as deep as the chain is long.
+fn parse_logical_chain(
+    p: &mut Parser,
+    token: TokenKind,
+    op: BinaryOp,
+    operand: fn(&mut Parser) -> Result<Expr>,
+) -> Result<Expr> {
+    let mut operands = vec![operand(p)?];
+    while p.eat(token) {
+        operands.push(operand(p)?);
+    }
+    Ok(Expr::balanced(operands, op).expect("at least one operand"))
 }
 
 fn parse_not_expr(p: &mut Parser) -> Result<Expr> {
     if p.eat(TokenKind::Not) {
-        let expr = parse_not_expr(p)?;
+        let expr = p.nested(parse_not_expr)?;
         Ok(Expr::UnaryOp { op: UnaryOp::Not, expr: Box::new(expr) })
     } else {
         parse_comparison(p)
@@ -1193,21 +1369,26 @@ fn parse_string_op(p: &mut Parser) -> Result<Expr> {
 }
 
 fn parse_addition(p: &mut Parser) -> Result<Expr> {
+    let links = p.links;
     let mut left = parse_multiplication(p)?;
     loop {
         let op = match p.peek_kind() {
             TokenKind::Plus => BinaryOp::Add,
-            TokenKind::Minus => BinaryOp::Sub,
+            // The lexer emits `-` as Dash (it doubles as a pattern edge)
+            TokenKind::Minus | TokenKind::Dash => BinaryOp::Sub,
             _ => break,
         };
+        p.link()?;
         p.advance();
         let right = parse_multiplication(p)?;
         left = Expr::BinaryOp { left: Box::new(left), op, right: Box::new(right) };
     }
+    p.links = links;
     Ok(left)
 }
 
 fn parse_multiplication(p: &mut Parser) -> Result<Expr> {
+    let links = p.links;
     let mut left = parse_power(p)?;
     loop {
         let op = match p.peek_kind() {
@@ -1216,17 +1397,19 @@ fn parse_multiplication(p: &mut Parser) -> Result<Expr> {
             TokenKind::Percent => BinaryOp::Mod,
             _ => break,
         };
+        p.link()?;
         p.advance();
         let right = parse_power(p)?;
         left = Expr::BinaryOp { left: Box::new(left), op, right: Box::new(right) };
     }
+    p.links = links;
     Ok(left)
 }
 
 fn parse_power(p: &mut Parser) -> Result<Expr> {
     let left = parse_unary(p)?;
     if p.eat(TokenKind::Caret) {
-        let right = parse_power(p)?; // right-associative
+        let right = p.nested(parse_power)?; // right-associative
         Ok(Expr::BinaryOp { left: Box::new(left), op: BinaryOp::Pow, right: Box::new(right) })
     } else {
         Ok(left)
@@ -1234,7 +1417,7 @@ fn parse_power(p: &mut Parser) -> Result<Expr> {
 }
 
 fn parse_unary(p: &mut Parser) -> Result<Expr> {
-    if p.eat(TokenKind::Minus) {
+    if p.eat(TokenKind::Minus) || p.eat(TokenKind::Dash) {
         let expr = parse_property_access(p)?;
         Ok(Expr::UnaryOp { op: UnaryOp::Negate, expr: Box::new(expr) })
     } else {
@@ -1243,19 +1426,22 @@ fn parse_unary(p: &mut Parser) -> Result<Expr> {
 }
 
 fn parse_property_access(p: &mut Parser) -> Result<Expr> {
+    let links = p.links;
     let mut expr = parse_primary(p)?;
 
     // Property access chain: n.name, n.address.city
     while p.at(TokenKind::Dot) {
+        p.link()?;
         p.advance();
         let key = p.expect(TokenKind::Identifier)?.text.clone();
         expr = Expr::Property { expr: Box::new(expr), key };
     }
+    p.links = links;
 
     // Label check: n:Person
     if p.at(TokenKind::Colon) {
         // Only if expr is a variable
-        if let Expr::Variable(_) = &expr {
+        if let Expr::Variable { .. } = &expr {
             p.advance();
             let label = p.expect(TokenKind::Identifier)?.text.clone();
             expr = Expr::HasLabel { expr: Box::new(expr), label };
@@ -1265,6 +1451,16 @@ fn parse_property_access(p: &mut Parser) -> Result<Expr> {
     Ok(expr)
 }
 
+/// The `[MATCH] pattern [WHERE expr] }` of an `EXISTS { ... }` or
+/// `COUNT { ... }` subquery, after its opening brace.
+fn parse_subquery_body(p: &mut Parser) -> Result<(Box<MatchClause>, Option<Box<Expr>>)> {
+    p.eat(TokenKind::Match);
+    let patterns = parse_pattern_list(p)?;
+    let where_clause = if p.eat(TokenKind::Where) { Some(Box::new(parse_expr(p)?)) } else { None };
+    p.expect(TokenKind::RBrace)?;
+    Ok((Box::new(MatchClause { optional: false, patterns, hints: Vec::new() }), where_clause))
+}
+
 fn parse_primary(p: &mut Parser) -> Result<Expr> {
     match p.peek_kind() {
         // Literals
@@ -1302,7 +1498,7 @@ fn parse_primary(p: &mut Parser) -> Result<Expr> {
         // Parameter
         TokenKind::Parameter => {
             let tok = p.advance();
-            Ok(Expr::Parameter(tok.text.clone()))
+            Ok(Expr::Parameter { name: tok.text.clone(), span: Some(tok.span) })
         }
 
         // Star (for RETURN *)
@@ -1363,19 +1559,41 @@ fn parse_primary(p: &mut Parser) -> Result<Expr> {
             Ok(Expr::Case { operand, whens, else_expr })
         }
 
-        // EXISTS
+        // EXISTS(pattern) or EXISTS { [MATCH] pattern [WHERE expr] }
         TokenKind::Exists => {
             p.advance();
+            if p.eat(TokenKind::LBrace) {
+                let (pattern, where_clause) = parse_subquery_body(p)?;
+                return Ok(Expr::Exists { pattern, where_clause });
+            }
             p.expect(TokenKind::LParen)?;
-            // Simplified: parse a pattern as a match clause
             let patterns = parse_pattern_list(p)?;
             p.expect(TokenKind::RParen)?;
-            Ok(Expr::Exists(Box::new(MatchClause { optional: false, patterns })))
+            Ok(Expr::Exists { pattern: Box::new(MatchClause { optional: false, patterns, hints: Vec::new() }), where_clause: None })
         }
 
         // Identifier — could be variable or function call
+        // COUNT { [MATCH] pattern [WHERE expr] }
+        TokenKind::Identifier
+            if p.peek().text.eq_ignore_ascii_case("COUNT")
+                && p.tokens.get(p.pos + 1).is_some_and(|t| t.kind == TokenKind::LBrace) =>
+        {
+            p.advance();
+            p.advance();
+            let (pattern, where_clause) = parse_subquery_body(p)?;
+            Ok(Expr::CountSubquery { pattern, where_clause })
+        }
+
         TokenKind::Identifier => {
-            let tok = p.advance().clone();
+            let mut tok = p.advance().clone();
+            // Namespaced function: apoc.map.fromLists(...). Only taken when the
+            // dotted chain ends in `(`, otherwise it's property access.
+            if let Some(end) = namespaced_call_end(p) {
+                while p.pos < end {
+                    let part = p.advance().text.clone();
+                    tok.text.push_str(&part);
+                }
+            }
             if p.at(TokenKind::LParen) {
                 // Function call: name(args)
                 p.advance(); // consume (
@@ -1397,9 +1615,10 @@ fn parse_primary(p: &mut Parser) -> Result<Expr> {
                     }
                 }
                 p.expect(TokenKind::RParen)?;
-                Ok(Expr::FunctionCall { name: tok.text, args, distinct })
+                let span = Span { start: tok.span.start, end: p.tokens[p.pos - 1].span.end };
+                Ok(Expr::FunctionCall { name: tok.text, args, distinct, span: Some(span) })
             } else {
-                Ok(Expr::Variable(tok.text))
+                Ok(Expr::Variable { name: tok.text, span: Some(tok.span) })
             }
         }
 
@@ -1407,6 +1626,18 @@ fn parse_primary(p: &mut Parser) -> Result<Expr> {
     }
 }
 
+/// If the tokens at the cursor are `(. word)+ (`, the position of that `(`.
+fn namespaced_call_end(p: &Parser) -> Option<usize> {
+    let mut pos = p.pos;
+    let is_word = |t: &Token| {
+        t.kind != TokenKind::StringLiteral && t.text.starts_with(|c: char| c.is_alphabetic() || c == '_')
+    };
+    while p.tokens.get(pos)?.kind == TokenKind::Dot && is_word(p.tokens.get(pos + 1)?) {
+        pos += 2;
+    }
+    (pos > p.pos && p.tokens.get(pos)?.kind == TokenKind::LParen).then_some(pos)
+}
+
 fn parse_map_literal_inner(p: &mut Parser) -> Result<HashMap<String, Expr>> {
     p.expect(TokenKind::LBrace)?;
     let mut map = HashMap::new();
@@ -1504,6 +1735,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pattern_predicates() {
+        let stmt = parse("MATCH (a:Person {name: 'Ada'} WHERE a.age > 18)-[r:KNOWS WHERE r.since > 2020]->(b) RETURN b").unwrap();
+        let Statement::Query(q) = stmt else { panic!("Expected Query") };
+        let elements = &q.matches[0].patterns[0].elements;
+        match (&elements[0], &elements[1], &elements[2]) {
+            (PatternElement::Node(a), PatternElement::Relationship(r), PatternElement::Node(b)) => {
+                assert_eq!(a.properties.len(), 1);
+                assert!(matches!(a.predicate, Some(Expr::BinaryOp { op: BinaryOp::Gt, .. })));
+                assert!(matches!(r.predicate, Some(Expr::BinaryOp { op: BinaryOp::Gt, .. })));
+                assert!(b.predicate.is_none());
+            }
+            _ => panic!("Expected node, relationship, node"),
+        }
+        assert!(q.where_clause.is_none());
+
+        // Over a variable-length relationship, `r` would be a list
+        assert!(parse("MATCH (a)-[r:KNOWS*1..3 WHERE r.since > 2020]->(b) RETURN b").is_err());
+    }
+
     #[test]
     fn test_match_set() {
         let stmt = parse("MATCH (n:Person) WHERE n.name = 'Ada' SET n.age = 4").unwrap();
@@ -1535,9 +1786,23 @@ mod tests {
         match stmt {
             Statement::Query(q) => {
                 assert!(q.limit.is_some());
+                assert!(!q.limit_percent);
+            }
+            _ => panic!("Expected Query"),
+        }
+    }
+
+    #[test]
+    fn test_limit_percent() {
+        match parse("MATCH (n:Person) RETURN n ORDER BY n.score DESC LIMIT 25 percent").unwrap() {
+            Statement::Query(q) => {
+                assert!(matches!(q.limit, Some(Expr::Literal(Literal::Int(25)))));
+                assert!(q.limit_percent);
             }
             _ => panic!("Expected Query"),
         }
+        // Still an ordinary name outside LIMIT
+        assert!(parse("MATCH (percent:Person) RETURN percent.score AS percent").is_ok());
     }
 
     #[test]
@@ -1567,7 +1832,7 @@ mod tests {
         match stmt {
             Statement::Query(q) => {
                 if let Some(Expr::BinaryOp { right, .. }) = &q.where_clause {
-                    assert!(matches!(right.as_ref(), Expr::Parameter(_)));
+                    assert!(matches!(right.as_ref(), Expr::Parameter { .. }));
                 }
             }
             _ => panic!("Expected Query"),
@@ -1705,6 +1970,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_with_star_and_items() {
+        let stmt = super::super::parse("MATCH (a)-[r]->(b) WITH *, r.weight AS w RETURN a").unwrap();
+        match stmt {
+            Statement::Query(q) => {
+                let items = &q.with_clauses[0].items;
+                assert_eq!(items.len(), 2);
+                assert!(matches!(items[0].expr, Expr::Star));
+                assert_eq!(items[1].alias.as_deref(), Some("w"));
+            }
+            _ => panic!("Expected Query"),
+        }
+    }
+
     #[test]
     fn test_remove_property() {
         let input = "MATCH (n:Person) WHERE n.name = 'Alice' REMOVE n.age";
@@ -1765,4 +2044,201 @@ mod tests {
             _ => panic!("Expected Remove"),
         }
     }
+
+    #[test]
+    fn test_path_variable() {
+        match parse("MATCH p = (a)-[:KNOWS]->(b), (c) RETURN p").unwrap() {
+            Statement::Query(q) => {
+                let patterns = &q.matches[0].patterns;
+                assert_eq!(patterns[0].path.as_deref(), Some("p"));
+                assert_eq!(patterns[0].elements.len(), 3);
+                assert_eq!(patterns[1].path, None);
+            }
+            _ => panic!("Expected Query"),
+        }
+    }
+
+    #[test]
+    fn test_index_hint() {
+        match parse("MATCH (n:Person) USING INDEX n:Person(name) WHERE n.name = $x RETURN n").unwrap() {
+            Statement::Query(q) => {
+                let span = Some(Span { start: 17, end: 43 });
+                let hint = IndexHint { variable: "n".into(), label: "Person".into(), property: "name".into(), span };
+                assert_eq!(q.matches[0].hints, [hint]);
+                assert_eq!(q.matches[0].patterns[0].span, Some(Span { start: 6, end: 16 }));
+                assert!(q.where_clause.is_some());
+            }
+            _ => panic!("Expected Query"),
+        }
+        assert!(parse("MATCH (n:Person) USING INDEX n.name RETURN n").is_err());
+    }
+
+    #[test]
+    fn test_load_csv() {
+        let input = "LOAD CSV WITH HEADERS FROM 'file:///people.csv' AS row FIELDTERMINATOR ';' CREATE (:Person {name: row.name})";
+        match super::super::parse(input).unwrap() {
+            Statement::LoadCsv { load, body } => {
+                assert!(load.with_headers);
+                assert_eq!(load.alias, "row");
+                assert_eq!(load.field_terminator, Some(';'));
+                assert!(matches!(*body, Statement::Create(_)));
+            }
+            _ => panic!("Expected LoadCsv"),
+        }
+    }
+
+    #[test]
+    fn test_namespaced_function_call() {
+        let input = "MATCH (n) RETURN apoc.map.fromLists(['a'], [n.x]) AS m, n.name";
+        match super::super::parse(input).unwrap() {
+            Statement::Query(q) => {
+                assert!(matches!(&q.return_clause.items[0].expr,
+                    Expr::FunctionCall { name, args, .. } if name == "apoc.map.fromLists" && args.len() == 2));
+                assert!(matches!(&q.return_clause.items[1].expr, Expr::Property { key, .. } if key == "name"));
+            }
+            _ => panic!("Expected Query"),
+        }
+    }
+
+    #[test]
+    fn test_var_length_bounds() {
+        fn var_length(hops: &str) -> Result<Option<VarLength>> {
+            let Statement::Query(q) = super::super::parse(&format!("MATCH (a)-[:R{hops}]->(b) RETURN b"))? else {
+                panic!("Expected Query");
+            };
+            match &q.matches[0].patterns[0].elements[1] {
+                PatternElement::Relationship(r) => Ok(r.var_length.clone()),
+                _ => panic!("Expected relationship"),
+            }
+        }
+
+        let v = var_length("*..3").unwrap().unwrap();
+        assert_eq!((v.min, v.max, v.min_hops()), (None, Some(3), 1));
+        let v = var_length("*2..").unwrap().unwrap();
+        assert_eq!((v.min, v.max), (Some(2), None));
+        let v = var_length("*2..2").unwrap().unwrap();
+        assert_eq!((v.min, v.max), (Some(2), Some(2)));
+        let v = var_length("*3").unwrap().unwrap();
+        assert_eq!((v.min, v.max), (Some(3), Some(3)));
+        let v = var_length("*").unwrap().unwrap();
+        assert_eq!((v.min, v.max, v.min_hops()), (None, None, 1));
+        assert!(var_length("").unwrap().is_none());
+
+        for bad in ["*2..1", "*1.5", "*-1..2", "*1..-2", "*99999999999999999999999"] {
+            let err = var_length(bad).unwrap_err();
+            assert!(matches!(err, Error::SyntaxError { .. }), "{bad}: {err:?}");
+        }
+        let err = var_length("*2..1").unwrap_err();
+        assert!(err.to_string().contains("lower bound 2 exceeds upper bound 1"), "got: {err}");
+    }
+
+    #[test]
+    fn test_exists_brace_form() {
+        let input = "MATCH (n) WHERE EXISTS { MATCH (n)-[:R]->(m), (m)-->(x) WHERE m.active } RETURN n";
+        let Statement::Query(q) = super::super::parse(input).unwrap() else {
+            panic!("Expected Query");
+        };
+        match q.where_clause.unwrap() {
+            Expr::Exists { pattern, where_clause } => {
+                assert_eq!(pattern.patterns.len(), 2);
+                assert!(matches!(where_clause.as_deref(), Some(Expr::Property { key, .. }) if key == "active"));
+            }
+            other => panic!("Expected EXISTS, got {other:?}"),
+        }
+
+        assert!(super::super::parse("MATCH (n) WHERE EXISTS { MATCH (n)-->(m) RETURN n").is_err());
+    }
+
+    #[test]
+    fn test_count_subquery() {
+        let input = "MATCH (n) RETURN COUNT { (n)-->(m) WHERE m.active } AS c, count(n) AS d";
+        let Statement::Query(q) = super::super::parse(input).unwrap() else {
+            panic!("Expected Query");
+        };
+        match &q.return_clause.items[0].expr {
+            Expr::CountSubquery { pattern, where_clause } => {
+                assert_eq!(pattern.patterns.len(), 1);
+                assert!(where_clause.is_some());
+            }
+            other => panic!("Expected COUNT subquery, got {other:?}"),
+        }
+        assert!(matches!(&q.return_clause.items[1].expr, Expr::FunctionCall { name, .. } if name == "count"));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |open: &str, close: &str, depth: usize| {
+            format!("MATCH (n) WHERE {}1{} = 1 RETURN n", open.repeat(depth), close.repeat(depth))
+        };
+        // Within the limit
+        parse(&nested("(", ")", 40)).unwrap();
+
+        // Pathological input is an error, not a stack overflow
+        for query in [
+            nested("(", ")", 100_000),
+            nested("[", "]", 100_000),
+            nested("f(", ")", 100_000),
+            format!("MATCH (n) WHERE {}true RETURN n", "NOT ".repeat(100_000)),
+            format!("MATCH (n) RETURN 2{} AS x", "^2".repeat(100_000)),
+        ] {
+            let err = parse(&query).unwrap_err();
+            assert!(
+                matches!(&err, Error::SyntaxError { message, .. } if message == "expression too deeply nested"),
+                "{err:?}"
+            );
+        }
+
+        // Long flat chains don't nest: boolean ones build a balanced tree
+        let chain = |op: &str, terms: usize| vec!["n.x = 1"; terms].join(op);
+        for op in [" OR ", " AND ", " XOR "] {
+            parse(&format!("MATCH (n) WHERE {} RETURN n", chain(op, 100_000))).unwrap();
+        }
+        let Statement::Query(q) = parse(&format!("MATCH (n) WHERE {} RETURN n", chain(" OR ", 1_000))).unwrap() else {
+            panic!("expected a query");
+        };
+        fn height(e: &Expr) -> usize {
+            match e {
+                Expr::BinaryOp { left, right, .. } => 1 + height(left).max(height(right)),
+                _ => 0,
+            }
+        }
+        assert_eq!(height(q.where_clause.as_ref().unwrap()), 11);
+
+        // Arithmetic and property chains nest to the left, up to a length
+        parse(&format!("MATCH (n) RETURN {} AS x", vec!["1"; 1_000].join(" + "))).unwrap();
+        parse(&format!("MATCH (n) RETURN n{} AS x", ".a".repeat(1_000))).unwrap();
+        for query in [
+            format!("MATCH (n) RETURN {} AS x", vec!["1"; 100_000].join(" + ")),
+            format!("MATCH (n) RETURN {} AS x", vec!["1"; 100_000].join(" * ")),
+            format!("MATCH (n) RETURN n{} AS x", ".a".repeat(100_000)),
+        ] {
+            let err = parse(&query).unwrap_err();
+            assert!(
+                matches!(&err, Error::SyntaxError { message, .. } if message == "expression chain too long"),
+                "{err:?}"
+            );
+        }
+
+        // The limit is configurable
+        let tokens = tokenize(&nested("(", ")", 10)).unwrap();
+        assert!(parse_statement_with_max_depth(&tokens, 5).is_err());
+        assert!(parse_statement_with_max_depth(&tokens, 20).is_ok());
+    }
+
+    #[test]
+    fn test_empty_token_stream() {
+        let err = parse_statement(&[]).unwrap_err();
+        assert!(matches!(err, Error::SyntaxError { position: 0, .. }), "{err:?}");
+    }
+
+    #[test]
+    fn test_empty_query() {
+        for query in ["", "   ", "// just a comment", "/* block */\n"] {
+            let err = parse(query).unwrap_err();
+            assert!(
+                matches!(&err, Error::SyntaxError { message, .. } if message == "empty query"),
+                "{query:?}: {err:?}"
+            );
+        }
+    }
 }
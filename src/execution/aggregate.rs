@@ -0,0 +1,161 @@
+//! Incremental aggregation for the algebraic aggregates.
+//!
+//! `count`, `sum`, `avg`, `min` and `max` fold each row into a running
+//! value, so a group costs one accumulator per aggregate no matter how many
+//! rows it has. Anything else (`collect`, DISTINCT, the `apoc.agg.*`
+//! statistics, expressions over aggregates) needs the group's rows and goes
+//! through the buffering path in `aggregate_rows`. Results are the same
+//! either way, groups included, in first-seen order.
+//!
+//! `Groups` takes its input a batch at a time. Over a LOAD CSV the executor
+//! hands it each batch of records as it is read, so memory stays bounded by
+//! the batch and the groups. Other inputs come from operators that build
+//! all their rows first; those are folded in and dropped one by one, which
+//! saves the per-group copies but not the input itself.
+
+use std::collections::HashMap;
+
+use crate::cypher::ast::Expr;
+use crate::model::{Collation, Value};
+use crate::Result;
+
+use super::{eval_expr, integer_overflow, EvalParams, Row};
+
+/// The running value of one aggregate over one group.
+#[derive(Clone)]
+enum Accumulator {
+    CountRows(i64),
+    Count(i64),
+    Sum { int: i64, float: f64, has_float: bool },
+    Avg { sum: f64, count: i64 },
+    Min(Option<Value>),
+    Max(Option<Value>),
+}
+
+impl Accumulator {
+    /// The accumulator for `expr`, or None when it needs the group's rows.
+    fn for_expr(expr: &Expr) -> Option<Self> {
+        let Expr::FunctionCall { name, args, distinct: false, .. } = expr else {
+            return None;
+        };
+        match (name.to_uppercase().as_str(), args.len()) {
+            ("COUNT", 0) => Some(Accumulator::CountRows(0)),
+            ("COUNT", 1) => Some(Accumulator::Count(0)),
+            ("SUM", 1) => Some(Accumulator::Sum { int: 0, float: 0.0, has_float: false }),
+            ("AVG", 1) => Some(Accumulator::Avg { sum: 0.0, count: 0 }),
+            ("MIN", 1) => Some(Accumulator::Min(None)),
+            ("MAX", 1) => Some(Accumulator::Max(None)),
+            _ => None,
+        }
+    }
+
+    /// Fold in `value`, the aggregate's argument for one row (unused by
+    /// `count(*)`). Nulls are skipped, and an integer sum past the i64
+    /// range fails, as in the buffering path.
+    fn update(&mut self, value: Value, collation: Collation) -> Result<()> {
+        if value.is_null() && !matches!(self, Accumulator::CountRows(_)) {
+            return Ok(());
+        }
+        match self {
+            Accumulator::CountRows(n) | Accumulator::Count(n) => *n += 1,
+            Accumulator::Sum { int, float, has_float } => match value {
+                Value::Int(i) => *int = int.checked_add(i).ok_or_else(integer_overflow)?,
+                Value::Float(f) => {
+                    *has_float = true;
+                    *float += f;
+                }
+                _ => {}
+            },
+            Accumulator::Avg { sum, count } => {
+                *sum += value.as_float().unwrap_or(0.0);
+                *count += 1;
+            }
+            Accumulator::Min(current) => {
+                let keep = current.as_ref().is_some_and(|c| c.neo4j_cmp_with(&value, collation) == Some(std::cmp::Ordering::Less));
+                if !keep {
+                    *current = Some(value);
+                }
+            }
+            Accumulator::Max(current) => {
+                let keep = current.as_ref().is_some_and(|c| c.neo4j_cmp_with(&value, collation) == Some(std::cmp::Ordering::Greater));
+                if !keep {
+                    *current = Some(value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        match self {
+            Accumulator::CountRows(n) | Accumulator::Count(n) => Value::Int(n),
+            Accumulator::Sum { int, float, has_float: true } => Value::Float(int as f64 + float),
+            Accumulator::Sum { int, .. } => Value::Int(int),
+            Accumulator::Avg { count: 0, .. } => Value::Null,
+            Accumulator::Avg { sum, count } => Value::Float(sum / count as f64),
+            Accumulator::Min(value) | Accumulator::Max(value) => value.unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// Groups being aggregated incrementally: a key and accumulators each.
+pub(super) struct Groups<'a> {
+    group_by: &'a [(Expr, String)],
+    aggregations: &'a [(Expr, String)],
+    initial: Vec<Accumulator>,
+    groups: Vec<(Vec<Value>, Vec<Accumulator>)>,
+}
+
+impl<'a> Groups<'a> {
+    /// None when some aggregate in `aggregations` needs the buffering path.
+    pub(super) fn new(group_by: &'a [(Expr, String)], aggregations: &'a [(Expr, String)]) -> Option<Self> {
+        let initial = aggregations.iter().map(|(expr, _)| Accumulator::for_expr(expr)).collect::<Option<_>>()?;
+        Some(Self { group_by, aggregations, initial, groups: Vec::new() })
+    }
+
+    /// Fold in `rows`, dropping each once it is counted.
+    pub(super) fn add(&mut self, rows: Vec<Row>, params: &EvalParams) -> Result<()> {
+        for row in rows {
+            let key: Vec<Value> = self.group_by.iter()
+                .map(|(expr, _)| eval_expr(expr, &row, params).unwrap_or(Value::Null))
+                .collect();
+            // Keys group by equivalence: nulls together, 1 with 1.0
+            let index = match self.groups.iter().position(|(k, _)| k.iter().zip(&key).all(|(a, b)| a.equivalent(b))) {
+                Some(index) => index,
+                None => {
+                    self.groups.push((key, self.initial.clone()));
+                    self.groups.len() - 1
+                }
+            };
+            for ((expr, _), acc) in self.aggregations.iter().zip(&mut self.groups[index].1) {
+                let value = match expr {
+                    Expr::FunctionCall { args, .. } if !args.is_empty() => eval_expr(&args[0], &row, params)?,
+                    _ => Value::Null,
+                };
+                acc.update(value, params.collation)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// One row per group, in first-seen order.
+    pub(super) fn finish(mut self) -> Vec<Row> {
+        // No grouping and no rows still yields one row of empty aggregates
+        if self.groups.is_empty() && self.group_by.is_empty() {
+            self.groups.push((Vec::new(), self.initial));
+        }
+        self.groups
+            .into_iter()
+            .map(|(key, accs)| {
+                let mut row: Row = HashMap::new();
+                for ((_, alias), value) in self.group_by.iter().zip(key) {
+                    row.insert(alias.clone(), value);
+                }
+                for ((_, alias), acc) in self.aggregations.iter().zip(accs) {
+                    row.insert(alias.clone(), acc.finish());
+                }
+                row
+            })
+            .collect()
+    }
+}
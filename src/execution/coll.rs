@@ -0,0 +1,133 @@
+//! `apoc.coll.*` list functions.
+//!
+//! Each takes already-evaluated arguments and returns null when its list
+//! argument is null. `sum` and `avg` skip null elements, reject other
+//! non-numbers and return floats, as in APOC. `min`, `max` and `sort` order
+//! the way ORDER BY does, so lists that mix types still compare.
+
+use std::cmp::Ordering;
+
+use crate::model::{Collation, Value};
+use crate::{Error, Result};
+
+/// Dispatch `apoc.coll.<name>`; `name` is upper-cased, without the prefix.
+pub(super) fn call(name: &str, args: &[Value], collation: Collation) -> Result<Value> {
+    match name {
+        "SUM" => {
+            let [list] = args else { return Err(arity("sum", "list")) };
+            with_list(list, |items| Ok(Value::Float(numbers(items)?.into_iter().sum())))
+        }
+        "AVG" => {
+            let [list] = args else { return Err(arity("avg", "list")) };
+            with_list(list, |items| {
+                let numbers = numbers(items)?;
+                Ok(match numbers.len() {
+                    0 => Value::Null,
+                    n => Value::Float(numbers.iter().sum::<f64>() / n as f64),
+                })
+            })
+        }
+        "MIN" => {
+            let [list] = args else { return Err(arity("min", "list")) };
+            with_list(list, |items| Ok(extreme(items, collation, Ordering::Less)))
+        }
+        "MAX" => {
+            let [list] = args else { return Err(arity("max", "list")) };
+            with_list(list, |items| Ok(extreme(items, collation, Ordering::Greater)))
+        }
+        "SORT" => {
+            let [list] = args else { return Err(arity("sort", "list")) };
+            with_list(list, |items| {
+                let mut sorted = items.to_vec();
+                sorted.sort_by(|a, b| a.order_cmp_with(b, collation));
+                Ok(Value::List(sorted))
+            })
+        }
+        "TOSET" => {
+            let [list] = args else { return Err(arity("toSet", "list")) };
+            with_list(list, |items| {
+                let mut set: Vec<Value> = Vec::with_capacity(items.len());
+                for item in items {
+                    if !set.iter().any(|seen| seen.equivalent(item)) {
+                        set.push(item.clone());
+                    }
+                }
+                Ok(Value::List(set))
+            })
+        }
+        "CONTAINS" => {
+            let [list, value] = args else { return Err(arity("contains", "list, value")) };
+            with_list(list, |items| Ok(Value::Bool(position(items, value).is_some())))
+        }
+        "INDEXOF" => {
+            let [list, value] = args else { return Err(arity("indexOf", "list, value")) };
+            with_list(list, |items| Ok(Value::Int(position(items, value).map_or(-1, |i| i as i64))))
+        }
+        "FLATTEN" => {
+            let [list] = args else { return Err(arity("flatten", "list")) };
+            with_list(list, |items| {
+                let mut flat = Vec::with_capacity(items.len());
+                flatten_into(items, &mut flat);
+                Ok(Value::List(flat))
+            })
+        }
+        _ => Err(Error::ExecutionError(format!("Unknown function: apoc.coll.{}", name.to_lowercase()))),
+    }
+}
+
+/// Apply `f` to the elements of `list`; null for a null list.
+fn with_list(list: &Value, f: impl FnOnce(&[Value]) -> Result<Value>) -> Result<Value> {
+    match list {
+        Value::Null => Ok(Value::Null),
+        Value::List(items) => f(items),
+        other => Err(type_error("List", other)),
+    }
+}
+
+/// The non-null elements as floats, for `sum` and `avg`.
+fn numbers(items: &[Value]) -> Result<Vec<f64>> {
+    items
+        .iter()
+        .filter(|v| !v.is_null())
+        .map(|v| v.as_float().ok_or_else(|| type_error("Number", v)))
+        .collect()
+}
+
+/// The smallest (`Less`) or largest (`Greater`) non-null element, or null.
+fn extreme(items: &[Value], collation: Collation, wanted: Ordering) -> Value {
+    items
+        .iter()
+        .filter(|v| !v.is_null())
+        .fold(None, |best: Option<&Value>, v| match best {
+            Some(b) if v.order_cmp_with(b, collation) != wanted => Some(b),
+            _ => Some(v),
+        })
+        .cloned()
+        .unwrap_or(Value::Null)
+}
+
+/// Where `value` first occurs in `items`, comparing as `=` does, so 1
+/// matches 1.0 and null matches nothing.
+fn position(items: &[Value], value: &Value) -> Option<usize> {
+    if value.is_null() {
+        return None;
+    }
+    items.iter().position(|item| item.equivalent(value))
+}
+
+fn flatten_into(items: &[Value], out: &mut Vec<Value>) {
+    for item in items {
+        match item {
+            Value::List(nested) => flatten_into(nested, out),
+            other => out.push(other.clone()),
+        }
+    }
+}
+
+fn type_error(expected: &str, got: &Value) -> Error {
+    Error::TypeError { expected: expected.into(), got: got.type_name().into(), span: None }
+}
+
+fn arity(name: &str, signature: &str) -> Error {
+    Error::ExecutionError(format!("apoc.coll.{name}() expects ({signature})"))
+}
@@ -0,0 +1,221 @@
+//! CSV sources for LOAD CSV.
+//!
+//! Records are read one at a time from a buffered reader, so a file is
+//! never held in memory as a whole; the executor takes them in batches (see
+//! `execute_csv_batches`). A plan that needs every row at once, such as
+//! one that sorts or aggregates them, still reads the whole file before it
+//! goes on. Fields follow RFC 4180 quoting: a quoted field may contain the
+//! terminator, newlines, and `""` escapes.
+//!
+//! Opening and reading block, so both run on tokio's blocking pool when
+//! there is a runtime to hand them to.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::model::{PropertyMap, Value};
+use crate::{Error, Result};
+
+use super::Row;
+
+/// Open the source behind a LOAD CSV url.
+///
+/// `file://` urls need an `import_root`: the path is read relative to it
+/// and may not leave it, symlinks and `..` included. `http://` and
+/// `https://` need the `load-csv-http` feature.
+fn open(url: &str, import_root: Option<&Path>) -> Result<Box<dyn BufRead + Send>> {
+    if let Some(path) = url.strip_prefix("file://") {
+        let Some(root) = import_root else {
+            return Err(Error::ExecutionError(format!(
+                "Cannot load from {url}: file urls need an import directory (Graph::with_import_root)"
+            )));
+        };
+        let path = confine(url, root, path)?;
+        let file = std::fs::File::open(path)
+            .map_err(|e| Error::ExecutionError(format!("Cannot load from {url}: {e}")))?;
+        return Ok(Box::new(BufReader::new(file)));
+    }
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return open_http(url);
+    }
+    Err(Error::ExecutionError(format!("Unsupported LOAD CSV url: {url}")))
+}
+
+/// `path` resolved under `root`, or an error if it lies outside it.
+fn confine(url: &str, root: &Path, path: &str) -> Result<PathBuf> {
+    let cannot = |why: String| Error::ExecutionError(format!("Cannot load from {url}: {why}"));
+    let root = root.canonicalize().map_err(|e| cannot(format!("import directory: {e}")))?;
+    let resolved = root
+        .join(path.trim_start_matches('/'))
+        .canonicalize()
+        .map_err(|e| cannot(e.to_string()))?;
+    if !resolved.starts_with(&root) {
+        return Err(cannot("outside the import directory".into()));
+    }
+    Ok(resolved)
+}
+
+#[cfg(feature = "load-csv-http")]
+fn open_http(url: &str) -> Result<Box<dyn BufRead + Send>> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| Error::ExecutionError(format!("Cannot load from {url}: {e}")))?;
+    Ok(Box::new(BufReader::new(response.into_reader())))
+}
+
+#[cfg(not(feature = "load-csv-http"))]
+fn open_http(url: &str) -> Result<Box<dyn BufRead + Send>> {
+    Err(Error::ExecutionError(format!(
+        "Cannot load from {url}: http(s) sources require the load-csv-http feature"
+    )))
+}
+
+/// Run blocking IO `f` on tokio's blocking pool, or inline outside a
+/// tokio runtime.
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Result<T> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle
+            .spawn_blocking(f)
+            .await
+            .map_err(|e| Error::ExecutionError(format!("CSV reader failed: {e}"))),
+        Err(_) => Ok(f()),
+    }
+}
+
+/// The rows of one LOAD CSV, read on demand: a map per record keyed by the
+/// header when there is one, a list of fields otherwise.
+pub(super) struct CsvRows {
+    /// Out on the blocking pool while a batch is read.
+    reader: Option<CsvReader>,
+}
+
+impl CsvRows {
+    pub(super) async fn open(
+        url: &str,
+        with_headers: bool,
+        alias: &str,
+        terminator: char,
+        import_root: Option<&Path>,
+    ) -> Result<Self> {
+        let (url, alias) = (url.to_string(), alias.to_string());
+        let import_root = import_root.map(Path::to_path_buf);
+        let reader = blocking(move || -> Result<CsvReader> {
+            let mut records = Records::new(open(&url, import_root.as_deref())?, terminator);
+            let headers = if with_headers { records.next_record()? } else { None };
+            Ok(CsvReader { records, headers, alias })
+        })
+        .await??;
+        Ok(Self { reader: Some(reader) })
+    }
+
+    /// Up to `n` more rows, binding each record to the alias; fewer only at
+    /// the end of the input.
+    pub(super) async fn next_batch(&mut self, n: usize) -> Result<Vec<Row>> {
+        let mut reader = self.reader.take()
+            .ok_or_else(|| Error::ExecutionError("CSV reader failed earlier".into()))?;
+        let (reader, rows) = blocking(move || {
+            let rows = reader.next_batch(n);
+            (reader, rows)
+        })
+        .await?;
+        self.reader = Some(reader);
+        rows
+    }
+}
+
+struct CsvReader {
+    records: Records<Box<dyn BufRead + Send>>,
+    headers: Option<Vec<String>>,
+    alias: String,
+}
+
+impl CsvReader {
+    fn next_batch(&mut self, n: usize) -> Result<Vec<Row>> {
+        let mut rows = Vec::new();
+        while rows.len() < n {
+            let Some(record) = self.records.next_record()? else { break };
+            let value = match &self.headers {
+                Some(keys) => {
+                    let mut map = PropertyMap::new();
+                    for (i, key) in keys.iter().enumerate() {
+                        let field = record.get(i).cloned().map_or(Value::Null, Value::String);
+                        map.insert(key.clone(), field);
+                    }
+                    Value::Map(map)
+                }
+                None => Value::List(record.into_iter().map(Value::String).collect()),
+            };
+            rows.push(HashMap::from([(self.alias.clone(), value)]));
+        }
+        Ok(rows)
+    }
+}
+
+/// Pull-based record reader.
+struct Records<R> {
+    reader: R,
+    terminator: char,
+    line: String,
+}
+
+impl<R: BufRead> Records<R> {
+    fn new(reader: R, terminator: char) -> Self {
+        Self { reader, terminator, line: String::new() }
+    }
+
+    /// The next record's fields, or `None` at end of input. Blank lines are skipped.
+    fn next_record(&mut self) -> Result<Option<Vec<String>>> {
+        loop {
+            if !self.read_line()? {
+                return Ok(None);
+            }
+            if !self.line.trim_end_matches(['\r', '\n']).is_empty() {
+                break;
+            }
+        }
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        loop {
+            let mut chars = self.line.chars().peekable();
+            while let Some(c) = chars.next() {
+                if in_quotes {
+                    match c {
+                        '"' if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        '"' => in_quotes = false,
+                        c => field.push(c),
+                    }
+                } else if c == '"' && field.is_empty() {
+                    in_quotes = true;
+                } else if c == self.terminator {
+                    fields.push(std::mem::take(&mut field));
+                } else if c == '\n' || (c == '\r' && matches!(chars.peek(), Some('\n') | None)) {
+                    // end of record
+                } else {
+                    field.push(c);
+                }
+            }
+            // A quoted field that runs past the end of the line continues on the next one
+            if !in_quotes {
+                break;
+            }
+            if !self.read_line()? {
+                return Err(Error::ExecutionError("Unterminated quoted field in CSV".into()));
+            }
+        }
+        fields.push(field);
+        Ok(Some(fields))
+    }
+
+    fn read_line(&mut self) -> Result<bool> {
+        self.line.clear();
+        let n = self.reader.read_line(&mut self.line)
+            .map_err(|e| Error::ExecutionError(format!("CSV read failed: {e}")))?;
+        Ok(n > 0)
+    }
+}
@@ -3,18 +3,37 @@
 //! Executes logical plans against a StorageBackend.
 
 use std::collections::HashMap;
+use std::ops::Bound;
+use std::sync::Arc;
+use parking_lot::Mutex;
+use rand::{RngExt, SeedableRng, rngs::StdRng};
 use crate::model::*;
-use crate::cypher::ast::{Expr, Literal, BinaryOp, UnaryOp, StringOp};
-use crate::storage::StorageBackend;
-use crate::planner::LogicalPlan;
+use crate::model::property_map::{parse_integer, truncate};
+use crate::cypher::ast::{self, Expr, Literal, BinaryOp, UnaryOp, StringOp};
+use crate::storage::{ProcedureResult, StorageBackend};
+use crate::planner::{LogicalPlan, is_aggregate_expr, is_aggregate_function};
 use crate::{Error, Result};
 
+mod aggregate;
+mod coll;
+mod csv;
+mod observer;
+mod path_expand;
+mod periodic;
+mod refactor;
+mod temporal;
+mod text;
+
+pub use observer::{NoopObserver, QueryObserver, TracingObserver};
+
 /// Query execution result.
 #[derive(Debug, Clone)]
 pub struct QueryResult {
     pub columns: Vec<String>,
     pub rows: Vec<ResultRow>,
     pub stats: ExecutionStats,
+    /// Warnings about the query, such as cartesian products. Set by `Graph`.
+    pub notifications: Vec<crate::planner::Notification>,
 }
 
 /// A single row in the result set. Preserves column order.
@@ -33,6 +52,17 @@ impl ResultRow {
         T::from_value(val)
     }
 
+    /// Get a typed value, treating a missing column or NULL as `None`.
+    ///
+    /// Useful after OPTIONAL MATCH or when rows are heterogeneous.
+    /// Type mismatches on a present, non-NULL value still error.
+    pub fn get_opt<T: FromValue>(&self, key: &str) -> Result<Option<T>> {
+        match self.get_value(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(val) => T::from_value(val).map(Some),
+        }
+    }
+
     /// Get a raw Value reference by column name.
     pub fn get_value(&self, key: &str) -> Option<&Value> {
         self.values.iter().find(|(k, _)| k == key).map(|(_, v)| v)
@@ -49,7 +79,34 @@ pub struct ExecutionStats {
     pub properties_set: u64,
     pub labels_added: u64,
     pub labels_removed: u64,
+    /// Total time, in milliseconds: parse, plan and execute when run
+    /// through `Graph`, execution alone when run through `execute`.
     pub execution_time_ms: u64,
+    /// Rows in the result.
+    pub rows_returned: u64,
+    /// Time spent parsing, in microseconds. Set by `Graph`.
+    pub parse_time_us: u64,
+    /// Time spent planning and optimizing, in microseconds. Set by `Graph`.
+    pub plan_time_us: u64,
+    /// Time spent executing the plan, in microseconds.
+    pub execute_time_us: u64,
+    /// `LogicalPlan::plan_hash` of the plan that ran, as optimized.
+    pub plan_hash: u64,
+    /// The plan came from `Graph`'s plan cache rather than the optimizer.
+    pub plan_cached: bool,
+}
+
+impl ExecutionStats {
+    /// Add `other`'s write counts to these.
+    fn add(&mut self, other: &ExecutionStats) {
+        self.nodes_created += other.nodes_created;
+        self.nodes_deleted += other.nodes_deleted;
+        self.relationships_created += other.relationships_created;
+        self.relationships_deleted += other.relationships_deleted;
+        self.properties_set += other.properties_set;
+        self.labels_added += other.labels_added;
+        self.labels_removed += other.labels_removed;
+    }
 }
 
 /// Convert from Value to concrete types.
@@ -64,6 +121,7 @@ impl FromValue for Node {
             _ => Err(Error::TypeError {
                 expected: "Node".into(),
                 got: val.type_name().into(),
+                span: None,
             }),
         }
     }
@@ -76,6 +134,7 @@ impl FromValue for String {
             _ => Err(Error::TypeError {
                 expected: "String".into(),
                 got: val.type_name().into(),
+                span: None,
             }),
         }
     }
@@ -86,6 +145,7 @@ impl FromValue for i64 {
         val.as_int().ok_or_else(|| Error::TypeError {
             expected: "Integer".into(),
             got: val.type_name().into(),
+            span: None,
         })
     }
 }
@@ -95,6 +155,7 @@ impl FromValue for f64 {
         val.as_float().ok_or_else(|| Error::TypeError {
             expected: "Float".into(),
             got: val.type_name().into(),
+            span: None,
         })
     }
 }
@@ -103,7 +164,7 @@ impl FromValue for bool {
     fn from_value(val: &Value) -> Result<Self> {
         match val {
             Value::Bool(b) => Ok(*b),
-            _ => Err(Error::TypeError { expected: "Bool".into(), got: val.type_name().into() }),
+            _ => Err(Error::TypeError { expected: "Bool".into(), got: val.type_name().into(), span: None }),
         }
     }
 }
@@ -118,7 +179,7 @@ impl FromValue for Relationship {
     fn from_value(val: &Value) -> Result<Self> {
         match val {
             Value::Relationship(r) => Ok(*r.clone()),
-            _ => Err(Error::TypeError { expected: "Relationship".into(), got: val.type_name().into() }),
+            _ => Err(Error::TypeError { expected: "Relationship".into(), got: val.type_name().into(), span: None }),
         }
     }
 }
@@ -127,7 +188,7 @@ impl FromValue for Path {
     fn from_value(val: &Value) -> Result<Self> {
         match val {
             Value::Path(p) => Ok(*p.clone()),
-            _ => Err(Error::TypeError { expected: "Path".into(), got: val.type_name().into() }),
+            _ => Err(Error::TypeError { expected: "Path".into(), got: val.type_name().into(), span: None }),
         }
     }
 }
@@ -136,7 +197,7 @@ impl FromValue for Vec<Value> {
     fn from_value(val: &Value) -> Result<Self> {
         match val {
             Value::List(l) => Ok(l.clone()),
-            _ => Err(Error::TypeError { expected: "List".into(), got: val.type_name().into() }),
+            _ => Err(Error::TypeError { expected: "List".into(), got: val.type_name().into(), span: None }),
         }
     }
 }
@@ -145,7 +206,16 @@ impl FromValue for HashMap<String, Value> {
     fn from_value(val: &Value) -> Result<Self> {
         match val {
             Value::Map(m) => Ok(m.clone()),
-            _ => Err(Error::TypeError { expected: "Map".into(), got: val.type_name().into() }),
+            _ => Err(Error::TypeError { expected: "Map".into(), got: val.type_name().into(), span: None }),
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(val: &Value) -> Result<Self> {
+        match val {
+            Value::Bytes(b) => Ok(b.clone()),
+            _ => Err(Error::TypeError { expected: "Bytes".into(), got: val.type_name().into(), span: None }),
         }
     }
 }
@@ -160,8 +230,63 @@ pub async fn execute<B: StorageBackend>(
     plan: LogicalPlan,
     params: PropertyMap,
 ) -> Result<QueryResult> {
-    let mut ctx = ExecContext::with_params(params);
-    let rows = execute_plan(backend, tx, &plan, &mut ctx).await?;
+    execute_with(backend, tx, plan, params, &ExecOptions::default()).await
+}
+
+/// How `execute_with` runs a plan, beyond its parameters.
+///
+/// The default draws `rand()` from the thread RNG, reports to no observer,
+/// orders strings bytewise, caps no rows and refuses LOAD CSV from files.
+#[derive(Clone)]
+pub struct ExecOptions {
+    /// Where `rand()` and `randomUUID()` draw their values from.
+    pub random: RandomSource,
+    /// Told about each operator and the finished query.
+    pub observer: Arc<dyn QueryObserver>,
+    /// How strings order in comparisons, ORDER BY, `min()` and `max()`.
+    pub collation: Collation,
+    /// Row caps; exceeding one fails with "result set too large".
+    pub limits: RowLimits,
+    /// Directory `LOAD CSV` `file://` urls are read from and confined to.
+    /// `None` refuses `file://` urls.
+    pub import_root: Option<std::path::PathBuf>,
+}
+
+impl Default for ExecOptions {
+    fn default() -> Self {
+        Self {
+            random: RandomSource::default(),
+            observer: Arc::new(NoopObserver),
+            collation: Collation::default(),
+            limits: RowLimits::default(),
+            import_root: None,
+        }
+    }
+}
+
+/// Like `execute`, run as `options` say.
+pub async fn execute_with<B: StorageBackend>(
+    backend: &B,
+    tx: &mut B::Tx,
+    plan: LogicalPlan,
+    params: PropertyMap,
+    options: &ExecOptions,
+) -> Result<QueryResult> {
+    let started = std::time::Instant::now();
+    let mut ctx = ExecContext::new(params, options);
+    ctx.stats.plan_hash = plan.plan_hash();
+    let mut rows = execute_plan(backend, tx, &plan, &mut ctx).await?;
+    // Without a RETURN the statement ends in its write and returns nothing
+    if ends_in_write(&plan) {
+        rows.clear();
+        ctx.columns.clear();
+    }
+    options.limits.check_result(rows.len())?;
+    let elapsed = started.elapsed();
+    ctx.stats.rows_returned = rows.len() as u64;
+    ctx.stats.execute_time_us = elapsed.as_micros() as u64;
+    ctx.stats.execution_time_ms = elapsed.as_millis() as u64;
+    ctx.observer.on_query_complete(&ctx.stats);
 
     let columns = ctx.columns.clone();
     let result_rows: Vec<ResultRow> = rows.into_iter().map(|row| {
@@ -175,27 +300,149 @@ pub async fn execute<B: StorageBackend>(
         columns,
         rows: result_rows,
         stats: ctx.stats,
+        notifications: Vec::new(),
     })
 }
 
+/// Whether `plan`'s last operator writes, as a statement with no RETURN
+/// or YIELD ends.
+fn ends_in_write(plan: &LogicalPlan) -> bool {
+    matches!(
+        plan,
+        LogicalPlan::CreateNode { .. }
+            | LogicalPlan::CreateRel { .. }
+            | LogicalPlan::MergeNode { .. }
+            | LogicalPlan::SetProperty { .. }
+            | LogicalPlan::SetProperties { .. }
+            | LogicalPlan::DeleteNode { .. }
+            | LogicalPlan::DeleteRel { .. }
+            | LogicalPlan::RemoveProperty { .. }
+            | LogicalPlan::RemoveLabel { .. }
+            | LogicalPlan::SchemaOp(_)
+    )
+}
+
 // ============================================================================
 // Execution context
 // ============================================================================
 
 type Row = HashMap<String, Value>;
 
+/// Caps on how many rows a query may hold, so one pathological query (a
+/// cartesian product of two large scans, say) can't exhaust the host's
+/// memory. `None` means unlimited, the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RowLimits {
+    /// Rows the query may return.
+    pub max_result_rows: Option<usize>,
+    /// Rows any one operator may produce, subqueries included.
+    pub max_intermediate_rows: Option<usize>,
+}
+
+impl RowLimits {
+    fn check_result(&self, rows: usize) -> Result<()> {
+        check_row_limit(self.max_result_rows, rows)
+    }
+
+    fn check_intermediate(&self, rows: usize) -> Result<()> {
+        check_row_limit(self.max_intermediate_rows, rows)
+    }
+}
+
+fn check_row_limit(max: Option<usize>, rows: usize) -> Result<()> {
+    match max {
+        Some(max) if rows > max => Err(Error::ExecutionError("result set too large".into())),
+        _ => Ok(()),
+    }
+}
+
 struct ExecContext {
     columns: Vec<String>,
     stats: ExecutionStats,
-    params: PropertyMap,
+    params: EvalParams,
+    observer: Arc<dyn QueryObserver>,
+    /// The row `Argument` produces: empty, or the outer row of a subquery.
+    argument: Row,
+    limits: RowLimits,
+    import_root: Option<std::path::PathBuf>,
+    /// Set while `execute_csv_batches` runs a plan over one batch.
+    in_csv_batch: bool,
+    /// That batch, until its `LoadCsv` takes it.
+    csv_batch: Option<Vec<Row>>,
 }
 
 impl ExecContext {
-    fn with_params(params: PropertyMap) -> Self {
+    fn new(params: PropertyMap, options: &ExecOptions) -> Self {
         Self {
             columns: Vec::new(),
             stats: ExecutionStats::default(),
-            params,
+            params: EvalParams { values: params, random: options.random.clone(), collation: options.collation },
+            observer: options.observer.clone(),
+            argument: Row::new(),
+            limits: options.limits,
+            import_root: options.import_root.clone(),
+            in_csv_batch: false,
+            csv_batch: None,
+        }
+    }
+
+    /// A context for a plan nested in this one's (a subquery, fragment or
+    /// batch), with its own `params` and columns but the same options.
+    fn sub(&self, params: PropertyMap) -> Self {
+        Self {
+            columns: Vec::new(),
+            stats: ExecutionStats::default(),
+            params: EvalParams { values: params, random: self.params.random.clone(), collation: self.params.collation },
+            observer: self.observer.clone(),
+            argument: Row::new(),
+            limits: self.limits,
+            import_root: self.import_root.clone(),
+            in_csv_batch: false,
+            csv_batch: None,
+        }
+    }
+}
+
+/// Query parameters, plus the random source `rand()` and `randomUUID()` use
+/// and the collation strings order by.
+struct EvalParams {
+    values: PropertyMap,
+    random: RandomSource,
+    collation: Collation,
+}
+
+impl std::ops::Deref for EvalParams {
+    type Target = PropertyMap;
+
+    fn deref(&self) -> &PropertyMap {
+        &self.values
+    }
+}
+
+/// Where `rand()` and `randomUUID()` draw their values from.
+///
+/// The default draws from the thread RNG. A seeded source replays the same
+/// sequence for the same sequence of calls, and clones share that sequence.
+#[derive(Debug, Clone, Default)]
+pub struct RandomSource(Option<Arc<Mutex<StdRng>>>);
+
+impl RandomSource {
+    /// A reproducible source starting from `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        Self(Some(Arc::new(Mutex::new(StdRng::seed_from_u64(seed)))))
+    }
+
+    fn next_f64(&self) -> f64 {
+        match &self.0 {
+            Some(rng) => rng.lock().random(),
+            None => rand::rng().random(),
+        }
+    }
+
+    fn fill(&self, buf: &mut [u8]) {
+        match &self.0 {
+            Some(rng) => rng.lock().fill(buf),
+            None => rand::rng().fill(buf),
         }
     }
 }
@@ -209,12 +456,114 @@ fn execute_plan<'a, B: StorageBackend>(
     tx: &'a mut B::Tx,
     plan: &'a LogicalPlan,
     ctx: &'a mut ExecContext,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<Row>>> + Send + 'a>> {
+    Box::pin(async move {
+        if !ctx.in_csv_batch && let Some(load) = streamed_load_csv(plan) {
+            let mut rows = Vec::new();
+            execute_csv_batches(backend, tx, plan, load, ctx, |batch, ctx| {
+                rows.extend(batch);
+                ctx.limits.check_intermediate(rows.len())
+            })
+            .await?;
+            return Ok(rows);
+        }
+        let op_name = plan.operator_name();
+        ctx.observer.on_operator_start(op_name);
+        let rows = execute_operator(backend, tx, plan, ctx).await?;
+        ctx.observer.on_rows_produced(op_name, rows.len());
+        ctx.limits.check_intermediate(rows.len())?;
+        Ok(rows)
+    })
+}
+
+/// Records `execute_csv_batches` hands a streamed LOAD CSV at a time.
+const CSV_BATCH_ROWS: usize = 1000;
+
+/// The LOAD CSV at the bottom of `plan` when every operator on the way
+/// down handles rows one at a time (filters, projections, writes, ...), so
+/// `plan` gives the same rows run a batch of records at a time.
+fn streamed_load_csv(plan: &LogicalPlan) -> Option<&LogicalPlan> {
+    fn per_row_input(plan: &LogicalPlan) -> Option<&LogicalPlan> {
+        match plan {
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Project { input, .. }
+            | LogicalPlan::Expand { input, .. }
+            | LogicalPlan::Unwind { input, .. }
+            | LogicalPlan::SetProperty { input, .. }
+            | LogicalPlan::SetProperties { input, .. }
+            | LogicalPlan::RemoveProperty { input, .. }
+            | LogicalPlan::RemoveLabel { input, .. }
+            | LogicalPlan::DeleteNode { input, .. }
+            | LogicalPlan::DeleteRel { input, .. }
+            | LogicalPlan::CreateNode { input: Some(input), .. }
+            | LogicalPlan::CreateRel { input: Some(input), .. }
+            | LogicalPlan::MergeNode { input: Some(input), .. } => Some(input),
+            _ => None,
+        }
+    }
+    let mut input = per_row_input(plan)?;
+    while !matches!(input, LogicalPlan::LoadCsv { .. }) {
+        input = per_row_input(input)?;
+    }
+    Some(input)
+}
+
+/// Run `plan`, per-row operators over the LOAD CSV `load`, once per batch
+/// of `CSV_BATCH_ROWS` records, handing each batch's rows to `sink`, so
+/// the file is never read into memory whole. Each batch sees the writes of
+/// the ones before it.
+async fn execute_csv_batches<B: StorageBackend>(
+    backend: &B,
+    tx: &mut B::Tx,
+    plan: &LogicalPlan,
+    load: &LogicalPlan,
+    ctx: &mut ExecContext,
+    mut sink: impl FnMut(Vec<Row>, &ExecContext) -> Result<()> + Send,
+) -> Result<()> {
+    let mut source = open_csv(load, ctx).await?;
+    ctx.in_csv_batch = true;
+    loop {
+        let batch = source.next_batch(CSV_BATCH_ROWS).await?;
+        let last = batch.len() < CSV_BATCH_ROWS;
+        ctx.csv_batch = Some(batch);
+        // Run even an empty first batch, so the columns are still set
+        let rows = execute_plan(backend, tx, plan, ctx).await?;
+        sink(rows, ctx)?;
+        if last {
+            break;
+        }
+    }
+    ctx.in_csv_batch = false;
+    Ok(())
+}
+
+/// Open the source of the `LoadCsv` operator `load`.
+async fn open_csv(load: &LogicalPlan, ctx: &ExecContext) -> Result<csv::CsvRows> {
+    let LogicalPlan::LoadCsv { url, with_headers, alias, field_terminator } = load else {
+        return Err(Error::ExecutionError(format!("{} is not a LOAD CSV", load.operator_name())));
+    };
+    let url = match eval_expr(url, &HashMap::new(), &ctx.params)? {
+        Value::String(s) => s,
+        other => return Err(Error::TypeError {
+            expected: "STRING".into(),
+            got: other.type_name().into(),
+            span: None,
+        }),
+    };
+    csv::CsvRows::open(&url, *with_headers, alias, field_terminator.unwrap_or(','), ctx.import_root.as_deref()).await
+}
+
+fn execute_operator<'a, B: StorageBackend>(
+    backend: &'a B,
+    tx: &'a mut B::Tx,
+    plan: &'a LogicalPlan,
+    ctx: &'a mut ExecContext,
 ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<Row>>> + Send + 'a>> {
     Box::pin(async move {
     match plan {
         LogicalPlan::Argument => {
-            // Produce one empty row (seed for pipelines)
-            Ok(vec![HashMap::new()])
+            // Produce one row (seed for pipelines)
+            Ok(vec![ctx.argument.clone()])
         }
 
         LogicalPlan::NodeScan { label, alias } => {
@@ -230,8 +579,9 @@ fn execute_plan<'a, B: StorageBackend>(
             Ok(rows)
         }
 
-        LogicalPlan::AllNodesScan { alias } => {
-            let nodes = backend.all_nodes(tx).await?;
+        LogicalPlan::MultiLabelScan { labels, alias } => {
+            let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+            let nodes = backend.nodes_by_labels(tx, &labels).await?;
             let rows: Vec<Row> = nodes.into_iter().map(|n| {
                 let mut row = HashMap::new();
                 row.insert(alias.clone(), Value::Node(Box::new(n)));
@@ -243,9 +593,8 @@ fn execute_plan<'a, B: StorageBackend>(
             Ok(rows)
         }
 
-        LogicalPlan::IndexLookup { label, property, alias } => {
-            // Falls back to label scan — memory backend has no real indexes
-            let nodes = backend.nodes_by_label(tx, label).await?;
+        LogicalPlan::AllNodesScan { alias } => {
+            let nodes = backend.all_nodes(tx).await?;
             let rows: Vec<Row> = nodes.into_iter().map(|n| {
                 let mut row = HashMap::new();
                 row.insert(alias.clone(), Value::Node(Box::new(n)));
@@ -254,52 +603,149 @@ fn execute_plan<'a, B: StorageBackend>(
             if !ctx.columns.contains(alias) {
                 ctx.columns.push(alias.clone());
             }
-            let _ = property; // suppress warning
             Ok(rows)
         }
 
-        LogicalPlan::Expand { input, from, dir, rel_types, to, rel_alias } => {
+        LogicalPlan::IndexLookup { label, property, value, alias } => {
+            let value = eval_expr(value, &HashMap::new(), &ctx.params)?;
+            if !ctx.columns.contains(alias) {
+                ctx.columns.push(alias.clone());
+            }
+            // `= null` is never true
+            if value.is_null() {
+                return Ok(Vec::new());
+            }
+            let nodes = backend.nodes_by_property(tx, label, property, &value).await?;
+            Ok(nodes.into_iter().map(|n| {
+                let mut row = HashMap::new();
+                row.insert(alias.clone(), Value::Node(Box::new(n)));
+                row
+            }).collect())
+        }
+
+        LogicalPlan::IndexRangeScan { label, property, lower, upper, alias } => {
+            let bound = |b: &Option<(Expr, bool)>| -> Result<Option<(Value, bool)>> {
+                b.as_ref()
+                    .map(|(e, inclusive)| Ok((eval_expr(e, &HashMap::new(), &ctx.params)?, *inclusive)))
+                    .transpose()
+            };
+            let (lower, upper) = (bound(lower)?, bound(upper)?);
+            if !ctx.columns.contains(alias) {
+                ctx.columns.push(alias.clone());
+            }
+            // A null bound compares as null, so nothing matches
+            if lower.iter().chain(&upper).any(|(v, _)| v.is_null()) {
+                return Ok(Vec::new());
+            }
+            fn as_bound(b: &Option<(Value, bool)>) -> Bound<&Value> {
+                match b {
+                    Some((v, true)) => Bound::Included(v),
+                    Some((v, false)) => Bound::Excluded(v),
+                    None => Bound::Unbounded,
+                }
+            }
+            let collation = ctx.params.collation;
+            let string_bound = lower.iter().chain(&upper).any(|(v, _)| matches!(v, Value::String(_)));
+            let nodes = if collation != Collation::ByteOrder && string_bound {
+                // Backend ranges order strings by code point: filter the label instead
+                use std::cmp::Ordering::{Equal, Greater, Less};
+                let within = |v: &Value, bound: &Option<(Value, bool)>, past: std::cmp::Ordering| match bound {
+                    None => true,
+                    Some((b, inclusive)) => match v.neo4j_cmp_with(b, collation) {
+                        Some(Equal) => *inclusive,
+                        ord => ord == Some(past),
+                    },
+                };
+                let mut nodes = backend.nodes_by_label(tx, label).await?;
+                nodes.retain(|n| n.get(property).is_some_and(|v| within(v, &lower, Greater) && within(v, &upper, Less)));
+                nodes
+            } else {
+                backend
+                    .nodes_by_property_range(tx, label, property, as_bound(&lower), as_bound(&upper))
+                    .await?
+            };
+            Ok(nodes.into_iter().map(|n| {
+                let mut row = HashMap::new();
+                row.insert(alias.clone(), Value::Node(Box::new(n)));
+                row
+            }).collect())
+        }
+
+        LogicalPlan::Expand { input, from, dir, rel_types, to, rel_alias, path, unique_from } => {
             // Execute input pipeline first to get rows with 'from' variable bound
             let input_rows = execute_plan(backend, tx, input, ctx).await?;
+            let types: Vec<&str> = rel_types.iter().map(String::as_str).collect();
             let mut rows = Vec::new();
             for input_row in &input_rows {
                 if let Some(Value::Node(from_node)) = input_row.get(from) {
-                    let rels = backend.get_relationships(tx, from_node.id, *dir, None).await?;
+                    // Expand into: when `to` is already bound (a cycle, or a
+                    // node from an earlier pattern) only relationships
+                    // landing on it match, and it is not rebound.
+                    let into = match input_row.get(to) {
+                        Some(Value::Node(n)) => Some(n.id),
+                        Some(_) => continue,
+                        None => None,
+                    };
+                    let rels = backend.get_relationships_typed(tx, from_node.id, *dir, &types).await?;
                     for rel in rels {
-                        if !rel_types.is_empty() && !rel_types.contains(&rel.rel_type) {
+                        let other_id = if rel.src == from_node.id { rel.dst } else { rel.src };
+                        if into.is_some_and(|id| id != other_id) {
+                            continue;
+                        }
+                        let reused = unique_from.iter().any(|r| {
+                            matches!(input_row.get(r), Some(Value::Relationship(used)) if used.id == rel.id)
+                        });
+                        if reused {
                             continue;
                         }
-                        let other_id = if rel.src == from_node.id { rel.dst } else { rel.src };
                         if let Some(other) = backend.get_node(tx, other_id).await? {
                             let mut row = input_row.clone();
+                            if let Some(path) = path {
+                                let mut walked = match input_row.get(path) {
+                                    Some(Value::Path(p)) => (**p).clone(),
+                                    _ => Path::single((**from_node).clone()),
+                                };
+                                walked.append(rel.clone(), other.clone());
+                                row.insert(path.clone(), Value::Path(Box::new(walked)));
+                            }
                             row.insert(to.clone(), Value::Node(Box::new(other)));
                             if let Some(ra) = rel_alias {
                                 row.insert(ra.clone(), Value::Relationship(Box::new(rel.clone())));
                             }
                             rows.push(row);
+                            ctx.limits.check_intermediate(rows.len())?;
                         }
                     }
                 }
             }
-            for col in [from, to] {
+            for col in [from, to].into_iter().chain(rel_alias).chain(path) {
                 if !ctx.columns.contains(col) {
                     ctx.columns.push(col.clone());
                 }
             }
-            if let Some(ra) = rel_alias {
-                if !ctx.columns.contains(ra) {
-                    ctx.columns.push(ra.clone());
-                }
-            }
             Ok(rows)
         }
 
         LogicalPlan::Filter { input, predicate } => {
             let rows = execute_plan(backend, tx, input, ctx).await?;
+            let mut subqueries = Vec::new();
+            let predicate = bind_subqueries(predicate, &mut subqueries);
+            if subqueries.is_empty() {
+                let mut filtered = Vec::new();
+                for row in rows {
+                    let val = eval_expr(&predicate, &row, &ctx.params)?;
+                    if val.is_truthy() {
+                        filtered.push(row);
+                    }
+                }
+                return Ok(filtered);
+            }
+
+            let mut plans: Vec<Option<LogicalPlan>> = vec![None; subqueries.len()];
             let mut filtered = Vec::new();
             for row in rows {
-                let val = eval_expr(predicate, &row, &ctx.params)?;
-                if val.is_truthy() {
+                let bound_row = eval_subqueries(backend, tx, &subqueries, &mut plans, &row, ctx).await?;
+                if eval_expr(&predicate, &bound_row, &ctx.params)?.is_truthy() {
                     filtered.push(row);
                 }
             }
@@ -311,11 +757,41 @@ fn execute_plan<'a, B: StorageBackend>(
             // Reset columns to the projection list
             ctx.columns = items.iter().map(|(_, alias)| alias.clone()).collect();
 
+            let mut subqueries = Vec::new();
+            let bound_items: Vec<(Expr, String)> =
+                items.iter().map(|(expr, alias)| (bind_subqueries(expr, &mut subqueries), alias.clone())).collect();
+            if !subqueries.is_empty() {
+                let mut plans: Vec<Option<LogicalPlan>> = vec![None; subqueries.len()];
+                let mut projected = Vec::new();
+                for row in &rows {
+                    let bound_row = eval_subqueries(backend, tx, &subqueries, &mut plans, row, ctx).await?;
+                    let mut new_row = HashMap::new();
+                    for (expr, alias) in &bound_items {
+                        new_row.insert(alias.clone(), eval_expr(expr, &bound_row, &ctx.params)?);
+                    }
+                    projected.push(new_row);
+                }
+                return Ok(projected);
+            }
+
+            // `RETURN n.a, n.b, n.c` evaluates (and clones) `n` once per row
+            let (bases, base_of) = property_bases(items);
             let mut projected = Vec::new();
+            let mut memo: Vec<Option<Value>> = vec![None; bases.len()];
             for row in &rows {
+                memo.fill(None);
                 let mut new_row = HashMap::new();
-                for (expr, alias) in items {
-                    let val = eval_expr(expr, row, &ctx.params)?;
+                for ((expr, alias), base) in items.iter().zip(&base_of) {
+                    let val = match (expr, base) {
+                        (Expr::Property { key, .. }, Some(i)) => {
+                            let value = match &memo[*i] {
+                                Some(value) => value,
+                                None => memo[*i].insert(eval_expr(bases[*i], row, &ctx.params)?),
+                            };
+                            property_of(value, key)?
+                        }
+                        _ => eval_expr(expr, row, &ctx.params)?,
+                    };
                     new_row.insert(alias.clone(), val);
                 }
                 projected.push(new_row);
@@ -323,28 +799,48 @@ fn execute_plan<'a, B: StorageBackend>(
             Ok(projected)
         }
 
-        LogicalPlan::CreateNode { labels, properties, alias } => {
+        LogicalPlan::CreateNode { input, labels, properties, alias } => {
+            // One node per input row (MATCH / LOAD CSV pipeline), or a single
+            // node for a standalone CREATE
+            let input_rows = if let Some(input_plan) = input {
+                execute_plan(backend, tx, input_plan, ctx).await?
+            } else {
+                vec![HashMap::new()]
+            };
+
             let label_refs: Vec<&str> = labels.iter().map(|s| s.as_str()).collect();
-            let empty_row = HashMap::new();
-            let mut props = PropertyMap::new();
-            for (key, expr) in properties {
-                let val = eval_expr(expr, &empty_row, &ctx.params)?;
-                props.insert(key.clone(), val);
-            }
-            let node_id = backend.create_node(tx, &label_refs, props).await?;
-            ctx.stats.nodes_created += 1;
-
-            let node = backend.get_node(tx, node_id).await?
-                .ok_or_else(|| Error::ExecutionError("Created node not found".into()))?;
-            let mut row = HashMap::new();
-            row.insert(alias.clone(), Value::Node(Box::new(node)));
+            let mut result_rows = Vec::with_capacity(input_rows.len());
+            for mut row in input_rows {
+                let mut props = PropertyMap::new();
+                for (key, expr) in properties {
+                    let val = eval_expr(expr, &row, &ctx.params)?;
+                    props.insert(key.clone(), val);
+                }
+                let node_id = backend.create_node(tx, &label_refs, props).await?;
+                ctx.stats.nodes_created += 1;
+
+                let node = backend.get_node(tx, node_id).await?
+                    .ok_or_else(|| Error::ExecutionError("Created node not found".into()))?;
+                row.insert(alias.clone(), Value::Node(Box::new(node)));
+                result_rows.push(row);
+            }
             if !ctx.columns.contains(alias) {
                 ctx.columns.push(alias.clone());
             }
-            Ok(vec![row])
+            Ok(result_rows)
         }
 
-        LogicalPlan::CreateRel { input, src, dst, rel_type, properties } => {
+        LogicalPlan::LoadCsv { .. } => {
+            // A batch handed down by `execute_csv_batches`, or, under an
+            // operator that needs every row (ORDER BY, aggregation, ...),
+            // the whole file in memory
+            if let Some(batch) = ctx.csv_batch.take() {
+                return Ok(batch);
+            }
+            open_csv(plan, ctx).await?.next_batch(usize::MAX).await
+        }
+
+        LogicalPlan::CreateRel { input, src, dst, rel_type, properties, alias } => {
             // Get input rows (from MATCH pipeline or preceding CreateNode)
             let input_rows = if let Some(input_plan) = input {
                 execute_plan(backend, tx, input_plan, ctx).await?
@@ -353,26 +849,36 @@ fn execute_plan<'a, B: StorageBackend>(
             };
 
             let mut result_rows = Vec::new();
-            for row in &input_rows {
+            for mut row in input_rows {
                 let mut props = PropertyMap::new();
                 for (key, expr) in properties {
-                    let val = eval_expr(expr, row, &ctx.params)?;
+                    let val = eval_expr(expr, &row, &ctx.params)?;
                     props.insert(key.clone(), val);
                 }
 
                 // Resolve src/dst from row bindings (Value::Node) or params fallback
-                let src_id = resolve_node_id(src, row, &ctx.params)
+                let src_id = resolve_node_id(src, &row, &ctx.params)
                     .ok_or_else(|| Error::ExecutionError(
                         format!("Cannot resolve source node '{}'. Ensure it is bound by MATCH or CREATE.", src)
                     ))?;
-                let dst_id = resolve_node_id(dst, row, &ctx.params)
+                let dst_id = resolve_node_id(dst, &row, &ctx.params)
                     .ok_or_else(|| Error::ExecutionError(
                         format!("Cannot resolve target node '{}'. Ensure it is bound by MATCH or CREATE.", dst)
                     ))?;
 
-                backend.create_relationship(tx, src_id, dst_id, rel_type, props).await?;
+                let rel_id = backend.create_relationship(tx, src_id, dst_id, rel_type, props).await?;
                 ctx.stats.relationships_created += 1;
-                result_rows.push(row.clone());
+                if let Some(alias) = alias {
+                    let rel = backend.get_relationship(tx, rel_id).await?
+                        .ok_or_else(|| Error::ExecutionError("Created relationship not found".into()))?;
+                    row.insert(alias.clone(), Value::Relationship(Box::new(rel)));
+                }
+                result_rows.push(row);
+            }
+            if let Some(alias) = alias
+                && !ctx.columns.contains(alias)
+            {
+                ctx.columns.push(alias.clone());
             }
 
             Ok(result_rows)
@@ -383,19 +889,26 @@ fn execute_plan<'a, B: StorageBackend>(
             Ok(rows.into_iter().take(*count).collect())
         }
 
+        LogicalPlan::LimitPercent { input, fraction } => {
+            let rows = execute_plan(backend, tx, input, ctx).await?;
+            // Shave off float error, so 10% of 30 rows is 3 rather than 4
+            let wanted = (rows.len() as f64 * fraction - 1e-9).ceil().max(0.0) as usize;
+            let count = if *fraction > 0.0 { wanted.max(1) } else { 0 };
+            Ok(rows.into_iter().take(count).collect())
+        }
+
         LogicalPlan::Sort { input, keys } => {
             let mut rows = execute_plan(backend, tx, input, ctx).await?;
-            let params = ctx.params.clone();
+            let params = &ctx.params;
             // Sort is best-effort — errors during eval are treated as NULL
             rows.sort_by(|a, b| {
                 for (expr, ascending) in keys {
-                    let va = eval_expr(expr, a, &params).unwrap_or(Value::Null);
-                    let vb = eval_expr(expr, b, &params).unwrap_or(Value::Null);
-                    if let Some(ord) = va.neo4j_cmp(&vb) {
-                        let ord = if *ascending { ord } else { ord.reverse() };
-                        if ord != std::cmp::Ordering::Equal {
-                            return ord;
-                        }
+                    let va = eval_expr(expr, a, params).unwrap_or(Value::Null);
+                    let vb = eval_expr(expr, b, params).unwrap_or(Value::Null);
+                    let ord = va.order_cmp_with(&vb, params.collation);
+                    let ord = if *ascending { ord } else { ord.reverse() };
+                    if ord != std::cmp::Ordering::Equal {
+                        return ord;
                     }
                 }
                 std::cmp::Ordering::Equal
@@ -406,6 +919,8 @@ fn execute_plan<'a, B: StorageBackend>(
         LogicalPlan::CartesianProduct { left, right } => {
             let left_rows = execute_plan(backend, tx, left, ctx).await?;
             let right_rows = execute_plan(backend, tx, right, ctx).await?;
+            // Refuse before building a product that would break the cap
+            ctx.limits.check_intermediate(left_rows.len().saturating_mul(right_rows.len()))?;
             let mut result = Vec::new();
             for lr in &left_rows {
                 for rr in &right_rows {
@@ -417,20 +932,87 @@ fn execute_plan<'a, B: StorageBackend>(
             Ok(result)
         }
 
+        LogicalPlan::HashJoin { left, right, left_key, right_key } => {
+            let left_rows = execute_plan(backend, tx, left, ctx).await?;
+            let right_rows = execute_plan(backend, tx, right, ctx).await?;
+
+            // Build on the smaller side, probe with the other
+            let build_left = left_rows.len() <= right_rows.len();
+            let (build, build_key, probe, probe_key) = if build_left {
+                (&left_rows, left_key, &right_rows, right_key)
+            } else {
+                (&right_rows, right_key, &left_rows, left_key)
+            };
+
+            let mut table: HashMap<u64, Vec<(Value, &Row)>> = HashMap::new();
+            for row in build {
+                let key = eval_expr(build_key, row, &ctx.params)?;
+                // NULL never compares equal, so it can't join
+                if !key.is_null() {
+                    table.entry(join_hash(&key)).or_default().push((key, row));
+                }
+            }
+
+            let mut result = Vec::new();
+            for probe_row in probe {
+                let key = eval_expr(probe_key, probe_row, &ctx.params)?;
+                if key.is_null() {
+                    continue;
+                }
+                let Some(bucket) = table.get(&join_hash(&key)) else { continue };
+                for (build_value, build_row) in bucket {
+                    // Same equality as the `=` operator the join replaced
+                    if build_value.neo4j_eq(&key) != Some(true) {
+                        continue;
+                    }
+                    let (lr, rr) = if build_left { (*build_row, probe_row) } else { (probe_row, *build_row) };
+                    let mut row = lr.clone();
+                    row.extend(rr.clone());
+                    result.push(row);
+                    ctx.limits.check_intermediate(result.len())?;
+                }
+            }
+            Ok(result)
+        }
+
         LogicalPlan::CallProcedure { name, args, yields } => {
             let empty_row = HashMap::new();
-            let arg_vals: Vec<Value> = args.iter()
+            let mut arg_vals: Vec<Value> = args.iter()
                 .map(|a| eval_expr(a, &empty_row, &ctx.params))
                 .collect::<Result<_>>()?;
-            let proc_result = backend.call_procedure(tx, name, arg_vals).await?;
+            // Vector queries take raw bytes; accept a list of floats as well
+            if name.eq_ignore_ascii_case("db.index.vector.queryNodes")
+                && let Some(v) = arg_vals.get_mut(2)
+            {
+                *v = Value::Bytes(vector_bytes(v)?);
+            }
+            let proc_result = if name.eq_ignore_ascii_case("db.index.vector.queryNodes")
+                && backend.capabilities().supports_vector_index
+            {
+                vector_query_nodes(backend, tx, &arg_vals).await?
+            } else if name.eq_ignore_ascii_case("apoc.path.expand") {
+                path_expand::path_expand(backend, tx, &arg_vals, ctx.limits).await?
+            } else if name.eq_ignore_ascii_case("apoc.do.when") {
+                do_when(backend, tx, &arg_vals, ctx).await?
+            } else if name.eq_ignore_ascii_case("apoc.periodic.iterate") {
+                periodic::iterate(backend, tx, &arg_vals, ctx).await?
+            } else if name.eq_ignore_ascii_case("apoc.create.node") {
+                create_node(backend, tx, &arg_vals, ctx).await?
+            } else if name.eq_ignore_ascii_case("apoc.refactor.mergeNodes") {
+                refactor::merge_nodes(backend, tx, &arg_vals, &mut ctx.stats).await?
+            } else {
+                backend.call_procedure(tx, name, arg_vals).await?
+            };
 
+            // Without YIELD, every column the procedure returned is kept
+            let yields = if yields.is_empty() { &proc_result.columns } else { yields };
             for col in yields {
                 if !ctx.columns.contains(col) {
                     ctx.columns.push(col.clone());
                 }
             }
 
-            let rows: Vec<Row> = proc_result.rows.into_iter().map(|pr| {
+            let rows: Vec<Row> = proc_result.rows.iter().map(|pr| {
                 let mut row = HashMap::new();
                 for col in yields {
                     if let Some(val) = pr.get(col) {
@@ -443,8 +1025,26 @@ fn execute_plan<'a, B: StorageBackend>(
         }
 
         LogicalPlan::Aggregate { input, group_by, aggregations } => {
-            let rows = execute_plan(backend, tx, input, ctx).await?;
-            let result = aggregate_rows(&rows, group_by, aggregations, &ctx.params)?;
+            let result = match aggregate::Groups::new(group_by, aggregations) {
+                // Fold rows in as they arrive, a batch at a time over LOAD CSV
+                Some(mut groups) => {
+                    match streamed_load_csv(input) {
+                        Some(load) if !ctx.in_csv_batch => {
+                            execute_csv_batches(backend, tx, input, load, ctx, |rows, ctx| groups.add(rows, &ctx.params))
+                                .await?;
+                        }
+                        _ => {
+                            let rows = execute_plan(backend, tx, input, ctx).await?;
+                            groups.add(rows, &ctx.params)?;
+                        }
+                    }
+                    groups.finish()
+                }
+                None => {
+                    let rows = execute_plan(backend, tx, input, ctx).await?;
+                    aggregate_rows(&rows, group_by, aggregations, &ctx.params)?
+                }
+            };
 
             ctx.columns.clear();
             for (_, alias) in group_by {
@@ -456,17 +1056,22 @@ fn execute_plan<'a, B: StorageBackend>(
             Ok(result)
         }
 
+        LogicalPlan::RelationshipCountFromCountStore { rel_type, column } => {
+            let count = backend.relationship_count_by_type(tx, rel_type).await?;
+            ctx.columns.clear();
+            ctx.columns.push(column.clone());
+            Ok(vec![HashMap::from([(column.clone(), Value::Int(count as i64))])])
+        }
+
         LogicalPlan::Distinct { input } => {
             let rows = execute_plan(backend, tx, input, ctx).await?;
-            let mut seen = Vec::new();
-            let mut result = Vec::new();
+            let mut result: Vec<Row> = Vec::new();
             for row in rows {
-                // Serialize the row values for dedup — simple but works
-                let key: Vec<(String, String)> = row.iter()
-                    .map(|(k, v)| (k.clone(), format!("{v}")))
-                    .collect();
-                if !seen.contains(&key) {
-                    seen.push(key);
+                let duplicate = result.iter().any(|seen| {
+                    seen.len() == row.len()
+                        && seen.iter().all(|(k, v)| row.get(k).is_some_and(|w| v.equivalent(w)))
+                });
+                if !duplicate {
                     result.push(row);
                 }
             }
@@ -483,16 +1088,64 @@ fn execute_plan<'a, B: StorageBackend>(
             for row in &rows {
                 let val = eval_expr(value, row, &ctx.params)?;
                 if let Some(Value::Node(n)) = row.get(variable) {
-                    backend.set_node_property(tx, n.id, key, val).await?;
+                    set_node_property(backend, tx, n.id, key, val).await?;
                     ctx.stats.properties_set += 1;
                 } else if let Some(Value::Relationship(r)) = row.get(variable) {
-                    backend.set_relationship_property(tx, r.id, key, val).await?;
+                    // As for nodes, null removes the property
+                    if val.is_null() {
+                        backend.remove_relationship_property(tx, r.id, key).await?;
+                    } else {
+                        backend.set_relationship_property(tx, r.id, key, val).await?;
+                    }
                     ctx.stats.properties_set += 1;
                 }
             }
             Ok(rows)
         }
 
+        LogicalPlan::SetProperties { input, variable, value, replace } => {
+            let rows = execute_plan(backend, tx, input, ctx).await?;
+            for row in &rows {
+                let mut props = match eval_expr(value, row, &ctx.params)? {
+                    Value::Map(m) => m,
+                    Value::Node(n) => n.properties,
+                    Value::Relationship(r) => r.properties,
+                    other => {
+                        return Err(Error::TypeError { expected: "Map".into(), got: other.type_name().into(), span: None });
+                    }
+                };
+                match row.get(variable) {
+                    Some(Value::Node(n)) => {
+                        // Earlier SET items may have added keys the row's copy lacks
+                        if *replace && let Some(current) = backend.get_node(tx, n.id).await? {
+                            for key in current.properties.into_keys() {
+                                props.entry(key).or_insert(Value::Null);
+                            }
+                        }
+                        ctx.stats.properties_set += props.len() as u64;
+                        backend.set_node_properties(tx, n.id, props).await?;
+                    }
+                    Some(Value::Relationship(r)) => {
+                        if *replace && let Some(current) = backend.get_relationship(tx, r.id).await? {
+                            for key in current.properties.into_keys() {
+                                props.entry(key).or_insert(Value::Null);
+                            }
+                        }
+                        ctx.stats.properties_set += props.len() as u64;
+                        for (key, val) in props {
+                            if val.is_null() {
+                                backend.remove_relationship_property(tx, r.id, &key).await?;
+                            } else {
+                                backend.set_relationship_property(tx, r.id, &key, val).await?;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(rows)
+        }
+
         LogicalPlan::DeleteNode { input, variable, detach } => {
             let rows = execute_plan(backend, tx, input, ctx).await?;
             for row in &rows {
@@ -500,6 +1153,10 @@ fn execute_plan<'a, B: StorageBackend>(
                     if *detach {
                         backend.detach_delete_node(tx, n.id).await?;
                     } else {
+                        // Checked here so every backend reports it the same way
+                        if !backend.get_relationships(tx, n.id, Direction::Both, None).await?.is_empty() {
+                            return Err(crate::storage::node_still_has_relationships(n.id));
+                        }
                         backend.delete_node(tx, n.id).await?;
                     }
                     ctx.stats.nodes_deleted += 1;
@@ -529,7 +1186,10 @@ fn execute_plan<'a, B: StorageBackend>(
                         let mut new_row = row.clone();
                         new_row.insert(alias.clone(), item);
                         result.push(new_row);
+                        ctx.limits.check_intermediate(result.len())?;
                     }
+                } else if val.is_null() {
+                    // UNWIND null produces no rows, like an empty list
                 } else {
                     // UNWIND on non-list: single row
                     let mut new_row = row.clone();
@@ -546,10 +1206,10 @@ fn execute_plan<'a, B: StorageBackend>(
             let rows = execute_plan(backend, tx, input, ctx).await?;
             for row in &rows {
                 if let Some(Value::Node(n)) = row.get(variable) {
-                    backend.set_node_property(tx, n.id, key, Value::Null).await?;
+                    backend.remove_node_property(tx, n.id, key).await?;
                     ctx.stats.properties_set += 1;
                 } else if let Some(Value::Relationship(r)) = row.get(variable) {
-                    backend.set_relationship_property(tx, r.id, key, Value::Null).await?;
+                    backend.remove_relationship_property(tx, r.id, key).await?;
                     ctx.stats.properties_set += 1;
                 }
             }
@@ -600,7 +1260,7 @@ fn execute_plan<'a, B: StorageBackend>(
                     for (var, key, expr) in on_match {
                         if var == alias {
                             let val = eval_expr(expr, row, &ctx.params)?;
-                            backend.set_node_property(tx, existing_node.id, key, val).await?;
+                            set_node_property(backend, tx, existing_node.id, key, val).await?;
                             ctx.stats.properties_set += 1;
                         }
                     }
@@ -616,7 +1276,7 @@ fn execute_plan<'a, B: StorageBackend>(
                     for (var, key, expr) in on_create {
                         if var == alias {
                             let val = eval_expr(expr, row, &ctx.params)?;
-                            backend.set_node_property(tx, id, key, val).await?;
+                            set_node_property(backend, tx, id, key, val).await?;
                             ctx.stats.properties_set += 1;
                         }
                     }
@@ -643,18 +1303,15 @@ fn execute_plan<'a, B: StorageBackend>(
         LogicalPlan::SchemaOp(cmd) => {
             // Execute schema operations via the storage backend
             match cmd {
-                crate::cypher::ast::SchemaCommand::CreateIndex { label, property, index_type: _ } => {
-                    backend.create_index(label, property, crate::index::IndexType::BTree).await?;
+                crate::cypher::ast::SchemaCommand::CreateIndex { label, property, index_type } => {
+                    let it = crate::schema::index_type_of(index_type.as_deref());
+                    backend.create_index(label, property, it).await?;
                 }
                 crate::cypher::ast::SchemaCommand::DropIndex { label, property } => {
                     backend.drop_index(label, property).await?;
                 }
                 crate::cypher::ast::SchemaCommand::CreateConstraint { label, property, constraint_type } => {
-                    let ct = if constraint_type.contains("UNIQUE") {
-                        crate::storage::ConstraintType::Unique
-                    } else {
-                        crate::storage::ConstraintType::Exists
-                    };
+                    let ct = crate::schema::constraint_type_of(constraint_type);
                     backend.create_constraint(label, property, ct).await?;
                 }
                 crate::cypher::ast::SchemaCommand::DropConstraint { label, property } => {
@@ -688,7 +1345,7 @@ fn resolve_node_id(alias: &str, row: &Row, params: &PropertyMap) -> Option<NodeI
 // ============================================================================
 
 /// Evaluate a Cypher expression against a row of bound variables.
-fn eval_expr(expr: &Expr, row: &Row, params: &PropertyMap) -> Result<Value> {
+fn eval_expr(expr: &Expr, row: &Row, params: &EvalParams) -> Result<Value> {
     match expr {
         Expr::Literal(lit) => Ok(match lit {
             Literal::Null => Value::Null,
@@ -698,54 +1355,64 @@ fn eval_expr(expr: &Expr, row: &Row, params: &PropertyMap) -> Result<Value> {
             Literal::String(s) => Value::String(s.clone()),
         }),
 
-        Expr::Variable(name) => {
+        Expr::Variable { name, span } => {
             row.get(name).cloned().ok_or_else(|| {
-                Error::SemanticError(format!("Unbound variable: {name}"))
+                Error::semantic(format!("Unbound variable: {name}")).or_span(*span)
             })
         }
 
-        Expr::Parameter(name) => {
+        Expr::Parameter { name, span } => {
             params.get(name).cloned().ok_or_else(|| {
-                Error::SemanticError(format!("Missing parameter: ${name}"))
+                Error::semantic(format!("Missing parameter: ${name}")).or_span(*span)
             })
         }
 
         Expr::Property { expr: inner, key } => {
-            let val = eval_expr(inner, row, params)?;
-            match val {
-                Value::Node(n) => Ok(n.get(key).cloned().unwrap_or(Value::Null)),
-                Value::Relationship(r) => Ok(r.properties.get(key).cloned().unwrap_or(Value::Null)),
-                Value::Map(m) => Ok(m.get(key).cloned().unwrap_or(Value::Null)),
-                Value::Null => Ok(Value::Null),
-                _ => Err(Error::TypeError {
-                    expected: "Node, Relationship, or Map".into(),
-                    got: val.type_name().into(),
-                }),
+            // `n.a.b.c` reads from the base out, without recursing per key
+            let mut keys = vec![key];
+            let mut base = inner.as_ref();
+            while let Expr::Property { expr, key } = base {
+                keys.push(key);
+                base = expr;
             }
+            keys.into_iter().rev().try_fold(eval_expr(base, row, params)?, |value, key| property_of(&value, key))
         }
 
-        Expr::FunctionCall { name, args, distinct: _ } => {
-            eval_function(name, args, row, params)
+        Expr::FunctionCall { name, args, span, .. } => {
+            eval_function(name, args, row, params).map_err(|e| e.or_span(*span))
         }
 
         Expr::BinaryOp { left, op, right } => {
-            let lv = eval_expr(left, row, params)?;
             // Short-circuit for AND/OR
             match op {
                 BinaryOp::And => {
-                    if !lv.is_truthy() { return Ok(Value::Bool(false)); }
+                    if !eval_expr(left, row, params)?.is_truthy() { return Ok(Value::Bool(false)); }
                     let rv = eval_expr(right, row, params)?;
                     return Ok(Value::Bool(rv.is_truthy()));
                 }
                 BinaryOp::Or => {
-                    if lv.is_truthy() { return Ok(Value::Bool(true)); }
+                    if eval_expr(left, row, params)?.is_truthy() { return Ok(Value::Bool(true)); }
                     let rv = eval_expr(right, row, params)?;
                     return Ok(Value::Bool(rv.is_truthy()));
                 }
                 _ => {}
             }
-            let rv = eval_expr(right, row, params)?;
-            eval_binary_op(&lv, *op, &rv)
+            // `a + b - c` nests to the left: fold it from its first operand
+            // rather than recursing once per operator
+            let mut chain = vec![(*op, right.as_ref())];
+            let mut first = left.as_ref();
+            while let Expr::BinaryOp { left, op, right } = first
+                && !matches!(op, BinaryOp::And | BinaryOp::Or)
+            {
+                chain.push((*op, right));
+                first = left;
+            }
+            let mut value = eval_expr(first, row, params)?;
+            for (op, right) in chain.into_iter().rev() {
+                let rv = eval_expr(right, row, params)?;
+                value = eval_binary_op(&value, op, &rv, params.collation)?;
+            }
+            Ok(value)
         }
 
         Expr::UnaryOp { op, expr: inner } => {
@@ -757,12 +1424,13 @@ fn eval_expr(expr: &Expr, row: &Row, params: &PropertyMap) -> Result<Value> {
                     _ => Ok(Value::Bool(!val.is_truthy())),
                 },
                 UnaryOp::Negate => match val {
-                    Value::Int(i) => Ok(Value::Int(-i)),
+                    Value::Int(i) => i.checked_neg().map(Value::Int).ok_or_else(integer_overflow),
                     Value::Float(f) => Ok(Value::Float(-f)),
                     Value::Null => Ok(Value::Null),
                     _ => Err(Error::TypeError {
                         expected: "Numeric".into(),
                         got: val.type_name().into(),
+                        span: None,
                     }),
                 },
             }
@@ -804,6 +1472,7 @@ fn eval_expr(expr: &Expr, row: &Row, params: &PropertyMap) -> Result<Value> {
                 _ => Err(Error::TypeError {
                     expected: "List".into(),
                     got: list_val.type_name().into(),
+                    span: None,
                 }),
             }
         }
@@ -816,6 +1485,7 @@ fn eval_expr(expr: &Expr, row: &Row, params: &PropertyMap) -> Result<Value> {
                 _ => Err(Error::TypeError {
                     expected: "Node".into(),
                     got: val.type_name().into(),
+                    span: None,
                 }),
             }
         }
@@ -836,13 +1506,13 @@ fn eval_expr(expr: &Expr, row: &Row, params: &PropertyMap) -> Result<Value> {
                 _ => Err(Error::TypeError {
                     expected: "String".into(),
                     got: format!("{}, {}", lv.type_name(), rv.type_name()),
+                    span: None,
                 }),
             }
         }
 
         Expr::Star => {
-            // In RETURN *, return all bound variables
-            // This is handled at the Project level
+            // The planner expands RETURN * into the variables in scope
             Ok(Value::Null)
         }
 
@@ -852,7 +1522,8 @@ fn eval_expr(expr: &Expr, row: &Row, params: &PropertyMap) -> Result<Value> {
                 let op_val = eval_expr(op, row, params)?;
                 for (when_expr, then_expr) in whens {
                     let when_val = eval_expr(when_expr, row, params)?;
-                    if op_val == when_val {
+                    // Same semantics as `=`: numeric coercion, NULL never matches
+                    if op_val.neo4j_eq(&when_val) == Some(true) {
                         return eval_expr(then_expr, row, params);
                     }
                 }
@@ -872,18 +1543,91 @@ fn eval_expr(expr: &Expr, row: &Row, params: &PropertyMap) -> Result<Value> {
             }
         }
 
-        Expr::Exists(_) => {
-            // EXISTS subqueries need the full backend — simplify for now
-            Err(Error::ExecutionError("EXISTS subquery not yet supported in execution".into()))
+        Expr::Exists { .. } | Expr::CountSubquery { .. } => {
+            // Only Filter and Project bind subquery results (see `bind_subqueries`)
+            Err(Error::ExecutionError("EXISTS and COUNT subqueries are only supported in WHERE and RETURN".into()))
+        }
+    }
+}
+
+/// `val.key` for a node, relationship or map; null stays null.
+fn property_of(val: &Value, key: &str) -> Result<Value> {
+    match val {
+        Value::Node(n) => Ok(n.get(key).cloned().unwrap_or(Value::Null)),
+        Value::Relationship(r) => Ok(r.properties.get(key).cloned().unwrap_or(Value::Null)),
+        Value::Map(m) => Ok(m.get(key).cloned().unwrap_or(Value::Null)),
+        Value::Null => Ok(Value::Null),
+        _ => Err(Error::TypeError {
+            expected: "Node, Relationship, or Map".into(),
+            got: val.type_name().into(),
+            span: None,
+        }),
+    }
+}
+
+/// The distinct bases of the property reads among `items` (`n` for `n.a`
+/// and `n.b`, `n.address` for `n.address.city`), matched by structural id
+/// and then compared path by path, so a hash collision can't alias two
+/// bases. For each item, the index of its base, if it is a property read.
+/// Only variable and property paths count as bases: anything else, like
+/// `{r: rand()}.r`, is evaluated per read as before.
+fn property_bases(items: &[(Expr, String)]) -> (Vec<&Expr>, Vec<Option<usize>>) {
+    fn is_path(e: &Expr) -> bool {
+        match e {
+            Expr::Variable { .. } | Expr::Parameter { .. } => true,
+            Expr::Property { expr, .. } => is_path(expr),
+            _ => false,
         }
     }
+    /// Whether two paths name the same value, ignoring spans.
+    fn same_path(a: &Expr, b: &Expr) -> bool {
+        match (a, b) {
+            (Expr::Variable { name: a, .. }, Expr::Variable { name: b, .. }) => a == b,
+            (Expr::Parameter { name: a, .. }, Expr::Parameter { name: b, .. }) => a == b,
+            (Expr::Property { expr: a, key: ka }, Expr::Property { expr: b, key: kb }) => {
+                ka == kb && same_path(a, b)
+            }
+            _ => false,
+        }
+    }
+    let mut bases: Vec<&Expr> = Vec::new();
+    let mut ids: Vec<u64> = Vec::new();
+    let base_of = items
+        .iter()
+        .map(|(expr, _)| {
+            let Expr::Property { expr: base, .. } = expr else { return None };
+            if !is_path(base) {
+                return None;
+            }
+            let id = crate::planner::expr_hash(base);
+            let seen = ids.iter().zip(&bases).position(|(&i, b)| i == id && same_path(b, base));
+            Some(seen.unwrap_or_else(|| {
+                ids.push(id);
+                bases.push(base);
+                bases.len() - 1
+            }))
+        })
+        .collect();
+    (bases, base_of)
 }
 
 // ============================================================================
 // Binary operator evaluation
 // ============================================================================
 
-fn eval_binary_op(left: &Value, op: BinaryOp, right: &Value) -> Result<Value> {
+/// `left` and `right` compared by an ordering operator, true when `holds`
+/// accepts their order. NaN orders against no number, which is false; any
+/// other pair without an order is null, as it is for `=`.
+fn eval_ordering(left: &Value, right: &Value, collation: Collation, holds: fn(std::cmp::Ordering) -> bool) -> Value {
+    let numeric = |v: &Value| matches!(v, Value::Int(_) | Value::Float(_));
+    match left.neo4j_cmp_with(right, collation) {
+        Some(order) => Value::Bool(holds(order)),
+        None if numeric(left) && numeric(right) => Value::Bool(false),
+        None => Value::Null,
+    }
+}
+
+fn eval_binary_op(left: &Value, op: BinaryOp, right: &Value, collation: Collation) -> Result<Value> {
     // NULL propagation for most operators
     if left.is_null() || right.is_null() {
         return match op {
@@ -894,32 +1638,38 @@ fn eval_binary_op(left: &Value, op: BinaryOp, right: &Value) -> Result<Value> {
 
     match op {
         // Comparison
-        BinaryOp::Eq => Ok(Value::Bool(left == right)),
-        BinaryOp::Neq => Ok(Value::Bool(left != right)),
-        BinaryOp::Lt => Ok(Value::Bool(left.neo4j_cmp(right) == Some(std::cmp::Ordering::Less))),
-        BinaryOp::Lte => Ok(Value::Bool(matches!(left.neo4j_cmp(right), Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)))),
-        BinaryOp::Gt => Ok(Value::Bool(left.neo4j_cmp(right) == Some(std::cmp::Ordering::Greater))),
-        BinaryOp::Gte => Ok(Value::Bool(matches!(left.neo4j_cmp(right), Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)))),
+        BinaryOp::Eq => Ok(left.neo4j_eq(right).map_or(Value::Null, Value::Bool)),
+        BinaryOp::Neq => Ok(left.neo4j_eq(right).map_or(Value::Null, |eq| Value::Bool(!eq))),
+        BinaryOp::Lt => Ok(eval_ordering(left, right, collation, std::cmp::Ordering::is_lt)),
+        BinaryOp::Lte => Ok(eval_ordering(left, right, collation, std::cmp::Ordering::is_le)),
+        BinaryOp::Gt => Ok(eval_ordering(left, right, collation, std::cmp::Ordering::is_gt)),
+        BinaryOp::Gte => Ok(eval_ordering(left, right, collation, std::cmp::Ordering::is_ge)),
 
         // Arithmetic
         BinaryOp::Add => eval_add(left, right),
-        BinaryOp::Sub => eval_arith(left, right, |a, b| a - b, |a, b| a - b),
-        BinaryOp::Mul => eval_arith(left, right, |a, b| a * b, |a, b| a * b),
+        BinaryOp::Sub => temporal::sub(left, right)
+            .unwrap_or_else(|| eval_arith(left, right, i64::checked_sub, |a, b| a - b)),
+        BinaryOp::Mul => eval_arith(left, right, i64::checked_mul, |a, b| a * b),
         BinaryOp::Div => {
             // Division by zero check
             match right {
                 Value::Int(0) => Err(Error::ExecutionError("Division by zero".into())),
                 Value::Float(f) if *f == 0.0 => Err(Error::ExecutionError("Division by zero".into())),
-                _ => eval_arith(left, right, |a, b| a / b, |a, b| a / b),
+                _ => eval_arith(left, right, i64::checked_div, |a, b| a / b),
             }
         }
-        BinaryOp::Mod => eval_arith(left, right, |a, b| a % b, |a, b| a % b),
+        BinaryOp::Mod => match (left, right) {
+            (Value::Int(_), Value::Int(0)) => Err(Error::ExecutionError("Division by zero".into())),
+            _ => eval_arith(left, right, i64::checked_rem, |a, b| a % b),
+        },
         BinaryOp::Pow => {
             let l = left.as_float().ok_or_else(|| Error::TypeError {
                 expected: "Numeric".into(), got: left.type_name().into(),
+                span: None,
             })?;
             let r = right.as_float().ok_or_else(|| Error::TypeError {
                 expected: "Numeric".into(), got: right.type_name().into(),
+                span: None,
             })?;
             Ok(Value::Float(l.powf(r)))
         }
@@ -939,6 +1689,7 @@ fn eval_binary_op(left: &Value, op: BinaryOp, right: &Value) -> Result<Value> {
                 _ => Err(Error::TypeError {
                     expected: "String".into(),
                     got: format!("{}, {}", left.type_name(), right.type_name()),
+                    span: None,
                 }),
             }
         }
@@ -946,8 +1697,11 @@ fn eval_binary_op(left: &Value, op: BinaryOp, right: &Value) -> Result<Value> {
 }
 
 fn eval_add(left: &Value, right: &Value) -> Result<Value> {
+    if let Some(result) = temporal::add(left, right) {
+        return result;
+    }
     match (left, right) {
-        (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a + b)),
+        (Value::Int(a), Value::Int(b)) => a.checked_add(*b).map(Value::Int).ok_or_else(integer_overflow),
         (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a + b)),
         (Value::Int(a), Value::Float(b)) => Ok(Value::Float(*a as f64 + b)),
         (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a + *b as f64)),
@@ -960,6 +1714,7 @@ fn eval_add(left: &Value, right: &Value) -> Result<Value> {
         _ => Err(Error::TypeError {
             expected: "compatible types for +".into(),
             got: format!("{}, {}", left.type_name(), right.type_name()),
+            span: None,
         }),
     }
 }
@@ -967,26 +1722,32 @@ fn eval_add(left: &Value, right: &Value) -> Result<Value> {
 fn eval_arith(
     left: &Value,
     right: &Value,
-    int_op: fn(i64, i64) -> i64,
+    int_op: fn(i64, i64) -> Option<i64>,
     float_op: fn(f64, f64) -> f64,
 ) -> Result<Value> {
     match (left, right) {
-        (Value::Int(a), Value::Int(b)) => Ok(Value::Int(int_op(*a, *b))),
+        (Value::Int(a), Value::Int(b)) => int_op(*a, *b).map(Value::Int).ok_or_else(integer_overflow),
         (Value::Float(a), Value::Float(b)) => Ok(Value::Float(float_op(*a, *b))),
         (Value::Int(a), Value::Float(b)) => Ok(Value::Float(float_op(*a as f64, *b))),
         (Value::Float(a), Value::Int(b)) => Ok(Value::Float(float_op(*a, *b as f64))),
         _ => Err(Error::TypeError {
             expected: "Numeric".into(),
             got: format!("{}, {}", left.type_name(), right.type_name()),
+            span: None,
         }),
     }
 }
 
+/// Integer results outside `i64` are an error, as in Neo4j, not a wrap.
+fn integer_overflow() -> Error {
+    Error::ExecutionError("integer overflow".into())
+}
+
 // ============================================================================
 // Built-in function evaluation
 // ============================================================================
 
-fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) -> Result<Value> {
+fn eval_function(name: &str, args: &[Expr], row: &Row, params: &EvalParams) -> Result<Value> {
     let upper = name.to_uppercase();
     match upper.as_str() {
         "ID" => {
@@ -994,21 +1755,40 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
             match val {
                 Value::Node(n) => Ok(Value::Int(n.id.0 as i64)),
                 Value::Relationship(r) => Ok(Value::Int(r.id.0 as i64)),
-                _ => Err(Error::TypeError { expected: "Node or Relationship".into(), got: val.type_name().into() }),
+                Value::Null => Ok(Value::Null),
+                _ => Err(Error::TypeError { expected: "Node or Relationship".into(), got: val.type_name().into(), span: None }),
             }
         }
+        // The backend's element id where it keeps one, else the numeric id
+        "ELEMENTID" => {
+            let [arg] = args else {
+                return Err(Error::ExecutionError("elementId() requires 1 argument".into()));
+            };
+            let val = eval_expr(arg, row, params)?;
+            let (element_id, id) = match &val {
+                Value::Node(n) => (&n.element_id, n.id.0),
+                Value::Relationship(r) => (&r.element_id, r.id.0),
+                Value::Null => return Ok(Value::Null),
+                _ => {
+                    return Err(Error::TypeError { expected: "Node or Relationship".into(), got: val.type_name().into(), span: None });
+                }
+            };
+            Ok(Value::String(element_id.clone().unwrap_or_else(|| id.to_string())))
+        }
         "LABELS" => {
             let val = eval_expr(&args[0], row, params)?;
             match val {
                 Value::Node(n) => Ok(Value::List(n.labels.iter().map(|l| Value::String(l.clone())).collect())),
-                _ => Err(Error::TypeError { expected: "Node".into(), got: val.type_name().into() }),
+                Value::Null => Ok(Value::Null),
+                _ => Err(Error::TypeError { expected: "Node".into(), got: val.type_name().into(), span: None }),
             }
         }
         "TYPE" => {
             let val = eval_expr(&args[0], row, params)?;
             match val {
                 Value::Relationship(r) => Ok(Value::String(r.rel_type.clone())),
-                _ => Err(Error::TypeError { expected: "Relationship".into(), got: val.type_name().into() }),
+                Value::Null => Ok(Value::Null),
+                _ => Err(Error::TypeError { expected: "Relationship".into(), got: val.type_name().into(), span: None }),
             }
         }
         "PROPERTIES" => {
@@ -1016,7 +1796,7 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
             match val {
                 Value::Node(n) => Ok(Value::Map(n.properties.clone())),
                 Value::Relationship(r) => Ok(Value::Map(r.properties.clone())),
-                _ => Err(Error::TypeError { expected: "Node or Relationship".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "Node or Relationship".into(), got: val.type_name().into(), span: None }),
             }
         }
         "KEYS" => {
@@ -1025,17 +1805,19 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
                 Value::Node(n) => Ok(Value::List(n.properties.keys().map(|k| Value::String(k.clone())).collect())),
                 Value::Relationship(r) => Ok(Value::List(r.properties.keys().map(|k| Value::String(k.clone())).collect())),
                 Value::Map(m) => Ok(Value::List(m.keys().map(|k| Value::String(k.clone())).collect())),
-                _ => Err(Error::TypeError { expected: "Node, Relationship, or Map".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "Node, Relationship, or Map".into(), got: val.type_name().into(), span: None }),
             }
         }
         "TOINTEGER" | "TOINT" => {
             let val = eval_expr(&args[0], row, params)?;
             match val {
                 Value::Int(_) => Ok(val),
-                Value::Float(f) => Ok(Value::Int(f as i64)),
-                Value::String(s) => s.parse::<i64>().map(Value::Int).map_err(|_| Error::TypeError { expected: "parseable integer".into(), got: s }),
+                // Truncated toward zero; NaN, infinities and floats past
+                // the Integer range have no Integer to give
+                Value::Float(f) => truncate(f).map(Value::Int).ok_or_else(|| Error::TypeError { expected: "Float within Integer range".into(), got: f.to_string(), span: None }),
+                Value::String(s) => parse_integer(&s).map(Value::Int).ok_or_else(|| Error::TypeError { expected: "parseable integer".into(), got: s, span: None }),
                 Value::Null => Ok(Value::Null),
-                _ => Err(Error::TypeError { expected: "convertible to Integer".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "convertible to Integer".into(), got: val.type_name().into(), span: None }),
             }
         }
         "TOFLOAT" => {
@@ -1043,14 +1825,17 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
             match val {
                 Value::Float(_) => Ok(val),
                 Value::Int(i) => Ok(Value::Float(i as f64)),
-                Value::String(s) => s.parse::<f64>().map(Value::Float).map_err(|_| Error::TypeError { expected: "parseable float".into(), got: s }),
+                Value::String(s) => s.parse::<f64>().map(Value::Float).map_err(|_| Error::TypeError { expected: "parseable float".into(), got: s, span: None }),
                 Value::Null => Ok(Value::Null),
-                _ => Err(Error::TypeError { expected: "convertible to Float".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "convertible to Float".into(), got: val.type_name().into(), span: None }),
             }
         }
         "TOSTRING" => {
-            let val = eval_expr(&args[0], row, params)?;
-            Ok(Value::String(format!("{val}")))
+            match eval_expr(&args[0], row, params)? {
+                Value::Null => Ok(Value::Null),
+                Value::String(s) => Ok(Value::String(s)),
+                val => Ok(Value::String(format!("{val}"))),
+            }
         }
         "TOBOOLEAN" => {
             let val = eval_expr(&args[0], row, params)?;
@@ -1062,7 +1847,53 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
                     _ => Ok(Value::Null),
                 },
                 Value::Null => Ok(Value::Null),
-                _ => Err(Error::TypeError { expected: "convertible to Boolean".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "convertible to Boolean".into(), got: val.type_name().into(), span: None }),
+            }
+        }
+        // Lenient conversions (Neo4j 5): unconvertible input yields NULL instead of an error
+        "TOINTEGERORNULL" => Ok(integer_or_null(eval_expr(&args[0], row, params)?)),
+        "TOFLOATORNULL" => Ok(float_or_null(eval_expr(&args[0], row, params)?)),
+        "TOBOOLEANORNULL" => Ok(boolean_or_null(eval_expr(&args[0], row, params)?)),
+        // List coercions: each element goes through the lenient scalar conversion
+        "TOINTEGERLIST" | "TOFLOATLIST" | "TOSTRINGLIST" | "TOBOOLEANLIST" => {
+            let convert = match upper.as_str() {
+                "TOINTEGERLIST" => integer_or_null,
+                "TOFLOATLIST" => float_or_null,
+                "TOSTRINGLIST" => string_or_null,
+                _ => boolean_or_null,
+            };
+            match eval_expr(&args[0], row, params)? {
+                Value::List(items) => Ok(Value::List(items.into_iter().map(convert).collect())),
+                Value::Null => Ok(Value::Null),
+                other => Err(Error::TypeError { expected: "List".into(), got: other.type_name().into(), span: None }),
+            }
+        }
+        "REVERSE" => {
+            let val = eval_expr(&args[0], row, params)?;
+            match val {
+                Value::List(mut items) => {
+                    items.reverse();
+                    Ok(Value::List(items))
+                }
+                Value::String(s) => Ok(Value::String(s.chars().rev().collect())),
+                Value::Null => Ok(Value::Null),
+                _ => Err(Error::TypeError { expected: "List or String".into(), got: val.type_name().into(), span: None }),
+            }
+        }
+        "TOBYTES" => {
+            let val = eval_expr(&args[0], row, params)?;
+            match val {
+                Value::Null => Ok(Value::Null),
+                Value::String(s) => Ok(Value::Bytes(s.into_bytes())),
+                other => Ok(Value::Bytes(vector_bytes(&other)?)),
+            }
+        }
+        "FROMBASE64" => {
+            let val = eval_expr(&args[0], row, params)?;
+            match val {
+                Value::Null => Ok(Value::Null),
+                Value::String(s) => decode_base64(&s).map(Value::Bytes),
+                _ => Err(Error::TypeError { expected: "String".into(), got: val.type_name().into(), span: None }),
             }
         }
         "SIZE" | "LENGTH" => {
@@ -1072,7 +1903,7 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
                 Value::List(l) => Ok(Value::Int(l.len() as i64)),
                 Value::Path(p) => Ok(Value::Int(p.len() as i64)),
                 Value::Null => Ok(Value::Null),
-                _ => Err(Error::TypeError { expected: "String, List, or Path".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "String, List, or Path".into(), got: val.type_name().into(), span: None }),
             }
         }
         "HEAD" => {
@@ -1080,15 +1911,15 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
             match val {
                 Value::List(l) => Ok(l.into_iter().next().unwrap_or(Value::Null)),
                 Value::Null => Ok(Value::Null),
-                _ => Err(Error::TypeError { expected: "List".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "List".into(), got: val.type_name().into(), span: None }),
             }
         }
         "LAST" => {
             let val = eval_expr(&args[0], row, params)?;
             match val {
-                Value::List(l) => Ok(l.into_iter().last().unwrap_or(Value::Null)),
+                Value::List(mut l) => Ok(l.pop().unwrap_or(Value::Null)),
                 Value::Null => Ok(Value::Null),
-                _ => Err(Error::TypeError { expected: "List".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "List".into(), got: val.type_name().into(), span: None }),
             }
         }
         "TAIL" => {
@@ -1096,17 +1927,41 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
             match val {
                 Value::List(mut l) => { if !l.is_empty() { l.remove(0); } Ok(Value::List(l)) }
                 Value::Null => Ok(Value::Null),
-                _ => Err(Error::TypeError { expected: "List".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "List".into(), got: val.type_name().into(), span: None }),
+            }
+        }
+        // elementAt(list, index[, default]): list[index], counting from the
+        // end when negative, or `default` (else null) when out of range
+        "ELEMENTAT" => {
+            if !(2..=3).contains(&args.len()) {
+                return Err(Error::ExecutionError("elementAt() expects (list, index[, default])".into()));
+            }
+            let list = eval_expr(&args[0], row, params)?;
+            let index = eval_expr(&args[1], row, params)?;
+            let mut l = match list {
+                Value::List(l) => l,
+                Value::Null => return Ok(Value::Null),
+                other => return Err(Error::TypeError { expected: "List".into(), got: other.type_name().into(), span: None }),
+            };
+            let index = match index {
+                Value::Int(i) => i,
+                Value::Null => return Ok(Value::Null),
+                other => return Err(Error::TypeError { expected: "Integer".into(), got: other.type_name().into(), span: None }),
+            };
+            let position = if index < 0 { l.len().checked_sub(index.unsigned_abs() as usize) } else { Some(index as usize) };
+            match position.filter(|p| *p < l.len()) {
+                Some(p) => Ok(l.swap_remove(p)),
+                None => args.get(2).map_or(Ok(Value::Null), |d| eval_expr(d, row, params)),
             }
         }
         "RANGE" => {
             let start = eval_expr(&args[0], row, params)?.as_int()
-                .ok_or_else(|| Error::TypeError { expected: "Integer".into(), got: "non-integer".into() })?;
+                .ok_or_else(|| Error::TypeError { expected: "Integer".into(), got: "non-integer".into(), span: None })?;
             let end = eval_expr(&args[1], row, params)?.as_int()
-                .ok_or_else(|| Error::TypeError { expected: "Integer".into(), got: "non-integer".into() })?;
+                .ok_or_else(|| Error::TypeError { expected: "Integer".into(), got: "non-integer".into(), span: None })?;
             let step = if args.len() > 2 {
                 eval_expr(&args[2], row, params)?.as_int()
-                    .ok_or_else(|| Error::TypeError { expected: "Integer".into(), got: "non-integer".into() })?
+                    .ok_or_else(|| Error::TypeError { expected: "Integer".into(), got: "non-integer".into(), span: None })?
             } else { 1 };
             let mut list = Vec::new();
             let mut i = start;
@@ -1129,14 +1984,89 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
             let val = eval_expr(&args[0], row, params)?;
             match val {
                 Value::Path(p) => Ok(Value::List(p.nodes.into_iter().map(|n| Value::Node(Box::new(n))).collect())),
-                _ => Err(Error::TypeError { expected: "Path".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "Path".into(), got: val.type_name().into(), span: None }),
             }
         }
         "RELATIONSHIPS" | "RELS" => {
             let val = eval_expr(&args[0], row, params)?;
             match val {
                 Value::Path(p) => Ok(Value::List(p.relationships.into_iter().map(|r| Value::Relationship(Box::new(r))).collect())),
-                _ => Err(Error::TypeError { expected: "Path".into(), got: val.type_name().into() }),
+                _ => Err(Error::TypeError { expected: "Path".into(), got: val.type_name().into(), span: None }),
+            }
+        }
+        // Evaluated per call, so each row (and each occurrence in a row) gets a fresh value
+        "RAND" => Ok(Value::Float(params.random.next_f64())),
+        "RANDOMUUID" => {
+            let mut b = [0u8; 16];
+            params.random.fill(&mut b);
+            b[6] = (b[6] & 0x0f) | 0x40; // version 4
+            b[8] = (b[8] & 0x3f) | 0x80; // RFC 4122 variant
+            let hex: String = b.iter().map(|x| format!("{x:02x}")).collect();
+            Ok(Value::String(format!(
+                "{}-{}-{}-{}-{}",
+                &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32]
+            )))
+        }
+        name if name.starts_with("APOC.COLL.") => {
+            let values = args.iter().map(|a| eval_expr(a, row, params)).collect::<Result<Vec<_>>>()?;
+            coll::call(&name["APOC.COLL.".len()..], &values, params.collation)
+        }
+        name if name.starts_with("APOC.TEXT.") => {
+            let values = args.iter().map(|a| eval_expr(a, row, params)).collect::<Result<Vec<_>>>()?;
+            text::call(&name["APOC.TEXT.".len()..], &values)
+        }
+        // GDS returns 0 for a zero vector rather than NaN
+        "GDS.SIMILARITY.COSINE" => similarity_vectors("cosine", args, row, params, |a, b| {
+            let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+            let norm = a.iter().map(|x| x * x).sum::<f64>().sqrt() * b.iter().map(|y| y * y).sum::<f64>().sqrt();
+            if norm == 0.0 { 0.0 } else { dot / norm }
+        }),
+        "GDS.SIMILARITY.EUCLIDEANDISTANCE" => similarity_vectors("euclideanDistance", args, row, params, euclidean_distance),
+        "GDS.SIMILARITY.EUCLIDEAN" => {
+            similarity_vectors("euclidean", args, row, params, |a, b| 1.0 / (1.0 + euclidean_distance(a, b)))
+        }
+        // Sorted by key, since maps are unordered
+        "APOC.MAP.ENTRIES" => match eval_expr(&args[0], row, params)? {
+            Value::Null => Ok(Value::Null),
+            Value::Map(map) => {
+                let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                Ok(Value::List(
+                    entries
+                        .into_iter()
+                        .map(|(k, v)| Value::Map(PropertyMap::from([("key".into(), Value::String(k)), ("value".into(), v)])))
+                        .collect(),
+                ))
+            }
+            other => Err(Error::TypeError { expected: "Map".into(), got: other.type_name().into(), span: None }),
+        },
+        "APOC.MAP.FROMLISTS" => {
+            let keys = eval_expr(&args[0], row, params)?;
+            let values = eval_expr(&args[1], row, params)?;
+            match (keys, values) {
+                (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+                (Value::List(keys), Value::List(values)) => {
+                    if keys.len() != values.len() {
+                        return Err(Error::ExecutionError(format!(
+                            "apoc.map.fromLists: {} keys but {} values",
+                            keys.len(),
+                            values.len()
+                        )));
+                    }
+                    let mut map = PropertyMap::new();
+                    for (key, value) in keys.into_iter().zip(values) {
+                        match key {
+                            Value::String(k) => { map.insert(k, value); }
+                            other => return Err(Error::TypeError { expected: "String key".into(), got: other.type_name().into(), span: None }),
+                        }
+                    }
+                    Ok(Value::Map(map))
+                }
+                (keys, values) => Err(Error::TypeError {
+                    expected: "List, List".into(),
+                    got: format!("{}, {}", keys.type_name(), values.type_name()),
+                    span: None,
+                }),
             }
         }
         // Aggregation functions are placeholders — real aggregation is done in aggregate_rows
@@ -1152,6 +2082,51 @@ fn eval_function(name: &str, args: &[Expr], row: &Row, params: &PropertyMap) ->
     }
 }
 
+// ============================================================================
+// Lenient conversions (Neo4j 5): unconvertible input yields NULL instead of an error
+// ============================================================================
+
+fn integer_or_null(val: Value) -> Value {
+    match val {
+        Value::Int(_) => val,
+        Value::Float(f) => truncate(f).map_or(Value::Null, Value::Int),
+        Value::Bool(b) => Value::Int(b as i64),
+        Value::String(s) => parse_integer(&s).map_or(Value::Null, Value::Int),
+        _ => Value::Null,
+    }
+}
+
+fn float_or_null(val: Value) -> Value {
+    match val {
+        Value::Float(_) => val,
+        Value::Int(i) => Value::Float(i as f64),
+        Value::String(s) => s.trim().parse::<f64>().map(Value::Float).unwrap_or(Value::Null),
+        _ => Value::Null,
+    }
+}
+
+fn boolean_or_null(val: Value) -> Value {
+    match val {
+        Value::Bool(_) => val,
+        Value::Int(i) => Value::Bool(i != 0),
+        Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::Null,
+        },
+        _ => Value::Null,
+    }
+}
+
+/// Only scalars have a string form; lists, maps and graph entities become NULL.
+fn string_or_null(val: Value) -> Value {
+    match val {
+        Value::String(_) => val,
+        Value::Int(_) | Value::Float(_) | Value::Bool(_) => Value::String(format!("{val}")),
+        _ => Value::Null,
+    }
+}
+
 // ============================================================================
 // Aggregation
 // ============================================================================
@@ -1160,7 +2135,7 @@ fn aggregate_rows(
     rows: &[Row],
     group_by: &[(Expr, String)],
     aggregations: &[(Expr, String)],
-    params: &PropertyMap,
+    params: &EvalParams,
 ) -> Result<Vec<Row>> {
     // Group rows by group-by key values
     let mut groups: Vec<(Vec<Value>, Vec<&Row>)> = Vec::new();
@@ -1170,7 +2145,8 @@ fn aggregate_rows(
             .map(|(expr, _)| eval_expr(expr, row, params).unwrap_or(Value::Null))
             .collect();
 
-        if let Some(group) = groups.iter_mut().find(|(k, _)| *k == key) {
+        // Keys group by equivalence: nulls together, 1 with 1.0
+        if let Some(group) = groups.iter_mut().find(|(k, _)| k.iter().zip(&key).all(|(a, b)| a.equivalent(b))) {
             group.1.push(row);
         } else {
             groups.push((key, vec![row]));
@@ -1204,9 +2180,9 @@ fn aggregate_rows(
     Ok(result)
 }
 
-fn compute_aggregate(expr: &Expr, rows: &[&Row], params: &PropertyMap) -> Result<Value> {
+fn compute_aggregate(expr: &Expr, rows: &[&Row], params: &EvalParams) -> Result<Value> {
     match expr {
-        Expr::FunctionCall { name, args, distinct } => {
+        Expr::FunctionCall { name, args, distinct, .. } if is_aggregate_function(name) => {
             let upper = name.to_uppercase();
             let vals: Vec<Value> = if args.is_empty() {
                 // count(*) — count all rows
@@ -1222,7 +2198,7 @@ fn compute_aggregate(expr: &Expr, rows: &[&Row], params: &PropertyMap) -> Result
                 if *distinct {
                     let mut deduped = Vec::new();
                     for val in v {
-                        if !deduped.contains(&val) {
+                        if !deduped.iter().any(|d: &Value| d.equivalent(&val)) {
                             deduped.push(val);
                         }
                     }
@@ -1246,7 +2222,7 @@ fn compute_aggregate(expr: &Expr, rows: &[&Row], params: &PropertyMap) -> Result
                     let mut has_float = false;
                     for val in &vals {
                         match val {
-                            Value::Int(i) => sum_i += i,
+                            Value::Int(i) => sum_i = sum_i.checked_add(*i).ok_or_else(integer_overflow)?,
                             Value::Float(f) => { has_float = true; sum_f += f; }
                             _ => {}
                         }
@@ -1267,20 +2243,56 @@ fn compute_aggregate(expr: &Expr, rows: &[&Row], params: &PropertyMap) -> Result
                 }
                 "MIN" => {
                     vals.into_iter().reduce(|a, b| {
-                        if a.neo4j_cmp(&b) == Some(std::cmp::Ordering::Less) { a } else { b }
+                        if a.neo4j_cmp_with(&b, params.collation) == Some(std::cmp::Ordering::Less) { a } else { b }
                     }).map(Ok).unwrap_or(Ok(Value::Null))
                 }
                 "MAX" => {
                     vals.into_iter().reduce(|a, b| {
-                        if a.neo4j_cmp(&b) == Some(std::cmp::Ordering::Greater) { a } else { b }
+                        if a.neo4j_cmp_with(&b, params.collation) == Some(std::cmp::Ordering::Greater) { a } else { b }
                     }).map(Ok).unwrap_or(Ok(Value::Null))
                 }
                 "COLLECT" => {
                     Ok(Value::List(vals))
                 }
+                // Always a Float, like APOC, even over integers
+                "APOC.AGG.MEDIAN" => {
+                    let mut nums: Vec<f64> = vals.iter().filter_map(|v| v.as_float()).collect();
+                    nums.sort_by(f64::total_cmp);
+                    let mid = nums.len() / 2;
+                    Ok(match nums.len() {
+                        0 => Value::Null,
+                        n if n % 2 == 1 => Value::Float(nums[mid]),
+                        _ => Value::Float((nums[mid - 1] + nums[mid]) / 2.0),
+                    })
+                }
+                "APOC.AGG.STATISTICS" => {
+                    let nums: Vec<f64> = vals.iter().filter_map(|v| v.as_float()).collect();
+                    let mut stats = PropertyMap::new();
+                    if nums.is_empty() {
+                        for key in ["min", "max", "mean", "stdev"] {
+                            stats.insert(key.into(), Value::Null);
+                        }
+                    } else {
+                        let mean = nums.iter().sum::<f64>() / nums.len() as f64;
+                        let variance = nums.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / nums.len() as f64;
+                        stats.insert("min".into(), Value::Float(nums.iter().copied().fold(f64::INFINITY, f64::min)));
+                        stats.insert("max".into(), Value::Float(nums.iter().copied().fold(f64::NEG_INFINITY, f64::max)));
+                        stats.insert("mean".into(), Value::Float(mean));
+                        stats.insert("stdev".into(), Value::Float(variance.sqrt()));
+                    }
+                    Ok(Value::Map(stats))
+                }
                 _ => Err(Error::ExecutionError(format!("Unknown aggregate: {name}"))),
             }
         }
+        // Expressions built over aggregates (e.g. `sum(n.a) + sum(n.b)`):
+        // compute each inner aggregate over the group, bind it to a synthetic
+        // variable, then evaluate the rewritten expression once.
+        other if is_aggregate_expr(other) => {
+            let mut bindings: Row = rows.first().map(|r| (*r).clone()).unwrap_or_default();
+            let rewritten = bind_aggregates(other, rows, params, &mut bindings)?;
+            eval_expr(&rewritten, &bindings, params)
+        }
         // Non-aggregate expressions in aggregation context — just eval against first row
         other => {
             if let Some(row) = rows.first() {
@@ -1291,3 +2303,493 @@ fn compute_aggregate(expr: &Expr, rows: &[&Row], params: &PropertyMap) -> Result
         }
     }
 }
+
+/// Replace every aggregate sub-expression with a variable bound to its
+/// value over `rows`. Non-aggregate parts of the tree are left intact.
+fn bind_aggregates(expr: &Expr, rows: &[&Row], params: &EvalParams, bindings: &mut Row) -> Result<Expr> {
+    if !is_aggregate_expr(expr) {
+        return Ok(expr.clone());
+    }
+    let rebind = |e: &Expr, b: &mut Row| bind_aggregates(e, rows, params, b).map(Box::new);
+    Ok(match expr {
+        Expr::FunctionCall { name, .. } if is_aggregate_function(name) => {
+            let val = compute_aggregate(expr, rows, params)?;
+            let var = format!("__agg_{}", bindings.len());
+            bindings.insert(var.clone(), val);
+            Expr::Variable { name: var, span: None }
+        }
+        Expr::FunctionCall { name, args, distinct, span } => Expr::FunctionCall {
+            name: name.clone(),
+            args: args.iter().map(|a| bind_aggregates(a, rows, params, bindings)).collect::<Result<_>>()?,
+            distinct: *distinct,
+            span: *span,
+        },
+        Expr::Property { expr: inner, key } => Expr::Property { expr: rebind(inner, bindings)?, key: key.clone() },
+        Expr::BinaryOp { left, op, right } => Expr::BinaryOp {
+            left: rebind(left, bindings)?,
+            op: *op,
+            right: rebind(right, bindings)?,
+        },
+        Expr::UnaryOp { op, expr: inner } => Expr::UnaryOp { op: *op, expr: rebind(inner, bindings)? },
+        Expr::List(items) => Expr::List(
+            items.iter().map(|i| bind_aggregates(i, rows, params, bindings)).collect::<Result<_>>()?,
+        ),
+        Expr::MapLiteral(entries) => {
+            let mut map = HashMap::new();
+            for (k, v) in entries {
+                map.insert(k.clone(), bind_aggregates(v, rows, params, bindings)?);
+            }
+            Expr::MapLiteral(map)
+        }
+        Expr::Case { operand, whens, else_expr } => {
+            let operand = match operand {
+                Some(o) => Some(rebind(o, bindings)?),
+                None => None,
+            };
+            let mut new_whens = Vec::with_capacity(whens.len());
+            for (w, t) in whens {
+                new_whens.push((
+                    bind_aggregates(w, rows, params, bindings)?,
+                    bind_aggregates(t, rows, params, bindings)?,
+                ));
+            }
+            let else_expr = match else_expr {
+                Some(e) => Some(rebind(e, bindings)?),
+                None => None,
+            };
+            Expr::Case { operand, whens: new_whens, else_expr }
+        }
+        Expr::In { expr: item, list } => Expr::In { expr: rebind(item, bindings)?, list: rebind(list, bindings)? },
+        Expr::IsNull { expr: inner, negated } => Expr::IsNull { expr: rebind(inner, bindings)?, negated: *negated },
+        Expr::HasLabel { expr: inner, label } => Expr::HasLabel { expr: rebind(inner, bindings)?, label: label.clone() },
+        Expr::StringOp { left, op, right } => Expr::StringOp {
+            left: rebind(left, bindings)?,
+            op: *op,
+            right: rebind(right, bindings)?,
+        },
+        other => other.clone(),
+    })
+}
+
+/// An `EXISTS` or `COUNT` subquery lifted out of an expression.
+struct Subquery {
+    pattern: ast::MatchClause,
+    where_clause: Option<Box<Expr>>,
+    count: bool,
+}
+
+/// Replace every `EXISTS` and `COUNT` sub-expression with a variable
+/// (`subquery_var(i)`) and collect the subquery into `subqueries`, in order.
+fn bind_subqueries(expr: &Expr, subqueries: &mut Vec<Subquery>) -> Expr {
+    if let Expr::Exists { pattern, where_clause } | Expr::CountSubquery { pattern, where_clause } = expr {
+        let count = matches!(expr, Expr::CountSubquery { .. });
+        subqueries.push(Subquery { pattern: (**pattern).clone(), where_clause: where_clause.clone(), count });
+        return Expr::Variable { name: subquery_var(subqueries.len() - 1), span: None };
+    }
+    let mut rebind = |e: &Expr| Box::new(bind_subqueries(e, subqueries));
+    match expr {
+        Expr::Property { expr: inner, key } => Expr::Property { expr: rebind(inner), key: key.clone() },
+        Expr::FunctionCall { name, args, distinct, span } => Expr::FunctionCall {
+            name: name.clone(),
+            args: args.iter().map(|a| bind_subqueries(a, subqueries)).collect(),
+            distinct: *distinct,
+            span: *span,
+        },
+        Expr::BinaryOp { left, op, right } => {
+            let left = rebind(left);
+            Expr::BinaryOp { left, op: *op, right: Box::new(bind_subqueries(right, subqueries)) }
+        }
+        Expr::UnaryOp { op, expr: inner } => Expr::UnaryOp { op: *op, expr: rebind(inner) },
+        Expr::List(items) => Expr::List(items.iter().map(|i| bind_subqueries(i, subqueries)).collect()),
+        Expr::MapLiteral(entries) => {
+            Expr::MapLiteral(entries.iter().map(|(k, v)| (k.clone(), bind_subqueries(v, subqueries))).collect())
+        }
+        Expr::Case { operand, whens, else_expr } => Expr::Case {
+            operand: operand.as_deref().map(|o| Box::new(bind_subqueries(o, subqueries))),
+            whens: whens.iter().map(|(w, t)| (bind_subqueries(w, subqueries), bind_subqueries(t, subqueries))).collect(),
+            else_expr: else_expr.as_deref().map(|e| Box::new(bind_subqueries(e, subqueries))),
+        },
+        Expr::In { expr: item, list } => {
+            let item = rebind(item);
+            Expr::In { expr: item, list: Box::new(bind_subqueries(list, subqueries)) }
+        }
+        Expr::IsNull { expr: inner, negated } => Expr::IsNull { expr: rebind(inner), negated: *negated },
+        Expr::HasLabel { expr: inner, label } => Expr::HasLabel { expr: rebind(inner), label: label.clone() },
+        Expr::StringOp { left, op, right } => {
+            let left = rebind(left);
+            Expr::StringOp { left, op: *op, right: Box::new(bind_subqueries(right, subqueries)) }
+        }
+        other => other.clone(),
+    }
+}
+
+fn subquery_var(i: usize) -> String {
+    format!("__subquery_{i}")
+}
+
+/// `row` with each of `subqueries` run for it and its result bound to
+/// `subquery_var(i)`: whether it matched for `EXISTS`, the number of
+/// matches for `COUNT`. eval_expr has no backend to run them itself. Each
+/// subquery is planned on first use, against the first row's variables.
+async fn eval_subqueries<B: StorageBackend>(
+    backend: &B,
+    tx: &mut B::Tx,
+    subqueries: &[Subquery],
+    plans: &mut [Option<LogicalPlan>],
+    row: &Row,
+    ctx: &ExecContext,
+) -> Result<Row> {
+    let mut bound_row = row.clone();
+    for (i, subquery) in subqueries.iter().enumerate() {
+        let plan = match &mut plans[i] {
+            Some(plan) => plan,
+            slot => {
+                let bound: Vec<String> = row.keys().cloned().collect();
+                let where_clause = subquery.where_clause.as_deref();
+                slot.insert(if subquery.count {
+                    crate::planner::plan_subquery(&subquery.pattern, where_clause, &bound)?
+                } else {
+                    crate::planner::plan_exists(&subquery.pattern, where_clause, &bound)?
+                })
+            }
+        };
+        let mut sub = ctx.sub(ctx.params.values.clone());
+        sub.argument = row.clone();
+        let rows = execute_plan(backend, tx, plan, &mut sub).await?;
+        let value = if subquery.count { Value::Int(rows.len() as i64) } else { Value::Bool(!rows.is_empty()) };
+        bound_row.insert(subquery_var(i), value);
+    }
+    Ok(bound_row)
+}
+
+/// `SET n.key = val`. A property is never null: setting it to null
+/// removes it, as in Neo4j.
+async fn set_node_property<B: StorageBackend>(backend: &B, tx: &mut B::Tx, id: NodeId, key: &str, val: Value) -> Result<()> {
+    if val.is_null() {
+        backend.remove_node_property(tx, id, key).await
+    } else {
+        backend.set_node_property(tx, id, key, val).await
+    }
+}
+
+/// Built-in `apoc.do.when(condition, ifQuery, elseQuery, params)`: runs
+/// `ifQuery` when `condition` is true and `elseQuery` otherwise (null counts
+/// as false), in the caller's transaction. The fragment sees only `params`;
+/// each of its rows becomes a `value` map. An empty fragment yields no rows.
+async fn do_when<B: StorageBackend>(
+    backend: &B,
+    tx: &mut B::Tx,
+    args: &[Value],
+    ctx: &mut ExecContext,
+) -> Result<ProcedureResult> {
+    let (condition, if_query, else_query, params) = match args {
+        [c, i] => (c, i, &Value::Null, &Value::Null),
+        [c, i, e] => (c, i, e, &Value::Null),
+        [c, i, e, p] => (c, i, e, p),
+        _ => {
+            return Err(Error::ExecutionError(
+                "apoc.do.when expects (condition, ifQuery[, elseQuery[, params]])".into(),
+            ));
+        }
+    };
+    let condition = match condition {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        other => return Err(Error::TypeError { expected: "Boolean".into(), got: other.type_name().into(), span: None }),
+    };
+    let query = match if condition { if_query } else { else_query } {
+        Value::Null => "",
+        Value::String(s) => s.as_str(),
+        other => return Err(Error::TypeError { expected: "String".into(), got: other.type_name().into(), span: None }),
+    };
+    let params = match params {
+        Value::Null => PropertyMap::new(),
+        Value::Map(m) => m.clone(),
+        other => return Err(Error::TypeError { expected: "Map".into(), got: other.type_name().into(), span: None }),
+    };
+
+    let mut result = ProcedureResult { columns: vec!["value".into()], rows: Vec::new() };
+    if query.trim().is_empty() {
+        return Ok(result);
+    }
+    let (columns, rows) = execute_fragment(backend, tx, query, params, ctx).await?;
+    for row in rows {
+        let value: PropertyMap = columns
+            .iter()
+            .map(|c| (c.clone(), row.get(c).cloned().unwrap_or(Value::Null)))
+            .collect();
+        result.rows.push(HashMap::from([("value".to_string(), Value::Map(value))]));
+    }
+    Ok(result)
+}
+
+/// `apoc.create.node(labels, props)`: create a node whose labels are only
+/// known at runtime, e.g. from parameters, which `CREATE (n:Label)` can't
+/// take. Returns it in a `node` column.
+async fn create_node<B: StorageBackend>(
+    backend: &B,
+    tx: &mut B::Tx,
+    args: &[Value],
+    ctx: &mut ExecContext,
+) -> Result<ProcedureResult> {
+    let (labels, props) = match args {
+        [labels] => (labels, &Value::Null),
+        [labels, props] => (labels, props),
+        _ => return Err(Error::ExecutionError("apoc.create.node expects (labels[, props])".into())),
+    };
+    let labels: Vec<&str> = match labels {
+        Value::Null => Vec::new(),
+        Value::List(labels) => labels
+            .iter()
+            .map(|label| match label {
+                Value::String(s) if !s.is_empty() => Ok(s.as_str()),
+                Value::String(_) => Err(Error::ExecutionError("apoc.create.node: labels must be non-empty".into())),
+                other => Err(Error::TypeError { expected: "String".into(), got: other.type_name().into(), span: None }),
+            })
+            .collect::<Result<_>>()?,
+        other => return Err(Error::TypeError { expected: "List".into(), got: other.type_name().into(), span: None }),
+    };
+    let props = match props {
+        Value::Null => PropertyMap::new(),
+        Value::Map(m) => m.clone(),
+        other => return Err(Error::TypeError { expected: "Map".into(), got: other.type_name().into(), span: None }),
+    };
+
+    let id = backend.create_node(tx, &labels, props).await?;
+    ctx.stats.nodes_created += 1;
+    let node = backend.get_node(tx, id).await?
+        .ok_or_else(|| Error::ExecutionError("Created node not found".into()))?;
+    Ok(ProcedureResult {
+        columns: vec!["node".into()],
+        rows: vec![HashMap::from([("node".to_string(), Value::Node(Box::new(node)))])],
+    })
+}
+
+/// Parse, plan and run `query` inside the current execution, for procedures
+/// that take Cypher fragments. Its write statistics count toward the outer
+/// query's.
+async fn execute_fragment<B: StorageBackend>(
+    backend: &B,
+    tx: &mut B::Tx,
+    query: &str,
+    params: PropertyMap,
+    ctx: &mut ExecContext,
+) -> Result<(Vec<String>, Vec<Row>)> {
+    let ast = crate::cypher::parse(query)?;
+    let plan = crate::planner::optimize(crate::planner::plan(&ast, &params)?)?;
+    let mut sub = ctx.sub(params);
+    let rows = execute_plan(backend, tx, &plan, &mut sub).await?;
+    ctx.stats.add(&sub.stats);
+    Ok((sub.columns, rows))
+}
+
+/// Built-in `db.index.vector.queryNodes(indexName, k, queryVector)`:
+/// runs `vector_query` and resolves each hit into `node` / `score` columns.
+async fn vector_query_nodes<B: StorageBackend>(
+    backend: &B,
+    tx: &B::Tx,
+    args: &[Value],
+) -> Result<ProcedureResult> {
+    let [index, k, vector] = args else {
+        return Err(Error::ExecutionError(
+            "db.index.vector.queryNodes expects (indexName, k, queryVector)".into(),
+        ));
+    };
+    let index = index.as_str().ok_or_else(|| Error::TypeError {
+        expected: "String".into(),
+        got: index.type_name().into(),
+        span: None,
+    })?;
+    let k = k.as_int().filter(|k| *k >= 0).ok_or_else(|| Error::TypeError {
+        expected: "non-negative Integer".into(),
+        got: k.type_name().into(),
+        span: None,
+    })?;
+    let Value::Bytes(vector) = vector else {
+        return Err(Error::TypeError { expected: "Bytes".into(), got: vector.type_name().into(), span: None });
+    };
+
+    let hits = backend.vector_query(tx, index, k as usize, vector).await?;
+    let mut result = ProcedureResult {
+        columns: vec!["node".into(), "score".into()],
+        rows: Vec::with_capacity(hits.len()),
+    };
+    let ids: Vec<NodeId> = hits.iter().map(|(id, _)| *id).collect();
+    let nodes = backend.get_nodes(tx, &ids).await?;
+    for ((id, score), node) in hits.into_iter().zip(nodes) {
+        let node = node.ok_or_else(|| Error::NotFound(format!("Node {id}")))?;
+        let mut row = HashMap::new();
+        row.insert("node".to_string(), Value::from(node));
+        row.insert("score".to_string(), Value::Float(score));
+        result.rows.push(row);
+    }
+    Ok(result)
+}
+
+/// Bucket hash for `HashJoin`. Values that are `==` always share a bucket;
+/// collisions are resolved by comparing the values themselves.
+fn join_hash(val: &Value) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut h = std::collections::hash_map::DefaultHasher::new();
+    // INTEGER and FLOAT compare equal across types, so share one tag
+    if let Some(f) = val.as_float() {
+        // -0.0 == 0.0, so hash them alike
+        (if f == 0.0 { 0.0f64 } else { f }).to_bits().hash(&mut h);
+        return h.finish();
+    }
+    std::mem::discriminant(val).hash(&mut h);
+    match val {
+        Value::Bool(b) => b.hash(&mut h),
+        Value::String(s) => s.hash(&mut h),
+        Value::Bytes(b) => b.hash(&mut h),
+        Value::Node(n) => n.id.hash(&mut h),
+        Value::Relationship(r) => r.id.hash(&mut h),
+        Value::List(items) => items.len().hash(&mut h),
+        _ => {}
+    }
+    h.finish()
+}
+
+/// Evaluate the two list arguments of a `gds.similarity.*` function and
+/// apply `f` to them as equal-length `f64` vectors. Null if either is null.
+fn similarity_vectors(
+    name: &str,
+    args: &[Expr],
+    row: &Row,
+    params: &EvalParams,
+    f: impl Fn(&[f64], &[f64]) -> f64,
+) -> Result<Value> {
+    let [a, b] = args else {
+        return Err(Error::ExecutionError(format!("gds.similarity.{name}() expects two lists")));
+    };
+    let (a, b) = (eval_expr(a, row, params)?, eval_expr(b, row, params)?);
+    if a.is_null() || b.is_null() {
+        return Ok(Value::Null);
+    }
+    let (a, b) = (number_list(&a)?, number_list(&b)?);
+    if a.len() != b.len() {
+        return Err(Error::ExecutionError(format!(
+            "gds.similarity.{name}: vectors have different lengths ({} and {})",
+            a.len(),
+            b.len()
+        )));
+    }
+    Ok(Value::Float(f(&a, &b)))
+}
+
+fn number_list(val: &Value) -> Result<Vec<f64>> {
+    let Value::List(items) = val else {
+        return Err(Error::TypeError { expected: "List".into(), got: val.type_name().into(), span: None });
+    };
+    items
+        .iter()
+        .map(|item| {
+            item.as_float().ok_or_else(|| Error::TypeError {
+                expected: "Float".into(),
+                got: item.type_name().into(),
+                span: None,
+            })
+        })
+        .collect()
+}
+
+fn euclidean_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f64>().sqrt()
+}
+
+/// Encode a query vector in the format `StorageBackend::vector_query` takes:
+/// `Bytes` pass through, a list of numbers becomes little-endian `f32`s.
+fn vector_bytes(val: &Value) -> Result<Vec<u8>> {
+    match val {
+        Value::Bytes(b) => Ok(b.clone()),
+        Value::List(items) => {
+            let mut out = Vec::with_capacity(items.len() * 4);
+            for item in items {
+                let f = item.as_float().ok_or_else(|| Error::TypeError {
+                    expected: "Float".into(),
+                    got: item.type_name().into(),
+                    span: None,
+                })?;
+                out.extend_from_slice(&(f as f32).to_le_bytes());
+            }
+            Ok(out)
+        }
+        _ => Err(Error::TypeError { expected: "Bytes or List".into(), got: val.type_name().into(), span: None }),
+    }
+}
+
+/// Decode standard (RFC 4648) base64, with or without `=` padding.
+fn decode_base64(s: &str) -> Result<Vec<u8>> {
+    fn sextet(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+            b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let data = s.trim().trim_end_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        if chunk.len() == 1 {
+            return Err(Error::ExecutionError(format!("invalid base64 length in '{s}'")));
+        }
+        let mut acc = 0u32;
+        for &c in chunk {
+            let v = sextet(c)
+                .ok_or_else(|| Error::ExecutionError(format!("invalid base64 character '{}'", c as char)))?;
+            acc = (acc << 6) | v;
+        }
+        acc <<= 6 * (4 - chunk.len() as u32);
+        out.extend_from_slice(&acc.to_be_bytes()[1..chunk.len()]);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str) -> Expr {
+        Expr::Variable { name: name.into(), span: None }
+    }
+
+    fn prop(expr: Expr, key: &str) -> Expr {
+        Expr::Property { expr: Box::new(expr), key: key.into() }
+    }
+
+    #[test]
+    fn test_property_bases_read_each_base_once() {
+        // A wide projection of `n` evaluates `n` once per row, not once per item
+        let mut items: Vec<(Expr, String)> = (0..30).map(|i| (prop(var("n"), &format!("p{i}")), format!("p{i}"))).collect();
+        items.push((prop(prop(var("n"), "address"), "city"), "city".into()));
+        items.push((prop(prop(var("n"), "address"), "zip"), "zip".into()));
+        items.push((prop(var("m"), "p0"), "m0".into()));
+        items.push((Expr::FunctionCall { name: "coalesce".into(), args: vec![prop(var("n"), "p0")], distinct: false, span: None }, "c".into()));
+        let (bases, base_of) = property_bases(&items);
+
+        assert_eq!(bases.len(), 3);
+        assert!(base_of[..30].iter().all(|b| *b == Some(0)));
+        assert_eq!(base_of[30], Some(1));
+        assert_eq!(base_of[31], Some(1));
+        assert_eq!(base_of[32], Some(2));
+        assert_eq!(base_of[33], None);
+    }
+
+    #[test]
+    fn test_property_bases_compare_paths_not_just_ids() {
+        // Same-looking paths with different spans share; different names don't
+        let spanned = Expr::Variable { name: "n".into(), span: Some(crate::cypher::lexer::Span { start: 7, end: 8 }) };
+        let items = vec![
+            (prop(var("n"), "a"), "a".into()),
+            (prop(spanned, "b"), "b".into()),
+            (prop(Expr::Parameter { name: "n".into(), span: None }, "a"), "pa".into()),
+        ];
+        let (bases, base_of) = property_bases(&items);
+        assert_eq!(bases.len(), 2);
+        assert_eq!(base_of, vec![Some(0), Some(0), Some(1)]);
+    }
+}
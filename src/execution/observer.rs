@@ -0,0 +1,82 @@
+//! Execution hooks for metrics and tracing; see `Graph::with_observer`.
+//!
+//! The executor calls `on_operator_start` when it enters each plan
+//! operator and `on_rows_produced` when that operator has built its rows.
+//! Operators run inputs first, so an operator's start is reported before
+//! its inputs' starts, and its rows after theirs. An operator that fails
+//! reports no rows, and a failed query never reports completion.
+
+use std::sync::Arc;
+
+use super::ExecutionStats;
+
+/// Receives execution events. Every callback defaults to doing nothing, so
+/// implementors only override what they need.
+pub trait QueryObserver: Send + Sync {
+    /// An operator (`LogicalPlan::operator_name`) started executing.
+    fn on_operator_start(&self, op_name: &str) {
+        let _ = op_name;
+    }
+
+    /// An operator finished with `rows` output rows.
+    fn on_rows_produced(&self, op_name: &str, rows: usize) {
+        let _ = (op_name, rows);
+    }
+
+    /// The whole plan finished successfully. `parse_time_us` and
+    /// `plan_time_us` are not filled in yet.
+    fn on_query_complete(&self, stats: &ExecutionStats) {
+        let _ = stats;
+    }
+}
+
+/// Lets a caller keep a handle on an installed observer, e.g. to read
+/// counters it collects.
+impl<T: QueryObserver + ?Sized> QueryObserver for Arc<T> {
+    fn on_operator_start(&self, op_name: &str) {
+        (**self).on_operator_start(op_name);
+    }
+
+    fn on_rows_produced(&self, op_name: &str, rows: usize) {
+        (**self).on_rows_produced(op_name, rows);
+    }
+
+    fn on_query_complete(&self, stats: &ExecutionStats) {
+        (**self).on_query_complete(stats);
+    }
+}
+
+/// Ignores every event. Used when no observer is installed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl QueryObserver for NoopObserver {}
+
+/// Forwards events to `tracing` at `DEBUG` level, under the
+/// `neo4j_rs::execution` target.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingObserver;
+
+impl QueryObserver for TracingObserver {
+    fn on_operator_start(&self, op_name: &str) {
+        tracing::debug!(target: "neo4j_rs::execution", operator = op_name, "operator start");
+    }
+
+    fn on_rows_produced(&self, op_name: &str, rows: usize) {
+        tracing::debug!(target: "neo4j_rs::execution", operator = op_name, rows, "operator rows");
+    }
+
+    fn on_query_complete(&self, stats: &ExecutionStats) {
+        tracing::debug!(
+            target: "neo4j_rs::execution",
+            nodes_created = stats.nodes_created,
+            nodes_deleted = stats.nodes_deleted,
+            relationships_created = stats.relationships_created,
+            relationships_deleted = stats.relationships_deleted,
+            properties_set = stats.properties_set,
+            rows_returned = stats.rows_returned,
+            execute_time_us = stats.execute_time_us,
+            "query complete"
+        );
+    }
+}
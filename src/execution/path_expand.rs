@@ -0,0 +1,189 @@
+//! `apoc.path.expand(start, relationshipFilter, labelFilter, minLevel, maxLevel)`.
+//!
+//! A breadth-first expansion over `get_relationships`, so it runs on any
+//! backend. Paths never repeat a relationship (APOC's default
+//! `RELATIONSHIP_PATH` uniqueness) and the start node is not label-filtered.
+//!
+//! The backend's `ExpandLimits` apply as they do to `expand()`: a negative
+//! (unbounded) `maxLevel` stops at `max_depth`, a larger explicit one is an
+//! error, and so is building more than `max_paths` paths. The query's
+//! `RowLimits` cap the paths returned.
+//!
+//! Supported filter syntax:
+//!
+//! - relationship filter: `|`-separated `TYPE>` (outgoing), `<TYPE`
+//!   (incoming) or `TYPE` (either); a bare `>` or `<` matches any type.
+//!   Empty or null matches everything.
+//! - label filter: `|`-separated `+Label` (whitelist), `-Label` (blacklist),
+//!   or a plain `Label` (whitelist).
+//!
+//! Not supported, and rejected with an error: relationship sequences
+//! (`,`-separated filters), and the `/Label` termination and `>Label`
+//! end-node label filters.
+
+use std::collections::HashMap;
+
+use crate::model::*;
+use crate::storage::{ExpandDepth, ProcedureResult, StorageBackend, expansion_limit_exceeded};
+use crate::{Error, Result};
+
+use super::RowLimits;
+
+pub(super) async fn path_expand<B: StorageBackend>(
+    backend: &B,
+    tx: &B::Tx,
+    args: &[Value],
+    row_limits: RowLimits,
+) -> Result<ProcedureResult> {
+    let [start, rel_filter, label_filter, min_level, max_level] = args else {
+        return Err(Error::ExecutionError(
+            "apoc.path.expand expects (start, relationshipFilter, labelFilter, minLevel, maxLevel)".into(),
+        ));
+    };
+    let start = match start {
+        Value::Node(n) => (**n).clone(),
+        Value::Int(id) => backend
+            .get_node(tx, NodeId(*id as u64))
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Node {id}")))?,
+        other => {
+            return Err(Error::TypeError { expected: "Node".into(), got: other.type_name().into(), span: None });
+        }
+    };
+    let rel_filter = parse_rel_filter(filter_str(rel_filter)?)?;
+    let label_filter = LabelFilter::parse(filter_str(label_filter)?)?;
+    let level = |v: &Value| {
+        v.as_int().ok_or_else(|| Error::TypeError {
+            expected: "Integer".into(),
+            got: v.type_name().into(),
+            span: None,
+        })
+    };
+    // A negative maxLevel means unbounded, as in APOC
+    let min_level = level(min_level)?.max(0) as usize;
+    let limits = backend.expand_limits();
+    let (_, max_level) = match usize::try_from(level(max_level)?) {
+        Ok(max) => limits.resolve(ExpandDepth::Range { min: min_level, max })?,
+        Err(_) => limits.resolve(ExpandDepth::Unbounded)?,
+    };
+
+    let mut result = ProcedureResult { columns: vec!["path".into()], rows: Vec::new() };
+    let mut emit = |path: &Path| {
+        result.rows.push(HashMap::from([("path".to_string(), Value::from(path.clone()))]));
+        row_limits.check_intermediate(result.rows.len())
+    };
+    let mut frontier = vec![Path::single(start)];
+    if min_level == 0 {
+        emit(&frontier[0])?;
+    }
+    let mut depth = 0;
+    let mut paths_built = 0;
+    while !frontier.is_empty() && depth < max_level {
+        depth += 1;
+        let mut next = Vec::new();
+        for path in &frontier {
+            let end = path.end().id;
+            let mut rels: Vec<Relationship> = Vec::new();
+            for (rel_type, dir) in &rel_filter {
+                for rel in backend.get_relationships(tx, end, *dir, rel_type.as_deref()).await? {
+                    if rels.iter().any(|r| r.id == rel.id) || path.relationships.iter().any(|r| r.id == rel.id) {
+                        continue;
+                    }
+                    rels.push(rel);
+                }
+            }
+            let others: Vec<NodeId> = rels.iter().map(|r| if r.src == end { r.dst } else { r.src }).collect();
+            let nodes = backend.get_nodes(tx, &others).await?;
+            for (rel, node) in rels.into_iter().zip(nodes) {
+                let Some(node) = node else { continue };
+                if !label_filter.allows(&node) {
+                    continue;
+                }
+                paths_built += 1;
+                if paths_built > limits.max_paths {
+                    return Err(expansion_limit_exceeded());
+                }
+                let mut extended = path.clone();
+                extended.nodes.push(node);
+                extended.relationships.push(rel);
+                if depth >= min_level {
+                    emit(&extended)?;
+                }
+                next.push(extended);
+            }
+        }
+        frontier = next;
+    }
+    Ok(result)
+}
+
+fn filter_str(v: &Value) -> Result<&str> {
+    match v {
+        Value::Null => Ok(""),
+        Value::String(s) => Ok(s),
+        other => Err(Error::TypeError { expected: "String".into(), got: other.type_name().into(), span: None }),
+    }
+}
+
+/// `(type, direction)` pairs; `None` matches any type.
+fn parse_rel_filter(filter: &str) -> Result<Vec<(Option<String>, Direction)>> {
+    if filter.contains(',') {
+        return Err(unsupported(format!("relationship sequence '{filter}'")));
+    }
+    if filter.trim().is_empty() {
+        return Ok(vec![(None, Direction::Both)]);
+    }
+    filter
+        .split('|')
+        .map(|token| {
+            let token = token.trim();
+            let (token, incoming) = match token.strip_prefix('<') {
+                Some(t) => (t, true),
+                None => (token, false),
+            };
+            let (token, outgoing) = match token.strip_suffix('>') {
+                Some(t) => (t, true),
+                None => (token, false),
+            };
+            let dir = match (incoming, outgoing) {
+                (true, true) => return Err(unsupported(format!("relationship filter '<{token}>'"))),
+                (true, false) => Direction::Incoming,
+                (false, true) => Direction::Outgoing,
+                (false, false) => Direction::Both,
+            };
+            Ok(((!token.is_empty()).then(|| token.to_string()), dir))
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct LabelFilter {
+    whitelist: Vec<String>,
+    blacklist: Vec<String>,
+}
+
+impl LabelFilter {
+    fn parse(filter: &str) -> Result<Self> {
+        let mut out = Self::default();
+        for token in filter.split('|').map(str::trim).filter(|t| !t.is_empty()) {
+            if let Some(label) = token.strip_prefix('-') {
+                out.blacklist.push(label.to_string());
+            } else if token.starts_with(['/', '>']) {
+                return Err(unsupported(format!("label filter '{token}'")));
+            } else {
+                out.whitelist.push(token.strip_prefix('+').unwrap_or(token).to_string());
+            }
+        }
+        Ok(out)
+    }
+
+    /// Whether the expansion may continue through `node`.
+    fn allows(&self, node: &Node) -> bool {
+        let has = |labels: &[String]| labels.iter().any(|l| node.has_label(l));
+        !has(&self.blacklist) && (self.whitelist.is_empty() || has(&self.whitelist))
+    }
+}
+
+fn unsupported(what: String) -> Error {
+    Error::ExecutionError(format!("apoc.path.expand: unsupported {what}"))
+}
@@ -0,0 +1,152 @@
+//! `apoc.periodic.iterate(cypherIterate, cypherAction, config)`.
+//!
+//! Runs `cypherIterate` in the caller's transaction, then `cypherAction`
+//! once per row it returned, in batches of `config.batchSize` rows (default
+//! 10000). Each batch gets its own read-write transaction and is committed
+//! before the next starts, so no one transaction holds the whole update.
+//! The action sees each row's columns both as variables and as parameters,
+//! along with `config.params`.
+//!
+//! What a failing row does depends on `supports_rollback`. A backend that
+//! can roll back fails the row's whole batch: the transaction is rolled
+//! back and all its rows count as failed operations. One that can't (the
+//! default `MemoryBackend`) has already applied the rows before it, so the
+//! batch carries on and only the failing rows count as failed, though a
+//! row that fails partway keeps the writes it made before the error.
+//! Either way later batches still run, and only committed rows count
+//! toward the caller's statistics.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::model::{PropertyMap, Value};
+use crate::storage::{ProcedureResult, StorageBackend};
+use crate::tx::TxMode;
+use crate::{Error, Result};
+
+use super::{execute_fragment, execute_plan, ExecContext, ExecutionStats, Row};
+
+const DEFAULT_BATCH_SIZE: usize = 10_000;
+
+pub(super) async fn iterate<B: StorageBackend>(
+    backend: &B,
+    tx: &mut B::Tx,
+    args: &[Value],
+    ctx: &mut ExecContext,
+) -> Result<ProcedureResult> {
+    let (iterate_query, action_query, config) = match args {
+        [i, a] => (i, a, &Value::Null),
+        [i, a, c] => (i, a, c),
+        _ => {
+            return Err(Error::ExecutionError(
+                "apoc.periodic.iterate expects (cypherIterate, cypherAction[, config])".into(),
+            ));
+        }
+    };
+    let iterate_query = string_arg(iterate_query)?;
+    let action_query = string_arg(action_query)?;
+    let (batch_size, params) = config_arg(config)?;
+
+    let started = Instant::now();
+    let (columns, rows) = execute_fragment(backend, tx, iterate_query, params.clone(), ctx).await?;
+    // Bind the columns up front, as APOC does, so a bare `SET n.x = 1` parses
+    let bound: Vec<&str> = columns.iter().map(String::as_str).filter(|c| is_identifier(c)).collect();
+    let ast = if bound.is_empty() {
+        crate::cypher::parse(action_query)?
+    } else {
+        crate::cypher::parse(&format!("WITH {} {action_query}", bound.join(", ")))?
+    };
+    let can_roll_back = backend.capabilities().supports_rollback;
+    let action = crate::planner::optimize(crate::planner::plan(&ast, &params)?)?;
+
+    let (mut batches, mut committed, mut failed, mut failed_batches) = (0i64, 0i64, 0i64, 0i64);
+    let mut errors: HashMap<String, i64> = HashMap::new();
+    for batch in rows.chunks(batch_size) {
+        batches += 1;
+        let mut batch_tx = backend.begin_tx(TxMode::ReadWrite).await?;
+        let mut stats = ExecutionStats::default();
+        let mut batch_failed = 0i64;
+        for row in batch {
+            let argument: Row = columns.iter().map(|c| (c.clone(), row.get(c).cloned().unwrap_or(Value::Null))).collect();
+            let mut values = params.clone();
+            values.extend(argument.iter().map(|(k, v)| (k.clone(), v.clone())));
+            let mut sub = ctx.sub(values);
+            sub.argument = argument;
+            match execute_plan(backend, &mut batch_tx, &action, &mut sub).await {
+                Ok(_) => stats.add(&sub.stats),
+                Err(e) => {
+                    *errors.entry(e.to_string()).or_default() += 1;
+                    batch_failed += 1;
+                    if can_roll_back {
+                        break;
+                    }
+                }
+            }
+        }
+        if batch_failed > 0 {
+            failed_batches += 1;
+        }
+        if batch_failed > 0 && can_roll_back {
+            backend.rollback_tx(batch_tx).await?;
+            failed += batch.len() as i64;
+        } else {
+            backend.commit_tx(batch_tx).await?;
+            ctx.stats.add(&stats);
+            committed += batch.len() as i64 - batch_failed;
+            failed += batch_failed;
+        }
+    }
+
+    let error_messages: PropertyMap = errors.into_iter().map(|(message, count)| (message, Value::Int(count))).collect();
+    let row = HashMap::from([
+        ("batches".to_string(), Value::Int(batches)),
+        ("total".to_string(), Value::Int(rows.len() as i64)),
+        ("timeTaken".to_string(), Value::Int(started.elapsed().as_secs() as i64)),
+        ("committedOperations".to_string(), Value::Int(committed)),
+        ("failedOperations".to_string(), Value::Int(failed)),
+        ("failedBatches".to_string(), Value::Int(failed_batches)),
+        ("errorMessages".to_string(), Value::Map(error_messages)),
+    ]);
+    Ok(ProcedureResult {
+        columns: ["batches", "total", "timeTaken", "committedOperations", "failedOperations", "failedBatches", "errorMessages"]
+            .map(String::from)
+            .to_vec(),
+        rows: vec![row],
+    })
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn string_arg(value: &Value) -> Result<&str> {
+    match value {
+        Value::String(s) => Ok(s.as_str()),
+        other => Err(Error::TypeError { expected: "String".into(), got: other.type_name().into(), span: None }),
+    }
+}
+
+/// `config.batchSize` and `config.params`; other APOC options are ignored.
+fn config_arg(config: &Value) -> Result<(usize, PropertyMap)> {
+    let config = match config {
+        Value::Null => return Ok((DEFAULT_BATCH_SIZE, PropertyMap::new())),
+        Value::Map(m) => m,
+        other => return Err(Error::TypeError { expected: "Map".into(), got: other.type_name().into(), span: None }),
+    };
+    let batch_size = match config.get("batchSize") {
+        None | Some(Value::Null) => DEFAULT_BATCH_SIZE,
+        Some(Value::Int(n)) if *n > 0 => *n as usize,
+        Some(other) => {
+            return Err(Error::ExecutionError(format!(
+                "apoc.periodic.iterate: batchSize must be a positive integer, got {other:?}"
+            )));
+        }
+    };
+    let params = match config.get("params") {
+        None | Some(Value::Null) => PropertyMap::new(),
+        Some(Value::Map(m)) => m.clone(),
+        Some(other) => return Err(Error::TypeError { expected: "Map".into(), got: other.type_name().into(), span: None }),
+    };
+    Ok((batch_size, params))
+}
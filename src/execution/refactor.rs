@@ -0,0 +1,163 @@
+//! `apoc.refactor.mergeNodes(nodes, config)`.
+//!
+//! Merges every node in the list into the first one, over plain
+//! `StorageBackend` calls so it runs on any backend. In list order, each
+//! later node hands its labels and properties to the first. Its
+//! relationships are recreated on the first node (they get new ids), and
+//! then it is deleted. A relationship between two of the merged nodes
+//! becomes a self-loop on the survivor, the way it does in APOC.
+//!
+//! `config.properties` picks how clashing properties are resolved:
+//!
+//! - `"overwrite"` (default): the later node's value wins.
+//! - `"discard"`: the first node's value is kept.
+//! - `"combine"`: differing values are collected into a list.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::model::{Direction, NodeId, Value};
+use crate::storage::{ProcedureResult, StorageBackend};
+use crate::{Error, Result};
+
+use super::ExecutionStats;
+
+#[derive(Clone, Copy)]
+enum PropertyStrategy {
+    Overwrite,
+    Discard,
+    Combine,
+}
+
+pub(super) async fn merge_nodes<B: StorageBackend>(
+    backend: &B,
+    tx: &mut B::Tx,
+    args: &[Value],
+    stats: &mut ExecutionStats,
+) -> Result<ProcedureResult> {
+    let (nodes, config) = match args {
+        [nodes] => (nodes, &Value::Null),
+        [nodes, config] => (nodes, config),
+        _ => return Err(Error::ExecutionError("apoc.refactor.mergeNodes expects (nodes[, config])".into())),
+    };
+    let mut ids: Vec<NodeId> = Vec::new();
+    match nodes {
+        Value::List(nodes) => {
+            for node in nodes {
+                let id = match node {
+                    Value::Node(n) => n.id,
+                    Value::Int(id) => match u64::try_from(*id) {
+                        Ok(id) => NodeId(id),
+                        Err(_) => {
+                            let got = format!("Integer {id}");
+                            return Err(Error::TypeError { expected: "Node or node id".into(), got, span: None });
+                        }
+                    },
+                    other => {
+                        return Err(Error::TypeError { expected: "Node".into(), got: other.type_name().into(), span: None });
+                    }
+                };
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        other => return Err(Error::TypeError { expected: "List".into(), got: other.type_name().into(), span: None }),
+    }
+    let strategy = property_strategy(config)?;
+
+    let mut result = ProcedureResult { columns: vec!["node".into()], rows: Vec::new() };
+    let Some((&target, others)) = ids.split_first() else {
+        return Ok(result);
+    };
+    let merged: HashSet<NodeId> = ids.iter().copied().collect();
+    let mut survivor = fetch(backend, tx, target).await?;
+
+    for &id in others {
+        let node = fetch(backend, tx, id).await?;
+        for label in &node.labels {
+            if !survivor.labels.contains(label) {
+                backend.add_label(tx, target, label).await?;
+                survivor.labels.push(label.clone());
+                stats.labels_added += 1;
+            }
+        }
+        for (key, value) in node.properties {
+            let merged_value = match (strategy, survivor.properties.get(&key)) {
+                (_, None) | (PropertyStrategy::Overwrite, Some(_)) => value,
+                (PropertyStrategy::Discard, Some(_)) => continue,
+                (PropertyStrategy::Combine, Some(existing)) => match combine(existing, value) {
+                    Some(combined) => combined,
+                    None => continue,
+                },
+            };
+            backend.set_node_property(tx, target, &key, merged_value.clone()).await?;
+            survivor.properties.insert(key, merged_value);
+            stats.properties_set += 1;
+        }
+
+        // A self-loop is listed once per end; move it once
+        let mut moved = HashSet::new();
+        for rel in backend.get_relationships(tx, id, Direction::Both, None).await? {
+            if !moved.insert(rel.id) {
+                continue;
+            }
+            let end = |n: NodeId| if merged.contains(&n) { target } else { n };
+            backend.create_relationship(tx, end(rel.src), end(rel.dst), &rel.rel_type, rel.properties).await?;
+            stats.relationships_created += 1;
+            if backend.delete_relationship(tx, rel.id).await? {
+                stats.relationships_deleted += 1;
+            }
+        }
+        if backend.delete_node(tx, id).await? {
+            stats.nodes_deleted += 1;
+        }
+    }
+
+    let survivor = fetch(backend, tx, target).await?;
+    result.rows.push(HashMap::from([("node".to_string(), Value::Node(Box::new(survivor)))]));
+    Ok(result)
+}
+
+async fn fetch<B: StorageBackend>(backend: &B, tx: &B::Tx, id: NodeId) -> Result<crate::model::Node> {
+    backend.get_node(tx, id).await?.ok_or_else(|| Error::NotFound(format!("Node {}", id.0)))
+}
+
+fn property_strategy(config: &Value) -> Result<PropertyStrategy> {
+    let strategy = match config {
+        Value::Null => None,
+        Value::Map(m) => m.get("properties"),
+        other => return Err(Error::TypeError { expected: "Map".into(), got: other.type_name().into(), span: None }),
+    };
+    match strategy {
+        None | Some(Value::Null) => Ok(PropertyStrategy::Overwrite),
+        Some(Value::String(s)) => match s.to_ascii_lowercase().as_str() {
+            "overwrite" | "override" => Ok(PropertyStrategy::Overwrite),
+            "discard" => Ok(PropertyStrategy::Discard),
+            "combine" => Ok(PropertyStrategy::Combine),
+            _ => Err(Error::ExecutionError(format!(
+                "apoc.refactor.mergeNodes: unknown properties strategy '{s}', expected overwrite, discard or combine"
+            ))),
+        },
+        Some(other) => Err(Error::TypeError { expected: "String".into(), got: other.type_name().into(), span: None }),
+    }
+}
+
+/// `existing` and `value` as one list of distinct values, or None when
+/// `value` adds nothing.
+fn combine(existing: &Value, value: Value) -> Option<Value> {
+    let mut items = match existing {
+        Value::List(items) => items.clone(),
+        other => vec![other.clone()],
+    };
+    let added = match value {
+        Value::List(values) => values,
+        other => vec![other],
+    };
+    let before = items.len();
+    for v in added {
+        if !items.iter().any(|i| i.equivalent(&v)) {
+            items.push(v);
+        }
+    }
+    (items.len() > before).then_some(Value::List(items))
+}
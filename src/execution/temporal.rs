@@ -0,0 +1,116 @@
+//! Temporal arithmetic for `+` and `-`.
+//!
+//! A duration shifts a date or time the way Neo4j does: months first
+//! (clamping to the end of a shorter month), then days, then seconds. A
+//! `Date` ignores the duration's time part and a `Time` its months and days.
+//! Subtracting two values of the same temporal type gives the duration
+//! between them.
+
+use chrono::{Months, NaiveDateTime, NaiveTime, TimeDelta};
+
+use crate::model::Value;
+use crate::model::value::IsoDuration;
+use crate::{Error, Result};
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// `left + right`, or None when neither side is temporal.
+pub(super) fn add(left: &Value, right: &Value) -> Option<Result<Value>> {
+    match (left, right) {
+        (Value::Duration(a), Value::Duration(b)) => Some(combine(a, b, 1)),
+        (Value::Duration(d), t) | (t, Value::Duration(d)) if is_instant(t) => Some(shift(t, d, 1)),
+        (a, b) if is_temporal(a) || is_temporal(b) => Some(Err(type_error("+", a, b))),
+        _ => None,
+    }
+}
+
+/// `left - right`, or None when neither side is temporal.
+pub(super) fn sub(left: &Value, right: &Value) -> Option<Result<Value>> {
+    let between = match (left, right) {
+        (Value::Duration(a), Value::Duration(b)) => return Some(combine(a, b, -1)),
+        (t, Value::Duration(d)) if is_instant(t) => return Some(shift(t, d, -1)),
+        (Value::Date(a), Value::Date(b)) => a.signed_duration_since(*b),
+        (Value::Time(a), Value::Time(b)) => a.signed_duration_since(*b),
+        (Value::DateTime(a), Value::DateTime(b)) => a.signed_duration_since(*b),
+        (Value::LocalDateTime(a), Value::LocalDateTime(b)) => a.signed_duration_since(*b),
+        (a, b) if is_temporal(a) || is_temporal(b) => return Some(Err(type_error("-", a, b))),
+        _ => return None,
+    };
+    Some(Ok(Value::Duration(from_delta(between))))
+}
+
+fn is_instant(v: &Value) -> bool {
+    matches!(v, Value::Date(_) | Value::Time(_) | Value::DateTime(_) | Value::LocalDateTime(_))
+}
+
+fn is_temporal(v: &Value) -> bool {
+    is_instant(v) || matches!(v, Value::Duration(_))
+}
+
+/// `a + sign * b`, component-wise, carrying nanoseconds into seconds.
+fn combine(a: &IsoDuration, b: &IsoDuration, sign: i64) -> Result<Value> {
+    let component = |x: i64, y: i64| sign.checked_mul(y).and_then(|y| x.checked_add(y));
+    let nanos = i64::from(a.nanoseconds) + sign * i64::from(b.nanoseconds);
+    let combined = (|| {
+        Some(IsoDuration {
+            months: component(a.months, b.months)?,
+            days: component(a.days, b.days)?,
+            seconds: component(a.seconds, b.seconds)?.checked_add(nanos.div_euclid(NANOS_PER_SECOND))?,
+            nanoseconds: nanos.rem_euclid(NANOS_PER_SECOND) as i32,
+        })
+    })();
+    combined.map(Value::Duration).ok_or_else(|| {
+        let op = if sign < 0 { "-" } else { "+" };
+        Error::ExecutionError(format!("Duration out of range: {} {op} {}", Value::Duration(*a), Value::Duration(*b)))
+    })
+}
+
+/// The duration between two instants, as whole days plus seconds.
+fn from_delta(delta: TimeDelta) -> IsoDuration {
+    let seconds = delta.num_seconds();
+    IsoDuration {
+        months: 0,
+        days: seconds / SECONDS_PER_DAY,
+        seconds: seconds % SECONDS_PER_DAY,
+        nanoseconds: delta.subsec_nanos(),
+    }
+}
+
+/// `t + sign * d`.
+fn shift(t: &Value, d: &IsoDuration, sign: i64) -> Result<Value> {
+    let date_part = IsoDuration { seconds: 0, nanoseconds: 0, ..*d };
+    let shifted = match t {
+        Value::Date(date) => {
+            shift_naive(date.and_time(NaiveTime::MIN), &date_part, sign).map(|dt| Value::Date(dt.date()))
+        }
+        Value::LocalDateTime(dt) => shift_naive(*dt, d, sign).map(Value::LocalDateTime),
+        Value::DateTime(dt) => shift_naive(dt.naive_utc(), d, sign).map(|dt| Value::DateTime(dt.and_utc())),
+        // Times of day wrap around midnight
+        Value::Time(time) => time_delta(0, d.seconds, d.nanoseconds, sign)
+            .map(|delta| Value::Time(time.overflowing_add_signed(delta).0)),
+        other => return Err(type_error(if sign < 0 { "-" } else { "+" }, other, &Value::Duration(*d))),
+    };
+    shifted.ok_or_else(|| Error::ExecutionError(format!("Temporal value out of range: {t} shifted by {}", Value::Duration(*d))))
+}
+
+fn shift_naive(dt: NaiveDateTime, d: &IsoDuration, sign: i64) -> Option<NaiveDateTime> {
+    let months = sign.checked_mul(d.months)?;
+    let step = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    let dt = if months >= 0 { dt.checked_add_months(step)? } else { dt.checked_sub_months(step)? };
+    dt.checked_add_signed(time_delta(d.days, d.seconds, d.nanoseconds, sign)?)
+}
+
+fn time_delta(days: i64, seconds: i64, nanoseconds: i32, sign: i64) -> Option<TimeDelta> {
+    TimeDelta::try_days(sign.checked_mul(days)?)?
+        .checked_add(&TimeDelta::try_seconds(sign.checked_mul(seconds)?)?)?
+        .checked_add(&TimeDelta::nanoseconds(sign * i64::from(nanoseconds)))
+}
+
+fn type_error(op: &str, left: &Value, right: &Value) -> Error {
+    Error::TypeError {
+        expected: format!("compatible types for {op}"),
+        got: format!("{}, {}", left.type_name(), right.type_name()),
+        span: None,
+    }
+}
@@ -0,0 +1,172 @@
+//! `apoc.text.*` string functions.
+//!
+//! Each takes already-evaluated arguments and returns null when its text
+//! argument is null. `regexGroups` needs the `regex` feature.
+
+use crate::model::Value;
+use crate::{Error, Result};
+
+/// Dispatch `apoc.text.<name>`; `name` is upper-cased, without the prefix.
+pub(super) fn call(name: &str, args: &[Value]) -> Result<Value> {
+    match name {
+        "JOIN" => {
+            let [list, delimiter] = args else { return Err(arity("join", "list, delimiter")) };
+            join(list, delimiter)
+        }
+        "FORMAT" => {
+            let [template, params] = args else { return Err(arity("format", "template, params")) };
+            format(template, params)
+        }
+        "SLUG" => match args {
+            [text] => slug(text, &Value::Null),
+            [text, delimiter] => slug(text, delimiter),
+            _ => Err(arity("slug", "text[, delimiter]")),
+        },
+        "REGEXGROUPS" => {
+            let [text, pattern] = args else { return Err(arity("regexGroups", "text, regex")) };
+            match (text, pattern) {
+                (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+                (text, pattern) => regex_groups(as_str(text)?, as_str(pattern)?),
+            }
+        }
+        _ => Err(Error::ExecutionError(format!("Unknown function: apoc.text.{}", name.to_lowercase()))),
+    }
+}
+
+/// `apoc.text.join(['a', 'b'], '-')` → `'a-b'`. Null elements are skipped.
+fn join(list: &Value, delimiter: &Value) -> Result<Value> {
+    let (list, delimiter) = match (list, delimiter) {
+        (Value::Null, _) | (_, Value::Null) => return Ok(Value::Null),
+        (Value::List(list), delimiter) => (list, as_str(delimiter)?),
+        (other, _) => return Err(type_error("List", other)),
+    };
+    let parts = list
+        .iter()
+        .filter(|v| !v.is_null())
+        .map(as_str)
+        .collect::<Result<Vec<&str>>>()?;
+    Ok(Value::String(parts.join(delimiter)))
+}
+
+/// `apoc.text.format('%s is %d', ['Ada', 36])`: a subset of Java's
+/// `String.format`. `%s` renders any value, `%d` needs an integer, `%f`
+/// (optionally `%.Nf`) a number, and `%%` is a literal `%`.
+fn format(template: &Value, params: &Value) -> Result<Value> {
+    let template = match template {
+        Value::Null => return Ok(Value::Null),
+        other => as_str(other)?,
+    };
+    let params: &[Value] = match params {
+        Value::Null => &[],
+        Value::List(l) => l,
+        other => return Err(type_error("List", other)),
+    };
+
+    let mut out = String::with_capacity(template.len());
+    let mut params = params.iter();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let mut precision = None;
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            let mut digits = String::new();
+            while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                digits.push(d);
+            }
+            precision = Some(digits.parse::<usize>().map_err(|_| bad_format(template))?);
+        }
+        let spec = chars.next().ok_or_else(|| bad_format(template))?;
+        if spec == '%' {
+            out.push('%');
+            continue;
+        }
+        let arg = params.next().ok_or_else(|| {
+            Error::ExecutionError(format!("apoc.text.format: too few parameters for '{template}'"))
+        })?;
+        match (spec, arg) {
+            ('s', Value::String(s)) => out.push_str(s),
+            ('s', v) => out.push_str(&v.to_string()),
+            ('d', Value::Int(i)) => out.push_str(&i.to_string()),
+            ('d', v) => return Err(type_error("Integer", v)),
+            ('f', v) => match v.as_float() {
+                Some(f) => out.push_str(&format!("{f:.*}", precision.unwrap_or(6))),
+                None => return Err(type_error("Float", v)),
+            },
+            _ => return Err(bad_format(template)),
+        }
+    }
+    Ok(Value::String(out))
+}
+
+/// `apoc.text.slug(' Hello, World ')` → `'Hello-World'`: trims, then
+/// replaces each run of non-word characters with `delimiter` (default `-`).
+fn slug(text: &Value, delimiter: &Value) -> Result<Value> {
+    let text = match text {
+        Value::Null => return Ok(Value::Null),
+        other => as_str(other)?,
+    };
+    let delimiter = match delimiter {
+        Value::Null => "-",
+        other => as_str(other)?,
+    };
+    let mut out = String::with_capacity(text.len());
+    let mut in_run = false;
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() || c == '_' {
+            out.push(c);
+            in_run = false;
+        } else if !in_run {
+            out.push_str(delimiter);
+            in_run = true;
+        }
+    }
+    Ok(Value::String(out))
+}
+
+/// `apoc.text.regexGroups('a1 b2', '([a-z])(\\d)')` → `[['a1', 'a', '1'], ['b2', 'b', '2']]`:
+/// for each match, the whole match followed by its groups (null when a
+/// group took no part in the match).
+#[cfg(feature = "regex")]
+fn regex_groups(text: &str, pattern: &str) -> Result<Value> {
+    let re = regex::Regex::new(pattern)
+        .map_err(|e| Error::ExecutionError(format!("apoc.text.regexGroups: invalid regex: {e}")))?;
+    let matches = re
+        .captures_iter(text)
+        .map(|caps| {
+            Value::List(
+                caps.iter()
+                    .map(|g| g.map_or(Value::Null, |m| Value::String(m.as_str().to_string())))
+                    .collect(),
+            )
+        })
+        .collect();
+    Ok(Value::List(matches))
+}
+
+#[cfg(not(feature = "regex"))]
+fn regex_groups(_text: &str, _pattern: &str) -> Result<Value> {
+    Err(Error::ExecutionError("apoc.text.regexGroups requires the regex feature".into()))
+}
+
+fn as_str(v: &Value) -> Result<&str> {
+    match v {
+        Value::String(s) => Ok(s),
+        other => Err(type_error("String", other)),
+    }
+}
+
+fn type_error(expected: &str, got: &Value) -> Error {
+    Error::TypeError { expected: expected.into(), got: got.type_name().into(), span: None }
+}
+
+fn arity(name: &str, signature: &str) -> Error {
+    Error::ExecutionError(format!("apoc.text.{name}() expects ({signature})"))
+}
+
+fn bad_format(template: &str) -> Error {
+    Error::ExecutionError(format!("apoc.text.format: unsupported format '{template}'"))
+}
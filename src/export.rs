@@ -8,12 +8,17 @@
 //! neo4j-rs Graph → export_cypher_dump() → CREATE/MERGE statements
 //!   → pipe into neo4j-admin import, or paste into Neo4j Browser
 //! ```
+//!
+//! `export_json` / `import_json` instead round-trip a whole graph through
+//! JSON, for seeding test graphs and readable, diffable backups.
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::Write;
+use serde::{Deserialize, Serialize};
 use crate::model::*;
 use crate::storage::StorageBackend;
 use crate::tx::TxMode;
-use crate::Result;
+use crate::{Error, Result};
 
 /// Export a graph as a Cypher DUMP script.
 ///
@@ -88,6 +93,109 @@ pub async fn export_cypher_dump<B: StorageBackend>(
     Ok(())
 }
 
+/// A whole graph as JSON. Nodes and relationships are sorted by id and
+/// properties by key, so exports of the same graph are identical.
+#[derive(Serialize, Deserialize)]
+struct JsonGraph {
+    nodes: Vec<JsonNode>,
+    relationships: Vec<JsonRelationship>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonNode {
+    id: u64,
+    labels: Vec<String>,
+    properties: BTreeMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonRelationship {
+    id: u64,
+    #[serde(rename = "type")]
+    rel_type: String,
+    start: u64,
+    end: u64,
+    properties: BTreeMap<String, Value>,
+}
+
+/// Export a graph as `{"nodes": [...], "relationships": [...]}`.
+///
+/// Relationships refer to nodes by their `id` in the export. Property
+/// values keep their type, e.g. `{"type": "Int", "value": 30}`.
+pub async fn export_json<B: StorageBackend>(backend: &B) -> Result<serde_json::Value> {
+    let tx = backend.begin_tx(TxMode::ReadOnly).await?;
+    let mut nodes = backend.all_nodes(&tx).await?;
+    nodes.sort_by_key(|n| n.id);
+    let mut relationships = Vec::new();
+    for node in &nodes {
+        relationships.extend(backend.get_relationships(&tx, node.id, Direction::Outgoing, None).await?);
+    }
+    backend.commit_tx(tx).await?;
+    relationships.sort_by_key(|r| r.id.0);
+
+    let graph = JsonGraph {
+        nodes: nodes
+            .into_iter()
+            .map(|n| JsonNode { id: n.id.0, labels: n.labels, properties: n.properties.into_iter().collect() })
+            .collect(),
+        relationships: relationships
+            .into_iter()
+            .map(|r| JsonRelationship {
+                id: r.id.0,
+                rel_type: r.rel_type,
+                start: r.src.0,
+                end: r.dst.0,
+                properties: r.properties.into_iter().collect(),
+            })
+            .collect(),
+    };
+    serde_json::to_value(&graph).map_err(|e| Error::ExecutionError(format!("JSON export failed: {e}")))
+}
+
+/// Recreate the nodes and relationships of an `export_json` document in
+/// one transaction, alongside whatever the graph already holds.
+///
+/// Nodes get new ids; relationships are attached to the new ids of their
+/// endpoints. Returns the old → new node id mapping.
+pub async fn import_json<B: StorageBackend>(backend: &B, value: &serde_json::Value) -> Result<HashMap<NodeId, NodeId>> {
+    let graph = JsonGraph::deserialize(value)
+        .map_err(|e| Error::ExecutionError(format!("Invalid graph JSON: {e}")))?;
+    let mut tx = backend.begin_tx(TxMode::ReadWrite).await?;
+    match import_in(backend, &mut tx, graph).await {
+        Ok(ids) => {
+            backend.commit_tx(tx).await?;
+            Ok(ids)
+        }
+        Err(e) => {
+            let _ = backend.rollback_tx(tx).await;
+            Err(e)
+        }
+    }
+}
+
+async fn import_in<B: StorageBackend>(backend: &B, tx: &mut B::Tx, graph: JsonGraph) -> Result<HashMap<NodeId, NodeId>> {
+    // Check every endpoint before writing, since not every backend can
+    // roll back what was already created
+    let exported: HashSet<u64> = graph.nodes.iter().map(|n| n.id).collect();
+    for rel in &graph.relationships {
+        if let Some(missing) = [rel.start, rel.end].into_iter().find(|id| !exported.contains(id)) {
+            return Err(Error::ExecutionError(format!("Relationship {} refers to missing node {missing}", rel.id)));
+        }
+    }
+
+    let mut ids = HashMap::with_capacity(graph.nodes.len());
+    for node in graph.nodes {
+        let labels: Vec<&str> = node.labels.iter().map(String::as_str).collect();
+        let id = backend.create_node(tx, &labels, node.properties.into_iter().collect()).await?;
+        ids.insert(NodeId(node.id), id);
+    }
+    for rel in graph.relationships {
+        let (src, dst) = (ids[&NodeId(rel.start)], ids[&NodeId(rel.end)]);
+        backend.create_relationship(tx, src, dst, &rel.rel_type, rel.properties.into_iter().collect()).await?;
+    }
+    Ok(ids)
+}
+
 /// Format a PropertyMap as Cypher property string (key: value, ...).
 fn format_properties(props: &PropertyMap) -> String {
     let mut parts = Vec::new();
@@ -21,7 +21,7 @@
 //! // Execute Cypher
 //! let mut params = PropertyMap::new();
 //! params.insert("name".into(), Value::from("Ada"));
-//! let result = graph.execute(
+//! let result = graph.run(
 //!     "CREATE (n:Person {name: $name}) RETURN n",
 //!     params,
 //! ).await?;
@@ -48,11 +48,14 @@
 pub mod model;
 pub mod cypher;
 pub mod planner;
+pub mod query;
 pub mod execution;
 pub mod storage;
 pub mod tx;
 pub mod index;
 pub mod export;
+pub mod schema;
+pub mod plan_cache;
 #[cfg(feature = "chess")]
 pub mod chess;
 #[cfg(feature = "chess")]
@@ -63,8 +66,8 @@ pub mod aiwar;
 // ============================================================================
 
 pub use model::{
-    Node, Relationship, Path, Value, PropertyMap,
-    NodeId, RelId, Direction,
+    Node, Relationship, Path, Value, PropertyMap, PropertyMapBuilder, PropertySchema, PropertyType,
+    NodeId, RelId, Direction, Collation,
 };
 
 // ============================================================================
@@ -72,8 +75,8 @@ pub use model::{
 // ============================================================================
 
 pub use storage::{
-    StorageBackend, BackendConfig, ConstraintType,
-    BackendCapabilities, ProcedureResult,
+    StorageBackend, BackendConfig, ConstraintType, ExpandDepth, ExpandLimits,
+    BackendCapabilities, ProcedureResult, FsyncPolicy,
 };
 
 // ============================================================================
@@ -82,11 +85,17 @@ pub use storage::{
 
 pub use tx::{Transaction, TxMode, TxId};
 
+pub use schema::SchemaRegistry;
+use schema::SchemaChanges;
+use plan_cache::PlanCache;
+
+pub use planner::Notification;
+
 // ============================================================================
 // Re-exports: Execution
 // ============================================================================
 
-pub use execution::{QueryResult, ResultRow};
+pub use execution::{ExecOptions, NoopObserver, QueryObserver, QueryResult, ResultRow, RowLimits, TracingObserver};
 
 // ============================================================================
 // Top-level Graph handle
@@ -96,67 +105,555 @@ pub use execution::{QueryResult, ResultRow};
 /// provides Cypher execution.
 pub struct Graph<B: StorageBackend> {
     backend: B,
-    // Future: schema cache, index registry, prepared statement cache
+    options: ExecOptions,
+    retry: RetryPolicy,
+    schema: SchemaRegistry,
+    plans: PlanCache,
 }
 
 impl<B: StorageBackend> Graph<B> {
     /// Create a Graph with the given backend.
     pub fn with_backend(backend: B) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            options: ExecOptions::default(),
+            retry: RetryPolicy::default(),
+            schema: SchemaRegistry::new(),
+            plans: PlanCache::new(plan_cache::DEFAULT_PLAN_CACHE_SIZE),
+        }
+    }
+
+    /// Make `rand()` and `randomUUID()` reproducible: queries on this graph
+    /// draw from one sequence seeded with `seed` instead of the thread RNG.
+    ///
+    /// Either way both functions are evaluated per call, so every row of
+    /// `MATCH (n) WHERE rand() < 0.1 RETURN n` gets its own draw.
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.options.random = execution::RandomSource::seeded(seed);
+        self
+    }
+
+    /// Order strings by `collation` in `<`, `>`, ORDER BY, `min()` and
+    /// `max()` of every query on this graph. Defaults to `Collation::ByteOrder`.
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.options.collation = collation;
+        self
+    }
+
+    /// Fail queries on this graph with "result set too large" once they
+    /// would return more than `max` rows.
+    pub fn with_max_result_rows(mut self, max: usize) -> Self {
+        self.options.limits.max_result_rows = Some(max);
+        self
+    }
+
+    /// Fail queries on this graph with "result set too large" once any one
+    /// operator produces more than `max` rows. Expansions, UNWIND and joins
+    /// stop at the row that passes `max`, and a cartesian product is refused
+    /// before it is built; other operators are checked once they finish.
+    pub fn with_max_intermediate_rows(mut self, max: usize) -> Self {
+        self.options.limits.max_intermediate_rows = Some(max);
+        self
     }
 
-    /// Execute a Cypher query with parameters.
+    /// Read `LOAD CSV` `file://` urls relative to `dir`, refusing any path
+    /// that resolves outside it. Without this, `file://` urls are refused.
+    pub fn with_import_root(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.options.import_root = Some(dir.into());
+        self
+    }
+
+    /// Report the operators of every query on this graph to `observer`,
+    /// for metrics or tracing; see `QueryObserver`.
+    pub fn with_observer(mut self, observer: impl QueryObserver + 'static) -> Self {
+        self.options.observer = std::sync::Arc::new(observer);
+        self
+    }
+
+    /// Keep at most `capacity` optimized plans for reuse by queries that
+    /// parse to the same plan (see `plan_cache`); zero turns caching off.
+    pub fn with_plan_cache_size(mut self, capacity: usize) -> Self {
+        self.plans = PlanCache::new(capacity);
+        self
+    }
+
+    /// Execute a read query with parameters, in a read-only transaction.
+    /// Fails before touching the backend if the query writes; use `mutate`
+    /// or `run` for those.
     pub async fn execute<P>(&self, query: &str, params: P) -> Result<QueryResult>
     where
         P: Into<PropertyMap>,
     {
         let params = params.into();
-        let ast = cypher::parse(query)?;
-        let logical = planner::plan(&ast, &params)?;
-        let optimized = planner::optimize(logical)?;
+        let compiled = compile(query, &params)?;
+        expect_mode(&compiled.plan, TxMode::ReadOnly)?;
 
         let mut tx = self.backend.begin_tx(TxMode::ReadOnly).await?;
-        let result = execution::execute(&self.backend, &mut tx, optimized, params).await?;
-        self.backend.commit_tx(tx).await?;
+        let (result, changes) = self.run_plan(&mut tx, compiled, params).await?;
+        self.commit(tx, changes).await?;
 
         Ok(result)
     }
 
-    /// Execute a write query (CREATE, MERGE, DELETE, SET, etc.)
+    /// Like `execute`, but first runs `planner::typecheck` against `schema`
+    /// and fails with a `SemanticError` before touching the backend.
+    pub async fn execute_checked<P>(
+        &self,
+        query: &str,
+        params: P,
+        schema: &planner::Schema,
+    ) -> Result<QueryResult>
+    where
+        P: Into<PropertyMap>,
+    {
+        let params = params.into();
+        let compiled = compile(query, &params)?;
+        planner::typecheck(&compiled.plan, schema)?;
+        expect_mode(&compiled.plan, TxMode::ReadOnly)?;
+
+        let mut tx = self.backend.begin_tx(TxMode::ReadOnly).await?;
+        let (result, changes) = self.run_plan(&mut tx, compiled, params).await?;
+        self.commit(tx, changes).await?;
+
+        Ok(result)
+    }
+
+    /// The optimized plan of `query` as JSON, for tooling: each operator
+    /// with its fields, estimated rows and children (see
+    /// `planner::plan_json`). The query is checked and planned exactly as
+    /// running it would be, against current label counts, but not executed.
+    pub async fn explain_json<P>(&self, query: &str, params: P) -> Result<serde_json::Value>
+    where
+        P: Into<PropertyMap>,
+    {
+        let params = params.into();
+        let compiled = compile(query, &params)?;
+
+        let tx = self.backend.begin_tx(TxMode::ReadOnly).await?;
+        let planned = self.optimize(&tx, compiled.plan, true).await;
+        self.backend.commit_tx(tx).await?;
+        let (optimized, stats) = planned?;
+        Ok(planner::plan_json(&optimized, &stats))
+    }
+
+    /// Execute a write query (CREATE, MERGE, DELETE, SET, etc.), in a
+    /// read-write transaction. Fails before touching the backend if the
+    /// query doesn't write; use `execute` or `run` for those.
     pub async fn mutate<P>(&self, query: &str, params: P) -> Result<QueryResult>
     where
         P: Into<PropertyMap>,
     {
         let params = params.into();
-        let ast = cypher::parse(query)?;
-        let logical = planner::plan(&ast, &params)?;
-        let optimized = planner::optimize(logical)?;
+        let compiled = compile(query, &params)?;
+        expect_mode(&compiled.plan, TxMode::ReadWrite)?;
 
         let mut tx = self.backend.begin_tx(TxMode::ReadWrite).await?;
-        let result = execution::execute(&self.backend, &mut tx, optimized, params).await?;
-        self.backend.commit_tx(tx).await?;
+        let (result, changes) = self.run_plan(&mut tx, compiled, params).await?;
+        self.commit(tx, changes).await?;
+
+        Ok(result)
+    }
+
+    /// Execute any query, in a read-write transaction if it writes
+    /// (`planner::plan_writes`) and a read-only one otherwise.
+    pub async fn run<P>(&self, query: &str, params: P) -> Result<QueryResult>
+    where
+        P: Into<PropertyMap>,
+    {
+        let params = params.into();
+        let compiled = compile(query, &params)?;
+        let mode = if planner::plan_writes(&compiled.plan) { TxMode::ReadWrite } else { TxMode::ReadOnly };
+
+        let mut tx = self.backend.begin_tx(mode).await?;
+        let (result, changes) = self.run_plan(&mut tx, compiled, params).await?;
+        self.commit(tx, changes).await?;
+
+        Ok(result)
+    }
+
+    /// Execute an already-built plan, such as one from `query::QueryBuilder`,
+    /// choosing the transaction mode the way `run` does.
+    pub async fn run_logical<P>(&self, logical: planner::LogicalPlan, params: P) -> Result<QueryResult>
+    where
+        P: Into<PropertyMap>,
+    {
+        let mode = if planner::plan_writes(&logical) { TxMode::ReadWrite } else { TxMode::ReadOnly };
+
+        let mut tx = self.backend.begin_tx(mode).await?;
+        let compiled = Compiled { plan: logical, timings: PhaseTimings::default(), notifications: Vec::new() };
+        let (result, changes) = self.run_plan(&mut tx, compiled, params.into()).await?;
+        self.commit(tx, changes).await?;
 
         Ok(result)
     }
 
+    /// Find the `label` node whose `key` equals `value`, or create it.
+    ///
+    /// Lookup and creation run in one read-write transaction. `props` only
+    /// apply on creation; `key` is always set to `value` on the new node.
+    /// Backend unique constraints are enforced by `create_node` as usual.
+    pub async fn merge_node(
+        &self,
+        label: &str,
+        key: &str,
+        value: Value,
+        props: PropertyMap,
+    ) -> Result<Node> {
+        let mut tx = self.backend.begin_tx(TxMode::ReadWrite).await?;
+        match self.merge_node_in(&mut tx, label, key, value, props).await {
+            Ok(node) => {
+                self.backend.commit_tx(tx).await?;
+                Ok(node)
+            }
+            Err(e) => {
+                let _ = self.backend.rollback_tx(tx).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn merge_node_in(
+        &self,
+        tx: &mut B::Tx,
+        label: &str,
+        key: &str,
+        value: Value,
+        mut props: PropertyMap,
+    ) -> Result<Node> {
+        let existing = self.backend.nodes_by_property(tx, label, key, &value).await?;
+        if let Some(node) = existing.into_iter().next() {
+            return Ok(node);
+        }
+        props.insert(key.to_string(), value);
+        let id = self.backend.create_node(tx, &[label], props).await?;
+        self.backend
+            .get_node(tx, id)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("node {id:?} vanished after creation")))
+    }
+
+    /// Set how `with_transaction` retries on write conflicts.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Run `f` in a transaction: commit if it returns `Ok`, roll back on `Err`.
+    ///
+    /// If the attempt (closure or commit) fails with a write conflict, the
+    /// closure is re-run in a fresh transaction, up to the graph's
+    /// `RetryPolicy::max_retries` times. `f` may therefore run more than once.
+    pub async fn with_transaction<F, T>(&self, mode: TxMode, mut f: F) -> Result<T>
+    where
+        F: AsyncFnMut(&mut ExplicitTx<'_, B>) -> Result<T>,
+    {
+        let mut retries = 0;
+        loop {
+            let mut tx = self.begin(mode).await?;
+            let outcome = match f(&mut tx).await {
+                Ok(value) => tx.commit().await.map(|()| value),
+                Err(e) => {
+                    let _ = tx.rollback().await;
+                    Err(e)
+                }
+            };
+            match outcome {
+                Err(e) if e.is_write_conflict() && retries < self.retry.max_retries => retries += 1,
+                other => return other,
+            }
+        }
+    }
+
     /// Begin an explicit transaction.
     pub async fn begin(&self, mode: TxMode) -> Result<ExplicitTx<'_, B>> {
         let tx = self.backend.begin_tx(mode).await?;
-        Ok(ExplicitTx { graph: self, tx: Some(tx) })
+        Ok(ExplicitTx { graph: self, tx: Some(tx), schema: SchemaChanges::default() })
     }
 
     /// Access the underlying backend (for advanced use).
     pub fn backend(&self) -> &B {
         &self.backend
     }
+
+    /// The labels, relationship types, indexes and constraints this graph
+    /// knows about. Empty of labels and types until the first query runs.
+    pub fn schema(&self) -> &SchemaRegistry {
+        &self.schema
+    }
+
+    /// Reload labels, relationship types, indexes and constraints from the
+    /// backend, e.g. after writing through `backend()` directly.
+    pub async fn refresh_schema(&self) -> Result<&SchemaRegistry> {
+        let tx = self.backend.begin_tx(TxMode::ReadOnly).await?;
+        self.load_schema(&tx).await?;
+        self.backend.commit_tx(tx).await?;
+        Ok(&self.schema)
+    }
+
+    /// The node with `id`, read in its own read-only transaction.
+    pub async fn get_node(&self, id: NodeId) -> Result<Option<Node>> {
+        let tx = self.backend.begin_tx(TxMode::ReadOnly).await?;
+        let node = self.backend.get_node(&tx, id).await?;
+        self.backend.commit_tx(tx).await?;
+        Ok(node)
+    }
+
+    /// The relationship with `id`, read in its own read-only transaction.
+    pub async fn get_relationship(&self, id: RelId) -> Result<Option<Relationship>> {
+        let tx = self.backend.begin_tx(TxMode::ReadOnly).await?;
+        let rel = self.backend.get_relationship(&tx, id).await?;
+        self.backend.commit_tx(tx).await?;
+        Ok(rel)
+    }
+
+    /// The relationships of node `id` in direction `dir`, optionally of one
+    /// type, each with the node at its other end, read in one read-only
+    /// transaction. A self-loop pairs with `id`'s own node.
+    ///
+    /// For walking the graph from code without Cypher:
+    ///
+    /// ```rust,no_run
+    /// # async fn example(graph: &neo4j_rs::Graph<neo4j_rs::storage::MemoryBackend>, ada: neo4j_rs::NodeId) -> neo4j_rs::Result<()> {
+    /// use neo4j_rs::Direction;
+    /// let since: Vec<_> = graph.neighbors(ada, Direction::Outgoing, Some("KNOWS")).await?
+    ///     .into_iter()
+    ///     .map(|(knows, friend)| (friend.id, knows.properties.get("since").cloned()))
+    ///     .collect();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn neighbors(&self, id: NodeId, dir: Direction, rel_type: Option<&str>) -> Result<Vec<(Relationship, Node)>> {
+        let tx = self.backend.begin_tx(TxMode::ReadOnly).await?;
+        let rels = self.backend.get_relationships(&tx, id, dir, rel_type).await?;
+        let ends: Vec<NodeId> = rels.iter().map(|r| r.other_node(id).unwrap_or(id)).collect();
+        let nodes = self.backend.get_nodes(&tx, &ends).await?;
+        self.backend.commit_tx(tx).await?;
+        Ok(rels.into_iter().zip(nodes).filter_map(|(rel, node)| Some((rel, node?))).collect())
+    }
+
+    /// The whole graph as JSON; see `export::export_json`.
+    pub async fn export_json(&self) -> Result<serde_json::Value> {
+        export::export_json(&self.backend).await
+    }
+
+    /// Recreate an `export_json` document in this graph, giving its nodes
+    /// new ids; see `export::import_json`. Returns the old → new id mapping.
+    pub async fn import_json(&self, value: &serde_json::Value) -> Result<std::collections::HashMap<NodeId, NodeId>> {
+        let ids = export::import_json(&self.backend, value).await?;
+        self.refresh_schema().await?;
+        Ok(ids)
+    }
+
+    async fn load_schema(&self, tx: &B::Tx) -> Result<()> {
+        self.schema.load(schema::LoadedSchema {
+            labels: self.backend.labels(tx).await?,
+            relationship_types: self.backend.relationship_types(tx).await?,
+            indexes: self.backend.indexes().await?,
+            constraints: self.backend.constraints().await?,
+        });
+        Ok(())
+    }
+
+    /// Commit `tx`, then apply the schema changes its statements made.
+    async fn commit(&self, tx: B::Tx, changes: SchemaChanges) -> Result<()> {
+        self.backend.commit_tx(tx).await?;
+        self.schema.apply(changes);
+        Ok(())
+    }
+
+    /// Check `logical` against the backend and optimize it: the one
+    /// planning path behind both execution and `explain_json`. Statistics
+    /// cover the labels joins are ordered by; with `estimates`, also every
+    /// scanned label and the node count, for `plan_json`.
+    async fn optimize(
+        &self,
+        tx: &B::Tx,
+        logical: planner::LogicalPlan,
+        estimates: bool,
+    ) -> Result<(planner::LogicalPlan, planner::Statistics)> {
+        check_index_hints(&self.backend, &logical).await?;
+        planner::check_capabilities(&logical, &self.backend.capabilities())?;
+        let mut labels = planner::join_labels(&logical);
+        if estimates {
+            for label in planner::scan_labels(&logical) {
+                if !labels.contains(&label) {
+                    labels.push(label);
+                }
+            }
+        }
+        let mut stats = plan_statistics(&self.backend, tx, labels).await?;
+        if estimates && stats.node_count.is_none() {
+            stats.node_count = Some(self.backend.node_count(tx).await?);
+        }
+        let optimized = planner::optimize_with_stats(logical, &stats)?;
+        Ok((optimized, stats))
+    }
+
+    /// Optimize and execute a compiled query in `tx`. Returns its schema
+    /// changes, for the registry once `tx` commits.
+    async fn run_plan(
+        &self,
+        tx: &mut B::Tx,
+        compiled: Compiled,
+        params: PropertyMap,
+    ) -> Result<(QueryResult, SchemaChanges)> {
+        let Compiled { plan: logical, mut timings, mut notifications } = compiled;
+        if !self.schema.is_loaded() {
+            self.load_schema(tx).await?;
+        }
+        let started = std::time::Instant::now();
+        let key = logical.plan_hash();
+        let cached = self.plans.get(key);
+        let plan_cached = cached.is_some();
+        let optimized = match cached {
+            Some(plan) => {
+                // The index a hint names may have been dropped since
+                check_index_hints(&self.backend, &plan).await?;
+                plan
+            }
+            None => {
+                let (optimized, _) = self.optimize(tx, logical, false).await?;
+                self.plans.insert(key, optimized.clone());
+                optimized
+            }
+        };
+        notifications.extend(planner::plan_notifications(&optimized));
+        timings.plan += started.elapsed();
+        let mut changes = SchemaChanges::default();
+        changes.add(&optimized);
+        let mut result = execution::execute_with(&self.backend, tx, optimized, params, &self.options).await?;
+        result.notifications = notifications;
+
+        let stats = &mut result.stats;
+        stats.plan_cached = plan_cached;
+        stats.parse_time_us = timings.parse.as_micros() as u64;
+        stats.plan_time_us = timings.plan.as_micros() as u64;
+        stats.execution_time_ms = (stats.parse_time_us + stats.plan_time_us + stats.execute_time_us) / 1000;
+        Ok((result, changes))
+    }
+}
+
+/// A planned query, with what it took to get there.
+struct Compiled {
+    plan: planner::LogicalPlan,
+    timings: PhaseTimings,
+    /// Warnings from the statement itself; the optimized plan adds its own.
+    notifications: Vec<planner::Notification>,
+}
+
+/// Time spent in the phases before execution.
+#[derive(Debug, Clone, Copy, Default)]
+struct PhaseTimings {
+    parse: std::time::Duration,
+    plan: std::time::Duration,
+}
+
+/// Parse and plan `query`, timing both.
+fn compile(query: &str, params: &PropertyMap) -> Result<Compiled> {
+    let started = std::time::Instant::now();
+    let ast = cypher::parse(query)?;
+    let parsed = std::time::Instant::now();
+    let plan = planner::plan(&ast, params)?;
+    let notifications = planner::statement_notifications(&ast);
+    let timings = PhaseTimings { parse: parsed - started, plan: parsed.elapsed() };
+    Ok(Compiled { plan, timings, notifications })
+}
+
+/// Fail if an `IndexLookup` planned for a `USING INDEX` hint names an index
+/// `backend` doesn't have. Plans without one don't ask the backend.
+async fn check_index_hints<B: StorageBackend>(backend: &B, plan: &planner::LogicalPlan) -> Result<()> {
+    let mut lookups = Vec::new();
+    let mut stack = vec![plan];
+    while let Some(p) = stack.pop() {
+        if let planner::LogicalPlan::IndexLookup { label, property, alias, .. } = p {
+            lookups.push((label, property, alias));
+        }
+        stack.extend(planner::plan_inputs(p));
+    }
+    if lookups.is_empty() {
+        return Ok(());
+    }
+    let indexes = backend.indexes().await?;
+    for (label, property, alias) in lookups {
+        if !indexes.iter().any(|(l, p, _)| l == label && p == property) {
+            return Err(plan_err!("Cannot use index hint USING INDEX {alias}:{label}({property}): no such index"));
+        }
+    }
+    Ok(())
+}
+
+/// Check that `plan` suits a `mode` transaction: reads for `execute`,
+/// writes for `mutate`.
+fn expect_mode(plan: &planner::LogicalPlan, mode: TxMode) -> Result<()> {
+    match (mode, planner::plan_writes(plan)) {
+        (TxMode::ReadOnly, true) => Err(Error::TxError(
+            "execute() runs read-only queries, but this query writes; use mutate() or run()".into(),
+        )),
+        (TxMode::ReadWrite, false) => Err(Error::TxError(
+            "mutate() runs write queries, but this query only reads; use execute() or run()".into(),
+        )),
+        _ => Ok(()),
+    }
 }
 
 /// In-memory graph for testing and embedding.
 impl Graph<storage::MemoryBackend> {
+    /// In-memory graph as `config` (`Memory` or `DurableMemory`) describes.
+    pub async fn open(config: BackendConfig) -> Result<Self> {
+        Ok(Self::with_backend(storage::MemoryBackend::open(&config).await?))
+    }
+
     pub async fn open_memory() -> Result<Self> {
         let backend = storage::MemoryBackend::new();
         Ok(Self::with_backend(backend))
     }
+
+    /// In-memory graph whose writes are logged to `wal_path` and replayed
+    /// on the next open. The log grows until `backend().checkpoint()`
+    /// compacts it.
+    pub async fn open_durable(
+        wal_path: impl AsRef<std::path::Path>,
+        fsync: storage::FsyncPolicy,
+    ) -> Result<Self> {
+        let backend = storage::MemoryBackend::open_durable(wal_path, fsync).await?;
+        Ok(Self::with_backend(backend))
+    }
+}
+
+/// A Neo4j server over Bolt. Queries are planned and executed here, their
+/// reads and writes forwarded to the server; `execute_stream` hands the
+/// server the whole query instead.
+#[cfg(feature = "bolt")]
+impl Graph<storage::BoltBackend> {
+    /// Connect to the Neo4j server at `uri` (`bolt://host:port`).
+    pub fn open_bolt(uri: &str, user: &str, password: &str) -> Result<Self> {
+        Ok(Self::with_backend(storage::BoltBackend::new(uri, user, password)?))
+    }
+
+    /// Run a read query on the server as written, in an auto-commit
+    /// transaction of its own, and stream its records: they are fetched
+    /// the backend's fetch size at a time (`PULL n`) as the stream is read,
+    /// never buffered whole.
+    pub async fn execute_stream<P>(&self, query: &str, params: P) -> Result<storage::RecordStream>
+    where
+        P: Into<PropertyMap>,
+    {
+        self.backend.stream_autocommit(query, params.into(), TxMode::ReadOnly).await
+    }
+
+    /// Run a read query on the server like `execute_stream`, and hand its
+    /// records out `page_size` at a time: each page is pulled from the
+    /// server when asked for, so only one page is held at once.
+    pub async fn execute_paged<P>(&self, query: &str, params: P, page_size: usize) -> Result<storage::PagedResult>
+    where
+        P: Into<PropertyMap>,
+    {
+        if page_size == 0 {
+            return Err(Error::ExecutionError("page size must be at least 1".into()));
+        }
+        let stream = self.execute_stream(query, params).await?;
+        Ok(storage::PagedResult::new(stream, page_size))
+    }
 }
 
 /// Ladybug-rs backed graph — production engine.
@@ -169,6 +666,12 @@ impl Graph<storage::LadybugBackend> {
         Self::with_backend(backend)
     }
 
+    /// Open a graph backed by ladybug-rs as a `BackendConfig::Ladybug`
+    /// describes.
+    pub fn open_ladybug_with(config: &BackendConfig) -> Result<Self> {
+        Ok(Self::with_backend(storage::LadybugBackend::from_config(config)?))
+    }
+
     /// Open a graph backed by an existing BindSpace (shared ownership).
     pub fn with_bind_space(bs: std::sync::Arc<parking_lot::RwLock<ladybug::storage::bind_space::BindSpace>>) -> Self {
         let backend = storage::LadybugBackend::new(bs);
@@ -176,10 +679,45 @@ impl Graph<storage::LadybugBackend> {
     }
 }
 
+/// Gather the node count and the counts of `labels`, such as the
+/// `planner::join_labels` the optimizer orders joins by. No labels, as for
+/// plans without a cartesian product, cost no backend calls.
+async fn plan_statistics<B: StorageBackend>(
+    backend: &B,
+    tx: &B::Tx,
+    labels: Vec<String>,
+) -> Result<planner::Statistics> {
+    let mut stats = planner::Statistics::default();
+    if labels.is_empty() {
+        return Ok(stats);
+    }
+    stats.node_count = Some(backend.node_count(tx).await?);
+    for label in labels {
+        let count = backend.label_count(tx, &label).await?;
+        stats.label_counts.insert(label, count);
+    }
+    Ok(stats)
+}
+
+/// Retry behaviour for `Graph::with_transaction`. The default never retries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Re-runs allowed after the first attempt hits a write conflict.
+    pub max_retries: u32,
+}
+
+impl RetryPolicy {
+    pub fn retries(max_retries: u32) -> Self {
+        Self { max_retries }
+    }
+}
+
 /// Explicit transaction handle. Warns on drop without commit/rollback.
 pub struct ExplicitTx<'g, B: StorageBackend> {
     graph: &'g Graph<B>,
     tx: Option<B::Tx>,
+    /// Applied to the graph's schema registry on commit.
+    schema: SchemaChanges,
 }
 
 impl<'g, B: StorageBackend> ExplicitTx<'g, B> {
@@ -188,16 +726,16 @@ impl<'g, B: StorageBackend> ExplicitTx<'g, B> {
         P: Into<PropertyMap>,
     {
         let params = params.into();
-        let ast = cypher::parse(query)?;
-        let logical = planner::plan(&ast, &params)?;
-        let optimized = planner::optimize(logical)?;
+        let compiled = compile(query, &params)?;
         let tx = self.tx.as_mut().ok_or_else(|| Error::TxError("Transaction already finished".into()))?;
-        execution::execute(&self.graph.backend, tx, optimized, params).await
+        let (result, changes) = self.graph.run_plan(tx, compiled, params).await?;
+        self.schema.merge(changes);
+        Ok(result)
     }
 
     pub async fn commit(mut self) -> Result<()> {
         let tx = self.tx.take().ok_or_else(|| Error::TxError("Transaction already finished".into()))?;
-        self.graph.backend.commit_tx(tx).await
+        self.graph.commit(tx, std::mem::take(&mut self.schema)).await
     }
 
     pub async fn rollback(mut self) -> Result<()> {
@@ -226,18 +764,20 @@ pub enum Error {
     #[error("Cypher syntax error at position {position}: {message}")]
     SyntaxError { position: usize, message: String },
 
-    #[error("Semantic error: {0}")]
-    SemanticError(String),
+    #[error("Semantic error: {message}")]
+    SemanticError { message: String, span: Option<cypher::ast::Span> },
 
     #[error("Type error: expected {expected}, got {got}")]
-    TypeError { expected: String, got: String },
+    TypeError { expected: String, got: String, span: Option<cypher::ast::Span> },
 
+    /// `file`/`line`/`col` locate the planner code; `span` the query text.
     #[error("Planning error at {file}:{line}:{col}: {message}")]
     PlanError {
         message: String,
         file: &'static str,
         line: u32,
         col: u32,
+        span: Option<cypher::ast::Span>,
     },
 
     #[error("Execution error: {0}")]
@@ -259,6 +799,72 @@ pub enum Error {
     Io(#[from] std::io::Error),
 }
 
+impl Error {
+    /// The error optimistic backends raise when a concurrent commit won.
+    pub fn is_write_conflict(&self) -> bool {
+        matches!(self, Error::TxError(msg) if msg == "write conflict")
+    }
+}
+
+impl Error {
+    /// Shorthand for a `SemanticError` with no position yet.
+    pub fn semantic(message: impl Into<String>) -> Self {
+        Error::SemanticError { message: message.into(), span: None }
+    }
+
+    /// The part of the query this error points at, if known.
+    pub fn span(&self) -> Option<cypher::ast::Span> {
+        match self {
+            Error::SyntaxError { position, .. } => {
+                Some(cypher::ast::Span { start: *position, end: *position + 1 })
+            }
+            Error::SemanticError { span, .. }
+            | Error::TypeError { span, .. }
+            | Error::PlanError { span, .. } => *span,
+            _ => None,
+        }
+    }
+
+    /// Attach `span` unless the error already points somewhere more specific.
+    pub fn or_span(mut self, at: Option<cypher::ast::Span>) -> Self {
+        if let Error::SemanticError { span, .. }
+        | Error::TypeError { span, .. }
+        | Error::PlanError { span, .. } = &mut self
+        {
+            *span = span.or(at);
+        }
+        self
+    }
+
+    /// Render the error with the offending query line and a caret underline:
+    ///
+    /// ```text
+    /// Semantic error: Unbound variable: m
+    ///   MATCH (n) RETURN m.name
+    ///                    ^
+    /// ```
+    ///
+    /// Errors without a position render as their plain message.
+    pub fn render(&self, query: &str) -> String {
+        let Some(span) = self.span() else { return self.to_string() };
+        // A span from another query may land past the end or inside a character
+        let floor = |mut i: usize| {
+            i = i.min(query.len());
+            while !query.is_char_boundary(i) {
+                i -= 1;
+            }
+            i
+        };
+        let start = floor(span.start);
+        let line_start = query[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = query[start..].find('\n').map_or(query.len(), |i| start + i);
+        let line = &query[line_start..line_end];
+        let col = query[line_start..start].chars().count();
+        let width = query[start..floor(span.end).clamp(start, line_end)].chars().count().max(1);
+        format!("{self}\n  {line}\n  {}{}", " ".repeat(col), "^".repeat(width))
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Create a `PlanError` with zero-cost compile-time location capture.
@@ -275,6 +881,7 @@ macro_rules! plan_err {
             file: file!(),
             line: line!(),
             col: column!(),
+            span: None,
         }
     };
 }
@@ -287,7 +894,7 @@ mod tests {
     fn test_plan_err_location() {
         let err = plan_err!("test error: {}", 42);
         match err {
-            Error::PlanError { message, file, line, col } => {
+            Error::PlanError { message, file, line, col, .. } => {
                 assert_eq!(message, "test error: 42");
                 assert!(file.contains("lib.rs"), "file should contain lib.rs, got: {}", file);
                 assert!(line > 0, "line should be non-zero");
@@ -304,4 +911,26 @@ mod tests {
         assert!(display.contains("bad pattern"), "display: {}", display);
         assert!(display.contains("lib.rs"), "display should contain file: {}", display);
     }
+
+    #[test]
+    fn test_render_points_at_span() {
+        let query = "MATCH (n)\nRETURN m.name";
+        let err = Error::semantic("Unbound variable: m").or_span(Some(cypher::ast::Span { start: 17, end: 18 }));
+        assert_eq!(
+            err.render(query),
+            "Semantic error: Unbound variable: m\n  RETURN m.name\n         ^"
+        );
+        // An existing span is kept
+        let err = err.or_span(Some(cypher::ast::Span { start: 0, end: 5 }));
+        assert_eq!(err.span(), Some(cypher::ast::Span { start: 17, end: 18 }));
+        // No span, no caret
+        assert_eq!(Error::semantic("x").render(query), "Semantic error: x");
+        // Spans inside a character or past the end don't panic
+        let query = "RETURN 'é' + x";
+        let err = Error::semantic("x").or_span(Some(cypher::ast::Span { start: 9, end: 40 }));
+        assert_eq!(err.render(query), "Semantic error: x\n  RETURN 'é' + x\n          ^^^^^^");
+        let err = Error::semantic("x").or_span(Some(cypher::ast::Span { start: 99, end: 100 }));
+        assert_eq!(err.render(query), "Semantic error: x\n  RETURN 'é' + x\n                ^");
+    }
 }
+
@@ -150,6 +150,20 @@ impl AwarenessTensor {
         }
     }
 
+    /// Cell-wise average of `tensors`; zero when empty.
+    pub fn mean(tensors: &[AwarenessTensor]) -> Self {
+        if tensors.is_empty() {
+            return Self::zero();
+        }
+        let n = tensors.len() as f32;
+        let avg = |cell: fn(&AwarenessTensor) -> f32| tensors.iter().map(cell).sum::<f32>() / n;
+        Self {
+            s_sign: avg(|t| t.s_sign), s_exp: avg(|t| t.s_exp), s_mant: avg(|t| t.s_mant),
+            p_sign: avg(|t| t.p_sign), p_exp: avg(|t| t.p_exp), p_mant: avg(|t| t.p_mant),
+            o_sign: avg(|t| t.o_sign), o_exp: avg(|t| t.o_exp), o_mant: avg(|t| t.o_mant),
+        }
+    }
+
     /// Overall agreement: average of all 9 cells.
     pub fn total_agreement(&self) -> f32 {
         (self.s_sign + self.s_exp + self.s_mant
@@ -325,6 +339,19 @@ impl AwarenessMask {
          self.o_sign, self.o_exp, self.o_mant]
             .iter().filter(|&&v| v).count()
     }
+
+    /// The mask named by a query selector: `"all"`, `"causal_only"`,
+    /// `"subject_only"`, `"predicate_only"` or `"object_only"`.
+    pub fn from_selector(selector: &str) -> Option<Self> {
+        Some(match selector {
+            "all" => Self::all(),
+            "causal_only" => Self::causal_only(),
+            "subject_only" => Self::subject_only(),
+            "predicate_only" => Self::predicate_only(),
+            "object_only" => Self::object_only(),
+            _ => return None,
+        })
+    }
 }
 
 // ============================================================================
@@ -370,6 +397,41 @@ pub struct CausalPath {
     pub edge_tensors: Vec<AwarenessTensor>,
 }
 
+impl CausalPath {
+    /// Compose `edge_tensors` along `path` by the bundle connection rules.
+    ///
+    /// Each tensor compares one edge of the path with the next, so an
+    /// n-edge path has n - 1 of them; a cycle adds the closing comparison
+    /// of its last edge with its first. Sign agreement composes as XOR on
+    /// agreement probabilities, exponent agreement sums, and mantissa
+    /// agreement multiplies. A cycle's holonomy is its composed sign.
+    pub fn compose(path: Path, edge_tensors: Vec<AwarenessTensor>) -> Self {
+        let mut composed_sign = 1.0;
+        let mut composed_exp = 0.0;
+        let mut composed_mant = 1.0;
+        for t in &edge_tensors {
+            let sign = t.sign_agreement();
+            composed_sign = composed_sign * sign + (1.0 - composed_sign) * (1.0 - sign);
+            composed_exp += t.exp_agreement();
+            composed_mant *= t.mant_agreement();
+        }
+        let is_cycle = !path.is_empty() && path.start().id == path.end().id;
+        Self {
+            path,
+            composed_sign,
+            composed_exp,
+            composed_mant,
+            holonomy: is_cycle.then_some(composed_sign),
+            edge_tensors,
+        }
+    }
+
+    /// Whether the path is a cycle whose composed direction flips.
+    pub fn has_causal_inversion(&self) -> bool {
+        self.holonomy.is_some_and(|h| h < 0.5)
+    }
+}
+
 // ============================================================================
 // Perspective Gestalt (node-level meta-awareness)
 // ============================================================================
@@ -400,6 +462,46 @@ pub struct PerspectiveGestalt {
     pub total_edges: usize,
 }
 
+impl PerspectiveGestalt {
+    /// Aggregate one tensor per edge — that edge's mean agreement with the
+    /// node's other edges. Each edge is classified by `awareness_state`;
+    /// ties for the dominant state favour Crystallized, then Tensioned.
+    /// With no edges the gestalt is Uncertain.
+    pub fn from_edge_tensors(tensors: &[AwarenessTensor]) -> Self {
+        Self::from_edges(&tensors.iter().copied().map(Some).collect::<Vec<_>>())
+    }
+
+    /// Like `from_edge_tensors`, where `None` is an edge with no other edge
+    /// to compare against. It counts as Uncertain and towards `total_edges`
+    /// but not towards the mean.
+    pub fn from_edges(edges: &[Option<AwarenessTensor>]) -> Self {
+        let tensors: Vec<AwarenessTensor> = edges.iter().flatten().copied().collect();
+        let count = |state| tensors.iter().filter(|t| t.awareness_state() == state).count();
+        let crystallized_count = count(AwarenessState::Crystallized);
+        let tensioned_count = count(AwarenessState::Tensioned);
+        let uncertain_count = count(AwarenessState::Uncertain) + (edges.len() - tensors.len());
+        let dominant_state = if tensors.is_empty() {
+            AwarenessState::Uncertain
+        } else if crystallized_count >= tensioned_count && crystallized_count >= uncertain_count {
+            AwarenessState::Crystallized
+        } else if tensioned_count >= uncertain_count {
+            AwarenessState::Tensioned
+        } else {
+            AwarenessState::Uncertain
+        };
+        let mean_tensor = AwarenessTensor::mean(&tensors);
+        Self {
+            dominant_state,
+            mean_tensor,
+            crystallized_count,
+            tensioned_count,
+            uncertain_count,
+            most_tensioned_dimension: (!tensors.is_empty()).then(|| mean_tensor.most_tensioned().0.to_string()),
+            total_edges: edges.len(),
+        }
+    }
+}
+
 // ============================================================================
 // Awareness Filter (for queries)
 // ============================================================================
@@ -422,6 +524,20 @@ pub struct AwarenessFilter {
     pub mask: Option<AwarenessMask>,
 }
 
+impl AwarenessFilter {
+    /// Focus `tensor` through the mask, then check it against the
+    /// thresholds. Returns the masked tensor when it passes.
+    pub fn apply(&self, tensor: &AwarenessTensor) -> Option<AwarenessTensor> {
+        let masked = self.mask.map_or(*tensor, |mask| tensor.apply_mask(&mask));
+        let sign = masked.sign_agreement();
+        let passes = self.min_sign_agreement.is_none_or(|min| sign >= min)
+            && self.max_sign_agreement.is_none_or(|max| sign <= max)
+            && self.awareness_state.is_none_or(|state| masked.awareness_state() == state)
+            && self.causal_direction.is_none_or(|dir| masked.causal_direction() == dir);
+        passes.then_some(masked)
+    }
+}
+
 // ============================================================================
 // ResonanceEdge (extended relationship with SPO containers)
 // ============================================================================
@@ -691,6 +807,89 @@ mod tests {
         assert!((val - 0.1).abs() < f32::EPSILON);
     }
 
+    #[test]
+    fn test_perspective_gestalt_from_edge_tensors() {
+        let mut conflicted = AwarenessTensor::zero();
+        conflicted.s_exp = 0.9;
+        let tensors = [AwarenessTensor::identity(), AwarenessTensor::identity(), conflicted];
+        let g = PerspectiveGestalt::from_edge_tensors(&tensors);
+        assert_eq!(g.dominant_state, AwarenessState::Crystallized);
+        assert_eq!((g.crystallized_count, g.tensioned_count, g.uncertain_count), (2, 1, 0));
+        assert_eq!(g.total_edges, 3);
+        assert!((g.mean_tensor.s_exp - (2.9 / 3.0)).abs() < 1e-6);
+        assert!((g.mean_tensor.p_sign - (2.0 / 3.0)).abs() < 1e-6);
+        assert_eq!(g.most_tensioned_dimension.as_deref(), Some("s_sign"));
+
+        let empty = PerspectiveGestalt::from_edge_tensors(&[]);
+        assert_eq!(empty.dominant_state, AwarenessState::Uncertain);
+        assert_eq!(empty.most_tensioned_dimension, None);
+
+        // An edge with no peer still counts
+        let lone = PerspectiveGestalt::from_edges(&[None]);
+        assert_eq!(lone.dominant_state, AwarenessState::Uncertain);
+        assert_eq!((lone.uncertain_count, lone.total_edges), (1, 1));
+        assert_eq!(lone.most_tensioned_dimension, None);
+        let mixed = PerspectiveGestalt::from_edges(&[Some(AwarenessTensor::identity()), None]);
+        assert_eq!((mixed.crystallized_count, mixed.uncertain_count, mixed.total_edges), (1, 1, 2));
+        assert!((mixed.mean_tensor.total_agreement() - 1.0).abs() < 1e-6);
+    }
+
+    fn chain(ids: &[u64]) -> Path {
+        let node = |id| crate::model::Node {
+            id: crate::model::NodeId(id),
+            element_id: None,
+            labels: Vec::new(),
+            properties: Default::default(),
+        };
+        Path {
+            nodes: ids.iter().map(|&id| node(id)).collect(),
+            relationships: ids.windows(2).enumerate().map(|(i, w)| crate::model::Relationship {
+                id: crate::model::RelId(i as u64),
+                element_id: None,
+                src: crate::model::NodeId(w[0]),
+                dst: crate::model::NodeId(w[1]),
+                rel_type: "CAUSES".into(),
+                properties: Default::default(),
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_causal_path_linear() {
+        let mut half = AwarenessTensor::identity();
+        half.s_mant = 0.5;
+        half.p_mant = 0.5;
+        half.o_mant = 0.5;
+        let cp = CausalPath::compose(chain(&[1, 2, 3]), vec![half]);
+        assert_eq!(cp.composed_sign, 1.0);
+        assert_eq!(cp.composed_exp, 1.0);
+        assert_eq!(cp.composed_mant, 0.5);
+        assert_eq!(cp.holonomy, None);
+        assert!(!cp.has_causal_inversion());
+
+        // Two agreeing steps keep the direction; mantissa keeps degrading
+        let cp = CausalPath::compose(chain(&[1, 2, 3, 4]), vec![half, half]);
+        assert_eq!((cp.composed_sign, cp.composed_exp, cp.composed_mant), (1.0, 2.0, 0.25));
+    }
+
+    #[test]
+    fn test_causal_path_cycle_inversion() {
+        let mut flipped = AwarenessTensor::identity();
+        flipped.s_sign = 0.0;
+        flipped.p_sign = 0.0;
+        flipped.o_sign = 0.0;
+        // + ⊕ + ⊕ - = -
+        let steps = vec![AwarenessTensor::identity(), AwarenessTensor::identity(), flipped];
+        let cp = CausalPath::compose(chain(&[1, 2, 3, 1]), steps);
+        assert_eq!(cp.holonomy, Some(0.0));
+        assert!(cp.has_causal_inversion());
+
+        // Two flips cancel out around the cycle
+        let cp = CausalPath::compose(chain(&[1, 2, 3, 1]), vec![flipped, flipped, AwarenessTensor::identity()]);
+        assert_eq!(cp.holonomy, Some(1.0));
+        assert!(!cp.has_causal_inversion());
+    }
+
     #[test]
     fn test_identity_tensor() {
         let tensor = AwarenessTensor::identity();
@@ -712,6 +911,33 @@ mod tests {
         assert_eq!(AwarenessMask::subject_only().active_count(), 3);
     }
 
+    #[test]
+    fn test_mask_from_selector() {
+        assert_eq!(AwarenessMask::from_selector("all").unwrap().active_count(), 9);
+        assert!(AwarenessMask::from_selector("causal_only").unwrap().p_sign);
+        assert!(!AwarenessMask::from_selector("object_only").unwrap().s_sign);
+        assert!(AwarenessMask::from_selector("causal").is_none());
+    }
+
+    #[test]
+    fn test_filter_masks_before_thresholding() {
+        let tensor = AwarenessTensor {
+            s_sign: 0.9, s_exp: 0.9, s_mant: 0.9,
+            p_sign: 0.6, p_exp: 0.1, p_mant: 0.1,
+            o_sign: 0.6, o_exp: 0.1, o_mant: 0.1,
+        };
+        let filter = AwarenessFilter { min_sign_agreement: Some(0.5), ..Default::default() };
+        assert!(filter.apply(&tensor).is_some());
+
+        // Subject focus drops the P and O signs from the average
+        let focused = AwarenessFilter { mask: Some(AwarenessMask::subject_only()), ..filter };
+        assert!(focused.apply(&tensor).is_none());
+        let focused = AwarenessFilter { min_sign_agreement: Some(0.25), ..focused };
+        let masked = focused.apply(&tensor).unwrap();
+        assert_eq!((masked.p_sign, masked.o_mant), (0.0, 0.0));
+        assert!((masked.total_agreement() - 0.3).abs() < 1e-6);
+    }
+
     // ====================================================================
     // NARS Truth Value tests
     // ====================================================================
@@ -17,8 +17,8 @@ pub mod bf16_distance;
 pub use node::{Node, NodeId};
 pub use relationship::{Relationship, RelId, Direction};
 pub use path::Path;
-pub use value::Value;
-pub use property_map::PropertyMap;
+pub use value::{Collation, Value};
+pub use property_map::{PropertyMap, PropertyMapBuilder, PropertySchema, PropertyType};
 pub use awareness::{
     AwarenessState, AwarenessTensor, AwarenessMask, AwarenessFilter,
     CausalDirection, CausalPath, PerspectiveGestalt,
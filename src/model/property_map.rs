@@ -16,3 +16,338 @@ where
         Value::Map(pairs.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
     }
 }
+
+/// Builds a `PropertyMap` from plain Rust values.
+///
+/// `PropertyMap` is a std `HashMap`, so conversions from application data
+/// live here; the builder converts into a `PropertyMap` wherever one is
+/// expected, e.g. as `Graph::execute` parameters:
+///
+/// ```
+/// use neo4j_rs::PropertyMapBuilder;
+///
+/// let params = PropertyMapBuilder::new()
+///     .insert_str("name", "Alice")
+///     .insert_int("age", 30)
+///     .insert_list("tags", ["admin", "ops"]);
+/// let map: neo4j_rs::PropertyMap = params.into();
+/// assert_eq!(map.len(), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PropertyMapBuilder(PropertyMap);
+
+impl PropertyMapBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a JSON object; numbers become INTEGER when they fit in an
+    /// `i64` and FLOAT otherwise.
+    pub fn from_json(json: serde_json::Value) -> crate::Result<Self> {
+        match Value::from(json) {
+            Value::Map(map) => Ok(Self(map)),
+            other => Err(crate::Error::TypeError {
+                expected: "MAP".into(),
+                got: other.type_name().into(),
+                span: None,
+            }),
+        }
+    }
+
+    pub fn insert(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.0.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn insert_str(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.insert(key, Value::String(value.into()))
+    }
+
+    pub fn insert_int(self, key: impl Into<String>, value: impl Into<i64>) -> Self {
+        self.insert(key, Value::Int(value.into()))
+    }
+
+    pub fn insert_float(self, key: impl Into<String>, value: impl Into<f64>) -> Self {
+        self.insert(key, Value::Float(value.into()))
+    }
+
+    pub fn insert_bool(self, key: impl Into<String>, value: bool) -> Self {
+        self.insert(key, Value::Bool(value))
+    }
+
+    pub fn insert_list<T: Into<Value>>(
+        self,
+        key: impl Into<String>,
+        items: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.insert(key, Value::List(items.into_iter().map(Into::into).collect()))
+    }
+
+    pub fn build(self) -> PropertyMap {
+        self.0
+    }
+}
+
+impl From<PropertyMapBuilder> for PropertyMap {
+    fn from(builder: PropertyMapBuilder) -> Self {
+        builder.0
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for PropertyMapBuilder {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(iter.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+impl<V: Into<Value>> From<HashMap<String, V>> for PropertyMapBuilder {
+    fn from(map: HashMap<String, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> From<Vec<(K, V)>> for PropertyMapBuilder {
+    fn from(pairs: Vec<(K, V)>) -> Self {
+        pairs.into_iter().collect()
+    }
+}
+
+/// A property type a `PropertySchema` coerces to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyType {
+    Integer,
+    Float,
+    String,
+    Boolean,
+}
+
+impl PropertyType {
+    fn name(self) -> &'static str {
+        match self {
+            PropertyType::Integer => "INTEGER",
+            PropertyType::Float => "FLOAT",
+            PropertyType::String => "STRING",
+            PropertyType::Boolean => "BOOLEAN",
+        }
+    }
+}
+
+/// Declared property types and defaults, for cleaning up imported data
+/// (CSV fields, JSON strings) before it becomes node or relationship
+/// properties:
+///
+/// ```
+/// use neo4j_rs::{PropertyMapBuilder, PropertySchema, PropertyType, Value};
+///
+/// let schema = PropertySchema::new()
+///     .with_type("weight", PropertyType::Integer)
+///     .with_default("weight", 1)
+///     .with_type("verified", PropertyType::Boolean);
+/// let props = PropertyMapBuilder::new().insert_str("verified", "TRUE").build();
+/// let props = schema.coerce(props).unwrap();
+/// assert_eq!(props["weight"], Value::Int(1));
+/// assert_eq!(props["verified"], Value::Bool(true));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PropertySchema {
+    types: HashMap<String, PropertyType>,
+    defaults: PropertyMap,
+}
+
+impl PropertySchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_type(mut self, key: impl Into<String>, ty: PropertyType) -> Self {
+        self.types.insert(key.into(), ty);
+        self
+    }
+
+    /// Fill in `value` when the key is missing or null (after coercion, so
+    /// the default is coerced too).
+    pub fn with_default(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.defaults.insert(key.into(), value.into());
+        self
+    }
+
+    /// Convert each declared property of `props` to its type, then fill in
+    /// defaults. Strings parse the way `toInteger()`, `toFloat()` and
+    /// `toBoolean()` read them, an empty string counts as missing for any
+    /// type but STRING, and a null without a default is dropped. Keys the
+    /// schema doesn't declare pass through unchanged. Fails with a
+    /// `TypeError` naming the key on a value that doesn't convert.
+    pub fn coerce(&self, mut props: PropertyMap) -> crate::Result<PropertyMap> {
+        for (key, value) in &self.defaults {
+            if props.get(key).is_none_or(|v| v.is_null() || self.blank(key, v)) {
+                props.insert(key.clone(), value.clone());
+            }
+        }
+        let mut out = PropertyMap::with_capacity(props.len());
+        for (key, value) in props {
+            let value = match self.types.get(&key) {
+                Some(_) if self.blank(&key, &value) => Value::Null,
+                Some(&ty) => coerce_value(value, ty).map_err(|got| crate::Error::TypeError {
+                    expected: format!("{} for property `{key}`", ty.name()),
+                    got,
+                    span: None,
+                })?,
+                None => value,
+            };
+            if !value.is_null() {
+                out.insert(key, value);
+            }
+        }
+        Ok(out)
+    }
+
+    /// An empty string where `key` is declared as something other than STRING.
+    fn blank(&self, key: &str, value: &Value) -> bool {
+        matches!(value, Value::String(s) if s.trim().is_empty())
+            && self.types.get(key).is_some_and(|ty| *ty != PropertyType::String)
+    }
+}
+
+/// `value` as `ty`, or what it was when it doesn't convert.
+fn coerce_value(value: Value, ty: PropertyType) -> Result<Value, String> {
+    let unconvertible = |v: &Value| match v {
+        Value::String(s) => format!("'{s}'"),
+        other => other.type_name().into(),
+    };
+    match (ty, value) {
+        (_, Value::Null) => Ok(Value::Null),
+        (PropertyType::Integer, v @ Value::Int(_)) => Ok(v),
+        (PropertyType::Integer, Value::Float(f)) => truncate(f).map(Value::Int).ok_or_else(|| unconvertible(&Value::Float(f))),
+        // "2.5" reads as 2, the way toInteger() reads it
+        (PropertyType::Integer, Value::String(s)) => parse_integer(&s).map(Value::Int).ok_or_else(|| unconvertible(&Value::String(s))),
+        (PropertyType::Float, v @ Value::Float(_)) => Ok(v),
+        (PropertyType::Float, Value::Int(i)) => Ok(Value::Float(i as f64)),
+        (PropertyType::Float, Value::String(s)) => match s.trim().parse::<f64>() {
+            Ok(f) => Ok(Value::Float(f)),
+            Err(_) => Err(unconvertible(&Value::String(s))),
+        },
+        (PropertyType::Boolean, v @ Value::Bool(_)) => Ok(v),
+        (PropertyType::Boolean, Value::String(s)) => match s.trim().to_lowercase().as_str() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => Err(unconvertible(&Value::String(s))),
+        },
+        (PropertyType::String, v @ Value::String(_)) => Ok(v),
+        (PropertyType::String, v @ (Value::Int(_) | Value::Float(_) | Value::Bool(_))) => Ok(Value::String(v.to_string())),
+        (_, other) => Err(unconvertible(&other)),
+    }
+}
+
+/// `f` truncated toward zero, unless it is NaN, infinite or out of range.
+pub(crate) fn truncate(f: f64) -> Option<i64> {
+    let t = f.trunc();
+    // i64::MAX as f64 rounds up to 2^63, itself out of range
+    (t >= i64::MIN as f64 && t < i64::MAX as f64).then_some(t as i64)
+}
+
+/// `s`, trimmed, as an integer, or as a float `truncate` accepts.
+pub(crate) fn parse_integer(s: &str) -> Option<i64> {
+    let s = s.trim();
+    s.parse::<i64>().ok().or_else(|| s.parse::<f64>().ok().and_then(truncate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_insert_helpers() {
+        let map = PropertyMapBuilder::new()
+            .insert_str("name", "Alice")
+            .insert_int("age", 30i32)
+            .insert_float("score", 0.5f32)
+            .insert_bool("active", true)
+            .insert_list("ids", [1i64, 2])
+            .build();
+        assert_eq!(map["name"], Value::from("Alice"));
+        assert_eq!(map["age"], Value::Int(30));
+        assert_eq!(map["score"], Value::Float(0.5));
+        assert_eq!(map["active"], Value::Bool(true));
+        assert_eq!(map["ids"], Value::List(vec![Value::Int(1), Value::Int(2)]));
+    }
+
+    #[test]
+    fn test_builder_from_collections() {
+        let from_pairs = PropertyMapBuilder::from(vec![("a", 1), ("b", 2)]);
+        let from_map = PropertyMapBuilder::from(HashMap::from([
+            ("a".to_string(), 1),
+            ("b".to_string(), 2),
+        ]));
+        assert_eq!(from_pairs, from_map);
+        let map: PropertyMap = from_pairs.into();
+        assert_eq!(map["b"], Value::Int(2));
+    }
+
+    #[test]
+    fn test_builder_from_json() {
+        let json = serde_json::json!({
+            "name": "Alice",
+            "age": 30,
+            "ratio": 0.25,
+            "big": u64::MAX,
+            "tags": ["x", null],
+            "nested": {"ok": true},
+        });
+        let map = PropertyMapBuilder::from_json(json).unwrap().build();
+        assert_eq!(map["age"], Value::Int(30));
+        assert_eq!(map["ratio"], Value::Float(0.25));
+        assert_eq!(map["big"], Value::Float(u64::MAX as f64));
+        assert_eq!(map["tags"], Value::List(vec![Value::from("x"), Value::Null]));
+        assert_eq!(map["nested"], Value::from(vec![("ok", true)]));
+
+        let err = PropertyMapBuilder::from_json(serde_json::json!([1, 2])).unwrap_err();
+        assert!(err.to_string().contains("expected MAP, got LIST"));
+    }
+
+    #[test]
+    fn test_schema_coerces_imported_strings() {
+        let schema = PropertySchema::new()
+            .with_type("since", PropertyType::Integer)
+            .with_type("weight", PropertyType::Float)
+            .with_type("active", PropertyType::Boolean)
+            .with_type("code", PropertyType::String)
+            .with_default("weight", 1.0)
+            .with_default("source", "csv");
+        let props = PropertyMapBuilder::new()
+            .insert_str("since", " 2019 ")
+            .insert_str("weight", "")
+            .insert_str("active", "False")
+            .insert_int("code", 7)
+            .insert_str("note", "kept")
+            .build();
+        let props = schema.coerce(props).unwrap();
+        assert_eq!(props["since"], Value::Int(2019));
+        assert_eq!(props["weight"], Value::Float(1.0));
+        assert_eq!(props["active"], Value::Bool(false));
+        assert_eq!(props["code"], Value::from("7"));
+        assert_eq!(props["note"], Value::from("kept"));
+        assert_eq!(props["source"], Value::from("csv"));
+
+        // Blank and null values without a default are dropped
+        let props = schema
+            .coerce(PropertyMapBuilder::new().insert_str("since", "").insert("active", Value::Null).build())
+            .unwrap();
+        assert_eq!(props.len(), 2);
+        assert!(!props.contains_key("since") && !props.contains_key("active"));
+
+        let err = schema.coerce(PropertyMapBuilder::new().insert_str("since", "last year").build()).unwrap_err();
+        assert!(err.to_string().contains("INTEGER for property `since`"), "{err}");
+        assert!(err.to_string().contains("'last year'"), "{err}");
+        let err = schema.coerce(PropertyMapBuilder::new().insert_list("active", [true]).build()).unwrap_err();
+        assert!(err.to_string().contains("got LIST"), "{err}");
+
+        // Decimal strings and floats truncate; NaN and infinities don't convert
+        assert_eq!(schema.coerce(PropertyMapBuilder::new().insert_str("since", "2.5").build()).unwrap()["since"], Value::Int(2));
+        assert_eq!(schema.coerce(PropertyMapBuilder::new().insert_float("since", -7.9).build()).unwrap()["since"], Value::Int(-7));
+        for bad in [f64::NAN, f64::INFINITY, 1e300] {
+            let err = schema.coerce(PropertyMapBuilder::new().insert_float("since", bad).build()).unwrap_err();
+            assert!(err.to_string().contains("INTEGER for property `since`"), "{err}");
+        }
+        assert!(schema.coerce(PropertyMapBuilder::new().insert_str("since", "NaN").build()).is_err());
+    }
+}
@@ -153,6 +153,72 @@ impl From<u32> for Value { fn from(v: u32) -> Self { Value::Int(v as i64) } }
 impl From<u64> for Value { fn from(v: u64) -> Self { Value::Int(v as i64) } }
 impl From<usize> for Value { fn from(v: usize) -> Self { Value::Int(v as i64) } }
 
+impl From<serde_json::Value> for Value {
+    fn from(v: serde_json::Value) -> Self {
+        match v {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => n.as_f64().map_or(Value::Null, Value::Float),
+            },
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(arr) => Value::List(arr.into_iter().map(Value::from).collect()),
+            serde_json::Value::Object(map) => {
+                Value::Map(map.into_iter().map(|(k, v)| (k, Value::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Plain JSON for this value, e.g. for an HTTP response. JSON has no
+    /// NaN or infinities, so those floats become null. Bytes become a list
+    /// of integers, temporal values their ISO 8601 string, and graph types
+    /// maps of their fields.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::{Value as Json, json};
+
+        fn properties(props: &HashMap<String, Value>) -> Json {
+            Json::Object(props.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+        }
+        fn node(n: &Node) -> Json {
+            json!({ "id": n.id.0, "labels": n.labels, "properties": properties(&n.properties) })
+        }
+        fn relationship(r: &Relationship) -> Json {
+            json!({
+                "id": r.id.0,
+                "type": r.rel_type,
+                "start": r.src.0,
+                "end": r.dst.0,
+                "properties": properties(&r.properties),
+            })
+        }
+
+        match self {
+            Value::Null => Json::Null,
+            Value::Bool(b) => Json::Bool(*b),
+            Value::Int(i) => Json::from(*i),
+            Value::Float(f) => serde_json::Number::from_f64(*f).map_or(Json::Null, Json::Number),
+            Value::String(s) => Json::String(s.clone()),
+            Value::Bytes(b) => Json::from(b.as_slice()),
+            Value::List(l) => Json::Array(l.iter().map(Value::to_json).collect()),
+            Value::Map(m) => properties(m),
+            Value::Node(n) => node(n),
+            Value::Relationship(r) => relationship(r),
+            Value::Path(p) => json!({
+                "nodes": p.nodes.iter().map(node).collect::<Vec<_>>(),
+                "relationships": p.relationships.iter().map(relationship).collect::<Vec<_>>(),
+            }),
+            Value::Date(_) | Value::Time(_) | Value::DateTime(_) | Value::LocalDateTime(_) | Value::Duration(_) => {
+                Json::String(self.to_string())
+            }
+            Value::Point2D { srid, x, y } => json!({ "srid": srid, "x": x, "y": y }),
+            Value::Point3D { srid, x, y, z } => json!({ "srid": srid, "x": x, "y": y, "z": z }),
+        }
+    }
+}
+
 // ============================================================================
 // Display
 // ============================================================================
@@ -163,6 +229,8 @@ impl fmt::Display for Value {
             Value::Null => write!(f, "null"),
             Value::Bool(b) => write!(f, "{b}"),
             Value::Int(i) => write!(f, "{i}"),
+            // Cypher's spelling, not Rust's `inf`
+            Value::Float(v) if v.is_infinite() => write!(f, "{}Infinity", if *v < 0.0 { "-" } else { "" }),
             Value::Float(v) => write!(f, "{v}"),
             Value::String(s) => write!(f, "\"{}\"", s.replace('"', "\\\"")),
             Value::Bytes(b) => write!(f, "<bytes[{}]>", b.len()),
@@ -200,9 +268,43 @@ impl fmt::Display for Value {
 // Comparison (Neo4j ordering rules)
 // ============================================================================
 
+/// How strings order in comparisons, ORDER BY, `min()` and `max()`.
+/// Equality is unaffected: `'ada' = 'Ada'` is false under every collation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Collation {
+    /// By Unicode code point, as Neo4j does: `'Bob' < 'ada'`.
+    #[default]
+    ByteOrder,
+    /// By lowercased text, so `'ada' < 'Bob'`. Strings that differ only in
+    /// case fall back to code point order, keeping the order total.
+    CaseInsensitive,
+}
+
+impl Collation {
+    pub fn compare(self, a: &str, b: &str) -> std::cmp::Ordering {
+        match self {
+            Collation::ByteOrder => a.cmp(b),
+            Collation::CaseInsensitive => a
+                .chars()
+                .flat_map(char::to_lowercase)
+                .cmp(b.chars().flat_map(char::to_lowercase))
+                .then_with(|| a.cmp(b)),
+        }
+    }
+}
+
 impl Value {
     /// Neo4j comparison. Returns None for incompatible types (like SQL NULL behavior).
+    /// Lists compare element by element: the first pair that differs
+    /// decides, a list sorts before the lists it is a prefix of, and a pair
+    /// that doesn't compare (a null, say) makes the lists incomparable.
     pub fn neo4j_cmp(&self, other: &Value) -> Option<std::cmp::Ordering> {
+        self.neo4j_cmp_with(other, Collation::ByteOrder)
+    }
+
+    /// `neo4j_cmp` with strings ordered by `collation`.
+    pub fn neo4j_cmp_with(&self, other: &Value, collation: Collation) -> Option<std::cmp::Ordering> {
         match (self, other) {
             (Value::Null, Value::Null) => None, // NULL = NULL is NULL in Neo4j
             (Value::Null, _) | (_, Value::Null) => None,
@@ -211,10 +313,188 @@ impl Value {
             (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
             (Value::Int(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
             (Value::Float(a), Value::Int(b)) => a.partial_cmp(&(*b as f64)),
-            (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+            (Value::String(a), Value::String(b)) => Some(collation.compare(a, b)),
+            (Value::Bytes(a), Value::Bytes(b)) => a.partial_cmp(b),
+            (Value::Date(a), Value::Date(b)) => a.partial_cmp(b),
+            (Value::Time(a), Value::Time(b)) => a.partial_cmp(b),
+            (Value::DateTime(a), Value::DateTime(b)) => a.partial_cmp(b),
+            (Value::LocalDateTime(a), Value::LocalDateTime(b)) => a.partial_cmp(b),
+            (Value::List(a), Value::List(b)) => {
+                for (x, y) in a.iter().zip(b) {
+                    match x.neo4j_cmp_with(y, collation)? {
+                        std::cmp::Ordering::Equal => {}
+                        ord => return Some(ord),
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
+            }
+            // Durations don't compare: is P1M longer than P30D?
             _ => None,
         }
     }
+
+    /// Cypher `=`: `None` (null) if either side is null. Integers and floats
+    /// compare by numeric value. Lists and maps compare element-wise: any
+    /// unequal pair makes them unequal, otherwise a null pair makes `=` null.
+    pub fn neo4j_eq(&self, other: &Value) -> Option<bool> {
+        fn all_eq<'v>(pairs: impl Iterator<Item = (&'v Value, &'v Value)>) -> Option<bool> {
+            let mut unknown = false;
+            for (x, y) in pairs {
+                match x.neo4j_eq(y) {
+                    Some(false) => return Some(false),
+                    None => unknown = true,
+                    Some(true) => {}
+                }
+            }
+            if unknown { None } else { Some(true) }
+        }
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => None,
+            (a, b) if a.is_numeric() && b.is_numeric() => Some(a.neo4j_cmp(b) == Some(std::cmp::Ordering::Equal)),
+            (Value::List(a), Value::List(b)) => {
+                if a.len() != b.len() {
+                    return Some(false);
+                }
+                all_eq(a.iter().zip(b))
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                if a.len() != b.len() || a.keys().any(|k| !b.contains_key(k)) {
+                    return Some(false);
+                }
+                all_eq(a.iter().map(|(k, v)| (v, &b[k])))
+            }
+            (a, b) => Some(a == b),
+        }
+    }
+
+    /// Neo4j equivalence, used for grouping and DISTINCT: like `=`, except
+    /// null is equivalent to null and NaN to NaN. Integers and floats with
+    /// the same numeric value are equivalent, in lists and maps too.
+    pub fn equivalent(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Float(a), Value::Float(b)) if a.is_nan() && b.is_nan() => true,
+            (a, b) if a.is_numeric() && b.is_numeric() => a.neo4j_cmp(b) == Some(std::cmp::Ordering::Equal),
+            (Value::List(a), Value::List(b)) => a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.equivalent(y)),
+            (Value::Map(a), Value::Map(b)) => {
+                a.len() == b.len() && a.iter().all(|(k, v)| b.get(k).is_some_and(|w| v.equivalent(w)))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Neo4j ORDER BY ordering: a total order over all values, unlike
+    /// `neo4j_cmp`. Types sort in Neo4j's documented precedence, ascending:
+    ///
+    /// Map < Node < Relationship < List < Path < DateTime < LocalDateTime
+    /// < Date < Time < Duration < Point < String < Boolean < Number < Null
+    ///
+    /// Integers and floats compare numerically with NaN above every other
+    /// number. `Bytes` is not a Cypher type; it sorts with lists.
+    pub fn order_cmp(&self, other: &Value) -> std::cmp::Ordering {
+        self.order_cmp_with(other, Collation::ByteOrder)
+    }
+
+    /// `order_cmp` with strings, in lists and maps too, ordered by `collation`.
+    pub fn order_cmp_with(&self, other: &Value, collation: Collation) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+
+        fn rank(v: &Value) -> u8 {
+            match v {
+                Value::Map(_) => 0,
+                Value::Node(_) => 1,
+                Value::Relationship(_) => 2,
+                Value::List(_) | Value::Bytes(_) => 3,
+                Value::Path(_) => 4,
+                Value::DateTime(_) => 5,
+                Value::LocalDateTime(_) => 6,
+                Value::Date(_) => 7,
+                Value::Time(_) => 8,
+                Value::Duration(_) => 9,
+                Value::Point2D { .. } | Value::Point3D { .. } => 10,
+                Value::String(_) => 11,
+                Value::Bool(_) => 12,
+                Value::Int(_) | Value::Float(_) => 13,
+                Value::Null => 14,
+            }
+        }
+
+        fn float_cmp(a: f64, b: f64) -> Ordering {
+            match (a.is_nan(), b.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            }
+        }
+
+        fn list_cmp<'a>(
+            a: impl IntoIterator<Item = &'a Value>,
+            b: impl IntoIterator<Item = &'a Value>,
+            collation: Collation,
+        ) -> Ordering {
+            let (mut a, mut b) = (a.into_iter(), b.into_iter());
+            loop {
+                match (a.next(), b.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(x), Some(y)) => match x.order_cmp_with(y, collation) {
+                        Ordering::Equal => {}
+                        ord => return ord,
+                    },
+                }
+            }
+        }
+
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+                float_cmp(self.as_float().unwrap_or(f64::NAN), other.as_float().unwrap_or(f64::NAN))
+            }
+            (Value::String(a), Value::String(b)) => collation.compare(a, b),
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::List(a), Value::List(b)) => list_cmp(a, b, collation),
+            (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+            (Value::Bytes(_), Value::List(_)) => Ordering::Greater,
+            (Value::List(_), Value::Bytes(_)) => Ordering::Less,
+            // Maps compare by their sorted keys first, then by the values in key order
+            (Value::Map(a), Value::Map(b)) => {
+                let mut ka: Vec<&String> = a.keys().collect();
+                let mut kb: Vec<&String> = b.keys().collect();
+                ka.sort();
+                kb.sort();
+                ka.cmp(&kb).then_with(|| list_cmp(ka.iter().map(|k| &a[*k]), kb.iter().map(|k| &b[*k]), collation))
+            }
+            (Value::Node(a), Value::Node(b)) => a.id.0.cmp(&b.id.0),
+            (Value::Relationship(a), Value::Relationship(b)) => a.id.0.cmp(&b.id.0),
+            (Value::Path(a), Value::Path(b)) => {
+                let nodes = |p: &Path| p.nodes.iter().map(|n| n.id.0).collect::<Vec<_>>();
+                let rels = |p: &Path| p.relationships.iter().map(|r| r.id.0).collect::<Vec<_>>();
+                nodes(a).cmp(&nodes(b)).then_with(|| rels(a).cmp(&rels(b)))
+            }
+            (Value::DateTime(a), Value::DateTime(b)) => a.cmp(b),
+            (Value::LocalDateTime(a), Value::LocalDateTime(b)) => a.cmp(b),
+            (Value::Date(a), Value::Date(b)) => a.cmp(b),
+            (Value::Time(a), Value::Time(b)) => a.cmp(b),
+            (Value::Duration(a), Value::Duration(b)) => {
+                (a.months, a.days, a.seconds, a.nanoseconds).cmp(&(b.months, b.days, b.seconds, b.nanoseconds))
+            }
+            (Value::Point2D { srid: sa, x: xa, y: ya }, Value::Point2D { srid: sb, x: xb, y: yb }) => {
+                sa.cmp(sb).then(float_cmp(*xa, *xb)).then(float_cmp(*ya, *yb))
+            }
+            (
+                Value::Point3D { srid: sa, x: xa, y: ya, z: za },
+                Value::Point3D { srid: sb, x: xb, y: yb, z: zb },
+            ) => sa
+                .cmp(sb)
+                .then(float_cmp(*xa, *xb))
+                .then(float_cmp(*ya, *yb))
+                .then(float_cmp(*za, *zb)),
+            (Value::Point2D { .. }, Value::Point3D { .. }) => Ordering::Less,
+            (Value::Point3D { .. }, Value::Point2D { .. }) => Ordering::Greater,
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -235,6 +515,39 @@ mod tests {
         assert_eq!(Value::Null.neo4j_cmp(&Value::Int(1)), None);
     }
 
+    #[test]
+    fn test_order_cmp_across_types() {
+        let mut values = vec![
+            Value::Null,
+            Value::Int(2),
+            Value::from("b"),
+            Value::Bool(true),
+            Value::Float(f64::NAN),
+            Value::List(vec![Value::Int(1)]),
+            Value::Float(1.5),
+            Value::from("a"),
+            Value::Map(HashMap::new()),
+            Value::Bool(false),
+            Value::List(vec![]),
+        ];
+        values.sort_by(|a, b| a.order_cmp(b));
+        let rendered: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        assert_eq!(rendered, ["{}", "[]", "[1]", "\"a\"", "\"b\"", "false", "true", "1.5", "2", "NaN", "null"]);
+    }
+
+    #[test]
+    fn test_collation() {
+        use std::cmp::Ordering::{Greater, Less};
+        let (ada, bob) = (Value::from("ada"), Value::from("Bob"));
+        assert_eq!(ada.neo4j_cmp(&bob), Some(Greater));
+        assert_eq!(ada.neo4j_cmp_with(&bob, Collation::CaseInsensitive), Some(Less));
+        // Case only breaks ties, so the order stays total
+        assert_eq!(Collation::CaseInsensitive.compare("Ada", "ada"), Less);
+        let (a, b) = (Value::List(vec![ada.clone()]), Value::List(vec![bob.clone()]));
+        assert_eq!(a.order_cmp_with(&b, Collation::CaseInsensitive), Less);
+        assert_eq!(a.order_cmp(&b), Greater);
+    }
+
     #[test]
     fn test_numeric_comparison() {
         assert_eq!(
@@ -243,6 +556,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_list_comparison() {
+        use std::cmp::Ordering::*;
+        let ints = |items: &[i64]| Value::List(items.iter().map(|i| Value::Int(*i)).collect());
+        assert_eq!(ints(&[1, 2]).neo4j_cmp(&ints(&[1, 3])), Some(Less));
+        assert_eq!(ints(&[1]).neo4j_cmp(&ints(&[1, 2])), Some(Less));
+        assert_eq!(ints(&[1, 2]).neo4j_cmp(&Value::List(vec![Value::Float(1.0), Value::Int(2)])), Some(Equal));
+        assert_eq!(ints(&[]).neo4j_cmp(&ints(&[])), Some(Equal));
+
+        // Nested lists recurse
+        let nested = |a: i64, b: i64| Value::List(vec![ints(&[a]), ints(&[b])]);
+        assert_eq!(nested(1, 3).neo4j_cmp(&nested(1, 2)), Some(Greater));
+        assert_eq!(nested(1, 2).neo4j_cmp(&Value::List(vec![ints(&[1, 2])])), Some(Less));
+
+        // An incomparable pair before any difference leaves the lists incomparable
+        let with_null = |i: i64, null_first: bool| {
+            let pair = [Value::Null, Value::Int(i)];
+            Value::List(if null_first { pair.to_vec() } else { pair.into_iter().rev().collect() })
+        };
+        assert_eq!(with_null(1, true).neo4j_cmp(&with_null(2, true)), None);
+        assert_eq!(with_null(1, false).neo4j_cmp(&with_null(2, false)), Some(Less));
+        assert_eq!(ints(&[1]).neo4j_cmp(&Value::List(vec![Value::from("1")])), None);
+    }
+
+    #[test]
+    fn test_neo4j_eq() {
+        let list = |items: Vec<Value>| Value::List(items);
+        assert_eq!(Value::Int(1).neo4j_eq(&Value::Float(1.0)), Some(true));
+        assert_eq!(list(vec![Value::Int(1)]).neo4j_eq(&list(vec![Value::Float(1.0)])), Some(true));
+        assert_eq!(list(vec![Value::Int(1)]).neo4j_eq(&list(vec![Value::Int(1), Value::Int(2)])), Some(false));
+
+        // A null element makes `=` null unless another pair already differs
+        assert_eq!(list(vec![Value::Null]).neo4j_eq(&list(vec![Value::Null])), None);
+        assert_eq!(list(vec![Value::Null, Value::Int(1)]).neo4j_eq(&list(vec![Value::Null, Value::Int(2)])), Some(false));
+
+        let map = |v: Value| Value::Map([("k".to_string(), v)].into_iter().collect());
+        assert_eq!(map(Value::Int(2)).neo4j_eq(&map(Value::Float(2.0))), Some(true));
+        assert_eq!(map(Value::Null).neo4j_eq(&map(Value::Int(2))), None);
+        assert_eq!(map(Value::Int(2)).neo4j_eq(&Value::Map(HashMap::new())), Some(false));
+    }
+
+    #[test]
+    fn test_temporal_comparison() {
+        use chrono::TimeZone;
+        let a = Value::DateTime(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap());
+        let b = Value::DateTime(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 1).unwrap());
+        assert_eq!(a.neo4j_cmp(&b), Some(std::cmp::Ordering::Less));
+        // Different temporal types don't compare
+        let date = Value::Date(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        assert_eq!(a.neo4j_cmp(&date), None);
+    }
+
+    #[test]
+    fn test_equivalent() {
+        assert!(Value::Null.equivalent(&Value::Null));
+        assert!(Value::Float(f64::NAN).equivalent(&Value::Float(f64::NAN)));
+        assert!(Value::Int(1).equivalent(&Value::Float(1.0)));
+        assert!(Value::List(vec![Value::Int(1), Value::Null]).equivalent(&Value::List(vec![Value::Float(1.0), Value::Null])));
+        assert!(!Value::Int(1).equivalent(&Value::from("1")));
+        assert!(!Value::Null.equivalent(&Value::Int(0)));
+    }
+
+    #[test]
+    fn test_non_finite_floats() {
+        let (nan, inf) = (Value::Float(f64::NAN), Value::Float(f64::INFINITY));
+        assert_eq!(nan.to_string(), "NaN");
+        assert_eq!(inf.to_string(), "Infinity");
+        assert_eq!(Value::Float(f64::NEG_INFINITY).to_string(), "-Infinity");
+
+        // NaN compares as nothing, but orders after every other number
+        assert_eq!(nan.neo4j_cmp(&Value::Int(1)), None);
+        assert_eq!(nan.neo4j_cmp(&nan), None);
+        assert_eq!(inf.neo4j_cmp(&Value::Int(1)), Some(std::cmp::Ordering::Greater));
+        let mut values = [nan.clone(), Value::Int(1), inf.clone(), Value::Float(f64::NEG_INFINITY)];
+        values.sort_by(|a, b| a.order_cmp(b));
+        let rendered: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        assert_eq!(rendered, ["-Infinity", "1", "Infinity", "NaN"]);
+
+        // JSON has no NaN or infinities
+        assert_eq!(nan.to_json(), serde_json::Value::Null);
+        assert_eq!(Value::List(vec![inf, Value::Float(1.5)]).to_json(), serde_json::json!([null, 1.5]));
+    }
+
     #[test]
     fn test_value_from_bytes() {
         assert_eq!(Value::from(vec![1u8, 2, 3]), Value::Bytes(vec![1, 2, 3]));
@@ -0,0 +1,77 @@
+//! Plan cache: the optimized plans a `Graph` reuses, keyed by the
+//! `LogicalPlan::plan_hash` of the plan each query parses to.
+//!
+//! A query whose parsed plan hashes like one already run skips gathering
+//! statistics and optimizing, and runs the cached plan. Parameter values
+//! don't enter the hash, so `WHERE n.age > $min` shares one plan across
+//! every `$min`. A cached plan keeps the join order chosen from the label
+//! counts when it was first optimized. The cache is bounded and evicts the
+//! oldest plan first.
+
+use std::collections::{HashMap, VecDeque};
+
+use parking_lot::Mutex;
+
+use crate::planner::LogicalPlan;
+
+/// Plans a `Graph` caches unless `Graph::with_plan_cache_size` says otherwise.
+pub const DEFAULT_PLAN_CACHE_SIZE: usize = 256;
+
+/// Optimized plans by the hash of the plan they were optimized from.
+#[derive(Debug)]
+pub(crate) struct PlanCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    plans: HashMap<u64, LogicalPlan>,
+    /// Keys in insertion order, oldest first.
+    order: VecDeque<u64>,
+}
+
+impl PlanCache {
+    /// A cache of at most `capacity` plans; zero caches nothing.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self { capacity, state: Mutex::new(CacheState::default()) }
+    }
+
+    pub(crate) fn get(&self, key: u64) -> Option<LogicalPlan> {
+        self.state.lock().plans.get(&key).cloned()
+    }
+
+    pub(crate) fn insert(&self, key: u64, plan: LogicalPlan) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock();
+        if state.plans.insert(key, plan).is_some() {
+            return;
+        }
+        state.order.push_back(key);
+        while state.order.len() > self.capacity {
+            let oldest = state.order.pop_front().expect("over capacity, so not empty");
+            state.plans.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_oldest_past_capacity() {
+        let cache = PlanCache::new(2);
+        for key in 1..=3 {
+            cache.insert(key, LogicalPlan::Argument);
+        }
+        assert!(cache.get(1).is_none());
+        assert!(cache.get(2).is_some() && cache.get(3).is_some());
+
+        let off = PlanCache::new(0);
+        off.insert(1, LogicalPlan::Argument);
+        assert!(off.get(1).is_none());
+    }
+}
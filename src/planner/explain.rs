@@ -0,0 +1,341 @@
+//! Machine-readable plans; see `Graph::explain_json`.
+//!
+//! Each operator becomes an object holding its `operator` name, its own
+//! fields, `estimatedRows` and its `children`. Expressions are written back
+//! as Cypher text (`n.age > 30`), so tools can show them as the user would.
+//! Estimates come from `Statistics` and are upper bounds: a filter is
+//! assumed to keep every row. They are null where nothing bounds them.
+
+use serde_json::{json, Map, Value as Json};
+
+use crate::cypher::ast::*;
+use crate::model::Direction;
+use super::{plan_inputs, LogicalPlan, Statistics};
+
+/// `plan` as a JSON tree, with row estimates drawn from `stats`.
+pub fn plan_json(plan: &LogicalPlan, stats: &Statistics) -> Json {
+    let mut out = Map::new();
+    out.insert("operator".into(), json!(plan.operator_name()));
+    let fields = match plan {
+        LogicalPlan::NodeScan { label, alias } => json!({ "label": label, "alias": alias }),
+        LogicalPlan::MultiLabelScan { labels, alias } => json!({ "labels": labels, "alias": alias }),
+        LogicalPlan::AllNodesScan { alias } => json!({ "alias": alias }),
+        LogicalPlan::IndexLookup { label, property, value, alias } => {
+            json!({ "label": label, "property": property, "value": expr_text(value), "alias": alias })
+        }
+        LogicalPlan::IndexRangeScan { label, property, lower, upper, alias } => {
+            let bound = |b: &Option<(Expr, bool)>| {
+                b.as_ref().map(|(e, inclusive)| json!({ "value": expr_text(e), "inclusive": inclusive }))
+            };
+            json!({
+                "label": label,
+                "property": property,
+                "lower": bound(lower),
+                "upper": bound(upper),
+                "alias": alias,
+            })
+        }
+        LogicalPlan::Expand { from, dir, rel_types, to, rel_alias, path, .. } => json!({
+            "from": from,
+            "direction": match dir {
+                Direction::Outgoing => "OUTGOING",
+                Direction::Incoming => "INCOMING",
+                Direction::Both => "BOTH",
+            },
+            "relTypes": rel_types,
+            "to": to,
+            "relAlias": rel_alias,
+            "path": path,
+        }),
+        LogicalPlan::Filter { predicate, .. } => json!({ "predicate": expr_text(predicate) }),
+        LogicalPlan::Project { items, .. } => json!({ "items": aliased(items) }),
+        LogicalPlan::CreateNode { labels, properties, alias, .. } => {
+            json!({ "labels": labels, "properties": properties_json(properties), "alias": alias })
+        }
+        LogicalPlan::CreateRel { src, dst, rel_type, properties, alias, .. } => json!({
+            "src": src,
+            "dst": dst,
+            "relType": rel_type,
+            "properties": properties_json(properties),
+            "alias": alias,
+        }),
+        LogicalPlan::Limit { count, .. } | LogicalPlan::Skip { count, .. } => json!({ "count": count }),
+        LogicalPlan::LimitPercent { fraction, .. } => json!({ "fraction": fraction }),
+        LogicalPlan::Sort { keys, .. } => json!({
+            "keys": keys.iter().map(|(e, asc)| json!({ "expr": expr_text(e), "ascending": asc })).collect::<Vec<_>>(),
+        }),
+        LogicalPlan::HashJoin { left_key, right_key, .. } => {
+            json!({ "leftKey": expr_text(left_key), "rightKey": expr_text(right_key) })
+        }
+        LogicalPlan::CallProcedure { name, args, yields } => json!({
+            "name": name,
+            "args": args.iter().map(expr_text).collect::<Vec<_>>(),
+            "yields": yields,
+        }),
+        LogicalPlan::Aggregate { group_by, aggregations, .. } => {
+            json!({ "groupBy": aliased(group_by), "aggregations": aliased(aggregations) })
+        }
+        LogicalPlan::SetProperty { variable, key, value, .. } => {
+            json!({ "variable": variable, "key": key, "value": expr_text(value) })
+        }
+        LogicalPlan::SetProperties { variable, value, replace, .. } => {
+            json!({ "variable": variable, "value": expr_text(value), "replace": replace })
+        }
+        LogicalPlan::DeleteNode { variable, detach, .. } => json!({ "variable": variable, "detach": detach }),
+        LogicalPlan::DeleteRel { variable, .. } => json!({ "variable": variable }),
+        LogicalPlan::Unwind { expr, alias, .. } => json!({ "expr": expr_text(expr), "alias": alias }),
+        LogicalPlan::RemoveProperty { variable, key, .. } => json!({ "variable": variable, "key": key }),
+        LogicalPlan::RemoveLabel { variable, label, .. } => json!({ "variable": variable, "label": label }),
+        LogicalPlan::MergeNode { labels, properties, alias, on_create, on_match, .. } => {
+            let sets = |sets: &[(String, String, Expr)]| {
+                sets.iter()
+                    .map(|(variable, key, value)| json!({ "variable": variable, "key": key, "value": expr_text(value) }))
+                    .collect::<Vec<_>>()
+            };
+            json!({
+                "labels": labels,
+                "properties": properties_json(properties),
+                "alias": alias,
+                "onCreate": sets(on_create),
+                "onMatch": sets(on_match),
+            })
+        }
+        LogicalPlan::SchemaOp(command) => json!({ "command": schema_text(command) }),
+        LogicalPlan::LoadCsv { url, with_headers, alias, field_terminator } => json!({
+            "url": expr_text(url),
+            "withHeaders": with_headers,
+            "alias": alias,
+            "fieldTerminator": field_terminator.map(String::from),
+        }),
+        LogicalPlan::RelationshipCountFromCountStore { rel_type, column } => {
+            json!({ "relType": rel_type, "column": column })
+        }
+        LogicalPlan::CartesianProduct { .. } | LogicalPlan::Argument | LogicalPlan::Distinct { .. } => json!({}),
+    };
+    if let Json::Object(fields) = fields {
+        out.extend(fields);
+    }
+    out.insert("estimatedRows".into(), json!(estimated_rows(plan, stats)));
+    out.insert(
+        "children".into(),
+        Json::Array(plan_inputs(plan).into_iter().map(|p| plan_json(p, stats)).collect()),
+    );
+    Json::Object(out)
+}
+
+/// Labels of every label scan in `plan`, for gathering the `Statistics`
+/// that `plan_json` estimates from.
+pub fn scan_labels(plan: &LogicalPlan) -> Vec<String> {
+    let mut labels = Vec::new();
+    let mut stack = vec![plan];
+    while let Some(p) = stack.pop() {
+        let scanned = match p {
+            LogicalPlan::NodeScan { label, .. }
+            | LogicalPlan::IndexLookup { label, .. }
+            | LogicalPlan::IndexRangeScan { label, .. } => std::slice::from_ref(label),
+            LogicalPlan::MultiLabelScan { labels, .. } => labels.as_slice(),
+            _ => &[],
+        };
+        for label in scanned {
+            if !labels.contains(label) {
+                labels.push(label.clone());
+            }
+        }
+        stack.extend(plan_inputs(p));
+    }
+    labels
+}
+
+fn estimated_rows(plan: &LogicalPlan, stats: &Statistics) -> Option<u64> {
+    match plan {
+        LogicalPlan::Argument | LogicalPlan::RelationshipCountFromCountStore { .. } => Some(1),
+        LogicalPlan::Aggregate { group_by, .. } if group_by.is_empty() => Some(1),
+        LogicalPlan::IndexLookup { label, .. } => stats.label_counts.get(label).copied(),
+        LogicalPlan::Limit { input, count } => {
+            Some(estimated_rows(input, stats).map_or(*count as u64, |n| n.min(*count as u64)))
+        }
+        LogicalPlan::Skip { input, count } => estimated_rows(input, stats).map(|n| n.saturating_sub(*count as u64)),
+        LogicalPlan::CartesianProduct { left, right } => {
+            Some(estimated_rows(left, stats)?.saturating_mul(estimated_rows(right, stats)?))
+        }
+        LogicalPlan::Filter { input, .. }
+        | LogicalPlan::Project { input, .. }
+        | LogicalPlan::Sort { input, .. }
+        | LogicalPlan::Distinct { input }
+        | LogicalPlan::Aggregate { input, .. }
+        | LogicalPlan::LimitPercent { input, .. } => estimated_rows(input, stats),
+        other => stats.cardinality(other),
+    }
+}
+
+fn aliased(items: &[(Expr, String)]) -> Vec<Json> {
+    items.iter().map(|(e, alias)| json!({ "expr": expr_text(e), "alias": alias })).collect()
+}
+
+fn properties_json(properties: &[(String, Expr)]) -> Json {
+    Json::Object(properties.iter().map(|(k, v)| (k.clone(), json!(expr_text(v)))).collect())
+}
+
+pub(super) fn schema_text(command: &SchemaCommand) -> String {
+    match command {
+        SchemaCommand::CreateIndex { label, property, index_type } => match index_type {
+            Some(t) => format!("CREATE {t} INDEX FOR (n:{label}) ON (n.{property})"),
+            None => format!("CREATE INDEX FOR (n:{label}) ON (n.{property})"),
+        },
+        SchemaCommand::DropIndex { label, property } => format!("DROP INDEX ON :{label}({property})"),
+        SchemaCommand::CreateConstraint { label, property, constraint_type } => {
+            format!("CREATE CONSTRAINT FOR (n:{label}) REQUIRE n.{property} IS {constraint_type}")
+        }
+        SchemaCommand::DropConstraint { label, property } => format!("DROP CONSTRAINT ON (n:{label}) ASSERT n.{property}"),
+    }
+}
+
+/// `expr` as Cypher text. Operands that are themselves operations are
+/// parenthesized, so the text reads the same way the tree groups.
+pub fn expr_text(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal(Literal::Null) => "null".into(),
+        Expr::Literal(Literal::Bool(b)) => b.to_string(),
+        Expr::Literal(Literal::Int(i)) => i.to_string(),
+        Expr::Literal(Literal::Float(f)) => format!("{f:?}"),
+        Expr::Literal(Literal::String(s)) => format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'")),
+        Expr::Variable { name, .. } => name.clone(),
+        Expr::Parameter { name, .. } => format!("${name}"),
+        Expr::Property { expr, key } => format!("{}.{key}", operand(expr)),
+        Expr::FunctionCall { name, args, distinct, .. } => {
+            let args = args.iter().map(expr_text).collect::<Vec<_>>().join(", ");
+            if *distinct { format!("{name}(DISTINCT {args})") } else { format!("{name}({args})") }
+        }
+        Expr::BinaryOp { left, op, right } => format!("{} {} {}", operand(left), binary_op(*op), operand(right)),
+        Expr::UnaryOp { op: UnaryOp::Not, expr } => format!("NOT {}", operand(expr)),
+        Expr::UnaryOp { op: UnaryOp::Negate, expr } => format!("-{}", operand(expr)),
+        Expr::List(items) => format!("[{}]", items.iter().map(expr_text).collect::<Vec<_>>().join(", ")),
+        Expr::MapLiteral(entries) => {
+            let mut entries: Vec<_> = entries.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let entries: Vec<String> = entries.into_iter().map(|(k, v)| format!("{k}: {}", expr_text(v))).collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Expr::Case { operand: subject, whens, else_expr } => {
+            let mut out = String::from("CASE");
+            if let Some(subject) = subject {
+                out += &format!(" {}", expr_text(subject));
+            }
+            for (when, then) in whens {
+                out += &format!(" WHEN {} THEN {}", expr_text(when), expr_text(then));
+            }
+            if let Some(e) = else_expr {
+                out += &format!(" ELSE {}", expr_text(e));
+            }
+            out + " END"
+        }
+        Expr::Exists { pattern, where_clause } => format!("EXISTS {}", subquery_text(pattern, where_clause.as_deref())),
+        Expr::CountSubquery { pattern, where_clause } => {
+            format!("COUNT {}", subquery_text(pattern, where_clause.as_deref()))
+        }
+        Expr::In { expr, list } => format!("{} IN {}", operand(expr), operand(list)),
+        Expr::IsNull { expr, negated: false } => format!("{} IS NULL", operand(expr)),
+        Expr::IsNull { expr, negated: true } => format!("{} IS NOT NULL", operand(expr)),
+        Expr::HasLabel { expr, label } => format!("{}:{label}", operand(expr)),
+        Expr::StringOp { left, op, right } => {
+            let op = match op {
+                StringOp::StartsWith => "STARTS WITH",
+                StringOp::EndsWith => "ENDS WITH",
+                StringOp::Contains => "CONTAINS",
+            };
+            format!("{} {op} {}", operand(left), operand(right))
+        }
+        Expr::Star => "*".into(),
+    }
+}
+
+fn operand(expr: &Expr) -> String {
+    match expr {
+        Expr::BinaryOp { .. }
+        | Expr::UnaryOp { .. }
+        | Expr::In { .. }
+        | Expr::IsNull { .. }
+        | Expr::HasLabel { .. }
+        | Expr::StringOp { .. } => format!("({})", expr_text(expr)),
+        _ => expr_text(expr),
+    }
+}
+
+fn binary_op(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Mod => "%",
+        BinaryOp::Pow => "^",
+        BinaryOp::Eq => "=",
+        BinaryOp::Neq => "<>",
+        BinaryOp::Lt => "<",
+        BinaryOp::Lte => "<=",
+        BinaryOp::Gt => ">",
+        BinaryOp::Gte => ">=",
+        BinaryOp::And => "AND",
+        BinaryOp::Or => "OR",
+        BinaryOp::Xor => "XOR",
+        BinaryOp::RegexMatch => "=~",
+    }
+}
+
+fn subquery_text(body: &MatchClause, where_clause: Option<&Expr>) -> String {
+    let patterns: Vec<String> = body.patterns.iter().map(pattern_text).collect();
+    match where_clause {
+        Some(w) => format!("{{ MATCH {} WHERE {} }}", patterns.join(", "), expr_text(w)),
+        None => format!("{{ MATCH {} }}", patterns.join(", ")),
+    }
+}
+
+fn pattern_text(pattern: &Pattern) -> String {
+    let mut out = pattern.path.as_ref().map(|p| format!("{p} = ")).unwrap_or_default();
+    for element in &pattern.elements {
+        match element {
+            PatternElement::Node(np) => {
+                let inner = element_text(&np.alias, &np.labels, ":", &np.properties, np.predicate.as_ref());
+                out += &format!("({inner})");
+            }
+            PatternElement::Relationship(rp) => {
+                let mut inner = element_text(&rp.alias, &rp.rel_types, "|", &rp.properties, None);
+                if let Some(v) = &rp.var_length {
+                    let bound = |b: Option<usize>| b.map(|n| n.to_string()).unwrap_or_default();
+                    inner += &format!("*{}..{}", bound(v.min), bound(v.max));
+                }
+                if let Some(p) = &rp.predicate {
+                    inner += &format!(" WHERE {}", expr_text(p));
+                }
+                out += &match rp.direction {
+                    PatternDirection::Right => format!("-[{inner}]->"),
+                    PatternDirection::Left => format!("<-[{inner}]-"),
+                    PatternDirection::Both => format!("-[{inner}]-"),
+                };
+            }
+        }
+    }
+    out
+}
+
+/// The inside of `(...)` or `[...]`: alias, labels or types, properties and
+/// an inline WHERE.
+fn element_text(
+    alias: &Option<String>,
+    names: &[String],
+    separator: &str,
+    properties: &std::collections::HashMap<String, Expr>,
+    predicate: Option<&Expr>,
+) -> String {
+    let mut out = alias.clone().unwrap_or_default();
+    if !names.is_empty() {
+        out += &format!(":{}", names.join(separator));
+    }
+    if !properties.is_empty() {
+        out += &format!(" {}", expr_text(&Expr::MapLiteral(properties.clone())));
+    }
+    if let Some(p) = predicate {
+        out += &format!(" WHERE {}", expr_text(p));
+    }
+    out
+}
@@ -0,0 +1,368 @@
+//! Structural plan fingerprints; see `LogicalPlan::plan_hash`.
+//!
+//! The hash covers operators, names and literal constants. It skips what
+//! doesn't change meaning: source spans (so whitespace and layout don't
+//! matter), map key order, and parameter values (`$name` hashes by name).
+//! FNV-1a keeps it stable across processes and Rust releases, unlike
+//! `DefaultHasher`.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::cypher::ast::*;
+use super::LogicalPlan;
+
+impl LogicalPlan {
+    /// A stable structural hash of this plan tree: `Graph` keys its plan
+    /// cache by it (see `plan_cache`), and telemetry groups executions of
+    /// the same query shape by it (see `ExecutionStats::plan_hash`). Plans
+    /// that differ only in whitespace, map key order or parameter values
+    /// hash equal.
+    pub fn plan_hash(&self) -> u64 {
+        let mut h = Fnv1a::default();
+        plan(&mut h, self);
+        h.finish()
+    }
+}
+
+/// A structural id for `e`, hashed like expressions in `plan_hash`:
+/// `n` in `n.a` and in `n.b` get the same id, whatever their spans.
+pub(crate) fn expr_hash(e: &Expr) -> u64 {
+    let mut h = Fnv1a::default();
+    expr(&mut h, e);
+    h.finish()
+}
+
+/// 64-bit FNV-1a.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+fn plan(h: &mut Fnv1a, p: &LogicalPlan) {
+    p.operator_name().hash(h);
+    match p {
+        LogicalPlan::NodeScan { label, alias } => (label, alias).hash(h),
+        LogicalPlan::MultiLabelScan { labels, alias } => (labels, alias).hash(h),
+        LogicalPlan::AllNodesScan { alias } => alias.hash(h),
+        LogicalPlan::IndexLookup { label, property, value, alias } => {
+            (label, property, alias).hash(h);
+            expr(h, value);
+        }
+        LogicalPlan::IndexRangeScan { label, property, lower, upper, alias } => {
+            (label, property, alias).hash(h);
+            for bound in [lower, upper] {
+                bound.is_some().hash(h);
+                if let Some((e, inclusive)) = bound {
+                    expr(h, e);
+                    inclusive.hash(h);
+                }
+            }
+        }
+        LogicalPlan::Expand { input, from, dir, rel_types, to, rel_alias, path, unique_from } => {
+            plan(h, input);
+            (from, dir, rel_types, to, rel_alias, path, unique_from).hash(h);
+        }
+        LogicalPlan::Filter { input, predicate } => {
+            plan(h, input);
+            expr(h, predicate);
+        }
+        LogicalPlan::Project { input, items } => {
+            plan(h, input);
+            projection(h, items);
+        }
+        LogicalPlan::CreateNode { input, labels, properties, alias } => {
+            opt_plan(h, input);
+            (labels, alias).hash(h);
+            assignments(h, properties);
+        }
+        LogicalPlan::CreateRel { input, src, dst, rel_type, properties, alias } => {
+            opt_plan(h, input);
+            (src, dst, rel_type, alias).hash(h);
+            assignments(h, properties);
+        }
+        LogicalPlan::Limit { input, count } | LogicalPlan::Skip { input, count } => {
+            plan(h, input);
+            count.hash(h);
+        }
+        LogicalPlan::LimitPercent { input, fraction } => {
+            plan(h, input);
+            fraction.to_bits().hash(h);
+        }
+        LogicalPlan::Sort { input, keys } => {
+            plan(h, input);
+            keys.len().hash(h);
+            for (e, ascending) in keys {
+                expr(h, e);
+                ascending.hash(h);
+            }
+        }
+        LogicalPlan::CartesianProduct { left, right } => {
+            plan(h, left);
+            plan(h, right);
+        }
+        LogicalPlan::HashJoin { left, right, left_key, right_key } => {
+            plan(h, left);
+            plan(h, right);
+            expr(h, left_key);
+            expr(h, right_key);
+        }
+        LogicalPlan::CallProcedure { name, args, yields } => {
+            name.hash(h);
+            exprs(h, args);
+            yields.hash(h);
+        }
+        LogicalPlan::Argument => {}
+        LogicalPlan::Aggregate { input, group_by, aggregations } => {
+            plan(h, input);
+            projection(h, group_by);
+            projection(h, aggregations);
+        }
+        LogicalPlan::Distinct { input } => plan(h, input),
+        LogicalPlan::SetProperty { input, variable, key, value } => {
+            plan(h, input);
+            (variable, key).hash(h);
+            expr(h, value);
+        }
+        LogicalPlan::SetProperties { input, variable, value, replace } => {
+            plan(h, input);
+            (variable, replace).hash(h);
+            expr(h, value);
+        }
+        LogicalPlan::DeleteNode { input, variable, detach } => {
+            plan(h, input);
+            (variable, detach).hash(h);
+        }
+        LogicalPlan::DeleteRel { input, variable } => {
+            plan(h, input);
+            variable.hash(h);
+        }
+        LogicalPlan::Unwind { input, expr: e, alias } => {
+            plan(h, input);
+            expr(h, e);
+            alias.hash(h);
+        }
+        LogicalPlan::RemoveProperty { input, variable, key } => {
+            plan(h, input);
+            (variable, key).hash(h);
+        }
+        LogicalPlan::RemoveLabel { input, variable, label } => {
+            plan(h, input);
+            (variable, label).hash(h);
+        }
+        LogicalPlan::MergeNode { input, labels, properties, alias, on_create, on_match } => {
+            opt_plan(h, input);
+            (labels, alias).hash(h);
+            assignments(h, properties);
+            for items in [on_create, on_match] {
+                items.len().hash(h);
+                for (variable, key, e) in items {
+                    (variable, key).hash(h);
+                    expr(h, e);
+                }
+            }
+        }
+        LogicalPlan::SchemaOp(cmd) => schema_command(h, cmd),
+        LogicalPlan::LoadCsv { url, with_headers, alias, field_terminator } => {
+            expr(h, url);
+            (with_headers, alias, field_terminator).hash(h);
+        }
+        LogicalPlan::RelationshipCountFromCountStore { rel_type, column } => (rel_type, column).hash(h),
+    }
+}
+
+fn opt_plan(h: &mut Fnv1a, p: &Option<Box<LogicalPlan>>) {
+    p.is_some().hash(h);
+    if let Some(p) = p {
+        plan(h, p);
+    }
+}
+
+fn schema_command(h: &mut Fnv1a, cmd: &SchemaCommand) {
+    match cmd {
+        SchemaCommand::CreateIndex { label, property, index_type } => {
+            ("CreateIndex", label, property, index_type).hash(h);
+        }
+        SchemaCommand::DropIndex { label, property } => ("DropIndex", label, property).hash(h),
+        SchemaCommand::CreateConstraint { label, property, constraint_type } => {
+            ("CreateConstraint", label, property, constraint_type).hash(h);
+        }
+        SchemaCommand::DropConstraint { label, property } => ("DropConstraint", label, property).hash(h),
+    }
+}
+
+fn exprs(h: &mut Fnv1a, es: &[Expr]) {
+    es.len().hash(h);
+    for e in es {
+        expr(h, e);
+    }
+}
+
+/// `(expr, alias)` projection items, in order.
+fn projection(h: &mut Fnv1a, items: &[(Expr, String)]) {
+    items.len().hash(h);
+    for (e, alias) in items {
+        expr(h, e);
+        alias.hash(h);
+    }
+}
+
+/// `(key, expr)` property assignments, sorted by key: the planner builds
+/// them from pattern maps, so their order is arbitrary.
+fn assignments(h: &mut Fnv1a, items: &[(String, Expr)]) {
+    let mut items: Vec<_> = items.iter().collect();
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    items.len().hash(h);
+    for (key, e) in items {
+        key.hash(h);
+        expr(h, e);
+    }
+}
+
+/// Map entries sorted by key, since `HashMap` iteration order is arbitrary.
+fn expr_map(h: &mut Fnv1a, map: &HashMap<String, Expr>) {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries.len().hash(h);
+    for (k, v) in entries {
+        k.hash(h);
+        expr(h, v);
+    }
+}
+
+fn expr(h: &mut Fnv1a, e: &Expr) {
+    match e {
+        Expr::Literal(lit) => match lit {
+            Literal::Null => "null".hash(h),
+            Literal::Bool(b) => ("bool", b).hash(h),
+            Literal::Int(i) => ("int", i).hash(h),
+            Literal::Float(f) => ("float", f.to_bits()).hash(h),
+            Literal::String(s) => ("string", s).hash(h),
+        },
+        Expr::Variable { name, span: _ } => ("var", name).hash(h),
+        Expr::Parameter { name, span: _ } => ("param", name).hash(h),
+        Expr::Property { expr: inner, key } => {
+            "prop".hash(h);
+            expr(h, inner);
+            key.hash(h);
+        }
+        Expr::FunctionCall { name, args, distinct, span: _ } => {
+            "call".hash(h);
+            // Function names are case-insensitive
+            name.to_ascii_lowercase().hash(h);
+            exprs(h, args);
+            distinct.hash(h);
+        }
+        Expr::BinaryOp { left, op, right } => {
+            "binary".hash(h);
+            expr(h, left);
+            op.hash(h);
+            expr(h, right);
+        }
+        Expr::UnaryOp { op, expr: inner } => {
+            "unary".hash(h);
+            op.hash(h);
+            expr(h, inner);
+        }
+        Expr::List(items) => {
+            "list".hash(h);
+            exprs(h, items);
+        }
+        Expr::MapLiteral(map) => {
+            "map".hash(h);
+            expr_map(h, map);
+        }
+        Expr::Case { operand, whens, else_expr } => {
+            "case".hash(h);
+            operand.is_some().hash(h);
+            if let Some(operand) = operand {
+                expr(h, operand);
+            }
+            whens.len().hash(h);
+            for (when, then) in whens {
+                expr(h, when);
+                expr(h, then);
+            }
+            else_expr.is_some().hash(h);
+            if let Some(else_expr) = else_expr {
+                expr(h, else_expr);
+            }
+        }
+        Expr::Exists { pattern, where_clause } => {
+            "exists".hash(h);
+            match_clause(h, pattern);
+            where_clause.is_some().hash(h);
+            if let Some(w) = where_clause {
+                expr(h, w);
+            }
+        }
+        Expr::CountSubquery { pattern, where_clause } => {
+            "count_subquery".hash(h);
+            match_clause(h, pattern);
+            where_clause.is_some().hash(h);
+            if let Some(w) = where_clause {
+                expr(h, w);
+            }
+        }
+        Expr::In { expr: inner, list } => {
+            "in".hash(h);
+            expr(h, inner);
+            expr(h, list);
+        }
+        Expr::IsNull { expr: inner, negated } => {
+            "is_null".hash(h);
+            expr(h, inner);
+            negated.hash(h);
+        }
+        Expr::HasLabel { expr: inner, label } => {
+            "has_label".hash(h);
+            expr(h, inner);
+            label.hash(h);
+        }
+        Expr::StringOp { left, op, right } => {
+            "string_op".hash(h);
+            expr(h, left);
+            op.hash(h);
+            expr(h, right);
+        }
+        Expr::Star => "star".hash(h),
+    }
+}
+
+fn match_clause(h: &mut Fnv1a, m: &MatchClause) {
+    m.optional.hash(h);
+    m.patterns.len().hash(h);
+    for pattern in &m.patterns {
+        pattern.path.hash(h);
+        pattern.elements.len().hash(h);
+        for element in &pattern.elements {
+            match element {
+                PatternElement::Node(n) => {
+                    ("node", &n.alias, &n.labels).hash(h);
+                    expr_map(h, &n.properties);
+                }
+                PatternElement::Relationship(r) => {
+                    ("rel", &r.alias, &r.rel_types, r.direction).hash(h);
+                    expr_map(h, &r.properties);
+                    r.var_length.as_ref().map(|v| (v.min, v.max)).hash(h);
+                }
+            }
+        }
+    }
+}
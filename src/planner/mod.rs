@@ -3,37 +3,80 @@
 //! The planner is backend-agnostic. It produces logical operators that
 //! the execution engine maps to StorageBackend calls.
 
+use std::collections::HashMap;
+
 use crate::model::PropertyMap;
+use crate::storage::BackendCapabilities;
 use crate::cypher::ast::{self, *};
 use crate::{Error, Result};
 
+mod explain;
+mod fingerprint;
+mod notifications;
+mod typecheck;
+pub(crate) use fingerprint::expr_hash;
+pub use explain::{expr_text, plan_json, scan_labels};
+pub use notifications::{Notification, plan_notifications, statement_notifications};
+pub use typecheck::{Schema, typecheck};
+
 /// Logical plan node.
 #[derive(Debug, Clone)]
 pub enum LogicalPlan {
     /// Scan all nodes with a given label
     NodeScan { label: String, alias: String },
+    /// Scan nodes carrying every one of several labels
+    MultiLabelScan { labels: Vec<String>, alias: String },
     /// Scan ALL nodes (no label filter)
     AllNodesScan { alias: String },
-    /// Index-backed property lookup
-    IndexLookup { label: String, property: String, alias: String },
-    /// Expand relationships from a node (piped from input plan)
-    Expand { input: Box<LogicalPlan>, from: String, dir: crate::model::Direction, rel_types: Vec<String>, to: String, rel_alias: Option<String> },
+    /// Index-backed lookup of the `label` nodes whose `property` equals
+    /// `value`, a literal or parameter. Planned for `USING INDEX` hints.
+    IndexLookup { label: String, property: String, value: Expr, alias: String },
+    /// Label scan narrowed to nodes whose `property` lies between the bounds.
+    /// Each bound is a literal or parameter paired with whether it is inclusive.
+    IndexRangeScan {
+        label: String,
+        property: String,
+        lower: Option<(Expr, bool)>,
+        upper: Option<(Expr, bool)>,
+        alias: String,
+    },
+    /// Expand relationships from a node (piped from input plan). With `path`,
+    /// the hop is appended to the path bound there, started at `from` by the
+    /// pattern's first expand. A relationship already bound to one of
+    /// `unique_from`, the earlier hops of the same pattern, is not traversed
+    /// again (Cypher's relationship uniqueness).
+    Expand {
+        input: Box<LogicalPlan>,
+        from: String,
+        dir: crate::model::Direction,
+        rel_types: Vec<String>,
+        to: String,
+        rel_alias: Option<String>,
+        path: Option<String>,
+        unique_from: Vec<String>,
+    },
     /// Filter rows by predicate
     Filter { input: Box<LogicalPlan>, predicate: Expr },
     /// Project columns
     Project { input: Box<LogicalPlan>, items: Vec<(Expr, String)> },
     /// Create node
-    CreateNode { labels: Vec<String>, properties: Vec<(String, Expr)>, alias: String },
+    CreateNode { input: Option<Box<LogicalPlan>>, labels: Vec<String>, properties: Vec<(String, Expr)>, alias: String },
     /// Create relationship (optionally piped from an input plan for MATCH...CREATE)
-    CreateRel { input: Option<Box<LogicalPlan>>, src: String, dst: String, rel_type: String, properties: Vec<(String, Expr)> },
+    /// `alias` binds the new relationship in the output row, when the pattern names it.
+    CreateRel { input: Option<Box<LogicalPlan>>, src: String, dst: String, rel_type: String, properties: Vec<(String, Expr)>, alias: Option<String> },
     /// Limit output rows
     Limit { input: Box<LogicalPlan>, count: usize },
+    /// Keep the first `fraction` of the rows (`LIMIT n PERCENT`), rounding
+    /// up and keeping at least one row when `fraction` is positive
+    LimitPercent { input: Box<LogicalPlan>, fraction: f64 },
     /// Skip first N rows
     Skip { input: Box<LogicalPlan>, count: usize },
     /// Sort
     Sort { input: Box<LogicalPlan>, keys: Vec<(Expr, bool)> },
     /// Cartesian product of two inputs
     CartesianProduct { left: Box<LogicalPlan>, right: Box<LogicalPlan> },
+    /// Equi-join of two independent inputs on `left_key = right_key`
+    HashJoin { left: Box<LogicalPlan>, right: Box<LogicalPlan>, left_key: Expr, right_key: Expr },
     /// Call a procedure: CALL name(args) YIELD columns
     CallProcedure { name: String, args: Vec<Expr>, yields: Vec<String> },
     /// Empty leaf (produces one empty row)
@@ -44,6 +87,8 @@ pub enum LogicalPlan {
     Distinct { input: Box<LogicalPlan> },
     /// SET n.key = expr
     SetProperty { input: Box<LogicalPlan>, variable: String, key: String, value: Expr },
+    /// SET n = map (`replace`, dropping keys the map lacks) or SET n += map
+    SetProperties { input: Box<LogicalPlan>, variable: String, value: Expr, replace: bool },
     /// DELETE n (or DETACH DELETE n)
     DeleteNode { input: Box<LogicalPlan>, variable: String, detach: bool },
     /// DELETE r
@@ -65,43 +110,210 @@ pub enum LogicalPlan {
     },
     /// CREATE INDEX / CREATE CONSTRAINT / DROP INDEX / DROP CONSTRAINT
     SchemaOp(SchemaCommand),
+    /// LOAD CSV: one row per record, bound to `alias`
+    LoadCsv { url: Expr, with_headers: bool, alias: String, field_terminator: Option<char> },
+    /// One row binding `column` to the number of `rel_type` relationships,
+    /// read from the backend's count instead of expanding them
+    RelationshipCountFromCountStore { rel_type: String, column: String },
+}
+
+impl LogicalPlan {
+    /// The operator's variant name, e.g. `"NodeScan"`.
+    pub fn operator_name(&self) -> &'static str {
+        match self {
+            LogicalPlan::NodeScan { .. } => "NodeScan",
+            LogicalPlan::MultiLabelScan { .. } => "MultiLabelScan",
+            LogicalPlan::AllNodesScan { .. } => "AllNodesScan",
+            LogicalPlan::IndexLookup { .. } => "IndexLookup",
+            LogicalPlan::IndexRangeScan { .. } => "IndexRangeScan",
+            LogicalPlan::Expand { .. } => "Expand",
+            LogicalPlan::Filter { .. } => "Filter",
+            LogicalPlan::Project { .. } => "Project",
+            LogicalPlan::CreateNode { .. } => "CreateNode",
+            LogicalPlan::CreateRel { .. } => "CreateRel",
+            LogicalPlan::Limit { .. } => "Limit",
+            LogicalPlan::LimitPercent { .. } => "LimitPercent",
+            LogicalPlan::Skip { .. } => "Skip",
+            LogicalPlan::Sort { .. } => "Sort",
+            LogicalPlan::CartesianProduct { .. } => "CartesianProduct",
+            LogicalPlan::HashJoin { .. } => "HashJoin",
+            LogicalPlan::CallProcedure { .. } => "CallProcedure",
+            LogicalPlan::Argument => "Argument",
+            LogicalPlan::Aggregate { .. } => "Aggregate",
+            LogicalPlan::Distinct { .. } => "Distinct",
+            LogicalPlan::SetProperty { .. } => "SetProperty",
+            LogicalPlan::SetProperties { .. } => "SetProperties",
+            LogicalPlan::DeleteNode { .. } => "DeleteNode",
+            LogicalPlan::DeleteRel { .. } => "DeleteRel",
+            LogicalPlan::Unwind { .. } => "Unwind",
+            LogicalPlan::RemoveProperty { .. } => "RemoveProperty",
+            LogicalPlan::RemoveLabel { .. } => "RemoveLabel",
+            LogicalPlan::MergeNode { .. } => "MergeNode",
+            LogicalPlan::SchemaOp(_) => "SchemaOp",
+            LogicalPlan::LoadCsv { .. } => "LoadCsv",
+            LogicalPlan::RelationshipCountFromCountStore { .. } => "RelationshipCountFromCountStore",
+        }
+    }
 }
 
 /// Create a logical plan from a parsed AST.
 pub fn plan(ast: &Statement, params: &PropertyMap) -> Result<LogicalPlan> {
     let _ = params; // used by optimize() later
+    let plan = plan_statement(ast, &mut AnonNames::default())?;
+    reject_misplaced_distinct(&plan)?;
+    Ok(plan)
+}
+
+fn plan_statement(ast: &Statement, names: &mut AnonNames) -> Result<LogicalPlan> {
     match ast {
-        Statement::Query(q) => plan_query(q),
-        Statement::Create(c) => plan_create(c),
-        Statement::Delete(d) => plan_delete(d),
-        Statement::Set(s) => plan_set(s),
-        Statement::Merge(m) => plan_merge(m),
+        Statement::Query(q) => plan_query(q, names),
+        Statement::Create(c) => plan_create(c, names),
+        Statement::Delete(d) => plan_delete(d, names),
+        Statement::Set(s) => plan_set(s, names),
+        Statement::Merge(m) => plan_merge(m, names),
         Statement::Schema(s) => Ok(LogicalPlan::SchemaOp(s.clone())),
-        Statement::Remove(r) => plan_remove(r),
+        Statement::Remove(r) => plan_remove(r, names),
+        Statement::Call(c) => Ok(LogicalPlan::CallProcedure {
+            name: c.name.clone(),
+            args: c.args.clone(),
+            yields: c.yields.clone(),
+        }),
+        Statement::LoadCsv { load, body } => {
+            let source = LogicalPlan::LoadCsv {
+                url: load.url.clone(),
+                with_headers: load.with_headers,
+                alias: load.alias.clone(),
+                field_terminator: load.field_terminator,
+            };
+            Ok(attach_source(plan_statement(body, names)?, source))
+        }
     }
 }
 
-fn plan_query(q: &Query) -> Result<LogicalPlan> {
-    let mut current = if q.matches.is_empty() {
-        LogicalPlan::Argument
-    } else {
-        plan_matches(&q.matches)?
+/// Honor `USING INDEX`: replace the label scan of `hint.variable` with an
+/// `IndexLookup`, taking the `variable.property = value` conjunct it answers
+/// out of the WHERE filter on top of `plan`.
+fn apply_index_hint(plan: LogicalPlan, hint: &IndexHint) -> Result<LogicalPlan> {
+    let IndexHint { variable, label, property, span } = hint;
+    let unusable = |why: &str| {
+        crate::plan_err!("Cannot use index hint USING INDEX {variable}:{label}({property}): {why}").or_span(*span)
+    };
+    let LogicalPlan::Filter { input, predicate } = plan else {
+        return Err(unusable(&format!("no predicate on {variable}.{property}")));
     };
 
-    if let Some(ref where_expr) = q.where_clause {
-        current = LogicalPlan::Filter {
-            input: Box::new(current),
-            predicate: where_expr.clone(),
-        };
+    let mut conjuncts = Vec::new();
+    split_conjuncts(predicate, &mut conjuncts);
+    let position = conjuncts.iter().position(|c| equality_value(c, variable, property).is_some());
+    let Some(position) = position else {
+        return Err(unusable(&format!("no {variable}.{property} = <literal or parameter> predicate")));
+    };
+    let mut value = equality_value(&conjuncts.remove(position), variable, property);
+
+    fn replace_scan(plan: LogicalPlan, hint: &IndexHint, value: &mut Option<Expr>) -> LogicalPlan {
+        match plan {
+            LogicalPlan::NodeScan { label, alias } if alias == hint.variable && label == hint.label => {
+                match value.take() {
+                    Some(value) => LogicalPlan::IndexLookup { label, property: hint.property.clone(), value, alias },
+                    None => LogicalPlan::NodeScan { label, alias },
+                }
+            }
+            other => map_inputs(other, |child| replace_scan(child, hint, value)),
+        }
     }
+    let input = replace_scan(*input, hint, &mut value);
+    if value.is_some() {
+        return Err(unusable(&format!("{variable} is not matched as (:{label}) by a label scan")));
+    }
+    Ok(match join_conjuncts(conjuncts) {
+        Some(predicate) => LogicalPlan::Filter { input: Box::new(input), predicate },
+        None => input,
+    })
+}
 
-    // Apply UNWIND clauses
-    for (expr, alias) in &q.unwinds {
-        current = LogicalPlan::Unwind {
-            input: Box::new(current),
-            expr: expr.clone(),
-            alias: alias.clone(),
-        };
+/// The literal or parameter that `expr` equates `alias.property` with.
+fn equality_value(expr: &Expr, alias: &str, property: &str) -> Option<Expr> {
+    let Expr::BinaryOp { left, op: BinaryOp::Eq, right } = expr else { return None };
+    let is_property = |e: &Expr| match e {
+        Expr::Property { expr, key } => {
+            key == property && matches!(expr.as_ref(), Expr::Variable { name, .. } if name == alias)
+        }
+        _ => false,
+    };
+    let is_constant = |e: &Expr| matches!(e, Expr::Literal(_) | Expr::Parameter { .. });
+    if is_property(left) && is_constant(right) {
+        Some((**right).clone())
+    } else if is_property(right) && is_constant(left) {
+        Some((**left).clone())
+    } else {
+        None
+    }
+}
+
+/// Feed `source` rows into the leftmost leaf of `plan`, so every operator
+/// downstream of it runs once per source row.
+fn attach_source(plan: LogicalPlan, source: LogicalPlan) -> LogicalPlan {
+    match plan {
+        LogicalPlan::Argument => source,
+        scan @ (LogicalPlan::NodeScan { .. }
+        | LogicalPlan::MultiLabelScan { .. }
+        | LogicalPlan::AllNodesScan { .. }
+        | LogicalPlan::IndexLookup { .. }
+        | LogicalPlan::IndexRangeScan { .. }
+        | LogicalPlan::CallProcedure { .. }) => {
+            LogicalPlan::CartesianProduct { left: Box::new(source), right: Box::new(scan) }
+        }
+        LogicalPlan::CreateNode { input: None, labels, properties, alias } => {
+            LogicalPlan::CreateNode { input: Some(Box::new(source)), labels, properties, alias }
+        }
+        LogicalPlan::CreateRel { input: None, src, dst, rel_type, properties, alias } => {
+            LogicalPlan::CreateRel { input: Some(Box::new(source)), src, dst, rel_type, properties, alias }
+        }
+        LogicalPlan::MergeNode { input: None, labels, properties, alias, on_create, on_match } => {
+            LogicalPlan::MergeNode { input: Some(Box::new(source)), labels, properties, alias, on_create, on_match }
+        }
+        other => {
+            let mut source = Some(source);
+            map_inputs(other, |child| match source.take() {
+                Some(s) => attach_source(child, s),
+                None => child,
+            })
+        }
+    }
+}
+
+fn plan_query(q: &Query, names: &mut AnonNames) -> Result<LogicalPlan> {
+    let mut current = LogicalPlan::Argument;
+    let (mut matches, mut unwinds) = (0, 0);
+    // Each WITH closes a segment: the clauses before it run first, then its
+    // WHERE filters on what it passes on, its names inlined, before the
+    // clauses after it see a row
+    for i in 0..=q.with_clauses.len() {
+        let with = q.with_clauses.get(i);
+        let (match_end, unwind_end) = with.map_or((q.matches.len(), q.unwinds.len()), |w| (w.preceding_matches, w.preceding_unwinds));
+        current = continue_matches(current, &q.matches[matches..match_end], names)?;
+
+        // The WHERE and hints parsed with the last MATCH apply once it has run
+        if match_end == q.matches.len() && matches < match_end {
+            current = plan_match_where(current, q)?;
+        }
+
+        for (expr, alias) in &q.unwinds[unwinds..unwind_end] {
+            current = LogicalPlan::Unwind {
+                input: Box::new(current),
+                expr: expr.clone(),
+                alias: alias.clone(),
+            };
+        }
+        (matches, unwinds) = (match_end, unwind_end);
+
+        let Some(where_expr) = with.and_then(|w| w.where_clause.as_ref()) else { continue };
+        let predicate = inline_aliases(where_expr, &with_scope(q, i + 1).0);
+        if is_aggregate_expr(&predicate) {
+            return Err(crate::plan_err!("WHERE after an aggregating WITH is not supported")
+                .or_span(typecheck::covering_span(where_expr)));
+        }
+        current = LogicalPlan::Filter { input: Box::new(current), predicate };
     }
 
     // Sort BEFORE projection so ORDER BY expressions can reference
@@ -113,7 +325,14 @@ fn plan_query(q: &Query) -> Result<LogicalPlan> {
         current = LogicalPlan::Sort { input: Box::new(current), keys };
     }
 
-    let (has_agg, group_by, aggregations, _plain) = classify_return_items(&q.return_clause);
+    let expanded;
+    let return_clause = if matches!(q.return_clause.items.as_slice(), [ReturnItem { expr: Expr::Star, .. }]) {
+        expanded = ReturnClause { distinct: q.return_clause.distinct, items: star_items(q)? };
+        &expanded
+    } else {
+        &q.return_clause
+    };
+    let (has_agg, group_by, aggregations, _plain) = classify_return_items(return_clause);
 
     if has_agg {
         current = LogicalPlan::Aggregate {
@@ -122,7 +341,7 @@ fn plan_query(q: &Query) -> Result<LogicalPlan> {
             aggregations,
         };
     } else {
-        let items: Vec<(Expr, String)> = q.return_clause.items.iter().map(|item| {
+        let items: Vec<(Expr, String)> = return_clause.items.iter().map(|item| {
             let alias = item.alias.clone().unwrap_or_else(|| expr_default_alias(&item.expr));
             (item.expr.clone(), alias)
         }).collect();
@@ -143,7 +362,17 @@ fn plan_query(q: &Query) -> Result<LogicalPlan> {
     }
 
     if let Some(ref limit_expr) = q.limit {
-        if let Expr::Literal(Literal::Int(n)) = limit_expr {
+        if q.limit_percent {
+            let percent = match limit_expr {
+                Expr::Literal(Literal::Int(n)) => *n as f64,
+                Expr::Literal(Literal::Float(f)) => *f,
+                _ => return Err(crate::plan_err!("LIMIT ... PERCENT takes a numeric literal")),
+            };
+            if !(0.0..=100.0).contains(&percent) {
+                return Err(crate::plan_err!("LIMIT ... PERCENT must be between 0 and 100, got {percent}"));
+            }
+            current = LogicalPlan::LimitPercent { input: Box::new(current), fraction: percent / 100.0 };
+        } else if let Expr::Literal(Literal::Int(n)) = limit_expr {
             current = LogicalPlan::Limit { input: Box::new(current), count: *n as usize };
         }
     }
@@ -151,70 +380,276 @@ fn plan_query(q: &Query) -> Result<LogicalPlan> {
     Ok(current)
 }
 
-fn plan_matches(matches: &[MatchClause]) -> Result<LogicalPlan> {
-    let mut plans = Vec::new();
-    for m in matches {
-        for pattern in &m.patterns {
-            plans.push(plan_pattern(pattern)?);
-        }
+/// `current` filtered by the query's MATCH WHERE, with its index hints applied.
+fn plan_match_where(mut current: LogicalPlan, q: &Query) -> Result<LogicalPlan> {
+    if let Some(ref where_expr) = q.where_clause {
+        current = match current {
+            // Inline pattern properties filter the last element already:
+            // one filter over the scan keeps it visible to hints and range scans
+            LogicalPlan::Filter { input, predicate } => LogicalPlan::Filter {
+                input,
+                predicate: Expr::BinaryOp { left: Box::new(predicate), op: BinaryOp::And, right: Box::new(where_expr.clone()) },
+            },
+            other => LogicalPlan::Filter { input: Box::new(other), predicate: where_expr.clone() },
+        };
     }
 
-    if plans.is_empty() {
-        return Ok(LogicalPlan::Argument);
+    for hint in q.matches.iter().flat_map(|m| &m.hints) {
+        current = apply_index_hint(current, hint)?;
     }
+    Ok(current)
+}
 
-    let mut current = plans.remove(0);
-    for p in plans {
-        current = LogicalPlan::CartesianProduct {
-            left: Box::new(current),
-            right: Box::new(p),
+/// `RETURN *` as one item per variable in scope: pattern variables in the
+/// order the patterns name them (a path variable before its pattern), then
+/// UNWIND variables. A WITH keeps only what it passes on, each under its
+/// projected name, and MATCHes after it add to that.
+fn star_items(q: &Query) -> Result<Vec<ReturnItem>> {
+    let (mut scope, matches, unwinds) = with_scope(q, q.with_clauses.len());
+    bind_clauses(&mut scope, &q.matches[matches..], &q.unwinds[unwinds..]);
+    if scope.is_empty() {
+        return Err(crate::plan_err!("RETURN * is not allowed when there are no variables in scope"));
+    }
+    Ok(scope)
+}
+
+/// The variables passed on by the first `withs` WITH clauses of `q`, each
+/// aliasing the expression it stands for, with how many of the matches and
+/// unwinds those clauses cover. `WITH *` passes on everything in scope, in
+/// the order `RETURN *` would list it, followed by any items after the `*`.
+fn with_scope(q: &Query, withs: usize) -> (Vec<ReturnItem>, usize, usize) {
+    let mut scope = Vec::new();
+    let (mut matches, mut unwinds) = (0, 0);
+    for with in &q.with_clauses[..withs] {
+        bind_clauses(&mut scope, &q.matches[matches..with.preceding_matches], &q.unwinds[unwinds..with.preceding_unwinds]);
+        (matches, unwinds) = (with.preceding_matches, with.preceding_unwinds);
+        let (mut carried, items) = match with.items.split_first() {
+            Some((ReturnItem { expr: Expr::Star, .. }, rest)) => (scope.clone(), rest),
+            _ => (Vec::new(), with.items.as_slice()),
         };
+        for item in items {
+            let name = item.alias.clone().unwrap_or_else(|| expr_default_alias(&item.expr));
+            // A name from an earlier WITH stands for what it projected
+            bind(&mut carried, &name, inline_aliases(&item.expr, &scope));
+        }
+        scope = carried;
+    }
+    (scope, matches, unwinds)
+}
+
+fn bind(scope: &mut Vec<ReturnItem>, name: &String, expr: Expr) {
+    if !scope.iter().any(|item| item.alias.as_ref() == Some(name)) {
+        scope.push(ReturnItem { expr, alias: Some(name.clone()) });
+    }
+}
+
+fn bind_clauses(scope: &mut Vec<ReturnItem>, matches: &[MatchClause], unwinds: &[(Expr, String)]) {
+    for pattern in matches.iter().flat_map(|m| &m.patterns) {
+        let elements = pattern.elements.iter().filter_map(|el| match el {
+            PatternElement::Node(np) => np.alias.as_ref(),
+            PatternElement::Relationship(rp) => rp.alias.as_ref(),
+        });
+        for name in pattern.path.iter().chain(elements) {
+            bind(scope, name, Expr::Variable { name: name.clone(), span: None });
+        }
+    }
+    for (_, name) in unwinds {
+        bind(scope, name, Expr::Variable { name: name.clone(), span: None });
+    }
+}
+
+/// `expr` with each variable that `scope` aliases replaced by the
+/// expression it stands for. Subquery bodies are left alone.
+fn inline_aliases(expr: &Expr, scope: &[ReturnItem]) -> Expr {
+    let inline = |e: &Expr| inline_aliases(e, scope);
+    let boxed = |e: &Expr| Box::new(inline_aliases(e, scope));
+    match expr {
+        Expr::Variable { name, .. } => scope
+            .iter()
+            .find(|i| i.alias.as_ref() == Some(name))
+            .map_or_else(|| expr.clone(), |i| i.expr.clone()),
+        Expr::Property { expr, key } => Expr::Property { expr: boxed(expr), key: key.clone() },
+        Expr::FunctionCall { name, args, distinct, span } => {
+            Expr::FunctionCall { name: name.clone(), args: args.iter().map(inline).collect(), distinct: *distinct, span: *span }
+        }
+        Expr::BinaryOp { left, op, right } => Expr::BinaryOp { left: boxed(left), op: *op, right: boxed(right) },
+        Expr::UnaryOp { op, expr } => Expr::UnaryOp { op: *op, expr: boxed(expr) },
+        Expr::List(items) => Expr::List(items.iter().map(inline).collect()),
+        Expr::MapLiteral(entries) => Expr::MapLiteral(entries.iter().map(|(k, v)| (k.clone(), inline(v))).collect()),
+        Expr::Case { operand, whens, else_expr } => Expr::Case {
+            operand: operand.as_deref().map(boxed),
+            whens: whens.iter().map(|(w, t)| (inline(w), inline(t))).collect(),
+            else_expr: else_expr.as_deref().map(boxed),
+        },
+        Expr::In { expr, list } => Expr::In { expr: boxed(expr), list: boxed(list) },
+        Expr::IsNull { expr, negated } => Expr::IsNull { expr: boxed(expr), negated: *negated },
+        Expr::HasLabel { expr, label } => Expr::HasLabel { expr: boxed(expr), label: label.clone() },
+        Expr::StringOp { left, op, right } => Expr::StringOp { left: boxed(left), op: *op, right: boxed(right) },
+        Expr::Literal(_) | Expr::Parameter { .. } | Expr::Exists { .. } | Expr::CountSubquery { .. } | Expr::Star => expr.clone(),
+    }
+}
+
+fn plan_matches(matches: &[MatchClause], names: &mut AnonNames) -> Result<LogicalPlan> {
+    continue_matches(LogicalPlan::Argument, matches, names)
+}
+
+/// Plan `matches` on top of `input`, the rows of the clauses before them.
+/// A pattern starting at a node `input` binds expands from it.
+fn continue_matches(input: LogicalPlan, matches: &[MatchClause], names: &mut AnonNames) -> Result<LogicalPlan> {
+    let mut current = match input {
+        LogicalPlan::Argument => None,
+        plan => Some(plan),
+    };
+    for pattern in matches.iter().flat_map(|m| &m.patterns) {
+        let bound = current.as_ref().and_then(bound_aliases).unwrap_or_default();
+        let starts_bound = matches!(
+            pattern.elements.first(),
+            Some(PatternElement::Node(NodePattern { alias: Some(a), .. })) if bound.contains(a)
+        );
+        current = Some(match current.take() {
+            // Continue from the bound node rather than rescanning it
+            Some(plan) if starts_bound => plan_pattern(pattern, Some(plan), names)?,
+            Some(plan) => LogicalPlan::CartesianProduct {
+                left: Box::new(plan),
+                right: Box::new(plan_pattern(pattern, None, names)?),
+            },
+            None => plan_pattern(pattern, None, names)?,
+        });
+    }
+    Ok(current.unwrap_or(LogicalPlan::Argument))
+}
+
+/// Plan the body of an `EXISTS` subquery, run once per outer row. Only
+/// whether it yields a row matters, so it stops at the first.
+pub(crate) fn plan_exists(body: &MatchClause, where_clause: Option<&Expr>, bound: &[String]) -> Result<LogicalPlan> {
+    let plan = plan_subquery(body, where_clause, bound)?;
+    Ok(LogicalPlan::Limit { input: Box::new(plan), count: 1 })
+}
+
+/// Plan the body of an `EXISTS` or `COUNT` subquery. The plan starts from
+/// `Argument`, which the executor seeds with the outer row, so variables in
+/// `bound` are imported rather than rescanned.
+pub(crate) fn plan_subquery(body: &MatchClause, where_clause: Option<&Expr>, bound: &[String]) -> Result<LogicalPlan> {
+    // The outer row may hold anonymous variables of its own
+    let mut names = AnonNames { taken: bound.to_vec(), ..AnonNames::default() };
+    let mut bound = bound.to_vec();
+    let mut current = LogicalPlan::Argument;
+    for pattern in &body.patterns {
+        for part in split_at_bound(pattern, &bound)? {
+            let starts_bound = matches!(
+                part.elements.first(),
+                Some(PatternElement::Node(NodePattern { alias: Some(a), .. })) if bound.contains(a)
+            );
+            current = if starts_bound {
+                plan_pattern(&part, Some(current), &mut names)?
+            } else {
+                LogicalPlan::CartesianProduct { left: Box::new(current), right: Box::new(plan_pattern(&part, None, &mut names)?) }
+            };
+        }
+        bound.extend(pattern.elements.iter().filter_map(|el| match el {
+            PatternElement::Node(np) => np.alias.clone(),
+            PatternElement::Relationship(rp) => rp.alias.clone(),
+        }));
+    }
+    if let Some(predicate) = where_clause {
+        current = LogicalPlan::Filter { input: Box::new(current), predicate: predicate.clone() };
     }
     Ok(current)
 }
 
-fn plan_pattern(pattern: &Pattern) -> Result<LogicalPlan> {
+/// Split `pattern` at its first node bound in `bound` into the parts on
+/// either side, each starting from that node (the left part reversed), so
+/// both can expand from it. A pattern with no bound node is returned whole,
+/// as is one whose first node is bound. A path variable can't follow a
+/// pattern split in two, so splitting one is an error.
+fn split_at_bound(pattern: &Pattern, bound: &[String]) -> Result<Vec<Pattern>> {
+    let split = pattern.elements.iter().position(|el| {
+        matches!(el, PatternElement::Node(NodePattern { alias: Some(a), .. }) if bound.contains(a))
+    });
+    let Some(i) = split.filter(|&i| i > 0) else {
+        return Ok(vec![pattern.clone()]);
+    };
+    if let Some(path) = &pattern.path {
+        return Err(crate::plan_err!("Path variable {path} can't bind a pattern that starts before its bound node")
+            .or_span(pattern.span));
+    }
+    let elements = pattern.elements[..=i]
+        .iter()
+        .rev()
+        .map(|el| match el {
+            PatternElement::Relationship(rp) => {
+                let direction = match rp.direction {
+                    PatternDirection::Right => PatternDirection::Left,
+                    PatternDirection::Left => PatternDirection::Right,
+                    PatternDirection::Both => PatternDirection::Both,
+                };
+                PatternElement::Relationship(RelPattern { direction, ..rp.clone() })
+            }
+            node => node.clone(),
+        })
+        .collect();
+    let mut parts = vec![Pattern { elements, path: None, span: pattern.span }];
+    if i + 1 < pattern.elements.len() {
+        parts.push(Pattern { elements: pattern.elements[i..].to_vec(), path: None, span: pattern.span });
+    }
+    Ok(parts)
+}
+
+/// Plan `pattern` as scans and expands. With `input`, the pattern's first
+/// node is already bound by it and is only label-checked.
+fn plan_pattern(pattern: &Pattern, mut input: Option<LogicalPlan>, names: &mut AnonNames) -> Result<LogicalPlan> {
     if pattern.elements.is_empty() {
-        return Ok(LogicalPlan::Argument);
+        return Ok(input.unwrap_or(LogicalPlan::Argument));
+    }
+    if let Some(path) = &pattern.path
+        && pattern.elements.len() < 3
+    {
+        return Err(crate::plan_err!("Path variable {path} needs a pattern with at least one relationship")
+            .or_span(pattern.span));
     }
 
     let mut plan: Option<LogicalPlan> = None;
     let mut last_alias: Option<String> = None;
     let mut i = 0;
+    // With more than one hop, every relationship is bound so later hops
+    // can skip it
+    let hops = pattern.elements.iter().filter(|el| matches!(el, PatternElement::Relationship(_))).count();
+    let mut walked: Vec<String> = Vec::new();
 
     while i < pattern.elements.len() {
         match &pattern.elements[i] {
             PatternElement::Node(np) => {
-                let alias = np.alias.clone().unwrap_or_else(|| format!("_anon_{}", next_id()));
-                if plan.is_none() {
-                    plan = Some(if np.labels.is_empty() {
-                        LogicalPlan::AllNodesScan { alias: alias.clone() }
-                    } else {
-                        LogicalPlan::NodeScan {
-                            label: np.labels[0].clone(),
-                            alias: alias.clone(),
-                        }
-                    });
+                let alias = np.alias.clone().unwrap_or_else(|| names.next());
+                if let Some(input) = input.take() {
+                    let predicates = pattern_predicates(&alias, &np.labels, &np.properties, np.predicate.as_ref());
+                    plan = Some(with_pattern_filter(input, predicates));
+                } else if plan.is_none() {
+                    let scan = match np.labels.as_slice() {
+                        [] => LogicalPlan::AllNodesScan { alias: alias.clone() },
+                        [label] => LogicalPlan::NodeScan { label: label.clone(), alias: alias.clone() },
+                        labels => LogicalPlan::MultiLabelScan { labels: labels.to_vec(), alias: alias.clone() },
+                    };
+                    plan = Some(with_pattern_filter(scan, pattern_predicates(&alias, &[], &np.properties, np.predicate.as_ref())));
                 }
                 last_alias = Some(alias);
                 i += 1;
             }
             PatternElement::Relationship(rp) => {
                 let from = last_alias.clone().ok_or_else(|| {
-                    crate::plan_err!("Relationship pattern without preceding node")
+                    crate::plan_err!("Relationship pattern without preceding node").or_span(pattern.span)
                 })?;
 
                 i += 1;
-                let to_alias = if i < pattern.elements.len() {
+                let (to_alias, to_np) = if i < pattern.elements.len() {
                     if let PatternElement::Node(to_np) = &pattern.elements[i] {
-                        let a = to_np.alias.clone().unwrap_or_else(|| format!("_anon_{}", next_id()));
+                        let a = to_np.alias.clone().unwrap_or_else(|| names.next());
                         i += 1;
-                        a
+                        (a, to_np)
                     } else {
-                        return Err(crate::plan_err!("Expected node after relationship"));
+                        return Err(crate::plan_err!("Expected node after relationship").or_span(pattern.span));
                     }
                 } else {
-                    return Err(crate::plan_err!("Relationship pattern must end with node"));
+                    return Err(crate::plan_err!("Relationship pattern must end with node").or_span(pattern.span));
                 };
 
                 let dir = match rp.direction {
@@ -223,29 +658,90 @@ fn plan_pattern(pattern: &Pattern) -> Result<LogicalPlan> {
                     PatternDirection::Both => crate::model::Direction::Both,
                 };
 
+                let rel_alias = rp.alias.clone()
+                    .or_else(|| (hops > 1 || !rp.properties.is_empty()).then(|| names.next()));
                 let input = plan.take().unwrap_or(LogicalPlan::Argument);
-                plan = Some(LogicalPlan::Expand {
+                let expand = LogicalPlan::Expand {
                     input: Box::new(input),
                     from,
                     dir,
                     rel_types: rp.rel_types.clone(),
                     to: to_alias.clone(),
-                    rel_alias: rp.alias.clone(),
-                });
+                    rel_alias: rel_alias.clone(),
+                    path: pattern.path.clone(),
+                    unique_from: walked.clone(),
+                };
+                let mut predicates = match &rel_alias {
+                    Some(rel_alias) => pattern_predicates(rel_alias, &[], &rp.properties, rp.predicate.as_ref()),
+                    None => rp.predicate.iter().cloned().collect(),
+                };
+                predicates.extend(pattern_predicates(&to_alias, &to_np.labels, &to_np.properties, to_np.predicate.as_ref()));
+                walked.extend(rel_alias);
+                plan = Some(with_pattern_filter(expand, predicates));
                 last_alias = Some(to_alias);
             }
         }
     }
 
-    plan.ok_or_else(|| crate::plan_err!("Empty pattern"))
+    plan.ok_or_else(|| crate::plan_err!("Empty pattern").or_span(pattern.span))
 }
 
-fn plan_create(c: &CreateClause) -> Result<LogicalPlan> {
+/// The checks a pattern element makes on `alias`: a `HasLabel` per label,
+/// then `alias.key = value` per inline property, in key order, then its
+/// inline `WHERE`.
+fn pattern_predicates(
+    alias: &str,
+    labels: &[String],
+    properties: &HashMap<String, Expr>,
+    predicate: Option<&Expr>,
+) -> Vec<Expr> {
+    let variable = || Box::new(Expr::Variable { name: alias.to_string(), span: None });
+    let mut keys: Vec<&String> = properties.keys().collect();
+    keys.sort();
+    labels
+        .iter()
+        .map(|label| Expr::HasLabel { expr: variable(), label: label.clone() })
+        .chain(keys.into_iter().map(|key| Expr::BinaryOp {
+            left: Box::new(Expr::Property { expr: variable(), key: key.clone() }),
+            op: BinaryOp::Eq,
+            right: Box::new(properties[key].clone()),
+        }))
+        .chain(predicate.cloned())
+        .collect()
+}
+
+/// Wrap `input` in a `Filter` on the conjunction of `predicates`.
+/// Returns `input` unchanged when there is nothing to check.
+fn with_pattern_filter(input: LogicalPlan, predicates: Vec<Expr>) -> LogicalPlan {
+    match join_conjuncts(predicates) {
+        Some(predicate) => LogicalPlan::Filter { input: Box::new(input), predicate },
+        None => input,
+    }
+}
+
+/// Inline `WHERE` filters what a pattern matches, so creating clauses
+/// reject it rather than ignore it.
+fn reject_pattern_predicates(patterns: &[Pattern], clause: &str) -> Result<()> {
+    let with_predicate = patterns.iter().find(|p| {
+        p.elements.iter().any(|element| match element {
+            PatternElement::Node(np) => np.predicate.is_some(),
+            PatternElement::Relationship(rp) => rp.predicate.is_some(),
+        })
+    });
+    if let Some(pattern) = with_predicate {
+        return Err(crate::plan_err!("Pattern predicates (WHERE inside a pattern) are not allowed in {clause}")
+            .or_span(pattern.span));
+    }
+    Ok(())
+}
+
+fn plan_create(c: &CreateClause, names: &mut AnonNames) -> Result<LogicalPlan> {
+    reject_pattern_predicates(&c.patterns, "CREATE")?;
     // Start from MATCH clauses if present (compound MATCH...CREATE)
     let mut current: Option<LogicalPlan> = if c.matches.is_empty() {
         None
     } else {
-        Some(plan_matches(&c.matches)?)
+        Some(plan_matches(&c.matches, names)?)
     };
 
     if let Some(ref where_expr) = c.where_clause {
@@ -256,6 +752,8 @@ fn plan_create(c: &CreateClause) -> Result<LogicalPlan> {
         });
     }
 
+    // Every create is piped from the one before, not joined as a product,
+    // so each input row gains all created variables in a single row.
     for pattern in &c.patterns {
         let mut i = 0;
         let mut last_alias: Option<String> = None;
@@ -263,7 +761,7 @@ fn plan_create(c: &CreateClause) -> Result<LogicalPlan> {
         while i < pattern.elements.len() {
             match &pattern.elements[i] {
                 PatternElement::Node(np) => {
-                    let alias = np.alias.clone().unwrap_or_else(|| format!("_anon_{}", next_id()));
+                    let alias = np.alias.clone().unwrap_or_else(|| names.next());
 
                     // Only emit CreateNode if the node has labels or properties.
                     // A bare (alias) is a reference to a MATCH binding.
@@ -271,17 +769,11 @@ fn plan_create(c: &CreateClause) -> Result<LogicalPlan> {
                         let properties: Vec<(String, Expr)> = np.properties.iter()
                             .map(|(k, v)| (k.clone(), v.clone()))
                             .collect();
-                        let create = LogicalPlan::CreateNode {
+                        current = Some(LogicalPlan::CreateNode {
+                            input: current.take().map(Box::new),
                             labels: np.labels.clone(),
                             properties,
                             alias: alias.clone(),
-                        };
-                        current = Some(match current.take() {
-                            Some(prev) => LogicalPlan::CartesianProduct {
-                                left: Box::new(prev),
-                                right: Box::new(create),
-                            },
-                            None => create,
                         });
                     }
 
@@ -290,37 +782,31 @@ fn plan_create(c: &CreateClause) -> Result<LogicalPlan> {
                 }
                 PatternElement::Relationship(rp) => {
                     let src_alias = last_alias.clone().ok_or_else(|| {
-                        crate::plan_err!("Relationship pattern without preceding node")
+                        crate::plan_err!("Relationship pattern without preceding node").or_span(pattern.span)
                     })?;
 
                     i += 1;
                     let dst_alias = if i < pattern.elements.len() {
                         if let PatternElement::Node(to_np) = &pattern.elements[i] {
-                            let a = to_np.alias.clone().unwrap_or_else(|| format!("_anon_{}", next_id()));
+                            let a = to_np.alias.clone().unwrap_or_else(|| names.next());
                             if !to_np.labels.is_empty() || !to_np.properties.is_empty() {
                                 let properties: Vec<(String, Expr)> = to_np.properties.iter()
                                     .map(|(k, v)| (k.clone(), v.clone()))
                                     .collect();
-                                let create = LogicalPlan::CreateNode {
+                                current = Some(LogicalPlan::CreateNode {
+                                    input: current.take().map(Box::new),
                                     labels: to_np.labels.clone(),
                                     properties,
                                     alias: a.clone(),
-                                };
-                                current = Some(match current.take() {
-                                    Some(prev) => LogicalPlan::CartesianProduct {
-                                        left: Box::new(prev),
-                                        right: Box::new(create),
-                                    },
-                                    None => create,
                                 });
                             }
                             i += 1;
                             a
                         } else {
-                            return Err(crate::plan_err!("Expected node after relationship"));
+                            return Err(crate::plan_err!("Expected node after relationship").or_span(pattern.span));
                         }
                     } else {
-                        return Err(crate::plan_err!("Relationship pattern must end with node"));
+                        return Err(crate::plan_err!("Relationship pattern must end with node").or_span(pattern.span));
                     };
 
                     let rel_type = rp.rel_types.first().cloned().unwrap_or_else(|| "RELATED_TO".into());
@@ -339,6 +825,7 @@ fn plan_create(c: &CreateClause) -> Result<LogicalPlan> {
                         dst: actual_dst,
                         rel_type,
                         properties,
+                        alias: rp.alias.clone(),
                     };
                     current = Some(create_rel);
 
@@ -364,11 +851,11 @@ fn plan_create(c: &CreateClause) -> Result<LogicalPlan> {
     Ok(result)
 }
 
-fn plan_delete(d: &DeleteClause) -> Result<LogicalPlan> {
+fn plan_delete(d: &DeleteClause, names: &mut AnonNames) -> Result<LogicalPlan> {
     let mut current = if d.matches.is_empty() {
         LogicalPlan::Argument
     } else {
-        plan_matches(&d.matches)?
+        plan_matches(&d.matches, names)?
     };
 
     if let Some(ref where_expr) = d.where_clause {
@@ -378,22 +865,41 @@ fn plan_delete(d: &DeleteClause) -> Result<LogicalPlan> {
         };
     }
 
+    // Variables bound by a relationship pattern delete the relationship.
+    let rel_aliases: Vec<&String> = d
+        .matches
+        .iter()
+        .flat_map(|m| &m.patterns)
+        .flat_map(|p| &p.elements)
+        .filter_map(|el| match el {
+            PatternElement::Relationship(rp) => rp.alias.as_ref(),
+            PatternElement::Node(_) => None,
+        })
+        .collect();
+
     for var in &d.variables {
-        current = LogicalPlan::DeleteNode {
-            input: Box::new(current),
-            variable: var.clone(),
-            detach: d.detach,
+        current = if rel_aliases.contains(&var) {
+            LogicalPlan::DeleteRel {
+                input: Box::new(current),
+                variable: var.clone(),
+            }
+        } else {
+            LogicalPlan::DeleteNode {
+                input: Box::new(current),
+                variable: var.clone(),
+                detach: d.detach,
+            }
         };
     }
 
     Ok(current)
 }
 
-fn plan_remove(r: &RemoveClause) -> Result<LogicalPlan> {
+fn plan_remove(r: &RemoveClause, names: &mut AnonNames) -> Result<LogicalPlan> {
     let mut current = if r.matches.is_empty() {
         LogicalPlan::Argument
     } else {
-        plan_matches(&r.matches)?
+        plan_matches(&r.matches, names)?
     };
 
     if let Some(ref where_expr) = r.where_clause {
@@ -436,11 +942,11 @@ fn plan_remove(r: &RemoveClause) -> Result<LogicalPlan> {
     Ok(current)
 }
 
-fn plan_set(s: &SetClause) -> Result<LogicalPlan> {
+fn plan_set(s: &SetClause, names: &mut AnonNames) -> Result<LogicalPlan> {
     let mut current = if s.matches.is_empty() {
         LogicalPlan::Argument
     } else {
-        plan_matches(&s.matches)?
+        plan_matches(&s.matches, names)?
     };
 
     if let Some(ref where_expr) = s.where_clause {
@@ -460,7 +966,15 @@ fn plan_set(s: &SetClause) -> Result<LogicalPlan> {
                     value: value.clone(),
                 };
             }
-            _ => return Err(crate::plan_err!("Only SET n.prop = expr is currently supported")),
+            SetItem::AllProperties { variable, value } | SetItem::MergeProperties { variable, value } => {
+                current = LogicalPlan::SetProperties {
+                    input: Box::new(current),
+                    variable: variable.clone(),
+                    value: value.clone(),
+                    replace: matches!(item, SetItem::AllProperties { .. }),
+                };
+            }
+            _ => return Err(crate::plan_err!("Only SET n.prop = expr, SET n = map and SET n += map are currently supported")),
         }
     }
 
@@ -478,12 +992,13 @@ fn plan_set(s: &SetClause) -> Result<LogicalPlan> {
     Ok(current)
 }
 
-fn plan_merge(m: &MergeClause) -> Result<LogicalPlan> {
+fn plan_merge(m: &MergeClause, names: &mut AnonNames) -> Result<LogicalPlan> {
+    reject_pattern_predicates(std::slice::from_ref(&m.pattern), "MERGE")?;
     // Start from MATCH clauses if present (compound MATCH...MERGE)
     let mut input: Option<LogicalPlan> = if m.matches.is_empty() {
         None
     } else {
-        Some(plan_matches(&m.matches)?)
+        Some(plan_matches(&m.matches, names)?)
     };
 
     if let Some(ref where_expr) = m.where_clause {
@@ -497,9 +1012,9 @@ fn plan_merge(m: &MergeClause) -> Result<LogicalPlan> {
     // Extract the node from the MERGE pattern
     let node_pattern = m.pattern.elements.iter().find_map(|e| {
         if let PatternElement::Node(np) = e { Some(np) } else { None }
-    }).ok_or_else(|| crate::plan_err!("MERGE requires at least one node pattern"))?;
+    }).ok_or_else(|| crate::plan_err!("MERGE requires at least one node pattern").or_span(m.pattern.span))?;
 
-    let alias = node_pattern.alias.clone().unwrap_or_else(|| format!("_anon_{}", next_id()));
+    let alias = node_pattern.alias.clone().unwrap_or_else(|| names.next());
     let properties: Vec<(String, Expr)> = node_pattern.properties.iter()
         .map(|(k, v): (&String, &Expr)| (k.clone(), v.clone()))
         .collect();
@@ -567,18 +1082,98 @@ fn classify_return_items(ret: &ReturnClause) -> (bool, Vec<(Expr, String)>, Vec<
     (has_agg, group_by, aggregations, plain)
 }
 
-fn is_aggregate_expr(expr: &Expr) -> bool {
+/// Names of the aggregate functions computed by the `Aggregate` operator.
+pub(crate) fn is_aggregate_function(name: &str) -> bool {
+    matches!(
+        name.to_uppercase().as_str(),
+        "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "COLLECT" | "APOC.AGG.MEDIAN" | "APOC.AGG.STATISTICS"
+    )
+}
+
+/// True if an aggregate appears anywhere in the expression tree,
+/// e.g. `sum(n.a) + sum(n.b)` or `toString(count(n))`.
+pub(crate) fn is_aggregate_expr(expr: &Expr) -> bool {
     match expr {
-        Expr::FunctionCall { name, .. } => {
-            matches!(name.to_uppercase().as_str(), "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "COLLECT")
+        Expr::FunctionCall { name, args, .. } => {
+            is_aggregate_function(name) || args.iter().any(is_aggregate_expr)
         }
-        _ => false,
+        Expr::Property { expr, .. } => is_aggregate_expr(expr),
+        Expr::BinaryOp { left, right, .. } => is_aggregate_expr(left) || is_aggregate_expr(right),
+        Expr::UnaryOp { expr, .. } => is_aggregate_expr(expr),
+        Expr::List(items) => items.iter().any(is_aggregate_expr),
+        Expr::MapLiteral(entries) => entries.values().any(is_aggregate_expr),
+        Expr::Case { operand, whens, else_expr } => {
+            operand.as_deref().is_some_and(is_aggregate_expr)
+                || whens.iter().any(|(w, t)| is_aggregate_expr(w) || is_aggregate_expr(t))
+                || else_expr.as_deref().is_some_and(is_aggregate_expr)
+        }
+        Expr::In { expr, list } => is_aggregate_expr(expr) || is_aggregate_expr(list),
+        Expr::IsNull { expr, .. } => is_aggregate_expr(expr),
+        Expr::HasLabel { expr, .. } => is_aggregate_expr(expr),
+        Expr::StringOp { left, right, .. } => is_aggregate_expr(left) || is_aggregate_expr(right),
+        Expr::Literal(_) | Expr::Variable { .. } | Expr::Parameter { .. } | Expr::Exists { .. } | Expr::CountSubquery { .. } | Expr::Star => false,
+    }
+}
+
+/// Reject `DISTINCT` on a function that isn't an aggregate, such as
+/// `toUpper(DISTINCT x)`, as Neo4j does.
+fn reject_misplaced_distinct(plan: &LogicalPlan) -> Result<()> {
+    let exprs: Vec<&Expr> = match plan {
+        LogicalPlan::IndexLookup { value, .. } => vec![value],
+        LogicalPlan::IndexRangeScan { lower, upper, .. } => lower.iter().chain(upper).map(|(e, _)| e).collect(),
+        LogicalPlan::Filter { predicate, .. } => vec![predicate],
+        LogicalPlan::Project { items, .. } => items.iter().map(|(e, _)| e).collect(),
+        LogicalPlan::Aggregate { group_by, aggregations, .. } => group_by.iter().chain(aggregations).map(|(e, _)| e).collect(),
+        LogicalPlan::Sort { keys, .. } => keys.iter().map(|(e, _)| e).collect(),
+        LogicalPlan::HashJoin { left_key, right_key, .. } => vec![left_key, right_key],
+        LogicalPlan::CallProcedure { args, .. } => args.iter().collect(),
+        LogicalPlan::CreateNode { properties, .. }
+        | LogicalPlan::CreateRel { properties, .. } => properties.iter().map(|(_, e)| e).collect(),
+        LogicalPlan::MergeNode { properties, on_create, on_match, .. } => properties
+            .iter()
+            .map(|(_, e)| e)
+            .chain(on_create.iter().chain(on_match).map(|(_, _, e)| e))
+            .collect(),
+        LogicalPlan::SetProperty { value, .. } | LogicalPlan::SetProperties { value, .. } => vec![value],
+        LogicalPlan::Unwind { expr, .. } => vec![expr],
+        LogicalPlan::LoadCsv { url, .. } => vec![url],
+        _ => Vec::new(),
+    };
+    if let Some(Expr::FunctionCall { name, span, .. }) = exprs.into_iter().find_map(misplaced_distinct) {
+        return Err(Error::SemanticError {
+            message: format!("Invalid use of DISTINCT with function '{name}'"),
+            span: *span,
+        });
+    }
+    plan_inputs(plan).into_iter().try_for_each(reject_misplaced_distinct)
+}
+
+/// The first call in `expr` that takes `DISTINCT` without being an aggregate.
+fn misplaced_distinct(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::FunctionCall { name, distinct: true, .. } if !is_aggregate_function(name) => Some(expr),
+        Expr::FunctionCall { args, .. } | Expr::List(args) => args.iter().find_map(misplaced_distinct),
+        Expr::Property { expr, .. }
+        | Expr::UnaryOp { expr, .. }
+        | Expr::IsNull { expr, .. }
+        | Expr::HasLabel { expr, .. } => misplaced_distinct(expr),
+        Expr::BinaryOp { left, right, .. }
+        | Expr::StringOp { left, right, .. }
+        | Expr::In { expr: left, list: right } => misplaced_distinct(left).or_else(|| misplaced_distinct(right)),
+        Expr::MapLiteral(entries) => entries.values().find_map(misplaced_distinct),
+        Expr::Case { operand, whens, else_expr } => operand
+            .iter()
+            .chain(else_expr)
+            .map(|b| b.as_ref())
+            .chain(whens.iter().flat_map(|(w, t)| [w, t]))
+            .find_map(misplaced_distinct),
+        Expr::Literal(_) | Expr::Variable { .. } | Expr::Parameter { .. } | Expr::Exists { .. } | Expr::CountSubquery { .. } | Expr::Star => None,
     }
 }
 
 fn expr_default_alias(expr: &Expr) -> String {
     match expr {
-        Expr::Variable(name) => name.clone(),
+        Expr::Variable { name, .. } => name.clone(),
         Expr::Property { expr, key } => format!("{}.{}", expr_default_alias(expr), key),
         Expr::FunctionCall { name, .. } => name.clone(),
         Expr::Star => "*".to_string(),
@@ -586,15 +1181,576 @@ fn expr_default_alias(expr: &Expr) -> String {
     }
 }
 
-fn next_id() -> u32 {
-    use std::sync::atomic::{AtomicU32, Ordering};
-    static COUNTER: AtomicU32 = AtomicU32::new(0);
-    COUNTER.fetch_add(1, Ordering::Relaxed)
+/// Prefix of anonymous variable names. Like Neo4j's, it starts with spaces,
+/// which no Cypher identifier can, so it never collides with a user variable.
+pub(crate) const ANON_PREFIX: &str = "  UNNAMED";
+
+/// Names for anonymous pattern elements, numbered from zero per query so
+/// the same query always gets the same plan.
+#[derive(Default)]
+struct AnonNames {
+    next: u32,
+    /// Names already in use, e.g. by the outer row of an `EXISTS` subquery
+    taken: Vec<String>,
+}
+
+impl AnonNames {
+    fn next(&mut self) -> String {
+        loop {
+            let name = format!("{ANON_PREFIX}{}", self.next);
+            self.next += 1;
+            if !self.taken.contains(&name) {
+                return name;
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Optimizer
+// ============================================================================
+
+/// Backend statistics used for cardinality estimates.
+///
+/// Missing entries mean "unknown"; unknown inputs are never reordered.
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    pub node_count: Option<u64>,
+    pub label_counts: HashMap<String, u64>,
+}
+
+impl Statistics {
+    /// Estimated row count of a cartesian-product input, driven by its scan.
+    fn cardinality(&self, plan: &LogicalPlan) -> Option<u64> {
+        match plan {
+            LogicalPlan::NodeScan { label, .. } | LogicalPlan::IndexRangeScan { label, .. } => {
+                self.label_counts.get(label).copied()
+            }
+            // Bounded by the rarest label
+            LogicalPlan::MultiLabelScan { labels, .. } => {
+                labels.iter().map(|l| self.label_counts.get(l).copied()).min().flatten()
+            }
+            LogicalPlan::AllNodesScan { .. } => self.node_count,
+            LogicalPlan::Filter { input, .. } | LogicalPlan::Expand { input, .. } => self.cardinality(input),
+            _ => None,
+        }
+    }
+}
+
+/// Labels whose counts `optimize_with_stats` would use for `plan`.
+///
+/// Empty when the plan has no cartesian product, so callers can skip
+/// gathering statistics entirely.
+pub fn join_labels(plan: &LogicalPlan) -> Vec<String> {
+    fn driving_label(plan: &LogicalPlan, out: &mut Vec<String>) {
+        match plan {
+            LogicalPlan::NodeScan { label, .. } | LogicalPlan::IndexRangeScan { label, .. }
+                if !out.contains(label) =>
+            {
+                out.push(label.clone())
+            }
+            LogicalPlan::MultiLabelScan { labels, .. } => {
+                for label in labels {
+                    if !out.contains(label) {
+                        out.push(label.clone());
+                    }
+                }
+            }
+            LogicalPlan::Filter { input, .. } | LogicalPlan::Expand { input, .. } => driving_label(input, out),
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut stack = vec![plan];
+    while let Some(p) = stack.pop() {
+        match p {
+            LogicalPlan::CartesianProduct { .. } => {
+                for leaf in product_leaves(p) {
+                    driving_label(leaf, &mut out);
+                }
+            }
+            other => stack.extend(plan_inputs(other)),
+        }
+    }
+    out
 }
 
 /// Optimize a logical plan.
 pub fn optimize(plan: LogicalPlan) -> Result<LogicalPlan> {
-    // TODO: Cost-based optimizer
-    // Rules: predicate pushdown, index selection, join ordering
-    Ok(plan)
+    optimize_with_stats(plan, &Statistics::default())
+}
+
+/// Optimize a logical plan using backend statistics.
+///
+/// Rules: range scans for label scans filtered on `n.prop <cmp> constant`,
+/// join ordering (cheapest independent input drives each cartesian
+/// product), hash joins for products filtered on an equality, then count
+/// store lookups for `count(r)` over a bare relationship type.
+// TODO: predicate pushdown
+pub fn optimize_with_stats(plan: LogicalPlan, stats: &Statistics) -> Result<LogicalPlan> {
+    Ok(count_store(hash_joins(order_joins(range_scans(plan), stats))))
+}
+
+/// Rewrite `Filter(NodeScan(n:L), .. AND n.p > x AND ..)`, where `x` is a
+/// literal or parameter, into `IndexRangeScan(n:L, p)` with the remaining
+/// conjuncts as a filter on top. A lower and an upper bound on the same
+/// property (`n.p >= a AND n.p < b`) merge into one scan.
+fn range_scans(plan: LogicalPlan) -> LogicalPlan {
+    let plan = map_inputs(plan, range_scans);
+    let LogicalPlan::Filter { input, predicate } = plan else {
+        return plan;
+    };
+    let LogicalPlan::NodeScan { label, alias } = *input else {
+        return LogicalPlan::Filter { input, predicate };
+    };
+
+    let mut conjuncts = Vec::new();
+    split_conjuncts(predicate, &mut conjuncts);
+    let mut property: Option<String> = None;
+    let mut lower = None;
+    let mut upper = None;
+    let mut rest = Vec::new();
+    for c in conjuncts {
+        let bound = range_bound(&c, &alias)
+            .filter(|(key, ..)| property.as_ref().is_none_or(|p| p == key))
+            .filter(|(_, is_lower, ..)| if *is_lower { lower.is_none() } else { upper.is_none() });
+        match bound {
+            Some((key, is_lower, value, inclusive)) => {
+                property = Some(key);
+                if is_lower { lower = Some((value, inclusive)) } else { upper = Some((value, inclusive)) }
+            }
+            None => rest.push(c),
+        }
+    }
+
+    let scan = match property {
+        Some(property) => LogicalPlan::IndexRangeScan { label, property, lower, upper, alias },
+        None => LogicalPlan::NodeScan { label, alias },
+    };
+    match join_conjuncts(rest) {
+        Some(predicate) => LogicalPlan::Filter { input: Box::new(scan), predicate },
+        None => scan,
+    }
+}
+
+/// `(property, is_lower, bound, inclusive)` when `expr` compares
+/// `alias.property` with a literal or parameter, on either side.
+fn range_bound(expr: &Expr, alias: &str) -> Option<(String, bool, Expr, bool)> {
+    let Expr::BinaryOp { left, op, right } = expr else { return None };
+    let (is_lower, inclusive) = match op {
+        BinaryOp::Gt => (true, false),
+        BinaryOp::Gte => (true, true),
+        BinaryOp::Lt => (false, false),
+        BinaryOp::Lte => (false, true),
+        _ => return None,
+    };
+    let property_of = |e: &Expr| match e {
+        Expr::Property { expr, key } if matches!(expr.as_ref(), Expr::Variable { name, .. } if name == alias) => {
+            Some(key.clone())
+        }
+        _ => None,
+    };
+    let is_constant = |e: &Expr| matches!(e, Expr::Literal(_) | Expr::Parameter { .. });
+    if let Some(key) = property_of(left) && is_constant(right) {
+        Some((key, is_lower, (**right).clone(), inclusive))
+    } else if let Some(key) = property_of(right) && is_constant(left) {
+        // `x < n.p` bounds `n.p` from below
+        Some((key, !is_lower, (**left).clone(), inclusive))
+    } else {
+        None
+    }
+}
+
+/// Rewrite `Filter(CartesianProduct(l, r), .. AND x = y AND ..)`, where `x`
+/// only reads `l` and `y` only reads `r` (or vice versa), into
+/// `HashJoin(l, r, x, y)` with the remaining conjuncts as a filter on top.
+fn hash_joins(plan: LogicalPlan) -> LogicalPlan {
+    let plan = map_inputs(plan, hash_joins);
+    let LogicalPlan::Filter { input, predicate } = plan else {
+        return plan;
+    };
+    let LogicalPlan::CartesianProduct { left, right } = *input else {
+        return LogicalPlan::Filter { input, predicate };
+    };
+    let (Some(left_vars), Some(right_vars)) = (bound_aliases(&left), bound_aliases(&right)) else {
+        return LogicalPlan::Filter { input: Box::new(LogicalPlan::CartesianProduct { left, right }), predicate };
+    };
+
+    let reads_only = |expr: &Expr, side: &[String]| {
+        expr_variables(expr).is_some_and(|vars| !vars.is_empty() && vars.iter().all(|v| side.contains(v)))
+    };
+    let mut conjuncts = Vec::new();
+    split_conjuncts(predicate, &mut conjuncts);
+    let keys = conjuncts.iter().position(|c| match c {
+        Expr::BinaryOp { left: a, op: BinaryOp::Eq, right: b } => {
+            (reads_only(a, &left_vars) && reads_only(b, &right_vars))
+                || (reads_only(b, &left_vars) && reads_only(a, &right_vars))
+        }
+        _ => false,
+    });
+    let Some(idx) = keys else {
+        let predicate = join_conjuncts(conjuncts).expect("split yields at least one conjunct");
+        return LogicalPlan::Filter { input: Box::new(LogicalPlan::CartesianProduct { left, right }), predicate };
+    };
+
+    let Expr::BinaryOp { left: a, right: b, .. } = conjuncts.remove(idx) else { unreachable!() };
+    let (left_key, right_key) = if reads_only(&a, &left_vars) { (*a, *b) } else { (*b, *a) };
+    let join = LogicalPlan::HashJoin { left, right, left_key, right_key };
+    match join_conjuncts(conjuncts) {
+        Some(predicate) => LogicalPlan::Filter { input: Box::new(join), predicate },
+        None => join,
+    }
+}
+
+/// Rewrite `Aggregate(Expand(AllNodesScan(a), a-[r:T]->b), count(r))`, with
+/// no grouping, labels or predicates, into `RelationshipCountFromCountStore(T)`.
+/// `count(*)` qualifies too. An undirected or multi-type expand, or one
+/// into a bound node, does not.
+fn count_store(plan: LogicalPlan) -> LogicalPlan {
+    let plan = map_inputs(plan, count_store);
+    let LogicalPlan::Aggregate { input, group_by, aggregations } = &plan else {
+        return plan;
+    };
+    let LogicalPlan::Expand { input: scan, from, dir, rel_types, to, rel_alias, path: None, unique_from } = input.as_ref()
+    else {
+        return plan;
+    };
+    let counted = match aggregations.as_slice() {
+        [(Expr::FunctionCall { name, args, distinct: false, .. }, column)] if name.eq_ignore_ascii_case("count") => {
+            match args.as_slice() {
+                [] | [Expr::Star] => Some(column),
+                [Expr::Variable { name, .. }] if rel_alias.as_ref() == Some(name) => Some(column),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    let eligible = group_by.is_empty()
+        && matches!(scan.as_ref(), LogicalPlan::AllNodesScan { alias } if alias == from)
+        && *dir != crate::model::Direction::Both
+        && from != to
+        && unique_from.is_empty();
+    match (counted, rel_types.as_slice()) {
+        (Some(column), [rel_type]) if eligible => {
+            LogicalPlan::RelationshipCountFromCountStore { rel_type: rel_type.clone(), column: column.clone() }
+        }
+        _ => plan,
+    }
+}
+
+fn split_conjuncts(expr: Expr, out: &mut Vec<Expr>) {
+    match expr {
+        Expr::BinaryOp { left, op: BinaryOp::And, right } => {
+            split_conjuncts(*left, out);
+            split_conjuncts(*right, out);
+        }
+        other => out.push(other),
+    }
+}
+
+fn join_conjuncts(conjuncts: Vec<Expr>) -> Option<Expr> {
+    Expr::balanced(conjuncts, BinaryOp::And)
+}
+
+/// Variables an expression reads, or `None` if it has a subquery we can't see into.
+fn expr_variables(expr: &Expr) -> Option<Vec<String>> {
+    fn walk(expr: &Expr, out: &mut Vec<String>) -> Option<()> {
+        match expr {
+            Expr::Variable { name, .. } => out.push(name.clone()),
+            Expr::Property { expr, .. }
+            | Expr::UnaryOp { expr, .. }
+            | Expr::IsNull { expr, .. }
+            | Expr::HasLabel { expr, .. } => walk(expr, out)?,
+            Expr::FunctionCall { args, .. } | Expr::List(args) => {
+                for a in args {
+                    walk(a, out)?;
+                }
+            }
+            Expr::BinaryOp { left, right, .. } | Expr::StringOp { left, right, .. } => {
+                walk(left, out)?;
+                walk(right, out)?;
+            }
+            Expr::In { expr, list } => {
+                walk(expr, out)?;
+                walk(list, out)?;
+            }
+            Expr::MapLiteral(entries) => {
+                for e in entries.values() {
+                    walk(e, out)?;
+                }
+            }
+            Expr::Case { operand, whens, else_expr } => {
+                if let Some(op) = operand {
+                    walk(op, out)?;
+                }
+                for (w, t) in whens {
+                    walk(w, out)?;
+                    walk(t, out)?;
+                }
+                if let Some(e) = else_expr {
+                    walk(e, out)?;
+                }
+            }
+            Expr::Literal(_) | Expr::Parameter { .. } => {}
+            Expr::Exists { .. } | Expr::CountSubquery { .. } | Expr::Star => return None,
+        }
+        Some(())
+    }
+
+    let mut out = Vec::new();
+    walk(expr, &mut out)?;
+    Some(out)
+}
+
+/// Flatten a left-deep chain of cartesian products, sort its inputs by
+/// estimated cardinality (smallest first) and rebuild it. Inputs that share
+/// variables, or whose size is unknown, keep their pattern order.
+///
+/// Each input runs on its own, so a predicate of one that reads another's
+/// variable (`MATCH (a:A), (b:B {x: a.x})`) is hoisted out of it first and
+/// checked above the rebuilt product, where both are bound.
+fn order_joins(plan: LogicalPlan, stats: &Statistics) -> LogicalPlan {
+    match plan {
+        LogicalPlan::CartesianProduct { .. } => {
+            let mut leaves: Vec<LogicalPlan> = product_leaves(&plan).into_iter().cloned().collect();
+            let bound = leaves.iter().map(bound_aliases).collect::<Option<Vec<_>>>();
+            let mut hoisted = Vec::new();
+            if let Some(sets) = &bound {
+                leaves = leaves
+                    .into_iter()
+                    .zip(sets)
+                    .map(|(leaf, own)| hoist_foreign_predicates(leaf, own, &mut hoisted))
+                    .collect();
+            }
+            let independent = bound.is_some_and(|sets| {
+                sets.iter().enumerate().all(|(i, a)| {
+                    sets[i + 1..].iter().all(|b| a.iter().all(|v| !b.contains(v)))
+                })
+            });
+            if independent && leaves.iter().all(|l| stats.cardinality(l).is_some()) {
+                leaves.sort_by_key(|l| stats.cardinality(l));
+            }
+            let mut leaves = leaves.into_iter();
+            let first = leaves.next().expect("cartesian product has inputs");
+            let product = leaves.fold(first, |left, right| LogicalPlan::CartesianProduct {
+                left: Box::new(left),
+                right: Box::new(right),
+            });
+            with_pattern_filter(product, hoisted)
+        }
+        other => map_inputs(other, |p| order_joins(p, stats)),
+    }
+}
+
+/// Remove from the filters of a product input the conjuncts that read a
+/// variable outside `own`, the ones it binds, or that we can't see into,
+/// and push them onto `hoisted`.
+fn hoist_foreign_predicates(plan: LogicalPlan, own: &[String], hoisted: &mut Vec<Expr>) -> LogicalPlan {
+    match plan {
+        LogicalPlan::Filter { input, predicate } => {
+            let input = hoist_foreign_predicates(*input, own, hoisted);
+            let mut conjuncts = Vec::new();
+            split_conjuncts(predicate, &mut conjuncts);
+            let (local, foreign): (Vec<Expr>, Vec<Expr>) = conjuncts
+                .into_iter()
+                .partition(|c| expr_variables(c).is_some_and(|vars| vars.iter().all(|v| own.contains(v))));
+            hoisted.extend(foreign);
+            with_pattern_filter(input, local)
+        }
+        LogicalPlan::Expand { .. } => map_inputs(plan, |p| hoist_foreign_predicates(p, own, hoisted)),
+        other => other,
+    }
+}
+
+/// The non-product inputs of a (possibly nested) cartesian product, in order.
+fn product_leaves(plan: &LogicalPlan) -> Vec<&LogicalPlan> {
+    match plan {
+        LogicalPlan::CartesianProduct { left, right } => {
+            let mut out = product_leaves(left);
+            out.extend(product_leaves(right));
+            out
+        }
+        other => vec![other],
+    }
+}
+
+/// Variables bound by a pattern plan, or `None` for operators we don't track.
+fn bound_aliases(plan: &LogicalPlan) -> Option<Vec<String>> {
+    match plan {
+        LogicalPlan::NodeScan { alias, .. }
+        | LogicalPlan::MultiLabelScan { alias, .. }
+        | LogicalPlan::IndexLookup { alias, .. }
+        | LogicalPlan::IndexRangeScan { alias, .. }
+        | LogicalPlan::AllNodesScan { alias }
+        | LogicalPlan::LoadCsv { alias, .. } => Some(vec![alias.clone()]),
+        LogicalPlan::RelationshipCountFromCountStore { column, .. } => Some(vec![column.clone()]),
+        LogicalPlan::Filter { input, .. } => bound_aliases(input),
+        LogicalPlan::CartesianProduct { left, right } | LogicalPlan::HashJoin { left, right, .. } => {
+            let mut out = bound_aliases(left)?;
+            out.extend(bound_aliases(right)?);
+            Some(out)
+        }
+        LogicalPlan::Expand { input, to, rel_alias, path, .. } => {
+            let mut out = bound_aliases(input)?;
+            out.push(to.clone());
+            out.extend(rel_alias.clone());
+            if let Some(path) = path
+                && !out.contains(path)
+            {
+                out.push(path.clone());
+            }
+            Some(out)
+        }
+        _ => None,
+    }
+}
+
+/// Whether executing `plan` may write: it creates, merges, sets, removes
+/// or deletes, changes the schema, or calls a procedure that writes.
+pub fn plan_writes(plan: &LogicalPlan) -> bool {
+    let mut stack = vec![plan];
+    while let Some(p) = stack.pop() {
+        match p {
+            LogicalPlan::CreateNode { .. }
+            | LogicalPlan::CreateRel { .. }
+            | LogicalPlan::MergeNode { .. }
+            | LogicalPlan::SetProperty { .. }
+            | LogicalPlan::SetProperties { .. }
+            | LogicalPlan::DeleteNode { .. }
+            | LogicalPlan::DeleteRel { .. }
+            | LogicalPlan::RemoveProperty { .. }
+            | LogicalPlan::RemoveLabel { .. }
+            | LogicalPlan::SchemaOp(_) => return true,
+            LogicalPlan::CallProcedure { name, .. } if procedure_writes(name) => return true,
+            _ => {}
+        }
+        stack.extend(plan_inputs(p));
+    }
+    false
+}
+
+/// Built-in procedures that write, though their plan shows no write
+/// operator: they run Cypher fragments, take labels at runtime or
+/// refactor existing nodes.
+pub(crate) fn procedure_writes(name: &str) -> bool {
+    ["apoc.create.node", "apoc.do.when", "apoc.periodic.iterate", "apoc.refactor.mergeNodes"]
+        .iter()
+        .any(|p| p.eq_ignore_ascii_case(name))
+}
+
+/// Fail if `plan` needs something the backend reports it can't do, so the
+/// query stops before execution with an error that says what is missing.
+pub fn check_capabilities(plan: &LogicalPlan, capabilities: &BackendCapabilities) -> Result<()> {
+    let mut stack = vec![plan];
+    while let Some(p) = stack.pop() {
+        if let LogicalPlan::CallProcedure { name, .. } = p
+            && name.eq_ignore_ascii_case("db.index.vector.queryNodes")
+            && !capabilities.supports_vector_index
+        {
+            return Err(crate::plan_err!("backend does not support vector indexes, which {name} needs"));
+        }
+        if let LogicalPlan::SchemaOp(cmd) = p {
+            let missing = match cmd {
+                SchemaCommand::CreateIndex { index_type, .. } => match crate::schema::index_type_of(index_type.as_deref()) {
+                    crate::index::IndexType::FullText if !capabilities.supports_fulltext_index => Some("full-text indexes"),
+                    crate::index::IndexType::Vector if !capabilities.supports_vector_index => Some("vector indexes"),
+                    _ => None,
+                },
+                SchemaCommand::CreateConstraint { .. } | SchemaCommand::DropConstraint { .. } => {
+                    (!capabilities.supports_constraints).then_some("constraints")
+                }
+                SchemaCommand::DropIndex { .. } => None,
+            };
+            if let Some(missing) = missing {
+                return Err(crate::plan_err!("backend does not support {missing}, which {} needs", explain::schema_text(cmd)));
+            }
+        }
+        stack.extend(plan_inputs(p));
+    }
+    Ok(())
+}
+
+/// Direct child plans of `plan`.
+pub(crate) fn plan_inputs(plan: &LogicalPlan) -> Vec<&LogicalPlan> {
+    match plan {
+        LogicalPlan::CartesianProduct { left, right } | LogicalPlan::HashJoin { left, right, .. } => {
+            vec![left, right]
+        }
+        LogicalPlan::CreateNode { input, .. }
+        | LogicalPlan::CreateRel { input, .. }
+        | LogicalPlan::MergeNode { input, .. } => input.iter().map(|i| &**i).collect(),
+        LogicalPlan::Expand { input, .. }
+        | LogicalPlan::Filter { input, .. }
+        | LogicalPlan::Project { input, .. }
+        | LogicalPlan::Limit { input, .. }
+        | LogicalPlan::LimitPercent { input, .. }
+        | LogicalPlan::Skip { input, .. }
+        | LogicalPlan::Sort { input, .. }
+        | LogicalPlan::Aggregate { input, .. }
+        | LogicalPlan::Distinct { input }
+        | LogicalPlan::SetProperty { input, .. }
+        | LogicalPlan::SetProperties { input, .. }
+        | LogicalPlan::DeleteNode { input, .. }
+        | LogicalPlan::DeleteRel { input, .. }
+        | LogicalPlan::Unwind { input, .. }
+        | LogicalPlan::RemoveProperty { input, .. }
+        | LogicalPlan::RemoveLabel { input, .. } => vec![input],
+        _ => Vec::new(),
+    }
+}
+
+/// Rebuild `plan` with `f` applied to each direct child.
+fn map_inputs(plan: LogicalPlan, mut f: impl FnMut(LogicalPlan) -> LogicalPlan) -> LogicalPlan {
+    let mut apply = |b: Box<LogicalPlan>| Box::new(f(*b));
+    match plan {
+        LogicalPlan::CartesianProduct { left, right } => {
+            let left = apply(left);
+            LogicalPlan::CartesianProduct { left, right: apply(right) }
+        }
+        LogicalPlan::HashJoin { left, right, left_key, right_key } => {
+            let left = apply(left);
+            LogicalPlan::HashJoin { left, right: apply(right), left_key, right_key }
+        }
+        LogicalPlan::CreateNode { input, labels, properties, alias } => {
+            LogicalPlan::CreateNode { input: input.map(apply), labels, properties, alias }
+        }
+        LogicalPlan::CreateRel { input, src, dst, rel_type, properties, alias } => {
+            LogicalPlan::CreateRel { input: input.map(apply), src, dst, rel_type, properties, alias }
+        }
+        LogicalPlan::MergeNode { input, labels, properties, alias, on_create, on_match } => {
+            LogicalPlan::MergeNode { input: input.map(apply), labels, properties, alias, on_create, on_match }
+        }
+        LogicalPlan::Expand { input, from, dir, rel_types, to, rel_alias, path, unique_from } => {
+            LogicalPlan::Expand { input: apply(input), from, dir, rel_types, to, rel_alias, path, unique_from }
+        }
+        LogicalPlan::Filter { input, predicate } => LogicalPlan::Filter { input: apply(input), predicate },
+        LogicalPlan::Project { input, items } => LogicalPlan::Project { input: apply(input), items },
+        LogicalPlan::Limit { input, count } => LogicalPlan::Limit { input: apply(input), count },
+        LogicalPlan::LimitPercent { input, fraction } => LogicalPlan::LimitPercent { input: apply(input), fraction },
+        LogicalPlan::Skip { input, count } => LogicalPlan::Skip { input: apply(input), count },
+        LogicalPlan::Sort { input, keys } => LogicalPlan::Sort { input: apply(input), keys },
+        LogicalPlan::Aggregate { input, group_by, aggregations } => {
+            LogicalPlan::Aggregate { input: apply(input), group_by, aggregations }
+        }
+        LogicalPlan::Distinct { input } => LogicalPlan::Distinct { input: apply(input) },
+        LogicalPlan::SetProperty { input, variable, key, value } => {
+            LogicalPlan::SetProperty { input: apply(input), variable, key, value }
+        }
+        LogicalPlan::SetProperties { input, variable, value, replace } => {
+            LogicalPlan::SetProperties { input: apply(input), variable, value, replace }
+        }
+        LogicalPlan::DeleteNode { input, variable, detach } => {
+            LogicalPlan::DeleteNode { input: apply(input), variable, detach }
+        }
+        LogicalPlan::DeleteRel { input, variable } => LogicalPlan::DeleteRel { input: apply(input), variable },
+        LogicalPlan::Unwind { input, expr, alias } => LogicalPlan::Unwind { input: apply(input), expr, alias },
+        LogicalPlan::RemoveProperty { input, variable, key } => {
+            LogicalPlan::RemoveProperty { input: apply(input), variable, key }
+        }
+        LogicalPlan::RemoveLabel { input, variable, label } => {
+            LogicalPlan::RemoveLabel { input: apply(input), variable, label }
+        }
+        other => other,
+    }
 }
@@ -0,0 +1,84 @@
+//! Query notifications: warnings about queries that run, but probably not
+//! the way their author meant, after Neo4j's notification codes.
+
+use crate::cypher::ast::*;
+use super::{bound_aliases, plan_inputs, LogicalPlan, ANON_PREFIX};
+
+/// A warning attached to a `QueryResult`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// Neo4j's status code, e.g. `Neo.ClientNotification.Statement.CartesianProduct`.
+    pub code: String,
+    pub title: String,
+    pub description: String,
+}
+
+/// Notifications for an optimized plan: a cartesian product that join
+/// planning couldn't turn into a hash join joins disconnected patterns with
+/// nothing relating them.
+pub fn plan_notifications(plan: &LogicalPlan) -> Vec<Notification> {
+    let mut out = Vec::new();
+    let mut stack = vec![plan];
+    while let Some(p) = stack.pop() {
+        // LOAD CSV feeds its rows in through a product; that one is meant
+        if let LogicalPlan::CartesianProduct { left, right } = p
+            && !matches!(left.as_ref(), LogicalPlan::LoadCsv { .. })
+        {
+            let identifiers = bound_aliases(right)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|a| !a.starts_with(ANON_PREFIX))
+                .map(|a| format!("({a})"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push(Notification {
+                code: "Neo.ClientNotification.Statement.CartesianProduct".into(),
+                title: "This query builds a cartesian product between disconnected patterns.".into(),
+                description: format!(
+                    "If a part of a query contains multiple disconnected patterns, this will build a cartesian \
+                     product between all those parts. This may produce a large amount of data and slow down query \
+                     processing. While occasionally intended, it may often be possible to reformulate the query that \
+                     avoids the use of this cross product, perhaps by adding a relationship between the different \
+                     parts or by using OPTIONAL MATCH (identifier is: {identifiers})"
+                ),
+            });
+        }
+        stack.extend(plan_inputs(p));
+    }
+    out
+}
+
+/// Notifications for the MATCH patterns of `ast`: variable-length
+/// relationships without an upper bound (`*`, `*2..`).
+pub fn statement_notifications(ast: &Statement) -> Vec<Notification> {
+    let matches = match ast {
+        Statement::Query(q) => &q.matches,
+        Statement::Create(c) => &c.matches,
+        Statement::Merge(m) => &m.matches,
+        Statement::Delete(d) => &d.matches,
+        Statement::Set(s) => &s.matches,
+        Statement::Remove(r) => &r.matches,
+        Statement::LoadCsv { body, .. } => return statement_notifications(body),
+        Statement::Schema(_) | Statement::Call(_) => return Vec::new(),
+    };
+    matches
+        .iter()
+        .flat_map(|m| &m.patterns)
+        .flat_map(|p| &p.elements)
+        .filter_map(|element| match element {
+            PatternElement::Relationship(RelPattern { var_length: Some(v), alias, .. }) if v.max.is_none() => {
+                Some(alias)
+            }
+            _ => None,
+        })
+        .map(|alias| Notification {
+            code: "Neo.ClientNotification.Statement.UnboundedVariableLengthPattern".into(),
+            title: "The provided pattern is unbounded, consider adding an upper limit to the number of node hops."
+                .into(),
+            description: match alias {
+                Some(alias) => format!("Relationship {alias} has no upper bound on its length, so it may visit the whole graph"),
+                None => "A relationship has no upper bound on its length, so it may visit the whole graph".into(),
+            },
+        })
+        .collect()
+}
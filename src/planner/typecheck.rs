@@ -0,0 +1,299 @@
+//! Static type checks over a logical plan.
+//!
+//! This is a best-effort pass: it only rejects operations whose operand
+//! types are known at plan time (literals, list/map constructors, scanned
+//! node and relationship variables, and properties listed in `Schema`).
+//! Anything it can't type is let through to execution.
+
+use std::collections::HashMap;
+
+use crate::cypher::ast::{BinaryOp, Expr, Literal, Span, UnaryOp};
+use crate::{Error, Result};
+
+use super::{LogicalPlan, plan_inputs};
+
+/// What the type checker knows about the graph beyond the query itself.
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    /// Known property types keyed by `(label, property)`, using
+    /// `Value::type_name()` names such as `"STRING"` or `"INTEGER"`.
+    pub property_types: HashMap<(String, String), String>,
+}
+
+/// Reject clearly ill-typed operations in `plan` with a `SemanticError`.
+pub fn typecheck(plan: &LogicalPlan, schema: &Schema) -> Result<()> {
+    let mut scope = Scope { schema, vars: HashMap::new() };
+    scope.bind(plan);
+    scope.check_plan(plan)
+}
+
+/// Static type of an expression, when it can be known without executing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ty {
+    Null,
+    Bool,
+    Int,
+    Float,
+    String,
+    List,
+    Map,
+    Node,
+    Relationship,
+}
+
+impl Ty {
+    fn from_name(name: &str) -> Option<Ty> {
+        Some(match name {
+            "BOOLEAN" => Ty::Bool,
+            "INTEGER" => Ty::Int,
+            "FLOAT" => Ty::Float,
+            "STRING" => Ty::String,
+            "LIST" => Ty::List,
+            "MAP" => Ty::Map,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Ty::Null => "NULL",
+            Ty::Bool => "BOOLEAN",
+            Ty::Int => "INTEGER",
+            Ty::Float => "FLOAT",
+            Ty::String => "STRING",
+            Ty::List => "LIST",
+            Ty::Map => "MAP",
+            Ty::Node => "NODE",
+            Ty::Relationship => "RELATIONSHIP",
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(self, Ty::Int | Ty::Float)
+    }
+
+    /// Types that `=` and `<` can meaningfully compare with each other.
+    fn comparable_with(self, other: Ty) -> bool {
+        self == other || (self.is_numeric() && other.is_numeric())
+    }
+}
+
+/// Variables bound by the plan: node variables carry their scanned label.
+enum Binding {
+    Node(Option<String>),
+    Relationship,
+}
+
+struct Scope<'s> {
+    schema: &'s Schema,
+    vars: HashMap<String, Binding>,
+}
+
+impl Scope<'_> {
+    fn bind(&mut self, plan: &LogicalPlan) {
+        match plan {
+            LogicalPlan::NodeScan { label, alias }
+            | LogicalPlan::IndexLookup { label, alias, .. }
+            | LogicalPlan::IndexRangeScan { label, alias, .. } => {
+                self.vars.insert(alias.clone(), Binding::Node(Some(label.clone())));
+            }
+            // Property types are looked up under the first label
+            LogicalPlan::MultiLabelScan { labels, alias } => {
+                self.vars.insert(alias.clone(), Binding::Node(labels.first().cloned()));
+            }
+            LogicalPlan::AllNodesScan { alias } => {
+                self.vars.insert(alias.clone(), Binding::Node(None));
+            }
+            LogicalPlan::Expand { to, rel_alias, .. } => {
+                self.vars.entry(to.clone()).or_insert(Binding::Node(None));
+                if let Some(r) = rel_alias {
+                    self.vars.insert(r.clone(), Binding::Relationship);
+                }
+            }
+            _ => {}
+        }
+        for input in plan_inputs(plan) {
+            self.bind(input);
+        }
+    }
+
+    fn check_plan(&self, plan: &LogicalPlan) -> Result<()> {
+        match plan {
+            LogicalPlan::Filter { predicate, .. } => self.check_expr(predicate)?,
+            LogicalPlan::Project { items, .. } => {
+                for (e, _) in items {
+                    self.check_expr(e)?;
+                }
+            }
+            LogicalPlan::Sort { keys, .. } => {
+                for (e, _) in keys {
+                    self.check_expr(e)?;
+                }
+            }
+            LogicalPlan::Aggregate { group_by, aggregations, .. } => {
+                for (e, _) in group_by.iter().chain(aggregations) {
+                    self.check_expr(e)?;
+                }
+            }
+            LogicalPlan::HashJoin { left_key, right_key, .. } => {
+                self.check_expr(left_key)?;
+                self.check_expr(right_key)?;
+            }
+            LogicalPlan::Unwind { expr, .. } => self.check_expr(expr)?,
+            LogicalPlan::SetProperty { value, .. } | LogicalPlan::SetProperties { value, .. } => self.check_expr(value)?,
+            LogicalPlan::CreateNode { properties, .. } => {
+                for (_, e) in properties {
+                    self.check_expr(e)?;
+                }
+            }
+            LogicalPlan::RemoveLabel { variable, label, .. } => {
+                if matches!(self.vars.get(variable), Some(Binding::Relationship)) {
+                    return Err(Error::semantic(format!(
+                        "Cannot remove label :{label} from relationship variable `{variable}`"
+                    )));
+                }
+            }
+            _ => {}
+        }
+        for input in plan_inputs(plan) {
+            self.check_plan(input)?;
+        }
+        Ok(())
+    }
+
+    fn ty(&self, expr: &Expr) -> Option<Ty> {
+        match expr {
+            Expr::Literal(Literal::Null) => Some(Ty::Null),
+            Expr::Literal(Literal::Bool(_)) => Some(Ty::Bool),
+            Expr::Literal(Literal::Int(_)) => Some(Ty::Int),
+            Expr::Literal(Literal::Float(_)) => Some(Ty::Float),
+            Expr::Literal(Literal::String(_)) => Some(Ty::String),
+            Expr::List(_) => Some(Ty::List),
+            Expr::MapLiteral(_) => Some(Ty::Map),
+            Expr::Variable { name: v, .. } => match self.vars.get(v)? {
+                Binding::Node(_) => Some(Ty::Node),
+                Binding::Relationship => Some(Ty::Relationship),
+            },
+            Expr::Property { expr, key } => {
+                let Expr::Variable { name: v, .. } = expr.as_ref() else { return None };
+                let Binding::Node(Some(label)) = self.vars.get(v)? else { return None };
+                let name = self.schema.property_types.get(&(label.clone(), key.clone()))?;
+                Ty::from_name(name)
+            }
+            Expr::IsNull { .. } | Expr::HasLabel { .. } | Expr::StringOp { .. } | Expr::In { .. } => Some(Ty::Bool),
+            Expr::UnaryOp { op: UnaryOp::Not, .. } => Some(Ty::Bool),
+            Expr::BinaryOp { op, .. } if !matches!(
+                op,
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod | BinaryOp::Pow
+            ) => Some(Ty::Bool),
+            _ => None,
+        }
+    }
+
+    fn check_expr(&self, expr: &Expr) -> Result<()> {
+        match expr {
+            Expr::BinaryOp { left, op, right } => {
+                self.check_expr(left)?;
+                self.check_expr(right)?;
+                if let (Some(l), Some(r)) = (self.ty(left), self.ty(right)) {
+                    self.check_binary(l, *op, r).map_err(|e| e.or_span(covering_span(expr)))?;
+                }
+            }
+            Expr::UnaryOp { expr, .. }
+            | Expr::Property { expr, .. }
+            | Expr::IsNull { expr, .. }
+            | Expr::HasLabel { expr, .. } => self.check_expr(expr)?,
+            Expr::FunctionCall { args, .. } | Expr::List(args) => {
+                for a in args {
+                    self.check_expr(a)?;
+                }
+            }
+            Expr::MapLiteral(entries) => {
+                for e in entries.values() {
+                    self.check_expr(e)?;
+                }
+            }
+            Expr::In { expr, list } => {
+                self.check_expr(expr)?;
+                self.check_expr(list)?;
+                if let Some(t) = self.ty(list).filter(|t| !matches!(t, Ty::List | Ty::Null)) {
+                    return Err(Error::semantic(format!("IN expects a LIST, got {}", t.name()))
+                        .or_span(covering_span(list)));
+                }
+            }
+            Expr::StringOp { left, right, .. } => {
+                self.check_expr(left)?;
+                self.check_expr(right)?;
+            }
+            Expr::Case { operand, whens, else_expr } => {
+                for e in operand.iter().chain(else_expr).map(|b| b.as_ref()) {
+                    self.check_expr(e)?;
+                }
+                for (w, t) in whens {
+                    self.check_expr(w)?;
+                    self.check_expr(t)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn check_binary(&self, l: Ty, op: BinaryOp, r: Ty) -> Result<()> {
+        if l == Ty::Null || r == Ty::Null {
+            return Ok(());
+        }
+        let ok = match op {
+            BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod | BinaryOp::Pow => {
+                l.is_numeric() && r.is_numeric()
+            }
+            // `+` also concatenates strings and lists
+            BinaryOp::Add => {
+                (l.is_numeric() && r.is_numeric())
+                    || l == Ty::List
+                    || r == Ty::List
+                    || (l == Ty::String && matches!(r, Ty::String | Ty::Int | Ty::Float))
+                    || (r == Ty::String && matches!(l, Ty::Int | Ty::Float))
+            }
+            BinaryOp::Eq | BinaryOp::Neq | BinaryOp::Lt | BinaryOp::Lte | BinaryOp::Gt | BinaryOp::Gte => {
+                l.comparable_with(r)
+            }
+            BinaryOp::And | BinaryOp::Or | BinaryOp::Xor => l == Ty::Bool && r == Ty::Bool,
+            BinaryOp::RegexMatch => l == Ty::String && r == Ty::String,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(Error::semantic(format!(
+                "Type mismatch: cannot apply {op:?} to {} and {}",
+                l.name(),
+                r.name()
+            )))
+        }
+    }
+}
+
+/// The smallest span covering every located sub-expression of `expr`.
+/// Literals and operators carry no position, so this can be narrower than
+/// the expression's full text, or `None`.
+pub(super) fn covering_span(expr: &Expr) -> Option<Span> {
+    let mut children: Vec<&Expr> = Vec::new();
+    match expr {
+        Expr::Variable { span, .. } | Expr::Parameter { span, .. } | Expr::FunctionCall { span, .. } => return *span,
+        Expr::Property { expr, .. }
+        | Expr::UnaryOp { expr, .. }
+        | Expr::IsNull { expr, .. }
+        | Expr::HasLabel { expr, .. } => children.push(expr),
+        Expr::BinaryOp { left, right, .. } | Expr::StringOp { left, right, .. } => {
+            children.extend([left.as_ref(), right.as_ref()]);
+        }
+        Expr::In { expr, list } => children.extend([expr.as_ref(), list.as_ref()]),
+        Expr::List(items) => children.extend(items),
+        Expr::MapLiteral(entries) => children.extend(entries.values()),
+        _ => {}
+    }
+    children.into_iter().filter_map(covering_span).reduce(|a, b| Span {
+        start: a.start.min(b.start),
+        end: a.end.max(b.end),
+    })
+}
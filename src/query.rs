@@ -0,0 +1,170 @@
+//! Programmatic query construction.
+//!
+//! `QueryBuilder` assembles the AST of a read query and plans it with
+//! `planner::plan`, so it yields exactly the `LogicalPlan` the equivalent
+//! Cypher string would, without going through the parser. Values are always
+//! bound as parameters, never spliced into query text.
+//!
+//! ```rust,no_run
+//! use neo4j_rs::{Graph, query::QueryBuilder};
+//!
+//! # async fn example() -> neo4j_rs::Result<()> {
+//! let graph = Graph::open_memory().await?;
+//! // MATCH (n:Person) WHERE n.name = $p0 RETURN n
+//! let (plan, params) = QueryBuilder::new()
+//!     .match_node("n", "Person")
+//!     .where_eq("n.name", "Ada")
+//!     .return_vars(&["n"])
+//!     .build()?;
+//! let result = graph.run_logical(plan, params).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::cypher::ast::*;
+use crate::model::{Direction, PropertyMap, Value};
+use crate::planner::{self, LogicalPlan};
+use crate::Result;
+
+/// Fluent builder for `MATCH ... WHERE ... RETURN` queries.
+///
+/// Property paths such as `"n.name"` name a variable followed by property
+/// keys. Each `where_eq` value becomes a parameter `$p0`, `$p1`, ... in call
+/// order, and the conditions are joined with `AND`.
+#[derive(Debug, Clone, Default)]
+pub struct QueryBuilder {
+    patterns: Vec<Pattern>,
+    predicates: Vec<Expr>,
+    order_by: Vec<OrderExpr>,
+    items: Vec<ReturnItem>,
+    distinct: bool,
+    skip: Option<usize>,
+    limit: Option<usize>,
+    params: PropertyMap,
+}
+
+impl QueryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `MATCH (alias:label)`.
+    pub fn match_node(mut self, alias: &str, label: &str) -> Self {
+        self.patterns.push(Pattern { elements: vec![node(alias, Some(label))], path: None, span: None });
+        self
+    }
+
+    /// `MATCH (from)-[:rel_type]->(to)`, or the other way round for
+    /// `Direction::Incoming`. `from` is usually bound by an earlier `match_node`.
+    pub fn match_rel(mut self, from: &str, rel_type: &str, to: &str, dir: Direction) -> Self {
+        let direction = match dir {
+            Direction::Outgoing => PatternDirection::Right,
+            Direction::Incoming => PatternDirection::Left,
+            Direction::Both => PatternDirection::Both,
+        };
+        let rel = RelPattern {
+            alias: None,
+            rel_types: vec![rel_type.to_string()],
+            direction,
+            properties: HashMap::new(),
+            var_length: None,
+            predicate: None,
+        };
+        self.patterns.push(Pattern {
+            elements: vec![node(from, None), PatternElement::Relationship(rel), node(to, None)],
+            path: None,
+            span: None,
+        });
+        self
+    }
+
+    /// `WHERE path = $pN`, binding `value` to the next parameter.
+    pub fn where_eq(mut self, path: &str, value: impl Into<Value>) -> Self {
+        let name = format!("p{}", self.params.len());
+        self.params.insert(name.clone(), value.into());
+        self.predicates.push(Expr::BinaryOp {
+            left: Box::new(property_path(path)),
+            op: BinaryOp::Eq,
+            right: Box::new(Expr::Parameter { name, span: None }),
+        });
+        self
+    }
+
+    /// `ORDER BY path [DESC]`; repeated calls add further sort keys.
+    pub fn order_by(mut self, path: &str, ascending: bool) -> Self {
+        self.order_by.push(OrderExpr { expr: property_path(path), ascending });
+        self
+    }
+
+    /// `RETURN` each path, as a column named after it (`"n"`, `"n.name"`).
+    pub fn return_vars(mut self, paths: &[&str]) -> Self {
+        self.items.extend(paths.iter().map(|path| ReturnItem { expr: property_path(path), alias: None }));
+        self
+    }
+
+    /// `RETURN DISTINCT`.
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
+        self
+    }
+
+    pub fn skip(mut self, count: usize) -> Self {
+        self.skip = Some(count);
+        self
+    }
+
+    pub fn limit(mut self, count: usize) -> Self {
+        self.limit = Some(count);
+        self
+    }
+
+    /// Plan the query, returning it with the parameters it binds.
+    pub fn build(self) -> Result<(LogicalPlan, PropertyMap)> {
+        if self.items.is_empty() {
+            return Err(crate::plan_err!("QueryBuilder needs at least one return item"));
+        }
+        let query = Query {
+            matches: if self.patterns.is_empty() {
+                Vec::new()
+            } else {
+                vec![MatchClause { optional: false, patterns: self.patterns, hints: Vec::new() }]
+            },
+            where_clause: self.predicates.into_iter().reduce(|left, right| Expr::BinaryOp {
+                left: Box::new(left),
+                op: BinaryOp::And,
+                right: Box::new(right),
+            }),
+            unwinds: Vec::new(),
+            with_clauses: Vec::new(),
+            return_clause: ReturnClause { distinct: self.distinct, items: self.items },
+            order_by: (!self.order_by.is_empty()).then_some(self.order_by),
+            skip: self.skip.map(count_literal),
+            limit: self.limit.map(count_literal),
+            limit_percent: false,
+        };
+        let plan = planner::plan(&Statement::Query(query), &self.params)?;
+        Ok((plan, self.params))
+    }
+}
+
+fn node(alias: &str, label: Option<&str>) -> PatternElement {
+    PatternElement::Node(NodePattern {
+        alias: Some(alias.to_string()),
+        labels: label.map(str::to_string).into_iter().collect(),
+        properties: HashMap::new(),
+        predicate: None,
+    })
+}
+
+/// `"n.address.city"` as `n`, then one property access per key.
+fn property_path(path: &str) -> Expr {
+    let mut parts = path.split('.');
+    let variable = Expr::Variable { name: parts.next().unwrap_or_default().to_string(), span: None };
+    parts.fold(variable, |expr, key| Expr::Property { expr: Box::new(expr), key: key.to_string() })
+}
+
+fn count_literal(count: usize) -> Expr {
+    Expr::Literal(Literal::Int(count as i64))
+}
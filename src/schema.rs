@@ -0,0 +1,274 @@
+//! Schema registry: the labels, relationship types, indexes and
+//! constraints a `Graph` knows about; see `Graph::schema`.
+//!
+//! Labels, relationship types, indexes and constraints are loaded from the
+//! backend by the first query a `Graph` runs. After that the registry
+//! follows each committed transaction: its statements' new labels and
+//! types are added, and its `CREATE`/`DROP INDEX` or `CONSTRAINT`
+//! statements applied. A rolled-back transaction changes nothing. A
+//! procedure that writes (`apoc.create.node`, `apoc.do.when`) makes the
+//! next query reload from the backend instead. Writes made directly
+//! through `Graph::backend()` are not seen; call `Graph::refresh_schema()`
+//! after them.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use parking_lot::RwLock;
+
+use crate::cypher::ast::SchemaCommand;
+use crate::index::IndexType;
+use crate::planner::{LogicalPlan, plan_inputs, procedure_writes};
+use crate::storage::ConstraintType;
+
+/// Cached schema of one graph. Cheap to read; every accessor returns a
+/// snapshot.
+#[derive(Debug, Default)]
+pub struct SchemaRegistry {
+    state: RwLock<SchemaState>,
+}
+
+#[derive(Debug, Default)]
+struct SchemaState {
+    loaded: bool,
+    labels: BTreeSet<String>,
+    relationship_types: BTreeSet<String>,
+    /// `(label, property)` → index type
+    indexes: BTreeMap<(String, String), IndexType>,
+    /// `(label, property)` → constraint type
+    constraints: BTreeMap<(String, String), ConstraintType>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Known labels, sorted.
+    pub fn labels(&self) -> Vec<String> {
+        self.state.read().labels.iter().cloned().collect()
+    }
+
+    /// Known relationship types, sorted.
+    pub fn relationship_types(&self) -> Vec<String> {
+        self.state.read().relationship_types.iter().cloned().collect()
+    }
+
+    pub fn has_label(&self, label: &str) -> bool {
+        self.state.read().labels.contains(label)
+    }
+
+    pub fn has_relationship_type(&self, rel_type: &str) -> bool {
+        self.state.read().relationship_types.contains(rel_type)
+    }
+
+    /// Known indexes as `(label, property, type)`, sorted by label and property.
+    pub fn indexes(&self) -> Vec<(String, String, IndexType)> {
+        let state = self.state.read();
+        state.indexes.iter().map(|((l, p), t)| (l.clone(), p.clone(), *t)).collect()
+    }
+
+    /// The type of the index on `label.property`, if there is one.
+    pub fn index_type(&self, label: &str, property: &str) -> Option<IndexType> {
+        self.state.read().indexes.get(&(label.to_string(), property.to_string())).copied()
+    }
+
+    pub fn has_index(&self, label: &str, property: &str) -> bool {
+        self.index_type(label, property).is_some()
+    }
+
+    /// Known constraints as `(label, property, type)`, sorted by label and property.
+    pub fn constraints(&self) -> Vec<(String, String, ConstraintType)> {
+        let state = self.state.read();
+        state.constraints.iter().map(|((l, p), t)| (l.clone(), p.clone(), *t)).collect()
+    }
+
+    /// The constraint on `label.property`, if there is one.
+    pub fn constraint(&self, label: &str, property: &str) -> Option<ConstraintType> {
+        self.state.read().constraints.get(&(label.to_string(), property.to_string())).copied()
+    }
+
+    /// Whether labels and relationship types have been loaded from the backend.
+    pub(crate) fn is_loaded(&self) -> bool {
+        self.state.read().loaded
+    }
+
+    /// Merge the backend's labels and relationship types into the registry,
+    /// and take its indexes and constraints as they are.
+    pub(crate) fn load(&self, loaded: LoadedSchema) {
+        let mut state = self.state.write();
+        state.labels.extend(loaded.labels);
+        state.relationship_types.extend(loaded.relationship_types);
+        state.indexes = loaded.indexes.into_iter().map(|(l, p, t)| ((l, p), t)).collect();
+        state.constraints = loaded.constraints.into_iter().map(|(l, p, t)| ((l, p), t)).collect();
+        state.loaded = true;
+    }
+
+    /// Apply the changes of a transaction that has just committed.
+    pub(crate) fn apply(&self, changes: SchemaChanges) {
+        let mut state = self.state.write();
+        state.labels.extend(changes.labels);
+        state.relationship_types.extend(changes.relationship_types);
+        for cmd in &changes.commands {
+            state.apply(cmd);
+        }
+        if changes.reload {
+            state.loaded = false;
+        }
+    }
+}
+
+/// What the backend reports, for `SchemaRegistry::load`.
+#[derive(Debug, Default)]
+pub(crate) struct LoadedSchema {
+    pub labels: Vec<String>,
+    pub relationship_types: Vec<String>,
+    pub indexes: Vec<(String, String, IndexType)>,
+    pub constraints: Vec<(String, String, ConstraintType)>,
+}
+
+/// The schema effects of the statements a transaction has run, held until
+/// it commits and then handed to `SchemaRegistry::apply`.
+#[derive(Debug, Default)]
+pub(crate) struct SchemaChanges {
+    labels: BTreeSet<String>,
+    relationship_types: BTreeSet<String>,
+    commands: Vec<SchemaCommand>,
+    reload: bool,
+}
+
+impl SchemaChanges {
+    /// Add the effects of `plan`, which has just executed successfully.
+    pub(crate) fn add(&mut self, plan: &LogicalPlan) {
+        let mut stack = vec![plan];
+        while let Some(p) = stack.pop() {
+            match p {
+                LogicalPlan::CreateNode { labels, .. } | LogicalPlan::MergeNode { labels, .. } => {
+                    self.labels.extend(labels.iter().cloned());
+                }
+                LogicalPlan::CreateRel { rel_type, .. } => {
+                    self.relationship_types.insert(rel_type.clone());
+                }
+                LogicalPlan::SchemaOp(cmd) => self.commands.push(cmd.clone()),
+                // Procedures that write can create any label or type, so
+                // the next query reloads them from the backend
+                LogicalPlan::CallProcedure { name, .. } if procedure_writes(name) => self.reload = true,
+                _ => {}
+            }
+            stack.extend(plan_inputs(p));
+        }
+    }
+
+    /// Add the changes of a later statement in the same transaction.
+    pub(crate) fn merge(&mut self, other: SchemaChanges) {
+        self.labels.extend(other.labels);
+        self.relationship_types.extend(other.relationship_types);
+        self.commands.extend(other.commands);
+        self.reload |= other.reload;
+    }
+}
+
+impl SchemaState {
+    fn apply(&mut self, cmd: &SchemaCommand) {
+        match cmd {
+            SchemaCommand::CreateIndex { label, property, index_type } => {
+                self.indexes.insert((label.clone(), property.clone()), index_type_of(index_type.as_deref()));
+            }
+            // An empty property (`DROP ... ON (n:Label)` or by name) drops
+            // every entry under that name
+            SchemaCommand::DropIndex { label, property } if property.is_empty() => {
+                self.indexes.retain(|(l, _), _| l != label);
+            }
+            SchemaCommand::DropIndex { label, property } => {
+                self.indexes.remove(&(label.clone(), property.clone()));
+            }
+            SchemaCommand::CreateConstraint { label, property, constraint_type } => {
+                self.constraints.insert((label.clone(), property.clone()), constraint_type_of(constraint_type));
+            }
+            SchemaCommand::DropConstraint { label, property } if property.is_empty() => {
+                self.constraints.retain(|(l, _), _| l != label);
+            }
+            SchemaCommand::DropConstraint { label, property } => {
+                self.constraints.remove(&(label.clone(), property.clone()));
+            }
+        }
+    }
+}
+
+/// The index a `CREATE [type] INDEX` statement asks for; untyped and
+/// `RANGE` indexes are sorted ones.
+pub(crate) fn index_type_of(index_type: Option<&str>) -> IndexType {
+    match index_type.map(str::to_ascii_uppercase).as_deref() {
+        Some("TEXT" | "FULLTEXT") => IndexType::FullText,
+        Some("VECTOR") => IndexType::Vector,
+        Some("UNIQUE") => IndexType::Unique,
+        _ => IndexType::BTree,
+    }
+}
+
+/// The constraint a `CREATE CONSTRAINT ... REQUIRE` clause describes.
+pub(crate) fn constraint_type_of(constraint_type: &str) -> ConstraintType {
+    if constraint_type.contains("UNIQUE") {
+        ConstraintType::Unique
+    } else {
+        ConstraintType::Exists
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    trait Record {
+        fn record(&self, plan: &LogicalPlan);
+    }
+
+    impl Record for SchemaRegistry {
+        fn record(&self, plan: &LogicalPlan) {
+            let mut changes = SchemaChanges::default();
+            changes.add(plan);
+            self.apply(changes);
+        }
+    }
+
+    fn schema_op(cmd: SchemaCommand) -> LogicalPlan {
+        LogicalPlan::SchemaOp(cmd)
+    }
+
+    #[test]
+    fn test_record_schema_commands() {
+        let registry = SchemaRegistry::new();
+        registry.record(&schema_op(SchemaCommand::CreateIndex {
+            label: "Person".into(),
+            property: "age".into(),
+            index_type: None,
+        }));
+        registry.record(&schema_op(SchemaCommand::CreateConstraint {
+            label: "Person".into(),
+            property: "email".into(),
+            constraint_type: "UNIQUE".into(),
+        }));
+        registry.record(&schema_op(SchemaCommand::CreateConstraint {
+            label: "Person".into(),
+            property: "name".into(),
+            constraint_type: "NOT".into(),
+        }));
+        assert_eq!(registry.indexes(), vec![("Person".into(), "age".into(), IndexType::BTree)]);
+        registry.record(&schema_op(SchemaCommand::CreateIndex {
+            label: "Doc".into(),
+            property: "body".into(),
+            index_type: Some("TEXT".into()),
+        }));
+        assert_eq!(registry.index_type("Doc", "body"), Some(IndexType::FullText));
+        assert_eq!(registry.constraint("Person", "email"), Some(ConstraintType::Unique));
+        assert_eq!(registry.constraint("Person", "name"), Some(ConstraintType::Exists));
+
+        registry.record(&schema_op(SchemaCommand::DropIndex { label: "Person".into(), property: "age".into() }));
+        registry.record(&schema_op(SchemaCommand::DropConstraint { label: "Person".into(), property: "email".into() }));
+        assert!(!registry.has_index("Person", "age"));
+        assert_eq!(registry.constraints(), vec![("Person".into(), "name".into(), ConstraintType::Exists)]);
+
+        // DROP CONSTRAINT ON (n:Person) names no property
+        registry.record(&schema_op(SchemaCommand::DropConstraint { label: "Person".into(), property: String::new() }));
+        assert!(registry.constraints().is_empty());
+    }
+}
@@ -0,0 +1,1709 @@
+//! Bolt Storage Backend — neo4j-rs → an external Neo4j server
+//!
+//! Speaks Bolt 5.0 (or 4.4, whichever the server picks) over plain TCP.
+//! Every `StorageBackend` operation becomes a Cypher statement run inside
+//! the transaction's own connection; `stream` and `Graph::execute_stream`
+//! forward a whole query and fetch its records `fetch_size` at a time, so
+//! a large result is never buffered in full.
+//!
+//! ```text
+//! begin_tx       → connect, HELLO, BEGIN
+//! backend calls  → RUN "<cypher>" {params}, PULL {n: -1}
+//! stream         → RUN, then PULL {n: fetch_size} whenever the buffer runs dry,
+//!                  DISCARD {n: -1} if it is dropped before the end
+//! commit_tx      → COMMIT           rollback_tx → ROLLBACK
+//! ```
+//!
+//! A `FAILURE` resets the connection and fails the transaction: the server
+//! has already rolled it back, so `commit_tx` then errors and `rollback_tx`
+//! has nothing left to do.
+//!
+//! One connection per transaction; no pooling, routing or TLS (`bolt+s://`).
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveTime, Timelike, Utc};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::execution::ResultRow;
+use crate::model::*;
+use crate::model::value::IsoDuration;
+use crate::tx::{Transaction, TxMode, TxId};
+use crate::index::IndexType;
+use crate::storage::{
+    StorageBackend, ExpandDepth, ExpandLimits, ConstraintType, BackendCapabilities, ProcedureResult,
+    expansion_limit_exceeded,
+};
+use crate::{Error, Result};
+
+/// Records fetched per `PULL` unless `with_fetch_size` says otherwise; the
+/// official drivers' default.
+pub const DEFAULT_FETCH_SIZE: usize = 1000;
+
+const USER_AGENT: &str = concat!("neo4j-rs/", env!("CARGO_PKG_VERSION"));
+
+// =============================================================================
+// BOLT BACKEND
+// =============================================================================
+
+/// Storage backend forwarding to a Neo4j server over Bolt.
+pub struct BoltBackend {
+    /// `host:port`
+    addr: String,
+    user: String,
+    password: String,
+    database: Option<String>,
+    fetch_size: usize,
+    next_tx_id: AtomicU64,
+    expand_limits: ExpandLimits,
+}
+
+impl BoltBackend {
+    /// A backend for the server at `uri`: `bolt://host[:port]`, or
+    /// `neo4j://host[:port]` taken as a direct connection. No connection is
+    /// made until the first transaction.
+    pub fn new(uri: &str, user: impl Into<String>, password: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            addr: address_of(uri)?,
+            user: user.into(),
+            password: password.into(),
+            database: None,
+            fetch_size: DEFAULT_FETCH_SIZE,
+            next_tx_id: AtomicU64::new(1),
+            expand_limits: ExpandLimits::default(),
+        })
+    }
+
+    /// Run everything against `database` rather than the server's default.
+    pub fn with_database(mut self, database: impl Into<String>) -> Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// Fetch streamed records `fetch_size` at a time (at least one).
+    pub fn with_fetch_size(mut self, fetch_size: usize) -> Self {
+        self.fetch_size = fetch_size.max(1);
+        self
+    }
+
+    /// Override the depth and path limits used by `expand()`.
+    pub fn with_expand_limits(mut self, limits: ExpandLimits) -> Self {
+        self.expand_limits = limits;
+        self
+    }
+
+    /// Run `query` in `tx` and stream its records.
+    ///
+    /// The stream holds the transaction's connection: read it to the end,
+    /// `discard` it or drop it before using `tx` again. Records a dropped
+    /// stream didn't read are discarded on the server first.
+    pub async fn stream(&self, tx: &BoltTx, query: &str, params: PropertyMap) -> Result<RecordStream> {
+        let mut conn = Arc::clone(&tx.conn).lock_owned().await;
+        let fields = conn.run(query, params, HashMap::new()).await?;
+        Ok(RecordStream::new(conn, fields, self.fetch_size))
+    }
+
+    /// Run `query` in an auto-commit transaction of its own and stream its
+    /// records. The server commits once the last record has been pulled.
+    pub async fn stream_autocommit(&self, query: &str, params: PropertyMap, mode: TxMode) -> Result<RecordStream> {
+        let mut conn = self.connect().await?;
+        let fields = conn.run(query, params, self.tx_metadata(mode)).await?;
+        Ok(RecordStream::new(Arc::new(Mutex::new(conn)).lock_owned().await, fields, self.fetch_size))
+    }
+
+    /// A fresh, authenticated connection.
+    async fn connect(&self) -> Result<Connection> {
+        let mut conn = Connection::open(&self.addr).await?;
+        let auth = map([
+            ("user_agent", Value::String(USER_AGENT.into())),
+            ("scheme", Value::String("basic".into())),
+            ("principal", Value::String(self.user.clone())),
+            ("credentials", Value::String(self.password.clone())),
+        ]);
+        conn.request(HELLO, vec![Value::Map(auth)]).await?;
+        Ok(conn)
+    }
+
+    /// The `BEGIN` / auto-commit `RUN` metadata: database and access mode.
+    fn tx_metadata(&self, mode: TxMode) -> HashMap<String, Value> {
+        let mut extra = HashMap::new();
+        if let Some(db) = &self.database {
+            extra.insert("db".into(), Value::String(db.clone()));
+        }
+        if mode == TxMode::ReadOnly {
+            extra.insert("mode".into(), Value::String("r".into()));
+        }
+        extra
+    }
+
+    /// Run `cypher` in `tx` and pull all of its records.
+    async fn query(&self, tx: &BoltTx, cypher: &str, params: PropertyMap) -> Result<Vec<Vec<Value>>> {
+        let mut conn = tx.conn.lock().await;
+        conn.run(cypher, params, HashMap::new()).await?;
+        conn.pull_all().await
+    }
+
+    /// Run a schema command, which Neo4j won't mix with data writes, in an
+    /// auto-commit transaction of its own.
+    async fn schema_query(&self, cypher: &str) -> Result<Vec<Vec<Value>>> {
+        let mut conn = self.connect().await?;
+        conn.run(cypher, PropertyMap::new(), self.tx_metadata(TxMode::ReadWrite)).await?;
+        conn.pull_all().await
+    }
+
+    async fn nodes(&self, tx: &BoltTx, cypher: &str, params: PropertyMap) -> Result<Vec<Node>> {
+        self.query(tx, cypher, params).await?.into_iter().map(|row| node_of(first(row)?)).collect()
+    }
+
+    async fn relationships(&self, tx: &BoltTx, cypher: &str, params: PropertyMap) -> Result<Vec<Relationship>> {
+        self.query(tx, cypher, params).await?.into_iter().map(|row| relationship_of(first(row)?)).collect()
+    }
+
+    /// The integer in the first column of the first record; 0 if there
+    /// are no records.
+    async fn int_query(&self, tx: &BoltTx, cypher: &str, params: PropertyMap) -> Result<u64> {
+        let rows = self.query(tx, cypher, params).await?;
+        let int = rows.into_iter().next().map(first).transpose()?.map(|v| int_of(&v)).transpose()?;
+        Ok(int.unwrap_or(0).max(0) as u64)
+    }
+
+    async fn strings(&self, tx: &BoltTx, cypher: &str) -> Result<Vec<String>> {
+        self.query(tx, cypher, PropertyMap::new())
+            .await?
+            .into_iter()
+            .map(|row| match first(row)? {
+                Value::String(s) => Ok(s),
+                other => Err(unexpected("STRING", &other)),
+            })
+            .collect()
+    }
+
+    /// The server's `(name, label, property, type)` node indexes on a
+    /// single label and property, with constraint-backed ones as `Unique`.
+    async fn index_names(&self) -> Result<Vec<(String, String, String, IndexType)>> {
+        let rows = self
+            .schema_query(
+                "SHOW INDEXES YIELD name, type, entityType, labelsOrTypes, properties, owningConstraint \
+                 WHERE entityType = 'NODE' AND size(labelsOrTypes) = 1 AND size(properties) = 1 \
+                 RETURN name, type, labelsOrTypes[0], properties[0], owningConstraint IS NOT NULL",
+            )
+            .await?;
+        let mut indexes = Vec::new();
+        for row in rows {
+            let [Value::String(name), Value::String(kind), Value::String(label), Value::String(property), Value::Bool(owned)] =
+                <[Value; 5]>::try_from(row).map_err(|_| malformed("SHOW INDEXES row"))?
+            else {
+                return Err(malformed("SHOW INDEXES row"));
+            };
+            let index_type = match kind.as_str() {
+                _ if owned => IndexType::Unique,
+                "RANGE" | "BTREE" => IndexType::BTree,
+                "FULLTEXT" => IndexType::FullText,
+                "VECTOR" => IndexType::Vector,
+                _ => continue,
+            };
+            indexes.push((name, label, property, index_type));
+        }
+        Ok(indexes)
+    }
+
+    /// The server's `(name, label, property, type)` node constraints on a
+    /// single label and property.
+    async fn constraint_names(&self) -> Result<Vec<(String, String, String, ConstraintType)>> {
+        let rows = self
+            .schema_query(
+                "SHOW CONSTRAINTS YIELD name, type, entityType, labelsOrTypes, properties \
+                 WHERE entityType = 'NODE' AND size(labelsOrTypes) = 1 AND size(properties) = 1 \
+                 RETURN name, type, labelsOrTypes[0], properties[0]",
+            )
+            .await?;
+        let mut constraints = Vec::new();
+        for row in rows {
+            let [Value::String(name), Value::String(kind), Value::String(label), Value::String(property)] =
+                <[Value; 4]>::try_from(row).map_err(|_| malformed("SHOW CONSTRAINTS row"))?
+            else {
+                return Err(malformed("SHOW CONSTRAINTS row"));
+            };
+            let constraint_type = match kind.as_str() {
+                "UNIQUENESS" | "NODE_PROPERTY_UNIQUENESS" => ConstraintType::Unique,
+                "NODE_PROPERTY_EXISTENCE" => ConstraintType::Exists,
+                _ => continue,
+            };
+            constraints.push((name, label, property, constraint_type));
+        }
+        Ok(constraints)
+    }
+}
+
+/// `host:port` from a `bolt://` or `neo4j://` uri, or from `host[:port]`.
+fn address_of(uri: &str) -> Result<String> {
+    let rest = match uri.split_once("://") {
+        Some(("bolt" | "neo4j", rest)) => rest,
+        Some((scheme, _)) if scheme.contains('+') => {
+            return Err(Error::StorageError(format!("TLS is not supported: {uri}")));
+        }
+        Some((scheme, _)) => return Err(Error::StorageError(format!("unsupported uri scheme '{scheme}'"))),
+        None => uri,
+    };
+    let host = rest.split(['/', '?']).next().unwrap_or_default();
+    if host.is_empty() {
+        return Err(Error::StorageError(format!("no host in '{uri}'")));
+    }
+    let has_port = host.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    Ok(if has_port { host.to_string() } else { format!("{host}:7687") })
+}
+
+// =============================================================================
+// TRANSACTIONS
+// =============================================================================
+
+/// A transaction on its own Bolt connection.
+pub struct BoltTx {
+    id: TxId,
+    mode: TxMode,
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Transaction for BoltTx {
+    fn mode(&self) -> TxMode { self.mode }
+    fn id(&self) -> TxId { self.id }
+}
+
+// =============================================================================
+// RECORD STREAMING
+// =============================================================================
+
+/// The records of a query running on the server, pulled `fetch_size` at a
+/// time as they are read.
+///
+/// A `FAILURE` mid-stream is returned by `next`, after the records that
+/// came before it, and ends the stream; the server has rolled the
+/// transaction back by then. Dropping the stream
+/// early leaves the rest to be discarded before the connection is used
+/// again (or closed, for `stream_autocommit`).
+pub struct RecordStream {
+    conn: OwnedMutexGuard<Connection>,
+    fields: Vec<String>,
+    buffer: VecDeque<Vec<Value>>,
+    fetch_size: usize,
+    /// More records wait on the server.
+    has_more: bool,
+    /// The failure that ended the last batch, once its records are read.
+    failure: Option<Error>,
+}
+
+impl RecordStream {
+    fn new(conn: OwnedMutexGuard<Connection>, fields: Vec<String>, fetch_size: usize) -> Self {
+        Self { conn, fields, buffer: VecDeque::new(), fetch_size, has_more: true, failure: None }
+    }
+
+    /// The column names.
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// The next record, pulling another batch from the server when the
+    /// buffered ones run out; `None` at the end.
+    pub async fn next(&mut self) -> Result<Option<ResultRow>> {
+        if self.buffer.is_empty() && self.has_more {
+            let pulled = self.conn.pull(self.fetch_size as i64, &mut self.buffer).await;
+            self.has_more = matches!(pulled, Ok(true));
+            self.failure = pulled.err();
+        }
+        // Records that arrived before a failure come first
+        match self.buffer.pop_front() {
+            Some(values) => Ok(Some(ResultRow { values: self.fields.iter().cloned().zip(values).collect() })),
+            None => self.failure.take().map_or(Ok(None), Err),
+        }
+    }
+
+    /// Read the remaining records into a vector.
+    pub async fn collect(mut self) -> Result<Vec<ResultRow>> {
+        let mut rows = Vec::new();
+        while let Some(row) = self.next().await? {
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    /// Drop the records not yet read, on the server too, without fetching them.
+    pub async fn discard(mut self) -> Result<()> {
+        self.buffer.clear();
+        if self.has_more {
+            self.has_more = false;
+            self.conn.discard().await?;
+        }
+        Ok(())
+    }
+}
+
+/// A query's records handed out a page at a time; see `Graph::execute_paged`.
+///
+/// Each page is one `PULL` of `page_size` records, made when `next_page`
+/// asks for it, so memory holds a page rather than the whole result. The
+/// total row count isn't known until the last page has been read.
+pub struct PagedResult {
+    stream: RecordStream,
+    page_size: usize,
+}
+
+impl PagedResult {
+    pub(crate) fn new(mut stream: RecordStream, page_size: usize) -> Self {
+        stream.fetch_size = page_size;
+        Self { stream, page_size }
+    }
+
+    /// The column names.
+    pub fn columns(&self) -> &[String] {
+        self.stream.fields()
+    }
+
+    /// The next `page_size` records (fewer on the last page), or `None`
+    /// once every record has been returned.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<ResultRow>>> {
+        let mut page = Vec::with_capacity(self.page_size);
+        while page.len() < self.page_size {
+            match self.stream.next().await? {
+                Some(row) => page.push(row),
+                None => break,
+            }
+        }
+        Ok((!page.is_empty()).then_some(page))
+    }
+
+    /// Drop the pages not yet read, on the server too, without fetching them.
+    pub async fn discard(self) -> Result<()> {
+        self.stream.discard().await
+    }
+}
+
+// =============================================================================
+// CONNECTION
+// =============================================================================
+
+// Request signatures
+const HELLO: u8 = 0x01;
+const RESET: u8 = 0x0F;
+const RUN: u8 = 0x10;
+const BEGIN: u8 = 0x11;
+const COMMIT: u8 = 0x12;
+const ROLLBACK: u8 = 0x13;
+const DISCARD: u8 = 0x2F;
+const PULL: u8 = 0x3F;
+
+// Response signatures
+const SUCCESS: u8 = 0x70;
+const RECORD: u8 = 0x71;
+const IGNORED: u8 = 0x7E;
+const FAILURE: u8 = 0x7F;
+
+/// Bolt 5.0, then 4.4, as `[0, range, minor, major]`.
+const VERSIONS: [[u8; 4]; 4] = [[0, 0, 0, 5], [0, 0, 4, 4], [0; 4], [0; 4]];
+
+/// A server reply.
+enum Response {
+    Success(HashMap<String, Value>),
+    Record(Vec<Value>),
+    Ignored,
+    Failure { code: String, message: String },
+}
+
+/// One Bolt session, one request in flight at a time.
+struct Connection {
+    stream: TcpStream,
+    /// A `BEGIN` succeeded and no `COMMIT` or `ROLLBACK` followed yet.
+    in_tx: bool,
+    /// Why the server failed the open transaction.
+    failed: Option<String>,
+    /// Records of the last `RUN` are still on the server.
+    open_result: bool,
+}
+
+impl Connection {
+    async fn open(addr: &str) -> Result<Self> {
+        let mut stream = TcpStream::connect(addr).await?;
+        let mut handshake = vec![0x60, 0x60, 0xB0, 0x17];
+        handshake.extend(VERSIONS.concat());
+        stream.write_all(&handshake).await?;
+        let mut agreed = [0u8; 4];
+        stream.read_exact(&mut agreed).await?;
+        if agreed == [0; 4] {
+            return Err(Error::StorageError(format!("{addr} speaks neither Bolt 5.0 nor 4.4")));
+        }
+        Ok(Self { stream, in_tx: false, failed: None, open_result: false })
+    }
+
+    async fn send(&mut self, signature: u8, fields: Vec<Value>) -> Result<()> {
+        let message = pack_message(signature, &fields)?;
+        let mut framed = Vec::with_capacity(message.len() + 4);
+        for chunk in message.chunks(u16::MAX as usize) {
+            framed.extend((chunk.len() as u16).to_be_bytes());
+            framed.extend(chunk);
+        }
+        framed.extend([0, 0]);
+        self.stream.write_all(&framed).await?;
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Result<Response> {
+        let message = read_message(&mut self.stream).await?;
+        let (signature, mut fields) = unpack_message(&message)?;
+        let metadata = |fields: &mut Vec<Value>| match fields.pop() {
+            Some(Value::Map(map)) => Ok(map),
+            _ => Err(malformed("response metadata")),
+        };
+        Ok(match signature {
+            SUCCESS => Response::Success(metadata(&mut fields)?),
+            RECORD => match fields.pop() {
+                Some(Value::List(values)) => Response::Record(values),
+                _ => return Err(malformed("RECORD")),
+            },
+            IGNORED => Response::Ignored,
+            FAILURE => {
+                let mut meta = metadata(&mut fields)?;
+                let mut text = |key: &str| match meta.remove(key) {
+                    Some(Value::String(s)) => s,
+                    _ => String::new(),
+                };
+                Response::Failure { code: text("code"), message: text("message") }
+            }
+            other => return Err(malformed(&format!("response signature {other:#04x}"))),
+        })
+    }
+
+    /// Send a request that answers with a single `SUCCESS`.
+    async fn request(&mut self, signature: u8, fields: Vec<Value>) -> Result<HashMap<String, Value>> {
+        self.send(signature, fields).await?;
+        match self.recv().await? {
+            Response::Success(meta) => Ok(meta),
+            Response::Failure { code, message } => Err(self.fail(&code, &message).await),
+            Response::Ignored => Err(Error::StorageError("request ignored by the server".into())),
+            Response::Record(_) => Err(malformed("RECORD outside a result")),
+        }
+    }
+
+    /// Turn a `FAILURE` into an error, after resetting the connection so it
+    /// takes requests again. An open transaction is gone with it.
+    async fn fail(&mut self, code: &str, message: &str) -> Error {
+        self.open_result = false;
+        if self.in_tx {
+            self.in_tx = false;
+            self.failed = Some(message.to_string());
+        }
+        let error = server_error(code, message);
+        match self.request_reset().await {
+            Ok(()) => error,
+            Err(reset) => Error::StorageError(format!("{error}; then RESET failed: {reset}")),
+        }
+    }
+
+    async fn request_reset(&mut self) -> Result<()> {
+        self.send(RESET, Vec::new()).await?;
+        loop {
+            match self.recv().await? {
+                Response::Success(_) => return Ok(()),
+                Response::Ignored | Response::Record(_) => continue,
+                Response::Failure { message, .. } => return Err(Error::StorageError(message)),
+            }
+        }
+    }
+
+    /// Refuse to go on in a transaction the server has already failed:
+    /// statements would otherwise run outside of it.
+    fn check_open(&self) -> Result<()> {
+        match &self.failed {
+            Some(message) => Err(Error::TxError(format!("transaction failed: {message}"))),
+            None => Ok(()),
+        }
+    }
+
+    async fn begin(&mut self, extra: HashMap<String, Value>) -> Result<()> {
+        self.request(BEGIN, vec![Value::Map(extra)]).await?;
+        self.in_tx = true;
+        Ok(())
+    }
+
+    async fn commit(&mut self) -> Result<()> {
+        self.check_open()?;
+        self.finish_result().await?;
+        self.request(COMMIT, Vec::new()).await?;
+        self.in_tx = false;
+        Ok(())
+    }
+
+    async fn rollback(&mut self) -> Result<()> {
+        if self.failed.is_some() {
+            return Ok(());
+        }
+        self.finish_result().await?;
+        self.request(ROLLBACK, Vec::new()).await?;
+        self.in_tx = false;
+        Ok(())
+    }
+
+    /// Start `query`, returning its column names. The records wait on the
+    /// server for `pull` or `discard`.
+    async fn run(&mut self, query: &str, params: PropertyMap, extra: HashMap<String, Value>) -> Result<Vec<String>> {
+        self.check_open()?;
+        self.finish_result().await?;
+        let meta = self
+            .request(RUN, vec![Value::String(query.into()), Value::Map(params), Value::Map(extra)])
+            .await?;
+        self.open_result = true;
+        match meta.get("fields") {
+            Some(Value::List(fields)) => fields
+                .iter()
+                .map(|f| match f {
+                    Value::String(s) => Ok(s.clone()),
+                    other => Err(unexpected("STRING", other)),
+                })
+                .collect(),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Pull up to `n` records (`-1`: all) into `into`; whether more remain.
+    async fn pull(&mut self, n: i64, into: &mut VecDeque<Vec<Value>>) -> Result<bool> {
+        if !self.open_result {
+            return Ok(false);
+        }
+        self.send(PULL, vec![Value::Map(map([("n", Value::Int(n))]))]).await?;
+        loop {
+            match self.recv().await? {
+                Response::Record(values) => into.push_back(values),
+                Response::Success(meta) => {
+                    let has_more = matches!(meta.get("has_more"), Some(Value::Bool(true)));
+                    self.open_result = has_more;
+                    return Ok(has_more);
+                }
+                Response::Failure { code, message } => return Err(self.fail(&code, &message).await),
+                Response::Ignored => return Err(Error::StorageError("PULL ignored by the server".into())),
+            }
+        }
+    }
+
+    async fn pull_all(&mut self) -> Result<Vec<Vec<Value>>> {
+        let mut rows = VecDeque::new();
+        self.pull(-1, &mut rows).await?;
+        Ok(rows.into())
+    }
+
+    async fn discard(&mut self) -> Result<()> {
+        if !self.open_result {
+            return Ok(());
+        }
+        self.request(DISCARD, vec![Value::Map(map([("n", Value::Int(-1))]))]).await?;
+        self.open_result = false;
+        Ok(())
+    }
+
+    /// Discard what a dropped stream left on the server.
+    async fn finish_result(&mut self) -> Result<()> {
+        self.discard().await
+    }
+}
+
+/// Read one message, joining its chunks. Empty chunks between messages are
+/// keep-alives.
+async fn read_message(stream: &mut TcpStream) -> Result<Vec<u8>> {
+    let mut message = Vec::new();
+    loop {
+        let mut header = [0u8; 2];
+        stream.read_exact(&mut header).await?;
+        let len = u16::from_be_bytes(header) as usize;
+        if len == 0 {
+            if message.is_empty() {
+                continue;
+            }
+            return Ok(message);
+        }
+        let start = message.len();
+        message.resize(start + len, 0);
+        stream.read_exact(&mut message[start..]).await?;
+    }
+}
+
+/// The error for a server `FAILURE`.
+fn server_error(code: &str, message: &str) -> Error {
+    match code {
+        "Neo.ClientError.Schema.ConstraintValidationFailed" => Error::ConstraintViolation(message.to_string()),
+        "Neo.TransientError.Transaction.DeadlockDetected" | "Neo.TransientError.Transaction.Outdated" => {
+            Error::TxError("write conflict".into())
+        }
+        _ => Error::ExecutionError(format!("{message} ({code})")),
+    }
+}
+
+fn malformed(what: &str) -> Error {
+    Error::StorageError(format!("malformed Bolt message: {what}"))
+}
+
+fn unexpected(expected: &str, got: &Value) -> Error {
+    Error::TypeError { expected: expected.into(), got: got.type_name().into(), span: None }
+}
+
+// =============================================================================
+// PACKSTREAM
+// =============================================================================
+
+fn pack_message(signature: u8, fields: &[Value]) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    buf.extend([0xB0 | fields.len() as u8, signature]);
+    for field in fields {
+        pack(&mut buf, field)?;
+    }
+    Ok(buf)
+}
+
+/// Write `len` with the tiny marker `tiny` or the sized markers from `sized`.
+fn pack_len(buf: &mut Vec<u8>, len: usize, tiny: Option<u8>, sized: u8) -> Result<()> {
+    match len {
+        0..=15 if tiny.is_some() => buf.push(tiny.unwrap_or_default() | len as u8),
+        0..=0xFF => buf.extend([sized, len as u8]),
+        0x100..=0xFFFF => {
+            buf.push(sized + 1);
+            buf.extend((len as u16).to_be_bytes());
+        }
+        _ => {
+            let len = u32::try_from(len).map_err(|_| Error::StorageError("value too large for Bolt".into()))?;
+            buf.push(sized + 2);
+            buf.extend(len.to_be_bytes());
+        }
+    }
+    Ok(())
+}
+
+fn pack_struct(buf: &mut Vec<u8>, signature: u8, fields: &[Value]) -> Result<()> {
+    buf.extend([0xB0 | fields.len() as u8, signature]);
+    fields.iter().try_for_each(|f| pack(buf, f))
+}
+
+fn pack(buf: &mut Vec<u8>, value: &Value) -> Result<()> {
+    match value {
+        Value::Null => buf.push(0xC0),
+        Value::Bool(b) => buf.push(if *b { 0xC3 } else { 0xC2 }),
+        Value::Int(i) => match *i {
+            -16..=127 => buf.push(*i as u8),
+            -128..=-17 => buf.extend([0xC8, *i as u8]),
+            -32_768..=32_767 => {
+                buf.push(0xC9);
+                buf.extend((*i as i16).to_be_bytes());
+            }
+            -2_147_483_648..=2_147_483_647 => {
+                buf.push(0xCA);
+                buf.extend((*i as i32).to_be_bytes());
+            }
+            _ => {
+                buf.push(0xCB);
+                buf.extend(i.to_be_bytes());
+            }
+        },
+        Value::Float(f) => {
+            buf.push(0xC1);
+            buf.extend(f.to_be_bytes());
+        }
+        Value::String(s) => {
+            pack_len(buf, s.len(), Some(0x80), 0xD0)?;
+            buf.extend(s.as_bytes());
+        }
+        Value::Bytes(b) => {
+            pack_len(buf, b.len(), None, 0xCC)?;
+            buf.extend(b);
+        }
+        Value::List(items) => {
+            pack_len(buf, items.len(), Some(0x90), 0xD4)?;
+            items.iter().try_for_each(|v| pack(buf, v))?;
+        }
+        Value::Map(map) => {
+            pack_len(buf, map.len(), Some(0xA0), 0xD8)?;
+            for (k, v) in map {
+                pack(buf, &Value::String(k.clone()))?;
+                pack(buf, v)?;
+            }
+        }
+        Value::Date(d) => pack_struct(buf, b'D', &[Value::Int(days_since_epoch(*d))])?,
+        Value::Time(t) => pack_struct(buf, b't', &[Value::Int(nanos_of_day(*t))])?,
+        Value::LocalDateTime(dt) => {
+            let utc = dt.and_utc();
+            pack_struct(buf, b'd', &[Value::Int(utc.timestamp()), Value::Int(utc.timestamp_subsec_nanos() as i64)])?
+        }
+        Value::DateTime(dt) => pack_struct(
+            buf,
+            b'I',
+            &[Value::Int(dt.timestamp()), Value::Int(dt.timestamp_subsec_nanos() as i64), Value::Int(0)],
+        )?,
+        Value::Duration(d) => pack_struct(
+            buf,
+            b'E',
+            &[Value::Int(d.months), Value::Int(d.days), Value::Int(d.seconds), Value::Int(d.nanoseconds as i64)],
+        )?,
+        Value::Point2D { srid, x, y } => {
+            pack_struct(buf, b'X', &[Value::Int(*srid as i64), Value::Float(*x), Value::Float(*y)])?
+        }
+        Value::Point3D { srid, x, y, z } => pack_struct(
+            buf,
+            b'Y',
+            &[Value::Int(*srid as i64), Value::Float(*x), Value::Float(*y), Value::Float(*z)],
+        )?,
+        Value::Node(_) | Value::Relationship(_) | Value::Path(_) => {
+            return Err(unexpected("a parameter value", value));
+        }
+    }
+    Ok(())
+}
+
+/// The signature and fields of a message.
+fn unpack_message(bytes: &[u8]) -> Result<(u8, Vec<Value>)> {
+    let mut unpacker = Unpacker { buf: bytes, pos: 0 };
+    let marker = unpacker.byte()?;
+    if marker & 0xF0 != 0xB0 {
+        return Err(malformed("message is not a structure"));
+    }
+    let signature = unpacker.byte()?;
+    let fields = (0..marker & 0x0F).map(|_| unpacker.value()).collect::<Result<_>>()?;
+    Ok((signature, fields))
+}
+
+struct Unpacker<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Unpacker<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let bytes = self.buf.get(self.pos..self.pos + n).ok_or_else(|| malformed("truncated value"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap_or([0; N]))
+    }
+
+    /// A length following a sized marker; `width` is 0, 1 or 2 for u8, u16, u32.
+    fn len(&mut self, width: u8) -> Result<usize> {
+        Ok(match width {
+            0 => self.byte()? as usize,
+            1 => u16::from_be_bytes(self.array()?) as usize,
+            _ => u32::from_be_bytes(self.array()?) as usize,
+        })
+    }
+
+    fn string(&mut self, len: usize) -> Result<Value> {
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map(Value::String).map_err(|_| malformed("string is not UTF-8"))
+    }
+
+    fn list(&mut self, len: usize) -> Result<Value> {
+        (0..len).map(|_| self.value()).collect::<Result<_>>().map(Value::List)
+    }
+
+    fn map(&mut self, len: usize) -> Result<Value> {
+        let mut map = HashMap::with_capacity(len);
+        for _ in 0..len {
+            let Value::String(key) = self.value()? else {
+                return Err(malformed("map key is not a string"));
+            };
+            map.insert(key, self.value()?);
+        }
+        Ok(Value::Map(map))
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let marker = self.byte()?;
+        match marker {
+            0x00..=0x7F => Ok(Value::Int(marker as i64)),
+            0xF0..=0xFF => Ok(Value::Int(marker as i8 as i64)),
+            0x80..=0x8F => self.string((marker & 0x0F) as usize),
+            0x90..=0x9F => self.list((marker & 0x0F) as usize),
+            0xA0..=0xAF => self.map((marker & 0x0F) as usize),
+            0xB0..=0xBF => {
+                let signature = self.byte()?;
+                let fields = (0..marker & 0x0F).map(|_| self.value()).collect::<Result<_>>()?;
+                structure(signature, fields)
+            }
+            0xC0 => Ok(Value::Null),
+            0xC1 => Ok(Value::Float(f64::from_be_bytes(self.array()?))),
+            0xC2 => Ok(Value::Bool(false)),
+            0xC3 => Ok(Value::Bool(true)),
+            0xC8 => Ok(Value::Int(i8::from_be_bytes(self.array()?) as i64)),
+            0xC9 => Ok(Value::Int(i16::from_be_bytes(self.array()?) as i64)),
+            0xCA => Ok(Value::Int(i32::from_be_bytes(self.array()?) as i64)),
+            0xCB => Ok(Value::Int(i64::from_be_bytes(self.array()?))),
+            0xCC..=0xCE => {
+                let len = self.len(marker - 0xCC)?;
+                Ok(Value::Bytes(self.take(len)?.to_vec()))
+            }
+            0xD0..=0xD2 => {
+                let len = self.len(marker - 0xD0)?;
+                self.string(len)
+            }
+            0xD4..=0xD6 => {
+                let len = self.len(marker - 0xD4)?;
+                self.list(len)
+            }
+            0xD8..=0xDA => {
+                let len = self.len(marker - 0xD8)?;
+                self.map(len)
+            }
+            _ => Err(malformed(&format!("marker {marker:#04x}"))),
+        }
+    }
+}
+
+/// Decode a PackStream structure: graph entities, temporal and spatial
+/// values. Bolt 5 appends element ids to the graph entities; 4.4 doesn't.
+fn structure(signature: u8, fields: Vec<Value>) -> Result<Value> {
+    let mut fields = fields.into_iter();
+    let mut next = |what: &str| fields.next().ok_or_else(|| malformed(what));
+    let value = match signature {
+        b'N' => {
+            let mut node = Node::new(NodeId(int_of(&next("node id")?)? as u64));
+            node.labels = strings_of(next("node labels")?)?;
+            node.properties = map_of(next("node properties")?)?;
+            node.element_id = next("").ok().and_then(string_of);
+            Value::Node(Box::new(node))
+        }
+        b'R' => {
+            let id = RelId(int_of(&next("relationship id")?)? as u64);
+            let src = NodeId(int_of(&next("start node id")?)? as u64);
+            let dst = NodeId(int_of(&next("end node id")?)? as u64);
+            let rel_type = string_of(next("relationship type")?).ok_or_else(|| malformed("relationship type"))?;
+            let mut rel = Relationship::new(id, src, dst, rel_type);
+            rel.properties = map_of(next("relationship properties")?)?;
+            rel.element_id = next("").ok().and_then(string_of);
+            Value::Relationship(Box::new(rel))
+        }
+        // Unbound: only inside a path, which fills in the ends
+        b'r' => {
+            let id = RelId(int_of(&next("relationship id")?)? as u64);
+            let rel_type = string_of(next("relationship type")?).ok_or_else(|| malformed("relationship type"))?;
+            let mut rel = Relationship::new(id, NodeId(0), NodeId(0), rel_type);
+            rel.properties = map_of(next("relationship properties")?)?;
+            rel.element_id = next("").ok().and_then(string_of);
+            Value::Relationship(Box::new(rel))
+        }
+        b'P' => Value::Path(Box::new(path_of(next("path nodes")?, next("path relationships")?, next("path sequence")?)?)),
+        b'D' => {
+            let days = int_of(&next("date")?)?;
+            let date = chrono::TimeDelta::try_days(days).and_then(|d| NaiveDate::default().checked_add_signed(d));
+            Value::Date(date.ok_or_else(|| malformed("date"))?)
+        }
+        // Time with an offset: kept as the local time, the offset dropped
+        b't' | b'T' => Value::Time(time_of(int_of(&next("time")?)?)?),
+        b'd' => {
+            let (seconds, nanos) = (int_of(&next("seconds")?)?, int_of(&next("nanoseconds")?)?);
+            Value::LocalDateTime(utc(seconds, nanos)?.naive_utc())
+        }
+        // Bolt 5: seconds are UTC
+        b'I' | b'i' => Value::DateTime(utc(int_of(&next("seconds")?)?, int_of(&next("nanoseconds")?)?)?),
+        // Bolt 4.4: seconds are local, offset given
+        b'F' => {
+            let (seconds, nanos, offset) =
+                (int_of(&next("seconds")?)?, int_of(&next("nanoseconds")?)?, int_of(&next("offset")?)?);
+            Value::DateTime(utc(seconds - offset, nanos)?)
+        }
+        b'f' => return Err(Error::StorageError("Bolt 4.4 zone-id datetimes are not supported".into())),
+        b'E' => Value::Duration(IsoDuration {
+            months: int_of(&next("months")?)?,
+            days: int_of(&next("days")?)?,
+            seconds: int_of(&next("seconds")?)?,
+            nanoseconds: int_of(&next("nanoseconds")?)? as i32,
+        }),
+        b'X' => Value::Point2D {
+            srid: int_of(&next("srid")?)? as i32,
+            x: float_of(&next("x")?)?,
+            y: float_of(&next("y")?)?,
+        },
+        b'Y' => Value::Point3D {
+            srid: int_of(&next("srid")?)? as i32,
+            x: float_of(&next("x")?)?,
+            y: float_of(&next("y")?)?,
+            z: float_of(&next("z")?)?,
+        },
+        other => return Err(malformed(&format!("structure signature {other:#04x}"))),
+    };
+    Ok(value)
+}
+
+/// Assemble a path from its distinct nodes, its unbound relationships and
+/// the sequence of `(relationship, node)` indices walking it. A negative
+/// relationship index walks that relationship backwards.
+fn path_of(nodes: Value, rels: Value, sequence: Value) -> Result<Path> {
+    let nodes: Vec<Node> = list_of(nodes)?.into_iter().map(node_of).collect::<Result<_>>()?;
+    let rels: Vec<Relationship> = list_of(rels)?.into_iter().map(relationship_of).collect::<Result<_>>()?;
+    let sequence: Vec<i64> = list_of(sequence)?.iter().map(int_of).collect::<Result<_>>()?;
+    let mut path = Path::single(nodes.first().cloned().ok_or_else(|| malformed("empty path"))?);
+    for step in sequence.chunks(2) {
+        let &[rel_index, node_index] = step else {
+            return Err(malformed("path sequence"));
+        };
+        let mut rel = rels
+            .get(rel_index.unsigned_abs() as usize - 1)
+            .cloned()
+            .ok_or_else(|| malformed("path relationship index"))?;
+        let node = nodes.get(node_index as usize).cloned().ok_or_else(|| malformed("path node index"))?;
+        let prev = path.nodes.last().map_or(NodeId(0), |n| n.id);
+        (rel.src, rel.dst) = if rel_index > 0 { (prev, node.id) } else { (node.id, prev) };
+        path.relationships.push(rel);
+        path.nodes.push(node);
+    }
+    Ok(path)
+}
+
+fn days_since_epoch(date: NaiveDate) -> i64 {
+    (date - NaiveDate::default()).num_days()
+}
+
+fn nanos_of_day(time: NaiveTime) -> i64 {
+    time.num_seconds_from_midnight() as i64 * 1_000_000_000 + time.nanosecond() as i64
+}
+
+fn time_of(nanos: i64) -> Result<NaiveTime> {
+    let seconds = u32::try_from(nanos.div_euclid(1_000_000_000)).map_err(|_| malformed("time"))?;
+    NaiveTime::from_num_seconds_from_midnight_opt(seconds, nanos.rem_euclid(1_000_000_000) as u32)
+        .ok_or_else(|| malformed("time"))
+}
+
+fn utc(seconds: i64, nanos: i64) -> Result<DateTime<Utc>> {
+    let nanos = u32::try_from(nanos).map_err(|_| malformed("datetime"))?;
+    DateTime::from_timestamp(seconds, nanos).ok_or_else(|| malformed("datetime"))
+}
+
+fn int_of(value: &Value) -> Result<i64> {
+    match value {
+        Value::Int(i) => Ok(*i),
+        other => Err(unexpected("INTEGER", other)),
+    }
+}
+
+fn float_of(value: &Value) -> Result<f64> {
+    match value {
+        Value::Float(f) => Ok(*f),
+        other => Err(unexpected("FLOAT", other)),
+    }
+}
+
+fn string_of(value: Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn list_of(value: Value) -> Result<Vec<Value>> {
+    match value {
+        Value::List(items) => Ok(items),
+        other => Err(unexpected("LIST", &other)),
+    }
+}
+
+fn strings_of(value: Value) -> Result<Vec<String>> {
+    list_of(value)?.into_iter().map(|v| string_of(v).ok_or_else(|| malformed("label"))).collect()
+}
+
+fn map_of(value: Value) -> Result<PropertyMap> {
+    match value {
+        Value::Map(map) => Ok(map),
+        other => Err(unexpected("MAP", &other)),
+    }
+}
+
+fn node_of(value: Value) -> Result<Node> {
+    match value {
+        Value::Node(node) => Ok(*node),
+        other => Err(unexpected("NODE", &other)),
+    }
+}
+
+fn relationship_of(value: Value) -> Result<Relationship> {
+    match value {
+        Value::Relationship(rel) => Ok(*rel),
+        other => Err(unexpected("RELATIONSHIP", &other)),
+    }
+}
+
+fn first(row: Vec<Value>) -> Result<Value> {
+    row.into_iter().next().ok_or_else(|| malformed("empty record"))
+}
+
+fn map<const N: usize>(entries: [(&str, Value); N]) -> HashMap<String, Value> {
+    entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+}
+
+/// A label, type or property name as a Cypher identifier.
+fn quote(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// `-[r]->`, `<-[r]-` or `-[r]-` with `pattern` inside the brackets.
+fn arrow(dir: Direction, pattern: &str) -> String {
+    match dir {
+        Direction::Outgoing => format!("-[{pattern}]->"),
+        Direction::Incoming => format!("<-[{pattern}]-"),
+        Direction::Both => format!("-[{pattern}]-"),
+    }
+}
+
+/// `:A|B` for `rel_types`, or nothing for any type.
+fn type_filter(rel_types: &[&str]) -> String {
+    if rel_types.is_empty() {
+        return String::new();
+    }
+    format!(":{}", rel_types.iter().map(|t| quote(t)).collect::<Vec<_>>().join("|"))
+}
+
+fn id_param(id: u64) -> PropertyMap {
+    map([("id", Value::Int(id as i64))])
+}
+
+// =============================================================================
+// STORAGE BACKEND
+// =============================================================================
+
+#[async_trait]
+impl StorageBackend for BoltBackend {
+    type Tx = BoltTx;
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn begin_tx(&self, mode: TxMode) -> Result<BoltTx> {
+        let mut conn = self.connect().await?;
+        conn.begin(self.tx_metadata(mode)).await?;
+        let id = TxId(self.next_tx_id.fetch_add(1, Ordering::Relaxed));
+        Ok(BoltTx { id, mode, conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    async fn commit_tx(&self, tx: BoltTx) -> Result<()> {
+        tx.conn.lock().await.commit().await
+    }
+
+    async fn rollback_tx(&self, tx: BoltTx) -> Result<()> {
+        tx.conn.lock().await.rollback().await
+    }
+
+    // ========================================================================
+    // Node CRUD
+    // ========================================================================
+
+    async fn create_node(&self, tx: &mut BoltTx, labels: &[&str], props: PropertyMap) -> Result<NodeId> {
+        let labels: String = labels.iter().map(|l| format!(":{}", quote(l))).collect();
+        let cypher = format!("CREATE (n{labels}) SET n = $props RETURN id(n)");
+        let id = self.int_query(tx, &cypher, map([("props", Value::Map(props))])).await?;
+        Ok(NodeId(id))
+    }
+
+    async fn get_node(&self, tx: &BoltTx, id: NodeId) -> Result<Option<Node>> {
+        let nodes = self.nodes(tx, "MATCH (n) WHERE id(n) = $id RETURN n", id_param(id.0)).await?;
+        Ok(nodes.into_iter().next())
+    }
+
+    async fn delete_node(&self, tx: &mut BoltTx, id: NodeId) -> Result<bool> {
+        let deleted = self.int_query(tx, "MATCH (n) WHERE id(n) = $id DELETE n RETURN count(*)", id_param(id.0)).await?;
+        Ok(deleted > 0)
+    }
+
+    async fn detach_delete_node(&self, tx: &mut BoltTx, id: NodeId) -> Result<bool> {
+        let cypher = "MATCH (n) WHERE id(n) = $id DETACH DELETE n RETURN count(*)";
+        Ok(self.int_query(tx, cypher, id_param(id.0)).await? > 0)
+    }
+
+    async fn set_node_property(&self, tx: &mut BoltTx, id: NodeId, key: &str, val: Value) -> Result<()> {
+        self.set_node_properties(tx, id, map([(key, val)])).await
+    }
+
+    /// One `SET n += $props`; null values remove their properties.
+    async fn set_node_properties(&self, tx: &mut BoltTx, id: NodeId, props: PropertyMap) -> Result<()> {
+        let mut params = id_param(id.0);
+        params.insert("props".into(), Value::Map(props));
+        self.query(tx, "MATCH (n) WHERE id(n) = $id SET n += $props", params).await?;
+        Ok(())
+    }
+
+    async fn remove_node_property(&self, tx: &mut BoltTx, id: NodeId, key: &str) -> Result<()> {
+        self.set_node_properties(tx, id, map([(key, Value::Null)])).await
+    }
+
+    async fn add_label(&self, tx: &mut BoltTx, id: NodeId, label: &str) -> Result<()> {
+        let cypher = format!("MATCH (n) WHERE id(n) = $id SET n:{}", quote(label));
+        self.query(tx, &cypher, id_param(id.0)).await?;
+        Ok(())
+    }
+
+    async fn remove_label(&self, tx: &mut BoltTx, id: NodeId, label: &str) -> Result<()> {
+        let cypher = format!("MATCH (n) WHERE id(n) = $id REMOVE n:{}", quote(label));
+        self.query(tx, &cypher, id_param(id.0)).await?;
+        Ok(())
+    }
+
+    // ========================================================================
+    // Relationship CRUD
+    // ========================================================================
+
+    async fn create_relationship(
+        &self,
+        tx: &mut BoltTx,
+        src: NodeId,
+        dst: NodeId,
+        rel_type: &str,
+        props: PropertyMap,
+    ) -> Result<RelId> {
+        let cypher = format!(
+            "MATCH (a), (b) WHERE id(a) = $src AND id(b) = $dst \
+             CREATE (a)-[r:{}]->(b) SET r = $props RETURN id(r)",
+            quote(rel_type)
+        );
+        let params = map([
+            ("src", Value::Int(src.0 as i64)),
+            ("dst", Value::Int(dst.0 as i64)),
+            ("props", Value::Map(props)),
+        ]);
+        let rows = self.query(tx, &cypher, params).await?;
+        match rows.into_iter().next() {
+            Some(row) => Ok(RelId(int_of(&first(row)?)? as u64)),
+            None => Err(Error::NotFound(format!("node {src} or {dst}"))),
+        }
+    }
+
+    async fn get_relationship(&self, tx: &BoltTx, id: RelId) -> Result<Option<Relationship>> {
+        let rels = self.relationships(tx, "MATCH ()-[r]->() WHERE id(r) = $id RETURN r", id_param(id.0)).await?;
+        Ok(rels.into_iter().next())
+    }
+
+    async fn delete_relationship(&self, tx: &mut BoltTx, id: RelId) -> Result<bool> {
+        let cypher = "MATCH ()-[r]->() WHERE id(r) = $id DELETE r RETURN count(*)";
+        Ok(self.int_query(tx, cypher, id_param(id.0)).await? > 0)
+    }
+
+    async fn set_relationship_property(&self, tx: &mut BoltTx, id: RelId, key: &str, val: Value) -> Result<()> {
+        let mut params = id_param(id.0);
+        params.insert("props".into(), Value::Map(map([(key, val)])));
+        self.query(tx, "MATCH ()-[r]->() WHERE id(r) = $id SET r += $props", params).await?;
+        Ok(())
+    }
+
+    async fn remove_relationship_property(&self, tx: &mut BoltTx, id: RelId, key: &str) -> Result<()> {
+        self.set_relationship_property(tx, id, key, Value::Null).await
+    }
+
+    // ========================================================================
+    // Traversal
+    // ========================================================================
+
+    async fn get_relationships(
+        &self,
+        tx: &BoltTx,
+        node: NodeId,
+        dir: Direction,
+        rel_type: Option<&str>,
+    ) -> Result<Vec<Relationship>> {
+        self.get_relationships_typed(tx, node, dir, rel_type.as_slice()).await
+    }
+
+    /// One query, the types matched by the server.
+    async fn get_relationships_typed(
+        &self,
+        tx: &BoltTx,
+        node: NodeId,
+        dir: Direction,
+        rel_types: &[&str],
+    ) -> Result<Vec<Relationship>> {
+        let pattern = arrow(dir, &format!("r{}", type_filter(rel_types)));
+        let cypher = format!("MATCH (n){pattern}() WHERE id(n) = $id RETURN DISTINCT r ORDER BY id(r)");
+        self.relationships(tx, &cypher, id_param(node.0)).await
+    }
+
+    async fn expand(
+        &self,
+        tx: &BoltTx,
+        node: NodeId,
+        dir: Direction,
+        rel_types: &[&str],
+        depth: ExpandDepth,
+    ) -> Result<Vec<Path>> {
+        let (min, max) = self.expand_limits.resolve(depth)?;
+        let pattern = arrow(dir, &format!("{}*{min}..{max}", type_filter(rel_types)));
+        let cypher = format!("MATCH p = (n){pattern}() WHERE id(n) = $id RETURN p LIMIT $limit");
+        let mut params = id_param(node.0);
+        params.insert("limit".into(), Value::Int(self.expand_limits.max_paths as i64 + 1));
+        let rows = self.query(tx, &cypher, params).await?;
+        if rows.len() > self.expand_limits.max_paths {
+            return Err(expansion_limit_exceeded());
+        }
+        rows.into_iter()
+            .map(|row| match first(row)? {
+                Value::Path(path) => Ok(*path),
+                other => Err(unexpected("PATH", &other)),
+            })
+            .collect()
+    }
+
+    fn expand_limits(&self) -> ExpandLimits {
+        self.expand_limits
+    }
+
+    // ========================================================================
+    // Index
+    // ========================================================================
+
+    async fn create_index(&self, label: &str, property: &str, index_type: IndexType) -> Result<()> {
+        let (label, property) = (quote(label), quote(property));
+        let cypher = match index_type {
+            IndexType::BTree => format!("CREATE INDEX IF NOT EXISTS FOR (n:{label}) ON (n.{property})"),
+            IndexType::Unique => {
+                format!("CREATE CONSTRAINT IF NOT EXISTS FOR (n:{label}) REQUIRE n.{property} IS UNIQUE")
+            }
+            IndexType::FullText => {
+                format!("CREATE FULLTEXT INDEX IF NOT EXISTS FOR (n:{label}) ON EACH [n.{property}]")
+            }
+            IndexType::Vector => format!("CREATE VECTOR INDEX IF NOT EXISTS FOR (n:{label}) ON (n.{property})"),
+        };
+        self.schema_query(&cypher).await?;
+        Ok(())
+    }
+
+    /// Drops every index on `label` and `property`, and the constraint
+    /// behind a unique one.
+    async fn drop_index(&self, label: &str, property: &str) -> Result<()> {
+        for (name, l, p, index_type) in self.index_names().await? {
+            if l == label && p == property {
+                let kind = if index_type == IndexType::Unique { "CONSTRAINT" } else { "INDEX" };
+                self.schema_query(&format!("DROP {kind} {} IF EXISTS", quote(&name))).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn indexes(&self) -> Result<Vec<(String, String, IndexType)>> {
+        let mut indexes: Vec<_> = self.index_names().await?.into_iter().map(|(_, l, p, t)| (l, p, t)).collect();
+        indexes.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        Ok(indexes)
+    }
+
+    // ========================================================================
+    // Schema introspection
+    // ========================================================================
+
+    async fn node_count(&self, tx: &BoltTx) -> Result<u64> {
+        self.int_query(tx, "MATCH (n) RETURN count(n)", PropertyMap::new()).await
+    }
+
+    async fn relationship_count(&self, tx: &BoltTx) -> Result<u64> {
+        self.int_query(tx, "MATCH ()-[r]->() RETURN count(r)", PropertyMap::new()).await
+    }
+
+    async fn labels(&self, tx: &BoltTx) -> Result<Vec<String>> {
+        self.strings(tx, "CALL db.labels() YIELD label RETURN label ORDER BY label").await
+    }
+
+    async fn label_count(&self, tx: &BoltTx, label: &str) -> Result<u64> {
+        self.int_query(tx, &format!("MATCH (n:{}) RETURN count(n)", quote(label)), PropertyMap::new()).await
+    }
+
+    async fn relationship_count_by_type(&self, tx: &BoltTx, rel_type: &str) -> Result<u64> {
+        self.int_query(tx, &format!("MATCH ()-[r:{}]->() RETURN count(r)", quote(rel_type)), PropertyMap::new()).await
+    }
+
+    async fn relationship_types(&self, tx: &BoltTx) -> Result<Vec<String>> {
+        self.strings(tx, "CALL db.relationshipTypes() YIELD relationshipType AS t RETURN t ORDER BY t").await
+    }
+
+    // ========================================================================
+    // Scan
+    // ========================================================================
+
+    async fn all_nodes(&self, tx: &BoltTx) -> Result<Vec<Node>> {
+        self.nodes(tx, "MATCH (n) RETURN n ORDER BY id(n)", PropertyMap::new()).await
+    }
+
+    async fn nodes_by_label(&self, tx: &BoltTx, label: &str) -> Result<Vec<Node>> {
+        self.nodes(tx, &format!("MATCH (n:{}) RETURN n ORDER BY id(n)", quote(label)), PropertyMap::new()).await
+    }
+
+    async fn nodes_by_labels(&self, tx: &BoltTx, labels: &[&str]) -> Result<Vec<Node>> {
+        let labels: String = labels.iter().map(|l| format!(":{}", quote(l))).collect();
+        self.nodes(tx, &format!("MATCH (n{labels}) RETURN n ORDER BY id(n)"), PropertyMap::new()).await
+    }
+
+    async fn nodes_by_property(&self, tx: &BoltTx, label: &str, key: &str, value: &Value) -> Result<Vec<Node>> {
+        let cypher = format!("MATCH (n:{}) WHERE n.{} = $value RETURN n ORDER BY id(n)", quote(label), quote(key));
+        self.nodes(tx, &cypher, map([("value", value.clone())])).await
+    }
+
+    async fn relationships_by_type(&self, tx: &BoltTx, rel_type: &str) -> Result<Vec<Relationship>> {
+        let cypher = format!("MATCH ()-[r:{}]->() RETURN r ORDER BY id(r)", quote(rel_type));
+        self.relationships(tx, &cypher, PropertyMap::new()).await
+    }
+
+    // ========================================================================
+    // Constraints
+    // ========================================================================
+
+    async fn create_constraint(&self, label: &str, property: &str, constraint_type: ConstraintType) -> Result<()> {
+        let requirement = match constraint_type {
+            ConstraintType::Unique => "IS UNIQUE",
+            ConstraintType::Exists => "IS NOT NULL",
+        };
+        let cypher = format!(
+            "CREATE CONSTRAINT IF NOT EXISTS FOR (n:{}) REQUIRE n.{} {requirement}",
+            quote(label),
+            quote(property)
+        );
+        self.schema_query(&cypher).await?;
+        Ok(())
+    }
+
+    async fn drop_constraint(&self, label: &str, property: &str) -> Result<()> {
+        for (name, l, p, _) in self.constraint_names().await? {
+            if l == label && p == property {
+                self.schema_query(&format!("DROP CONSTRAINT {} IF EXISTS", quote(&name))).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn constraints(&self) -> Result<Vec<(String, String, ConstraintType)>> {
+        let mut constraints: Vec<_> =
+            self.constraint_names().await?.into_iter().map(|(_, l, p, t)| (l, p, t)).collect();
+        constraints.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        Ok(constraints)
+    }
+
+    // ========================================================================
+    // Escape hatches
+    // ========================================================================
+
+    /// Runs `query` on the server in `tx`, buffering its records.
+    async fn execute_raw(&self, tx: &BoltTx, query: &str, params: PropertyMap) -> Result<ProcedureResult> {
+        let mut conn = tx.conn.lock().await;
+        let columns = conn.run(query, params, HashMap::new()).await?;
+        let rows = conn
+            .pull_all()
+            .await?
+            .into_iter()
+            .map(|values| columns.iter().cloned().zip(values).collect())
+            .collect();
+        Ok(ProcedureResult { columns, rows })
+    }
+
+    /// Calls the server's procedure `name`.
+    async fn call_procedure(&self, tx: &mut BoltTx, name: &str, args: Vec<Value>) -> Result<ProcedureResult> {
+        let name = name.split('.').map(quote).collect::<Vec<_>>().join(".");
+        let placeholders = (0..args.len()).map(|i| format!("$a{i}")).collect::<Vec<_>>().join(", ");
+        let params = args.into_iter().enumerate().map(|(i, v)| (format!("a{i}"), v)).collect();
+        self.execute_raw(tx, &format!("CALL {name}({placeholders})"), params).await
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            supports_fulltext_index: true,
+            supports_procedures: true,
+            supports_rollback: true,
+            supports_constraints: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_packstream_round_trip() {
+        let values = vec![
+            Value::Null,
+            Value::Bool(true),
+            Value::Int(-16),
+            Value::Int(-17),
+            Value::Int(200),
+            Value::Int(-40_000),
+            Value::Int(i64::MIN),
+            Value::Float(1.5),
+            Value::String("é".repeat(20)),
+            Value::Bytes(vec![1, 2, 3]),
+            Value::List((0..20).map(Value::Int).collect()),
+            Value::Map(map([("a", Value::Int(1))])),
+            Value::Date(NaiveDate::from_ymd_opt(1969, 7, 20).unwrap()),
+            Value::Time(NaiveTime::from_hms_nano_opt(20, 17, 40, 5).unwrap()),
+            Value::DateTime(utc(1_700_000_000, 7).unwrap()),
+            Value::LocalDateTime(utc(-1, 0).unwrap().naive_utc()),
+            Value::Duration(IsoDuration { months: 1, days: -2, seconds: 3, nanoseconds: 4 }),
+            Value::Point3D { srid: 9157, x: 1.0, y: 2.0, z: 3.0 },
+        ];
+        let message = pack_message(RECORD, &[Value::List(values.clone())]).unwrap();
+        let (signature, fields) = unpack_message(&message).unwrap();
+        assert_eq!(signature, RECORD);
+        assert_eq!(fields, vec![Value::List(values)]);
+    }
+
+    #[test]
+    fn test_path_walks_relationships_both_ways() {
+        let node = |id: i64| Value::Node(Box::new(Node::new(NodeId(id as u64))));
+        let rel = |id: u64| Value::Relationship(Box::new(Relationship::new(RelId(id), NodeId(0), NodeId(0), "R")));
+        // (1)-[10]->(2)<-[11]-(3)
+        let path = path_of(
+            Value::List(vec![node(1), node(2), node(3)]),
+            Value::List(vec![rel(10), rel(11)]),
+            Value::List(vec![Value::Int(1), Value::Int(1), Value::Int(-2), Value::Int(2)]),
+        )
+        .unwrap();
+        let ends: Vec<_> = path.relationships.iter().map(|r| (r.id.0, r.src.0, r.dst.0)).collect();
+        assert_eq!(ends, vec![(10, 1, 2), (11, 3, 2)]);
+        assert_eq!(path.nodes.iter().map(|n| n.id.0).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_addresses() {
+        assert_eq!(address_of("bolt://db.example:7688").unwrap(), "db.example:7688");
+        assert_eq!(address_of("neo4j://localhost").unwrap(), "localhost:7687");
+        assert_eq!(address_of("localhost").unwrap(), "localhost:7687");
+        assert!(address_of("bolt+s://localhost").is_err());
+        assert!(address_of("http://localhost").is_err());
+    }
+
+    /// Requests received: signature and first field.
+    type Log = Arc<parking_lot::Mutex<Vec<(u8, Option<Value>)>>>;
+
+    /// A scripted Bolt server: `records` rows of one column `i` per `RUN`,
+    /// failing the `PULL` that would send record `fail_at`, if set. Logs
+    /// every request as its signature and first field.
+    struct MockServer {
+        addr: String,
+        log: Log,
+    }
+
+    impl MockServer {
+        async fn start(records: i64, fail_at: Option<i64>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+            let log = Arc::new(parking_lot::Mutex::new(Vec::new()));
+            let server_log = Arc::clone(&log);
+            tokio::spawn(async move {
+                loop {
+                    let Ok((socket, _)) = listener.accept().await else { return };
+                    tokio::spawn(Self::serve(socket, records, fail_at, Arc::clone(&server_log)));
+                }
+            });
+            Self { addr, log }
+        }
+
+        async fn serve(
+            mut socket: TcpStream,
+            records: i64,
+            fail_at: Option<i64>,
+            log: Log,
+        ) {
+            let mut handshake = [0u8; 20];
+            if socket.read_exact(&mut handshake).await.is_err() {
+                return;
+            }
+            socket.write_all(&[0, 0, 0, 5]).await.unwrap();
+            let (mut sent, mut failed) = (0, false);
+            while let Ok(message) = read_message(&mut socket).await {
+                let (signature, fields) = unpack_message(&message).unwrap();
+                log.lock().push((signature, fields.first().cloned()));
+                let reply = |signature: u8, meta: HashMap<String, Value>| (signature, vec![Value::Map(meta)]);
+                let mut replies = Vec::new();
+                match signature {
+                    _ if failed && signature != RESET => replies.push((IGNORED, Vec::new())),
+                    RESET => {
+                        failed = false;
+                        replies.push(reply(SUCCESS, HashMap::new()));
+                    }
+                    RUN => {
+                        sent = 0;
+                        replies.push(reply(SUCCESS, map([("fields", Value::List(vec![Value::String("i".into())]))])));
+                    }
+                    PULL => {
+                        let Value::Map(extra) = &fields[0] else { panic!("PULL without extra") };
+                        let n = int_of(&extra["n"]).unwrap();
+                        let end = if n < 0 { records } else { (sent + n).min(records) };
+                        while sent < end {
+                            if Some(sent) == fail_at {
+                                failed = true;
+                                break;
+                            }
+                            replies.push((RECORD, vec![Value::List(vec![Value::Int(sent)])]));
+                            sent += 1;
+                        }
+                        replies.push(if failed {
+                            let error = [
+                                ("code", Value::String("Neo.ClientError.Statement.ArithmeticError".into())),
+                                ("message", Value::String("/ by zero".into())),
+                            ];
+                            reply(FAILURE, map(error))
+                        } else {
+                            reply(SUCCESS, map([("has_more", Value::Bool(sent < records))]))
+                        });
+                    }
+                    DISCARD => {
+                        sent = records;
+                        replies.push(reply(SUCCESS, HashMap::new()));
+                    }
+                    _ => replies.push(reply(SUCCESS, HashMap::new())),
+                }
+                for (signature, fields) in replies {
+                    let mut framed = Vec::new();
+                    let body = pack_message(signature, &fields).unwrap();
+                    framed.extend((body.len() as u16).to_be_bytes());
+                    framed.extend(body);
+                    framed.extend([0, 0]);
+                    socket.write_all(&framed).await.unwrap();
+                }
+            }
+        }
+
+        /// The requests so far, as signatures, with each `PULL`'s `n`.
+        fn requests(&self) -> Vec<(u8, Option<i64>)> {
+            self.log
+                .lock()
+                .iter()
+                .map(|(signature, first)| {
+                    let n = match first {
+                        Some(Value::Map(m)) if *signature == PULL => m.get("n").and_then(|n| int_of(n).ok()),
+                        _ => None,
+                    };
+                    (*signature, n)
+                })
+                .collect()
+        }
+
+        fn backend(&self, fetch_size: usize) -> BoltBackend {
+            BoltBackend::new(&format!("bolt://{}", self.addr), "neo4j", "secret").unwrap().with_fetch_size(fetch_size)
+        }
+    }
+
+    fn ints(rows: &[ResultRow]) -> Vec<i64> {
+        rows.iter().map(|r| r.get::<i64>("i").unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_pulls_in_batches() {
+        let server = MockServer::start(10, None).await;
+        let backend = server.backend(4);
+        let tx = backend.begin_tx(TxMode::ReadOnly).await.unwrap();
+
+        let mut stream = backend.stream(&tx, "UNWIND range(0, 9) AS i RETURN i", PropertyMap::new()).await.unwrap();
+        assert_eq!(stream.fields(), ["i"]);
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.get::<i64>("i").unwrap(), 0);
+        // One batch fetched so far, not the whole result
+        assert_eq!(server.requests().last(), Some(&(PULL, Some(4))));
+        let rest = stream.collect().await.unwrap();
+        assert_eq!(ints(&rest), (1..10).collect::<Vec<_>>());
+        backend.commit_tx(tx).await.unwrap();
+
+        assert_eq!(
+            server.requests(),
+            vec![(HELLO, None), (BEGIN, None), (RUN, None), (PULL, Some(4)), (PULL, Some(4)), (PULL, Some(4)), (COMMIT, None)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unread_records_are_discarded() {
+        let server = MockServer::start(10, None).await;
+        let backend = server.backend(3);
+        let mut tx = backend.begin_tx(TxMode::ReadWrite).await.unwrap();
+
+        // Dropped after one record: the rest go before the next statement
+        let mut stream = backend.stream(&tx, "RETURN 1", PropertyMap::new()).await.unwrap();
+        stream.next().await.unwrap();
+        drop(stream);
+        backend.set_node_property(&mut tx, NodeId(1), "x", Value::Int(1)).await.unwrap();
+
+        // Discarded explicitly: nothing more is pulled
+        let stream = backend.stream(&tx, "RETURN 1", PropertyMap::new()).await.unwrap();
+        stream.discard().await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+
+        let signatures: Vec<u8> = server.requests().into_iter().map(|(s, _)| s).collect();
+        assert_eq!(signatures, vec![HELLO, BEGIN, RUN, PULL, DISCARD, RUN, PULL, RUN, DISCARD, COMMIT]);
+    }
+
+    #[tokio::test]
+    async fn test_failure_mid_stream_fails_the_transaction() {
+        let server = MockServer::start(10, Some(5)).await;
+        let backend = server.backend(4);
+        let tx = backend.begin_tx(TxMode::ReadWrite).await.unwrap();
+
+        let mut stream = backend.stream(&tx, "UNWIND range(0, 9) AS i RETURN 1 / (5 - i)", PropertyMap::new()).await.unwrap();
+        let mut read = Vec::new();
+        let err = loop {
+            match stream.next().await {
+                Ok(Some(row)) => read.push(row),
+                Ok(None) => panic!("stream ended without the failure"),
+                Err(err) => break err,
+            }
+        };
+        assert!(err.to_string().contains("/ by zero"), "{err}");
+        // The first batch, and the record the second sent before failing
+        assert_eq!(ints(&read), vec![0, 1, 2, 3, 4]);
+        assert!(stream.next().await.unwrap().is_none());
+        drop(stream);
+
+        // The server rolled back: no statement runs outside the transaction,
+        // and there is nothing left to roll back
+        let mut tx = tx;
+        assert!(matches!(backend.create_node(&mut tx, &[], PropertyMap::new()).await, Err(Error::TxError(_))));
+        backend.rollback_tx(tx).await.unwrap();
+        let signatures: Vec<u8> = server.requests().into_iter().map(|(s, _)| s).collect();
+        assert_eq!(signatures, vec![HELLO, BEGIN, RUN, PULL, PULL, RESET]);
+
+        // Nor can it be committed
+        let tx = backend.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let stream = backend.stream(&tx, "UNWIND range(0, 9) AS i RETURN 1 / (5 - i)", PropertyMap::new()).await.unwrap();
+        assert!(stream.collect().await.is_err());
+        assert!(matches!(backend.commit_tx(tx).await, Err(Error::TxError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream_autocommits() {
+        let server = MockServer::start(5, None).await;
+        let graph = crate::Graph::with_backend(server.backend(2));
+        let rows = graph.execute_stream("UNWIND range(0, 4) AS i RETURN i", PropertyMap::new()).await.unwrap();
+        assert_eq!(ints(&rows.collect().await.unwrap()), vec![0, 1, 2, 3, 4]);
+        assert_eq!(
+            server.requests(),
+            vec![(HELLO, None), (RUN, None), (PULL, Some(2)), (PULL, Some(2)), (PULL, Some(2))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_paged_pulls_a_page_at_a_time() {
+        let server = MockServer::start(25, None).await;
+        let graph = crate::Graph::with_backend(server.backend(1000));
+        let mut pages = graph.execute_paged("UNWIND range(0, 24) AS i RETURN i", PropertyMap::new(), 10).await.unwrap();
+        assert_eq!(pages.columns(), ["i"]);
+
+        let first = pages.next_page().await.unwrap().unwrap();
+        assert_eq!(ints(&first), (0..10).collect::<Vec<_>>());
+        // Only the first page has been fetched
+        assert_eq!(server.requests(), vec![(HELLO, None), (RUN, None), (PULL, Some(10))]);
+
+        let mut sizes = vec![first.len()];
+        let mut seen = ints(&first);
+        while let Some(page) = pages.next_page().await.unwrap() {
+            sizes.push(page.len());
+            seen.extend(ints(&page));
+        }
+        assert_eq!(sizes, vec![10, 10, 5]);
+        assert_eq!(seen, (0..25).collect::<Vec<_>>());
+        assert!(pages.next_page().await.unwrap().is_none());
+
+        let err = graph.execute_paged("RETURN 1", PropertyMap::new(), 0).await.err().unwrap();
+        assert!(err.to_string().contains("page size"), "{err}");
+    }
+}
@@ -33,7 +33,8 @@ use crate::model::*;
 use crate::tx::{Transaction, TxMode, TxId};
 use crate::index::IndexType;
 use crate::storage::{
-    StorageBackend, ExpandDepth, ConstraintType, BackendCapabilities, ProcedureResult,
+    StorageBackend, BackendConfig, ExpandDepth, ExpandLimits, ConstraintType, BackendCapabilities, ProcedureResult,
+    expansion_limit_exceeded,
 };
 use crate::{Error, Result};
 
@@ -49,6 +50,8 @@ use ladybug::storage::bind_space::{Addr, BindEdge, BindNode, BindSpace, FINGERPR
 pub struct LadybugBackend {
     bs: Arc<RwLock<BindSpace>>,
     next_tx_id: AtomicU64,
+    expand_limits: ExpandLimits,
+    fingerprinter: PropertyFingerprinter,
 }
 
 impl LadybugBackend {
@@ -57,18 +60,433 @@ impl LadybugBackend {
         Self {
             bs,
             next_tx_id: AtomicU64::new(1),
+            expand_limits: ExpandLimits { max_depth: 10, ..ExpandLimits::default() },
+            fingerprinter: PropertyFingerprinter::cam(),
         }
     }
 
+    /// Override the depth and path limits used by `expand()`.
+    pub fn with_expand_limits(mut self, limits: ExpandLimits) -> Self {
+        self.expand_limits = limits;
+        self
+    }
+
+    /// Fingerprint nodes written from now on with `fingerprinter`.
+    ///
+    /// Nodes already in the BindSpace keep their fingerprints; see
+    /// `refingerprint_all()`.
+    pub fn with_fingerprinter(mut self, fingerprinter: PropertyFingerprinter) -> Self {
+        self.fingerprinter = fingerprinter;
+        self
+    }
+
     /// Create with a fresh empty BindSpace.
     pub fn open() -> Self {
         Self::new(Arc::new(RwLock::new(BindSpace::new())))
     }
 
+    /// Create with a fresh empty BindSpace set up as a `BackendConfig::Ladybug`
+    /// describes.
+    ///
+    /// The BindSpace lives in memory, so `data_dir` and `cache_size_mb` are
+    /// not read yet; `fingerprinter` is.
+    pub fn from_config(config: &BackendConfig) -> Result<Self> {
+        match config {
+            BackendConfig::Ladybug { fingerprinter, .. } => Ok(Self::open().with_fingerprinter(fingerprinter.clone())),
+            _ => Err(Error::StorageError("not a ladybug backend configuration".into())),
+        }
+    }
+
     /// Access the underlying BindSpace.
     pub fn bind_space(&self) -> &Arc<RwLock<BindSpace>> {
         &self.bs
     }
+
+    /// The fingerprint stored for a node, if it exists.
+    pub fn fingerprint_of(&self, id: NodeId) -> Option<Fingerprint> {
+        self.bs.read().read(addr_from_node_id(id)).map(|bn| bn.fingerprint)
+    }
+
+    /// Every node's fingerprint, ordered by id.
+    ///
+    /// Together with `import_fingerprints` this snapshots the Hamming index
+    /// without re-fingerprinting property maps. A `NodeId` is the node's
+    /// BindSpace address, so the snapshot needs no separate id mapping.
+    pub fn export_fingerprints(&self) -> Vec<(NodeId, Fingerprint)> {
+        let bs = self.bs.read();
+        let mut out: Vec<(NodeId, Fingerprint)> = bs.nodes_iter()
+            .map(|(addr, bn)| (NodeId(addr.0 as u64), bn.fingerprint))
+            .collect();
+        out.sort_by_key(|(id, _)| *id);
+        out
+    }
+
+    /// Overwrite node fingerprints from an `export_fingerprints` snapshot.
+    ///
+    /// All-or-nothing: if any id has no node, nothing is written.
+    pub fn import_fingerprints(&self, fingerprints: &[(NodeId, Fingerprint)]) -> Result<()> {
+        let mut bs = self.bs.write();
+        if let Some((id, _)) = fingerprints.iter().find(|(id, _)| bs.read(addr_from_node_id(*id)).is_none()) {
+            return Err(Error::NotFound(format!("Node {id}")));
+        }
+        for (id, fp) in fingerprints {
+            if let Some(node) = bs.read_mut(addr_from_node_id(*id)) {
+                node.fingerprint = *fp;
+            }
+        }
+        Ok(())
+    }
+
+    /// Recompute every node's fingerprint with the current fingerprinter.
+    /// Returns the number of nodes rewritten.
+    pub fn refingerprint_all(&self) -> usize {
+        let mut bs = self.bs.write();
+        let updates: Vec<(Addr, Fingerprint)> = bs.nodes_iter()
+            .map(|(addr, bn)| {
+                let node = bind_node_to_node(addr, bn);
+                let label = node.labels.first().map(|s| s.as_str()).unwrap_or("Node");
+                (addr, self.fingerprinter.fingerprint(label, &node.properties))
+            })
+            .collect();
+        for (addr, fp) in &updates {
+            if let Some(node) = bs.read_mut(*addr) {
+                node.fingerprint = *fp;
+            }
+        }
+        updates.len()
+    }
+
+    /// `StorageBackend::perspective_gestalt`: every edge touching `id`.
+    ///
+    /// Each edge is taken as its (from, verb, to) fingerprints and compared
+    /// with each of the node's other edges by `spo_distance`. An edge's
+    /// tensor is its mean agreement with the others; see
+    /// `PerspectiveGestalt::from_edges`.
+    fn gestalt_of(&self, id: NodeId) -> Result<PerspectiveGestalt> {
+        let bs = self.bs.read();
+        let addr = addr_from_node_id(id);
+        if bs.read(addr).is_none() {
+            return Err(Error::NotFound(format!("Node {id}")));
+        }
+        let fingerprint = |a: Addr| bs.read(a).map(|bn| bn.fingerprint);
+        let spo: Vec<[Fingerprint; 3]> = bs.edges_out(addr)
+            .chain(bs.edges_in(addr).filter(|e| e.from != addr))
+            .filter_map(|e| Some([fingerprint(e.from)?, fingerprint(e.verb)?, fingerprint(e.to)?]))
+            .collect();
+
+        let tensors: Vec<Option<AwarenessTensor>> = spo.iter().enumerate().map(|(i, [s_a, p_a, o_a])| {
+            let others: Vec<AwarenessTensor> = spo.iter().enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, [s_b, p_b, o_b])| spo_distance(s_a, s_b, p_a, p_b, o_a, o_b).to_awareness_tensor())
+                .collect();
+            // An edge with nothing to compare against has no perspective
+            (!others.is_empty()).then(|| AwarenessTensor::mean(&others))
+        }).collect();
+        Ok(PerspectiveGestalt::from_edges(&tensors))
+    }
+
+    /// The edges of `anchor` that resonate with its edge `reference`, with
+    /// the masked tensor each passed `filter` with, strongest first.
+    /// Relationship ids are those `get_relationships` reports for `anchor`.
+    pub fn resonance_of(
+        &self,
+        anchor: NodeId,
+        reference: RelId,
+        filter: &AwarenessFilter,
+    ) -> Result<Vec<(ResonanceEdge, AwarenessTensor)>> {
+        let bs = self.bs.read();
+        let addr = addr_from_node_id(anchor);
+        if bs.read(addr).is_none() {
+            return Err(Error::NotFound(format!("Node {anchor}")));
+        }
+        let fingerprint = |a: Addr| bs.read(a).map(|bn| bn.fingerprint);
+        let edges: Vec<(RelId, &BindEdge)> = bs.edges_out(addr).enumerate()
+            .map(|(i, e)| (RelId(i as u64), e))
+            .chain(bs.edges_in(addr).enumerate().map(|(i, e)| (RelId(10000 + i as u64), e)))
+            .collect();
+        let spo = |e: &BindEdge| Some([fingerprint(e.from)?, fingerprint(e.verb)?, fingerprint(e.to)?]);
+        let [s_a, p_a, o_a] = edges.iter()
+            .find(|(id, _)| *id == reference)
+            .and_then(|(_, e)| spo(e))
+            .ok_or_else(|| Error::NotFound(format!("Relationship {reference} of node {anchor}")))?;
+
+        let mut hits: Vec<(ResonanceEdge, AwarenessTensor)> = edges.iter()
+            .filter(|(id, _)| *id != reference)
+            .filter_map(|(id, e)| {
+                let [s_b, p_b, o_b] = spo(e)?;
+                let tensor = spo_distance(&s_a, &s_b, &p_a, &p_b, &o_a, &o_b).to_awareness_tensor();
+                let edge = ResonanceEdge {
+                    id: *id,
+                    src: NodeId(e.from.0 as u64),
+                    dst: NodeId(e.to.0 as u64),
+                    rel_type: bs.read(e.verb)
+                        .and_then(|n| n.label.clone())
+                        .unwrap_or_else(|| "RELATED_TO".to_string()),
+                    properties: PropertyMap::new(),
+                    container_s: None,
+                    container_p: None,
+                    container_o: None,
+                    spo_trace: None,
+                };
+                Some((edge, filter.apply(&tensor)?))
+            })
+            .collect();
+        hits.sort_by(|(a, ta), (b, tb)| {
+            tb.total_agreement().total_cmp(&ta.total_agreement()).then(a.id.0.cmp(&b.id.0))
+        });
+        Ok(hits)
+    }
+
+    /// `ladybug.resonance(nodeId, relId[, mask])`: `resonance_of` with an
+    /// optional `AwarenessMask` selector such as `'causal_only'`.
+    fn resonance(&self, args: &[Value]) -> Result<ProcedureResult> {
+        let (anchor, reference, mask) = match args {
+            [anchor, reference] => (anchor, reference, None),
+            [anchor, reference, Value::Null] => (anchor, reference, None),
+            [anchor, reference, Value::String(selector)] => {
+                let mask = AwarenessMask::from_selector(selector).ok_or_else(|| {
+                    Error::ExecutionError(format!("ladybug.resonance: unknown mask selector '{selector}'"))
+                })?;
+                (anchor, reference, Some(mask))
+            }
+            _ => return Err(Error::ExecutionError("ladybug.resonance expects (nodeId, relId[, mask])".into())),
+        };
+        let reference = reference.as_int().ok_or_else(|| Error::TypeError {
+            expected: "Integer".into(),
+            got: reference.type_name().into(),
+            span: None,
+        })?;
+        let filter = AwarenessFilter { mask, ..Default::default() };
+        let hits = self.resonance_of(procedure_node_id(anchor)?, RelId(reference as u64), &filter)?;
+        let columns = ["rel", "agreement", "awareness_state"];
+        let rows = hits.into_iter().map(|(edge, tensor)| {
+            let rel = Relationship {
+                id: edge.id,
+                element_id: None,
+                src: edge.src,
+                dst: edge.dst,
+                rel_type: edge.rel_type,
+                properties: edge.properties,
+            };
+            HashMap::from([
+                ("rel".to_string(), Value::from(rel)),
+                ("agreement".to_string(), Value::Float(tensor.total_agreement() as f64)),
+                ("awareness_state".to_string(), Value::from(tensor.awareness_state().to_string())),
+            ])
+        }).collect();
+        Ok(ProcedureResult { columns: columns.map(String::from).to_vec(), rows })
+    }
+
+    /// Compose the awareness of consecutive edges along `path`; see
+    /// `CausalPath::compose`. An edge is its (src, verb, dst) fingerprints.
+    pub fn compose_causal_path(&self, path: &Path) -> Result<CausalPath> {
+        let bs = self.bs.read();
+        let fingerprint = |id: NodeId| {
+            bs.read(addr_from_node_id(id))
+                .map(|bn| bn.fingerprint)
+                .ok_or_else(|| Error::NotFound(format!("Node {id}")))
+        };
+        let spo: Vec<[Fingerprint; 3]> = path.relationships.iter()
+            .map(|r| Ok([fingerprint(r.src)?, verb_fingerprint(&r.rel_type), fingerprint(r.dst)?]))
+            .collect::<Result<_>>()?;
+        let compare = |a: &[Fingerprint; 3], b: &[Fingerprint; 3]| {
+            spo_distance(&a[0], &b[0], &a[1], &b[1], &a[2], &b[2]).to_awareness_tensor()
+        };
+        let mut tensors: Vec<AwarenessTensor> = spo.windows(2).map(|w| compare(&w[0], &w[1])).collect();
+        // Close the loop: transport from the last edge back to the first
+        if spo.len() > 1 && path.start().id == path.end().id {
+            tensors.push(compare(&spo[spo.len() - 1], &spo[0]));
+        }
+        Ok(CausalPath::compose(path.clone(), tensors))
+    }
+
+    /// Shortest outgoing path from `src` to `dst`; the shortest cycle
+    /// through `src` when they are the same node.
+    fn shortest_path(&self, src: NodeId, dst: NodeId) -> Option<Path> {
+        let bs = self.bs.read();
+        let (start, goal) = (addr_from_node_id(src), addr_from_node_id(dst));
+        bs.read(start)?;
+        // addr → (previous addr, verb) on the first path that reached it
+        let mut parent: HashMap<Addr, (Addr, Addr)> = HashMap::new();
+        let mut frontier = vec![start];
+        while !frontier.is_empty() && !parent.contains_key(&goal) {
+            let mut next = Vec::new();
+            for current in frontier {
+                for edge in bs.edges_out(current) {
+                    // Only a cycle query may return to the start
+                    if parent.contains_key(&edge.to) || (edge.to == start && start != goal) {
+                        continue;
+                    }
+                    parent.insert(edge.to, (current, edge.verb));
+                    next.push(edge.to);
+                }
+            }
+            frontier = next;
+        }
+
+        let mut hops = Vec::new();
+        let mut at = goal;
+        while let Some(&(prev, verb)) = parent.get(&at) {
+            hops.push((prev, verb, at));
+            at = prev;
+            if at == start {
+                break;
+            }
+        }
+        if at != start || hops.is_empty() {
+            return None;
+        }
+        hops.reverse();
+
+        let node = |a: Addr| bs.read(a).map(|bn| bind_node_to_node(a, bn));
+        let mut nodes = vec![node(start)?];
+        let mut relationships = Vec::new();
+        for (i, (from, verb, to)) in hops.into_iter().enumerate() {
+            nodes.push(node(to)?);
+            relationships.push(Relationship {
+                id: RelId(i as u64),
+                element_id: None,
+                rel_type: bs.read(verb)
+                    .and_then(|n| n.label.clone())
+                    .unwrap_or_else(|| "RELATED_TO".to_string()),
+                src: NodeId(from.0 as u64),
+                dst: NodeId(to.0 as u64),
+                properties: PropertyMap::new(),
+            });
+        }
+        Some(Path { nodes, relationships })
+    }
+
+    /// `ladybug.causal_path(src, dst)`: the composed perspective along the
+    /// shortest path, or no rows when `dst` is unreachable.
+    fn causal_path_procedure(&self, args: &[Value]) -> Result<ProcedureResult> {
+        let [src, dst] = args else {
+            return Err(Error::ExecutionError("ladybug.causal_path expects (src, dst)".into()));
+        };
+        let columns = ["path", "composed_sign", "composed_exp", "composed_mant", "holonomy", "causal_inversion"];
+        let mut result = ProcedureResult { columns: columns.map(String::from).to_vec(), rows: Vec::new() };
+        let Some(path) = self.shortest_path(procedure_node_id(src)?, procedure_node_id(dst)?) else {
+            return Ok(result);
+        };
+        let cp = self.compose_causal_path(&path)?;
+        result.rows.push(HashMap::from([
+            ("composed_sign".to_string(), Value::Float(cp.composed_sign as f64)),
+            ("composed_exp".to_string(), Value::Float(cp.composed_exp as f64)),
+            ("composed_mant".to_string(), Value::Float(cp.composed_mant as f64)),
+            ("holonomy".to_string(), cp.holonomy.map_or(Value::Null, |h| Value::Float(h as f64))),
+            ("causal_inversion".to_string(), Value::Bool(cp.has_causal_inversion())),
+            ("path".to_string(), Value::from(cp.path)),
+        ]));
+        Ok(result)
+    }
+
+    /// `ladybug.gestalt(nodeId)`: one row summarising `gestalt_of`.
+    fn gestalt(&self, args: &[Value]) -> Result<ProcedureResult> {
+        let [id] = args else {
+            return Err(Error::ExecutionError("ladybug.gestalt expects (nodeId)".into()));
+        };
+        let g = self.gestalt_of(procedure_node_id(id)?)?;
+        let columns = ["dominant_state", "crystallized", "tensioned", "uncertain", "most_tensioned"];
+        let row = HashMap::from([
+            ("dominant_state".to_string(), Value::from(g.dominant_state.to_string())),
+            ("crystallized".to_string(), Value::Int(g.crystallized_count as i64)),
+            ("tensioned".to_string(), Value::Int(g.tensioned_count as i64)),
+            ("uncertain".to_string(), Value::Int(g.uncertain_count as i64)),
+            ("most_tensioned".to_string(), g.most_tensioned_dimension.map_or(Value::Null, Value::from)),
+        ]);
+        Ok(ProcedureResult { columns: columns.map(String::from).to_vec(), rows: vec![row] })
+    }
+
+    /// `ladybug.qualia_similar(nodeId, k)`: the `k` qualia-carrying nodes
+    /// nearest to `nodeId` by `nib4_full_distance`, nearest first.
+    fn qualia_similar(&self, args: &[Value]) -> Result<ProcedureResult> {
+        let [id, k] = args else {
+            return Err(Error::ExecutionError("ladybug.qualia_similar expects (nodeId, k)".into()));
+        };
+        if !matches!(self.fingerprinter.mode(), FingerprintMode::Nib4Qualia { .. }) {
+            return Err(Error::ExecutionError(
+                "ladybug.qualia_similar requires a Nib4 qualia fingerprinter".into(),
+            ));
+        }
+        let id = procedure_node_id(id)?;
+        let k = k.as_int().filter(|k| *k >= 0).ok_or_else(|| Error::TypeError {
+            expected: "non-negative Integer".into(),
+            got: k.type_name().into(),
+            span: None,
+        })?;
+
+        let bs = self.bs.read();
+        let seed = bs.read(addr_from_node_id(id))
+            .ok_or_else(|| Error::NotFound(format!("Node {id}")))?;
+        if qualia_vector(&bind_node_to_node(addr_from_node_id(id), seed).properties).is_none() {
+            return Err(Error::ExecutionError(format!("Node {id} has no qualia properties")));
+        }
+        let seed = qualia_words(&seed.fingerprint);
+
+        let mut scored: Vec<(Node, u32)> = bs.nodes_iter()
+            .filter(|(addr, _)| *addr != addr_from_node_id(id))
+            .map(|(addr, bn)| (bind_node_to_node(addr, bn), qualia_words(&bn.fingerprint)))
+            .filter(|(n, _)| qualia_vector(&n.properties).is_some())
+            .map(|(n, words)| (n, nib4_full_distance(&seed, &words, QUALIA_INTENSITY_PENALTY)))
+            .collect();
+        scored.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.id.cmp(&b.0.id)));
+        scored.truncate(k as usize);
+
+        Ok(ProcedureResult {
+            columns: vec!["node".into(), "distance".into()],
+            rows: scored.into_iter().map(|(node, distance)| {
+                HashMap::from([
+                    ("node".to_string(), Value::from(node)),
+                    ("distance".to_string(), Value::Int(distance as i64)),
+                ])
+            }).collect(),
+        })
+    }
+}
+
+/// The fingerprint of a relationship type's verb node.
+fn verb_fingerprint(rel_type: &str) -> Fingerprint {
+    let fp = ladybug::core::Fingerprint::from_content(rel_type);
+    let mut words = [0u64; FINGERPRINT_WORDS];
+    words.copy_from_slice(fp.as_raw());
+    words
+}
+
+/// A procedure's node argument: a node or its id.
+fn procedure_node_id(v: &Value) -> Result<NodeId> {
+    match v {
+        Value::Node(n) => Ok(n.id),
+        Value::Int(i) => Ok(NodeId(*i as u64)),
+        other => Err(Error::TypeError { expected: "Node or Integer".into(), got: other.type_name().into(), span: None }),
+    }
+}
+
+/// A node fingerprint: the raw BindSpace words.
+pub type Fingerprint = [u64; FINGERPRINT_WORDS];
+
+/// Read a `vector_query` argument as a fingerprint: raw `u64` words, or
+/// `f32` components binarized by sign.
+fn query_fingerprint(query_vector: &[u8]) -> Result<Fingerprint> {
+    let mut words = [0u64; FINGERPRINT_WORDS];
+    if query_vector.len() == FINGERPRINT_WORDS * 8 {
+        for (word, bytes) in words.iter_mut().zip(query_vector.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().expect("chunk of 8"));
+        }
+    } else if query_vector.len() == FINGERPRINT_WORDS * 64 * 4 {
+        for (bit, bytes) in query_vector.chunks_exact(4).enumerate() {
+            if f32::from_le_bytes(bytes.try_into().expect("chunk of 4")) > 0.0 {
+                words[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+    } else {
+        return Err(Error::ExecutionError(format!(
+            "query vector must be {} fingerprint bytes or {} floats, got {} bytes",
+            FINGERPRINT_WORDS * 8,
+            FINGERPRINT_WORDS * 64,
+            query_vector.len()
+        )));
+    }
+    Ok(words)
 }
 
 // =============================================================================
@@ -97,7 +515,7 @@ fn bind_node_to_node(addr: Addr, bn: &BindNode) -> Node {
     if let Some(ref payload) = bn.payload {
         if let Ok(map) = serde_json::from_slice::<HashMap<String, serde_json::Value>>(payload) {
             for (k, v) in map {
-                properties.insert(k, json_to_value(&v));
+                properties.insert(k, Value::from(v));
             }
         }
     }
@@ -112,46 +530,121 @@ fn bind_node_to_node(addr: Addr, bn: &BindNode) -> Node {
     }
 }
 
-/// Generate deterministic fingerprint from label + properties.
-fn node_fingerprint(label: &str, properties: &PropertyMap) -> [u64; FINGERPRINT_WORDS] {
-    let mut content = label.to_string();
-    let mut sorted: Vec<_> = properties.iter().collect();
-    sorted.sort_by_key(|(k, _)| k.clone());
-    for (k, v) in sorted {
-        content.push(':');
-        content.push_str(k);
-        content.push('=');
-        content.push_str(&format!("{:?}", v));
-    }
-    let fp = ladybug::core::Fingerprint::from_content(&content);
-    let mut words = [0u64; FINGERPRINT_WORDS];
-    words.copy_from_slice(fp.as_raw());
-    words
+// =============================================================================
+// FINGERPRINTING
+// =============================================================================
+
+/// Which encoding a `PropertyFingerprinter` uses.
+#[derive(Debug, Clone, Default)]
+pub enum FingerprintMode {
+    /// Content-addressable: a hash of the label and the sorted properties.
+    #[default]
+    Cam,
+    /// CAM, with the first five BF16-aligned `u16` words (the low 80 bits)
+    /// replaced by the node's Nib4-quantized qualia vector, for nodes that
+    /// carry every `QUALIA_JSON_KEYS` property. The rest stays topology.
+    Nib4Qualia { codebook: Nib4Codebook },
 }
 
-fn json_to_value(v: &serde_json::Value) -> Value {
-    match v {
-        serde_json::Value::String(s) => Value::String(s.clone()),
-        serde_json::Value::Number(n) => {
-            if let Some(i) = n.as_i64() { Value::Int(i) }
-            else if let Some(f) = n.as_f64() { Value::Float(f) }
-            else { Value::Null }
-        }
-        serde_json::Value::Bool(b) => Value::Bool(*b),
-        serde_json::Value::Null => Value::Null,
-        serde_json::Value::Array(arr) => {
-            Value::List(arr.iter().map(json_to_value).collect())
-        }
-        serde_json::Value::Object(map) => {
-            let mut pm = PropertyMap::new();
-            for (k, v) in map {
-                pm.insert(k.clone(), json_to_value(v));
-            }
-            Value::Map(pm)
+/// Optional boolean property holding the qualia intensity bit
+/// (`true` = CMYK/subtractive/caused).
+pub const QUALIA_INTENSITY_KEY: &str = "intensity";
+
+/// Penalty `ladybug.qualia_similar` adds when intensity bits differ:
+/// one full dimension.
+const QUALIA_INTENSITY_PENALTY: u32 = 16;
+
+/// Turns a node's label and properties into its fingerprint.
+///
+/// Fingerprints are computed when a node is written, so switching a backend
+/// to a different fingerprinter leaves existing nodes on the old strategy:
+/// Hamming scores then compare unlike encodings. Run
+/// `LadybugBackend::refingerprint_all()` after changing it.
+#[derive(Debug, Clone, Default)]
+pub struct PropertyFingerprinter {
+    mode: FingerprintMode,
+    keys: Option<Vec<String>>,
+}
+
+impl PropertyFingerprinter {
+    /// The default CAM fingerprinter over every property.
+    pub fn cam() -> Self {
+        Self::default()
+    }
+
+    /// Only fingerprint these property keys; the rest (ids, timestamps and
+    /// other high-cardinality noise) don't move a node's fingerprint.
+    pub fn with_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.keys = Some(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Qualia-carrying fingerprints, quantized with `codebook`.
+    pub fn nib4_qualia(codebook: Nib4Codebook) -> Self {
+        Self { mode: FingerprintMode::Nib4Qualia { codebook }, keys: None }
+    }
+
+    pub fn mode(&self) -> &FingerprintMode {
+        &self.mode
+    }
+
+    /// Deterministic fingerprint of `label` and the selected `properties`.
+    pub fn fingerprint(&self, label: &str, properties: &PropertyMap) -> Fingerprint {
+        let mut content = label.to_string();
+        let mut sorted: Vec<_> = properties
+            .iter()
+            .filter(|(k, _)| self.keys.as_ref().is_none_or(|keys| keys.contains(k)))
+            .collect();
+        sorted.sort_by_key(|(k, _)| k.clone());
+        for (k, v) in sorted {
+            content.push(':');
+            content.push_str(k);
+            content.push('=');
+            content.push_str(&format!("{:?}", v));
+        }
+        let fp = ladybug::core::Fingerprint::from_content(&content);
+        let mut words = [0u64; FINGERPRINT_WORDS];
+        words.copy_from_slice(fp.as_raw());
+        if let FingerprintMode::Nib4Qualia { codebook } = &self.mode
+            && let Some(qualia) = qualia_vector(properties)
+        {
+            let intensity = matches!(properties.get(QUALIA_INTENSITY_KEY), Some(Value::Bool(true)));
+            let packed = nib4_pack_bf16(&codebook.encode_vec(&qualia), intensity);
+            words[0] = packed[..4].iter().rev().fold(0, |acc, w| (acc << 16) | *w as u64);
+            words[1] = (words[1] & !0xFFFF) | packed[4] as u64;
         }
+        words
     }
 }
 
+/// The node's qualia vector in `QUALIA_JSON_KEYS` order, if it has every
+/// key as a number.
+fn qualia_vector(properties: &PropertyMap) -> Option<Vec<f32>> {
+    QUALIA_JSON_KEYS
+        .iter()
+        .map(|k| match properties.get(*k)? {
+            Value::Int(i) => Some(*i as f32),
+            Value::Float(f) => Some(*f as f32),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The five BF16-aligned qualia words `Nib4Qualia` stores in a fingerprint.
+fn qualia_words(fp: &Fingerprint) -> [u16; 5] {
+    [
+        fp[0] as u16,
+        (fp[0] >> 16) as u16,
+        (fp[0] >> 32) as u16,
+        (fp[0] >> 48) as u16,
+        fp[1] as u16,
+    ]
+}
+
 fn value_to_json(v: &Value) -> serde_json::Value {
     match v {
         Value::String(s) => serde_json::Value::String(s.clone()),
@@ -217,7 +710,7 @@ impl StorageBackend for LadybugBackend {
         properties: PropertyMap,
     ) -> Result<NodeId> {
         let primary_label = labels.first().map(|s| s.as_str()).unwrap_or("Node");
-        let fp = node_fingerprint(primary_label, &properties);
+        let fp = self.fingerprinter.fingerprint(primary_label, &properties);
 
         let mut bs = self.bs.write();
         let addr = bs.write_labeled(fp, primary_label);
@@ -235,6 +728,17 @@ impl StorageBackend for LadybugBackend {
         Ok(bs.read(addr).map(|bn| bind_node_to_node(addr, bn)))
     }
 
+    async fn get_nodes(&self, _tx: &Self::Tx, ids: &[NodeId]) -> Result<Vec<Option<Node>>> {
+        let bs = self.bs.read();
+        Ok(ids
+            .iter()
+            .map(|&id| {
+                let addr = addr_from_node_id(id);
+                bs.read(addr).map(|bn| bind_node_to_node(addr, bn))
+            })
+            .collect())
+    }
+
     async fn delete_node(&self, _tx: &mut Self::Tx, _id: NodeId) -> Result<bool> {
         // BindSpace doesn't support node deletion directly — mark as dead
         Ok(false)
@@ -309,6 +813,47 @@ impl StorageBackend for LadybugBackend {
         }
     }
 
+    /// One payload round trip for all the keys, instead of one per key.
+    async fn set_node_properties(
+        &self,
+        _tx: &mut Self::Tx,
+        id: NodeId,
+        properties: PropertyMap,
+    ) -> Result<()> {
+        let addr = addr_from_node_id(id);
+
+        // Stage 1: read under read lock
+        let old_payload = {
+            let bs = self.bs.read();
+            let node = bs.read(addr)
+                .ok_or_else(|| Error::NotFound(format!("Node {:?}", id)))?;
+            node.payload.clone()
+        };
+
+        // Stage 2: merge every key outside the lock
+        let mut props: HashMap<String, serde_json::Value> = old_payload
+            .as_ref()
+            .and_then(|p| serde_json::from_slice(p).ok())
+            .unwrap_or_default();
+        for (key, value) in &properties {
+            if value.is_null() {
+                props.remove(key);
+            } else {
+                props.insert(key.clone(), value_to_json(value));
+            }
+        }
+        let new_payload = serde_json::to_vec(&props).unwrap_or_default();
+
+        // Stage 3: gated write-back
+        let mut bs = self.bs.write();
+        if let Some(node) = bs.read_mut(addr) {
+            node.payload = Some(new_payload);
+            Ok(())
+        } else {
+            Err(Error::NotFound(format!("Node {:?}", id)))
+        }
+    }
+
     async fn add_label(&self, _tx: &mut Self::Tx, id: NodeId, label: String) -> Result<()> {
         let mut bs = self.bs.write();
         let addr = addr_from_node_id(id);
@@ -346,13 +891,7 @@ impl StorageBackend for LadybugBackend {
         let mut bs = self.bs.write();
 
         // Create or find verb node for this relationship type
-        let verb_fp = {
-            let fp = ladybug::core::Fingerprint::from_content(&rel_type);
-            let mut words = [0u64; FINGERPRINT_WORDS];
-            words.copy_from_slice(fp.as_raw());
-            words
-        };
-        let verb_addr = bs.write_labeled(verb_fp, &rel_type);
+        let verb_addr = bs.write_labeled(verb_fingerprint(&rel_type), &rel_type);
 
         let edge = BindEdge::new(from, verb_addr, to);
         let edge_idx = bs.edge_count();
@@ -376,7 +915,7 @@ impl StorageBackend for LadybugBackend {
         _tx: &mut Self::Tx,
         node_id: NodeId,
         direction: Direction,
-        _rel_type: Option<&str>,
+        rel_type: Option<&str>,
     ) -> Result<Vec<Relationship>> {
         let bs = self.bs.read();
         let addr = addr_from_node_id(node_id);
@@ -404,6 +943,10 @@ impl StorageBackend for LadybugBackend {
         match direction {
             Direction::Incoming | Direction::Both => {
                 for (i, edge) in bs.edges_in(addr).enumerate() {
+                    // A self-loop was already reported as outgoing
+                    if direction == Direction::Both && edge.from == addr {
+                        continue;
+                    }
                     let verb_label = bs.read(edge.verb)
                         .and_then(|n| n.label.clone())
                         .unwrap_or_else(|| "RELATED_TO".to_string());
@@ -420,6 +963,10 @@ impl StorageBackend for LadybugBackend {
             _ => {}
         }
 
+        // Callers rely on this for single-type pushdown (`get_relationships_typed`)
+        if let Some(t) = rel_type {
+            rels.retain(|r| r.rel_type == t);
+        }
         Ok(rels)
     }
 
@@ -433,11 +980,7 @@ impl StorageBackend for LadybugBackend {
     ) -> Result<Vec<Path>> {
         let bs = self.bs.read();
         let start_addr = addr_from_node_id(start);
-        let max_depth = match depth {
-            ExpandDepth::Exact(d) => d,
-            ExpandDepth::Range { max, .. } => max,
-            ExpandDepth::Unbounded => 10,
-        };
+        let (_, max_depth) = self.expand_limits.resolve(depth)?;
 
         let mut paths = Vec::new();
         let mut stack: Vec<(Addr, Vec<Addr>, Vec<(Addr, Addr)>)> = vec![(start_addr, vec![start_addr], vec![])];
@@ -452,7 +995,7 @@ impl StorageBackend for LadybugBackend {
                 Direction::Incoming => bs.edges_in(current).collect(),
                 Direction::Both => {
                     let mut all: Vec<_> = bs.edges_out(current).collect();
-                    all.extend(bs.edges_in(current));
+                    all.extend(bs.edges_in(current).filter(|e| e.from != current));
                     all
                 }
             };
@@ -468,7 +1011,7 @@ impl StorageBackend for LadybugBackend {
                     }
                 }
 
-                let next = if direction == Direction::Incoming { edge.from } else { edge.to };
+                let next = if edge.from == current { edge.to } else { edge.from };
 
                 if node_path.contains(&next) {
                     continue; // avoid cycles
@@ -498,6 +1041,9 @@ impl StorageBackend for LadybugBackend {
                     .collect();
 
                 paths.push(Path { nodes, relationships });
+                if paths.len() > self.expand_limits.max_paths {
+                    return Err(expansion_limit_exceeded());
+                }
 
                 if new_node_path.len() <= max_depth {
                     stack.push((next, new_node_path, new_edge_path));
@@ -511,17 +1057,21 @@ impl StorageBackend for LadybugBackend {
     // ---- Scanning ----
     async fn all_nodes(&self, _tx: &mut Self::Tx) -> Result<Vec<Node>> {
         let bs = self.bs.read();
-        Ok(bs.nodes_iter()
+        let mut all: Vec<Node> = bs.nodes_iter()
             .map(|(addr, bn)| bind_node_to_node(addr, bn))
-            .collect())
+            .collect();
+        all.sort_by_key(|n| n.id);
+        Ok(all)
     }
 
     async fn nodes_by_label(&self, _tx: &mut Self::Tx, label: &str) -> Result<Vec<Node>> {
         let bs = self.bs.read();
-        Ok(bs.nodes_iter()
+        let mut matched: Vec<Node> = bs.nodes_iter()
             .filter(|(_, bn)| bn.label.as_deref() == Some(label))
             .map(|(addr, bn)| bind_node_to_node(addr, bn))
-            .collect())
+            .collect();
+        matched.sort_by_key(|n| n.id);
+        Ok(matched)
     }
 
     async fn nodes_by_property(
@@ -604,7 +1154,70 @@ impl StorageBackend for LadybugBackend {
         Ok(())
     }
 
-    async fn capabilities(&self) -> BackendCapabilities {
+    /// Brute-force Hamming scan: every node is scored by bit similarity to
+    /// the query fingerprint. The query is either the fingerprint itself
+    /// (`Bytes` of little-endian `u64` words) or one `f32` per bit, set where
+    /// the component is positive. BindSpace has no named vector indexes, so
+    /// `index_name` is not used.
+    async fn vector_query(
+        &self,
+        _tx: &Self::Tx,
+        _index_name: &str,
+        k: usize,
+        query_vector: &[u8],
+    ) -> Result<Vec<(NodeId, f64)>> {
+        let query = query_fingerprint(query_vector)?;
+        let total_bits = (FINGERPRINT_WORDS * 64) as f64;
+
+        let bs = self.bs.read();
+        let mut scored: Vec<(NodeId, f64)> = bs.nodes_iter()
+            .map(|(addr, bn)| {
+                let distance: u32 = bn.fingerprint.iter()
+                    .zip(&query)
+                    .map(|(a, b)| (a ^ b).count_ones())
+                    .sum();
+                (NodeId(addr.0 as u64), 1.0 - distance as f64 / total_bits)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scored.truncate(k);
+        Ok(scored)
+    }
+
+    async fn call_procedure(
+        &self,
+        _tx: &mut Self::Tx,
+        name: &str,
+        args: Vec<Value>,
+    ) -> Result<ProcedureResult> {
+        match name.to_ascii_lowercase().as_str() {
+            "ladybug.qualia_similar" => self.qualia_similar(&args),
+            "ladybug.gestalt" => self.gestalt(&args),
+            "ladybug.causal_path" => self.causal_path_procedure(&args),
+            "ladybug.resonance" => self.resonance(&args),
+            _ => Err(Error::ExecutionError(format!("Unknown procedure: {name}"))),
+        }
+    }
+
+    async fn resonance_query(
+        &self,
+        _tx: &Self::Tx,
+        anchor: NodeId,
+        reference: RelId,
+        filter: AwarenessFilter,
+    ) -> Result<Vec<(ResonanceEdge, AwarenessTensor)>> {
+        self.resonance_of(anchor, reference, &filter)
+    }
+
+    async fn causal_path(&self, _tx: &Self::Tx, src: NodeId, dst: NodeId) -> Result<Option<CausalPath>> {
+        self.shortest_path(src, dst).map(|path| self.compose_causal_path(&path)).transpose()
+    }
+
+    async fn perspective_gestalt(&self, _tx: &Self::Tx, node: NodeId) -> Result<PerspectiveGestalt> {
+        self.gestalt_of(node)
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
         BackendCapabilities {
             supports_vector_index: true,
             supports_fulltext_index: false,
@@ -616,8 +1229,14 @@ impl StorageBackend for LadybugBackend {
                 "ladybug.hamming".to_string(),
                 "ladybug.bind".to_string(),
                 "ladybug.stats".to_string(),
+                "ladybug.qualia_similar".to_string(),
+                "ladybug.gestalt".to_string(),
+                "ladybug.causal_path".to_string(),
+                "ladybug.resonance".to_string(),
+                "db.index.vector.queryNodes".to_string(),
             ],
             similarity_accelerated: true,
+            ..Default::default()
         }
     }
 }
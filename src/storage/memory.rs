@@ -5,29 +5,41 @@
 //!
 //! ## Limitations
 //!
-//! - **No real transactions**: `commit_tx()` and `rollback_tx()` are no-ops.
-//!   Writes are applied immediately. Rollback does NOT undo mutations.
+//! - **No real transactions by default**: `commit_tx()` and `rollback_tx()`
+//!   are no-ops. Writes are applied immediately. Rollback does NOT undo
+//!   mutations. `with_isolated_transactions(true)` buffers each read-write
+//!   transaction in a private copy of the graph instead. Either way a
+//!   read-only transaction reads the graph as it was when it began.
 //! - **Single-writer only**: Per-collection locks mean multi-step mutations
 //!   are NOT atomic. Safe for single-threaded or read-heavy use only.
-//! - **No property indexes**: `create_index()` is a no-op. All property
-//!   lookups do a full scan.
+//! - **Range indexes only**: `create_index()` with `BTree` or `Unique`
+//!   keeps a sorted index of numeric and string values that backs
+//!   `nodes_by_property_range()`. Equality lookups still scan the label.
+//! - **Durability is opt-in**: `MemoryBackend::open_durable()` logs every
+//!   write to a write-ahead log and replays it on open; see `storage::wal`.
 //!
 //! Use this backend for:
 //! - Testing the Cypher parser, planner, and execution engine
-//! - Embedding neo4j-rs in applications that don't need persistence
+//! - Embedding neo4j-rs in applications that need at most single-file
+//!   persistence
 //! - Validating correctness before running against ladybug-rs or Neo4j
 
-use std::collections::HashMap;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::ops::{Bound, Deref};
+use std::pin::Pin;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use async_trait::async_trait;
 
 use crate::model::*;
 use crate::tx::{Transaction, TxMode, TxId};
 use crate::index::IndexType;
 use crate::{Error, Result};
-use super::{StorageBackend, ExpandDepth};
+use super::{BackendCapabilities, BackendConfig, ProcedureResult, StorageBackend, ExpandDepth, ExpandLimits, expansion_limit_exceeded, node_still_has_relationships, value_in_range};
+use super::wal::{FsyncPolicy, Snapshot, Wal, WalRecord};
 
 // ============================================================================
 // MemoryBackend
@@ -35,45 +47,695 @@ use super::{StorageBackend, ExpandDepth};
 
 /// In-memory property graph storage.
 pub struct MemoryBackend {
-    inner: Arc<MemoryInner>,
+    committed: RwLock<Committed>,
+    /// Run read-write transactions on a private copy; see
+    /// `with_isolated_transactions`.
+    isolated: bool,
+    next_tx_id: AtomicU64,
+    expand_limits: ExpandLimits,
+    /// Match relationship types ignoring ASCII case; see
+    /// `with_case_insensitive_rel_types`.
+    case_insensitive_rel_types: bool,
+    /// Hand out freed node ids again; see `with_node_id_reuse`.
+    reuse_node_ids: bool,
+    /// Lowercased name → handler, for `call_procedure`; see
+    /// `register_procedure`.
+    procedures: RwLock<HashMap<String, ProcedureHandler>>,
+    /// Appended to under the locks of each mutation, so log order is
+    /// apply order.
+    wal: Option<Wal>,
 }
 
+/// Lock order: a method that holds more than one of these locks at once
+/// takes them in field order (`nodes`, `label_index`, `property_index`,
+/// `relationships`, `adjacency`) and the WAL last, skipping any it doesn't
+/// need. Readers follow the same order as writers, since a waiting writer
+/// blocks new readers. With one global order no two callers can each hold
+/// a lock the other is waiting for.
 struct MemoryInner {
     nodes: RwLock<HashMap<NodeId, Node>>,
+    /// label → set of node IDs (poor man's label index)
+    label_index: RwLock<HashMap<String, Vec<NodeId>>>,
+    /// (label, property) → sorted values, for `create_index()`ed properties.
+    property_index: RwLock<HashMap<(String, String), PropertyIndex>>,
     relationships: RwLock<HashMap<RelId, Relationship>>,
+    /// rel_type → number of relationships of that type, locked after
+    /// `relationships`
+    rel_type_counts: RwLock<HashMap<String, u64>>,
     /// node_id → list of relationship IDs
     adjacency: RwLock<HashMap<NodeId, Vec<RelId>>>,
-    /// label → set of node IDs (poor man's label index)
-    label_index: RwLock<HashMap<String, Vec<NodeId>>>,
     next_node_id: AtomicU64,
+    /// Ids of deleted nodes below `next_node_id`, kept only while node ids
+    /// are reused; locked after `adjacency`
+    free_node_ids: Mutex<BTreeSet<NodeId>>,
     next_rel_id: AtomicU64,
-    next_tx_id: AtomicU64,
+}
+
+/// The committed graph. Without isolated transactions it is one state every
+/// transaction shares. With them, each commit replaces it and bumps
+/// `version`, which is how a commit notices that another got in first.
+struct Committed {
+    version: u64,
+    state: Arc<MemoryInner>,
+    /// Cloned by each read-only transaction reading `state`. Without
+    /// isolated transactions, a write finding it shared first replaces
+    /// `state` with a copy, so those readers keep the graph they began on.
+    readers: Arc<()>,
+}
+
+impl Committed {
+    fn new(version: u64, state: Arc<MemoryInner>) -> Self {
+        Self { version, state, readers: Arc::new(()) }
+    }
+}
+
+/// The state a write goes to. In the shared graph it holds `committed`
+/// read-locked until the write is applied, so no read-only transaction
+/// begins on the state halfway through it.
+struct Writable<'a> {
+    state: Arc<MemoryInner>,
+    _committed: Option<RwLockReadGuard<'a, Committed>>,
+}
+
+impl Deref for Writable<'_> {
+    type Target = MemoryInner;
+
+    fn deref(&self) -> &MemoryInner {
+        &self.state
+    }
+}
+
+impl MemoryInner {
+    fn new() -> Self {
+        Self {
+            nodes: RwLock::new(HashMap::new()),
+            label_index: RwLock::new(HashMap::new()),
+            property_index: RwLock::new(HashMap::new()),
+            relationships: RwLock::new(HashMap::new()),
+            rel_type_counts: RwLock::new(HashMap::new()),
+            adjacency: RwLock::new(HashMap::new()),
+            next_node_id: AtomicU64::new(1),
+            free_node_ids: Mutex::new(BTreeSet::new()),
+            next_rel_id: AtomicU64::new(1),
+        }
+    }
+
+    /// A deep copy, for an isolated transaction to write to.
+    fn fork(&self) -> Self {
+        Self {
+            nodes: RwLock::new(self.nodes.read().clone()),
+            label_index: RwLock::new(self.label_index.read().clone()),
+            property_index: RwLock::new(self.property_index.read().clone()),
+            relationships: RwLock::new(self.relationships.read().clone()),
+            rel_type_counts: RwLock::new(self.rel_type_counts.read().clone()),
+            adjacency: RwLock::new(self.adjacency.read().clone()),
+            next_node_id: AtomicU64::new(self.next_node_id.load(Ordering::Relaxed)),
+            free_node_ids: Mutex::new(self.free_node_ids.lock().clone()),
+            next_rel_id: AtomicU64::new(self.next_rel_id.load(Ordering::Relaxed)),
+        }
+    }
 }
 
 impl MemoryBackend {
     pub fn new() -> Self {
         Self {
-            inner: Arc::new(MemoryInner {
-                nodes: RwLock::new(HashMap::new()),
-                relationships: RwLock::new(HashMap::new()),
-                adjacency: RwLock::new(HashMap::new()),
-                label_index: RwLock::new(HashMap::new()),
-                next_node_id: AtomicU64::new(1),
-                next_rel_id: AtomicU64::new(1),
-                next_tx_id: AtomicU64::new(1),
-            }),
+            committed: RwLock::new(Committed::new(0, Arc::new(MemoryInner::new()))),
+            isolated: false,
+            next_tx_id: AtomicU64::new(1),
+            expand_limits: ExpandLimits::default(),
+            case_insensitive_rel_types: false,
+            reuse_node_ids: false,
+            procedures: RwLock::new(builtin_procedures()),
+            wal: None,
+        }
+    }
+
+    /// Override the depth and path limits used by `expand()`.
+    pub fn with_expand_limits(mut self, limits: ExpandLimits) -> Self {
+        self.expand_limits = limits;
+        self
+    }
+
+    /// Match relationship types ignoring ASCII case, so `[:knows]` finds
+    /// `KNOWS` relationships. Off by default. Applies to the type filters of
+    /// `get_relationships[_typed]`, `relationships_by_type` and `expand()`;
+    /// relationships keep the type they were created with.
+    pub fn with_case_insensitive_rel_types(mut self, enabled: bool) -> Self {
+        self.case_insensitive_rel_types = enabled;
+        self
+    }
+
+    /// Give new nodes the lowest id freed by a deleted node, if any, instead
+    /// of always the next unused one. Off by default, so ids are never
+    /// reused and stay stable references. With it on, import/delete cycles
+    /// keep ids (and anything sized by the largest id) bounded by the live
+    /// node count. Ids missing below the next unused one when this is
+    /// turned on count as freed.
+    pub fn with_node_id_reuse(mut self, enabled: bool) -> Self {
+        self.reuse_node_ids = enabled;
+        let inner = self.current();
+        let mut free = inner.free_node_ids.lock();
+        free.clear();
+        if enabled {
+            let nodes = inner.nodes.read();
+            let next = inner.next_node_id.load(Ordering::Relaxed);
+            free.extend((1..next).map(NodeId).filter(|id| !nodes.contains_key(id)));
+        }
+        drop(free);
+        self
+    }
+
+    /// Give each read-write transaction its own copy of the graph, made at
+    /// `begin_tx`, and publish it on commit. Off by default, where every
+    /// transaction writes straight to the shared graph.
+    ///
+    /// With it on, a transaction reads its own writes and no one else's
+    /// uncommitted ones, and rollback discards the transaction's writes. A commit
+    /// fails with a write conflict (see `Error::is_write_conflict`) if
+    /// another read-write transaction committed after this one began; one
+    /// that wrote nothing always commits. Writes reach the write-ahead log
+    /// on commit. Copying makes each read-write transaction cost time and
+    /// memory in proportion to the graph.
+    pub fn with_isolated_transactions(mut self, enabled: bool) -> Self {
+        self.isolated = enabled;
+        self
+    }
+
+    /// Make `CALL name(...)` run `handler`, replacing any procedure already
+    /// registered under `name`. Names match ignoring ASCII case. `db.labels`,
+    /// `db.relationshipTypes` and `db.propertyKeys` (and with the `chess`
+    /// feature, the `chess.*` procedures) are registered from the start.
+    ///
+    /// The handler gets the backend and the calling query's transaction, so
+    /// it can read and write the graph through `StorageBackend`. The planner
+    /// can't tell a registered procedure writes, so `Graph::execute` runs it
+    /// read-only: with isolated transactions, call writing ones in a
+    /// read-write `Graph::begin` transaction.
+    ///
+    /// ```rust,no_run
+    /// use neo4j_rs::storage::{MemoryBackend, ProcedureResult, StorageBackend};
+    /// use neo4j_rs::{PropertyMap, Value};
+    ///
+    /// let backend = MemoryBackend::new();
+    /// backend.register_procedure("my.touch", |backend, tx, _args| {
+    ///     Box::pin(async move {
+    ///         let id = backend.create_node(tx, &["Touched"], PropertyMap::new()).await?;
+    ///         let row = [("id".to_string(), Value::Int(id.0 as i64))].into();
+    ///         Ok(ProcedureResult { columns: vec!["id".into()], rows: vec![row] })
+    ///     })
+    /// });
+    /// ```
+    pub fn register_procedure<F>(&self, name: &str, handler: F)
+    where
+        F: for<'a> Fn(&'a MemoryBackend, &'a mut MemoryTx, Vec<Value>) -> ProcedureFuture<'a> + Send + Sync + 'static,
+    {
+        self.procedures.write().insert(name.to_ascii_lowercase(), Arc::new(handler));
+    }
+
+    /// The committed graph.
+    fn current(&self) -> Arc<MemoryInner> {
+        Arc::clone(&self.committed.read().state)
+    }
+
+    /// The state `tx` reads: its snapshot or private copy, or for a shared
+    /// read-write transaction the graph as it is now.
+    fn readable(&self, tx: &MemoryTx) -> Arc<MemoryInner> {
+        if self.isolated || tx.snapshot.is_some() {
+            Arc::clone(&tx.state)
+        } else {
+            self.current()
+        }
+    }
+
+    /// The state `tx` writes to. Isolated read-only transactions share the
+    /// committed graph, so they can't write. In the shared graph, a state
+    /// some read-only transaction began on is copied first; that costs time
+    /// and memory in proportion to the graph, once per state a reader holds.
+    fn writable(&self, tx: &mut MemoryTx) -> Result<Writable<'_>> {
+        if self.isolated {
+            if tx.mode == TxMode::ReadOnly {
+                return Err(Error::TxError("cannot write in a read-only transaction".into()));
+            }
+            return Ok(Writable { state: Arc::clone(&tx.state), _committed: None });
+        }
+        // A read-only transaction that writes leaves its snapshot, so it
+        // reads what it wrote
+        tx.snapshot = None;
+        let mut committed = self.committed.read();
+        if Arc::strong_count(&committed.readers) > 1 {
+            drop(committed);
+            let mut exclusive = self.committed.write();
+            if Arc::strong_count(&exclusive.readers) > 1 {
+                let state = Arc::new(exclusive.state.fork());
+                *exclusive = Committed::new(exclusive.version, state);
+            }
+            committed = RwLockWriteGuard::downgrade(exclusive);
+        }
+        tx.state = Arc::clone(&committed.state);
+        Ok(Writable { state: Arc::clone(&committed.state), _committed: Some(committed) })
+    }
+
+    fn rel_type_matches(&self, rel_type: &str, wanted: &str) -> bool {
+        if self.case_insensitive_rel_types {
+            rel_type.eq_ignore_ascii_case(wanted)
+        } else {
+            rel_type == wanted
+        }
+    }
+
+    /// Open the backend `config` describes, which must be `Memory` or
+    /// `DurableMemory`.
+    pub async fn open(config: &BackendConfig) -> Result<Self> {
+        let (backend, case_insensitive_rel_types) = match config {
+            BackendConfig::Memory { case_insensitive_rel_types } => (Self::new(), *case_insensitive_rel_types),
+            BackendConfig::DurableMemory { wal_path, fsync, case_insensitive_rel_types } => {
+                (Self::open_durable(wal_path, *fsync).await?, *case_insensitive_rel_types)
+            }
+            #[allow(unreachable_patterns)] // without the bolt or ladybug features
+            _ => return Err(Error::StorageError("not an in-memory backend configuration".into())),
+        };
+        Ok(backend.with_case_insensitive_rel_types(case_insensitive_rel_types))
+    }
+
+    /// Open a backend that logs every write to the write-ahead log at
+    /// `wal_path`, first rebuilding whatever state the log (and its
+    /// snapshot, if any) already holds.
+    pub async fn open_durable(wal_path: impl AsRef<std::path::Path>, fsync: FsyncPolicy) -> Result<Self> {
+        let (wal, snapshot, records) = Wal::open(wal_path.as_ref(), fsync)?;
+        let backend = Self::new();
+        let mut tx = backend.begin_tx(TxMode::ReadWrite).await?;
+        if let Some(snapshot) = snapshot {
+            backend.restore(&mut tx, snapshot).await?;
+        }
+        for record in records {
+            backend.replay(&mut tx, record).await?;
+        }
+        Ok(Self { wal: Some(wal), ..backend })
+    }
+
+    /// Write the whole graph to the write-ahead log's snapshot and empty
+    /// the log, so the next open replays only what comes after.
+    ///
+    /// Only this call checkpoints; the log is never compacted by size or
+    /// on shutdown. Through a `Graph`, call `graph.backend().checkpoint()`.
+    pub fn checkpoint(&self) -> Result<()> {
+        let wal = self.wal.as_ref()
+            .ok_or_else(|| Error::StorageError("checkpoint requires a write-ahead log".into()))?;
+        // Every mutation write-locks one of these, so holding them all
+        // keeps writers out while the snapshot is taken; holding `committed`
+        // keeps them from moving to a copy of the graph.
+        let committed = self.committed.read();
+        let inner = &committed.state;
+        let nodes = inner.nodes.read();
+        let props = inner.property_index.read();
+        let rels = inner.relationships.read();
+        wal.checkpoint(|lsn| {
+            let mut nodes: Vec<Node> = nodes.values().cloned().collect();
+            nodes.sort_by_key(|n| n.id);
+            let mut relationships: Vec<Relationship> = rels.values().cloned().collect();
+            relationships.sort_by_key(|r| r.id.0);
+            Snapshot {
+                lsn,
+                next_node_id: inner.next_node_id.load(Ordering::Relaxed),
+                next_rel_id: inner.next_rel_id.load(Ordering::Relaxed),
+                nodes,
+                relationships,
+                indexes: props.iter().map(|((label, key), index)| (label.clone(), key.clone(), index.index_type)).collect(),
+            }
+        })
+    }
+
+    /// Create a node under `id`, as replaying the log does, or under a
+    /// newly allocated id.
+    fn insert_node(&self, tx: &mut MemoryTx, id: Option<NodeId>, labels: &[&str], props: PropertyMap) -> Result<NodeId> {
+        let inner = self.writable(tx)?;
+        // BUNDLE: acquire all locks before any mutation so concurrent
+        // readers never see partial state (node without index, or
+        // index without adjacency entry).
+        let mut nodes = inner.nodes.write();
+        let mut idx = inner.label_index.write();
+        let mut prop_index = inner.property_index.write();
+        let mut adj = inner.adjacency.write();
+        let mut free = inner.free_node_ids.lock();
+
+        // Allocated under the locks so ids reach the log in order
+        let id = match id {
+            Some(id) => {
+                free.remove(&id);
+                inner.next_node_id.fetch_max(id.0 + 1, Ordering::Relaxed);
+                id
+            }
+            None => match free.pop_first() {
+                Some(id) => id,
+                None => NodeId(inner.next_node_id.fetch_add(1, Ordering::Relaxed)),
+            },
+        };
+        drop(free);
+        let node = Node {
+            id,
+            element_id: None,
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            properties: props,
+        };
+        self.log(tx, || WalRecord::CreateNode(node.clone()))?;
+
+        for label in &node.labels {
+            idx.entry(label.clone()).or_default().push(id);
+        }
+        index_node(&mut prop_index, &node, true);
+        nodes.insert(id, node);
+        adj.insert(id, Vec::new());
+
+        Ok(id)
+    }
+
+    /// Log a write made in `tx`: now, or for an isolated transaction, on
+    /// commit.
+    fn log(&self, tx: &mut MemoryTx, record: impl FnOnce() -> WalRecord) -> Result<()> {
+        match &mut tx.isolation {
+            Some(isolation) => {
+                isolation.dirty = true;
+                if self.wal.is_some() {
+                    isolation.pending.push(record());
+                }
+                Ok(())
+            }
+            None => self.append(record),
+        }
+    }
+
+    fn append(&self, record: impl FnOnce() -> WalRecord) -> Result<()> {
+        match &self.wal {
+            Some(wal) => wal.append(record()),
+            None => Ok(()),
+        }
+    }
+
+    async fn restore(&self, tx: &mut MemoryTx, snapshot: Snapshot) -> Result<()> {
+        for node in snapshot.nodes {
+            self.replay(tx, WalRecord::CreateNode(node)).await?;
+        }
+        for rel in snapshot.relationships {
+            self.replay(tx, WalRecord::CreateRelationship(rel)).await?;
         }
+        for (label, property, index_type) in snapshot.indexes {
+            self.create_index(&label, &property, index_type).await?;
+        }
+        tx.state.next_node_id.store(snapshot.next_node_id, Ordering::Relaxed);
+        tx.state.next_rel_id.store(snapshot.next_rel_id, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Apply a logged mutation; created entities keep their logged ids.
+    async fn replay(&self, tx: &mut MemoryTx, record: WalRecord) -> Result<()> {
+        match record {
+            WalRecord::CreateNode(node) => {
+                let labels: Vec<&str> = node.labels.iter().map(String::as_str).collect();
+                self.insert_node(tx, Some(node.id), &labels, node.properties)?;
+            }
+            WalRecord::DeleteNode(id) => {
+                self.delete_node(tx, id).await?;
+            }
+            WalRecord::SetNodeProperty { id, key, value } => self.set_node_property(tx, id, &key, value).await?,
+            WalRecord::RemoveNodeProperty { id, key } => self.remove_node_property(tx, id, &key).await?,
+            WalRecord::AddLabel { id, label } => self.add_label(tx, id, &label).await?,
+            WalRecord::RemoveLabel { id, label } => self.remove_label(tx, id, &label).await?,
+            WalRecord::CreateRelationship(rel) => {
+                tx.state.next_rel_id.store(rel.id.0, Ordering::Relaxed);
+                self.create_relationship(tx, rel.src, rel.dst, &rel.rel_type, rel.properties).await?;
+            }
+            WalRecord::SetRelationshipProperty { id, key, value } => {
+                self.set_relationship_property(tx, id, &key, value).await?
+            }
+            WalRecord::RemoveRelationshipProperty { id, key } => {
+                self.remove_relationship_property(tx, id, &key).await?
+            }
+            WalRecord::DeleteRelationship(id) => {
+                self.delete_relationship(tx, id).await?;
+            }
+            WalRecord::CreateIndex { label, property, index_type } => {
+                self.create_index(&label, &property, index_type).await?
+            }
+            WalRecord::DropIndex { label, property } => self.drop_index(&label, &property).await?,
+        }
+        Ok(())
     }
 }
 
+// ============================================================================
+// Property index
+// ============================================================================
+
+/// Sorted index over one (label, property). Ints and floats share one
+/// numeric order, as in `Value::neo4j_cmp`; other value types aren't indexed.
+#[derive(Clone)]
+struct PropertyIndex {
+    /// `BTree` or `Unique`, as created.
+    index_type: IndexType,
+    numbers: BTreeMap<NumberKey, BTreeSet<NodeId>>,
+    strings: BTreeMap<String, BTreeSet<NodeId>>,
+}
+
+/// `f64` with a total order, `-0.0` folded into `0.0`.
+#[derive(Debug, Clone, Copy)]
+struct NumberKey(f64);
+
+impl NumberKey {
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Int(i) => Some(Self(*i as f64 + 0.0)),
+            Value::Float(f) => Some(Self(*f + 0.0)),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for NumberKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for NumberKey {}
+
+impl PartialOrd for NumberKey {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NumberKey {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl PropertyIndex {
+    fn new(index_type: IndexType) -> Self {
+        Self { index_type, numbers: BTreeMap::new(), strings: BTreeMap::new() }
+    }
+
+    fn update(&mut self, value: &Value, id: NodeId, insert: bool) {
+        fn apply<K: Ord>(map: &mut BTreeMap<K, BTreeSet<NodeId>>, key: K, id: NodeId, insert: bool) {
+            if insert {
+                map.entry(key).or_default().insert(id);
+            } else if let Some(ids) = map.get_mut(&key) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    map.remove(&key);
+                }
+            }
+        }
+        match value {
+            Value::String(s) => apply(&mut self.strings, s.clone(), id, insert),
+            v => {
+                if let Some(key) = NumberKey::of(v) {
+                    apply(&mut self.numbers, key, id, insert);
+                }
+            }
+        }
+    }
+
+    /// Candidate ids for `lower..upper`, or `None` when the bounds aren't
+    /// of an indexed type. Numeric bounds are widened to inclusive, since
+    /// large ints lose precision as keys; callers re-check each value.
+    fn candidates(&self, lower: Bound<&Value>, upper: Bound<&Value>) -> Option<BTreeSet<NodeId>> {
+        fn collect<'a, K: Ord + 'a>(
+            map: &'a BTreeMap<K, BTreeSet<NodeId>>,
+            lower: Bound<K>,
+            upper: Bound<K>,
+        ) -> BTreeSet<NodeId> {
+            let empty = match (&lower, &upper) {
+                (Bound::Included(l), Bound::Included(u)) => l > u,
+                (Bound::Included(l) | Bound::Excluded(l), Bound::Included(u) | Bound::Excluded(u)) => l >= u,
+                _ => false,
+            };
+            if empty {
+                return BTreeSet::new();
+            }
+            map.range((lower, upper)).flat_map(|(_, ids)| ids.iter().copied()).collect()
+        }
+
+        let bound_value = match (lower, upper) {
+            (Bound::Included(v) | Bound::Excluded(v), _) | (_, Bound::Included(v) | Bound::Excluded(v)) => v,
+            (Bound::Unbounded, Bound::Unbounded) => return None,
+        };
+        if matches!(bound_value, Value::String(_)) {
+            let key = |b: Bound<&Value>| match b {
+                Bound::Included(Value::String(s)) => Some(Bound::Included(s.clone())),
+                Bound::Excluded(Value::String(s)) => Some(Bound::Excluded(s.clone())),
+                Bound::Unbounded => Some(Bound::Unbounded),
+                _ => None,
+            };
+            return Some(match (key(lower), key(upper)) {
+                (Some(l), Some(u)) => collect(&self.strings, l, u),
+                // A string bound and a non-string bound: nothing compares with both
+                _ => BTreeSet::new(),
+            });
+        }
+        NumberKey::of(bound_value)?;
+        let key = |b: Bound<&Value>| match b {
+            Bound::Included(v) | Bound::Excluded(v) => NumberKey::of(v).map(Bound::Included),
+            Bound::Unbounded => Some(Bound::Unbounded),
+        };
+        Some(match (key(lower), key(upper)) {
+            (Some(l), Some(u)) => collect(&self.numbers, l, u),
+            _ => BTreeSet::new(),
+        })
+    }
+}
+
+/// An index over the `property` values of `label` nodes.
+fn build_index(nodes: &HashMap<NodeId, Node>, label: &str, property: &str, index_type: IndexType) -> PropertyIndex {
+    let mut index = PropertyIndex::new(index_type);
+    for node in nodes.values().filter(|n| n.labels.iter().any(|l| l == label)) {
+        if let Some(value) = node.properties.get(property) {
+            index.update(value, node.id, true);
+        }
+    }
+    index
+}
+
+/// Give `state` the property indexes of `committed`, dropping any others
+/// and building any it lacks from its own nodes.
+fn sync_indexes(committed: &MemoryInner, state: &MemoryInner) {
+    let wanted: Vec<((String, String), IndexType)> = committed.property_index.read()
+        .iter()
+        .map(|(key, index)| (key.clone(), index.index_type))
+        .collect();
+    let nodes = state.nodes.read();
+    let mut props = state.property_index.write();
+    props.retain(|key, index| wanted.contains(&(key.clone(), index.index_type)));
+    for ((label, property), index_type) in wanted {
+        if !props.contains_key(&(label.clone(), property.clone())) {
+            let index = build_index(&nodes, &label, &property, index_type);
+            props.insert((label, property), index);
+        }
+    }
+}
+
+/// Add (or with `insert == false`, remove) `node`'s values in every index
+/// that covers one of its labels.
+fn index_node(indexes: &mut HashMap<(String, String), PropertyIndex>, node: &Node, insert: bool) {
+    for ((label, key), index) in indexes.iter_mut() {
+        if let Some(value) = node.properties.get(key)
+            && node.labels.contains(label)
+        {
+            index.update(value, node.id, insert);
+        }
+    }
+}
+
+// ============================================================================
+// Procedures
+// ============================================================================
+
+/// What a procedure registered with `MemoryBackend::register_procedure`
+/// returns.
+pub type ProcedureFuture<'a> = Pin<Box<dyn Future<Output = Result<ProcedureResult>> + Send + 'a>>;
+
+type ProcedureHandler =
+    Arc<dyn for<'a> Fn(&'a MemoryBackend, &'a mut MemoryTx, Vec<Value>) -> ProcedureFuture<'a> + Send + Sync>;
+
+fn builtin_procedures() -> HashMap<String, ProcedureHandler> {
+    let mut procedures: HashMap<String, ProcedureHandler> = HashMap::new();
+    procedures.insert("db.labels".into(), Arc::new(db_labels));
+    procedures.insert("db.relationshiptypes".into(), Arc::new(db_relationship_types));
+    procedures.insert("db.propertykeys".into(), Arc::new(db_property_keys));
+    #[cfg(feature = "chess")]
+    for (name, procedure) in crate::chess::register_chess_procedures() {
+        let handler: ProcedureHandler = Arc::new(move |_: &MemoryBackend, _: &mut MemoryTx, args: Vec<Value>| -> ProcedureFuture<'_> {
+            Box::pin(std::future::ready(procedure(args)))
+        });
+        procedures.insert(name.to_ascii_lowercase(), handler);
+    }
+    procedures
+}
+
+/// `CALL db.labels() YIELD label`: the labels some node has, sorted.
+fn db_labels<'a>(backend: &'a MemoryBackend, tx: &'a mut MemoryTx, args: Vec<Value>) -> ProcedureFuture<'a> {
+    let labels = backend.readable(tx).label_index.read()
+        .iter()
+        .filter(|(_, ids)| !ids.is_empty())
+        .map(|(label, _)| label.clone())
+        .collect::<BTreeSet<_>>();
+    Box::pin(std::future::ready(single_column("db.labels", &args, "label", labels)))
+}
+
+/// `CALL db.relationshipTypes() YIELD relationshipType`, sorted.
+fn db_relationship_types<'a>(backend: &'a MemoryBackend, tx: &'a mut MemoryTx, args: Vec<Value>) -> ProcedureFuture<'a> {
+    let types = backend.readable(tx).rel_type_counts.read()
+        .iter()
+        .filter(|(_, count)| **count > 0)
+        .map(|(rel_type, _)| rel_type.clone())
+        .collect::<BTreeSet<_>>();
+    Box::pin(std::future::ready(single_column("db.relationshipTypes", &args, "relationshipType", types)))
+}
+
+/// `CALL db.propertyKeys() YIELD propertyKey`: the keys some node or
+/// relationship has, sorted.
+fn db_property_keys<'a>(backend: &'a MemoryBackend, tx: &'a mut MemoryTx, args: Vec<Value>) -> ProcedureFuture<'a> {
+    let state = backend.readable(tx);
+    let mut keys: BTreeSet<String> = state.nodes.read().values().flat_map(|n| n.properties.keys().cloned()).collect();
+    keys.extend(state.relationships.read().values().flat_map(|r| r.properties.keys().cloned()));
+    Box::pin(std::future::ready(single_column("db.propertyKeys", &args, "propertyKey", keys)))
+}
+
+fn single_column(name: &str, args: &[Value], column: &str, values: BTreeSet<String>) -> Result<ProcedureResult> {
+    if !args.is_empty() {
+        return Err(Error::ExecutionError(format!("{name}() takes no arguments, got {}", args.len())));
+    }
+    Ok(ProcedureResult {
+        columns: vec![column.to_string()],
+        rows: values.into_iter().map(|v| HashMap::from([(column.to_string(), Value::String(v))])).collect(),
+    })
+}
+
 // ============================================================================
 // MemoryTx
 // ============================================================================
 
-/// In-memory transaction (currently just a marker — no real MVCC).
+/// In-memory transaction: the graph state it reads and writes.
 pub struct MemoryTx {
     id: TxId,
     mode: TxMode,
+    /// The committed graph as of `begin_tx`, or with isolated transactions
+    /// a private copy of it (read-write).
+    state: Arc<MemoryInner>,
+    /// Set for read-only transactions: a clone of `Committed::readers`,
+    /// keeping writers off `state`.
+    snapshot: Option<Arc<()>>,
+    /// Set for isolated read-write transactions.
+    isolation: Option<Isolation>,
+}
+
+struct Isolation {
+    /// `Committed::version` when the transaction began.
+    version: u64,
+    /// Whether the transaction wrote anything.
+    dirty: bool,
+    /// Log records to append on commit.
+    pending: Vec<WalRecord>,
 }
 
 impl Transaction for MemoryTx {
@@ -92,15 +754,44 @@ impl StorageBackend for MemoryBackend {
     async fn shutdown(&self) -> Result<()> { Ok(()) }
 
     async fn begin_tx(&self, mode: TxMode) -> Result<MemoryTx> {
-        let id = TxId(self.inner.next_tx_id.fetch_add(1, Ordering::Relaxed));
-        Ok(MemoryTx { id, mode })
+        let id = TxId(self.next_tx_id.fetch_add(1, Ordering::Relaxed));
+        if mode == TxMode::ReadOnly {
+            // Locked for writing, so no write is halfway through the state
+            let committed = self.committed.write();
+            let snapshot = Some(Arc::clone(&committed.readers));
+            return Ok(MemoryTx { id, mode, state: Arc::clone(&committed.state), snapshot, isolation: None });
+        }
+        let committed = self.committed.read();
+        if !self.isolated {
+            return Ok(MemoryTx { id, mode, state: Arc::clone(&committed.state), snapshot: None, isolation: None });
+        }
+        let isolation = Isolation { version: committed.version, dirty: false, pending: Vec::new() };
+        Ok(MemoryTx { id, mode, state: Arc::new(committed.state.fork()), snapshot: None, isolation: Some(isolation) })
     }
 
-    /// No-op: memory backend applies writes immediately, not on commit.
-    async fn commit_tx(&self, _tx: MemoryTx) -> Result<()> { Ok(()) }
+    /// Publishes an isolated transaction's writes; otherwise a no-op, as
+    /// writes are applied immediately.
+    async fn commit_tx(&self, tx: MemoryTx) -> Result<()> {
+        let Some(isolation) = tx.isolation.filter(|i| i.dirty) else {
+            return Ok(());
+        };
+        let mut committed = self.committed.write();
+        if committed.version != isolation.version {
+            return Err(Error::TxError("write conflict".into()));
+        }
+        // Indexes are schema, created and dropped on the committed graph:
+        // carry the ones made since this transaction began
+        sync_indexes(&committed.state, &tx.state);
+        for record in isolation.pending {
+            self.append(|| record)?;
+        }
+        *committed = Committed::new(committed.version + 1, tx.state);
+        Ok(())
+    }
 
-    /// WARNING: No-op. Memory backend has no write-ahead log.
-    /// Mutations applied during this transaction are NOT reverted.
+    /// Discards an isolated transaction's writes. WARNING: otherwise a
+    /// no-op; mutations applied during the transaction are NOT reverted,
+    /// and stay in the write-ahead log if there is one.
     async fn rollback_tx(&self, _tx: MemoryTx) -> Result<()> { Ok(()) }
 
     // ========================================================================
@@ -109,59 +800,50 @@ impl StorageBackend for MemoryBackend {
 
     async fn create_node(
         &self,
-        _tx: &mut MemoryTx,
+        tx: &mut MemoryTx,
         labels: &[&str],
         props: PropertyMap,
     ) -> Result<NodeId> {
-        let id = NodeId(self.inner.next_node_id.fetch_add(1, Ordering::Relaxed));
-        let node = Node {
-            id,
-            element_id: None,
-            labels: labels.iter().map(|l| l.to_string()).collect(),
-            properties: props,
-        };
-
-        // BUNDLE: acquire all locks before any mutation so concurrent
-        // readers never see partial state (node without index, or
-        // index without adjacency entry).
-        let mut idx = self.inner.label_index.write();
-        let mut nodes = self.inner.nodes.write();
-        let mut adj = self.inner.adjacency.write();
-
-        for label in &node.labels {
-            idx.entry(label.clone()).or_default().push(id);
-        }
-        nodes.insert(id, node);
-        adj.insert(id, Vec::new());
+        self.insert_node(tx, None, labels, props)
+    }
 
-        Ok(id)
+    async fn get_node(&self, tx: &MemoryTx, id: NodeId) -> Result<Option<Node>> {
+        let inner = self.readable(tx);
+        Ok(inner.nodes.read().get(&id).cloned())
     }
 
-    async fn get_node(&self, _tx: &MemoryTx, id: NodeId) -> Result<Option<Node>> {
-        Ok(self.inner.nodes.read().get(&id).cloned())
+    async fn get_nodes(&self, tx: &MemoryTx, ids: &[NodeId]) -> Result<Vec<Option<Node>>> {
+        let inner = self.readable(tx);
+        let nodes = inner.nodes.read();
+        Ok(ids.iter().map(|id| nodes.get(id).cloned()).collect())
     }
 
-    async fn delete_node(&self, _tx: &mut MemoryTx, id: NodeId) -> Result<bool> {
+    async fn delete_node(&self, tx: &mut MemoryTx, id: NodeId) -> Result<bool> {
+        let inner = self.writable(tx)?;
         // BUNDLE: acquire all locks before any mutation.
         // Prevents race where adjacency check passes but concurrent
         // create_relationship adds an edge before we remove the node.
-        let mut adj = self.inner.adjacency.write();
-        let mut nodes = self.inner.nodes.write();
-        let mut idx = self.inner.label_index.write();
+        let mut nodes = inner.nodes.write();
+        let mut idx = inner.label_index.write();
+        let mut props = inner.property_index.write();
+        let mut adj = inner.adjacency.write();
 
         // Check for existing relationships (Neo4j semantics: can't delete connected node)
-        if let Some(rels) = adj.get(&id) {
-            if !rels.is_empty() {
-                return Err(Error::ConstraintViolation(
-                    format!("Cannot delete node {id} with {} relationships. Delete relationships first.", rels.len())
-                ));
-            }
+        if adj.get(&id).is_some_and(|rels| !rels.is_empty()) {
+            return Err(node_still_has_relationships(id));
+        }
+        if nodes.contains_key(&id) {
+            self.log(tx, || WalRecord::DeleteNode(id))?;
         }
 
         let removed = nodes.remove(&id);
         adj.remove(&id);
+        if self.reuse_node_ids && removed.is_some() {
+            inner.free_node_ids.lock().insert(id);
+        }
 
         if let Some(node) = &removed {
+            index_node(&mut props, node, false);
             for label in &node.labels {
                 if let Some(ids) = idx.get_mut(label) {
                     ids.retain(|nid| *nid != id);
@@ -174,49 +856,94 @@ impl StorageBackend for MemoryBackend {
 
     async fn set_node_property(
         &self,
-        _tx: &mut MemoryTx,
+        tx: &mut MemoryTx,
         id: NodeId,
         key: &str,
         val: Value,
     ) -> Result<()> {
-        let mut nodes = self.inner.nodes.write();
+        let inner = self.writable(tx)?;
+        let mut nodes = inner.nodes.write();
+        let mut props = inner.property_index.write();
         let node = nodes.get_mut(&id).ok_or_else(|| Error::NotFound(format!("Node {id}")))?;
+        self.log(tx, || WalRecord::SetNodeProperty { id, key: key.to_string(), value: val.clone() })?;
+        index_node(&mut props, node, false);
         node.properties.insert(key.to_string(), val);
+        index_node(&mut props, node, true);
         Ok(())
     }
 
     async fn remove_node_property(
         &self,
-        _tx: &mut MemoryTx,
+        tx: &mut MemoryTx,
         id: NodeId,
         key: &str,
     ) -> Result<()> {
-        let mut nodes = self.inner.nodes.write();
+        let inner = self.writable(tx)?;
+        let mut nodes = inner.nodes.write();
+        let mut props = inner.property_index.write();
         let node = nodes.get_mut(&id).ok_or_else(|| Error::NotFound(format!("Node {id}")))?;
+        self.log(tx, || WalRecord::RemoveNodeProperty { id, key: key.to_string() })?;
+        index_node(&mut props, node, false);
         node.properties.remove(key);
+        index_node(&mut props, node, true);
         Ok(())
     }
 
-    async fn add_label(&self, _tx: &mut MemoryTx, id: NodeId, label: &str) -> Result<()> {
+    /// Reindexes the node once, after all the keys; logged one key at a
+    /// time, in key order.
+    async fn set_node_properties(&self, tx: &mut MemoryTx, id: NodeId, props: PropertyMap) -> Result<()> {
+        let inner = self.writable(tx)?;
+        let mut nodes = inner.nodes.write();
+        let mut prop_index = inner.property_index.write();
+        let node = nodes.get_mut(&id).ok_or_else(|| Error::NotFound(format!("Node {id}")))?;
+        let mut props: Vec<(String, Value)> = props.into_iter().collect();
+        props.sort_by(|a, b| a.0.cmp(&b.0));
+        index_node(&mut prop_index, node, false);
+        for (key, value) in props {
+            if value.is_null() {
+                self.log(tx, || WalRecord::RemoveNodeProperty { id, key: key.clone() })?;
+                node.properties.remove(&key);
+            } else {
+                self.log(tx, || WalRecord::SetNodeProperty { id, key: key.clone(), value: value.clone() })?;
+                node.properties.insert(key, value);
+            }
+        }
+        index_node(&mut prop_index, node, true);
+        Ok(())
+    }
+
+    async fn add_label(&self, tx: &mut MemoryTx, id: NodeId, label: &str) -> Result<()> {
+        let inner = self.writable(tx)?;
         // BUNDLE: acquire both locks before any mutation to prevent
         // concurrent readers seeing node with label but missing index entry.
-        let mut nodes = self.inner.nodes.write();
-        let mut idx = self.inner.label_index.write();
+        let mut nodes = inner.nodes.write();
+        let mut idx = inner.label_index.write();
+        let mut props = inner.property_index.write();
         let node = nodes.get_mut(&id).ok_or_else(|| Error::NotFound(format!("Node {id}")))?;
         if !node.labels.contains(&label.to_string()) {
+            self.log(tx, || WalRecord::AddLabel { id, label: label.to_string() })?;
+            index_node(&mut props, node, false);
             node.labels.push(label.to_string());
+            index_node(&mut props, node, true);
             idx.entry(label.to_string()).or_default().push(id);
         }
         Ok(())
     }
 
-    async fn remove_label(&self, _tx: &mut MemoryTx, id: NodeId, label: &str) -> Result<()> {
+    async fn remove_label(&self, tx: &mut MemoryTx, id: NodeId, label: &str) -> Result<()> {
+        let inner = self.writable(tx)?;
         // BUNDLE: acquire both locks atomically — no gap between
         // node mutation and index cleanup.
-        let mut nodes = self.inner.nodes.write();
-        let mut idx = self.inner.label_index.write();
+        let mut nodes = inner.nodes.write();
+        let mut idx = inner.label_index.write();
+        let mut props = inner.property_index.write();
         let node = nodes.get_mut(&id).ok_or_else(|| Error::NotFound(format!("Node {id}")))?;
+        if node.labels.iter().any(|l| l == label) {
+            self.log(tx, || WalRecord::RemoveLabel { id, label: label.to_string() })?;
+        }
+        index_node(&mut props, node, false);
         node.labels.retain(|l| l != label);
+        index_node(&mut props, node, true);
         if let Some(ids) = idx.get_mut(label) {
             ids.retain(|nid| *nid != id);
         }
@@ -229,18 +956,19 @@ impl StorageBackend for MemoryBackend {
 
     async fn create_relationship(
         &self,
-        _tx: &mut MemoryTx,
+        tx: &mut MemoryTx,
         src: NodeId,
         dst: NodeId,
         rel_type: &str,
         props: PropertyMap,
     ) -> Result<RelId> {
+        let inner = self.writable(tx)?;
         // BUNDLE: acquire all three locks before any mutation.
         // Prevents race where concurrent delete on src/dst between
         // validation and adjacency insert creates orphaned entries.
-        let nodes = self.inner.nodes.read();
-        let mut rels = self.inner.relationships.write();
-        let mut adj = self.inner.adjacency.write();
+        let nodes = inner.nodes.read();
+        let mut rels = inner.relationships.write();
+        let mut adj = inner.adjacency.write();
 
         if !nodes.contains_key(&src) {
             return Err(Error::NotFound(format!("Source node {src}")));
@@ -249,7 +977,7 @@ impl StorageBackend for MemoryBackend {
             return Err(Error::NotFound(format!("Target node {dst}")));
         }
 
-        let id = RelId(self.inner.next_rel_id.fetch_add(1, Ordering::Relaxed));
+        let id = RelId(inner.next_rel_id.fetch_add(1, Ordering::Relaxed));
         let rel = Relationship {
             id,
             element_id: None,
@@ -258,7 +986,9 @@ impl StorageBackend for MemoryBackend {
             rel_type: rel_type.to_string(),
             properties: props,
         };
+        self.log(tx, || WalRecord::CreateRelationship(rel.clone()))?;
 
+        *inner.rel_type_counts.write().entry(rel.rel_type.clone()).or_default() += 1;
         rels.insert(id, rel);
         adj.entry(src).or_default().push(id);
         if src != dst {
@@ -268,41 +998,59 @@ impl StorageBackend for MemoryBackend {
         Ok(id)
     }
 
-    async fn get_relationship(&self, _tx: &MemoryTx, id: RelId) -> Result<Option<Relationship>> {
-        Ok(self.inner.relationships.read().get(&id).cloned())
+    async fn get_relationship(&self, tx: &MemoryTx, id: RelId) -> Result<Option<Relationship>> {
+        let inner = self.readable(tx);
+        Ok(inner.relationships.read().get(&id).cloned())
     }
 
     async fn set_relationship_property(
         &self,
-        _tx: &mut MemoryTx,
+        tx: &mut MemoryTx,
         id: RelId,
         key: &str,
         val: Value,
     ) -> Result<()> {
-        let mut rels = self.inner.relationships.write();
+        let inner = self.writable(tx)?;
+        let mut rels = inner.relationships.write();
         let rel = rels.get_mut(&id)
             .ok_or_else(|| Error::NotFound(format!("Relationship {id}")))?;
+        self.log(tx, || WalRecord::SetRelationshipProperty { id, key: key.to_string(), value: val.clone() })?;
         rel.properties.insert(key.to_string(), val);
         Ok(())
     }
 
     async fn remove_relationship_property(
         &self,
-        _tx: &mut MemoryTx,
+        tx: &mut MemoryTx,
         id: RelId,
         key: &str,
     ) -> Result<()> {
-        let mut rels = self.inner.relationships.write();
+        let inner = self.writable(tx)?;
+        let mut rels = inner.relationships.write();
         let rel = rels.get_mut(&id)
             .ok_or_else(|| Error::NotFound(format!("Relationship {id}")))?;
+        self.log(tx, || WalRecord::RemoveRelationshipProperty { id, key: key.to_string() })?;
         rel.properties.remove(key);
         Ok(())
     }
 
-    async fn delete_relationship(&self, _tx: &mut MemoryTx, id: RelId) -> Result<bool> {
-        let removed = self.inner.relationships.write().remove(&id);
+    async fn delete_relationship(&self, tx: &mut MemoryTx, id: RelId) -> Result<bool> {
+        let inner = self.writable(tx)?;
+        let mut rels = inner.relationships.write();
+        if rels.contains_key(&id) {
+            self.log(tx, || WalRecord::DeleteRelationship(id))?;
+        }
+        let removed = rels.remove(&id);
         if let Some(rel) = &removed {
-            let mut adj = self.inner.adjacency.write();
+            let mut counts = inner.rel_type_counts.write();
+            if let Some(count) = counts.get_mut(&rel.rel_type) {
+                *count -= 1;
+                if *count == 0 {
+                    counts.remove(&rel.rel_type);
+                }
+            }
+            drop(counts);
+            let mut adj = inner.adjacency.write();
             if let Some(rels) = adj.get_mut(&rel.src) {
                 rels.retain(|rid| *rid != id);
             }
@@ -321,13 +1069,25 @@ impl StorageBackend for MemoryBackend {
 
     async fn get_relationships(
         &self,
-        _tx: &MemoryTx,
+        tx: &MemoryTx,
         node: NodeId,
         dir: Direction,
         rel_type: Option<&str>,
     ) -> Result<Vec<Relationship>> {
-        let adj = self.inner.adjacency.read();
-        let rels = self.inner.relationships.read();
+        self.get_relationships_typed(tx, node, dir, rel_type.as_slice()).await
+    }
+
+    /// One pass over the node's adjacency list, under one lock.
+    async fn get_relationships_typed(
+        &self,
+        tx: &MemoryTx,
+        node: NodeId,
+        dir: Direction,
+        rel_types: &[&str],
+    ) -> Result<Vec<Relationship>> {
+        let inner = self.readable(tx);
+        let rels = inner.relationships.read();
+        let adj = inner.adjacency.read();
 
         let rel_ids = adj.get(&node).cloned().unwrap_or_default();
         let mut result = Vec::new();
@@ -341,7 +1101,8 @@ impl StorageBackend for MemoryBackend {
                     Direction::Both => true,
                 };
                 // Type filter
-                let matches_type = rel_type.map_or(true, |t| rel.rel_type == t);
+                let matches_type = rel_types.is_empty()
+                    || rel_types.iter().any(|t| self.rel_type_matches(&rel.rel_type, t));
 
                 if matches_dir && matches_type {
                     result.push(rel.clone());
@@ -360,13 +1121,10 @@ impl StorageBackend for MemoryBackend {
         rel_types: &[&str],
         depth: ExpandDepth,
     ) -> Result<Vec<Path>> {
-        let (min_depth, max_depth) = match depth {
-            ExpandDepth::Exact(d) => (d, d),
-            ExpandDepth::Range { min, max } => (min, max),
-            ExpandDepth::Unbounded => (1, 100), // safety limit
-        };
+        let (min_depth, max_depth) = self.expand_limits.resolve(depth)?;
 
         let mut results = Vec::new();
+        let mut paths_built = 0usize;
         let start_node = self.get_node(tx, node).await?
             .ok_or_else(|| Error::NotFound(format!("Node {node}")))?;
 
@@ -382,7 +1140,7 @@ impl StorageBackend for MemoryBackend {
 
                 for rel in rels {
                     // Type filter
-                    if !rel_types.is_empty() && !rel_types.contains(&rel.rel_type.as_str()) {
+                    if !rel_types.is_empty() && !rel_types.iter().any(|t| self.rel_type_matches(&rel.rel_type, t)) {
                         continue;
                     }
 
@@ -394,6 +1152,10 @@ impl StorageBackend for MemoryBackend {
                     }
 
                     if let Some(next_node) = self.get_node(tx, next_id).await? {
+                        paths_built += 1;
+                        if paths_built > self.expand_limits.max_paths {
+                            return Err(expansion_limit_exceeded());
+                        }
                         let mut new_path = path.clone();
                         new_path.append(rel, next_node);
 
@@ -415,39 +1177,81 @@ impl StorageBackend for MemoryBackend {
     }
 
     // ========================================================================
-    // Index (stub for memory — label index is always maintained)
+    // Index (label index is always maintained; property indexes on demand)
     // ========================================================================
 
-    async fn create_index(&self, _label: &str, _property: &str, _index_type: IndexType) -> Result<()> {
-        // No-op: memory backend always full-scans. No real indexes are maintained.
+    async fn create_index(&self, label: &str, property: &str, index_type: IndexType) -> Result<()> {
+        // Only sorted indexes are kept; full-text and vector are no-ops.
+        if !matches!(index_type, IndexType::BTree | IndexType::Unique) {
+            return Ok(());
+        }
+        // Held so no commit replaces the graph in the meantime
+        let committed = self.committed.read();
+        let nodes = committed.state.nodes.read();
+        let mut props = committed.state.property_index.write();
+        self.append(|| WalRecord::CreateIndex {
+            label: label.to_string(),
+            property: property.to_string(),
+            index_type,
+        })?;
+        props.insert((label.to_string(), property.to_string()), build_index(&nodes, label, property, index_type));
         Ok(())
     }
 
-    async fn drop_index(&self, _label: &str, _property: &str) -> Result<()> {
+    async fn drop_index(&self, label: &str, property: &str) -> Result<()> {
+        let committed = self.committed.read();
+        let mut props = committed.state.property_index.write();
+        self.append(|| WalRecord::DropIndex { label: label.to_string(), property: property.to_string() })?;
+        props.remove(&(label.to_string(), property.to_string()));
         Ok(())
     }
 
+    /// The sorted indexes; full-text and vector ones aren't kept.
+    async fn indexes(&self) -> Result<Vec<(String, String, IndexType)>> {
+        let committed = self.committed.read();
+        let mut indexes: Vec<_> = committed.state.property_index.read()
+            .iter()
+            .map(|((label, property), index)| (label.clone(), property.clone(), index.index_type))
+            .collect();
+        indexes.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        Ok(indexes)
+    }
+
     // ========================================================================
     // Schema introspection
     // ========================================================================
 
-    async fn node_count(&self, _tx: &MemoryTx) -> Result<u64> {
-        Ok(self.inner.nodes.read().len() as u64)
+    async fn node_count(&self, tx: &MemoryTx) -> Result<u64> {
+        let inner = self.readable(tx);
+        Ok(inner.nodes.read().len() as u64)
     }
 
-    async fn relationship_count(&self, _tx: &MemoryTx) -> Result<u64> {
-        Ok(self.inner.relationships.read().len() as u64)
+    async fn relationship_count(&self, tx: &MemoryTx) -> Result<u64> {
+        let inner = self.readable(tx);
+        Ok(inner.relationships.read().len() as u64)
     }
 
-    async fn labels(&self, _tx: &MemoryTx) -> Result<Vec<String>> {
-        Ok(self.inner.label_index.read().keys().cloned().collect())
+    async fn labels(&self, tx: &MemoryTx) -> Result<Vec<String>> {
+        let inner = self.readable(tx);
+        Ok(inner.label_index.read().keys().cloned().collect())
     }
 
-    async fn relationship_types(&self, _tx: &MemoryTx) -> Result<Vec<String>> {
-        let rels = self.inner.relationships.read();
-        let mut types: Vec<String> = rels.values().map(|r| r.rel_type.clone()).collect();
+    async fn label_count(&self, tx: &MemoryTx, label: &str) -> Result<u64> {
+        let inner = self.readable(tx);
+        Ok(inner.label_index.read().get(label).map_or(0, |ids| ids.len() as u64))
+    }
+
+    /// Read from the per-type counts, without touching the relationships.
+    async fn relationship_count_by_type(&self, tx: &MemoryTx, rel_type: &str) -> Result<u64> {
+        let inner = self.readable(tx);
+        let counts = inner.rel_type_counts.read();
+        Ok(counts.iter().filter(|(t, _)| self.rel_type_matches(t, rel_type)).map(|(_, n)| n).sum())
+    }
+
+    async fn relationship_types(&self, tx: &MemoryTx) -> Result<Vec<String>> {
+        let inner = self.readable(tx);
+        let mut types: Vec<String> = inner.rel_type_counts.read().keys().cloned().collect();
         types.sort();
-        types.dedup();
         Ok(types)
     }
 
@@ -455,28 +1259,64 @@ impl StorageBackend for MemoryBackend {
     // Scan
     // ========================================================================
 
-    async fn all_nodes(&self, _tx: &MemoryTx) -> Result<Vec<Node>> {
-        Ok(self.inner.nodes.read().values().cloned().collect())
+    async fn all_nodes(&self, tx: &MemoryTx) -> Result<Vec<Node>> {
+        let inner = self.readable(tx);
+        // Sorted by id so scans (and LIMIT over them) are deterministic
+        let mut all: Vec<Node> = inner.nodes.read().values().cloned().collect();
+        all.sort_by_key(|n| n.id);
+        Ok(all)
     }
 
-    async fn nodes_by_label(&self, _tx: &MemoryTx, label: &str) -> Result<Vec<Node>> {
-        let idx = self.inner.label_index.read();
-        let nodes = self.inner.nodes.read();
+    async fn nodes_by_label(&self, tx: &MemoryTx, label: &str) -> Result<Vec<Node>> {
+        let inner = self.readable(tx);
+        let nodes = inner.nodes.read();
+        let idx = inner.label_index.read();
 
         let ids = idx.get(label).cloned().unwrap_or_default();
-        Ok(ids.iter().filter_map(|id| nodes.get(id).cloned()).collect())
+        let mut matched: Vec<Node> = ids.iter().filter_map(|id| nodes.get(id).cloned()).collect();
+        matched.sort_by_key(|n| n.id);
+        Ok(matched)
+    }
+
+    async fn nodes_by_labels(&self, tx: &MemoryTx, labels: &[&str]) -> Result<Vec<Node>> {
+        let inner = self.readable(tx);
+        if labels.is_empty() {
+            return self.all_nodes(tx).await;
+        }
+        let nodes = inner.nodes.read();
+        let idx = inner.label_index.read();
+
+        // Walk the smallest label set, probing the others
+        let mut sets: Vec<&Vec<NodeId>> = Vec::with_capacity(labels.len());
+        for label in labels {
+            match idx.get(*label) {
+                Some(ids) => sets.push(ids),
+                None => return Ok(Vec::new()),
+            }
+        }
+        sets.sort_by_key(|ids| ids.len());
+        let (smallest, others) = sets.split_first().expect("labels is non-empty");
+        let others: Vec<HashSet<&NodeId>> = others.iter().map(|ids| ids.iter().collect()).collect();
+        let mut matched: Vec<Node> = smallest
+            .iter()
+            .filter(|id| others.iter().all(|ids| ids.contains(id)))
+            .filter_map(|id| nodes.get(id).cloned())
+            .collect();
+        matched.sort_by_key(|n| n.id);
+        Ok(matched)
     }
 
     async fn nodes_by_property(
         &self,
-        _tx: &MemoryTx,
+        tx: &MemoryTx,
         label: &str,
         key: &str,
         value: &Value,
     ) -> Result<Vec<Node>> {
+        let inner = self.readable(tx);
         // Brute force scan (memory backend doesn't have real property indexes)
-        let idx = self.inner.label_index.read();
-        let nodes = self.inner.nodes.read();
+        let nodes = inner.nodes.read();
+        let idx = inner.label_index.read();
 
         let ids = idx.get(label).cloned().unwrap_or_default();
         Ok(ids.iter()
@@ -485,6 +1325,66 @@ impl StorageBackend for MemoryBackend {
             .cloned()
             .collect())
     }
+
+    async fn nodes_by_property_range(
+        &self,
+        tx: &MemoryTx,
+        label: &str,
+        key: &str,
+        lower: Bound<&Value>,
+        upper: Bound<&Value>,
+    ) -> Result<Vec<Node>> {
+        let inner = self.readable(tx);
+        let nodes = inner.nodes.read();
+        let idx = inner.label_index.read();
+        let props = inner.property_index.read();
+        let candidates = props
+            .get(&(label.to_string(), key.to_string()))
+            .and_then(|index| index.candidates(lower, upper));
+        let ids: Vec<NodeId> = match candidates {
+            // BTreeSet iterates in id order
+            Some(ids) => ids.into_iter().collect(),
+            None => {
+                let mut ids = idx.get(label).cloned().unwrap_or_default();
+                ids.sort();
+                ids
+            }
+        };
+        Ok(ids.iter()
+            .filter_map(|id| nodes.get(id))
+            .filter(|n| n.get(key).is_some_and(|v| value_in_range(v, lower, upper)))
+            .cloned()
+            .collect())
+    }
+
+    async fn call_procedure(&self, tx: &mut MemoryTx, name: &str, args: Vec<Value>) -> Result<ProcedureResult> {
+        let handler = self.procedures.read()
+            .get(&name.to_ascii_lowercase())
+            .cloned()
+            .ok_or_else(|| Error::ExecutionError(format!("procedure not supported: {name}")))?;
+        handler(self, tx, args).await
+    }
+
+    fn expand_limits(&self) -> ExpandLimits {
+        self.expand_limits
+    }
+
+    /// Only isolated transactions can be rolled back.
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities { supports_rollback: self.isolated, ..Default::default() }
+    }
+
+    /// One pass over the relationships, ordered by id.
+    async fn relationships_by_type(&self, tx: &MemoryTx, rel_type: &str) -> Result<Vec<Relationship>> {
+        let inner = self.readable(tx);
+        let mut result: Vec<Relationship> = inner.relationships.read()
+            .values()
+            .filter(|r| self.rel_type_matches(&r.rel_type, rel_type))
+            .cloned()
+            .collect();
+        result.sort_by_key(|r| r.id.0);
+        Ok(result)
+    }
 }
 
 // ============================================================================
@@ -510,6 +1410,21 @@ mod tests {
         assert_eq!(node.get("name"), Some(&Value::from("Ada")));
     }
 
+    #[tokio::test]
+    async fn test_get_nodes_mixes_found_and_missing() {
+        let db = MemoryBackend::new();
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+
+        let a = db.create_node(&mut tx, &["A"], PropertyMap::new()).await.unwrap();
+        let b = db.create_node(&mut tx, &["B"], PropertyMap::new()).await.unwrap();
+
+        let nodes = db.get_nodes(&tx, &[b, NodeId(999), a, b]).await.unwrap();
+        let ids: Vec<Option<NodeId>> = nodes.iter().map(|n| n.as_ref().map(|n| n.id)).collect();
+        assert_eq!(ids, vec![Some(b), None, Some(a), Some(b)]);
+        assert!(nodes[0].as_ref().unwrap().has_label("B"));
+        assert!(db.get_nodes(&tx, &[]).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_create_relationship() {
         let db = MemoryBackend::new();
@@ -592,6 +1507,24 @@ mod tests {
         assert!(rel.properties.get("since").is_none());
     }
 
+    #[tokio::test]
+    async fn test_get_relationships_typed() {
+        let db = MemoryBackend::new();
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let a = db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap();
+        let b = db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap();
+        for rel_type in ["KNOWS", "LIKES", "WORKS_WITH"] {
+            db.create_relationship(&mut tx, a, b, rel_type, PropertyMap::new()).await.unwrap();
+        }
+
+        let rels = db.get_relationships_typed(&tx, a, Direction::Outgoing, &["KNOWS", "LIKES"]).await.unwrap();
+        let mut types: Vec<&str> = rels.iter().map(|r| r.rel_type.as_str()).collect();
+        types.sort();
+        assert_eq!(types, ["KNOWS", "LIKES"]);
+        assert_eq!(db.get_relationships_typed(&tx, a, Direction::Outgoing, &[]).await.unwrap().len(), 3);
+        assert!(db.get_relationships_typed(&tx, a, Direction::Incoming, &["KNOWS"]).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_relationships_by_type() {
         let db = MemoryBackend::new();
@@ -612,6 +1545,33 @@ mod tests {
         assert_eq!(works.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_relationship_count_by_type() {
+        let db = MemoryBackend::new();
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let a = db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap();
+        let b = db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap();
+        let first = db.create_relationship(&mut tx, a, b, "KNOWS", PropertyMap::new()).await.unwrap();
+        db.create_relationship(&mut tx, b, a, "KNOWS", PropertyMap::new()).await.unwrap();
+        let likes = db.create_relationship(&mut tx, a, a, "LIKES", PropertyMap::new()).await.unwrap();
+        assert_eq!(db.relationship_count_by_type(&tx, "KNOWS").await.unwrap(), 2);
+        assert_eq!(db.relationship_count_by_type(&tx, "LIKES").await.unwrap(), 1);
+        assert_eq!(db.relationship_count_by_type(&tx, "NONE").await.unwrap(), 0);
+
+        db.delete_relationship(&mut tx, first).await.unwrap();
+        db.delete_relationship(&mut tx, likes).await.unwrap();
+        assert_eq!(db.relationship_count_by_type(&tx, "KNOWS").await.unwrap(), 1);
+        assert_eq!(db.relationship_count_by_type(&tx, "LIKES").await.unwrap(), 0);
+        assert_eq!(db.relationship_types(&tx).await.unwrap(), ["KNOWS"]);
+
+        let db = MemoryBackend::new().with_case_insensitive_rel_types(true);
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let a = db.create_node(&mut tx, &[], PropertyMap::new()).await.unwrap();
+        db.create_relationship(&mut tx, a, a, "KNOWS", PropertyMap::new()).await.unwrap();
+        db.create_relationship(&mut tx, a, a, "knows", PropertyMap::new()).await.unwrap();
+        assert_eq!(db.relationship_count_by_type(&tx, "Knows").await.unwrap(), 2);
+    }
+
     #[tokio::test]
     async fn test_traversal() {
         let db = MemoryBackend::new();
@@ -629,4 +1589,354 @@ mod tests {
         // Should find a->b and a->b->c
         assert_eq!(paths.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_expand_limits_on_clique() {
+        let db = MemoryBackend::new().with_expand_limits(ExpandLimits { max_depth: 4, max_paths: 50 });
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+
+        let mut ids = Vec::new();
+        for _ in 0..6 {
+            ids.push(db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap());
+        }
+        for &a in &ids {
+            for &b in &ids {
+                if a != b {
+                    db.create_relationship(&mut tx, a, b, "KNOWS", PropertyMap::new()).await.unwrap();
+                }
+            }
+        }
+
+        // Depth 1 stays well inside the path budget
+        let paths = db.expand(&tx, ids[0], Direction::Outgoing, &[], ExpandDepth::Exact(1)).await.unwrap();
+        assert_eq!(paths.len(), 5);
+
+        // Unbounded on a 6-clique builds far more than 50 paths
+        let err = db.expand(&tx, ids[0], Direction::Outgoing, &[], ExpandDepth::Unbounded).await.unwrap_err();
+        assert!(err.to_string().contains("expansion limit exceeded"), "got: {err}");
+
+        // Asking for more depth than allowed fails up front
+        let err = db.expand(&tx, ids[0], Direction::Outgoing, &[], ExpandDepth::Exact(5)).await.unwrap_err();
+        assert!(err.to_string().contains("expansion limit exceeded"), "got: {err}");
+    }
+
+    #[test]
+    fn test_concurrent_writers_do_not_deadlock() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        const THREADS: usize = 8;
+        const ROUNDS: usize = 300;
+
+        let db = Arc::new(MemoryBackend::new());
+        let (done, finished) = mpsc::channel();
+        for t in 0..THREADS {
+            let db = db.clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+                rt.block_on(async {
+                    let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+                    if t == 0 {
+                        db.create_index("A", "n", IndexType::BTree).await.unwrap();
+                    }
+                    let mut prev = db.create_node(&mut tx, &["A"], PropertyMap::new()).await.unwrap();
+                    for i in 0..ROUNDS {
+                        let props = PropertyMap::from([("n".to_string(), Value::from(i as i64))]);
+                        let id = db.create_node(&mut tx, &["A"], props).await.unwrap();
+                        db.add_label(&mut tx, id, "B").await.unwrap();
+                        db.set_node_property(&mut tx, id, "n", Value::from(-(i as i64))).await.unwrap();
+                        let rel = db.create_relationship(&mut tx, prev, id, "NEXT", PropertyMap::new()).await.unwrap();
+                        db.nodes_by_label(&tx, "B").await.unwrap();
+                        db.nodes_by_property_range(&tx, "A", "n", Bound::Unbounded, Bound::Unbounded).await.unwrap();
+                        db.get_relationships(&tx, id, Direction::Both, None).await.unwrap();
+                        db.delete_relationship(&mut tx, rel).await.unwrap();
+                        db.remove_label(&mut tx, id, "B").await.unwrap();
+                        db.delete_node(&mut tx, prev).await.unwrap();
+                        prev = id;
+                    }
+                    db.delete_node(&mut tx, prev).await.unwrap();
+                });
+                done.send(()).unwrap();
+            });
+        }
+        drop(done);
+
+        // A deadlocked worker never reports back
+        for _ in 0..THREADS {
+            finished.recv_timeout(Duration::from_secs(60)).expect("concurrent writers deadlocked");
+        }
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        rt.block_on(async {
+            let tx = db.begin_tx(TxMode::ReadOnly).await.unwrap();
+            assert_eq!(db.node_count(&tx).await.unwrap(), 0);
+            assert_eq!(db.relationship_count(&tx).await.unwrap(), 0);
+        });
+    }
+
+    /// A fresh write-ahead log path, with no log or snapshot behind it.
+    fn wal_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("neo4j_rs_wal_{name}_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(path.with_extension("log.snapshot"));
+        path
+    }
+
+    /// Every node and relationship, in id order.
+    async fn dump(db: &MemoryBackend) -> (Vec<Node>, Vec<Relationship>) {
+        let tx = db.begin_tx(TxMode::ReadOnly).await.unwrap();
+        let nodes = db.all_nodes(&tx).await.unwrap();
+        let mut rels = Vec::new();
+        for n in &nodes {
+            rels.extend(db.get_relationships(&tx, n.id, Direction::Outgoing, None).await.unwrap());
+        }
+        rels.sort_by_key(|r| r.id.0);
+        (nodes, rels)
+    }
+
+    #[tokio::test]
+    async fn test_wal_replay_matches_live_state() {
+        let path = wal_path("replay");
+        let db = MemoryBackend::open_durable(&path, FsyncPolicy::Never).await.unwrap();
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let mut ids = Vec::new();
+        for (name, age) in [("Ada", 36), ("Bob", 25), ("Cy", 40)] {
+            let props = PropertyMap::from([("name".into(), Value::from(name)), ("age".into(), Value::Int(age))]);
+            ids.push(db.create_node(&mut tx, &["Person"], props).await.unwrap());
+        }
+        let (ada, bob, cy) = (ids[0], ids[1], ids[2]);
+        db.create_index("Person", "age", IndexType::BTree).await.unwrap();
+        db.add_label(&mut tx, ada, "Admin").await.unwrap();
+        db.remove_label(&mut tx, bob, "Person").await.unwrap();
+        db.set_node_property(&mut tx, bob, "age", Value::Int(26)).await.unwrap();
+        db.remove_node_property(&mut tx, ada, "name").await.unwrap();
+        let knows = db.create_relationship(&mut tx, ada, bob, "KNOWS", PropertyMap::new()).await.unwrap();
+        db.set_relationship_property(&mut tx, knows, "since", Value::Int(2020)).await.unwrap();
+        let gone = db.create_relationship(&mut tx, bob, cy, "KNOWS", PropertyMap::new()).await.unwrap();
+        db.delete_relationship(&mut tx, gone).await.unwrap();
+        db.delete_node(&mut tx, cy).await.unwrap();
+        let expected = dump(&db).await;
+        drop(db);
+
+        let db = MemoryBackend::open_durable(&path, FsyncPolicy::Never).await.unwrap();
+        assert_eq!(dump(&db).await, expected);
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        // Ids carry on after the last logged one, and the index is rebuilt
+        assert_eq!(db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap(), NodeId(4));
+        let found = db
+            .nodes_by_property_range(&tx, "Person", "age", Bound::Included(&Value::Int(30)), Bound::Unbounded)
+            .await
+            .unwrap();
+        assert_eq!(found.iter().map(|n| n.id).collect::<Vec<_>>(), vec![ada]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_wal_checkpoint_and_torn_tail() {
+        let path = wal_path("checkpoint");
+        let db = MemoryBackend::open_durable(&path, FsyncPolicy::Always).await.unwrap();
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let a = db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap();
+        let b = db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap();
+        db.create_relationship(&mut tx, a, b, "KNOWS", PropertyMap::new()).await.unwrap();
+        let before_checkpoint = std::fs::read(&path).unwrap();
+        db.checkpoint().unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+        db.set_node_property(&mut tx, b, "name", Value::from("Bob")).await.unwrap();
+        let expected = dump(&db).await;
+        drop(db);
+
+        // A crash mid-append leaves a record without its newline
+        let mut log = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut log, br#"{"lsn":9,"record":{"DeleteNode""#).unwrap();
+        drop(log);
+        let db = MemoryBackend::open_durable(&path, FsyncPolicy::Always).await.unwrap();
+        assert_eq!(dump(&db).await, expected);
+        drop(db);
+
+        // A crash before the log was truncated replays nothing twice
+        let after_checkpoint = std::fs::read(&path).unwrap();
+        std::fs::write(&path, [before_checkpoint, after_checkpoint].concat()).unwrap();
+        let db = MemoryBackend::open_durable(&path, FsyncPolicy::Always).await.unwrap();
+        assert_eq!(dump(&db).await, expected);
+
+        assert!(MemoryBackend::new().checkpoint().is_err());
+        let _ = std::fs::remove_file(path.with_extension("log.snapshot"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_wal_checkpoint_keeps_index_types() {
+        let path = wal_path("checkpoint_indexes");
+        let db = MemoryBackend::open_durable(&path, FsyncPolicy::Always).await.unwrap();
+        db.create_index("Person", "email", IndexType::Unique).await.unwrap();
+        db.create_index("Person", "age", IndexType::BTree).await.unwrap();
+        db.checkpoint().unwrap();
+        drop(db);
+
+        let db = MemoryBackend::open_durable(&path, FsyncPolicy::Always).await.unwrap();
+        assert_eq!(db.indexes().await.unwrap(), vec![
+            ("Person".to_string(), "age".to_string(), IndexType::BTree),
+            ("Person".to_string(), "email".to_string(), IndexType::Unique),
+        ]);
+        let _ = std::fs::remove_file(path.with_extension("log.snapshot"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_node_id_reuse() {
+        async fn cycle(db: &MemoryBackend) -> Vec<NodeId> {
+            let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+            let mut ids = Vec::new();
+            for _ in 0..3 {
+                ids.push(db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap());
+            }
+            db.delete_node(&mut tx, ids[1]).await.unwrap();
+            db.delete_node(&mut tx, ids[0]).await.unwrap();
+            for _ in 0..3 {
+                ids.push(db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap());
+            }
+            ids
+        }
+        // Off by default: ids only go up
+        assert_eq!(cycle(&MemoryBackend::new()).await, [1, 2, 3, 4, 5, 6].map(NodeId));
+        // On: the lowest freed id first, then fresh ones
+        assert_eq!(cycle(&MemoryBackend::new().with_node_id_reuse(true)).await, [1, 2, 3, 1, 2, 4].map(NodeId));
+
+        // A reused node replaces the old one entirely
+        let db = MemoryBackend::new().with_node_id_reuse(true);
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let props = PropertyMap::from([("name".into(), Value::from("Ada"))]);
+        let old = db.create_node(&mut tx, &["Person"], props).await.unwrap();
+        db.delete_node(&mut tx, old).await.unwrap();
+        let new = db.create_node(&mut tx, &["Robot"], PropertyMap::new()).await.unwrap();
+        assert_eq!(new, old);
+        let node = db.get_node(&tx, new).await.unwrap().unwrap();
+        assert_eq!(node.labels, ["Robot"]);
+        assert!(node.properties.is_empty());
+        assert!(db.nodes_by_label(&tx, "Person").await.unwrap().is_empty());
+
+        // Replaying a log with reused ids rebuilds the same graph, and the
+        // gaps left in it are reused once reuse is turned on again
+        let path = wal_path("reuse");
+        let db = MemoryBackend::open_durable(&path, FsyncPolicy::Never).await.unwrap().with_node_id_reuse(true);
+        let ids = cycle(&db).await;
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        db.delete_node(&mut tx, ids[2]).await.unwrap();
+        let expected = dump(&db).await;
+        drop(db);
+        let db = MemoryBackend::open_durable(&path, FsyncPolicy::Never).await.unwrap();
+        assert_eq!(dump(&db).await, expected);
+        let db = db.with_node_id_reuse(true);
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        assert_eq!(db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap(), ids[2]);
+        assert_eq!(db.create_node(&mut tx, &["Person"], PropertyMap::new()).await.unwrap(), NodeId(5));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_isolated_transactions() {
+        let db = MemoryBackend::new().with_isolated_transactions(true);
+        let mut writer = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let ada = db.create_node(&mut writer, &["Person"], PropertyMap::new()).await.unwrap();
+        let reader = db.begin_tx(TxMode::ReadOnly).await.unwrap();
+        assert!(db.get_node(&writer, ada).await.unwrap().is_some());
+        assert!(db.get_node(&reader, ada).await.unwrap().is_none());
+
+        // A second writer that began before the first committed conflicts
+        let mut late = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        db.create_node(&mut late, &["Person"], PropertyMap::new()).await.unwrap();
+        db.commit_tx(writer).await.unwrap();
+        assert!(db.commit_tx(late).await.unwrap_err().is_write_conflict());
+        // The reader keeps its snapshot; a new one sees the commit
+        assert!(db.get_node(&reader, ada).await.unwrap().is_none());
+        let reader = db.begin_tx(TxMode::ReadOnly).await.unwrap();
+        assert_eq!(db.node_count(&reader).await.unwrap(), 1);
+
+        // Rollback discards, read-only transactions can't write, and an
+        // index created mid-transaction survives its commit
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        db.set_node_property(&mut tx, ada, "age", Value::Int(36)).await.unwrap();
+        db.rollback_tx(tx).await.unwrap();
+        let mut ro = db.begin_tx(TxMode::ReadOnly).await.unwrap();
+        assert!(db.get_node(&ro, ada).await.unwrap().unwrap().properties.is_empty());
+        assert!(db.set_node_property(&mut ro, ada, "age", Value::Int(1)).await.is_err());
+
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        db.set_node_property(&mut tx, ada, "age", Value::Int(36)).await.unwrap();
+        db.create_index("Person", "age", IndexType::BTree).await.unwrap();
+        db.commit_tx(tx).await.unwrap();
+        let tx = db.begin_tx(TxMode::ReadOnly).await.unwrap();
+        let found = db
+            .nodes_by_property_range(&tx, "Person", "age", Bound::Included(&Value::Int(30)), Bound::Unbounded)
+            .await
+            .unwrap();
+        assert_eq!(found.iter().map(|n| n.id).collect::<Vec<_>>(), vec![ada]);
+    }
+
+    #[tokio::test]
+    async fn test_register_procedure() {
+        let db = MemoryBackend::new();
+        // Creates a node per call and returns the nodes seen so far
+        db.register_procedure("test.touch", |backend, tx, args| {
+            Box::pin(async move {
+                let mut props = PropertyMap::new();
+                props.insert("tag".into(), args.into_iter().next().unwrap_or(Value::Null));
+                backend.create_node(tx, &["Touched"], props).await?;
+                let count = backend.label_count(tx, "Touched").await? as i64;
+                let row = HashMap::from([("count".to_string(), Value::Int(count))]);
+                Ok(ProcedureResult { columns: vec!["count".into()], rows: vec![row] })
+            })
+        });
+        let graph = crate::Graph::with_backend(db);
+
+        let mut tx = graph.begin(TxMode::ReadWrite).await.unwrap();
+        let result = tx.execute("CALL Test.Touch('a') YIELD count", PropertyMap::new()).await.unwrap();
+        assert_eq!(result.rows[0].get::<i64>("count").unwrap(), 1);
+        let result = tx.execute("CALL test.touch('b')", PropertyMap::new()).await.unwrap();
+        assert_eq!(result.rows[0].get::<i64>("count").unwrap(), 2);
+        tx.commit().await.unwrap();
+
+        let result = graph.execute("CALL db.labels() YIELD label", PropertyMap::new()).await.unwrap();
+        assert_eq!(result.rows.iter().map(|r| r.get::<String>("label").unwrap()).collect::<Vec<_>>(), vec!["Touched"]);
+        let result = graph.execute("CALL db.propertyKeys()", PropertyMap::new()).await.unwrap();
+        assert_eq!(result.rows[0].get::<String>("propertyKey").unwrap(), "tag");
+        assert!(graph.execute("CALL test.missing()", PropertyMap::new()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_node_properties_matches_per_key() {
+        let db = MemoryBackend::new();
+        db.create_index("Person", "age", IndexType::BTree).await.unwrap();
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let start: PropertyMap = [("name".to_string(), Value::from("Ada")), ("city".to_string(), Value::from("London"))].into();
+        let batched = db.create_node(&mut tx, &["Person"], start.clone()).await.unwrap();
+        let per_key = db.create_node(&mut tx, &["Person"], start).await.unwrap();
+
+        let update: PropertyMap = [
+            ("age".to_string(), Value::Int(36)),
+            ("city".to_string(), Value::Null),
+            ("name".to_string(), Value::from("Ada L.")),
+        ]
+        .into();
+        db.set_node_properties(&mut tx, batched, update.clone()).await.unwrap();
+        for (key, val) in update {
+            if val.is_null() {
+                db.remove_node_property(&mut tx, per_key, &key).await.unwrap();
+            } else {
+                db.set_node_property(&mut tx, per_key, &key, val).await.unwrap();
+            }
+        }
+
+        let a = db.get_node(&tx, batched).await.unwrap().unwrap();
+        let b = db.get_node(&tx, per_key).await.unwrap().unwrap();
+        assert_eq!(a.properties, b.properties);
+        assert!(!a.properties.contains_key("city"));
+        // Both are indexed under the new value
+        let found = db
+            .nodes_by_property_range(&tx, "Person", "age", Bound::Included(&Value::Int(36)), Bound::Included(&Value::Int(36)))
+            .await
+            .unwrap();
+        assert_eq!(found.iter().map(|n| n.id).collect::<Vec<_>>(), vec![batched, per_key]);
+    }
 }
@@ -7,22 +7,27 @@
 //!
 //! | Backend | Module | Description |
 //! |---------|--------|-------------|
-//! | `MemoryBackend` | `memory` | In-memory for testing/embedding |
+//! | `MemoryBackend` | `memory` | In-memory for testing/embedding, optionally logged by `wal` |
 //! | `BoltBackend` | `bolt` | External Neo4j via Bolt protocol |
 //! | `LadybugBackend` | `ladybug` | Hamming-accelerated via ladybug-rs |
 
 pub mod memory;
+pub mod wal;
 #[cfg(feature = "bolt")]
 pub mod bolt;
+#[cfg(feature = "bolt")]
+pub use bolt::{BoltBackend, BoltTx, PagedResult, RecordStream};
 /// Ladybug-rs BindSpace backend — the production engine.
 /// neo4j-rs is the glove, ladybug-rs is the hand.
 #[cfg(feature = "ladybug")]
 pub mod ladybug;
 #[cfg(feature = "ladybug")]
-pub use ladybug::LadybugBackend;
+pub use ladybug::{FingerprintMode, LadybugBackend, PropertyFingerprinter};
 #[cfg(feature = "ladybug-contract")]
 pub mod cogrecord;
 
+use std::ops::Bound;
+
 use async_trait::async_trait;
 use crate::model::*;
 use crate::tx::{Transaction, TxMode};
@@ -30,16 +35,32 @@ use crate::index::IndexType;
 use crate::{Error, Result};
 
 pub use memory::MemoryBackend;
+pub use wal::FsyncPolicy;
 
 // ============================================================================
 // Backend Configuration
 // ============================================================================
 
 /// Configuration for connecting to a storage backend.
+///
+/// Each backend opens from its own variants: `MemoryBackend::open` (and
+/// `Graph::open`) from `Memory` and `DurableMemory`.
 #[derive(Debug, Clone)]
 pub enum BackendConfig {
     /// In-memory (no persistence)
-    Memory,
+    Memory {
+        /// Match relationship types ignoring ASCII case
+        /// (see `MemoryBackend::with_case_insensitive_rel_types`).
+        case_insensitive_rel_types: bool,
+    },
+
+    /// In-memory, made crash-consistent by a write-ahead log
+    /// (see `MemoryBackend::open_durable`)
+    DurableMemory {
+        wal_path: std::path::PathBuf,
+        fsync: FsyncPolicy,
+        case_insensitive_rel_types: bool,
+    },
 
     /// Neo4j Bolt protocol
     #[cfg(feature = "bolt")]
@@ -55,6 +76,8 @@ pub enum BackendConfig {
     Ladybug {
         data_dir: std::path::PathBuf,
         cache_size_mb: usize,
+        /// How node properties are fingerprinted.
+        fingerprinter: ladybug::PropertyFingerprinter,
     },
 }
 
@@ -73,6 +96,68 @@ pub enum ExpandDepth {
     Unbounded,
 }
 
+/// Limits applied by `expand()` so traversals of dense graphs fail fast
+/// instead of exhausting memory.
+#[derive(Debug, Clone, Copy)]
+pub struct ExpandLimits {
+    /// Depth used for `ExpandDepth::Unbounded`; deeper explicit requests error.
+    pub max_depth: usize,
+    /// Maximum number of paths a single expansion may build.
+    pub max_paths: usize,
+}
+
+impl Default for ExpandLimits {
+    fn default() -> Self {
+        Self { max_depth: 100, max_paths: 100_000 }
+    }
+}
+
+impl ExpandLimits {
+    /// Resolve `depth` to `(min, max)`, erroring if it exceeds `max_depth`.
+    pub fn resolve(&self, depth: ExpandDepth) -> Result<(usize, usize)> {
+        let (min, max) = match depth {
+            ExpandDepth::Exact(d) => (d, d),
+            ExpandDepth::Range { min, max } => (min, max),
+            ExpandDepth::Unbounded => (1, self.max_depth),
+        };
+        if max > self.max_depth {
+            return Err(expansion_limit_exceeded());
+        }
+        Ok((min, max))
+    }
+}
+
+/// The error returned when an expansion hits its `ExpandLimits`.
+pub(crate) fn expansion_limit_exceeded() -> Error {
+    Error::ExecutionError("expansion limit exceeded".into())
+}
+
+/// The error returned when a plain `DELETE` hits a node that still has
+/// relationships.
+pub(crate) fn node_still_has_relationships(id: NodeId) -> Error {
+    Error::ConstraintViolation(format!(
+        "Cannot delete node {id}, because it still has relationships. \
+         Delete its relationships first, or use DETACH DELETE."
+    ))
+}
+
+/// Whether `value` lies within `lower..upper` under `Value::neo4j_cmp`.
+/// Values that don't compare with a bound (other types, null) are outside.
+pub(crate) fn value_in_range(value: &Value, lower: Bound<&Value>, upper: Bound<&Value>) -> bool {
+    use std::cmp::Ordering::*;
+    let above = match lower {
+        Bound::Included(b) => matches!(value.neo4j_cmp(b), Some(Greater | Equal)),
+        Bound::Excluded(b) => matches!(value.neo4j_cmp(b), Some(Greater)),
+        Bound::Unbounded => !value.is_null(),
+    };
+    let below = match upper {
+        Bound::Included(b) => matches!(value.neo4j_cmp(b), Some(Less | Equal)),
+        Bound::Excluded(b) => matches!(value.neo4j_cmp(b), Some(Less)),
+        Bound::Unbounded => true,
+    };
+    above && below
+}
+
 // ============================================================================
 // Constraint types
 // ============================================================================
@@ -90,7 +175,8 @@ pub enum ConstraintType {
 // Backend capabilities
 // ============================================================================
 
-/// What a backend can do — used by the planner for optimization decisions.
+/// What a backend can do — used by the planner for optimization decisions,
+/// and by `planner::check_capabilities` to reject plans the backend can't run.
 ///
 /// All fields default to false / empty. Backends override via `capabilities()`.
 #[derive(Debug, Clone, Default)]
@@ -102,6 +188,12 @@ pub struct BackendCapabilities {
     pub max_batch_size: Option<usize>,
     pub supported_procedures: Vec<String>,
     pub similarity_accelerated: bool,
+    /// `rollback_tx` discards the transaction's writes, rather than leaving
+    /// them applied.
+    pub supports_rollback: bool,
+    /// `create_constraint` and `drop_constraint` are implemented, rather
+    /// than left to the defaults that reject them.
+    pub supports_constraints: bool,
 }
 
 // ============================================================================
@@ -169,6 +261,17 @@ pub trait StorageBackend: Send + Sync + 'static {
     /// Get a node by ID. Returns None if not found.
     async fn get_node(&self, tx: &Self::Tx, id: NodeId) -> Result<Option<Node>>;
 
+    /// Get several nodes by ID, in order; `None` where a node doesn't exist.
+    ///
+    /// Default: one `get_node` per ID.
+    async fn get_nodes(&self, tx: &Self::Tx, ids: &[NodeId]) -> Result<Vec<Option<Node>>> {
+        let mut nodes = Vec::with_capacity(ids.len());
+        for &id in ids {
+            nodes.push(self.get_node(tx, id).await?);
+        }
+        Ok(nodes)
+    }
+
     /// Delete a node. Returns true if it existed.
     /// Fails if the node still has relationships (Neo4j semantics).
     async fn delete_node(&self, tx: &mut Self::Tx, id: NodeId) -> Result<bool>;
@@ -182,6 +285,21 @@ pub trait StorageBackend: Send + Sync + 'static {
         val: Value,
     ) -> Result<()>;
 
+    /// Set several properties on a node at once: each key is upserted, or
+    /// removed when its value is null. Backends that derive something from
+    /// the whole property map (an index, a fingerprint) override this to do
+    /// that once per call instead of once per key.
+    async fn set_node_properties(&self, tx: &mut Self::Tx, id: NodeId, props: PropertyMap) -> Result<()> {
+        for (key, val) in props {
+            if val.is_null() {
+                self.remove_node_property(tx, id, &key).await?;
+            } else {
+                self.set_node_property(tx, id, &key, val).await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Remove a property from a node.
     async fn remove_node_property(
         &self,
@@ -258,6 +376,9 @@ pub trait StorageBackend: Send + Sync + 'static {
     // ========================================================================
 
     /// Get all relationships of a node, optionally filtered by direction and type.
+    ///
+    /// With `Direction::Both` each relationship is reported once, self-loops
+    /// included.
     async fn get_relationships(
         &self,
         tx: &Self::Tx,
@@ -266,7 +387,38 @@ pub trait StorageBackend: Send + Sync + 'static {
         rel_type: Option<&str>,
     ) -> Result<Vec<Relationship>>;
 
+    /// Like `get_relationships`, keeping relationships of any of
+    /// `rel_types`; all of them when `rel_types` is empty.
+    ///
+    /// Default: one `get_relationships` call per type, so the backend's own
+    /// type matching applies, each relationship reported once.
+    async fn get_relationships_typed(
+        &self,
+        tx: &Self::Tx,
+        node: NodeId,
+        dir: Direction,
+        rel_types: &[&str],
+    ) -> Result<Vec<Relationship>> {
+        match rel_types {
+            [] => self.get_relationships(tx, node, dir, None).await,
+            [only] => self.get_relationships(tx, node, dir, Some(only)).await,
+            _ => {
+                let mut rels = Vec::new();
+                let mut seen = std::collections::HashSet::new();
+                for rel_type in rel_types {
+                    let typed = self.get_relationships(tx, node, dir, Some(rel_type)).await?;
+                    // `[:KNOWS|knows]` may find a relationship twice
+                    rels.extend(typed.into_iter().filter(|r| seen.insert(r.id)));
+                }
+                Ok(rels)
+            }
+        }
+    }
+
     /// Expand from a node: BFS/DFS traversal to the given depth.
+    ///
+    /// Implementations enforce their `ExpandLimits` and return
+    /// `Error::ExecutionError("expansion limit exceeded")` when hit.
     async fn expand(
         &self,
         tx: &Self::Tx,
@@ -276,6 +428,14 @@ pub trait StorageBackend: Send + Sync + 'static {
         depth: ExpandDepth,
     ) -> Result<Vec<Path>>;
 
+    /// The limits `expand()` enforces, for traversals built on
+    /// `get_relationships` (such as `apoc.path.expand`) to honour too.
+    ///
+    /// Default returns `ExpandLimits::default()`.
+    fn expand_limits(&self) -> ExpandLimits {
+        ExpandLimits::default()
+    }
+
     // ========================================================================
     // Index
     // ========================================================================
@@ -291,6 +451,13 @@ pub trait StorageBackend: Send + Sync + 'static {
     /// Drop an index.
     async fn drop_index(&self, label: &str, property: &str) -> Result<()>;
 
+    /// The indexes as `(label, property, type)`, sorted by label and property.
+    ///
+    /// Default returns none.
+    async fn indexes(&self) -> Result<Vec<(String, String, IndexType)>> {
+        Ok(Vec::new())
+    }
+
     // ========================================================================
     // Schema introspection
     // ========================================================================
@@ -304,6 +471,21 @@ pub trait StorageBackend: Send + Sync + 'static {
     /// All distinct labels in the graph.
     async fn labels(&self, tx: &Self::Tx) -> Result<Vec<String>>;
 
+    /// Number of nodes carrying `label`. Used for planner cardinality estimates.
+    ///
+    /// Default counts the result of `nodes_by_label`.
+    async fn label_count(&self, tx: &Self::Tx, label: &str) -> Result<u64> {
+        Ok(self.nodes_by_label(tx, label).await?.len() as u64)
+    }
+
+    /// Number of relationships of type `rel_type`. Answers `count(r)` over
+    /// `()-[r:T]->()` without expanding anything.
+    ///
+    /// Default counts the result of `relationships_by_type`.
+    async fn relationship_count_by_type(&self, tx: &Self::Tx, rel_type: &str) -> Result<u64> {
+        Ok(self.relationships_by_type(tx, rel_type).await?.len() as u64)
+    }
+
     /// All distinct relationship types in the graph.
     async fn relationship_types(&self, tx: &Self::Tx) -> Result<Vec<String>>;
 
@@ -320,6 +502,29 @@ pub trait StorageBackend: Send + Sync + 'static {
     /// Find all nodes with a given label.
     async fn nodes_by_label(&self, tx: &Self::Tx, label: &str) -> Result<Vec<Node>>;
 
+    /// Find all nodes carrying every label in `labels`, ordered by node id.
+    /// An empty `labels` matches every node.
+    ///
+    /// Default: intersects the `nodes_by_label` result of each label.
+    async fn nodes_by_labels(&self, tx: &Self::Tx, labels: &[&str]) -> Result<Vec<Node>> {
+        let Some((first, rest)) = labels.split_first() else {
+            let mut all = self.all_nodes(tx).await?;
+            all.sort_by_key(|n| n.id);
+            return Ok(all);
+        };
+        let mut nodes = self.nodes_by_label(tx, first).await?;
+        for label in rest {
+            if nodes.is_empty() {
+                break;
+            }
+            let ids: std::collections::HashSet<NodeId> =
+                self.nodes_by_label(tx, label).await?.into_iter().map(|n| n.id).collect();
+            nodes.retain(|n| ids.contains(&n.id));
+        }
+        nodes.sort_by_key(|n| n.id);
+        Ok(nodes)
+    }
+
     /// Find nodes by label + property value (index-backed if available).
     async fn nodes_by_property(
         &self,
@@ -329,6 +534,24 @@ pub trait StorageBackend: Send + Sync + 'static {
         value: &Value,
     ) -> Result<Vec<Node>>;
 
+    /// Find nodes by label whose `key` property lies within `lower..upper`,
+    /// ordered by node id (index-backed if available).
+    ///
+    /// Default: filters `nodes_by_label` with `Value::neo4j_cmp`, so values
+    /// of a different type than the bounds never match.
+    async fn nodes_by_property_range(
+        &self,
+        tx: &Self::Tx,
+        label: &str,
+        key: &str,
+        lower: Bound<&Value>,
+        upper: Bound<&Value>,
+    ) -> Result<Vec<Node>> {
+        let mut nodes = self.nodes_by_label(tx, label).await?;
+        nodes.retain(|n| n.get(key).is_some_and(|v| value_in_range(v, lower, upper)));
+        Ok(nodes)
+    }
+
     /// Find all relationships of a given type.
     ///
     /// Default: scans all nodes and collects outgoing relationships of that type.
@@ -371,6 +594,14 @@ pub trait StorageBackend: Send + Sync + 'static {
         Err(Error::ExecutionError("constraints not supported".into()))
     }
 
+    /// The constraints as `(label, property, type)`, sorted by label and
+    /// property.
+    ///
+    /// Default returns none.
+    async fn constraints(&self) -> Result<Vec<(String, String, ConstraintType)>> {
+        Ok(Vec::new())
+    }
+
     // ========================================================================
     // Batch operations
     // ========================================================================
@@ -427,10 +658,11 @@ pub trait StorageBackend: Send + Sync + 'static {
     /// Call a registered procedure. Neo4j: `CALL name(args) YIELD cols`.
     ///
     /// This is the standard extension point — APOC, GDS, and ladybug-rs
-    /// cognitive operations all go through here.
+    /// cognitive operations all go through here. Procedures get the
+    /// transaction mutably, so they may write.
     async fn call_procedure(
         &self,
-        _tx: &Self::Tx,
+        _tx: &mut Self::Tx,
         _name: &str,
         _args: Vec<Value>,
     ) -> Result<ProcedureResult> {
@@ -439,7 +671,12 @@ pub trait StorageBackend: Send + Sync + 'static {
 
     /// Vector similarity search (Neo4j 5.x compatible).
     ///
-    /// Returns (NodeId, score) pairs ordered by similarity.
+    /// `query_vector` is what the caller gave `db.index.vector.queryNodes`:
+    /// `Bytes` are passed unchanged, a list of numbers arrives as
+    /// little-endian `f32`s. Backends convert it to their own representation.
+    ///
+    /// Returns (NodeId, score) pairs ordered by similarity, with equal
+    /// scores in ascending `NodeId` order so top-k results are stable.
     async fn vector_query(
         &self,
         _tx: &Self::Tx,
@@ -544,3 +781,108 @@ pub trait StorageBackend: Send + Sync + 'static {
         Err(Error::ExecutionError("edge superposition not supported".into()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx::TxMode;
+
+    /// A `MemoryBackend` seen only through the trait's required methods,
+    /// so the provided ones run their defaults.
+    struct Defaults(MemoryBackend);
+
+    #[async_trait]
+    impl StorageBackend for Defaults {
+        type Tx = <MemoryBackend as StorageBackend>::Tx;
+
+        async fn shutdown(&self) -> Result<()> { self.0.shutdown().await }
+        async fn begin_tx(&self, mode: TxMode) -> Result<Self::Tx> { self.0.begin_tx(mode).await }
+        async fn commit_tx(&self, tx: Self::Tx) -> Result<()> { self.0.commit_tx(tx).await }
+        async fn rollback_tx(&self, tx: Self::Tx) -> Result<()> { self.0.rollback_tx(tx).await }
+        async fn create_node(&self, tx: &mut Self::Tx, labels: &[&str], props: PropertyMap) -> Result<NodeId> {
+            self.0.create_node(tx, labels, props).await
+        }
+        async fn get_node(&self, tx: &Self::Tx, id: NodeId) -> Result<Option<Node>> { self.0.get_node(tx, id).await }
+        async fn delete_node(&self, tx: &mut Self::Tx, id: NodeId) -> Result<bool> { self.0.delete_node(tx, id).await }
+        async fn set_node_property(&self, tx: &mut Self::Tx, id: NodeId, key: &str, val: Value) -> Result<()> {
+            self.0.set_node_property(tx, id, key, val).await
+        }
+        async fn remove_node_property(&self, tx: &mut Self::Tx, id: NodeId, key: &str) -> Result<()> {
+            self.0.remove_node_property(tx, id, key).await
+        }
+        async fn add_label(&self, tx: &mut Self::Tx, id: NodeId, label: &str) -> Result<()> {
+            self.0.add_label(tx, id, label).await
+        }
+        async fn remove_label(&self, tx: &mut Self::Tx, id: NodeId, label: &str) -> Result<()> {
+            self.0.remove_label(tx, id, label).await
+        }
+        async fn create_relationship(
+            &self,
+            tx: &mut Self::Tx,
+            src: NodeId,
+            dst: NodeId,
+            rel_type: &str,
+            props: PropertyMap,
+        ) -> Result<RelId> {
+            self.0.create_relationship(tx, src, dst, rel_type, props).await
+        }
+        async fn get_relationship(&self, tx: &Self::Tx, id: RelId) -> Result<Option<Relationship>> {
+            self.0.get_relationship(tx, id).await
+        }
+        async fn delete_relationship(&self, tx: &mut Self::Tx, id: RelId) -> Result<bool> {
+            self.0.delete_relationship(tx, id).await
+        }
+        async fn get_relationships(
+            &self,
+            tx: &Self::Tx,
+            node: NodeId,
+            dir: Direction,
+            rel_type: Option<&str>,
+        ) -> Result<Vec<Relationship>> {
+            self.0.get_relationships(tx, node, dir, rel_type).await
+        }
+        async fn expand(
+            &self,
+            tx: &Self::Tx,
+            node: NodeId,
+            dir: Direction,
+            rel_types: &[&str],
+            depth: ExpandDepth,
+        ) -> Result<Vec<Path>> {
+            self.0.expand(tx, node, dir, rel_types, depth).await
+        }
+        async fn create_index(&self, label: &str, property: &str, index_type: IndexType) -> Result<()> {
+            self.0.create_index(label, property, index_type).await
+        }
+        async fn drop_index(&self, label: &str, property: &str) -> Result<()> { self.0.drop_index(label, property).await }
+        async fn node_count(&self, tx: &Self::Tx) -> Result<u64> { self.0.node_count(tx).await }
+        async fn relationship_count(&self, tx: &Self::Tx) -> Result<u64> { self.0.relationship_count(tx).await }
+        async fn labels(&self, tx: &Self::Tx) -> Result<Vec<String>> { self.0.labels(tx).await }
+        async fn relationship_types(&self, tx: &Self::Tx) -> Result<Vec<String>> { self.0.relationship_types(tx).await }
+        async fn all_nodes(&self, tx: &Self::Tx) -> Result<Vec<Node>> { self.0.all_nodes(tx).await }
+        async fn nodes_by_label(&self, tx: &Self::Tx, label: &str) -> Result<Vec<Node>> {
+            self.0.nodes_by_label(tx, label).await
+        }
+        async fn nodes_by_property(&self, tx: &Self::Tx, label: &str, key: &str, value: &Value) -> Result<Vec<Node>> {
+            self.0.nodes_by_property(tx, label, key, value).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_typed_relationships_use_the_backend_type_filter() {
+        let db = Defaults(MemoryBackend::new().with_case_insensitive_rel_types(true));
+        let mut tx = db.begin_tx(TxMode::ReadWrite).await.unwrap();
+        let a = db.create_node(&mut tx, &[], PropertyMap::new()).await.unwrap();
+        let b = db.create_node(&mut tx, &[], PropertyMap::new()).await.unwrap();
+        for rel_type in ["KNOWS", "LIKES", "OWES"] {
+            db.create_relationship(&mut tx, a, b, rel_type, PropertyMap::new()).await.unwrap();
+        }
+
+        let types = |rels: Vec<Relationship>| rels.into_iter().map(|r| r.rel_type).collect::<Vec<_>>();
+        let typed = db.get_relationships_typed(&tx, a, Direction::Outgoing, &["knows", "Likes", "KNOWS"]).await.unwrap();
+        assert_eq!(types(typed), ["KNOWS", "LIKES"]);
+        let typed = db.get_relationships_typed(&tx, a, Direction::Outgoing, &["owes"]).await.unwrap();
+        assert_eq!(types(typed), ["OWES"]);
+        assert_eq!(db.get_relationships_typed(&tx, a, Direction::Outgoing, &[]).await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,165 @@
+//! Append-only write-ahead log for `MemoryBackend`.
+//!
+//! Every mutation is appended as one JSON line before it is applied, and a
+//! backend opened on the same path replays the log to rebuild its state.
+//! `MemoryBackend::checkpoint()` bounds replay time: it writes the whole
+//! graph to `<path>.snapshot` and empties the log. Nothing checkpoints on
+//! its own: until the owner calls it, the log grows with every write and
+//! opening it replays all of them.
+//!
+//! Records carry a log sequence number (LSN) and the snapshot remembers the
+//! last one it covers, so a crash between writing a snapshot and truncating
+//! the log never replays a record twice. A crash mid-append leaves a final
+//! line without its newline; opening the log drops that torn record. Any
+//! other unreadable line is an error.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::index::IndexType;
+use crate::model::*;
+use crate::{Error, Result};
+
+/// When write-ahead log appends are flushed to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsyncPolicy {
+    /// `fsync` after every record: a write that returned survives power loss.
+    #[default]
+    Always,
+    /// Leave flushing to the OS: survives a process crash, not power loss.
+    Never,
+}
+
+/// One logged mutation, with the ids it assigned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) enum WalRecord {
+    CreateNode(Node),
+    DeleteNode(NodeId),
+    SetNodeProperty { id: NodeId, key: String, value: Value },
+    RemoveNodeProperty { id: NodeId, key: String },
+    AddLabel { id: NodeId, label: String },
+    RemoveLabel { id: NodeId, label: String },
+    CreateRelationship(Relationship),
+    SetRelationshipProperty { id: RelId, key: String, value: Value },
+    RemoveRelationshipProperty { id: RelId, key: String },
+    DeleteRelationship(RelId),
+    CreateIndex { label: String, property: String, index_type: IndexType },
+    DropIndex { label: String, property: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    lsn: u64,
+    record: WalRecord,
+}
+
+/// The whole graph as of LSN `lsn`.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct Snapshot {
+    pub lsn: u64,
+    pub next_node_id: u64,
+    pub next_rel_id: u64,
+    pub nodes: Vec<Node>,
+    pub relationships: Vec<Relationship>,
+    /// `(label, property, type)` of each sorted property index.
+    pub indexes: Vec<(String, String, IndexType)>,
+}
+
+pub(super) struct Wal {
+    path: PathBuf,
+    fsync: FsyncPolicy,
+    /// The log file and the LSN its next record gets.
+    log: Mutex<(File, u64)>,
+}
+
+impl Wal {
+    /// Open the log at `path`, creating it if needed. Returns the snapshot
+    /// and the records after it, in order, for the caller to replay.
+    pub(super) fn open(path: &Path, fsync: FsyncPolicy) -> Result<(Self, Option<Snapshot>, Vec<WalRecord>)> {
+        let snapshot = match fs::read(snapshot_path(path)) {
+            Ok(bytes) => Some(serde_json::from_slice::<Snapshot>(&bytes).map_err(|e| corrupt(path, e))?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let mut last_lsn = snapshot.as_ref().map_or(0, |s| s.lsn);
+
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        // Everything up to the last newline is complete; the rest is torn
+        let complete = bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+        let mut records = Vec::new();
+        for line in bytes[..complete].split(|b| *b == b'\n').filter(|l| !l.is_empty()) {
+            let entry: Entry = serde_json::from_slice(line).map_err(|e| corrupt(path, e))?;
+            // Records the snapshot already covers
+            if entry.lsn <= last_lsn {
+                continue;
+            }
+            last_lsn = entry.lsn;
+            records.push(entry.record);
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        if complete < bytes.len() {
+            file.set_len(complete as u64)?;
+        }
+        let wal = Self { path: path.to_path_buf(), fsync, log: Mutex::new((file, last_lsn + 1)) };
+        Ok((wal, snapshot, records))
+    }
+
+    pub(super) fn append(&self, record: WalRecord) -> Result<()> {
+        let mut log = self.log.lock();
+        let (file, next_lsn) = &mut *log;
+        let mut line = serde_json::to_vec(&Entry { lsn: *next_lsn, record })
+            .map_err(|e| Error::StorageError(format!("write-ahead log: {e}")))?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        if self.fsync == FsyncPolicy::Always {
+            file.sync_data()?;
+        }
+        *next_lsn += 1;
+        Ok(())
+    }
+
+    /// Replace the snapshot with `build(lsn)`, the graph as of the last
+    /// appended record, then empty the log. The caller must keep writers
+    /// out until this returns.
+    pub(super) fn checkpoint(&self, build: impl FnOnce(u64) -> Snapshot) -> Result<()> {
+        let mut log = self.log.lock();
+        let (file, next_lsn) = &mut *log;
+        let snapshot = build(*next_lsn - 1);
+        let bytes = serde_json::to_vec(&snapshot)
+            .map_err(|e| Error::StorageError(format!("write-ahead log snapshot: {e}")))?;
+
+        // Write aside and rename, so a crash leaves the old snapshot intact
+        let target = snapshot_path(&self.path);
+        let tmp = target.with_extension("snapshot.tmp");
+        let mut out = File::create(&tmp)?;
+        out.write_all(&bytes)?;
+        out.sync_all()?;
+        fs::rename(&tmp, &target)?;
+
+        file.set_len(0)?;
+        if self.fsync == FsyncPolicy::Always {
+            file.sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+/// `<path>.snapshot`, next to the log.
+fn snapshot_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".snapshot");
+    PathBuf::from(name)
+}
+
+fn corrupt(path: &Path, e: serde_json::Error) -> Error {
+    Error::StorageError(format!("corrupt write-ahead log {}: {e}", path.display()))
+}
@@ -505,3 +505,624 @@ async fn test_sum_empty_result() {
     let total: i64 = result.rows[0].get("sum").unwrap();
     assert_eq!(total, 0, "sum() on empty set should be 0");
 }
+
+// ============================================================================
+// 19. Arithmetic over aggregates
+// ============================================================================
+
+#[tokio::test]
+async fn test_arithmetic_over_aggregates() {
+    let graph = setup_people().await;
+
+    let result = graph
+        .execute(
+            "MATCH (n:Person) \
+             RETURN sum(n.age) + count(n) AS total, max(n.age) - min(n.age) AS spread, \
+                    sum(n.age) / count(n) AS mean",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.rows.len(), 1, "Aggregate arithmetic must not become a group key");
+    let total: i64 = result.rows[0].get("total").unwrap();
+    assert_eq!(total, 145);
+    let spread: i64 = result.rows[0].get("spread").unwrap();
+    assert_eq!(spread, 13);
+    let mean: i64 = result.rows[0].get("mean").unwrap();
+    assert_eq!(mean, 28);
+}
+
+// ============================================================================
+// 20. Function wrapping an aggregate, grouped by a key
+// ============================================================================
+
+#[tokio::test]
+async fn test_function_over_aggregate_grouped() {
+    let graph = setup_people().await;
+
+    let result = graph
+        .execute(
+            "MATCH (n:Person) WHERE n.age > 26 \
+             RETURN n.age > 29 AS senior, toString(count(n) * 10) AS score",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.rows.len(), 2);
+    for row in &result.rows {
+        let senior: bool = row.get("senior").unwrap();
+        let score: String = row.get("score").unwrap();
+        // senior: Bob (30), Charlie (35); not senior: Diana (28)
+        assert_eq!(score, if senior { "20" } else { "10" });
+    }
+}
+
+// ============================================================================
+// 21. apoc.agg.median / apoc.agg.statistics over a numeric column
+// ============================================================================
+
+#[tokio::test]
+async fn test_apoc_median_and_statistics() {
+    let graph = setup_people().await;
+
+    let result = graph
+        .execute(
+            "MATCH (n:Person) RETURN apoc.agg.median(n.age) AS median, apoc.agg.statistics(n.age) AS stats",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.rows.len(), 1);
+    // A Float even when the middle value is an integer
+    assert_eq!(result.rows[0].get::<Value>("median").unwrap(), Value::Float(28.0));
+
+    let Value::Map(stats) = result.rows[0].get::<Value>("stats").unwrap() else {
+        panic!("expected a map");
+    };
+    assert_eq!(stats["min"], Value::Float(22.0));
+    assert_eq!(stats["max"], Value::Float(35.0));
+    assert_eq!(stats["mean"], Value::Float(28.0));
+    // Population standard deviation of 25, 30, 35, 28, 22
+    let stdev = stats["stdev"].as_float().unwrap();
+    assert!((stdev - 19.6f64.sqrt()).abs() < 1e-9, "stdev = {stdev}");
+
+    // Even count: mean of the two middle values
+    let result = graph
+        .execute(
+            "MATCH (n:Person) WHERE n.age < 35 RETURN apoc.agg.median(n.age) AS median",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<Value>("median").unwrap(), Value::Float(26.5));
+}
+
+// ============================================================================
+// 22. apoc.map.fromLists zips keys with values
+// ============================================================================
+
+#[tokio::test]
+async fn test_apoc_map_from_lists() {
+    let graph = setup_people().await;
+
+    let result = graph
+        .execute(
+            "MATCH (n:Person) WHERE n.name = 'Alice' \
+             RETURN apoc.map.fromLists(['name', 'age'], [n.name, n.age]) AS m",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let Value::Map(m) = result.rows[0].get::<Value>("m").unwrap() else {
+        panic!("expected a map");
+    };
+    assert_eq!(m.len(), 2);
+    assert_eq!(m["name"], Value::from("Alice"));
+    assert_eq!(m["age"], Value::Int(25));
+
+    let err = graph
+        .execute(
+            "MATCH (n:Person) RETURN apoc.map.fromLists(['a', 'b'], [1]) AS m",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("2 keys but 1 values"), "got: {err}");
+}
+
+// ============================================================================
+// 23. head / last / tail / elementAt on empty and short lists
+// ============================================================================
+
+#[tokio::test]
+async fn test_list_element_functions() {
+    let graph = setup_people().await;
+    let mut params = PropertyMap::new();
+    params.insert("neg".into(), Value::Int(-1));
+    params.insert("far".into(), Value::Int(-4));
+
+    let query = "MATCH (n:Person) WHERE n.name = 'Alice' RETURN \
+                 head([]) AS h0, last([]) AS l0, tail([]) AS t0, \
+                 head([7]) AS h1, last([7]) AS l1, tail([7]) AS t1, \
+                 last([1, 2, 3]) AS l3, tail([1, 2, 3]) AS t3, head(null) AS hn, \
+                 elementAt([1, 2, 3], 0) AS e0, elementAt([1, 2, 3], 2) AS e2, \
+                 elementAt([1, 2, 3], $neg) AS eneg, elementAt([1, 2, 3], 3) AS eout, \
+                 elementAt([1, 2, 3], $far, 'none') AS efar, elementAt([], 0, 'none') AS eempty, \
+                 elementAt([7], 0) AS esingle, elementAt(null, 0) AS enull";
+    let result = graph.execute(query, params).await.unwrap();
+    let row = &result.rows[0];
+    let get = |c: &str| row.get::<Value>(c).unwrap();
+
+    assert_eq!(get("h0"), Value::Null);
+    assert_eq!(get("l0"), Value::Null);
+    assert_eq!(get("t0"), Value::List(vec![]));
+    assert_eq!(get("h1"), Value::Int(7));
+    assert_eq!(get("l1"), Value::Int(7));
+    assert_eq!(get("t1"), Value::List(vec![]));
+    assert_eq!(get("l3"), Value::Int(3));
+    assert_eq!(get("t3"), Value::from(vec![2i64, 3]));
+    assert_eq!(get("hn"), Value::Null);
+    assert_eq!(get("e0"), Value::Int(1));
+    assert_eq!(get("e2"), Value::Int(3));
+    assert_eq!(get("eneg"), Value::Int(3));
+    assert_eq!(get("eout"), Value::Null);
+    assert_eq!(get("efar"), Value::from("none"));
+    assert_eq!(get("eempty"), Value::from("none"));
+    assert_eq!(get("esingle"), Value::Int(7));
+    assert_eq!(get("enull"), Value::Null);
+
+    let err = graph
+        .execute("MATCH (n:Person) RETURN elementAt([1], 'x') AS e", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::TypeError { .. }), "got: {err:?}");
+}
+
+// ============================================================================
+// 24. apoc.text.join / format / slug / regexGroups
+// ============================================================================
+
+#[tokio::test]
+async fn test_apoc_text_functions() {
+    let graph = setup_people().await;
+
+    let query = "MATCH (n:Person) WHERE n.name = 'Alice' RETURN \
+                 apoc.text.join(['a', n.name, null, 'c'], '-') AS joined, \
+                 apoc.text.join([], ',') AS empty, \
+                 apoc.text.format('%s is %d (%.1f%%)', [n.name, n.age, 12.345]) AS formatted, \
+                 apoc.text.slug('  Hello, World!  ') AS slug, \
+                 apoc.text.slug('a b', '_') AS underscored, \
+                 apoc.text.join(null, '-') AS jnull, \
+                 apoc.text.format(null, [1]) AS fnull, \
+                 apoc.text.slug(null) AS snull";
+    let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+    let row = &result.rows[0];
+    let get = |c: &str| row.get::<Value>(c).unwrap();
+
+    assert_eq!(get("joined"), Value::from("a-Alice-c"));
+    assert_eq!(get("empty"), Value::from(""));
+    assert_eq!(get("formatted"), Value::from("Alice is 25 (12.3%)"));
+    assert_eq!(get("slug"), Value::from("Hello-World-"));
+    assert_eq!(get("underscored"), Value::from("a_b"));
+    assert_eq!(get("jnull"), Value::Null);
+    assert_eq!(get("fnull"), Value::Null);
+    assert_eq!(get("snull"), Value::Null);
+
+    let err = graph
+        .execute("MATCH (n:Person) RETURN apoc.text.format('%s and %s', ['x']) AS f", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("too few parameters"), "got: {err}");
+
+    let err = graph
+        .execute("MATCH (n:Person) RETURN apoc.text.join(['a', 1], ',') AS j", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::TypeError { .. }), "got: {err:?}");
+}
+
+// ============================================================================
+// 25. gds.similarity.cosine / euclidean over number lists
+// ============================================================================
+
+#[tokio::test]
+async fn test_gds_similarity_functions() {
+    let graph = setup_people().await;
+
+    let query = "MATCH (n:Person) WHERE n.name = 'Alice' RETURN \
+                 gds.similarity.cosine([1, 0], [0, 1]) AS orthogonal, \
+                 gds.similarity.cosine([1.0, 2.0, 3.0], [2.0, 4.0, 6.0]) AS parallel, \
+                 gds.similarity.cosine([3, 4], [4, 3]) AS cos, \
+                 gds.similarity.cosine([0, 0], [1, 1]) AS zero, \
+                 gds.similarity.euclideanDistance([0, 0], [3, 4]) AS dist, \
+                 gds.similarity.euclidean([0, 0], [3, 4]) AS euclid, \
+                 gds.similarity.euclidean([1, 2], [1, 2]) AS same, \
+                 gds.similarity.cosine(null, [1]) AS cnull";
+    let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+    let row = &result.rows[0];
+    let float = |c: &str| row.get::<f64>(c).unwrap();
+
+    assert_eq!(float("orthogonal"), 0.0);
+    assert!((float("parallel") - 1.0).abs() < 1e-12);
+    assert!((float("cos") - 0.96).abs() < 1e-12);
+    assert_eq!(float("zero"), 0.0);
+    assert_eq!(float("dist"), 5.0);
+    assert!((float("euclid") - 1.0 / 6.0).abs() < 1e-12);
+    assert_eq!(float("same"), 1.0);
+    assert_eq!(row.get::<Value>("cnull").unwrap(), Value::Null);
+
+    let err = graph
+        .execute("MATCH (n:Person) RETURN gds.similarity.cosine([1, 2], [1]) AS s", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("different lengths"), "got: {err}");
+
+    let err = graph
+        .execute("MATCH (n:Person) RETURN gds.similarity.euclidean([1, 'x'], [1, 2]) AS s", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::TypeError { .. }), "got: {err:?}");
+}
+
+// ============================================================================
+// 26. ORDER BY over a mixed-type column follows Neo4j's type precedence
+// ============================================================================
+
+#[tokio::test]
+async fn test_order_by_mixed_types() {
+    let graph = Graph::open_memory().await.unwrap();
+    for (i, v) in [Value::Int(10), Value::from("b"), Value::Null, Value::Float(2.5), Value::from("a"), Value::Int(-3)]
+        .into_iter()
+        .enumerate()
+    {
+        let mut params = PropertyMap::new();
+        params.insert("i".into(), Value::Int(i as i64));
+        params.insert("v".into(), v);
+        graph.mutate("CREATE (n:Item {i: $i, v: $v})", params).await.unwrap();
+    }
+
+    // Strings before numbers, nulls last
+    let result = graph
+        .execute("MATCH (n:Item) RETURN n.v AS v ORDER BY n.v", PropertyMap::new())
+        .await
+        .unwrap();
+    let values: Vec<Value> = result.rows.iter().map(|r| r.get("v").unwrap()).collect();
+    assert_eq!(
+        values,
+        [Value::from("a"), Value::from("b"), Value::Int(-3), Value::Float(2.5), Value::Int(10), Value::Null]
+    );
+
+    // DESC reverses the whole order, nulls first
+    let result = graph
+        .execute("MATCH (n:Item) RETURN n.v AS v ORDER BY n.v DESC", PropertyMap::new())
+        .await
+        .unwrap();
+    let values: Vec<Value> = result.rows.iter().map(|r| r.get("v").unwrap()).collect();
+    assert_eq!(
+        values,
+        [Value::Null, Value::Int(10), Value::Float(2.5), Value::Int(-3), Value::from("b"), Value::from("a")]
+    );
+}
+
+// ============================================================================
+// 27. Temporal arithmetic and comparison
+// ============================================================================
+
+#[tokio::test]
+async fn test_temporal_arithmetic_and_comparison() {
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use neo4j_rs::model::value::IsoDuration;
+
+    let graph = Graph::open_memory().await.unwrap();
+    let duration = |months, days, seconds| Value::Duration(IsoDuration { months, days, seconds, nanoseconds: 0 });
+    let mut params = PropertyMap::new();
+    params.insert("date".into(), Value::Date(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()));
+    params.insert("a".into(), Value::DateTime(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()));
+    params.insert("b".into(), Value::DateTime(Utc.with_ymd_and_hms(2024, 3, 2, 13, 30, 0).unwrap()));
+    params.insert("month".into(), duration(1, 0, 0));
+    params.insert("day".into(), duration(0, 1, 3600));
+    graph.mutate("CREATE (:Tick)", PropertyMap::new()).await.unwrap();
+
+    let result = graph
+        .execute(
+            "MATCH (t:Tick) RETURN $date + $month AS next, $date - $day AS prev, $a - $day AS earlier, \
+             $a < $b AS lt, $b - $a AS gap, $month + $day AS sum",
+            params.clone(),
+        )
+        .await
+        .unwrap();
+    let row = &result.rows[0];
+    // Adding a month clamps to the end of February; dates ignore the hour
+    assert_eq!(row.get::<Value>("next").unwrap(), Value::Date(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()));
+    assert_eq!(row.get::<Value>("prev").unwrap(), Value::Date(NaiveDate::from_ymd_opt(2024, 1, 30).unwrap()));
+    assert_eq!(
+        row.get::<Value>("earlier").unwrap(),
+        Value::DateTime(Utc.with_ymd_and_hms(2024, 2, 29, 11, 0, 0).unwrap())
+    );
+    assert!(row.get::<bool>("lt").unwrap());
+    assert_eq!(row.get::<Value>("gap").unwrap(), duration(0, 1, 5400));
+    assert_eq!(row.get::<Value>("sum").unwrap(), duration(1, 1, 3600));
+
+    let err = graph.execute("MATCH (t:Tick) RETURN $date + $date AS x", params.clone()).await.unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::TypeError { .. }), "{err}");
+
+    // Duration components that overflow i64 fail instead of wrapping
+    params.insert("huge".into(), duration(i64::MAX, 0, 0));
+    params.insert("tiny".into(), duration(0, 0, i64::MIN));
+    for expr in ["$huge + $month", "$tiny - $day", "$day - $tiny"] {
+        let query = format!("MATCH (t:Tick) RETURN {expr} AS x");
+        let err = graph.execute(&query, params.clone()).await.unwrap_err();
+        assert!(matches!(&err, neo4j_rs::Error::ExecutionError(m) if m.contains("out of range")), "{expr}: {err}");
+    }
+}
+
+// ============================================================================
+// 28. Grouping keys: all NULLs form one group, 1 and 1.0 another
+// ============================================================================
+
+#[tokio::test]
+async fn test_group_by_null_and_numeric_keys() {
+    let graph = Graph::open_memory().await.unwrap();
+    for query in [
+        "CREATE (n:P {team: 'a', v: 1, k: 1})",
+        "CREATE (n:P {team: 'a', v: 2, k: 1.0})",
+        "CREATE (n:P {v: 3, k: 2})",
+        "CREATE (n:P {v: 4})",
+        "CREATE (n:P {v: 5})",
+    ] {
+        graph.mutate(query, PropertyMap::new()).await.unwrap();
+    }
+
+    let result = graph
+        .execute("MATCH (n:P) RETURN n.team AS team, count(*) AS c, sum(n.v) AS s", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 2);
+    let null_group = result.rows.iter().find(|r| r.get::<Value>("team").unwrap().is_null()).unwrap();
+    assert_eq!(null_group.get::<i64>("c").unwrap(), 3);
+    assert_eq!(null_group.get::<i64>("s").unwrap(), 12);
+
+    let result = graph
+        .execute("MATCH (n:P) RETURN n.k AS k, count(*) AS c", PropertyMap::new())
+        .await
+        .unwrap();
+    let mut counts: Vec<(String, i64)> =
+        result.rows.iter().map(|r| (r.get::<Value>("k").unwrap().to_string(), r.get("c").unwrap())).collect();
+    counts.sort();
+    assert_eq!(counts, [("1".into(), 2), ("2".into(), 1), ("null".into(), 2)]);
+
+    // DISTINCT and count(DISTINCT ...) use the same equivalence
+    let result = graph
+        .execute("MATCH (n:P) RETURN DISTINCT n.k AS k", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 3);
+    let result = graph
+        .execute("MATCH (n:P) RETURN count(DISTINCT n.k) AS c", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("c").unwrap(), 2);
+}
+
+// ============================================================================
+// 29. LIMIT n PERCENT keeps the top share of the ordered rows
+// ============================================================================
+
+#[tokio::test]
+async fn test_limit_percent() {
+    let graph = Graph::open_memory().await.unwrap();
+    for i in 1..=12 {
+        graph.mutate(&format!("CREATE (:Player {{score: {}}})", i * 10), PropertyMap::new()).await.unwrap();
+    }
+
+    let scores = |query: &'static str| {
+        let graph = &graph;
+        async move {
+            let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+            result.rows.iter().map(|r| r.get::<i64>("score").unwrap()).collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(
+        scores("MATCH (p:Player) RETURN p.score AS score ORDER BY p.score DESC LIMIT 25 PERCENT").await,
+        [120, 110, 100],
+    );
+    // Rounds up, and any positive share keeps at least one row
+    assert_eq!(scores("MATCH (p:Player) RETURN p.score AS score ORDER BY p.score DESC LIMIT 30 PERCENT").await.len(), 4);
+    assert_eq!(scores("MATCH (p:Player) RETURN p.score AS score ORDER BY p.score DESC LIMIT 0.5 PERCENT").await, [120]);
+    assert!(scores("MATCH (p:Player) RETURN p.score AS score LIMIT 0 PERCENT").await.is_empty());
+    assert_eq!(scores("MATCH (p:Player) RETURN p.score AS score LIMIT 100 PERCENT").await.len(), 12);
+
+    assert!(graph.execute("MATCH (p:Player) RETURN p LIMIT 150 PERCENT", PropertyMap::new()).await.is_err());
+}
+
+// ============================================================================
+// 30. Incremental aggregates agree with the buffering path
+// ============================================================================
+
+#[tokio::test]
+async fn test_incremental_aggregation_matches_buffered() {
+    let graph = Graph::open_memory().await.unwrap();
+    let values = [
+        (Value::from("a"), Value::Int(3)),
+        (Value::from("b"), Value::Float(2.5)),
+        (Value::from("a"), Value::Null),
+        (Value::Null, Value::Int(-4)),
+        (Value::from("a"), Value::Float(0.5)),
+        (Value::from("b"), Value::Int(7)),
+        (Value::Null, Value::Null),
+        (Value::from("c"), Value::Null),
+    ];
+    for (g, v) in values {
+        let mut params = PropertyMap::new();
+        params.insert("g".into(), g);
+        params.insert("v".into(), v);
+        graph.mutate("CREATE (n:Item {g: $g, v: $v})", params).await.unwrap();
+    }
+
+    // collect() sends the whole aggregation down the buffering path
+    let aggregates = "count(*) AS rows, count(n.v) AS c, sum(n.v) AS s, avg(n.v) AS a, min(n.v) AS lo, max(n.v) AS hi";
+    let columns = ["g", "rows", "c", "s", "a", "lo", "hi"];
+    let run = |query: String| {
+        let graph = &graph;
+        async move {
+            let result = graph.execute(&query, PropertyMap::new()).await.unwrap();
+            result
+                .rows
+                .iter()
+                .map(|r| columns.iter().map(|c| r.get::<Value>(c).unwrap_or(Value::Null)).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        }
+    };
+    for source in ["MATCH (n:Item)", "MATCH (n:Item) WHERE n.g = 'c'", "MATCH (n:Missing)"] {
+        let incremental = run(format!("{source} RETURN n.g AS g, {aggregates}")).await;
+        let buffered = run(format!("{source} RETURN n.g AS g, {aggregates}, collect(n.v) AS all")).await;
+        assert_eq!(incremental, buffered, "{source}");
+
+        let incremental = run(format!("{source} RETURN {aggregates}")).await;
+        let buffered = run(format!("{source} RETURN {aggregates}, collect(n.v) AS all")).await;
+        assert_eq!(incremental, buffered, "{source}");
+        assert_eq!(incremental.len(), 1, "{source}");
+    }
+
+    let groups = run(format!("MATCH (n:Item) RETURN n.g AS g, {aggregates}")).await;
+    assert_eq!(groups.len(), 4);
+    assert_eq!(
+        groups[0],
+        [Value::from("a"), Value::Int(3), Value::Int(2), Value::Float(3.5), Value::Float(1.75), Value::Float(0.5), Value::Int(3)],
+    );
+    assert_eq!(
+        groups[3],
+        [Value::from("c"), Value::Int(1), Value::Int(0), Value::Int(0), Value::Null, Value::Null, Value::Null],
+    );
+
+    // An integer sum past the i64 range fails on both paths
+    graph.mutate("CREATE (:Big {v: 9223372036854775807}), (:Big {v: 1})", PropertyMap::new()).await.unwrap();
+    for query in ["MATCH (n:Big) RETURN sum(n.v) AS s", "MATCH (n:Big) RETURN sum(n.v) AS s, collect(n.v) AS all"] {
+        let err = graph.execute(query, PropertyMap::new()).await.unwrap_err();
+        assert!(err.to_string().contains("integer overflow"), "{query}: {err}");
+    }
+}
+
+// ============================================================================
+// 31. String collation: byte order by default, case-insensitive on request
+// ============================================================================
+
+#[tokio::test]
+async fn test_string_collation() {
+    use neo4j_rs::Collation;
+
+    for collation in [Collation::ByteOrder, Collation::CaseInsensitive] {
+        let graph = Graph::open_memory().await.unwrap().with_collation(collation);
+        for name in ["bob", "Ada", "carol", "ada", "Bob"] {
+            let mut params = PropertyMap::new();
+            params.insert("name".into(), Value::from(name));
+            graph.mutate("CREATE (:Person {name: $name})", params).await.unwrap();
+        }
+        let strings = |query: &'static str, column: &'static str| {
+            let graph = &graph;
+            async move {
+                let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+                result.rows.iter().map(|r| r.get::<String>(column).unwrap()).collect::<Vec<_>>()
+            }
+        };
+        // `p.name < 'b'` plans as a range scan over the label
+        let below_b = "MATCH (p:Person) WHERE p.name < 'b' RETURN p.name AS name ORDER BY p.name";
+
+        let ordered = strings("MATCH (p:Person) RETURN p.name AS name ORDER BY p.name", "name").await;
+        let lowest = strings("MATCH (p:Person) RETURN min(p.name) AS lo", "lo").await;
+        let highest = strings("MATCH (p:Person) RETURN max(p.name) AS hi", "hi").await;
+        let lt = graph
+            .execute("UNWIND [1] AS i RETURN 'ada' < 'Bob' AS lt", PropertyMap::new())
+            .await
+            .unwrap()
+            .rows[0]
+            .get::<bool>("lt")
+            .unwrap();
+        // Ties on case break by code point, so both agree on the extremes
+        assert_eq!(lowest, ["Ada"]);
+        assert_eq!(highest, ["carol"]);
+        if collation == Collation::ByteOrder {
+            assert_eq!(ordered, ["Ada", "Bob", "ada", "bob", "carol"]);
+            assert_eq!(strings(below_b, "name").await, ["Ada", "Bob", "ada"]);
+            assert!(!lt);
+        } else {
+            assert_eq!(ordered, ["Ada", "ada", "Bob", "bob", "carol"]);
+            assert_eq!(strings(below_b, "name").await, ["Ada", "ada"]);
+            assert!(lt);
+        }
+
+        // An index on the property doesn't change which rows the range keeps
+        let unindexed = strings(below_b, "name").await;
+        graph.mutate("CREATE INDEX FOR (n:Person) ON (n.name)", PropertyMap::new()).await.unwrap();
+        assert_eq!(strings(below_b, "name").await, unindexed, "{collation:?}");
+    }
+}
+
+// ============================================================================
+// 32. apoc.coll.* list functions
+// ============================================================================
+
+#[tokio::test]
+async fn test_apoc_coll_functions() {
+    let graph = Graph::open_memory().await.unwrap();
+    let query = "UNWIND [1] AS i RETURN \
+                 apoc.coll.sum([1, 2.5, null, 3]) AS sum, \
+                 apoc.coll.avg([1, null, 2]) AS avg, \
+                 apoc.coll.avg([null]) AS avg_empty, \
+                 apoc.coll.min([3, null, 1, 2]) AS min, \
+                 apoc.coll.max(['b', 'a', 'c']) AS max, \
+                 apoc.coll.max([null]) AS max_empty, \
+                 apoc.coll.sort([3, 'b', true, 1.5, null, 'a', [1]]) AS sorted, \
+                 apoc.coll.toSet([1, 1.0, 'a', null, 'a', null, [1, 2], [1, 2]]) AS deduped, \
+                 apoc.coll.contains([1, 'a', [2]], 1.0) AS has_one, \
+                 apoc.coll.contains([1, 'a', [2]], [2]) AS has_list, \
+                 apoc.coll.contains([null], null) AS has_null, \
+                 apoc.coll.indexOf(['a', 'b', 'a'], 'a') AS first_a, \
+                 apoc.coll.indexOf(['a', 'b'], 'z') AS missing, \
+                 apoc.coll.flatten([1, [2, [3, [4]]], [], 5]) AS flat, \
+                 apoc.coll.sum(null) AS sum_null";
+    let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+    let row = &result.rows[0];
+    let get = |c: &str| row.get::<Value>(c).unwrap();
+    let list = |items: Vec<Value>| Value::List(items);
+
+    assert_eq!(get("sum"), Value::Float(6.5));
+    assert_eq!(get("avg"), Value::Float(1.5));
+    assert_eq!(get("avg_empty"), Value::Null);
+    assert_eq!(get("min"), Value::Int(1));
+    assert_eq!(get("max"), Value::from("c"));
+    assert_eq!(get("max_empty"), Value::Null);
+    // ORDER BY order across types: lists, strings, booleans, numbers, null
+    assert_eq!(
+        get("sorted"),
+        list(vec![
+            list(vec![Value::Int(1)]),
+            Value::from("a"),
+            Value::from("b"),
+            Value::Bool(true),
+            Value::Float(1.5),
+            Value::Int(3),
+            Value::Null,
+        ])
+    );
+    assert_eq!(
+        get("deduped"),
+        list(vec![Value::Int(1), Value::from("a"), Value::Null, list(vec![Value::Int(1), Value::Int(2)])])
+    );
+    assert_eq!(get("has_one"), Value::Bool(true));
+    assert_eq!(get("has_list"), Value::Bool(true));
+    assert_eq!(get("has_null"), Value::Bool(false));
+    assert_eq!(get("first_a"), Value::Int(0));
+    assert_eq!(get("missing"), Value::Int(-1));
+    assert_eq!(get("flat"), list((1..=5).map(Value::Int).collect()));
+    assert_eq!(get("sum_null"), Value::Null);
+
+    let err = graph
+        .execute("UNWIND [1] AS i RETURN apoc.coll.sum([1, 'a']) AS s", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::TypeError { .. }), "got: {err:?}");
+}
@@ -3,7 +3,11 @@
 //! Each test exercises: parse -> plan -> optimize -> execute against MemoryBackend.
 //! These tests use `Graph::execute()` for reads and `Graph::mutate()` for writes.
 
-use neo4j_rs::{Graph, Node, PropertyMap, Value};
+use std::sync::{Arc, Mutex};
+
+use neo4j_rs::{cypher, Graph, Node, PropertyMap, PropertyMapBuilder, QueryObserver, Value};
+use neo4j_rs::execution::ExecutionStats;
+use neo4j_rs::planner::{self, LogicalPlan};
 
 // ============================================================================
 // 1. CREATE a node, then MATCH it back
@@ -416,3 +420,460 @@ async fn test_return_multiple_columns() {
     let age: i64 = result.rows[0].get("n.age").unwrap();
     assert_eq!(age, 3);
 }
+
+// ============================================================================
+// 11. ResultRow::get_opt — missing column and NULL become None
+// ============================================================================
+
+#[tokio::test]
+async fn test_result_row_get_opt() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    graph
+        .mutate("CREATE (n:Person {name: 'Ada'})", PropertyMap::new())
+        .await
+        .unwrap();
+
+    let result = graph
+        .execute("MATCH (n:Person) RETURN n.name, n.age", PropertyMap::new())
+        .await
+        .unwrap();
+
+    let row = &result.rows[0];
+    assert_eq!(row.get_opt::<String>("n.name").unwrap(), Some("Ada".to_string()));
+    assert_eq!(row.get_opt::<i64>("n.age").unwrap(), None, "NULL value should be None");
+    assert_eq!(row.get_opt::<i64>("missing").unwrap(), None, "missing column should be None");
+    assert!(row.get_opt::<i64>("n.name").is_err(), "type mismatch should still error");
+    assert!(row.get::<i64>("missing").is_err(), "get stays strict");
+}
+
+// ============================================================================
+// 12. MATCH ... LIMIT is deterministic across runs
+// ============================================================================
+
+#[tokio::test]
+async fn test_match_limit_deterministic_order() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    for i in 0..20 {
+        graph
+            .mutate(&format!("CREATE (n:Person {{idx: {}}})", i), PropertyMap::new())
+            .await
+            .unwrap();
+    }
+
+    let run = || async {
+        let result = graph
+            .execute("MATCH (n) RETURN n.idx LIMIT 5", PropertyMap::new())
+            .await
+            .unwrap();
+        result.rows.iter().map(|r| r.get::<i64>("n.idx").unwrap()).collect::<Vec<_>>()
+    };
+
+    let first = run().await;
+    let second = run().await;
+    assert_eq!(first, second);
+    assert_eq!(first, vec![0, 1, 2, 3, 4], "nodes come back in creation (id) order");
+}
+
+// ============================================================================
+// 13. PropertyMapBuilder as query parameters
+// ============================================================================
+
+#[tokio::test]
+async fn test_property_map_builder_params() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let params = PropertyMapBuilder::from_json(serde_json::json!({"name": "Ada", "age": 3})).unwrap();
+    graph.mutate("CREATE (n:Person {name: $name, age: $age})", params).await.unwrap();
+
+    let result = graph
+        .execute(
+            "MATCH (n:Person) WHERE n.name IN $names RETURN n.age AS age",
+            PropertyMapBuilder::new().insert_list("names", ["Ada", "Bob"]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<i64>("age").unwrap(), 3);
+}
+
+// ============================================================================
+// 14. QueryObserver sees every operator of the plan
+// ============================================================================
+
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<String>>,
+}
+
+impl QueryObserver for Recorder {
+    fn on_operator_start(&self, op_name: &str) {
+        self.events.lock().unwrap().push(format!("start {op_name}"));
+    }
+
+    fn on_rows_produced(&self, op_name: &str, rows: usize) {
+        self.events.lock().unwrap().push(format!("{op_name} {rows}"));
+    }
+
+    fn on_query_complete(&self, stats: &ExecutionStats) {
+        self.events.lock().unwrap().push(format!("complete {}", stats.nodes_created));
+    }
+}
+
+#[tokio::test]
+async fn test_query_observer_counts_operators() {
+    let recorder = Arc::new(Recorder::default());
+    let graph = Graph::open_memory().await.unwrap().with_observer(recorder.clone());
+
+    graph.mutate("CREATE (n:Person {name: 'Ada'})", PropertyMap::new()).await.unwrap();
+    graph.mutate("CREATE (n:Person {name: 'Alan'})", PropertyMap::new()).await.unwrap();
+    assert_eq!(recorder.events.lock().unwrap().last().unwrap(), "complete 1");
+    recorder.events.lock().unwrap().clear();
+
+    graph
+        .execute("MATCH (n:Person) RETURN n.name LIMIT 1", PropertyMap::new())
+        .await
+        .unwrap();
+    let events = recorder.events.lock().unwrap().clone();
+    assert_eq!(
+        events,
+        [
+            "start Limit",
+            "start Project",
+            "start NodeScan",
+            "NodeScan 2",
+            "Project 2",
+            "Limit 1",
+            "complete 0",
+        ]
+    );
+}
+
+// ============================================================================
+// 15. Graph::schema() tracks labels, types, indexes and constraints
+// ============================================================================
+
+#[tokio::test]
+async fn test_schema_registry() {
+    use neo4j_rs::StorageBackend;
+
+    let graph = Graph::open_memory().await.unwrap();
+    {
+        // Written before any query, so the first query must load it
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::TxMode::ReadWrite).await.unwrap();
+        backend.create_node(&mut tx, &["Legacy"], PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+    assert!(graph.schema().labels().is_empty());
+
+    graph
+        .mutate("CREATE (a:Person {name: 'Ada'})-[:KNOWS]->(b:Person:Admin {name: 'Alan'})", PropertyMap::new())
+        .await
+        .unwrap();
+    let schema = graph.schema();
+    assert_eq!(schema.labels(), ["Admin", "Legacy", "Person"]);
+    assert_eq!(schema.relationship_types(), ["KNOWS"]);
+    assert!(!schema.has_index("Person", "name"));
+
+    graph.mutate("CREATE INDEX FOR (n:Person) ON (n.name)", PropertyMap::new()).await.unwrap();
+    assert!(schema.has_index("Person", "name"));
+    assert_eq!(schema.index_type("Person", "name"), Some(neo4j_rs::index::IndexType::BTree));
+    graph.mutate("DROP INDEX ON :Person(name)", PropertyMap::new()).await.unwrap();
+    assert!(schema.indexes().is_empty());
+
+    // A constraint the backend rejects is not recorded
+    let err = graph
+        .mutate("CREATE CONSTRAINT FOR (n:Person) REQUIRE n.name IS UNIQUE", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("backend does not support constraints"), "got: {err}");
+    assert!(schema.constraints().is_empty());
+
+    // Direct backend writes show up after a refresh
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::TxMode::ReadWrite).await.unwrap();
+        backend.create_node(&mut tx, &["Direct"], PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+    assert!(!schema.has_label("Direct"));
+    assert!(graph.refresh_schema().await.unwrap().has_label("Direct"));
+
+    // A rolled-back transaction leaves the registry as it was
+    let mut tx = graph.begin(neo4j_rs::TxMode::ReadWrite).await.unwrap();
+    tx.execute("CREATE (:Phantom)-[:HAUNTS]->(:Phantom)", PropertyMap::new()).await.unwrap();
+    tx.rollback().await.unwrap();
+    assert!(!schema.has_label("Phantom"));
+    assert!(!schema.has_relationship_type("HAUNTS"));
+
+    // Indexes created on the backend are loaded along with labels
+    graph.backend().create_index("Direct", "key", neo4j_rs::index::IndexType::Unique).await.unwrap();
+    graph.refresh_schema().await.unwrap();
+    assert_eq!(schema.index_type("Direct", "key"), Some(neo4j_rs::index::IndexType::Unique));
+}
+
+// ============================================================================
+// 16. ExecutionStats reports rows returned and time per phase
+// ============================================================================
+
+#[tokio::test]
+async fn test_execution_stats_phase_times() {
+    let graph = Graph::open_memory().await.unwrap();
+    for i in 0..200 {
+        graph.mutate(&format!("CREATE (n:Item {{i: {i}}})"), PropertyMap::new()).await.unwrap();
+    }
+
+    let result = graph
+        .execute("MATCH (n:Item) WHERE n.i % 2 = 0 RETURN n.i AS i ORDER BY i", PropertyMap::new())
+        .await
+        .unwrap();
+    let stats = &result.stats;
+    assert_eq!(stats.rows_returned, 100);
+    assert_eq!(stats.rows_returned, result.rows.len() as u64);
+    assert!(stats.parse_time_us + stats.plan_time_us > 0, "{stats:?}");
+    assert!(stats.execute_time_us > 0, "{stats:?}");
+
+    // The total is the phases' sum, truncated to milliseconds
+    let phases_us = stats.parse_time_us + stats.plan_time_us + stats.execute_time_us;
+    assert_eq!(stats.execution_time_ms, phases_us / 1000, "{stats:?}");
+
+    // A write without RETURN returns no rows
+    let result = graph.mutate("CREATE (n:Item {i: 1000})", PropertyMap::new()).await.unwrap();
+    assert_eq!(result.stats.rows_returned, 0);
+    assert!(result.rows.is_empty() && result.columns.is_empty(), "{:?}", result.columns);
+    assert_eq!(result.stats.nodes_created, 1);
+    let result = graph.mutate("CREATE (n:Item {i: 1001}) RETURN n.i AS i", PropertyMap::new()).await.unwrap();
+    assert_eq!(result.stats.rows_returned, 1);
+}
+
+// ============================================================================
+// 17. run() picks the transaction mode; execute/mutate reject the other kind
+// ============================================================================
+
+#[tokio::test]
+async fn test_run_detects_reads_and_writes() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let created = graph.run("CREATE (n:Person {name: 'Ada'}) RETURN n.name AS name", PropertyMap::new()).await.unwrap();
+    assert_eq!(created.stats.nodes_created, 1);
+    let read = graph.run("MATCH (n:Person) RETURN n.name AS name", PropertyMap::new()).await.unwrap();
+    assert_eq!(read.rows[0].get::<String>("name").unwrap(), "Ada");
+
+    // execute() refuses writes, mutate() refuses reads, and neither touches the graph
+    for write in [
+        "CREATE (n:Person {name: 'Bob'})",
+        "MATCH (n:Person) SET n.age = 1",
+        "MATCH (n:Person) DETACH DELETE n",
+        "CALL apoc.create.node(['Person'], {name: 'Bob'})",
+    ] {
+        let err = graph.execute(write, PropertyMap::new()).await.unwrap_err();
+        assert!(matches!(err, neo4j_rs::Error::TxError(_)), "{write}: {err}");
+    }
+    let err = graph.mutate("MATCH (n:Person) RETURN n", PropertyMap::new()).await.unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::TxError(_)), "{err}");
+
+    let result = graph.execute("MATCH (n:Person) RETURN n.name AS name, n.age AS age", PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<Value>("age").unwrap(), Value::Null);
+}
+
+// ============================================================================
+// 18. Inline properties in a MATCH pattern restrict the match
+// ============================================================================
+
+#[tokio::test]
+async fn test_match_inline_properties() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person {name: 'Ada', age: 36})-[:KNOWS {since: 1843}]->(b:Person {name: 'Bob', age: 36})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    graph
+        .mutate("MATCH (b:Person) WHERE b.name = 'Bob' CREATE (b)-[:KNOWS {since: 2020}]->(:Person {name: 'Cy'})", PropertyMap::new())
+        .await
+        .unwrap();
+    let names = |result: &neo4j_rs::QueryResult| -> Vec<String> {
+        result.rows.iter().map(|r| r.get::<String>("name").unwrap()).collect()
+    };
+
+    let result = graph.execute("MATCH (n:Person {name: 'Ada'}) RETURN n.name AS name", PropertyMap::new()).await.unwrap();
+    assert_eq!(names(&result), ["Ada"]);
+
+    // Several keys, a parameter, and WHERE on top all apply
+    let params = PropertyMapBuilder::new().insert_int("age", 36);
+    let result = graph
+        .execute(
+            "MATCH (n:Person {age: $age, name: 'Bob'}) WHERE n.age > 30 RETURN n.name AS name",
+            params,
+        )
+        .await
+        .unwrap();
+    assert_eq!(names(&result), ["Bob"]);
+
+    // On the far node and on the relationship, named or not
+    let result = graph
+        .execute("MATCH (:Person)-[:KNOWS]->(n {name: 'Cy'}) RETURN n.name AS name", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(names(&result), ["Cy"]);
+    for query in [
+        "MATCH (a:Person)-[:KNOWS {since: 1843}]->(b) RETURN a.name AS name",
+        "MATCH (a:Person)-[r:KNOWS {since: 1843}]->(b) RETURN a.name AS name",
+    ] {
+        let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+        assert_eq!(names(&result), ["Ada"], "{query}");
+    }
+
+    let result = graph.execute("MATCH (n:Person {name: 'Nobody'}) RETURN n.name AS name", PropertyMap::new()).await.unwrap();
+    assert!(result.rows.is_empty());
+}
+
+// ============================================================================
+// 19. RETURN * projects the variables in scope
+// ============================================================================
+
+#[tokio::test]
+async fn test_return_star() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate("CREATE (:Person {name: 'Ada'})-[:KNOWS]->(:Person {name: 'Bob'})", PropertyMap::new())
+        .await
+        .unwrap();
+    let columns = |query: &str| {
+        let graph = &graph;
+        let query = query.to_string();
+        async move { graph.execute(&query, PropertyMap::new()).await.unwrap().columns }
+    };
+
+    // Pattern order, anonymous elements left out
+    assert_eq!(columns("MATCH (a)-[r]->(b) RETURN *").await, ["a", "r", "b"]);
+    assert_eq!(columns("MATCH (b)<-[r]-(a) RETURN *").await, ["b", "r", "a"]);
+    assert_eq!(columns("MATCH p = (a)-->(b) RETURN *").await, ["p", "a", "b"]);
+    assert_eq!(columns("MATCH (a)-->(b) UNWIND [1, 2] AS i RETURN *").await, ["a", "b", "i"]);
+
+    let result = graph.execute("MATCH (a)-[r]->(b) RETURN *", PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert!(matches!(result.rows[0].get::<Value>("a").unwrap(), Value::Node(n) if n.properties["name"] == Value::from("Ada")));
+    assert!(matches!(result.rows[0].get::<Value>("r").unwrap(), Value::Relationship(_)));
+
+    // WITH passes on only what it names, under the names it gives
+    assert_eq!(columns("MATCH (a)-[r]->(b) WITH a RETURN *").await, ["a"]);
+    let result = graph
+        .execute("MATCH (a)-[r]->(b) WITH a, b.name AS friend RETURN *", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.columns, ["a", "friend"]);
+    assert_eq!(result.rows[0].get::<String>("friend").unwrap(), "Bob");
+    assert_eq!(columns("MATCH (a)-[r]->(b) WITH * RETURN *").await, ["a", "r", "b"]);
+
+    // Nothing named, nothing to return
+    assert!(graph.execute("MATCH (:Person)-->() RETURN *", PropertyMap::new()).await.is_err());
+}
+
+// ============================================================================
+// 20. id() and elementId() of nodes and relationships
+// ============================================================================
+
+#[tokio::test]
+async fn test_id_and_element_id() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate("CREATE (:Person {name: 'Ada'})-[:KNOWS]->(:Person {name: 'Bob'})", PropertyMap::new())
+        .await
+        .unwrap();
+
+    let result = graph
+        .execute(
+            "MATCH (a)-[r:KNOWS]->(b) RETURN id(a) AS a, elementId(a) AS ea, id(r) AS r, elementId(r) AS er",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let row = &result.rows[0];
+    let id = row.get::<i64>("a").unwrap();
+    assert_eq!(row.get::<String>("ea").unwrap(), id.to_string());
+    assert_eq!(row.get::<String>("er").unwrap(), row.get::<i64>("r").unwrap().to_string());
+
+    // The string id groups and filters like any other value
+    let result = graph
+        .execute("MATCH (n:Person) RETURN elementId(n) AS e, count(*) AS c ORDER BY e", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 2);
+    assert!(result.rows.iter().all(|r| r.get::<i64>("c").unwrap() == 1));
+    let params = PropertyMapBuilder::new().insert_str("e", id.to_string());
+    let result = graph
+        .execute("MATCH (n) WHERE elementId(n) = $e RETURN n.name AS name", params)
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<String>("name").unwrap(), "Ada");
+
+    // Null in, null out; a path has no id
+    let result = graph
+        .execute("UNWIND [null] AS x RETURN id(x) AS i, elementId(x) AS e", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<Value>("i").unwrap(), Value::Null);
+    assert_eq!(result.rows[0].get::<Value>("e").unwrap(), Value::Null);
+    let err = graph
+        .execute("MATCH p = (a)-->(b) RETURN id(p) AS i", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::TypeError { .. }), "{err:?}");
+}
+
+// ============================================================================
+// 21. WITH * passes on every variable in scope, plus any items after it
+// ============================================================================
+
+#[tokio::test]
+async fn test_with_star_pass_through() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person {name: 'Ada'}), (b:Person {name: 'Bob'}), (c:Person {name: 'Cy'}), \
+             (a)-[:KNOWS {weight: 2}]->(b), (a)-[:KNOWS {weight: 0}]->(c)",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    let result = graph
+        .execute("MATCH (a)-[r]->(b) WITH *, r.weight AS w WHERE w > 0 RETURN a, b", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert!(matches!(result.rows[0].get::<Value>("a").unwrap(), Value::Node(n) if n.properties["name"] == Value::from("Ada")));
+    assert!(matches!(result.rows[0].get::<Value>("b").unwrap(), Value::Node(n) if n.properties["name"] == Value::from("Bob")));
+
+    // Everything in scope, in pattern order, then the added items
+    let result = graph
+        .execute("MATCH (a)-[r]->(b) WITH *, r.weight AS w WHERE w > 0 RETURN *", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.columns, ["a", "r", "b", "w"]);
+    assert_eq!(result.rows[0].get::<i64>("w").unwrap(), 2);
+
+    // Names from an earlier WITH carry through a later WITH *
+    let result = graph
+        .execute("MATCH (a)-[r]->(b) WITH b.name AS friend WITH * WHERE friend = 'Cy' RETURN *", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.columns, ["friend"]);
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<String>("friend").unwrap(), "Cy");
+
+    // A WITH's WHERE filters at the WITH, before a later MATCH expands
+    let query = "MATCH (a:Person) WITH a WHERE a.name = 'Ada' MATCH (a)-[r]->(b) RETURN b.name";
+    let plan = planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap();
+    let LogicalPlan::Project { input, .. } = &plan else { panic!("{plan:?}") };
+    let LogicalPlan::Expand { input, .. } = input.as_ref() else { panic!("{plan:?}") };
+    assert!(matches!(input.as_ref(), LogicalPlan::Filter { .. }), "{plan:?}");
+    let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows.len(), 2);
+}
@@ -6,7 +6,7 @@
 //!   MATCH (a:Person), (b:Person) WHERE a.name = 'Alice' AND b.name = 'Bob'
 //!   CREATE (a)-[:KNOWS]->(b)
 
-use neo4j_rs::{Graph, PropertyMap};
+use neo4j_rs::{Graph, PropertyMap, Value};
 
 #[tokio::test]
 async fn test_match_create_relationship() {
@@ -220,3 +220,38 @@ async fn test_unwind_with_match() {
 
     assert_eq!(result.rows.len(), 2, "UNWIND should produce 2 rows");
 }
+
+#[tokio::test]
+async fn test_unwind_range_null_and_map_entries() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let result = graph.execute("UNWIND range(1, 10) AS i RETURN i", PropertyMap::new()).await.unwrap();
+    let values: Vec<i64> = result.rows.iter().map(|r| r.get("i").unwrap()).collect();
+    assert_eq!(values, (1..=10).collect::<Vec<_>>());
+
+    // Neo4j: UNWIND null yields no rows, not one null row
+    let result = graph.execute("UNWIND null AS x RETURN x", PropertyMap::new()).await.unwrap();
+    assert!(result.rows.is_empty());
+    let result = graph.execute("UNWIND [] AS x RETURN x", PropertyMap::new()).await.unwrap();
+    assert!(result.rows.is_empty());
+
+    // A map is unwound through apoc.map.entries, sorted by key
+    let mut params = PropertyMap::new();
+    params.insert(
+        "m".into(),
+        Value::Map(PropertyMap::from([("b".into(), Value::Int(2)), ("a".into(), Value::from("x"))])),
+    );
+    let result = graph
+        .execute("UNWIND apoc.map.entries($m) AS e RETURN e.key AS k, e.value AS v", params)
+        .await
+        .unwrap();
+    let entries: Vec<(String, Value)> =
+        result.rows.iter().map(|r| (r.get("k").unwrap(), r.get("v").unwrap())).collect();
+    assert_eq!(entries, vec![("a".into(), Value::from("x")), ("b".into(), Value::Int(2))]);
+
+    let result = graph
+        .execute("UNWIND apoc.map.entries(null) AS e RETURN e", PropertyMap::new())
+        .await
+        .unwrap();
+    assert!(result.rows.is_empty());
+}
@@ -4,7 +4,7 @@
 //! parameter substitution, arithmetic, CASE expressions, and more.
 //! Each test exercises: parse -> plan -> optimize -> execute against MemoryBackend.
 
-use neo4j_rs::{Graph, PropertyMap, Value};
+use neo4j_rs::{Graph, PropertyMap, PropertyMapBuilder, Value};
 
 // ============================================================================
 // Helper: create a set of Person nodes with names and ages.
@@ -608,3 +608,556 @@ async fn test_multiple_labels_match() {
         "Both Ada and Bob have the Person label"
     );
 }
+
+// ============================================================================
+// 19. Lenient conversions: toIntegerOrNull / toFloatOrNull / toBooleanOrNull
+// ============================================================================
+
+#[tokio::test]
+async fn test_lenient_conversions_return_null() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let result = graph
+        .execute(
+            "UNWIND ['42', '2.5', 'abc'] AS s \
+             RETURN toIntegerOrNull(s) AS i, toFloatOrNull(s) AS f, toBooleanOrNull(s) AS b",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.rows.len(), 3);
+    assert_eq!(result.rows[0].get::<Value>("i").unwrap(), Value::Int(42));
+    assert_eq!(result.rows[1].get::<Value>("i").unwrap(), Value::Int(2));
+    assert_eq!(result.rows[1].get::<Value>("f").unwrap(), Value::Float(2.5));
+    assert_eq!(result.rows[2].get::<Value>("i").unwrap(), Value::Null);
+    assert_eq!(result.rows[2].get::<Value>("f").unwrap(), Value::Null);
+    assert_eq!(result.rows[2].get::<Value>("b").unwrap(), Value::Null);
+
+    // Strict variant still fails on unparseable input
+    let strict = graph
+        .execute("UNWIND ['abc'] AS s RETURN toInteger(s) AS i", PropertyMap::new())
+        .await;
+    assert!(strict.is_err(), "toInteger should stay strict");
+}
+
+#[tokio::test]
+async fn test_integer_or_null_rejects_out_of_range() {
+    let graph = Graph::open_memory().await.unwrap();
+    let params = PropertyMapBuilder::new()
+        .insert_list("xs", [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e300, -7.9])
+        .build();
+
+    let result = graph
+        .execute("UNWIND $xs AS x RETURN toIntegerOrNull(x) AS i", params)
+        .await
+        .unwrap();
+    let got: Vec<Value> = result.rows.iter().map(|r| r.get::<Value>("i").unwrap()).collect();
+    assert_eq!(got, [Value::Null, Value::Null, Value::Null, Value::Null, Value::Int(-7)]);
+
+    let result = graph
+        .execute("UNWIND ['1e300', 'NaN', ' 12 '] AS s RETURN toIntegerOrNull(s) AS i", PropertyMap::new())
+        .await
+        .unwrap();
+    let got: Vec<Value> = result.rows.iter().map(|r| r.get::<Value>("i").unwrap()).collect();
+    assert_eq!(got, [Value::Null, Value::Null, Value::Int(12)]);
+}
+
+#[tokio::test]
+async fn test_to_integer_truncates_and_rejects_out_of_range() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let result = graph
+        .execute("UNWIND ['2.5', '-7.9', ' 12 ', '3'] AS s RETURN toInteger(s) AS i", PropertyMap::new())
+        .await
+        .unwrap();
+    let got: Vec<i64> = result.rows.iter().map(|r| r.get::<i64>("i").unwrap()).collect();
+    assert_eq!(got, [2, -7, 12, 3]);
+    let result = graph.execute("UNWIND [-7.9] AS x RETURN toInteger(x) AS i", PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("i").unwrap(), -7);
+
+    for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e300] {
+        let params = PropertyMapBuilder::new().insert_float("x", bad).build();
+        let err = graph.execute("UNWIND [$x] AS x RETURN toInteger(x) AS i", params).await.unwrap_err();
+        assert!(err.to_string().contains("Integer range"), "{bad}: {err}");
+    }
+    assert!(graph.execute("UNWIND ['1e300'] AS s RETURN toInteger(s) AS i", PropertyMap::new()).await.is_err());
+}
+
+// ============================================================================
+// 20. Multiple labels in MATCH: every label must be present
+// ============================================================================
+
+#[tokio::test]
+async fn test_multiple_labels_intersection() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    graph
+        .mutate("CREATE (n:Person:Admin {name: 'Ada'})", PropertyMap::new())
+        .await
+        .unwrap();
+    graph
+        .mutate("CREATE (n:Person {name: 'Bob'})", PropertyMap::new())
+        .await
+        .unwrap();
+
+    let result = graph
+        .execute("MATCH (n:Person:Admin) RETURN n.name", PropertyMap::new())
+        .await
+        .unwrap();
+
+    assert_eq!(result.rows.len(), 1, "Bob has only Person and must be excluded");
+    assert_eq!(result.rows[0].get::<String>("n.name").unwrap(), "Ada");
+}
+
+// ============================================================================
+// 21. labels() / type() on NULL return NULL
+// ============================================================================
+
+#[tokio::test]
+async fn test_labels_and_type_null_safe() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let result = graph
+        .execute(
+            "UNWIND [null] AS x RETURN labels(x) AS l, type(x) AS t",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<Value>("l").unwrap(), Value::Null);
+    assert_eq!(result.rows[0].get::<Value>("t").unwrap(), Value::Null);
+
+    // Non-null, non-node input is still a type error
+    let bad = graph
+        .execute("UNWIND [1] AS x RETURN labels(x) AS l", PropertyMap::new())
+        .await;
+    assert!(bad.is_err(), "labels() on an integer should still fail");
+}
+
+// ============================================================================
+// 22. Bytes values: parameters, toBytes(), fromBase64(), vector CALL args
+// ============================================================================
+
+#[tokio::test]
+async fn test_bytes_round_trip() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let mut params = PropertyMap::new();
+    params.insert("blob".into(), Value::Bytes(vec![0, 1, 254, 255]));
+    let result = graph
+        .execute(
+            "UNWIND [1] AS x RETURN $blob AS b, fromBase64('AAH+/w==') AS d, toBytes([1.0, 2.0]) AS v",
+            params,
+        )
+        .await
+        .unwrap();
+
+    let row = &result.rows[0];
+    assert_eq!(row.get::<Vec<u8>>("b").unwrap(), vec![0, 1, 254, 255]);
+    assert_eq!(row.get::<Vec<u8>>("d").unwrap(), vec![0, 1, 254, 255]);
+    let expected: Vec<u8> = [1.0f32, 2.0].iter().flat_map(|f| f.to_le_bytes()).collect();
+    assert_eq!(row.get::<Vec<u8>>("v").unwrap(), expected);
+
+    // The vector procedure name parses (`index` is a keyword) and a float list
+    // argument is accepted; planning then fails, as MemoryBackend has no
+    // vector index.
+    let mut params = PropertyMap::new();
+    params.insert("vector".into(), Value::List(vec![Value::Float(0.5), Value::Float(1.5)]));
+    let err = graph
+        .execute(
+            "CALL db.index.vector.queryNodes('embeddings', 3, $vector) YIELD node, score",
+            params,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("does not support vector indexes"), "got: {err}");
+}
+
+// ============================================================================
+// 23. Simple CASE uses `=` semantics: numeric coercion, NULL never matches
+// ============================================================================
+
+#[tokio::test]
+async fn test_simple_case_numeric_and_null_operand() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let result = graph
+        .execute(
+            "UNWIND [1, 2.0, null] AS x \
+             RETURN CASE x WHEN 1.0 THEN 'one' WHEN 2 THEN 'two' WHEN null THEN 'null' ELSE 'other' END AS c, \
+             x = 1.0 AS eq",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    let cases: Vec<String> = result.rows.iter().map(|r| r.get("c").unwrap()).collect();
+    assert_eq!(cases, vec!["one", "two", "other"]);
+    assert_eq!(result.rows[0].get::<Value>("eq").unwrap(), Value::Bool(true));
+    assert_eq!(result.rows[1].get::<Value>("eq").unwrap(), Value::Bool(false));
+    assert_eq!(result.rows[2].get::<Value>("eq").unwrap(), Value::Null);
+
+    // Lists and maps compare element-wise with the same rules
+    let result = graph
+        .execute(
+            "UNWIND [0] AS x RETURN [1] = [1.0] AS coerced, [null] = [null] AS nulls, [null, 1] = [null, 2] AS differs, \
+             {k: 1} <> {k: 1.0} AS map_neq",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let row = &result.rows[0];
+    assert_eq!(row.get::<Value>("coerced").unwrap(), Value::Bool(true));
+    assert_eq!(row.get::<Value>("nulls").unwrap(), Value::Null);
+    assert_eq!(row.get::<Value>("differs").unwrap(), Value::Bool(false));
+    assert_eq!(row.get::<Value>("map_neq").unwrap(), Value::Bool(false));
+}
+
+// ============================================================================
+// 24. rand() / randomUUID(): fresh value per row, reproducible with a seed
+// ============================================================================
+
+#[tokio::test]
+async fn test_rand_and_random_uuid_seeded() {
+    async fn draw(seed: u64) -> Vec<(f64, String)> {
+        let graph = Graph::open_memory().await.unwrap().with_rng_seed(seed);
+        let result = graph
+            .execute("UNWIND range(1, 5) AS i RETURN rand() AS r, randomUUID() AS id", PropertyMap::new())
+            .await
+            .unwrap();
+        result.rows.iter().map(|row| (row.get("r").unwrap(), row.get("id").unwrap())).collect()
+    }
+
+    let first = draw(7).await;
+    assert_eq!(first, draw(7).await);
+    assert_ne!(first, draw(8).await);
+
+    for (r, id) in &first {
+        assert!((0.0..1.0).contains(r), "rand() out of range: {r}");
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4", "not a v4 UUID: {id}");
+    }
+    assert_ne!(first[0].0, first[1].0);
+    assert_ne!(first[0].1, first[1].1);
+
+    // Unseeded graphs draw from the thread RNG
+    let graph = Graph::open_memory().await.unwrap();
+    let result = graph.execute("UNWIND [1, 2] AS i RETURN rand() AS r", PropertyMap::new()).await.unwrap();
+    for row in &result.rows {
+        let r: f64 = row.get("r").unwrap();
+        assert!((0.0..1.0).contains(&r));
+    }
+}
+
+// ============================================================================
+// 25. reverse() and the to*List() coercions over mixed-type lists
+// ============================================================================
+
+#[tokio::test]
+async fn test_list_coercions_mixed_input() {
+    let graph = Graph::open_memory().await.unwrap();
+    let mut params = PropertyMap::new();
+    params.insert(
+        "xs".into(),
+        Value::List(vec![
+            Value::from("42"),
+            Value::Int(7),
+            Value::Float(2.5),
+            Value::from("nope"),
+            Value::Bool(true),
+            Value::Null,
+            Value::List(vec![]),
+        ]),
+    );
+
+    let result = graph
+        .execute(
+            "UNWIND [1] AS i RETURN toIntegerList($xs) AS ints, toFloatList($xs) AS floats, \
+             toStringList($xs) AS strs, toBooleanList($xs) AS bools, reverse($xs) AS rev, \
+             reverse('abc') AS s, toIntegerList(null) AS none",
+            params,
+        )
+        .await
+        .unwrap();
+    let row = &result.rows[0];
+
+    let n = Value::Null;
+    assert_eq!(
+        row.get::<Value>("ints").unwrap(),
+        Value::List(vec![Value::Int(42), Value::Int(7), Value::Int(2), n.clone(), Value::Int(1), n.clone(), n.clone()])
+    );
+    assert_eq!(
+        row.get::<Value>("floats").unwrap(),
+        Value::List(vec![Value::Float(42.0), Value::Float(7.0), Value::Float(2.5), n.clone(), n.clone(), n.clone(), n.clone()])
+    );
+    let strs = row.get::<Value>("strs").unwrap();
+    let Value::List(strs) = strs else { panic!("expected list, got {strs:?}") };
+    assert_eq!(strs[0], Value::from("42"));
+    assert_eq!(strs[1], Value::from("7"));
+    assert_eq!(strs[3], Value::from("nope"));
+    assert_eq!(&strs[5..], &[n.clone(), n.clone()]);
+    assert_eq!(
+        row.get::<Value>("bools").unwrap(),
+        Value::List(vec![n.clone(), Value::Bool(true), n.clone(), n.clone(), Value::Bool(true), n.clone(), n.clone()])
+    );
+
+    let Value::List(rev) = row.get::<Value>("rev").unwrap() else { panic!("expected list") };
+    assert_eq!(rev.first(), Some(&Value::List(vec![])));
+    assert_eq!(rev.last(), Some(&Value::from("42")));
+    assert_eq!(row.get::<String>("s").unwrap(), "cba");
+    assert_eq!(row.get::<Value>("none").unwrap(), Value::Null);
+}
+
+// ============================================================================
+// 26. NaN and infinities: toString, comparison and ORDER BY
+// ============================================================================
+
+#[tokio::test]
+async fn test_nan_and_infinity() {
+    let graph = Graph::open_memory().await.unwrap();
+    let mut params = PropertyMap::new();
+    params.insert("nan".into(), Value::Float(f64::NAN));
+    params.insert("big".into(), Value::Float(1e308));
+    params.insert("xs".into(), Value::List(vec![Value::Float(f64::NAN), Value::Int(3), Value::Float(f64::NEG_INFINITY)]));
+
+    let result = graph
+        .execute(
+            "UNWIND [1] AS i RETURN toString($nan) AS nan, toString($big * 10) AS inf, \
+             toString('a') AS s, $nan = $nan AS eq, $nan > 1 AS gt",
+            params.clone(),
+        )
+        .await
+        .unwrap();
+    let row = &result.rows[0];
+    assert_eq!(row.get::<String>("nan").unwrap(), "NaN");
+    assert_eq!(row.get::<String>("inf").unwrap(), "Infinity");
+    assert_eq!(row.get::<String>("s").unwrap(), "a");
+    assert!(!row.get::<bool>("eq").unwrap());
+    assert!(!row.get::<bool>("gt").unwrap());
+
+    let result = graph
+        .execute("UNWIND $xs AS x RETURN x ORDER BY x", params)
+        .await
+        .unwrap();
+    let xs: Vec<String> = result.rows.iter().map(|r| r.get::<Value>("x").unwrap().to_string()).collect();
+    assert_eq!(xs, ["-Infinity", "3", "NaN"]);
+}
+
+// ============================================================================
+// 27. Integer overflow raises an error instead of wrapping
+// ============================================================================
+
+#[tokio::test]
+async fn test_integer_overflow() {
+    let graph = Graph::open_memory().await.unwrap();
+    let mut params = PropertyMap::new();
+    params.insert("max".into(), Value::Int(i64::MAX));
+    params.insert("min".into(), Value::Int(i64::MIN));
+
+    for query in [
+        "UNWIND [1] AS i RETURN $max + 1 AS x",
+        "UNWIND [1] AS i RETURN -$min AS x",
+        "UNWIND [1] AS i RETURN $min - 1 AS x",
+        "UNWIND [1] AS i RETURN $max * 2 AS x",
+        "UNWIND [1] AS i RETURN $min / -1 AS x",
+    ] {
+        let err = graph.execute(query, params.clone()).await.unwrap_err();
+        assert!(err.to_string().contains("integer overflow"), "{query}: {err}");
+    }
+
+    // Results that fit are unaffected, and floats still go to infinity
+    let result = graph
+        .execute("UNWIND [1] AS i RETURN $max - 1 + 1 AS x, $max * 2.0 > 0 AS f", params)
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("x").unwrap(), i64::MAX);
+    assert!(result.rows[0].get::<bool>("f").unwrap());
+}
+
+// ============================================================================
+// 28. A wide projection that shares its property base returns the same rows
+// ============================================================================
+
+#[tokio::test]
+async fn test_wide_projection_shares_property_base() {
+    let graph = Graph::open_memory().await.unwrap();
+    const WIDTH: usize = 30;
+    let props: Vec<String> = (0..WIDTH).map(|i| format!("p{i}: {i}")).collect();
+    for _ in 0..500 {
+        graph
+            .mutate(&format!("CREATE (:Wide {{{}, name: 'w', address: {{city: 'Paris'}}}})", props.join(", ")), PropertyMap::new())
+            .await
+            .unwrap();
+    }
+    let items = |wrap: fn(String) -> String| -> String {
+        (0..WIDTH).map(|i| format!("{} AS p{i}", wrap(format!("n.p{i}")))).collect::<Vec<_>>().join(", ")
+    };
+    // coalesce() hides the property read, so that query evaluates `n` per item
+    let shared = format!("MATCH (n:Wide) RETURN {}", items(|e| e));
+    let per_item = format!("MATCH (n:Wide) RETURN {}", items(|e| format!("coalesce({e})")));
+
+    // Sharing the base is invisible in the results; its speed-up is
+    // measured by `benches/wide_projection.rs`
+    let rows = |query: String| {
+        let graph = &graph;
+        async move {
+            let result = graph.execute(&query, PropertyMap::new()).await.unwrap();
+            result.rows.into_iter().map(|r| r.values).collect::<Vec<_>>()
+        }
+    };
+    let shared_rows = rows(shared).await;
+    let per_item_rows = rows(per_item).await;
+    assert_eq!(shared_rows.len(), 500);
+    assert_eq!(shared_rows, per_item_rows);
+    assert_eq!(shared_rows[0][7], ("p7".to_string(), Value::Int(7)));
+
+    // Nested paths and map parameters share bases too, with the same results
+    let mut params = PropertyMap::new();
+    params.insert("m".into(), Value::Map(std::collections::HashMap::from([("x".to_string(), Value::Int(1))])));
+    let result = graph
+        .execute(
+            "MATCH (n:Wide) RETURN n.address.city AS city, n.address.zip AS zip, n.name AS name, $m.x AS x, $m.y AS y LIMIT 1",
+            params,
+        )
+        .await
+        .unwrap();
+    let row = &result.rows[0];
+    assert_eq!(row.get::<String>("city").unwrap(), "Paris");
+    assert_eq!(row.get::<Value>("zip").unwrap(), Value::Null);
+    assert_eq!(row.get::<String>("name").unwrap(), "w");
+    assert_eq!(row.get::<i64>("x").unwrap(), 1);
+    assert_eq!(row.get::<Value>("y").unwrap(), Value::Null);
+}
+
+// ============================================================================
+// 29. Row limits abort oversized queries
+// ============================================================================
+
+#[tokio::test]
+async fn test_row_limits_abort_oversized_queries() {
+    let graph = Graph::open_memory()
+        .await
+        .unwrap()
+        .with_max_intermediate_rows(50)
+        .with_max_result_rows(20);
+    for i in 0..10 {
+        graph.mutate(&format!("CREATE (:Left {{i: {i}}}), (:Right {{i: {i}}})"), PropertyMap::new()).await.unwrap();
+    }
+    graph.mutate("CREATE (:Few {i: 0}), (:Few {i: 1}), (:Few {i: 2})", PropertyMap::new()).await.unwrap();
+
+    // 10 x 10 rows breaks the intermediate cap before the product is built
+    let err = graph
+        .execute("MATCH (a:Left), (b:Right) RETURN a.i, b.i", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("result set too large"), "{err}");
+
+    // UNWIND stops at the 51st row, though the query returns one
+    let err = graph
+        .execute("UNWIND range(1, 100) AS x RETURN count(x) AS n", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("result set too large"), "{err}");
+
+    // 10 x 3 is under the intermediate cap, but over the result cap
+    let err = graph
+        .execute("MATCH (a:Left), (b:Few) RETURN a.i, b.i", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("result set too large"), "{err}");
+
+    // Queries within both caps run as usual
+    let result = graph
+        .execute("MATCH (a:Few), (b:Few) RETURN a.i, b.i", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 9);
+    let result = graph
+        .execute("MATCH (a:Left), (b:Few) RETURN a.i, b.i LIMIT 20", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 20);
+
+    // Without limits the product runs
+    let unlimited = Graph::open_memory().await.unwrap();
+    for i in 0..10 {
+        unlimited.mutate(&format!("CREATE (:Left {{i: {i}}}), (:Right {{i: {i}}})"), PropertyMap::new()).await.unwrap();
+    }
+    let result = unlimited
+        .execute("MATCH (a:Left), (b:Right) RETURN a.i, b.i", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 100);
+}
+
+// ============================================================================
+// 30. Lists compare lexicographically
+// ============================================================================
+
+#[tokio::test]
+async fn test_list_comparison() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let result = graph
+        .execute(
+            "UNWIND [1] AS i RETURN [1, 2] < [1, 3] AS a, [1] < [1, 2] AS b, [[1], [3]] > [[1], [2]] AS c, \
+             [1, 2] >= [1.0, 2] AS d, [null, 1] < [null, 2] AS e, 1 < 'a' AS f",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let row = &result.rows[0];
+    assert!(row.get::<bool>("a").unwrap());
+    assert!(row.get::<bool>("b").unwrap());
+    assert!(row.get::<bool>("c").unwrap());
+    assert!(row.get::<bool>("d").unwrap());
+    // No order between the elements or the operands: null, as with `=`
+    assert_eq!(row.get::<Value>("e").unwrap(), Value::Null);
+    assert_eq!(row.get::<Value>("f").unwrap(), Value::Null);
+
+    // In WHERE, and sorted the same way
+    let result = graph
+        .execute(
+            "UNWIND [[1, 3], [1], [1, 2], [0, 9]] AS l WITH l WHERE l < [1, 3] RETURN l ORDER BY l",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let lists: Vec<Value> = result.rows.iter().map(|r| r.get::<Value>("l").unwrap()).collect();
+    let expected: Vec<Value> = [vec![0, 9], vec![1], vec![1, 2]]
+        .into_iter()
+        .map(|l| Value::List(l.into_iter().map(Value::Int).collect()))
+        .collect();
+    assert_eq!(lists, expected);
+}
+
+// ============================================================================
+// 31. Long operator chains parse and evaluate
+// ============================================================================
+
+#[tokio::test]
+async fn test_long_operator_chains() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph.mutate("CREATE (:Item {x: 7})", PropertyMap::new()).await.unwrap();
+
+    let terms: Vec<String> = (0..10_000).map(|i| format!("n.x = {i}")).collect();
+    let result = graph
+        .execute(&format!("MATCH (n:Item) WHERE {} RETURN n.x AS x", terms.join(" OR ")), PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    let result = graph
+        .execute(&format!("MATCH (n:Item) WHERE {} RETURN n.x AS x", terms.join(" AND ")), PropertyMap::new())
+        .await
+        .unwrap();
+    assert!(result.rows.is_empty());
+
+    let result = graph
+        .execute(&format!("MATCH (n:Item) RETURN {} AS sum", vec!["n.x"; 1_000].join(" + ")), PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("sum").unwrap(), 7_000);
+}
@@ -220,3 +220,46 @@ async fn test_export_relationship_endpoints() {
         assert!(line.contains("]->"), "Relationship line should have directed edge: {}", line);
     }
 }
+
+#[tokio::test]
+async fn test_json_export_import_roundtrip() {
+    let graph = Graph::open_memory().await.unwrap();
+    seed_graph(&graph).await;
+    let exported = graph.export_json().await.unwrap();
+    assert_eq!(exported["nodes"].as_array().unwrap().len(), 4);
+    assert_eq!(exported["relationships"].as_array().unwrap().len(), 3);
+    assert_eq!(exported["nodes"][0]["properties"]["name"], serde_json::json!({"type": "String", "value": "Alice"}));
+
+    // An existing node shifts the ids the import hands out
+    let copy = Graph::open_memory().await.unwrap();
+    copy.mutate("CREATE (:Existing)", PropertyMap::new()).await.unwrap();
+    let ids = copy.import_json(&exported).await.unwrap();
+    assert_eq!(ids.len(), 4);
+    assert!(ids.iter().all(|(old, new)| old != new));
+    assert!(copy.schema().has_relationship_type("WORKS_AT"));
+
+    let result = copy
+        .execute(
+            "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name AS a, b.name AS b ORDER BY a",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let pairs: Vec<(String, String)> =
+        result.rows.iter().map(|r| (r.get("a").unwrap(), r.get("b").unwrap())).collect();
+    assert_eq!(pairs, [("Alice".into(), "Bob".into()), ("Bob".into(), "Charlie".into())]);
+
+    let result = copy
+        .execute("MATCH (p:Person)-[:WORKS_AT]->(c:Company) RETURN p.age AS age, c.employees AS n", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("age").unwrap(), 30);
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 100);
+
+    // A relationship to a node the document doesn't contain is rejected
+    let mut broken = exported.clone();
+    broken["relationships"][0]["end"] = serde_json::json!(999);
+    let target = Graph::open_memory().await.unwrap();
+    assert!(target.import_json(&broken).await.is_err());
+    assert_eq!(target.execute("MATCH (n) RETURN n", PropertyMap::new()).await.unwrap().rows.len(), 0);
+}
@@ -239,3 +239,352 @@ async fn test_ladybug_where_filter() {
     assert_eq!(names[0], "Middle");
     assert_eq!(names[1], "Senior");
 }
+
+#[tokio::test]
+async fn test_ladybug_vector_query_nodes() {
+    let graph = Graph::open_ladybug();
+
+    for name in ["Alice", "Bob", "Carol", "Dave"] {
+        let mut params = PropertyMap::new();
+        params.insert("name".into(), Value::from(name));
+        graph.mutate("CREATE (n:Person {name: $name})", params).await.unwrap();
+    }
+
+    // Query with Alice's own fingerprint: she must come back first with score 1.0
+    let alice: neo4j_rs::Node = graph.execute(
+        "MATCH (n:Person) WHERE n.name = 'Alice' RETURN n",
+        PropertyMap::new(),
+    ).await.unwrap().rows[0].get("n").unwrap();
+    let vector: Vec<u8> = {
+        let bs = graph.backend().bind_space().read();
+        let addr = ladybug::storage::bind_space::Addr(alice.id.0 as u16);
+        bs.read(addr).unwrap().fingerprint.iter().flat_map(|w| w.to_le_bytes()).collect()
+    };
+
+    let mut params = PropertyMap::new();
+    params.insert("vector".into(), Value::Bytes(vector));
+    let result = graph.execute(
+        "CALL db.index.vector.queryNodes('person_fp', 3, $vector) YIELD node, score",
+        params,
+    ).await.unwrap();
+
+    assert_eq!(result.rows.len(), 3);
+    let scores: Vec<f64> = result.rows.iter().map(|r| r.get("score").unwrap()).collect();
+    assert!(scores.windows(2).all(|w| w[0] >= w[1]), "scores not descending: {scores:?}");
+    assert_eq!(scores[0], 1.0);
+    let top: neo4j_rs::Node = result.rows[0].get("node").unwrap();
+    assert_eq!(top.id, alice.id);
+}
+
+#[tokio::test]
+async fn test_ladybug_fingerprint_export_import_roundtrip() {
+    let graph = Graph::open_ladybug();
+    for name in ["Alice", "Bob", "Carol"] {
+        let mut params = PropertyMap::new();
+        params.insert("name".into(), Value::from(name));
+        graph.mutate("CREATE (n:Person {name: $name})", params).await.unwrap();
+    }
+    let backend = graph.backend();
+
+    let snapshot = backend.export_fingerprints();
+    assert_eq!(snapshot.len(), 3);
+    assert!(snapshot.windows(2).all(|w| w[0].0 < w[1].0));
+    let (alice, alice_fp) = snapshot[0];
+    assert_eq!(backend.fingerprint_of(alice), Some(alice_fp));
+
+    // Clobber every fingerprint, then restore from the snapshot
+    let blank = [0u64; ladybug::storage::bind_space::FINGERPRINT_WORDS];
+    let zeroed: Vec<_> = snapshot.iter().map(|(id, _)| (*id, blank)).collect();
+    backend.import_fingerprints(&zeroed).unwrap();
+    assert_eq!(backend.fingerprint_of(alice), Some(blank));
+    backend.import_fingerprints(&snapshot).unwrap();
+    assert_eq!(backend.export_fingerprints(), snapshot);
+
+    // Unknown ids are rejected without a partial write
+    let bad = vec![(alice, blank), (neo4j_rs::NodeId(4000), blank)];
+    assert!(backend.import_fingerprints(&bad).is_err());
+    assert_eq!(backend.fingerprint_of(alice), Some(alice_fp));
+
+    // Ids and fingerprints still line up for queries after the import
+    let result = graph
+        .execute("MATCH (n:Person) WHERE n.name = 'Alice' RETURN n", PropertyMap::new())
+        .await
+        .unwrap();
+    let node: neo4j_rs::Node = result.rows[0].get("n").unwrap();
+    assert_eq!(node.id, alice);
+}
+
+#[tokio::test]
+async fn test_ladybug_fingerprinter_key_subset_and_refingerprint() {
+    use neo4j_rs::storage::{LadybugBackend, PropertyFingerprinter};
+
+    let graph = Graph::open_ladybug();
+    for id in [17, 42] {
+        let mut params = PropertyMap::new();
+        params.insert("id".into(), Value::from(id));
+        graph.mutate("CREATE (n:Person {name: 'Alice', id: $id})", params).await.unwrap();
+    }
+    let (a, b) = (neo4j_rs::NodeId(1), neo4j_rs::NodeId(2));
+    // The default fingerprints every property, so differing ids differ
+    assert_ne!(graph.backend().fingerprint_of(a), graph.backend().fingerprint_of(b));
+
+    // Same BindSpace, fingerprinting only `name`: existing nodes are stale
+    // until refingerprinted
+    let by_name = LadybugBackend::new(graph.backend().bind_space().clone())
+        .with_fingerprinter(PropertyFingerprinter::cam().with_keys(["name"]));
+    assert_ne!(by_name.fingerprint_of(a), by_name.fingerprint_of(b));
+    assert_eq!(by_name.refingerprint_all(), 2);
+    assert_eq!(by_name.fingerprint_of(a), by_name.fingerprint_of(b));
+}
+
+#[tokio::test]
+async fn test_ladybug_qualia_similar() {
+    use neo4j_rs::model::{Nib4Codebook, QUALIA_JSON_KEYS};
+    use neo4j_rs::storage::{LadybugBackend, PropertyFingerprinter};
+
+    // (uniform qualia value, intensity): 0.1 quantizes two levels above 0.0
+    let corpus = [(0.0f32, false), (0.1, false), (1.0, false), (0.0, true)];
+    let vectors: Vec<Vec<f32>> = corpus.iter().map(|(v, _)| vec![*v; QUALIA_JSON_KEYS.len()]).collect();
+    let codebook = Nib4Codebook::from_corpus(&vectors.iter().map(|v| v.as_slice()).collect::<Vec<_>>());
+    let backend = LadybugBackend::open().with_fingerprinter(PropertyFingerprinter::nib4_qualia(codebook));
+    let graph = Graph::with_backend(backend);
+
+    for (value, intensity) in corpus {
+        let props: Vec<String> = QUALIA_JSON_KEYS.iter().map(|k| format!("{k}: {value:?}")).collect();
+        let query = format!("CREATE (n:Track {{{}, intensity: {intensity}}})", props.join(", "));
+        graph.mutate(&query, PropertyMap::new()).await.unwrap();
+    }
+    // No qualia: never scored
+    graph.mutate("CREATE (n:Person {name: 'Alice'})", PropertyMap::new()).await.unwrap();
+
+    let result = graph
+        .execute("CALL ladybug.qualia_similar(1, 3) YIELD node, distance", PropertyMap::new())
+        .await
+        .unwrap();
+    let hits: Vec<(u64, i64)> = result
+        .rows
+        .iter()
+        .map(|r| (r.get::<neo4j_rs::Node>("node").unwrap().id.0, r.get("distance").unwrap()))
+        .collect();
+    // Intensity mismatch costs one dimension (16); 2 levels × 16 dims = 32
+    assert_eq!(hits, vec![(4, 16), (2, 32), (3, 240)]);
+
+    let err = graph.execute("CALL ladybug.qualia_similar(5, 3) YIELD node", PropertyMap::new()).await;
+    assert!(err.is_err(), "a node without qualia can't seed the search");
+}
+
+#[tokio::test]
+async fn test_ladybug_gestalt() {
+    use neo4j_rs::StorageBackend;
+
+    let graph = Graph::open_ladybug();
+    for name in ["Alice", "Bob", "Carol"] {
+        graph.mutate(&format!("CREATE (n:Person {{name: '{name}'}})"), PropertyMap::new()).await.unwrap();
+    }
+    let (alice, bob) = (neo4j_rs::NodeId(1), neo4j_rs::NodeId(2));
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::TxMode::ReadWrite).await.unwrap();
+        // Two identical KNOWS edges agree perfectly with each other
+        backend.create_relationship(&mut tx, alice, bob, "KNOWS", PropertyMap::new()).await.unwrap();
+        backend.create_relationship(&mut tx, alice, bob, "KNOWS", PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+
+    let backend = graph.backend();
+    let tx = backend.begin_tx(neo4j_rs::TxMode::ReadOnly).await.unwrap();
+    let g = backend.perspective_gestalt(&tx, alice).await.unwrap();
+    assert_eq!(g.total_edges, 2);
+    assert_eq!(g.crystallized_count, 2);
+    assert_eq!(g.dominant_state, neo4j_rs::model::AwarenessState::Crystallized);
+
+    let result = graph
+        .execute(
+            "CALL ladybug.gestalt(1) YIELD dominant_state, crystallized, tensioned, uncertain, most_tensioned",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<String>("dominant_state").unwrap(), "Crystallized");
+    assert_eq!(result.rows[0].get::<i64>("crystallized").unwrap(), 2);
+    assert_eq!(result.rows[0].get::<i64>("tensioned").unwrap(), 0);
+
+    // Carol has no edges: nothing to compare, so no perspective yet
+    let g = backend.perspective_gestalt(&tx, neo4j_rs::NodeId(3)).await.unwrap();
+    assert_eq!((g.total_edges, g.dominant_state), (0, neo4j_rs::model::AwarenessState::Uncertain));
+    backend.commit_tx(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ladybug_causal_path() {
+    use neo4j_rs::model::Path;
+
+    let graph = Graph::open_ladybug();
+    for name in ["Alice", "Bob", "Carol", "Dave"] {
+        graph.mutate(&format!("CREATE (n:Person {{name: '{name}'}})"), PropertyMap::new()).await.unwrap();
+    }
+    for (src, dst) in [("Alice", "Bob"), ("Bob", "Carol"), ("Carol", "Alice")] {
+        graph
+            .mutate(
+                &format!("MATCH (a:Person), (b:Person) WHERE a.name = '{src}' AND b.name = '{dst}' CREATE (a)-[:KNOWS]->(b)"),
+                PropertyMap::new(),
+            )
+            .await
+            .unwrap();
+    }
+    let id_of = |name: &'static str| {
+        let graph = &graph;
+        async move {
+            let mut params = PropertyMap::new();
+            params.insert("name".into(), Value::from(name));
+            let r = graph.execute("MATCH (n:Person) WHERE n.name = $name RETURN id(n) AS id", params).await.unwrap();
+            r.rows[0].get::<i64>("id").unwrap()
+        }
+    };
+    let (alice, carol, dave) = (id_of("Alice").await, id_of("Carol").await, id_of("Dave").await);
+    let call = |src: i64, dst: i64| {
+        let graph = &graph;
+        async move {
+            let mut params = PropertyMap::new();
+            params.insert("src".into(), Value::Int(src));
+            params.insert("dst".into(), Value::Int(dst));
+            graph
+                .execute(
+                    "CALL ladybug.causal_path($src, $dst) YIELD path, composed_sign, holonomy, causal_inversion",
+                    params,
+                )
+                .await
+                .unwrap()
+        }
+    };
+    let names = |path: &Path| -> Vec<Value> { path.nodes.iter().map(|n| n.get("name").cloned().unwrap()).collect() };
+
+    // A linear path has no holonomy, so no inversion
+    let result = call(alice, carol).await;
+    assert_eq!(result.rows.len(), 1);
+    let row = &result.rows[0];
+    let path: Path = row.get("path").unwrap();
+    assert_eq!(names(&path), ["Alice", "Bob", "Carol"].map(Value::from));
+    assert!(path.relationships.iter().all(|r| r.rel_type == "KNOWS"));
+    assert_eq!(row.get::<Value>("holonomy").unwrap(), Value::Null);
+    assert!(!row.get::<bool>("causal_inversion").unwrap());
+
+    // The shortest cycle through Alice closes back on itself; its holonomy
+    // is the composed sign, and below 0.5 means an inversion
+    let result = call(alice, alice).await;
+    assert_eq!(result.rows.len(), 1);
+    let row = &result.rows[0];
+    let path: Path = row.get("path").unwrap();
+    assert_eq!(names(&path), ["Alice", "Bob", "Carol", "Alice"].map(Value::from));
+    let holonomy: f64 = row.get("holonomy").unwrap();
+    assert_eq!(holonomy, row.get::<f64>("composed_sign").unwrap());
+    assert_eq!(row.get::<bool>("causal_inversion").unwrap(), holonomy < 0.5);
+
+    // Unreachable: no rows
+    assert!(call(alice, dave).await.rows.is_empty());
+}
+
+#[tokio::test]
+async fn test_ladybug_resonance_mask() {
+    let graph = Graph::open_ladybug();
+    for name in ["Alice", "Bob", "Carol"] {
+        graph.mutate(&format!("CREATE (n:Person {{name: '{name}'}})"), PropertyMap::new()).await.unwrap();
+    }
+    let [alice, bob, carol] = [1, 2, 3].map(neo4j_rs::NodeId);
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::TxMode::ReadWrite).await.unwrap();
+        for (dst, rel_type) in [(bob, "KNOWS"), (bob, "KNOWS"), (carol, "LIKES")] {
+            backend.create_relationship(&mut tx, alice, dst, rel_type, PropertyMap::new()).await.unwrap();
+        }
+        backend.commit_tx(tx).await.unwrap();
+    }
+
+    // Relative to Alice's first KNOWS edge, its duplicate resonates most
+    let result = graph
+        .execute("CALL ladybug.resonance(1, 0) YIELD rel, agreement, awareness_state", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 2);
+    assert_eq!(result.rows[0].get::<String>("awareness_state").unwrap(), "Crystallized");
+    let full = result.rows[0].get::<f64>("agreement").unwrap();
+    assert!(full >= result.rows[1].get::<f64>("agreement").unwrap());
+
+    // Causal focus keeps only the three sign cells of nine
+    let result = graph
+        .execute("CALL ladybug.resonance(1, 0, 'causal_only') YIELD rel, agreement", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 2);
+    assert!(result.rows[0].get::<f64>("agreement").unwrap() <= 3.0 / 9.0 + 1e-6);
+
+    let err = graph
+        .execute("CALL ladybug.resonance(1, 0, 'sideways') YIELD rel", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown mask selector"));
+}
+
+#[tokio::test]
+async fn test_ladybug_vector_query_ties_break_by_node_id() {
+    use neo4j_rs::StorageBackend;
+
+    let graph = Graph::open_ladybug();
+    for i in 0..5 {
+        graph.mutate(&format!("CREATE (n:Doc {{i: {i}}})"), PropertyMap::new()).await.unwrap();
+    }
+    let backend = graph.backend();
+    // Every node gets the same fingerprint, so every score ties
+    let fp = [0xA5A5_A5A5_A5A5_A5A5u64; ladybug::storage::bind_space::FINGERPRINT_WORDS];
+    let same: Vec<_> = backend.export_fingerprints().iter().map(|(id, _)| (*id, fp)).collect();
+    backend.import_fingerprints(&same).unwrap();
+    let query: Vec<u8> = fp.iter().flat_map(|w| w.to_le_bytes()).collect();
+
+    let tx = backend.begin_tx(neo4j_rs::TxMode::ReadOnly).await.unwrap();
+    let hits = backend.vector_query(&tx, "docs", 3, &query).await.unwrap();
+    let ids: Vec<_> = hits.iter().map(|(id, _)| *id).collect();
+    let expected: Vec<_> = same.iter().take(3).map(|(id, _)| *id).collect();
+    assert_eq!(ids, expected);
+    assert!(hits.iter().all(|(_, score)| *score == 1.0));
+    for _ in 0..3 {
+        assert_eq!(backend.vector_query(&tx, "docs", 3, &query).await.unwrap(), hits);
+    }
+    backend.commit_tx(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ladybug_undirected_self_loop() {
+    use neo4j_rs::StorageBackend;
+
+    let graph = Graph::open_ladybug();
+    for name in ["Alice", "Bob"] {
+        graph.mutate(&format!("CREATE (n:Person {{name: '{name}'}})"), PropertyMap::new()).await.unwrap();
+    }
+    let [alice, bob] = [1, 2].map(neo4j_rs::NodeId);
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::TxMode::ReadWrite).await.unwrap();
+        backend.create_relationship(&mut tx, alice, alice, "LOVES", PropertyMap::new()).await.unwrap();
+        backend.create_relationship(&mut tx, bob, alice, "KNOWS", PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+
+    // The self-loop is both outgoing and incoming, but matches once
+    let result = graph
+        .execute("MATCH (a)-[:LOVES]-(b) RETURN b.name", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+
+    // Undirected expansion reaches incoming neighbours too
+    let backend = graph.backend();
+    let tx = backend.begin_tx(neo4j_rs::TxMode::ReadOnly).await.unwrap();
+    let paths = backend
+        .expand(&tx, alice, neo4j_rs::Direction::Both, &[], neo4j_rs::ExpandDepth::Exact(1))
+        .await
+        .unwrap();
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].end().id, bob);
+    backend.commit_tx(tx).await.unwrap();
+}
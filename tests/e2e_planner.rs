@@ -0,0 +1,850 @@
+//! End-to-end tests for the optimizer.
+//!
+//! These inspect the optimized `LogicalPlan` for a query and check that the
+//! rewritten plan still returns the same rows through `Graph::execute()`.
+
+use neo4j_rs::planner::{self, LogicalPlan, Schema, Statistics};
+use neo4j_rs::{cypher, Graph, PropertyMap, StorageBackend};
+
+// ============================================================================
+// 1. Join ordering: the smaller label scan drives the cartesian product
+// ============================================================================
+
+#[tokio::test]
+async fn test_join_order_puts_tiny_label_first() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    graph.mutate("CREATE (n:Tiny {name: 't', idx: 7})", PropertyMap::new()).await.unwrap();
+    for i in 0..50 {
+        graph
+            .mutate(&format!("CREATE (n:Huge {{idx: {}}})", i), PropertyMap::new())
+            .await
+            .unwrap();
+    }
+
+    let query = "MATCH (h:Huge), (t:Tiny) RETURN h.idx, t.name";
+    let logical = planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap();
+
+    let backend = graph.backend();
+    let tx = backend.begin_tx(neo4j_rs::TxMode::ReadOnly).await.unwrap();
+    let mut stats = Statistics { node_count: Some(backend.node_count(&tx).await.unwrap()), ..Default::default() };
+    for label in planner::join_labels(&logical) {
+        let count = backend.label_count(&tx, &label).await.unwrap();
+        stats.label_counts.insert(label, count);
+    }
+    backend.commit_tx(tx).await.unwrap();
+    assert_eq!(stats.label_counts.get("Tiny"), Some(&1));
+    assert_eq!(stats.label_counts.get("Huge"), Some(&50));
+
+    fn product(plan: &LogicalPlan) -> Option<(&LogicalPlan, &LogicalPlan)> {
+        match plan {
+            LogicalPlan::CartesianProduct { left, right } => Some((left, right)),
+            LogicalPlan::Project { input, .. } => product(input),
+            _ => None,
+        }
+    }
+
+    // Without statistics, pattern order is kept
+    let unordered = planner::optimize(logical.clone()).unwrap();
+    let (left, _) = product(&unordered).expect("cartesian product");
+    assert!(matches!(left, LogicalPlan::NodeScan { label, .. } if label == "Huge"));
+
+    let ordered = planner::optimize_with_stats(logical, &stats).unwrap();
+    let (left, right) = product(&ordered).expect("cartesian product");
+    assert!(matches!(left, LogicalPlan::NodeScan { label, .. } if label == "Tiny"), "got {left:?}");
+    assert!(matches!(right, LogicalPlan::NodeScan { label, .. } if label == "Huge"), "got {right:?}");
+
+    // Reordering doesn't change the result
+    let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows.len(), 50);
+    assert!(result.rows.iter().all(|r| r.get::<String>("t.name").unwrap() == "t"));
+
+    // A predicate on one input that reads another's variable moves above
+    // the product, so either input may run first
+    let query = "MATCH (h:Huge), (t:Tiny {idx: h.idx}) RETURN h.idx AS idx";
+    let logical = planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap();
+    let ordered = planner::optimize_with_stats(logical, &stats).unwrap();
+    let LogicalPlan::Project { input, .. } = &ordered else { panic!("got {ordered:?}") };
+    let LogicalPlan::HashJoin { left, right, .. } = input.as_ref() else { panic!("got {input:?}") };
+    assert!(matches!(left.as_ref(), LogicalPlan::NodeScan { label, .. } if label == "Tiny"), "got {left:?}");
+    assert!(matches!(right.as_ref(), LogicalPlan::NodeScan { label, .. } if label == "Huge"), "got {right:?}");
+    let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<i64>("idx").unwrap(), 7);
+}
+
+// ============================================================================
+// 2. Hash join: same rows as cartesian product + equality filter
+// ============================================================================
+
+#[tokio::test]
+async fn test_hash_join_matches_cartesian_filter() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    // Deterministic pseudo-random keys, with NULLs and int/float mixes
+    let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut next = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    for i in 0..40 {
+        let key = match next() % 10 {
+            0 => "null".to_string(),
+            1 => format!("{}.0", next() % 8),
+            _ => (next() % 8).to_string(),
+        };
+        graph.mutate(&format!("CREATE (n:A {{idx: {i}, k: {key}}})"), PropertyMap::new()).await.unwrap();
+    }
+    for i in 0..25 {
+        let key = next() % 8;
+        graph.mutate(&format!("CREATE (n:B {{idx: {i}, k: {key}}})"), PropertyMap::new()).await.unwrap();
+    }
+
+    let query = "MATCH (a:A), (b:B) WHERE a.k = b.k AND a.idx > 3 RETURN a.idx, b.idx";
+    let logical = planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap();
+
+    fn contains_hash_join(plan: &LogicalPlan) -> bool {
+        match plan {
+            LogicalPlan::HashJoin { .. } => true,
+            LogicalPlan::Project { input, .. } | LogicalPlan::Filter { input, .. } => contains_hash_join(input),
+            _ => false,
+        }
+    }
+    let optimized = planner::optimize(logical.clone()).unwrap();
+    assert!(contains_hash_join(&optimized), "expected a hash join: {optimized:?}");
+    assert!(!contains_hash_join(&logical));
+
+    let run = |plan: LogicalPlan| async {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::TxMode::ReadOnly).await.unwrap();
+        let result = neo4j_rs::execution::execute(backend, &mut tx, plan, PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+        let mut pairs: Vec<(i64, i64)> = result
+            .rows
+            .iter()
+            .map(|r| (r.get("a.idx").unwrap(), r.get("b.idx").unwrap()))
+            .collect();
+        pairs.sort();
+        pairs
+    };
+
+    let expected = run(logical).await;
+    let actual = run(optimized).await;
+    assert!(!expected.is_empty());
+    assert_eq!(actual, expected);
+}
+
+// ============================================================================
+// 3. typecheck / execute_checked: reject obviously ill-typed queries
+// ============================================================================
+
+#[tokio::test]
+async fn test_execute_checked_rejects_type_errors() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph.mutate("CREATE (n:Person {name: 'Ada', age: 36})", PropertyMap::new()).await.unwrap();
+
+    let mut schema = Schema::default();
+    schema.property_types.insert(("Person".into(), "name".into()), "STRING".into());
+
+    let rejected = [
+        "MATCH (n:Person) WHERE [1, 2] = 3 RETURN n",
+        "MATCH (n:Person) RETURN n.name - 1",
+        "MATCH (n:Person) WHERE n.age > 1 AND 'yes' RETURN n",
+        "MATCH (a:Person)-[r]->(b) REMOVE r:Friend",
+    ];
+    for query in rejected {
+        match graph.execute_checked(query, PropertyMap::new(), &schema).await {
+            Err(neo4j_rs::Error::SemanticError { .. }) => {}
+            other => panic!("expected SemanticError for {query:?}, got {other:?}"),
+        }
+    }
+
+    // Well-typed queries (and anything the checker can't type) still run
+    let ok = graph
+        .execute_checked(
+            "MATCH (n:Person) WHERE n.age - 1 > 30 AND n.name = 'Ada' RETURN n.name + '!' AS s",
+            PropertyMap::new(),
+            &schema,
+        )
+        .await
+        .unwrap();
+    assert_eq!(ok.rows[0].get::<String>("s").unwrap(), "Ada!");
+
+    // Unchecked execution is unaffected: [1, 2] = 3 is simply false
+    let unchecked = graph
+        .execute("MATCH (n:Person) WHERE [1, 2] = 3 RETURN n", PropertyMap::new())
+        .await
+        .unwrap();
+    assert!(unchecked.rows.is_empty());
+}
+
+// ============================================================================
+// 4. Semantic and type errors point back into the query text
+// ============================================================================
+
+#[tokio::test]
+async fn test_error_spans() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph.mutate("CREATE (n:Person {name: 'Ada'})", PropertyMap::new()).await.unwrap();
+
+    let query = "MATCH (n:Person) RETURN m.name";
+    let err = graph.execute(query, PropertyMap::new()).await.unwrap_err();
+    let span = err.span().expect("unbound variable should carry a span");
+    assert_eq!(&query[span.start..span.end], "m");
+    assert_eq!(
+        err.render(query),
+        format!("Semantic error: Unbound variable: m\n  {query}\n  {}^", " ".repeat(span.start))
+    );
+
+    let query = "MATCH (n:Person) RETURN n.name AS name, $missing AS p";
+    let err = graph.execute(query, PropertyMap::new()).await.unwrap_err();
+    let span = err.span().unwrap();
+    assert_eq!(&query[span.start..span.end], "$missing");
+
+    let query = "MATCH (n:Person) RETURN labels('Ada') AS l";
+    let err = graph.execute(query, PropertyMap::new()).await.unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::TypeError { .. }), "got {err:?}");
+    let span = err.span().unwrap();
+    assert_eq!(&query[span.start..span.end], "labels('Ada')");
+
+    let query = "MATCH (n:Person) WHERE n.name - 1 > 0 RETURN n";
+    let mut schema = Schema::default();
+    schema.property_types.insert(("Person".into(), "name".into()), "STRING".into());
+    let err = graph.execute_checked(query, PropertyMap::new(), &schema).await.unwrap_err();
+    let span = err.span().unwrap();
+    assert_eq!(&query[span.start..span.end], "n");
+
+    // Planning errors point at the pattern or hint they reject
+    let query = "MATCH (n:Person), p = (m:Person) RETURN p";
+    let err = graph.execute(query, PropertyMap::new()).await.unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::PlanError { .. }), "got {err:?}");
+    let span = err.span().unwrap();
+    assert_eq!(&query[span.start..span.end], "p = (m:Person)");
+
+    let query = "MATCH (n:Person) USING INDEX n:Person(name) RETURN n";
+    let err = graph.execute(query, PropertyMap::new()).await.unwrap_err();
+    let span = err.span().unwrap();
+    assert_eq!(&query[span.start..span.end], "USING INDEX n:Person(name)");
+}
+
+// ============================================================================
+// 5. Range predicates on a label scan become an index range scan
+// ============================================================================
+
+#[tokio::test]
+async fn test_index_range_scan() {
+    let graph = Graph::open_memory().await.unwrap();
+    for (i, age) in ["20", "25", "30", "30.5", "35", "'forty'", "null"].iter().enumerate() {
+        graph.mutate(&format!("CREATE (n:Person {{idx: {i}, age: {age}}})"), PropertyMap::new()).await.unwrap();
+    }
+    graph.mutate("CREATE INDEX FOR (n:Person) ON (n.age)", PropertyMap::new()).await.unwrap();
+    // Written after the index exists, so it must be maintained on SET
+    graph.mutate("CREATE (n:Person {idx: 7})", PropertyMap::new()).await.unwrap();
+    graph.mutate("MATCH (n:Person) WHERE n.idx = 7 SET n.age = 40", PropertyMap::new()).await.unwrap();
+
+    fn scan_bounds(plan: &LogicalPlan) -> Option<(bool, bool)> {
+        match plan {
+            LogicalPlan::IndexRangeScan { property, lower, upper, .. } if property == "age" => {
+                Some((lower.is_some(), upper.is_some()))
+            }
+            LogicalPlan::Project { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Filter { input, .. } => scan_bounds(input),
+            _ => None,
+        }
+    }
+
+    let cases: [(&str, &[i64], (bool, bool)); 6] = [
+        ("n.age > 30", &[3, 4, 7], (true, false)),
+        ("n.age >= 30", &[2, 3, 4, 7], (true, false)),
+        ("n.age < 30", &[0, 1], (false, true)),
+        ("30 >= n.age", &[0, 1, 2], (false, true)),
+        ("n.age > 20 AND n.age <= 35", &[1, 2, 3, 4], (true, true)),
+        ("n.age >= 25 AND n.idx <> 2 AND n.age < 35", &[1, 3], (true, true)),
+    ];
+    for (predicate, expected, bounds) in cases {
+        let query = format!("MATCH (n:Person) WHERE {predicate} RETURN n.idx ORDER BY n.idx");
+        let logical = planner::plan(&cypher::parse(&query).unwrap(), &PropertyMap::new()).unwrap();
+        let optimized = planner::optimize(logical).unwrap();
+        assert_eq!(scan_bounds(&optimized), Some(bounds), "{predicate}: {optimized:?}");
+
+        let result = graph.execute(&query, PropertyMap::new()).await.unwrap();
+        let idx: Vec<i64> = result.rows.iter().map(|r| r.get("n.idx").unwrap()).collect();
+        assert_eq!(idx, expected, "{predicate}");
+    }
+
+    // Strings range among strings only; parameters bind at execution
+    let mut params = PropertyMap::new();
+    params.insert("min".into(), "a".into());
+    let result = graph
+        .execute("MATCH (n:Person) WHERE n.age > $min RETURN n.idx", params)
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<i64>("n.idx").unwrap(), 5);
+}
+
+// ============================================================================
+// 6. Multi-label patterns scan the intersection of their labels
+// ============================================================================
+
+#[tokio::test]
+async fn test_multi_label_scan() {
+    let graph = Graph::open_memory().await.unwrap();
+    for (i, labels) in [":A", ":B", ":A:B", ":C:B:A", ":B:C", ":C:A", ":B:A"].iter().enumerate() {
+        graph.mutate(&format!("CREATE (n{labels} {{idx: {i}}})"), PropertyMap::new()).await.unwrap();
+    }
+
+    let backend = graph.backend();
+    let tx = backend.begin_tx(neo4j_rs::TxMode::ReadOnly).await.unwrap();
+    let idx = |nodes: Vec<neo4j_rs::Node>| -> Vec<i64> {
+        nodes.iter().map(|n| n.get("idx").and_then(|v| v.as_int()).unwrap()).collect()
+    };
+    let cases: [(&[&str], &[i64]); 5] = [
+        (&["A", "B"], &[2, 3, 6]),
+        (&["B", "A"], &[2, 3, 6]),
+        (&["A", "B", "C"], &[3]),
+        (&["A", "Missing"], &[]),
+        (&[], &[0, 1, 2, 3, 4, 5, 6]),
+    ];
+    for (labels, expected) in cases {
+        let nodes = backend.nodes_by_labels(&tx, labels).await.unwrap();
+        assert!(nodes.windows(2).all(|w| w[0].id < w[1].id), "{labels:?} not in id order");
+        assert_eq!(idx(nodes), expected, "{labels:?}");
+    }
+    backend.commit_tx(tx).await.unwrap();
+
+    let query = "MATCH (n:C:A) RETURN n.idx ORDER BY n.idx";
+    let logical = planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap();
+    fn scan(plan: &LogicalPlan) -> Option<&LogicalPlan> {
+        match plan {
+            LogicalPlan::Project { input, .. } | LogicalPlan::Sort { input, .. } => scan(input),
+            other => Some(other),
+        }
+    }
+    let optimized = planner::optimize(logical).unwrap();
+    assert!(
+        matches!(scan(&optimized), Some(LogicalPlan::MultiLabelScan { labels, .. }) if labels == &["C", "A"]),
+        "got {optimized:?}"
+    );
+
+    let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+    let rows: Vec<i64> = result.rows.iter().map(|r| r.get("n.idx").unwrap()).collect();
+    assert_eq!(rows, [3, 5]);
+}
+
+// ============================================================================
+// 7. Plan hashes ignore layout and parameter values, not structure
+// ============================================================================
+
+#[test]
+fn test_plan_hash() {
+    let hash = |query: &str, params: &PropertyMap| {
+        planner::plan(&cypher::parse(query).unwrap(), params).unwrap().plan_hash()
+    };
+    let none = PropertyMap::new();
+    let base = hash("MATCH (n:Person) WHERE n.age > $min RETURN n.name", &none);
+
+    assert_eq!(base, hash("MATCH   (n:Person)\n  WHERE n.age>$min\nRETURN n.name", &none));
+    assert_eq!(base, hash("match (n:Person) where n.age > $min return n.name", &none));
+    let mut params = PropertyMap::new();
+    params.insert("min".into(), neo4j_rs::Value::Int(30));
+    assert_eq!(base, hash("MATCH (n:Person) WHERE n.age > $min RETURN n.name", &params));
+
+    assert_ne!(base, hash("MATCH (n:Person) WHERE n.age < $min RETURN n.name", &none));
+    assert_ne!(base, hash("MATCH (n:Person) WHERE n.age > $max RETURN n.name", &none));
+    assert_ne!(base, hash("MATCH (n:Robot) WHERE n.age > $min RETURN n.name", &none));
+    assert_ne!(base, hash("MATCH (n:Person) WHERE n.age > 30 RETURN n.name", &none));
+    assert_ne!(
+        hash("MATCH (n:Person) WHERE n.age > 30 RETURN n.name", &none),
+        hash("MATCH (n:Person) WHERE n.age > 31 RETURN n.name", &none),
+    );
+
+    // Map literals hash independently of key order
+    assert_eq!(
+        hash("CREATE (n:Person {name: 'Ada', age: 36})", &none),
+        hash("CREATE (n:Person {age: 36, name: 'Ada'})", &none),
+    );
+}
+
+#[tokio::test]
+async fn test_plan_hash_in_stats() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph.mutate("CREATE (n:Person {name: 'Ada', age: 36})", PropertyMap::new()).await.unwrap();
+    let run = |query: &'static str, min: i64| {
+        let graph = &graph;
+        async move {
+            let mut params = PropertyMap::new();
+            params.insert("min".into(), neo4j_rs::Value::Int(min));
+            graph.execute(query, params).await.unwrap().stats.plan_hash
+        }
+    };
+
+    let base = run("MATCH (n:Person) WHERE n.age > $min RETURN n.name", 30).await;
+    assert_ne!(base, 0);
+    assert_eq!(base, run("MATCH (n:Person)\n WHERE n.age > $min RETURN n.name", 40).await);
+    assert_ne!(base, run("MATCH (n:Person) WHERE n.age < $min RETURN n.name", 30).await);
+}
+
+#[tokio::test]
+async fn test_plan_cache_reuses_plans() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph.mutate("CREATE (:Person {name: 'Ada', age: 36}), (:Person {name: 'Bob', age: 25})", PropertyMap::new()).await.unwrap();
+    async fn run(graph: &Graph<neo4j_rs::storage::MemoryBackend>, min: i64) -> neo4j_rs::Result<neo4j_rs::QueryResult> {
+        let mut params = PropertyMap::new();
+        params.insert("min".into(), neo4j_rs::Value::Int(min));
+        graph.execute("MATCH (n:Person) WHERE n.age > $min RETURN n.name AS name", params).await
+    }
+
+    // The first run optimizes; a repeat with another value reuses the plan
+    let first = run(&graph, 30).await.unwrap();
+    assert!(!first.stats.plan_cached);
+    let second = run(&graph, 20).await.unwrap();
+    assert!(second.stats.plan_cached);
+    assert_eq!(second.stats.plan_hash, first.stats.plan_hash);
+    assert_eq!(first.rows.len(), 1);
+    assert_eq!(second.rows.len(), 2);
+
+    // A different query shape doesn't hit it
+    let other = graph.execute("MATCH (n:Person) RETURN n.name", PropertyMap::new()).await.unwrap();
+    assert!(!other.stats.plan_cached);
+
+    // Nor does anything with the cache turned off
+    let uncached = Graph::open_memory().await.unwrap().with_plan_cache_size(0);
+    run(&uncached, 30).await.unwrap();
+    assert!(!run(&uncached, 30).await.unwrap().stats.plan_cached);
+}
+
+// ============================================================================
+// 8. QueryBuilder plans match the parser's and return the same rows
+// ============================================================================
+
+#[tokio::test]
+async fn test_query_builder_matches_cypher() {
+    use neo4j_rs::query::QueryBuilder;
+    use neo4j_rs::{Direction, Value};
+
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person {name: 'Ada', age: 36})-[:KNOWS]->(b:Person {name: 'Bob', age: 40}), \
+             (a)-[:KNOWS]->(c:Person {name: 'Cy', age: 25}), (b)-[:KNOWS]->(c)",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    let cases = [
+        (
+            QueryBuilder::new().match_node("n", "Person").where_eq("n.name", "Ada").return_vars(&["n.age"]),
+            "MATCH (n:Person) WHERE n.name = $p0 RETURN n.age",
+        ),
+        (
+            QueryBuilder::new()
+                .match_node("a", "Person")
+                .match_rel("a", "KNOWS", "b", Direction::Outgoing)
+                .where_eq("a.name", "Ada")
+                .where_eq("b.age", 25)
+                .return_vars(&["b.name"]),
+            "MATCH (a:Person), (a)-[:KNOWS]->(b) WHERE a.name = $p0 AND b.age = $p1 RETURN b.name",
+        ),
+        (
+            QueryBuilder::new()
+                .match_node("n", "Person")
+                .order_by("n.age", false)
+                .return_vars(&["n.name", "n.age"])
+                .skip(1)
+                .limit(1),
+            "MATCH (n:Person) RETURN n.name, n.age ORDER BY n.age DESC SKIP 1 LIMIT 1",
+        ),
+    ];
+
+    for (builder, cypher_text) in cases {
+        let (built, params) = builder.build().unwrap();
+        let parsed = planner::plan(&cypher::parse(cypher_text).unwrap(), &params).unwrap();
+        assert_eq!(built.plan_hash(), parsed.plan_hash(), "{cypher_text}");
+
+        let from_builder = graph.run_logical(built, params.clone()).await.unwrap();
+        let from_cypher = graph.execute(cypher_text, params).await.unwrap();
+        assert_eq!(from_builder.columns, from_cypher.columns);
+        let values = |r: &neo4j_rs::QueryResult| -> Vec<Vec<Value>> {
+            r.rows.iter().map(|row| row.values.iter().map(|(_, v)| v.clone()).collect()).collect()
+        };
+        assert!(!from_builder.rows.is_empty(), "{cypher_text}");
+        assert_eq!(values(&from_builder), values(&from_cypher), "{cypher_text}");
+    }
+
+    // A value that looks like Cypher stays a plain parameter
+    let (plan, params) = QueryBuilder::new()
+        .match_node("n", "Person")
+        .where_eq("n.name", "x' OR 1=1 //")
+        .return_vars(&["n"])
+        .build()
+        .unwrap();
+    assert!(graph.run_logical(plan, params).await.unwrap().rows.is_empty());
+
+    assert!(QueryBuilder::new().match_node("n", "Person").build().is_err());
+}
+
+// ============================================================================
+// 9. USING INDEX forces an index lookup, and fails without the index
+// ============================================================================
+
+#[tokio::test]
+async fn test_using_index_hint() {
+    let graph = Graph::open_memory().await.unwrap();
+    for name in ["Ada", "Bob", "Cy"] {
+        graph
+            .mutate(&format!("CREATE (n:Person {{name: '{name}', age: 30}})"), PropertyMap::new())
+            .await
+            .unwrap();
+    }
+    let query = "MATCH (n:Person) USING INDEX n:Person(name) WHERE n.name = $x AND n.age > 20 RETURN n.name AS name";
+    let mut params = PropertyMap::new();
+    params.insert("x".into(), neo4j_rs::Value::from("Bob"));
+
+    // Planned as a lookup, with the other conjunct left as a filter
+    let logical = planner::optimize(planner::plan(&cypher::parse(query).unwrap(), &params).unwrap()).unwrap();
+    let LogicalPlan::Project { input, .. } = &logical else { panic!("expected Project, got {logical:?}") };
+    let LogicalPlan::Filter { input, .. } = input.as_ref() else { panic!("expected Filter, got {input:?}") };
+    assert!(
+        matches!(input.as_ref(), LogicalPlan::IndexLookup { label, property, .. } if label == "Person" && property == "name"),
+        "expected IndexLookup, got {input:?}"
+    );
+
+    let err = graph.execute(query, params.clone()).await.unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::PlanError { .. }), "{err:?}");
+
+    graph.mutate("CREATE INDEX FOR (n:Person) ON (n.name)", PropertyMap::new()).await.unwrap();
+    let result = graph.execute(query, params.clone()).await.unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<String>("name").unwrap(), "Bob");
+
+    // A hint the WHERE clause can't serve is a plan error too
+    for unusable in [
+        "MATCH (n:Person) USING INDEX n:Person(name) RETURN n",
+        "MATCH (n:Person) USING INDEX n:Person(name) WHERE n.name > $x RETURN n",
+        "MATCH (n:Person) USING INDEX m:Person(name) WHERE n.name = $x RETURN n",
+    ] {
+        let err = graph.execute(unusable, params.clone()).await.unwrap_err();
+        assert!(matches!(err, neo4j_rs::Error::PlanError { .. }), "{unusable}: {err:?}");
+    }
+
+    // The backend decides: an index created on it directly serves the hint
+    let direct = Graph::open_memory().await.unwrap();
+    direct.mutate("CREATE (n:Person {name: 'Bob', age: 40})", PropertyMap::new()).await.unwrap();
+    assert!(direct.execute(query, params.clone()).await.is_err());
+    direct.backend().create_index("Person", "name", neo4j_rs::index::IndexType::BTree).await.unwrap();
+    assert_eq!(direct.execute(query, params).await.unwrap().rows.len(), 1);
+}
+
+// ============================================================================
+// 10. Notifications flag cartesian products and unbounded var-length patterns
+// ============================================================================
+
+#[tokio::test]
+async fn test_notifications() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate("CREATE (a:Person {name: 'Ada', team: 1})-[:KNOWS]->(b:Person {name: 'Bob', team: 1})", PropertyMap::new())
+        .await
+        .unwrap();
+    graph.mutate("CREATE (t:Team {id: 1})", PropertyMap::new()).await.unwrap();
+    let codes = |query: &'static str| {
+        let graph = &graph;
+        async move {
+            let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+            result.notifications.into_iter().map(|n| n.code).collect::<Vec<_>>()
+        }
+    };
+
+    let result = graph.execute("MATCH (p:Person), (t:Team) RETURN p.name, t.id", PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows.len(), 2);
+    assert_eq!(result.notifications.len(), 1);
+    let notification = &result.notifications[0];
+    assert_eq!(notification.code, "Neo.ClientNotification.Statement.CartesianProduct");
+    assert!(notification.title.contains("cartesian product"));
+
+    // Joined or connected patterns are not cartesian products
+    assert!(codes("MATCH (p:Person), (t:Team) WHERE p.team = t.id RETURN p.name").await.is_empty());
+    assert!(codes("MATCH (a:Person)-[:KNOWS]->(b) RETURN b.name").await.is_empty());
+
+    assert_eq!(
+        codes("MATCH (a:Person)-[r:KNOWS*]->(b) RETURN b.name").await,
+        ["Neo.ClientNotification.Statement.UnboundedVariableLengthPattern"],
+    );
+    assert!(codes("MATCH (a:Person)-[:KNOWS*1..3]->(b) RETURN b.name").await.is_empty());
+}
+
+// ============================================================================
+// 11. Anonymous variables are numbered per query
+// ============================================================================
+
+#[tokio::test]
+async fn test_anonymous_names_are_per_query() {
+    let query = "MATCH (a:Person)-[:KNOWS]->()-[:KNOWS]->(c) RETURN c.name";
+    let plan_of = || format!("{:?}", planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap());
+    let first = plan_of();
+    // Planning other queries in between doesn't shift the numbering
+    planner::plan(&cypher::parse("MATCH ()-->() RETURN 1").unwrap(), &PropertyMap::new()).unwrap();
+    assert_eq!(first, plan_of());
+    assert!(first.contains("\"  UNNAMED0\""), "{first}");
+
+    // A user variable spelled like an old-style anonymous name is its own variable
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate("CREATE (:Person {name: 'Ada'})-[:KNOWS]->(:Person {name: 'Bob'})", PropertyMap::new())
+        .await
+        .unwrap();
+    let result = graph
+        .execute("MATCH (_anon_0:Person)-[:KNOWS]->() RETURN _anon_0.name AS name", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<String>("name").unwrap(), "Ada");
+}
+
+// ============================================================================
+// 12. count(r) over a bare relationship type reads the count store
+// ============================================================================
+
+#[tokio::test]
+async fn test_relationship_count_from_count_store() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person)-[:LIKES]->(a)",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let optimized = |query: &str| {
+        planner::optimize(planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap()).unwrap()
+    };
+
+    for query in ["MATCH ()-[r:KNOWS]->() RETURN count(r)", "MATCH (a)<-[:KNOWS]-() RETURN count(*) AS n"] {
+        assert!(
+            matches!(optimized(query), LogicalPlan::RelationshipCountFromCountStore { ref rel_type, .. } if rel_type == "KNOWS"),
+            "{query}"
+        );
+    }
+    let result = graph.execute("MATCH ()-[r:KNOWS]->() RETURN count(r) AS n", PropertyMap::new()).await.unwrap();
+    assert_eq!(result.columns, ["n"]);
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 2);
+
+    // Anything that narrows the pattern still expands, with the same answer
+    for (query, expected) in [
+        ("MATCH (a:Person)-[r:KNOWS]->() RETURN count(r) AS n", 2),
+        ("MATCH ()-[r:KNOWS]-() RETURN count(r) AS n", 4),
+        ("MATCH ()-[r:KNOWS]->() WHERE r.since > 0 RETURN count(r) AS n", 0),
+        ("MATCH ()-[r:KNOWS|LIKES]->() RETURN count(r) AS n", 3),
+    ] {
+        assert!(!matches!(optimized(query), LogicalPlan::RelationshipCountFromCountStore { .. }), "{query}");
+        let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+        assert_eq!(result.rows[0].get::<i64>("n").unwrap(), expected, "{query}");
+    }
+}
+
+// ============================================================================
+// 13. Inline pattern WHERE filters right after the element it constrains
+// ============================================================================
+
+#[tokio::test]
+async fn test_pattern_predicates() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person {name: 'Ada', age: 36})-[:KNOWS {since: 2021}]->(:Person {name: 'Bob'}), \
+             (a)-[:KNOWS {since: 2019}]->(:Person {name: 'Carol'}), \
+             (:Person {name: 'Dan', age: 12})-[:KNOWS {since: 2022}]->(:Person {name: 'Eve'})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    let query = "MATCH (a:Person WHERE a.age > 18)-[r:KNOWS WHERE r.since > 2020]->(b) RETURN b.name AS name";
+    let plan = planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap();
+    let LogicalPlan::Project { input, .. } = plan else { panic!("expected Project, got {plan:?}") };
+    let LogicalPlan::Filter { input, .. } = *input else { panic!("expected the relationship's Filter") };
+    let LogicalPlan::Expand { input, .. } = *input else { panic!("expected Expand") };
+    let LogicalPlan::Filter { input, .. } = *input else { panic!("expected the node's Filter") };
+    assert!(matches!(*input, LogicalPlan::NodeScan { .. }));
+
+    let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+    let names: Vec<String> = result.rows.iter().map(|r| r.get::<String>("name").unwrap()).collect();
+    assert_eq!(names, ["Bob"]);
+
+    // Inline and trailing WHERE combine; the count store can't answer either
+    let result = graph
+        .execute(
+            "MATCH (a WHERE a.name <> 'Bob')-[r:KNOWS WHERE r.since > 2018]->(b) WHERE b.name <> 'Eve' RETURN count(r) AS n",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 2);
+    let result = graph
+        .execute("MATCH ()-[r:KNOWS WHERE r.since > 2020]->() RETURN count(r) AS n", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 2);
+
+    // Creating clauses don't filter, so they reject it
+    assert!(graph.mutate("CREATE (n:Person WHERE n.age > 1)", PropertyMap::new()).await.is_err());
+}
+
+// ============================================================================
+// 14. DISTINCT is rejected on non-aggregate functions
+// ============================================================================
+
+#[tokio::test]
+async fn test_distinct_rejected_outside_aggregates() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph.mutate("CREATE (:Person {name: 'Ada'}), (:Person {name: 'Ada'})", PropertyMap::new()).await.unwrap();
+
+    for query in [
+        "MATCH (n:Person) RETURN toUpper(DISTINCT n.name)",
+        "MATCH (n:Person) WHERE size(DISTINCT n.name) > 1 RETURN n",
+        "MATCH (n:Person) RETURN count(DISTINCT toLower(DISTINCT n.name))",
+    ] {
+        match graph.execute(query, PropertyMap::new()).await {
+            Err(neo4j_rs::Error::SemanticError { message, .. }) => {
+                assert!(message.contains("Invalid use of DISTINCT"), "{message}");
+            }
+            other => panic!("expected SemanticError for {query:?}, got {other:?}"),
+        }
+    }
+
+    // Aggregates still take it
+    let result = graph
+        .execute("MATCH (n:Person) RETURN count(DISTINCT n.name) AS c, collect(DISTINCT n.name) AS names", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("c").unwrap(), 1);
+    assert_eq!(result.rows[0].get::<Vec<neo4j_rs::Value>>("names").unwrap(), vec![neo4j_rs::Value::from("Ada")]);
+}
+
+// ============================================================================
+// 15. Plans the backend can't run fail before execution
+// ============================================================================
+
+#[tokio::test]
+async fn test_capability_check_rejects_vector_query() {
+    let graph = Graph::open_memory().await.unwrap();
+    let query = "CALL db.index.vector.queryNodes('embeddings', 3, $vector) YIELD node, score";
+    let mut params = PropertyMap::new();
+    params.insert("vector".into(), neo4j_rs::Value::List(vec![neo4j_rs::Value::Float(0.5)]));
+
+    match graph.execute(query, params).await {
+        Err(neo4j_rs::Error::PlanError { message, .. }) => {
+            assert!(message.contains("backend does not support vector indexes"), "{message}");
+            assert!(message.contains("db.index.vector.queryNodes"), "{message}");
+        }
+        other => panic!("expected PlanError, got {other:?}"),
+    }
+
+    // The same plan passes against a backend that reports a vector index
+    let plan = planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap();
+    let memory = neo4j_rs::storage::MemoryBackend::new();
+    assert!(planner::check_capabilities(&plan, &memory.capabilities()).is_err());
+    let capabilities = neo4j_rs::BackendCapabilities { supports_vector_index: true, ..Default::default() };
+    planner::check_capabilities(&plan, &capabilities).unwrap();
+}
+
+#[tokio::test]
+async fn test_capability_check_rejects_schema_ops() {
+    use neo4j_rs::cypher::ast::SchemaCommand;
+
+    let graph = Graph::open_memory().await.unwrap();
+    let query = "CREATE CONSTRAINT FOR (n:Person) REQUIRE n.name IS UNIQUE";
+    match graph.mutate(query, PropertyMap::new()).await {
+        Err(neo4j_rs::Error::PlanError { message, .. }) => {
+            assert!(message.contains("backend does not support constraints"), "{message}");
+        }
+        other => panic!("expected PlanError, got {other:?}"),
+    }
+    // Plain indexes need no capability
+    graph.mutate("CREATE INDEX FOR (n:Person) ON (n.name)", PropertyMap::new()).await.unwrap();
+
+    let plan = planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap();
+    let capabilities = neo4j_rs::BackendCapabilities { supports_constraints: true, ..Default::default() };
+    planner::check_capabilities(&plan, &capabilities).unwrap();
+
+    // Typed indexes need the matching index capability
+    let memory = neo4j_rs::storage::MemoryBackend::new();
+    for (index_type, missing) in [("FULLTEXT", "full-text indexes"), ("VECTOR", "vector indexes")] {
+        let plan = LogicalPlan::SchemaOp(SchemaCommand::CreateIndex {
+            label: "Doc".into(),
+            property: "body".into(),
+            index_type: Some(index_type.into()),
+        });
+        let err = planner::check_capabilities(&plan, &memory.capabilities()).unwrap_err();
+        assert!(err.to_string().contains(missing), "{err}");
+    }
+}
+
+// ============================================================================
+// 16. explain_json describes the optimized plan
+// ============================================================================
+
+#[tokio::test]
+async fn test_explain_json() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (:Person {name: 'Ada', age: 36}), (:Person {name: 'Bob', age: 25}), (:Person {name: 'Cy', age: 41}), (:Company)",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    let plan = graph
+        .explain_json(
+            "MATCH (n:Person) WHERE n.age > 30 AND (n.name = 'Ada' OR n.name STARTS WITH $prefix) RETURN n.name AS name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let expected = serde_json::json!({
+        "operator": "Project",
+        "items": [{ "expr": "n.name", "alias": "name" }],
+        "estimatedRows": 3,
+        "children": [{
+            "operator": "Filter",
+            "predicate": "(n.name = 'Ada') OR (n.name STARTS WITH $prefix)",
+            "estimatedRows": 3,
+            "children": [{
+                "operator": "IndexRangeScan",
+                "label": "Person",
+                "property": "age",
+                "lower": { "value": "30", "inclusive": false },
+                "upper": null,
+                "alias": "n",
+                "estimatedRows": 3,
+                "children": [],
+            }],
+        }],
+    });
+    assert_eq!(plan, expected);
+
+    // Explaining runs nothing
+    let plan = graph.explain_json("MATCH (n:Company) DETACH DELETE n", PropertyMap::new()).await.unwrap();
+    assert_eq!(plan["operator"], "DeleteNode");
+    assert_eq!(plan["detach"], true);
+    assert_eq!(plan["children"][0]["estimatedRows"], 1);
+    let count = graph.execute("MATCH (n:Company) RETURN count(n) AS c", PropertyMap::new()).await.unwrap();
+    assert_eq!(count.rows[0].get::<i64>("c").unwrap(), 1);
+
+    // Explaining checks the plan the way running it does
+    for query in [
+        "MATCH (n:Person) USING INDEX n:Person(name) WHERE n.name = 'Ada' RETURN n",
+        "CALL db.index.vector.queryNodes('embeddings', 3, [0.5]) YIELD node, score",
+    ] {
+        let explained = graph.explain_json(query, PropertyMap::new()).await.unwrap_err();
+        let executed = graph.execute(query, PropertyMap::new()).await.unwrap_err();
+        assert!(matches!(explained, neo4j_rs::Error::PlanError { .. }), "{query}: {explained}");
+        assert_eq!(explained.to_string(), executed.to_string());
+    }
+}
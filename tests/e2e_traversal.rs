@@ -5,7 +5,8 @@
 //!
 //! Each test exercises: parse -> plan -> optimize -> execute against MemoryBackend.
 
-use neo4j_rs::{Graph, PropertyMap, Value, StorageBackend, NodeId, Relationship};
+use neo4j_rs::planner::{self, LogicalPlan};
+use neo4j_rs::{cypher, Graph, PropertyMap, Value, StorageBackend, NodeId, Relationship};
 
 // ============================================================================
 // Helper: create a graph with nodes and relationships via the backend API.
@@ -440,3 +441,701 @@ async fn test_relationship_alias_return() {
     assert_eq!(rel.rel_type, "KNOWS");
     assert_eq!(rel.properties.get("since"), Some(&Value::Int(2015)));
 }
+
+// ============================================================================
+// 11. Labels on the expanded node are enforced
+// ============================================================================
+
+#[tokio::test]
+async fn test_target_label_filter() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    graph.mutate("CREATE (n:Person {name: 'Alice'})", PropertyMap::new()).await.unwrap();
+    graph.mutate("CREATE (n:Person {name: 'Bob'})", PropertyMap::new()).await.unwrap();
+    graph.mutate("CREATE (n:Company {name: 'Acme'})", PropertyMap::new()).await.unwrap();
+
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+        backend.create_relationship(&mut tx, NodeId(1), NodeId(2), "LINKED", PropertyMap::new()).await.unwrap();
+        backend.create_relationship(&mut tx, NodeId(1), NodeId(3), "LINKED", PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+
+    let result = graph
+        .execute(
+            "MATCH (a:Person)-[:LINKED]->(c:Company) RETURN c.name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.rows.len(), 1, "Only the Company neighbour should match");
+    assert_eq!(result.rows[0].get::<String>("c.name").unwrap(), "Acme");
+}
+
+// ============================================================================
+// 12. Single vs. multiple relationship types in Expand
+// ============================================================================
+
+#[tokio::test]
+async fn test_expand_single_and_multiple_types() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    for name in ["Alice", "Bob", "Charlie", "Diana"] {
+        graph
+            .mutate(&format!("CREATE (n:Person {{name: '{}'}})", name), PropertyMap::new())
+            .await
+            .unwrap();
+    }
+
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+        backend.create_relationship(&mut tx, NodeId(1), NodeId(2), "KNOWS", PropertyMap::new()).await.unwrap();
+        backend.create_relationship(&mut tx, NodeId(1), NodeId(3), "WORKS_WITH", PropertyMap::new()).await.unwrap();
+        backend.create_relationship(&mut tx, NodeId(1), NodeId(4), "BLOCKS", PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+
+    let single = graph
+        .execute("MATCH (a:Person)-[:WORKS_WITH]->(b) RETURN b.name", PropertyMap::new())
+        .await
+        .unwrap();
+    let names: Vec<String> = single.rows.iter().map(|r| r.get("b.name").unwrap()).collect();
+    assert_eq!(names, vec!["Charlie".to_string()]);
+
+    let multi = graph
+        .execute("MATCH (a:Person)-[:KNOWS|WORKS_WITH]->(b) RETURN b.name", PropertyMap::new())
+        .await
+        .unwrap();
+    let mut names: Vec<String> = multi.rows.iter().map(|r| r.get("b.name").unwrap()).collect();
+    names.sort();
+    assert_eq!(names, vec!["Bob".to_string(), "Charlie".to_string()], "BLOCKS must be filtered out");
+}
+
+// ============================================================================
+// 13. apoc.path.expand: relationship and label filters
+// ============================================================================
+
+#[tokio::test]
+async fn test_apoc_path_expand() {
+    let (graph, alice, bob, _charlie) = setup_linear_chain().await;
+    graph.mutate("CREATE (n:Person {name: 'Diana'})", PropertyMap::new()).await.unwrap();
+    graph.mutate("CREATE (n:Company {name: 'Acme'})", PropertyMap::new()).await.unwrap();
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+        backend.create_relationship(&mut tx, NodeId(4), alice, "FOLLOWS", PropertyMap::new()).await.unwrap();
+        backend.create_relationship(&mut tx, bob, NodeId(5), "KNOWS", PropertyMap::new()).await.unwrap();
+        // Wrong direction for `KNOWS>` from Alice
+        backend.create_relationship(&mut tx, NodeId(4), alice, "KNOWS", PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+
+    let mut unbounded = PropertyMap::new();
+    unbounded.insert("unbounded".into(), Value::Int(-1));
+    let expand = |filters: &'static str| {
+        let graph = &graph;
+        let unbounded = &unbounded;
+        async move {
+            let result = graph
+                .execute(&format!("CALL apoc.path.expand(1, {filters}) YIELD path"), unbounded.clone())
+                .await?;
+            let mut ends: Vec<(usize, String)> = result
+                .rows
+                .iter()
+                .map(|r| match r.get::<Value>("path").unwrap() {
+                    Value::Path(p) => (p.len(), p.end().get("name").unwrap().as_str().unwrap().to_string()),
+                    other => panic!("expected a path, got {other:?}"),
+                })
+                .collect();
+            ends.sort();
+            Ok::<_, neo4j_rs::Error>(ends)
+        }
+    };
+    let path = |len: usize, name: &str| (len, name.to_string());
+
+    // Acme is a Company, so the whitelist stops the expansion there. Paths
+    // only avoid repeating relationships, so Diana's KNOWS leads back to Alice.
+    assert_eq!(
+        expand("'KNOWS>|<FOLLOWS', '+Person', 1, 2").await.unwrap(),
+        vec![path(1, "Bob"), path(1, "Diana"), path(2, "Alice"), path(2, "Charlie")]
+    );
+    // A blacklist; minLevel 0 includes the start node on its own
+    assert_eq!(
+        expand("'KNOWS>', '-Company', 0, $unbounded").await.unwrap(),
+        vec![path(0, "Alice"), path(1, "Bob"), path(2, "Charlie")]
+    );
+    // No direction: both KNOWS edges from Alice, one hop
+    assert_eq!(expand("'KNOWS', '', 1, 1").await.unwrap(), vec![path(1, "Bob"), path(1, "Diana")]);
+
+    assert!(expand("'KNOWS>', '/Person', 1, 2").await.is_err());
+    assert!(expand("'KNOWS>,FOLLOWS>', '', 1, 2").await.is_err());
+}
+
+#[tokio::test]
+async fn test_apoc_path_expand_limits() {
+    use neo4j_rs::storage::MemoryBackend;
+
+    // Eight nodes all linked to each other: unbounded expansion explodes
+    let limits = neo4j_rs::ExpandLimits { max_depth: 100, max_paths: 1000 };
+    let backend = MemoryBackend::new().with_expand_limits(limits);
+    let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+    let mut ids = Vec::new();
+    for _ in 0..8 {
+        ids.push(backend.create_node(&mut tx, &["N"], PropertyMap::new()).await.unwrap());
+    }
+    for (i, &a) in ids.iter().enumerate() {
+        for &b in &ids[i + 1..] {
+            backend.create_relationship(&mut tx, a, b, "LINK", PropertyMap::new()).await.unwrap();
+        }
+    }
+    backend.commit_tx(tx).await.unwrap();
+
+    let mut params = PropertyMap::new();
+    params.insert("start".into(), Value::Int(ids[0].0 as i64));
+    let query = "CALL apoc.path.expand($start, '', '', 0, -1) YIELD path";
+    let graph = Graph::with_backend(backend);
+    let err = graph.execute(query, params.clone()).await.unwrap_err();
+    assert!(err.to_string().contains("expansion limit exceeded"), "{err}");
+    // An explicit maxLevel past max_depth is refused outright
+    let err = graph
+        .execute("CALL apoc.path.expand($start, '', '', 1, 101) YIELD path", params.clone())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("expansion limit exceeded"), "{err}");
+
+    let graph = graph.with_max_intermediate_rows(40);
+    let err = graph.execute("CALL apoc.path.expand($start, '', '', 0, 2) YIELD path", params).await.unwrap_err();
+    assert!(err.to_string().contains("result set too large"), "{err}");
+}
+
+// ============================================================================
+// 14. Expand into an already-bound node
+// ============================================================================
+
+#[tokio::test]
+async fn test_expand_into_bound_node() {
+    let (graph, alice, _bob, charlie) = setup_linear_chain().await;
+    {
+        // Close the triangle: Charlie -[:KNOWS]-> Alice
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+        backend.create_relationship(&mut tx, charlie, alice, "KNOWS", PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+
+    let pair = |a: &str, b: &str| {
+        let mut params = PropertyMap::new();
+        params.insert("a".into(), Value::from(a));
+        params.insert("b".into(), Value::from(b));
+        params
+    };
+    let query = "MATCH (a:Person), (b:Person), (a)-[:KNOWS]->(b) \
+                 WHERE a.name = $a AND b.name = $b RETURN a.name, b.name";
+
+    // Both endpoints are bound: the pattern checks the specific pair
+    let result = graph.execute(query, pair("Alice", "Bob")).await.unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<String>("b.name").unwrap(), "Bob");
+    let result = graph.execute(query, pair("Bob", "Alice")).await.unwrap();
+    assert!(result.rows.is_empty(), "direction is respected");
+    let result = graph.execute(query, pair("Alice", "Charlie")).await.unwrap();
+    assert!(result.rows.is_empty(), "Alice only reaches Charlie in two hops");
+
+    // A pattern that returns to its start only matches closed cycles
+    let result = graph
+        .execute(
+            "MATCH (a)-[:KNOWS]->(b)-[:KNOWS]->(c)-[:KNOWS]->(a) RETURN a.name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let mut names: Vec<String> = result.rows.iter().map(|r| r.get("a.name").unwrap()).collect();
+    names.sort();
+    assert_eq!(names, vec!["Alice", "Bob", "Charlie"]);
+}
+
+// ============================================================================
+// 15. Undirected patterns: self-loops and bidirectional pairs
+// ============================================================================
+
+#[tokio::test]
+async fn test_undirected_self_loop_and_pair() {
+    let graph = Graph::open_memory().await.unwrap();
+    for name in ["Alice", "Bob", "Charlie"] {
+        graph
+            .mutate(&format!("CREATE (n:Person {{name: '{}'}})", name), PropertyMap::new())
+            .await
+            .unwrap();
+    }
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+        backend.create_relationship(&mut tx, NodeId(1), NodeId(1), "LOVES", PropertyMap::new()).await.unwrap();
+        backend.create_relationship(&mut tx, NodeId(2), NodeId(3), "KNOWS", PropertyMap::new()).await.unwrap();
+        backend.create_relationship(&mut tx, NodeId(3), NodeId(2), "KNOWS", PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+    let count = |query: &'static str| {
+        let graph = &graph;
+        async move {
+            let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+            result.rows[0].get::<i64>("n").unwrap()
+        }
+    };
+
+    // A self-loop matches once either way, binding both ends to the node
+    let result = graph
+        .execute("MATCH (a)-[:LOVES]-(b) RETURN a.name, b.name", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<String>("a.name").unwrap(), "Alice");
+    assert_eq!(result.rows[0].get::<String>("b.name").unwrap(), "Alice");
+    assert_eq!(count("MATCH (a)-[:LOVES]->(b) RETURN count(*) AS n").await, 1);
+
+    // Undirected, every other relationship matches once from each end
+    assert_eq!(count("MATCH (a)-[:KNOWS]->(b) RETURN count(*) AS n").await, 2);
+    assert_eq!(count("MATCH (a)-[:KNOWS]-(b) RETURN count(*) AS n").await, 4);
+    assert_eq!(
+        count("MATCH (a:Person)-[:KNOWS]-(b) WHERE a.name = 'Bob' RETURN count(*) AS n").await,
+        2
+    );
+}
+
+// ============================================================================
+// 16. WHERE on relationship properties filters after the expand binds `r`
+// ============================================================================
+
+#[tokio::test]
+async fn test_where_on_relationship_property() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:User {name: 'Ann'}), (b:User {name: 'Ben'}), \
+             (m1:Movie {title: 'Alien'}), (m2:Movie {title: 'Brazil'}), (m3:Movie {title: 'Cube'})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    for (user, title, stars) in [("Ann", "Alien", 5), ("Ann", "Brazil", 2), ("Ann", "Cube", 4), ("Ben", "Brazil", 4)] {
+        graph
+            .mutate(
+                &format!(
+                    "MATCH (u:User), (m:Movie) WHERE u.name = '{user}' AND m.title = '{title}' \
+                     CREATE (u)-[:RATED {{stars: {stars}}}]->(m)"
+                ),
+                PropertyMap::new(),
+            )
+            .await
+            .unwrap();
+    }
+
+    let titles = |query: &'static str| {
+        let graph = &graph;
+        async move {
+            let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+            let mut titles: Vec<String> = result.rows.iter().map(|r| r.get("b.title").unwrap()).collect();
+            titles.sort();
+            titles
+        }
+    };
+
+    assert_eq!(
+        titles("MATCH (a)-[r:RATED]->(b) WHERE r.stars >= 4 RETURN b.title").await,
+        ["Alien", "Brazil", "Cube"]
+    );
+    assert_eq!(
+        titles("MATCH (a:User)-[r:RATED]->(b:Movie) WHERE a.name = 'Ann' AND r.stars >= 4 RETURN b.title").await,
+        ["Alien", "Cube"]
+    );
+    // A missing property compares as null, so it never matches
+    assert!(titles("MATCH (a)-[r:RATED]->(b) WHERE r.missing >= 4 RETURN b.title").await.is_empty());
+
+    // The predicate sits above the expand that binds `r`
+    let query = "MATCH (a:User)-[r:RATED]->(b) WHERE r.stars >= 4 RETURN b.title";
+    let logical = planner::plan(&cypher::parse(query).unwrap(), &PropertyMap::new()).unwrap();
+    let optimized = planner::optimize(logical).unwrap();
+    let LogicalPlan::Project { input, .. } = &optimized else { panic!("got {optimized:?}") };
+    assert!(
+        matches!(
+            input.as_ref(),
+            LogicalPlan::Filter { input, .. }
+                if matches!(input.as_ref(), LogicalPlan::Expand { rel_alias: Some(r), .. } if r == "r")
+        ),
+        "got {input:?}"
+    );
+}
+
+// ============================================================================
+// 17. EXISTS subqueries, with and without an inner WHERE
+// ============================================================================
+
+#[tokio::test]
+async fn test_exists_subquery() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person {name: 'Alice'})-[:KNOWS]->(b:Person {name: 'Bob', active: true})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    graph
+        .mutate(
+            "CREATE (c:Person {name: 'Carol'})-[:KNOWS]->(d:Person {name: 'Dan', active: false})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    let names = |result: neo4j_rs::QueryResult| {
+        let mut names: Vec<String> = result.rows.iter().map(|r| r.get("name").unwrap()).collect();
+        names.sort();
+        names
+    };
+
+    // Brace form with a predicate on the inner variable
+    let result = graph
+        .execute(
+            "MATCH (n:Person) WHERE EXISTS { MATCH (n)-[:KNOWS]->(m) WHERE m.active } RETURN n.name AS name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(names(result), ["Alice"]);
+
+    // Without the inner WHERE, and without the MATCH keyword
+    let result = graph
+        .execute(
+            "MATCH (n:Person) WHERE EXISTS { (n)-[:KNOWS]->(:Person) } RETURN n.name AS name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(names(result), ["Alice", "Carol"]);
+
+    // The bound variable at the end of the pattern; negated and combined with OR
+    let result = graph
+        .execute(
+            "MATCH (n:Person) WHERE NOT EXISTS { MATCH (m)-[:KNOWS]->(n) } OR n.name = 'Dan' \
+             RETURN n.name AS name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(names(result), ["Alice", "Carol", "Dan"]);
+
+    // Pattern form
+    let result = graph
+        .execute(
+            "MATCH (n:Person) WHERE EXISTS((n)<-[:KNOWS]-(:Person)) AND n.active RETURN n.name AS name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(names(result), ["Bob"]);
+}
+
+// ============================================================================
+// 18. Case-insensitive relationship types are opt-in on the backend
+// ============================================================================
+
+#[tokio::test]
+async fn test_case_insensitive_rel_types() {
+    use neo4j_rs::BackendConfig;
+
+    for (enabled, expected) in [(false, 0), (true, 1)] {
+        let graph = Graph::open(BackendConfig::Memory { case_insensitive_rel_types: enabled }).await.unwrap();
+        graph
+            .mutate("CREATE (a:Person {name: 'Alice'})-[:KNOWS]->(b:Person {name: 'Bob'})", PropertyMap::new())
+            .await
+            .unwrap();
+
+        for query in [
+            "MATCH (a:Person)-[:knows]->(b) RETURN b.name AS name",
+            "MATCH (a:Person)-[:Knows|LIKES]->(b) RETURN b.name AS name",
+            "MATCH ()-[r:knows]->() RETURN r",
+        ] {
+            let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+            assert_eq!(result.rows.len(), expected, "enabled: {enabled}, {query}");
+        }
+        let result = graph.execute("MATCH ()-[r:knows]->() RETURN count(r) AS n", PropertyMap::new()).await.unwrap();
+        assert_eq!(result.rows[0].get::<i64>("n").unwrap(), expected as i64, "enabled: {enabled}");
+    }
+
+    // The durable configuration carries the policy too
+    let dir = std::env::temp_dir().join(format!("neo4j-rs-case-{}", std::process::id()));
+    let config = BackendConfig::DurableMemory {
+        wal_path: dir.join("graph.wal"),
+        fsync: neo4j_rs::FsyncPolicy::Never,
+        case_insensitive_rel_types: true,
+    };
+    std::fs::create_dir_all(&dir).unwrap();
+    let graph = Graph::open(config.clone()).await.unwrap();
+    graph.mutate("CREATE (:A)-[:KNOWS]->(:B)", PropertyMap::new()).await.unwrap();
+    drop(graph);
+    let graph = Graph::open(config).await.unwrap();
+    let result = graph.execute("MATCH (:A)-[:knows]->(b) RETURN b", PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows.len(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// ============================================================================
+// 19. A two-type pattern over a node with three relationship types
+// ============================================================================
+
+#[tokio::test]
+async fn test_multi_type_expand() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person {name: 'Alice'})-[:KNOWS]->(b:Person {name: 'Bob'}), \
+             (a)-[:LIKES]->(c:Person {name: 'Carol'}), (a)-[:WORKS_WITH]->(d:Person {name: 'Dan'})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    let result = graph
+        .execute(
+            "MATCH (a:Person)-[r:KNOWS|LIKES]->(b) WHERE a.name = 'Alice' RETURN b.name AS name, type(r) AS t",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let mut found: Vec<(String, String)> =
+        result.rows.iter().map(|r| (r.get("name").unwrap(), r.get("t").unwrap())).collect();
+    found.sort();
+    assert_eq!(found, [("Bob".into(), "KNOWS".into()), ("Carol".into(), "LIKES".into())]);
+}
+
+// ============================================================================
+// 20. A path variable binds the matched path
+// ============================================================================
+
+#[tokio::test]
+async fn test_path_variable() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person {name: 'Alice'})-[:KNOWS {since: 2020}]->(b:Person {name: 'Bob'})-[:KNOWS {since: 2021}]->(c:Person {name: 'Carol'})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let path_of = |row: &neo4j_rs::ResultRow| match row.values.iter().find(|(k, _)| k == "p") {
+        Some((_, Value::Path(path))) => (**path).clone(),
+        other => panic!("p is not a path: {other:?}"),
+    };
+    let names = |path: &neo4j_rs::Path| -> Vec<Value> { path.nodes.iter().map(|n| n.get("name").unwrap().clone()).collect() };
+
+    let result = graph
+        .execute(
+            "MATCH p = (a:Person)-[:KNOWS]->(b)-[:KNOWS]->(c) RETURN p, length(p) AS len",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<i64>("len").unwrap(), 2);
+    let path = path_of(&result.rows[0]);
+    assert_eq!(names(&path), [Value::from("Alice"), Value::from("Bob"), Value::from("Carol")]);
+    let since: Vec<&Value> = path.relationships.iter().map(|r| &r.properties["since"]).collect();
+    assert_eq!(since, [&Value::Int(2020), &Value::Int(2021)]);
+    for (i, rel) in path.relationships.iter().enumerate() {
+        assert_eq!((rel.src, rel.dst), (path.nodes[i].id, path.nodes[i + 1].id));
+    }
+
+    // Walking a relationship backwards keeps its own direction
+    let result = graph
+        .execute("MATCH p = (b:Person)<-[:KNOWS]-(a) WHERE b.name = 'Bob' RETURN p", PropertyMap::new())
+        .await
+        .unwrap();
+    let path = path_of(&result.rows[0]);
+    assert_eq!(names(&path), [Value::from("Bob"), Value::from("Alice")]);
+    assert_eq!(path.relationships[0].src, path.nodes[1].id);
+
+    // In a subquery, a path starting at an outer node is bound too
+    let result = graph
+        .execute(
+            "MATCH (n:Person) WHERE EXISTS { MATCH p = (n)-[:KNOWS]->(m) WHERE length(p) = 1 } RETURN n.name AS name ORDER BY name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let found: Vec<String> = result.rows.iter().map(|r| r.get::<String>("name").unwrap()).collect();
+    assert_eq!(found, ["Alice", "Bob"]);
+
+    // One that reaches the outer node part way along can't be, and is rejected
+    let err = graph
+        .execute("MATCH (n:Person) WHERE EXISTS { MATCH p = (m)-[:KNOWS]->(n) } RETURN n", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::PlanError { .. }), "{err:?}");
+}
+
+// ============================================================================
+// 21. A relationship is traversed at most once per pattern
+// ============================================================================
+
+#[tokio::test]
+async fn test_relationship_uniqueness() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate("CREATE (:Person {name: 'Alice'})-[:KNOWS]->(:Person {name: 'Bob'})", PropertyMap::new())
+        .await
+        .unwrap();
+
+    // Going out and back over the one edge would need it twice
+    let result = graph
+        .execute("MATCH (a)-[:KNOWS]-(b)-[:KNOWS]-(c) RETURN a.name, c.name", PropertyMap::new())
+        .await
+        .unwrap();
+    assert!(result.rows.is_empty(), "reused the edge: {:?}", result.rows);
+
+    let result = graph
+        .execute("MATCH (a)-[r:KNOWS]->(b)<-[s:KNOWS]-(c) RETURN c.name", PropertyMap::new())
+        .await
+        .unwrap();
+    assert!(result.rows.is_empty());
+
+    // Each single hop still matches, once per direction for an undirected one
+    let result = graph
+        .execute("MATCH (a)-[:KNOWS]-(b) RETURN a.name", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 2);
+
+    // A second edge makes the two-hop match real
+    graph
+        .mutate(
+            "MATCH (b:Person {name: 'Bob'}) CREATE (b)-[:KNOWS]->(:Person {name: 'Carol'})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let result = graph
+        .execute("MATCH (a)-[:KNOWS]->(b)-[:KNOWS]->(c) RETURN a.name AS a, c.name AS c", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<String>("a").unwrap(), "Alice");
+    assert_eq!(result.rows[0].get::<String>("c").unwrap(), "Carol");
+}
+
+// ============================================================================
+// 22. COUNT { ... } subqueries
+// ============================================================================
+
+#[tokio::test]
+async fn test_count_subquery() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person {name: 'Alice'}), (b:Person {name: 'Bob'}), (c:Person {name: 'Carol'}), \
+             (a)-[:KNOWS]->(b), (a)-[:KNOWS]->(c), (b)-[:KNOWS]->(c)",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+
+    // Counted per outer row, zero included
+    let result = graph
+        .execute(
+            "MATCH (a:Person) RETURN a.name AS name, COUNT { (a)-[:KNOWS]->() } AS friends ORDER BY name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let counts: Vec<(String, i64)> = result
+        .rows
+        .iter()
+        .map(|r| (r.get::<String>("name").unwrap(), r.get::<i64>("friends").unwrap()))
+        .collect();
+    assert_eq!(counts, [("Alice".into(), 2), ("Bob".into(), 1), ("Carol".into(), 0)]);
+
+    // MATCH and WHERE inside the braces, COUNT in the outer WHERE
+    let result = graph
+        .execute(
+            "MATCH (a:Person) WHERE COUNT { MATCH (a)-[:KNOWS]->(f) WHERE f.name <> 'Bob' } = 1 RETURN a.name AS name ORDER BY name",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let names: Vec<String> = result.rows.iter().map(|r| r.get::<String>("name").unwrap()).collect();
+    assert_eq!(names, ["Alice", "Bob"]);
+
+    // Nothing to correlate with: the same count on every row
+    let result = graph
+        .execute("MATCH (a:Person) RETURN COUNT { (p:Person) } AS people", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 3);
+    assert!(result.rows.iter().all(|r| r.get::<i64>("people").unwrap() == 3));
+
+    // count() the function is unaffected
+    let result = graph
+        .execute("MATCH (a:Person) RETURN count(a) AS n", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 3);
+}
+
+// ============================================================================
+// 23. Walking the graph from code: neighbors, get_node, get_relationship
+// ============================================================================
+
+#[tokio::test]
+async fn test_imperative_traversal() {
+    use neo4j_rs::{Direction, RelId};
+
+    let (graph, alice, bob, charlie) = setup_linear_chain().await;
+    let loop_id = {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+        backend.create_relationship(&mut tx, alice, charlie, "LIKES", PropertyMap::new()).await.unwrap();
+        let id = backend.create_relationship(&mut tx, bob, bob, "KNOWS", PropertyMap::new()).await.unwrap();
+        backend.commit_tx(tx).await.unwrap();
+        id
+    };
+
+    // Walk Alice -> Bob -> Charlie over KNOWS, skipping Bob's self-loop
+    let mut path = vec![alice];
+    let mut at = alice;
+    while let Some((_, next)) = graph
+        .neighbors(at, Direction::Outgoing, Some("KNOWS"))
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|(_, n)| n.id != at)
+    {
+        path.push(next.id);
+        at = next.id;
+    }
+    assert_eq!(path, [alice, bob, charlie]);
+
+    // Incoming, and any type: Charlie is known by Bob and liked by Alice
+    let mut incoming: Vec<(String, NodeId)> = graph
+        .neighbors(charlie, Direction::Incoming, None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(r, n)| (r.rel_type, n.id))
+        .collect();
+    incoming.sort();
+    assert_eq!(incoming, [("KNOWS".to_string(), bob), ("LIKES".to_string(), alice)]);
+
+    // A self-loop pairs with the node itself
+    let both = graph.neighbors(bob, Direction::Both, Some("KNOWS")).await.unwrap();
+    assert!(both.iter().any(|(r, n)| r.id == loop_id && n.id == bob));
+
+    let node = graph.get_node(charlie).await.unwrap().unwrap();
+    assert_eq!(node.properties["name"], Value::from("Charlie"));
+    assert!(graph.get_node(NodeId(99)).await.unwrap().is_none());
+    let rel = graph.get_relationship(loop_id).await.unwrap().unwrap();
+    assert_eq!((rel.src, rel.dst), (bob, bob));
+    assert!(graph.get_relationship(RelId(99)).await.unwrap().is_none());
+}
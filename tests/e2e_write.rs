@@ -499,3 +499,862 @@ async fn test_create_different_labels_and_count() {
     let company_count: i64 = result.rows[0].get("count").unwrap();
     assert_eq!(company_count, 1);
 }
+
+// ============================================================================
+// 14. Graph::merge_node — find by key or create, idempotently
+// ============================================================================
+
+#[tokio::test]
+async fn test_merge_node_idempotent() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let mut props = PropertyMap::new();
+    props.insert("age".into(), Value::Int(36));
+    let first = graph
+        .merge_node("Person", "email", Value::from("ada@example.com"), props)
+        .await
+        .unwrap();
+    assert!(first.has_label("Person"));
+    assert_eq!(first.get("email"), Some(&Value::from("ada@example.com")));
+    assert_eq!(first.get("age"), Some(&Value::Int(36)));
+
+    // Second call finds the existing node; props are not reapplied.
+    let mut props = PropertyMap::new();
+    props.insert("age".into(), Value::Int(99));
+    let second = graph
+        .merge_node("Person", "email", Value::from("ada@example.com"), props)
+        .await
+        .unwrap();
+    assert_eq!(second.id, first.id);
+    assert_eq!(second.get("age"), Some(&Value::Int(36)));
+
+    let other = graph
+        .merge_node("Person", "email", Value::from("bob@example.com"), PropertyMap::new())
+        .await
+        .unwrap();
+    assert_ne!(other.id, first.id);
+
+    let result = graph
+        .execute("MATCH (n:Person) RETURN count(n)", PropertyMap::new())
+        .await
+        .unwrap();
+    let count: i64 = result.rows[0].get("count").unwrap();
+    assert_eq!(count, 2);
+}
+
+// ============================================================================
+// 15. SET a property on a relationship bound in MATCH
+// ============================================================================
+
+#[tokio::test]
+async fn test_set_relationship_property() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    graph.mutate("CREATE (n:Person {name: 'Alice'})", PropertyMap::new()).await.unwrap();
+    graph.mutate("CREATE (n:Person {name: 'Bob'})", PropertyMap::new()).await.unwrap();
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+        backend
+            .create_relationship(&mut tx, NodeId(1), NodeId(2), "KNOWS", PropertyMap::new())
+            .await
+            .unwrap();
+        backend.commit_tx(tx).await.unwrap();
+    }
+
+    graph
+        .mutate("MATCH (a)-[r:KNOWS]->(b) SET r.weight = 1.5", PropertyMap::new())
+        .await
+        .unwrap();
+
+    let result = graph
+        .execute("MATCH (a)-[r:KNOWS]->(b) RETURN r.weight", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    let weight: f64 = result.rows[0].get("r.weight").unwrap();
+    assert_eq!(weight, 1.5);
+
+    graph
+        .mutate("MATCH (a)-[r:KNOWS]->(b) DELETE r", PropertyMap::new())
+        .await
+        .unwrap();
+    let result = graph
+        .execute("MATCH (a)-[r:KNOWS]->(b) RETURN r", PropertyMap::new())
+        .await
+        .unwrap();
+    assert!(result.rows.is_empty());
+}
+
+// ============================================================================
+// 16. LOAD CSV feeds each record into the statement that follows it
+// ============================================================================
+
+#[tokio::test]
+async fn test_load_csv_create() {
+    let path = std::env::temp_dir().join(format!("neo4j_rs_load_csv_{}.csv", std::process::id()));
+    std::fs::write(&path, "name,age\nAlice,30\n\"Smith, Bob\",25\n\nCarol\n").unwrap();
+    let url = format!("file:///{}", path.file_name().unwrap().to_str().unwrap());
+
+    let graph = Graph::open_memory().await.unwrap().with_import_root(std::env::temp_dir());
+    let mut params = PropertyMap::new();
+    params.insert("url".into(), Value::from(url.as_str()));
+    let result = graph
+        .mutate(
+            "LOAD CSV WITH HEADERS FROM $url AS row CREATE (:Person {name: row.name, age: row.age})",
+            params.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.stats.nodes_created, 3);
+
+    let result = graph
+        .execute("MATCH (p:Person) RETURN p.name, p.age ORDER BY p.name", PropertyMap::new())
+        .await
+        .unwrap();
+    let names: Vec<String> = result.rows.iter().map(|r| r.get("p.name").unwrap()).collect();
+    assert_eq!(names, vec!["Alice", "Carol", "Smith, Bob"]);
+    assert_eq!(result.rows[0].get::<String>("p.age").unwrap(), "30");
+    // Missing trailing fields come through as NULL
+    assert_eq!(result.rows[1].get_opt::<String>("p.age").unwrap(), None);
+
+    // Without headers each row is a list of fields
+    let result = graph
+        .execute("LOAD CSV FROM $url AS row RETURN row", params)
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 4);
+    assert_eq!(
+        result.rows[0].get_value("row"),
+        Some(&Value::List(vec![Value::from("name"), Value::from("age")]))
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_load_csv_field_terminator() {
+    let path = std::env::temp_dir().join(format!("neo4j_rs_load_tsv_{}.csv", std::process::id()));
+    std::fs::write(&path, "a;b\n1;\"x\ny\"\n").unwrap();
+    let query = format!(
+        "LOAD CSV WITH HEADERS FROM 'file:///{}' AS row FIELDTERMINATOR ';' RETURN row.a AS a, row.b AS b",
+        path.file_name().unwrap().to_str().unwrap()
+    );
+
+    let graph = Graph::open_memory().await.unwrap().with_import_root(std::env::temp_dir());
+    let result = graph.execute(&query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<String>("a").unwrap(), "1");
+    assert_eq!(result.rows[0].get::<String>("b").unwrap(), "x\ny");
+
+    let err = graph
+        .execute("LOAD CSV FROM 'ftp://example.com/x.csv' AS row RETURN row", PropertyMap::new())
+        .await;
+    assert!(err.is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// The largest row count each LOAD CSV operator reported.
+#[derive(Default)]
+struct LoadCsvRows(std::sync::Mutex<usize>);
+
+impl neo4j_rs::QueryObserver for LoadCsvRows {
+    fn on_rows_produced(&self, op_name: &str, rows: usize) {
+        if op_name == "LoadCsv" {
+            let mut max = self.0.lock().unwrap();
+            *max = (*max).max(rows);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_load_csv_streams_in_batches() {
+    let path = std::env::temp_dir().join(format!("neo4j_rs_load_csv_batches_{}.csv", std::process::id()));
+    let csv: String = std::iter::once("i\n".to_string()).chain((0..2500).map(|i| format!("{i}\n"))).collect();
+    std::fs::write(&path, csv).unwrap();
+
+    let observer = std::sync::Arc::new(LoadCsvRows::default());
+    let graph = Graph::open_memory()
+        .await
+        .unwrap()
+        .with_import_root(std::env::temp_dir())
+        .with_observer(observer.clone());
+    let query = format!(
+        "LOAD CSV WITH HEADERS FROM 'file:///{}' AS row CREATE (:Row {{i: row.i}})",
+        path.file_name().unwrap().to_str().unwrap()
+    );
+    let result = graph.mutate(&query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.stats.nodes_created, 2500);
+    // Records reach CREATE a batch at a time, never all at once
+    assert_eq!(*observer.0.lock().unwrap(), 1000);
+
+    let count = graph.execute("MATCH (r:Row) RETURN count(r) AS n", PropertyMap::new()).await.unwrap();
+    assert_eq!(count.rows[0].get::<i64>("n").unwrap(), 2500);
+
+    // Counting and summing fold each batch in as it is read
+    *observer.0.lock().unwrap() = 0;
+    let query = format!(
+        "LOAD CSV WITH HEADERS FROM 'file:///{}' AS row WITH row WHERE toInteger(row.i) % 2 = 0 \
+         RETURN count(*) AS n, sum(toInteger(row.i)) AS total",
+        path.file_name().unwrap().to_str().unwrap()
+    );
+    let result = graph.execute(&query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 1250);
+    assert_eq!(result.rows[0].get::<i64>("total").unwrap(), (0..2500).step_by(2).sum::<i64>());
+    assert_eq!(*observer.0.lock().unwrap(), 1000);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_load_csv_import_root() {
+    let root = std::env::temp_dir().join(format!("neo4j_rs_import_{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("people.csv"), "name\nAda\n").unwrap();
+    let outside = root.with_extension("csv");
+    std::fs::write(&outside, "name\nMallory\n").unwrap();
+
+    let graph = Graph::open_memory().await.unwrap().with_import_root(&root);
+    let result = graph
+        .execute("LOAD CSV WITH HEADERS FROM 'file:///people.csv' AS row RETURN row.name AS name", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<String>("name").unwrap(), "Ada");
+
+    // `..` can't climb out, and absolute paths are read under the root
+    let escape = format!("file:///../{}", outside.file_name().unwrap().to_str().unwrap());
+    for url in [escape, format!("file://{}", outside.display())] {
+        let err = graph
+            .execute(&format!("LOAD CSV FROM '{url}' AS row RETURN row"), PropertyMap::new())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Cannot load from"), "{url}: {err}");
+    }
+
+    // Without an import root, file urls are refused outright
+    let graph = Graph::open_memory().await.unwrap();
+    let err = graph
+        .execute("LOAD CSV FROM 'file:///etc/passwd' AS row RETURN row", PropertyMap::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("import directory"), "{err}");
+
+    std::fs::remove_file(&outside).unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+// ============================================================================
+// 17. with_transaction commits on Ok and retries write conflicts
+// ============================================================================
+
+#[tokio::test]
+async fn test_with_transaction_retry() {
+    use neo4j_rs::{Error, RetryPolicy, tx::TxMode};
+
+    let graph = Graph::open_memory().await.unwrap().with_retry_policy(RetryPolicy::retries(3));
+
+    let mut attempts = 0;
+    let created = graph
+        .with_transaction(TxMode::ReadWrite, async |tx| {
+            attempts += 1;
+            if attempts < 3 {
+                return Err(Error::TxError("write conflict".into()));
+            }
+            let result = tx.execute("CREATE (n:Person {name: 'Alice'})", PropertyMap::new()).await?;
+            Ok(result.stats.nodes_created)
+        })
+        .await
+        .unwrap();
+    assert_eq!(attempts, 3);
+    assert_eq!(created, 1);
+
+    // Other errors are returned straight away
+    let mut attempts = 0;
+    let err = graph
+        .with_transaction(TxMode::ReadOnly, async |tx| {
+            attempts += 1;
+            tx.execute("MATCH (n RETURN n", PropertyMap::new()).await
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::SyntaxError { .. }), "got: {err:?}");
+    assert_eq!(attempts, 1);
+
+    // Without a policy, a conflict is not retried
+    let graph = Graph::open_memory().await.unwrap();
+    let mut attempts = 0;
+    let err = graph
+        .with_transaction(TxMode::ReadWrite, async |_tx| -> neo4j_rs::Result<()> {
+            attempts += 1;
+            Err(Error::TxError("write conflict".into()))
+        })
+        .await
+        .unwrap_err();
+    assert!(err.is_write_conflict());
+    assert_eq!(attempts, 1);
+}
+
+// ============================================================================
+// 18. CREATE of independent patterns returns one row binding all of them
+// ============================================================================
+
+#[tokio::test]
+async fn test_create_independent_patterns_returns_one_row() {
+    let graph = Graph::open_memory().await.unwrap();
+
+    let result = graph
+        .mutate("CREATE (a:X {v: 1}), (b:Y {v: 2}), (c:Z {v: 3}) RETURN a, b, c", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.columns, vec!["a", "b", "c"]);
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.stats.nodes_created, 3);
+    for (column, label, v) in [("a", "X", 1), ("b", "Y", 2), ("c", "Z", 3)] {
+        let node: Node = result.rows[0].get(column).unwrap();
+        assert!(node.has_label(label), "{column}: {node:?}");
+        assert_eq!(node.get("v"), Some(&Value::from(v)));
+    }
+
+    // Each input row still creates one set of nodes
+    graph.mutate("CREATE (s:Seed {i: 1}), (t:Seed {i: 2})", PropertyMap::new()).await.unwrap();
+    let result = graph
+        .mutate("MATCH (s:Seed) CREATE (a:P {i: s.i}), (b:Q {i: s.i}) RETURN a.i, b.i", PropertyMap::new())
+        .await
+        .unwrap();
+    let mut pairs: Vec<(i64, i64)> =
+        result.rows.iter().map(|r| (r.get("a.i").unwrap(), r.get("b.i").unwrap())).collect();
+    pairs.sort();
+    assert_eq!(pairs, [(1, 1), (2, 2)]);
+    assert_eq!(result.stats.nodes_created, 4);
+}
+
+// ============================================================================
+// 19. DELETE of a connected node fails and suggests DETACH DELETE
+// ============================================================================
+
+#[tokio::test]
+async fn test_delete_connected_node_without_detach_fails() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate("CREATE (a:Person {name: 'Alice'})-[:KNOWS]->(b:Person {name: 'Bob'})", PropertyMap::new())
+        .await
+        .unwrap();
+
+    let err = graph
+        .mutate("MATCH (n:Person) WHERE n.name = 'Alice' DELETE n", PropertyMap::new())
+        .await
+        .unwrap_err();
+    let neo4j_rs::Error::ConstraintViolation(msg) = &err else {
+        panic!("expected a constraint violation, got: {err:?}");
+    };
+    assert!(msg.contains("Cannot delete node"), "got: {msg}");
+    assert!(msg.contains("DETACH DELETE"), "got: {msg}");
+
+    // Nothing was deleted
+    let result = graph.execute("MATCH (n:Person) RETURN count(n) AS c", PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("c").unwrap(), 2);
+
+    // Once its relationship is gone the node can be deleted
+    graph.mutate("MATCH (a)-[r:KNOWS]->(b) DELETE r", PropertyMap::new()).await.unwrap();
+    let result = graph
+        .mutate("MATCH (n:Person) WHERE n.name = 'Alice' DELETE n", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.stats.nodes_deleted, 1);
+}
+
+// ============================================================================
+// 20. apoc.do.when runs one of two write fragments in the same transaction
+// ============================================================================
+
+#[tokio::test]
+async fn test_apoc_do_when_branches() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph.mutate("CREATE (n:Account {name: 'a', balance: 10})", PropertyMap::new()).await.unwrap();
+
+    let call = "CALL apoc.do.when($flag, \
+                'CREATE (n:Audit {note: $note}) RETURN n.note AS note', \
+                'MATCH (n:Account) SET n.balance = $balance RETURN n.name AS name', \
+                $params) YIELD value";
+    let params = |flag: Value| {
+        let mut p = PropertyMap::new();
+        p.insert("flag".into(), flag);
+        p.insert(
+            "params".into(),
+            Value::Map(PropertyMap::from([("note".into(), Value::from("hi")), ("balance".into(), Value::Int(99))])),
+        );
+        p
+    };
+
+    // True runs the first fragment
+    let result = graph.mutate(call, params(Value::Bool(true))).await.unwrap();
+    assert_eq!(result.rows.len(), 1);
+    let Value::Map(value) = result.rows[0].get::<Value>("value").unwrap() else { panic!("expected a map") };
+    assert_eq!(value["note"], Value::from("hi"));
+    assert_eq!(result.stats.nodes_created, 1);
+
+    // False (and null) run the second
+    let result = graph.mutate(call, params(Value::Bool(false))).await.unwrap();
+    let Value::Map(value) = result.rows[0].get::<Value>("value").unwrap() else { panic!("expected a map") };
+    assert_eq!(value["name"], Value::from("a"));
+    assert_eq!(result.stats.nodes_created, 0);
+    assert_eq!(result.stats.properties_set, 1);
+    graph.mutate(call, params(Value::Null)).await.unwrap();
+
+    let result = graph
+        .execute(
+            "MATCH (a:Account), (n:Audit) RETURN a.balance AS balance, count(n) AS audits",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("balance").unwrap(), 99);
+    assert_eq!(result.rows[0].get::<i64>("audits").unwrap(), 1);
+
+    // An empty else fragment yields no rows
+    let result = graph
+        .mutate("CALL apoc.do.when(false, 'CREATE (n:X)', '') YIELD value", PropertyMap::new())
+        .await
+        .unwrap();
+    assert!(result.rows.is_empty());
+    assert_eq!(result.stats.nodes_created, 0);
+}
+
+// ============================================================================
+// 21. apoc.create.node takes its labels from parameters
+// ============================================================================
+
+#[tokio::test]
+async fn test_apoc_create_node_dynamic_labels() {
+    let graph = Graph::open_memory().await.unwrap();
+    let call = "CALL apoc.create.node($labels, $props) YIELD node";
+    let params = |labels: Vec<Value>| {
+        let mut p = PropertyMap::new();
+        p.insert("labels".into(), Value::List(labels));
+        p.insert("props".into(), Value::Map(PropertyMap::from([("name".into(), Value::from("Ada"))])));
+        p
+    };
+
+    let result = graph.mutate(call, params(vec![Value::from("Person"), Value::from("Engineer")])).await.unwrap();
+    assert_eq!(result.stats.nodes_created, 1);
+    let node: Node = result.rows[0].get("node").unwrap();
+    assert_eq!(node.labels, ["Person", "Engineer"]);
+    assert_eq!(node.get("name"), Some(&Value::from("Ada")));
+
+    let result = graph
+        .execute("MATCH (n:Engineer) RETURN n.name AS name", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert!(graph.schema().has_label("Engineer"));
+
+    // Every label must be a non-empty string
+    for labels in [vec![Value::from("")], vec![Value::Int(1)]] {
+        assert!(graph.mutate(call, params(labels)).await.is_err());
+    }
+    let count = graph.execute("MATCH (n) RETURN count(n) AS c", PropertyMap::new()).await.unwrap();
+    assert_eq!(count.rows[0].get::<i64>("c").unwrap(), 1);
+}
+
+// ============================================================================
+// 22. CREATE binds a named relationship for RETURN
+// ============================================================================
+
+#[tokio::test]
+async fn test_create_returns_relationship() {
+    let graph = Graph::open_memory().await.unwrap();
+    let result = graph
+        .mutate(
+            "CREATE (a:Person {name: 'Alice'})-[r:KNOWS {since: 2020}]->(b:Person {name: 'Bob'}) RETURN a, r, b",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    let row = &result.rows[0];
+    let (a, b): (Node, Node) = (row.get("a").unwrap(), row.get("b").unwrap());
+    let r: neo4j_rs::Relationship = row.get("r").unwrap();
+    assert_eq!(r.rel_type, "KNOWS");
+    assert_eq!((r.src, r.dst), (a.id, b.id));
+    assert_eq!(r.properties.get("since"), Some(&Value::Int(2020)));
+
+    // Leftward patterns swap the endpoints; MATCH ... CREATE binds per row
+    let result = graph
+        .mutate(
+            "MATCH (a:Person), (b:Person) WHERE a.name = 'Alice' AND b.name = 'Bob' \
+             CREATE (a)<-[r:FOLLOWS]-(b) RETURN r.since AS since, r",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    let r: neo4j_rs::Relationship = result.rows[0].get("r").unwrap();
+    assert_eq!(r.rel_type, "FOLLOWS");
+    assert_eq!((r.src, r.dst), (b.id, a.id));
+    assert_eq!(result.rows[0].get::<Value>("since").unwrap(), Value::Null);
+}
+
+// ============================================================================
+// 23. apoc.refactor.mergeNodes folds duplicates into the first node
+// ============================================================================
+
+#[tokio::test]
+async fn test_apoc_merge_nodes() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (a:Person {name: 'Ada', email: 'ada@example.com'})-[:KNOWS]->(b:Person:Employee {name: 'Ada L', age: 36}), \
+             (b)-[:WORKS_AT]->(c:Company {name: 'Acme'}), (d:Person {name: 'Dan'})-[:KNOWS]->(b)",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let id_of = |name: &'static str| {
+        let graph = &graph;
+        async move {
+            let mut p = PropertyMap::new();
+            p.insert("name".into(), Value::from(name));
+            let r = graph.execute("MATCH (n) WHERE n.name = $name RETURN id(n) AS id", p).await.unwrap();
+            r.rows[0].get::<i64>("id").unwrap()
+        }
+    };
+    let (a, b) = (id_of("Ada").await, id_of("Ada L").await);
+
+    let mut params = PropertyMap::new();
+    params.insert("ids".into(), Value::List(vec![Value::Int(a), Value::Int(b)]));
+    let result = graph
+        .mutate("CALL apoc.refactor.mergeNodes($ids, {properties: 'combine'}) YIELD node", params)
+        .await
+        .unwrap();
+    assert_eq!(result.stats.nodes_deleted, 1);
+    let node: Node = result.rows[0].get("node").unwrap();
+    assert_eq!(node.id.0 as i64, a);
+    assert_eq!(node.labels, ["Person", "Employee"]);
+    assert_eq!(node.get("name"), Some(&Value::List(vec![Value::from("Ada"), Value::from("Ada L")])));
+    assert_eq!(node.get("email"), Some(&Value::from("ada@example.com")));
+    assert_eq!(node.get("age"), Some(&Value::Int(36)));
+
+    let count = graph.execute("MATCH (n) RETURN count(n) AS c", PropertyMap::new()).await.unwrap();
+    assert_eq!(count.rows[0].get::<i64>("c").unwrap(), 3);
+
+    // Every relationship of the merged node now ends at the survivor; the
+    // one between the two became a self-loop
+    let result = graph
+        .execute("MATCH (x)-[r]->(y) RETURN id(x) AS x, type(r) AS t, id(y) AS y", PropertyMap::new())
+        .await
+        .unwrap();
+    let mut rels: Vec<(i64, String, i64)> = result
+        .rows
+        .iter()
+        .map(|r| (r.get("x").unwrap(), r.get("t").unwrap(), r.get("y").unwrap()))
+        .collect();
+    rels.sort();
+    let (c, d) = (id_of("Acme").await, id_of("Dan").await);
+    let mut expected = vec![(a, "KNOWS".to_string(), a), (a, "WORKS_AT".to_string(), c), (d, "KNOWS".to_string(), a)];
+    expected.sort();
+    assert_eq!(rels, expected);
+
+    let mut params = PropertyMap::new();
+    params.insert("ids".into(), Value::List(vec![Value::Int(a), Value::Int(c)]));
+    assert!(graph.mutate("CALL apoc.refactor.mergeNodes($ids, {properties: 'sum'})", params).await.is_err());
+
+    // A negative id names no node rather than wrapping to a huge one
+    let mut params = PropertyMap::new();
+    params.insert("ids".into(), Value::List(vec![Value::Int(a), Value::Int(-1)]));
+    let err = graph.mutate("CALL apoc.refactor.mergeNodes($ids)", params).await.unwrap_err();
+    assert!(matches!(err, neo4j_rs::Error::TypeError { .. }), "got {err:?}");
+}
+
+// ============================================================================
+// 24. apoc.periodic.iterate commits an update in batches
+// ============================================================================
+
+#[tokio::test]
+async fn test_apoc_periodic_iterate() {
+    let graph = Graph::open_memory().await.unwrap();
+    {
+        let backend = graph.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+        for i in 0..5000 {
+            let props = PropertyMap::from([("i".into(), Value::Int(i))]);
+            backend.create_node(&mut tx, &["Item"], props).await.unwrap();
+        }
+        backend.commit_tx(tx).await.unwrap();
+    }
+
+    let result = graph
+        .mutate(
+            "CALL apoc.periodic.iterate('MATCH (n:Item) RETURN n', 'SET n.done = true', {batchSize: 1000})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let row = &result.rows[0];
+    assert_eq!(row.get::<i64>("batches").unwrap(), 5);
+    assert_eq!(row.get::<i64>("total").unwrap(), 5000);
+    assert_eq!(row.get::<i64>("committedOperations").unwrap(), 5000);
+    assert_eq!(row.get::<i64>("failedOperations").unwrap(), 0);
+    assert_eq!(result.stats.properties_set, 5000);
+    let result = graph
+        .execute("MATCH (n:Item) WHERE n.done = true RETURN count(n) AS done", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("done").unwrap(), 5000);
+
+    // Columns reach the action as parameters too, and a short last batch
+    // still runs. Without rollback, only the failing row fails: the rest of
+    // its batch is applied
+    let query = "CALL apoc.periodic.iterate(\
+        'MATCH (n:Item) WHERE n.i < 2500 RETURN n, n.i AS i', \
+        'SET n.score = $base / ($i - 1234)', \
+        {batchSize: 1000, params: {base: 10}})";
+    let result = graph.mutate(query, PropertyMap::new()).await.unwrap();
+    let row = &result.rows[0];
+    assert_eq!(row.get::<i64>("batches").unwrap(), 3);
+    assert_eq!(row.get::<i64>("failedBatches").unwrap(), 1);
+    assert_eq!(row.get::<i64>("failedOperations").unwrap(), 1);
+    assert_eq!(row.get::<i64>("committedOperations").unwrap(), 2499);
+    let Value::Map(errors) = row.get::<Value>("errorMessages").unwrap() else { panic!("expected a map") };
+    assert_eq!(errors.len(), 1);
+    let scored = "MATCH (n:Item) WHERE n.score IS NOT NULL RETURN count(n) AS scored";
+    let result = graph.execute(scored, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("scored").unwrap(), 2499);
+
+    // With rollback, the failing row's batch is undone as a whole
+    let isolated = Graph::with_backend(neo4j_rs::storage::MemoryBackend::new().with_isolated_transactions(true));
+    {
+        let backend = isolated.backend();
+        let mut tx = backend.begin_tx(neo4j_rs::tx::TxMode::ReadWrite).await.unwrap();
+        for i in 0..2500 {
+            let props = PropertyMap::from([("i".into(), Value::Int(i))]);
+            backend.create_node(&mut tx, &["Item"], props).await.unwrap();
+        }
+        backend.commit_tx(tx).await.unwrap();
+    }
+    let result = isolated.mutate(query, PropertyMap::new()).await.unwrap();
+    let row = &result.rows[0];
+    assert_eq!(row.get::<i64>("failedBatches").unwrap(), 1);
+    assert_eq!(row.get::<i64>("failedOperations").unwrap(), 1000);
+    assert_eq!(row.get::<i64>("committedOperations").unwrap(), 1500);
+    let result = isolated.execute(scored, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("scored").unwrap(), 1500);
+    let result = isolated
+        .execute("MATCH (n:Item) WHERE n.i >= 1000 AND n.i < 2000 AND n.score IS NOT NULL RETURN count(n) AS c", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("c").unwrap(), 0);
+
+    let err = graph
+        .mutate("CALL apoc.periodic.iterate('MATCH (n) RETURN n', 'SET n.x = 1', {batchSize: 0})", PropertyMap::new())
+        .await;
+    assert!(err.is_err());
+}
+
+// ============================================================================
+// 25. An ExplicitTx reads its own writes; other transactions don't
+// ============================================================================
+
+#[tokio::test]
+async fn test_explicit_tx_reads_its_own_writes() {
+    use neo4j_rs::{storage::MemoryBackend, tx::TxMode};
+
+    let graph = Graph::with_backend(MemoryBackend::new().with_isolated_transactions(true));
+    let count = async |graph: &Graph<MemoryBackend>| {
+        let result = graph.execute("MATCH (n:Person) RETURN count(n) AS n", PropertyMap::new()).await.unwrap();
+        result.rows[0].get::<i64>("n").unwrap()
+    };
+
+    let mut tx = graph.begin(TxMode::ReadWrite).await.unwrap();
+    tx.execute("CREATE (:Person {name: 'Ada'}), (:Person {name: 'Bob'})", PropertyMap::new()).await.unwrap();
+    tx.execute("MATCH (n:Person) WHERE n.name = 'Ada' SET n.age = 36", PropertyMap::new()).await.unwrap();
+    tx.execute("MATCH (n:Person) WHERE n.name = 'Bob' DELETE n", PropertyMap::new()).await.unwrap();
+    let result = tx
+        .execute("MATCH (n:Person) RETURN n.name AS name, n.age AS age", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].get::<String>("name").unwrap(), "Ada");
+    assert_eq!(result.rows[0].get::<i64>("age").unwrap(), 36);
+
+    // Nothing is visible outside until commit
+    assert_eq!(count(&graph).await, 0);
+    tx.commit().await.unwrap();
+    assert_eq!(count(&graph).await, 1);
+
+    // A rolled back transaction leaves nothing behind
+    let mut tx = graph.begin(TxMode::ReadWrite).await.unwrap();
+    tx.execute("CREATE (:Person {name: 'Cy'})", PropertyMap::new()).await.unwrap();
+    tx.rollback().await.unwrap();
+    assert_eq!(count(&graph).await, 1);
+}
+
+// ============================================================================
+// 26. A read-only transaction reads one snapshot throughout
+// ============================================================================
+
+#[tokio::test]
+async fn test_read_only_tx_snapshot() {
+    use neo4j_rs::{storage::MemoryBackend, tx::TxMode};
+
+    let graph = Graph::with_backend(MemoryBackend::new().with_isolated_transactions(true));
+    graph.mutate("CREATE (:Person {name: 'Ada'})", PropertyMap::new()).await.unwrap();
+    let count = "MATCH (n:Person) RETURN count(n) AS n";
+
+    let mut reader = graph.begin(TxMode::ReadOnly).await.unwrap();
+    let result = reader.execute(count, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 1);
+
+    // Commits after the reader began, explicit or autocommit, stay invisible to it
+    let mut writer = graph.begin(TxMode::ReadWrite).await.unwrap();
+    writer.execute("CREATE (:Person {name: 'Bob'}), (:Person {name: 'Cy'})", PropertyMap::new()).await.unwrap();
+    writer.commit().await.unwrap();
+    graph.mutate("MATCH (n:Person {name: 'Ada'}) DETACH DELETE n", PropertyMap::new()).await.unwrap();
+    let result = reader
+        .execute("MATCH (n:Person) RETURN count(n) AS n, collect(n.name) AS names", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 1);
+    assert_eq!(result.rows[0].get::<Vec<Value>>("names").unwrap(), [Value::from("Ada")]);
+    reader.commit().await.unwrap();
+
+    // A reader that begins now sees them
+    let result = graph.execute(count, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_read_only_tx_snapshot_by_default() {
+    use neo4j_rs::tx::TxMode;
+
+    let graph = Graph::open_memory().await.unwrap();
+    graph.mutate("CREATE (:Person {name: 'Ada', age: 36})", PropertyMap::new()).await.unwrap();
+    let query = "MATCH (n:Person) RETURN count(n) AS n, collect(n.age) AS ages";
+
+    let mut reader = graph.begin(TxMode::ReadOnly).await.unwrap();
+    let result = reader.execute(query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 1);
+
+    // Writes to the shared graph, in a transaction or not, don't reach it
+    let mut writer = graph.begin(TxMode::ReadWrite).await.unwrap();
+    writer.execute("CREATE (:Person {name: 'Bob'})", PropertyMap::new()).await.unwrap();
+    writer.execute("MATCH (n:Person {name: 'Ada'}) SET n.age = 37", PropertyMap::new()).await.unwrap();
+    // The writer reads its own writes
+    let result = writer.execute(query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 2);
+    writer.commit().await.unwrap();
+    graph.mutate("CREATE (:Person {name: 'Cy'})", PropertyMap::new()).await.unwrap();
+
+    let result = reader.execute(query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 1);
+    assert_eq!(result.rows[0].get::<Vec<Value>>("ages").unwrap(), [Value::Int(36)]);
+    reader.commit().await.unwrap();
+
+    let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+    assert_eq!(result.rows[0].get::<i64>("n").unwrap(), 3);
+    assert_eq!(result.rows[0].get::<Vec<Value>>("ages").unwrap(), [Value::Int(37)]);
+}
+
+// ============================================================================
+// 27. REMOVE and SET to null drop the key
+// ============================================================================
+
+#[tokio::test]
+async fn test_remove_and_set_null_drop_the_key() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (:Person {name: 'Ada', age: 36, city: 'London'})-[:KNOWS {since: 2020, via: 'work'}]->(:Person {name: 'Bob'})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    graph.mutate("MATCH (n:Person {name: 'Ada'}) REMOVE n.age", PropertyMap::new()).await.unwrap();
+    graph.mutate("MATCH (n:Person {name: 'Ada'}) SET n.city = null", PropertyMap::new()).await.unwrap();
+    graph.mutate("MATCH ()-[r:KNOWS]->() REMOVE r.since", PropertyMap::new()).await.unwrap();
+    graph.mutate("MATCH ()-[r:KNOWS]->() SET r.via = null", PropertyMap::new()).await.unwrap();
+
+    let result = graph
+        .execute(
+            "MATCH (n:Person {name: 'Ada'})-[r:KNOWS]->() RETURN keys(n) AS nk, keys(r) AS rk, n.age IS NULL AS gone",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let row = &result.rows[0];
+    assert_eq!(row.get::<Vec<Value>>("nk").unwrap(), [Value::from("name")]);
+    assert!(row.get::<Vec<Value>>("rk").unwrap().is_empty());
+    assert!(row.get::<bool>("gone").unwrap());
+
+    // The node's properties hold no null entries either
+    let tx = graph.backend().begin_tx(neo4j_rs::TxMode::ReadOnly).await.unwrap();
+    let ada = graph.backend().nodes_by_label(&tx, "Person").await.unwrap().remove(0);
+    assert_eq!(ada.properties.len(), 1);
+
+    // MERGE ... ON MATCH SET to null removes as well
+    graph
+        .mutate("MERGE (n:Person {name: 'Bob'}) ON MATCH SET n.name = null", PropertyMap::new())
+        .await
+        .unwrap();
+    let result = graph
+        .execute("MATCH (n:Person) WHERE n.name IS NULL RETURN size(keys(n)) AS k", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].get::<i64>("k").unwrap(), 0);
+}
+
+// ============================================================================
+// 28. SET n = map replaces the properties; SET n += map merges into them
+// ============================================================================
+
+#[tokio::test]
+async fn test_set_all_and_merge_properties() {
+    let graph = Graph::open_memory().await.unwrap();
+    graph
+        .mutate(
+            "CREATE (:Person {name: 'Ada', age: 36})-[:KNOWS {since: 2020}]->(:Person {name: 'Bob', age: 40})",
+            PropertyMap::new(),
+        )
+        .await
+        .unwrap();
+    let props = async |graph: &Graph<neo4j_rs::storage::MemoryBackend>, query: &str| {
+        let result = graph.execute(query, PropertyMap::new()).await.unwrap();
+        match result.rows[0].get::<Value>("p").unwrap() {
+            Value::Map(m) => m,
+            other => panic!("expected a map, got {other:?}"),
+        }
+    };
+
+    // += keeps keys the map doesn't mention and removes those it sets to null
+    let result = graph
+        .mutate("MATCH (n:Person {name: 'Ada'}) SET n += {city: 'London', age: null}", PropertyMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.stats.properties_set, 2);
+    let ada = props(&graph, "MATCH (n:Person {name: 'Ada'}) RETURN properties(n) AS p").await;
+    assert_eq!(ada.len(), 2);
+    assert_eq!(ada["city"], Value::from("London"));
+
+    // = drops every key the map lacks, including ones set earlier in the same SET
+    let mut params = PropertyMap::new();
+    params.insert("props".into(), Value::Map([("name".to_string(), Value::from("Robert"))].into()));
+    graph
+        .mutate("MATCH (n:Person {name: 'Bob'}) SET n.nick = 'Bobby', n = $props", params)
+        .await
+        .unwrap();
+    let bob = props(&graph, "MATCH (n:Person {name: 'Robert'}) RETURN properties(n) AS p").await;
+    assert_eq!(bob.keys().collect::<Vec<_>>(), ["name"]);
+
+    // Relationships too, and a node's properties can be copied
+    graph.mutate("MATCH ()-[r:KNOWS]->() SET r = {weight: 2}", PropertyMap::new()).await.unwrap();
+    let knows = props(&graph, "MATCH ()-[r:KNOWS]->() RETURN properties(r) AS p").await;
+    assert_eq!(knows.keys().collect::<Vec<_>>(), ["weight"]);
+    graph
+        .mutate("MATCH (a:Person {name: 'Ada'}), (b:Person {name: 'Robert'}) SET b = a", PropertyMap::new())
+        .await
+        .unwrap();
+    let copied = props(&graph, "MATCH (n:Person {city: 'London'}) WHERE id(n) <> 1 RETURN properties(n) AS p").await;
+    assert_eq!(copied["name"], Value::from("Ada"));
+
+    assert!(graph.mutate("MATCH (n:Person) SET n = 1", PropertyMap::new()).await.is_err());
+}